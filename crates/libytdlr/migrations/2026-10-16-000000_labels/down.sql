@@ -0,0 +1,3 @@
+DROP INDEX labels_label;
+
+DROP TABLE labels;
@@ -0,0 +1,7 @@
+CREATE TABLE labels (
+	media_archive_id BIGINT NOT NULL REFERENCES media_archive (_id) ON DELETE CASCADE,
+	label VARCHAR NOT NULL,
+	PRIMARY KEY (media_archive_id, label)
+);
+
+CREATE INDEX labels_label ON labels (label);
@@ -0,0 +1,3 @@
+DROP INDEX fingerprints_duration;
+
+DROP TABLE fingerprints;
@@ -0,0 +1,7 @@
+CREATE TABLE fingerprints (
+	media_archive_id BIGINT NOT NULL PRIMARY KEY REFERENCES media_archive (_id) ON DELETE CASCADE,
+	duration INTEGER NOT NULL,
+	fingerprint VARCHAR NOT NULL
+);
+
+CREATE INDEX fingerprints_duration ON fingerprints (duration);
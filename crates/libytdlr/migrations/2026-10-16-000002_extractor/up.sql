@@ -0,0 +1 @@
+ALTER TABLE media_archive ADD COLUMN extractor VARCHAR;
@@ -0,0 +1,6 @@
+CREATE TABLE blacklist (
+	media_id VARCHAR NOT NULL,
+	provider VARCHAR NOT NULL,
+	inserted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+	PRIMARY KEY (media_id, provider)
+);
@@ -0,0 +1,4 @@
+ALTER TABLE media_archive DROP COLUMN webpage_url;
+ALTER TABLE media_archive DROP COLUMN duration;
+ALTER TABLE media_archive DROP COLUMN upload_date;
+ALTER TABLE media_archive DROP COLUMN uploader;
@@ -0,0 +1,4 @@
+ALTER TABLE media_archive ADD COLUMN uploader VARCHAR;
+ALTER TABLE media_archive ADD COLUMN upload_date VARCHAR;
+ALTER TABLE media_archive ADD COLUMN duration INTEGER;
+ALTER TABLE media_archive ADD COLUMN webpage_url VARCHAR;
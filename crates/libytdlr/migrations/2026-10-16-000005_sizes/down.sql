@@ -0,0 +1,2 @@
+ALTER TABLE media_archive DROP COLUMN filesize_bytes;
+ALTER TABLE media_archive RENAME COLUMN duration_sec TO duration;
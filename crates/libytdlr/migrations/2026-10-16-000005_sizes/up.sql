@@ -0,0 +1,2 @@
+ALTER TABLE media_archive RENAME COLUMN duration TO duration_sec;
+ALTER TABLE media_archive ADD COLUMN filesize_bytes BIGINT;
@@ -0,0 +1 @@
+ALTER TABLE media_archive DROP COLUMN content_hash;
@@ -0,0 +1 @@
+ALTER TABLE media_archive ADD COLUMN removed_at DATETIME;
@@ -0,0 +1,281 @@
+//! Module for the config file, which stores defaults for options that would otherwise need to be specified every run
+
+use std::{
+	collections::BTreeMap,
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+use serde::Deserialize;
+
+use crate::error::IOErrorToError;
+
+/// The directory name used in the system config directory
+const CONFIG_DIR_NAME: &str = "ytdlr";
+/// The file name of the config file
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// The config file contents, all values are optional and only used when not specified otherwise (like on the command-line)
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+	/// Default Archive path to use
+	pub archive_path:  Option<PathBuf>,
+	/// Default temporary directory path
+	pub tmp_path:      Option<PathBuf>,
+	/// Default youtube-dl binary to run
+	pub ytdl_bin:      Option<PathBuf>,
+	/// Default Audio Editor
+	pub audio_editor:  Option<PathBuf>,
+	/// Default Video Editor
+	pub video_editor:  Option<PathBuf>,
+	/// Default Tagger
+	pub tagger_editor: Option<PathBuf>,
+	/// Default Media player
+	pub player_editor: Option<PathBuf>,
+	/// Default Output path for finished media
+	pub output_path:   Option<PathBuf>,
+	/// Default subtitle languages to download
+	pub sub_langs:     Option<String>,
+	/// Default output directories by provider, like `soundcloud = "Music"`
+	pub output_routes: Option<BTreeMap<String, PathBuf>>,
+	/// Default channel or playlist URLs to poll for new media in "watch"
+	pub subscriptions: Option<Vec<String>>,
+	/// Default download options by provider, like `[profiles.soundcloud]` with `audio_only = true`
+	pub profiles:      Option<BTreeMap<String, Profile>>,
+}
+
+/// Download options which are used for all URLs of a provider, all values are optional and only used when set
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+	/// Download audio only, like "--audio-only"
+	pub audio_only:   Option<bool>,
+	/// The audio format to use, like "--audio-format"
+	pub audio_format: Option<String>,
+	/// The video format to use, like "--video-format"
+	pub video_format: Option<String>,
+	/// The subtitle languages to download, like "--sub-langs"
+	pub sub_langs:    Option<String>,
+}
+
+impl Config {
+	/// Get the default path of the config file, like "~/.config/ytdlr/config.toml" on linux
+	/// Returns [`None`] if there is no config directory for the current system
+	#[must_use]
+	pub fn default_path() -> Option<PathBuf> {
+		return dirs::config_dir().map(|v| return v.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME));
+	}
+
+	/// Parse and validate a config from the given toml string
+	pub fn parse(input: &str) -> Result<Self, crate::Error> {
+		return Self::parse_inner(input).map_err(crate::Error::other);
+	}
+
+	/// Read, parse and validate the config file at `path`
+	pub fn load(path: &Path) -> Result<Self, crate::Error> {
+		if !path.is_file() {
+			return Err(crate::Error::not_a_file(
+				"Config path does not exist or is not a file",
+				path,
+			));
+		}
+
+		let contents = std::fs::read_to_string(path).attach_path_err(path)?;
+
+		return Self::parse_inner(&contents).map_err(|msg| {
+			return crate::Error::invalid_config(msg, path);
+		});
+	}
+
+	/// Load the config file from [`Self::default_path`]
+	/// Returns [`None`] if the file does not exist
+	pub fn load_default() -> Result<Option<Self>, crate::Error> {
+		let Some(path) = Self::default_path() else {
+			return Ok(None);
+		};
+
+		if !path.exists() {
+			return Ok(None);
+		}
+
+		return Self::load(&path).map(Some);
+	}
+
+	/// Internal function to parse and validate a config, returning the error message so it can be wrapped by the caller
+	fn parse_inner(input: &str) -> Result<Self, String> {
+		let config: Self = toml::from_str(input).map_err(|err| return format!("Invalid config: {err}"))?;
+
+		config.validate()?;
+
+		return Ok(config);
+	}
+
+	/// Validate the values of the config
+	fn validate(&self) -> Result<(), String> {
+		let paths = [
+			("archive_path", &self.archive_path),
+			("tmp_path", &self.tmp_path),
+			("ytdl_bin", &self.ytdl_bin),
+			("audio_editor", &self.audio_editor),
+			("video_editor", &self.video_editor),
+			("tagger_editor", &self.tagger_editor),
+			("player_editor", &self.player_editor),
+			("output_path", &self.output_path),
+		];
+
+		for (name, path) in paths {
+			if path.as_ref().is_some_and(|v| return v.as_os_str().is_empty()) {
+				return Err(format!("Config value \"{name}\" cannot be empty"));
+			}
+		}
+
+		for (provider, dir) in self.output_routes.iter().flatten() {
+			if provider.is_empty() || dir.as_os_str().is_empty() {
+				return Err("Config value \"output_routes\" cannot have empty providers or directories".to_owned());
+			}
+		}
+
+		if self.subscriptions.iter().flatten().any(|v| return v.trim().is_empty()) {
+			return Err("Config value \"subscriptions\" cannot have empty URLs".to_owned());
+		}
+
+		if self.sub_langs.as_ref().is_some_and(|v| return v.trim().is_empty()) {
+			return Err("Config value \"sub_langs\" cannot be empty".to_owned());
+		}
+
+		for (provider, profile) in self.profiles.iter().flatten() {
+			if provider.trim().is_empty() {
+				return Err("Config value \"profiles\" cannot have empty providers".to_owned());
+			}
+
+			let values = [
+				("audio_format", &profile.audio_format),
+				("video_format", &profile.video_format),
+				("sub_langs", &profile.sub_langs),
+			];
+
+			for (name, value) in values {
+				if value.as_ref().is_some_and(|v| return v.trim().is_empty()) {
+					return Err(format!("Config value \"profiles.{provider}.{name}\" cannot be empty"));
+				}
+			}
+		}
+
+		return Ok(());
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_parse() {
+		assert_eq!(Ok(Config::default()), Config::parse(""));
+
+		assert_eq!(
+			Ok(Config {
+				archive_path: Some(PathBuf::from("~/archive.db")),
+				audio_editor: Some(PathBuf::from("audacity")),
+				sub_langs: Some("en,de".to_owned()),
+				..Default::default()
+			}),
+			Config::parse("archive_path = \"~/archive.db\"\naudio_editor = \"audacity\"\nsub_langs = \"en,de\"\n")
+		);
+		assert_eq!(
+			Ok(Config {
+				output_routes: Some(BTreeMap::from([
+					("soundcloud".to_owned(), PathBuf::from("Music")),
+					("twitch".to_owned(), PathBuf::from("VODs")),
+				])),
+				..Default::default()
+			}),
+			Config::parse("[output_routes]\nsoundcloud = \"Music\"\ntwitch = \"VODs\"\n")
+		);
+		assert_eq!(
+			Ok(Config {
+				subscriptions: Some(vec!["https://www.youtube.com/@channel".to_owned()]),
+				..Default::default()
+			}),
+			Config::parse("subscriptions = [\"https://www.youtube.com/@channel\"]\n")
+		);
+		assert_eq!(
+			Ok(Config {
+				profiles: Some(BTreeMap::from([
+					(
+						"soundcloud".to_owned(),
+						Profile {
+							audio_only: Some(true),
+							audio_format: Some("mp3".to_owned()),
+							..Default::default()
+						}
+					),
+					(
+						"youtube".to_owned(),
+						Profile {
+							video_format: Some("mkv".to_owned()),
+							sub_langs: Some("en".to_owned()),
+							..Default::default()
+						}
+					),
+				])),
+				..Default::default()
+			}),
+			Config::parse(
+				"[profiles.soundcloud]\naudio_only = true\naudio_format = \"mp3\"\n[profiles.youtube]\nvideo_format = \"mkv\"\nsub_langs = \"en\"\n"
+			)
+		);
+	}
+
+	#[test]
+	fn test_parse_invalid() {
+		// unknown keys should not be silently ignored
+		assert!(Config::parse("archive = \"~/archive.db\"").is_err());
+		// wrong types
+		assert!(Config::parse("tmp_path = 10").is_err());
+		// empty values
+		assert_eq!(
+			Err(crate::Error::other("Config value \"output_path\" cannot be empty")),
+			Config::parse("output_path = \"\"")
+		);
+		assert!(Config::parse("sub_langs = \" \"").is_err());
+		assert!(Config::parse("[output_routes]\nsoundcloud = \"\"").is_err());
+		assert!(Config::parse("subscriptions = [\" \"]").is_err());
+		assert!(Config::parse("[profiles.soundcloud]\naudio = true").is_err());
+		assert_eq!(
+			Err(crate::Error::other(
+				"Config value \"profiles.youtube.sub_langs\" cannot be empty"
+			)),
+			Config::parse("[profiles.youtube]\nsub_langs = \"\"")
+		);
+	}
+
+	#[test]
+	fn test_load() {
+		let tmp = tempfile::Builder::new()
+			.prefix("ytdl-test-config-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let path = tmp.path().join(CONFIG_FILE_NAME);
+
+		assert!(Config::load(&path).is_err());
+
+		std::fs::write(&path, "tmp_path = \"/tmp/ytdlr\"").expect("Expected to write config");
+		assert_eq!(
+			Ok(Config {
+				tmp_path: Some(PathBuf::from("/tmp/ytdlr")),
+				..Default::default()
+			}),
+			Config::load(&path)
+		);
+
+		std::fs::write(&path, "unknown = true").expect("Expected to write config");
+		assert!(matches!(
+			Config::load(&path).map_err(|err| return err.to_string()),
+			Err(msg) if msg.starts_with("InvalidConfig: Invalid config:")
+		));
+	}
+}
@@ -0,0 +1,161 @@
+//! Module containing [`InfoJson`], for reading the ".info.json" files written by youtube-dl (see "--write-info-json")
+
+use serde::Deserialize;
+use std::{
+	fs::File,
+	io::BufReader,
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+use crate::error::IOErrorToError;
+
+/// Suffix of the ".info.json" file youtube-dl writes for a media (like "media.mkv" -> "media.info.json")
+pub const INFO_JSON_SUFFIX: &str = "info.json";
+
+/// The fields of a youtube-dl ".info.json" file that are used, all other fields are ignored
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+pub struct InfoJson {
+	/// The name of the uploader (like the channel name)
+	pub uploader:    Option<String>,
+	/// The date the media was uploaded, in the youtube-dl format "YYYYMMDD"
+	pub upload_date: Option<String>,
+	/// The duration of the media in seconds, may contain fractions
+	pub duration:    Option<f64>,
+	/// The URL of the page of the media (like "https://www.youtube.com/watch?v=...")
+	pub webpage_url: Option<String>,
+}
+
+impl InfoJson {
+	/// Get the ".info.json" path for the given media path (like "media.mkv" -> "media.info.json")
+	/// youtube-dl names it like the media, but without the media's extension
+	#[must_use]
+	pub fn path_for_media(media_path: &Path) -> PathBuf {
+		return media_path.with_extension(INFO_JSON_SUFFIX);
+	}
+
+	/// Read a [`InfoJson`] from `path`
+	pub fn from_file(path: &Path) -> Result<Self, crate::Error> {
+		let reader = BufReader::new(File::open(path).attach_path_err(path)?);
+
+		return Ok(serde_json::from_reader(reader)?);
+	}
+
+	/// Try to read the [`InfoJson`] of `media_path`
+	/// Returns [`None`] if no ".info.json" exists for the media
+	pub fn try_for_media(media_path: &Path) -> Result<Option<Self>, crate::Error> {
+		let path = Self::path_for_media(media_path);
+
+		if !path.is_file() {
+			return Ok(None);
+		}
+
+		return Self::from_file(&path).map(Some);
+	}
+
+	/// Get the upload date as "YYYY-MM-DD"
+	/// Returns [`None`] if not set or not in the format "YYYYMMDD"
+	#[must_use]
+	pub fn upload_date_iso(&self) -> Option<String> {
+		let date = self.upload_date.as_deref()?;
+
+		return chrono::NaiveDate::parse_from_str(date, "%Y%m%d")
+			.ok()
+			.map(|v| return v.format("%Y-%m-%d").to_string());
+	}
+
+	/// Get the duration in whole seconds, rounded
+	/// Returns [`None`] if not set or not a valid duration
+	#[must_use]
+	pub fn duration_secs(&self) -> Option<u32> {
+		let duration = self.duration?;
+
+		if !duration.is_finite() || duration < 0.0 || duration > f64::from(u32::MAX) {
+			return None;
+		}
+
+		// casting is safe, because the range has been checked above
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		return Some(duration.round() as u32);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_path_for_media() {
+		assert_eq!(
+			PathBuf::from("/tmp/somedir/'provider'-'id'-Some Title.info.json"),
+			InfoJson::path_for_media(Path::new("/tmp/somedir/'provider'-'id'-Some Title.mkv"))
+		);
+	}
+
+	#[test]
+	fn test_deserialize() {
+		let info: InfoJson = serde_json::from_str(
+			r#"{"id":"someid","title":"Some Title","uploader":"Someone","upload_date":"20230524","duration":212.5,"webpage_url":"https://www.youtube.com/watch?v=someid","formats":[]}"#,
+		)
+		.expect("Expected deserialize to be ok");
+
+		assert_eq!(
+			InfoJson {
+				uploader:    Some("Someone".to_owned()),
+				upload_date: Some("20230524".to_owned()),
+				duration:    Some(212.5),
+				webpage_url: Some("https://www.youtube.com/watch?v=someid".to_owned()),
+			},
+			info
+		);
+		assert_eq!(Some("2023-05-24".to_owned()), info.upload_date_iso());
+		assert_eq!(Some(213), info.duration_secs());
+
+		// all fields are optional
+		let info: InfoJson =
+			serde_json::from_str(r#"{"id":"someid","duration":null}"#).expect("Expected deserialize to be ok");
+
+		assert_eq!(InfoJson::default(), info);
+		assert_eq!(None, info.upload_date_iso());
+		assert_eq!(None, info.duration_secs());
+	}
+
+	#[test]
+	fn test_invalid_values() {
+		let info = InfoJson {
+			upload_date: Some("2023-05-24".to_owned()),
+			duration: Some(-1.0),
+			..Default::default()
+		};
+
+		assert_eq!(None, info.upload_date_iso());
+		assert_eq!(None, info.duration_secs());
+	}
+
+	#[test]
+	fn test_try_for_media() {
+		let testdir = tempfile::Builder::new()
+			.prefix("ytdl-test-infojson-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let media_path = testdir.as_ref().join("'youtube'-'someid'-Some Title.mkv");
+
+		assert_eq!(None, InfoJson::try_for_media(&media_path).unwrap());
+
+		std::fs::write(
+			testdir.as_ref().join("'youtube'-'someid'-Some Title.info.json"),
+			r#"{"uploader":"Someone"}"#,
+		)
+		.expect("Expected the info json to be written");
+
+		assert_eq!(
+			Some(InfoJson {
+				uploader: Some("Someone".to_owned()),
+				..Default::default()
+			}),
+			InfoJson::try_for_media(&media_path).unwrap()
+		);
+	}
+}
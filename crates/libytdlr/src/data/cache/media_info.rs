@@ -6,38 +6,80 @@ use serde::{
 	Deserialize,
 	Serialize,
 };
-use std::path::{
-	Path,
-	PathBuf,
+use std::{
+	fs::File,
+	io::{
+		BufReader,
+		BufWriter,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
 };
 
-use super::media_provider::MediaProvider;
-use crate::data::{
-	sql_models::InsMedia,
-	UNKNOWN_NONE_PROVIDED,
+use super::{
+	info_json::InfoJson,
+	media_provider::MediaProvider,
+	media_stage::MediaStage,
 };
+use crate::{
+	data::{
+		sql_models::InsMedia,
+		UNKNOWN_NONE_PROVIDED,
+	},
+	error::IOErrorToError,
+};
+
+/// Extension appended to a media's filename for its sidecar file (like "media.mkv" -> "media.mkv.ytdlr.json")
+pub const SIDECAR_EXTENSION: &str = "ytdlr.json";
 
 /// Contains Media Information, like file-name and last processed status
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MediaInfo {
 	/// The file-name of the media
-	pub filename: Option<PathBuf>,
+	pub filename:    Option<PathBuf>,
 	/// The title of the media, may differ from "filename"
-	pub title:    Option<String>,
+	pub title:       Option<String>,
 	/// The ID of the media,
-	pub id:       String,
+	pub id:          String,
 	/// The Provider that provided this media
-	pub provider: MediaProvider,
+	pub provider:    MediaProvider,
+	/// The Stage the media is in, defaults to [`MediaStage::Discovered`] for data written before stages existed
+	#[serde(default)]
+	pub stage:       MediaStage,
+	/// The name of the uploader (like the channel name), see [`MediaInfo::apply_info_json`]
+	#[serde(default)]
+	pub uploader:    Option<String>,
+	/// The date the media was uploaded as "YYYY-MM-DD", see [`MediaInfo::apply_info_json`]
+	#[serde(default)]
+	pub upload_date: Option<String>,
+	/// The duration of the media in seconds, see [`MediaInfo::apply_info_json`]
+	/// Probed from the media file if no ".info.json" was available
+	#[serde(default)]
+	pub duration:    Option<u32>,
+	/// The URL of the page of the media, see [`MediaInfo::apply_info_json`]
+	#[serde(default)]
+	pub webpage_url: Option<String>,
+	/// The size of the media file in bytes, set once the media has been downloaded
+	#[serde(default)]
+	pub filesize:    Option<u64>,
 }
 
 impl MediaInfo {
 	/// Crate a new instance of [`MediaInfo`]
 	pub fn new<I: AsRef<str>, P: Into<MediaProvider>>(id: I, provider: P) -> Self {
 		return Self {
-			id:       id.as_ref().into(),
-			filename: None,
-			title:    None,
-			provider: provider.into(),
+			id:          id.as_ref().into(),
+			filename:    None,
+			title:       None,
+			provider:    provider.into(),
+			stage:       MediaStage::default(),
+			uploader:    None,
+			upload_date: None,
+			duration:    None,
+			webpage_url: None,
+			filesize:    None,
 		};
 	}
 
@@ -57,6 +99,48 @@ impl MediaInfo {
 		return self;
 	}
 
+	/// Builder function to set the stage
+	#[must_use]
+	pub fn with_stage(mut self, stage: MediaStage) -> Self {
+		self.stage = stage;
+
+		return self;
+	}
+
+	/// Advance the stage of the current [`MediaInfo`] to `next`
+	/// Returns `false` and keeps the current stage if `next` would go back, see [`MediaStage::can_advance_to`]
+	pub fn advance_stage(&mut self, next: MediaStage) -> bool {
+		if !self.stage.can_advance_to(next) {
+			debug!(
+				"Not changing stage of \"{}\" from \"{}\" back to \"{}\"",
+				self.id, self.stage, next
+			);
+
+			return false;
+		}
+
+		self.stage = next;
+
+		return true;
+	}
+
+	/// Enrich the current [`MediaInfo`] with the metadata from a youtube-dl ".info.json"
+	/// Values that are not set in `info` are kept
+	pub fn apply_info_json(&mut self, info: &InfoJson) {
+		if let Some(uploader) = info.uploader.as_ref() {
+			self.uploader = Some(uploader.clone());
+		}
+		if let Some(upload_date) = info.upload_date_iso() {
+			self.upload_date = Some(upload_date);
+		}
+		if let Some(duration) = info.duration_secs() {
+			self.duration = Some(duration);
+		}
+		if let Some(webpage_url) = info.webpage_url.as_ref() {
+			self.webpage_url = Some(webpage_url.clone());
+		}
+	}
+
 	/// Set the filename of the current [`MediaInfo`]
 	pub fn set_filename<F: AsRef<Path>>(&mut self, filename: F) {
 		self.filename = Some(filename.as_ref().into());
@@ -91,15 +175,75 @@ impl MediaInfo {
 
 		return Some(Self::new(&cap[2], &cap[1]).with_title(&cap[3]).with_filename(filename));
 	}
+
+	/// Get the sidecar path for the given media path (like "media.mkv" -> "media.mkv.ytdlr.json")
+	#[must_use]
+	pub fn sidecar_path(media_path: &Path) -> PathBuf {
+		let mut sidecar_path = media_path.as_os_str().to_owned();
+		sidecar_path.push(".");
+		sidecar_path.push(SIDECAR_EXTENSION);
+
+		return sidecar_path.into();
+	}
+
+	/// Write the current [`MediaInfo`] as the sidecar of `media_path`
+	/// The sidecar stores only the file name of `media_path`, so that it does not depend on the directory
+	pub fn write_sidecar(&self, media_path: &Path) -> Result<(), crate::Error> {
+		let mut sidecar_data = self.clone();
+		sidecar_data.filename = media_path.file_name().map(PathBuf::from);
+
+		let sidecar_path = Self::sidecar_path(media_path);
+		let writer = BufWriter::new(File::create(&sidecar_path).attach_path_err(&sidecar_path)?);
+		serde_json::to_writer(writer, &sidecar_data)?;
+
+		return Ok(());
+	}
+
+	/// Try to read a [`MediaInfo`] from the sidecar of `media_path`
+	/// Returns [`None`] if no sidecar exists, the filename will be the file name of `media_path`
+	///
+	/// This is preferred over [`MediaInfo::try_from_filename`], because it does not depend on the title in the filename being parsable
+	pub fn try_from_sidecar(media_path: &Path) -> Result<Option<Self>, crate::Error> {
+		let sidecar_path = Self::sidecar_path(media_path);
+
+		if !sidecar_path.is_file() {
+			return Ok(None);
+		}
+
+		let reader = BufReader::new(File::open(&sidecar_path).attach_path_err(&sidecar_path)?);
+		let mut media: Self = serde_json::from_reader(reader)?;
+		media.filename = media_path.file_name().map(PathBuf::from);
+
+		return Ok(Some(media));
+	}
+
+	/// Remove the sidecar of `media_path`, if it exists
+	pub fn remove_sidecar(media_path: &Path) -> Result<(), crate::Error> {
+		let sidecar_path = Self::sidecar_path(media_path);
+
+		if sidecar_path.exists() {
+			std::fs::remove_file(&sidecar_path).attach_path_err(&sidecar_path)?;
+		}
+
+		return Ok(());
+	}
 }
 
 impl<'a> From<&'a MediaInfo> for InsMedia<'a> {
 	fn from(v: &'a MediaInfo) -> Self {
-		return Self::new(
-			&v.id,
-			v.provider.as_str(),
-			v.title.as_ref().map_or(UNKNOWN_NONE_PROVIDED, |v| return v.as_str()),
-		);
+		return Self {
+			uploader: v.uploader.as_deref(),
+			upload_date: v.upload_date.as_deref(),
+			// durations and sizes that do not fit into the database are not stored
+			duration_sec: v.duration.and_then(|v| return i32::try_from(v).ok()),
+			webpage_url: v.webpage_url.as_deref(),
+			filesize_bytes: v.filesize.and_then(|v| return i64::try_from(v).ok()),
+			..Self::new(
+				&v.id,
+				v.provider.as_str(),
+				v.title.as_ref().map_or(UNKNOWN_NONE_PROVIDED, |v| return v.as_str()),
+			)
+		};
 	}
 }
 
@@ -113,20 +257,32 @@ mod test {
 	fn test_new() {
 		assert_eq!(
 			MediaInfo {
-				id:       String::new(),
-				filename: None,
-				title:    None,
-				provider: "".into(),
+				id:          String::new(),
+				filename:    None,
+				title:       None,
+				provider:    "".into(),
+				stage:       MediaStage::Discovered,
+				uploader:    None,
+				upload_date: None,
+				duration:    None,
+				webpage_url: None,
+				filesize:    None,
 			},
 			MediaInfo::new("", "")
 		);
 
 		assert_eq!(
 			MediaInfo {
-				id:       "hello".to_owned(),
-				filename: None,
-				title:    None,
-				provider: "hello".into(),
+				id:          "hello".to_owned(),
+				filename:    None,
+				title:       None,
+				provider:    "hello".into(),
+				stage:       MediaStage::Discovered,
+				uploader:    None,
+				upload_date: None,
+				duration:    None,
+				webpage_url: None,
+				filesize:    None,
 			},
 			MediaInfo::new("hello", "hello")
 		);
@@ -136,10 +292,16 @@ mod test {
 	fn test_with_filename() {
 		assert_eq!(
 			MediaInfo {
-				id:       "someid".to_owned(),
-				filename: Some(PathBuf::from("Hello")),
-				title:    None,
-				provider: "".into(),
+				id:          "someid".to_owned(),
+				filename:    Some(PathBuf::from("Hello")),
+				title:       None,
+				provider:    "".into(),
+				stage:       MediaStage::Discovered,
+				uploader:    None,
+				upload_date: None,
+				duration:    None,
+				webpage_url: None,
+				filesize:    None,
 			},
 			MediaInfo::new("someid", "").with_filename("Hello")
 		);
@@ -149,10 +311,16 @@ mod test {
 	fn test_with_title() {
 		assert_eq!(
 			MediaInfo {
-				id:       "someid".to_owned(),
-				filename: None,
-				title:    Some("Hello".to_owned()),
-				provider: "".into(),
+				id:          "someid".to_owned(),
+				filename:    None,
+				title:       Some("Hello".to_owned()),
+				provider:    "".into(),
+				stage:       MediaStage::Discovered,
+				uploader:    None,
+				upload_date: None,
+				duration:    None,
+				webpage_url: None,
+				filesize:    None,
 			},
 			MediaInfo::new("someid", "").with_title("Hello")
 		);
@@ -162,15 +330,71 @@ mod test {
 	fn test_with_provider() {
 		assert_eq!(
 			MediaInfo {
-				id:       "someid".to_owned(),
-				filename: None,
-				title:    None,
-				provider: MediaProvider::from("youtube"),
+				id:          "someid".to_owned(),
+				filename:    None,
+				title:       None,
+				provider:    MediaProvider::from("youtube"),
+				stage:       MediaStage::Discovered,
+				uploader:    None,
+				upload_date: None,
+				duration:    None,
+				webpage_url: None,
+				filesize:    None,
 			},
 			MediaInfo::new("someid", "youtube")
 		);
 	}
 
+	#[test]
+	fn test_advance_stage() {
+		let mut media = MediaInfo::new("someid", "youtube");
+		assert_eq!(MediaStage::Discovered, media.stage);
+
+		assert!(media.advance_stage(MediaStage::Downloaded));
+		assert!(media.advance_stage(MediaStage::Moved));
+		assert!(!media.advance_stage(MediaStage::Edited));
+		assert_eq!(MediaStage::Moved, media.stage);
+	}
+
+	#[test]
+	fn test_deserialize_without_stage() {
+		let media: MediaInfo =
+			serde_json::from_str(r#"{"filename":null,"title":"Some Title","id":"someid","provider":"youtube"}"#)
+				.expect("Expected deserialize to be ok");
+
+		assert_eq!(MediaInfo::new("someid", "youtube").with_title("Some Title"), media);
+	}
+
+	#[test]
+	fn test_apply_info_json() {
+		let mut media = MediaInfo::new("someid", "youtube").with_title("Some Title");
+		media.uploader = Some("Old Uploader".to_owned());
+
+		media.apply_info_json(&InfoJson {
+			uploader:    None,
+			upload_date: Some("20230524".to_owned()),
+			duration:    Some(61.2),
+			webpage_url: Some("https://www.youtube.com/watch?v=someid".to_owned()),
+		});
+
+		assert_eq!(Some("Old Uploader".to_owned()), media.uploader);
+		assert_eq!(Some("2023-05-24".to_owned()), media.upload_date);
+		assert_eq!(Some(61), media.duration);
+		assert_eq!(
+			Some("https://www.youtube.com/watch?v=someid".to_owned()),
+			media.webpage_url
+		);
+
+		media.filesize = Some(2048);
+
+		let insmedia = InsMedia::from(&media);
+		assert_eq!(Some("Old Uploader"), insmedia.uploader);
+		assert_eq!(Some("2023-05-24"), insmedia.upload_date);
+		assert_eq!(Some(61), insmedia.duration_sec);
+		assert_eq!(Some("https://www.youtube.com/watch?v=someid"), insmedia.webpage_url);
+		assert_eq!(Some(2048), insmedia.filesize_bytes);
+	}
+
 	#[test]
 	fn test_into_insmedia() {
 		// test with full options
@@ -186,6 +410,45 @@ mod test {
 		);
 	}
 
+	#[test]
+	fn test_sidecar_path() {
+		assert_eq!(
+			PathBuf::from("/tmp/somedir/'provider'-'id'-Some Title.mkv.ytdlr.json"),
+			MediaInfo::sidecar_path(Path::new("/tmp/somedir/'provider'-'id'-Some Title.mkv"))
+		);
+	}
+
+	#[test]
+	fn test_sidecar_roundtrip() {
+		let testdir = tempfile::Builder::new()
+			.prefix("ytdl-test-sidecar-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		// a title which would not be parsable by "try_from_filename"
+		let media_path = testdir.as_ref().join("some-weird' title.mkv");
+
+		assert_eq!(None, MediaInfo::try_from_sidecar(&media_path).unwrap());
+
+		MediaInfo::new("id", "provider")
+			.with_title("some-weird' title")
+			.with_filename("/some/other/path.mkv")
+			.write_sidecar(&media_path)
+			.expect("Expected write_sidecar to be ok");
+
+		assert_eq!(
+			Some(
+				MediaInfo::new("id", "provider")
+					.with_title("some-weird' title")
+					.with_filename("some-weird' title.mkv")
+			),
+			MediaInfo::try_from_sidecar(&media_path).unwrap()
+		);
+
+		MediaInfo::remove_sidecar(&media_path).expect("Expected remove_sidecar to be ok");
+		assert!(!MediaInfo::sidecar_path(&media_path).exists());
+		assert_eq!(None, MediaInfo::try_from_sidecar(&media_path).unwrap());
+	}
+
 	#[test]
 	fn test_try_from_filename() {
 		// test a non-proper name
@@ -9,6 +9,37 @@ use serde::{
 
 use crate::data::UNKNOWN;
 
+/// Known extractor families, as "(canonical provider, extractors)"
+/// A provider is part of a family if it is the canonical provider, one of the extractors, or starts with "canonical:" (like "youtube:tab")
+const PROVIDER_FAMILIES: &[(&str, &[&str])] = &[(
+	"youtube",
+	&["youtubetab", "youtubeplaylist", "youtubemusic", "youtubemusicsearchurl"],
+)];
+
+/// Get the canonical provider of `provider`, if it is part of a known extractor family (see [`PROVIDER_FAMILIES`])
+/// Media of the same family share ids (like "youtube", "youtube:tab" and music.youtube), so they should be archived under one provider
+/// Input will be trimmed and lowercased for matching
+#[must_use]
+pub fn canonical_provider(provider: &str) -> Option<&'static str> {
+	let lower = provider.trim().to_lowercase();
+
+	return PROVIDER_FAMILIES
+		.iter()
+		.find(|(canonical, extractors)| {
+			return lower == *canonical
+				|| extractors.contains(&lower.as_str())
+				|| lower.strip_prefix(canonical).is_some_and(|v| return v.starts_with(':'));
+		})
+		.map(|(canonical, _)| return *canonical);
+}
+
+/// Get the normalized form of `provider`, which is the canonical provider (see [`canonical_provider`]) or otherwise the trimmed and lowercased provider
+/// Providers with the same normalized form are considered the same provider
+#[must_use]
+pub fn normalize_provider(provider: &str) -> String {
+	return canonical_provider(provider).map_or_else(|| return provider.trim().to_lowercase(), |v| return v.to_owned());
+}
+
 /// NewType struct to contain the provider in formatted form for [`super::media_info::MediaInfo`]
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct MediaProvider(String);
@@ -68,6 +99,22 @@ impl std::fmt::Display for MediaProvider {
 mod test {
 	use super::*;
 
+	#[test]
+	fn test_canonical_provider() {
+		assert_eq!(Some("youtube"), canonical_provider("youtube"));
+		assert_eq!(Some("youtube"), canonical_provider("youtube:tab"));
+		assert_eq!(Some("youtube"), canonical_provider("YoutubeTab"));
+		assert_eq!(Some("youtube"), canonical_provider(" youtube:music:search_url "));
+		assert_eq!(None, canonical_provider("youtubefake"));
+		assert_eq!(None, canonical_provider("soundcloud"));
+	}
+
+	#[test]
+	fn test_normalize_provider() {
+		assert_eq!("youtube", normalize_provider("Youtube:Tab"));
+		assert_eq!("soundcloud", normalize_provider(" SoundCloud "));
+	}
+
 	mod trait_impls {
 		use super::*;
 
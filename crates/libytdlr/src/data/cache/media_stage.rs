@@ -0,0 +1,107 @@
+//! Module containing [`MediaStage`]
+
+use std::{
+	fmt::Display,
+	str::FromStr,
+};
+
+use serde::{
+	Deserialize,
+	Serialize,
+};
+
+/// The Stage a media is in its lifecycle, stages can only advance in the order they are defined in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaStage {
+	/// The media has been found (like in a playlist), but has not been downloaded (yet)
+	#[default]
+	Discovered,
+	/// The media file has been fully downloaded
+	Downloaded,
+	/// The media has been shown in the edit stage, but was not edited (like being skipped)
+	Reviewed,
+	/// The media file has been edited (like with a audio editor)
+	Edited,
+	/// The media file has been moved to its final location
+	Moved,
+	/// The media has been finished and is recorded in the archive
+	Archived,
+}
+
+impl MediaStage {
+	/// Get the string representation of the stage, which is also used for serialization
+	#[must_use]
+	pub fn as_str(self) -> &'static str {
+		return match self {
+			MediaStage::Discovered => "discovered",
+			MediaStage::Downloaded => "downloaded",
+			MediaStage::Reviewed => "reviewed",
+			MediaStage::Edited => "edited",
+			MediaStage::Moved => "moved",
+			MediaStage::Archived => "archived",
+		};
+	}
+
+	/// Get whether the stage can advance to `next`
+	/// Staying in the same stage is allowed, going back is not
+	#[must_use]
+	pub fn can_advance_to(self, next: MediaStage) -> bool {
+		return next >= self;
+	}
+}
+
+impl Display for MediaStage {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return write!(f, "{}", self.as_str());
+	}
+}
+
+impl FromStr for MediaStage {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		return match s.to_lowercase().as_str() {
+			"discovered" => Ok(MediaStage::Discovered),
+			"downloaded" => Ok(MediaStage::Downloaded),
+			"reviewed" => Ok(MediaStage::Reviewed),
+			"edited" => Ok(MediaStage::Edited),
+			"moved" => Ok(MediaStage::Moved),
+			"archived" => Ok(MediaStage::Archived),
+			_ => Err(crate::Error::other(format!("Unknown media stage \"{s}\""))),
+		};
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_can_advance_to() {
+		assert!(MediaStage::Discovered.can_advance_to(MediaStage::Downloaded));
+		assert!(MediaStage::Downloaded.can_advance_to(MediaStage::Moved));
+		assert!(MediaStage::Reviewed.can_advance_to(MediaStage::Edited));
+		assert!(!MediaStage::Edited.can_advance_to(MediaStage::Reviewed));
+		assert!(MediaStage::Edited.can_advance_to(MediaStage::Edited));
+		assert!(!MediaStage::Moved.can_advance_to(MediaStage::Edited));
+		assert!(!MediaStage::Archived.can_advance_to(MediaStage::Discovered));
+	}
+
+	#[test]
+	fn test_str_roundtrip() {
+		for stage in [
+			MediaStage::Discovered,
+			MediaStage::Downloaded,
+			MediaStage::Reviewed,
+			MediaStage::Edited,
+			MediaStage::Moved,
+			MediaStage::Archived,
+		] {
+			assert_eq!(Ok(stage), stage.as_str().parse::<MediaStage>());
+			assert_eq!(format!("\"{stage}\""), serde_json::to_string(&stage).unwrap());
+		}
+
+		assert!("unknown".parse::<MediaStage>().is_err());
+	}
+}
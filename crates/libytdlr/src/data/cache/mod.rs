@@ -1,5 +1,7 @@
 //! Module for Cache Media Information
 //! This is used while ytdl is downloading & to recover from premature termination
 
+pub mod info_json;
 pub mod media_info;
 pub mod media_provider;
+pub mod media_stage;
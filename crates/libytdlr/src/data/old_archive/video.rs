@@ -164,11 +164,19 @@ mod test {
 		// reference
 		{
 			let media = crate::data::sql_models::Media {
-				_id:         0,
-				media_id:    "someid".to_owned(),
-				provider:    "youtube".to_owned(),
-				title:       "helloTitle".to_owned(),
-				inserted_at: chrono::NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
+				_id:            0,
+				media_id:       "someid".to_owned(),
+				provider:       "youtube".to_owned(),
+				title:          "helloTitle".to_owned(),
+				inserted_at:    chrono::NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
+				extractor:      None,
+				uploader:       None,
+				upload_date:    None,
+				duration_sec:   None,
+				webpage_url:    None,
+				filesize_bytes: None,
+				content_hash:   None,
+				removed_at:     None,
 			};
 
 			assert_eq!(
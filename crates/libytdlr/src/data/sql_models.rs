@@ -1,6 +1,14 @@
 //! Module for SQL Diesel Models
 
-use crate::data::sql_schema::media_archive;
+use crate::data::{
+	cache::media_provider::canonical_provider,
+	sql_schema::{
+		blacklist,
+		fingerprints,
+		labels,
+		media_archive,
+	},
+};
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
 
@@ -9,15 +17,31 @@ use diesel::prelude::*;
 #[diesel(table_name = media_archive)]
 pub struct Media {
 	/// The ID of the video, auto-incremented upwards
-	pub _id:         i64,
+	pub _id:            i64,
 	/// The ID of the media given used by the provider
-	pub media_id:    String,
+	pub media_id:       String,
 	/// The Provider from where this media was downloaded from
-	pub provider:    String,
+	pub provider:       String,
 	/// The Title the media has
-	pub title:       String,
+	pub title:          String,
 	/// The Time this media was inserted into the database
-	pub inserted_at: NaiveDateTime,
+	pub inserted_at:    NaiveDateTime,
+	/// The original extractor, if the provider was canonicalized (like "youtube:tab" for provider "youtube")
+	pub extractor:      Option<String>,
+	/// The name of the uploader (like the channel name), if known
+	pub uploader:       Option<String>,
+	/// The date the media was uploaded as "YYYY-MM-DD", if known
+	pub upload_date:    Option<String>,
+	/// The duration of the media in seconds, if known
+	pub duration_sec:   Option<i32>,
+	/// The URL of the page of the media, if known
+	pub webpage_url:    Option<String>,
+	/// The size of the media file in bytes at download time, if known
+	pub filesize_bytes: Option<i64>,
+	/// The SHA-256 of the media file at download time as lowercase hex, if known
+	pub content_hash:   Option<String>,
+	/// The Time this media was removed from the archive, if it is only kept as a tombstone (see [`crate::main::archive::remove::tombstone_entries`])
+	pub removed_at:     Option<NaiveDateTime>,
 }
 
 /// Struct for inserting a [Media] into the database
@@ -25,11 +49,25 @@ pub struct Media {
 #[diesel(table_name = media_archive)]
 pub struct InsMedia<'a> {
 	/// The ID of the media given used by the provider
-	pub media_id: &'a str,
+	pub media_id:       &'a str,
 	/// The Provider from where this media was downloaded from
-	pub provider: &'a str,
+	pub provider:       &'a str,
 	/// The Title the media has
-	pub title:    &'a str,
+	pub title:          &'a str,
+	/// The original extractor, if the provider was canonicalized (see [`InsMedia::canonicalized`])
+	pub extractor:      Option<&'a str>,
+	/// The name of the uploader (like the channel name), if known
+	pub uploader:       Option<&'a str>,
+	/// The date the media was uploaded as "YYYY-MM-DD", if known
+	pub upload_date:    Option<&'a str>,
+	/// The duration of the media in seconds, if known
+	pub duration_sec:   Option<i32>,
+	/// The URL of the page of the media, if known
+	pub webpage_url:    Option<&'a str>,
+	/// The size of the media file in bytes at download time, if known
+	pub filesize_bytes: Option<i64>,
+	/// The SHA-256 of the media file at download time as lowercase hex, if known
+	pub content_hash:   Option<&'a str>,
 }
 
 impl<'a> InsMedia<'a> {
@@ -39,6 +77,35 @@ impl<'a> InsMedia<'a> {
 			media_id,
 			provider,
 			title,
+			extractor: None,
+			uploader: None,
+			upload_date: None,
+			duration_sec: None,
+			webpage_url: None,
+			filesize_bytes: None,
+			content_hash: None,
+		};
+	}
+
+	/// Get a copy with the provider replaced by its canonical provider (see [`canonical_provider`]),
+	/// keeping the original provider as the extractor if it is not only a different case of the canonical provider
+	/// Returns a unchanged copy if the provider is not part of a known extractor family
+	#[must_use]
+	pub fn canonicalized(&self) -> Self {
+		let Some(canonical) = canonical_provider(self.provider) else {
+			return self.clone();
+		};
+
+		let extractor = if self.provider.trim().eq_ignore_ascii_case(canonical) {
+			self.extractor
+		} else {
+			self.extractor.or(Some(self.provider))
+		};
+
+		return Self {
+			provider: canonical,
+			extractor,
+			..self.clone()
 		};
 	}
 }
@@ -46,9 +113,111 @@ impl<'a> InsMedia<'a> {
 impl<'a> From<&'a Media> for InsMedia<'a> {
 	fn from(value: &'a Media) -> Self {
 		return Self {
-			media_id: &value.media_id,
-			provider: &value.provider,
-			title:    &value.title,
+			media_id:       &value.media_id,
+			provider:       &value.provider,
+			title:          &value.title,
+			extractor:      value.extractor.as_deref(),
+			uploader:       value.uploader.as_deref(),
+			upload_date:    value.upload_date.as_deref(),
+			duration_sec:   value.duration_sec,
+			webpage_url:    value.webpage_url.as_deref(),
+			filesize_bytes: value.filesize_bytes,
+			content_hash:   value.content_hash.as_deref(),
+		};
+	}
+}
+
+/// Struct representing a Label table entry
+#[derive(Debug, Clone, PartialEq, Queryable)]
+#[diesel(table_name = labels)]
+pub struct Label {
+	/// The [Media] "_id" this label is for
+	pub media_archive_id: i64,
+	/// The Label itself, like "music"
+	pub label:            String,
+}
+
+/// Struct for inserting a [Label] into the database
+#[derive(Debug, Clone, PartialEq, Insertable)]
+#[diesel(table_name = labels)]
+pub struct InsLabel<'a> {
+	/// The [Media] "_id" this label is for
+	pub media_archive_id: i64,
+	/// The Label itself, like "music"
+	pub label:            &'a str,
+}
+
+impl<'a> InsLabel<'a> {
+	/// Create a new instance of [InsLabel]
+	pub fn new(media_archive_id: i64, label: &'a str) -> Self {
+		return Self {
+			media_archive_id,
+			label,
+		};
+	}
+}
+
+/// Struct representing a Fingerprint table entry
+#[derive(Debug, Clone, PartialEq, Queryable)]
+#[diesel(table_name = fingerprints)]
+pub struct DbFingerprint {
+	/// The [Media] "_id" this fingerprint is for
+	pub media_archive_id: i64,
+	/// The Duration of the fingerprinted audio in seconds
+	pub duration:         i32,
+	/// The raw fingerprint, as comma-separated numbers
+	pub fingerprint:      String,
+}
+
+/// Struct for inserting a [DbFingerprint] into the database
+#[derive(Debug, Clone, PartialEq, Insertable, AsChangeset)]
+#[diesel(table_name = fingerprints)]
+pub struct InsFingerprint<'a> {
+	/// The [Media] "_id" this fingerprint is for
+	pub media_archive_id: i64,
+	/// The Duration of the fingerprinted audio in seconds
+	pub duration:         i32,
+	/// The raw fingerprint, as comma-separated numbers
+	pub fingerprint:      &'a str,
+}
+
+impl<'a> InsFingerprint<'a> {
+	/// Create a new instance of [InsFingerprint]
+	pub fn new(media_archive_id: i64, duration: i32, fingerprint: &'a str) -> Self {
+		return Self {
+			media_archive_id,
+			duration,
+			fingerprint,
 		};
 	}
 }
+
+/// Struct representing a Blacklist table entry
+#[derive(Debug, Clone, PartialEq, Queryable)]
+#[diesel(table_name = blacklist)]
+pub struct Blacklisted {
+	/// The ID of the media given used by the provider
+	pub media_id:    String,
+	/// The normalized Provider of the media (see [`crate::data::cache::media_provider::normalize_provider`])
+	pub provider:    String,
+	/// The Time this media was blacklisted
+	pub inserted_at: NaiveDateTime,
+}
+
+/// Struct for inserting a [Blacklisted] into the database
+#[derive(Debug, Clone, PartialEq, Insertable)]
+#[diesel(table_name = blacklist)]
+pub struct InsBlacklisted<'a> {
+	/// The ID of the media given used by the provider
+	pub media_id: &'a str,
+	/// The normalized Provider of the media (see [`crate::data::cache::media_provider::normalize_provider`])
+	pub provider: &'a str,
+}
+
+impl<'a> InsBlacklisted<'a> {
+	/// Create a new instance of [InsBlacklisted]
+	/// The provider is expected to already be normalized
+	pub fn new(media_id: &'a str, provider: &'a str) -> Self {
+		return Self { media_id, provider };
+	}
+}
@@ -2,6 +2,29 @@
 #![allow(missing_docs)]
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+	blacklist (media_id, provider) {
+		media_id -> Text,
+		provider -> Text,
+		inserted_at -> Timestamp,
+	}
+}
+
+diesel::table! {
+	fingerprints (media_archive_id) {
+		media_archive_id -> BigInt,
+		duration -> Integer,
+		fingerprint -> Text,
+	}
+}
+
+diesel::table! {
+	labels (media_archive_id, label) {
+		media_archive_id -> BigInt,
+		label -> Text,
+	}
+}
+
 diesel::table! {
 	media_archive (_id) {
 		_id -> BigInt,
@@ -9,5 +32,18 @@ diesel::table! {
 		provider -> Text,
 		title -> Text,
 		inserted_at -> Timestamp,
+		extractor -> Nullable<Text>,
+		uploader -> Nullable<Text>,
+		upload_date -> Nullable<Text>,
+		duration_sec -> Nullable<Integer>,
+		webpage_url -> Nullable<Text>,
+		filesize_bytes -> Nullable<BigInt>,
+		content_hash -> Nullable<Text>,
+		removed_at -> Nullable<Timestamp>,
 	}
 }
+
+diesel::joinable!(fingerprints -> media_archive (media_archive_id));
+diesel::joinable!(labels -> media_archive (media_archive_id));
+
+diesel::allow_tables_to_appear_in_same_query!(blacklist, fingerprints, labels, media_archive,);
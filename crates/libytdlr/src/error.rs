@@ -92,6 +92,44 @@ impl Error {
 	fn_string!(command_unsuccessful, ErrorInner::CommandNotSuccesful);
 	fn_path!(not_a_directory, ErrorInner::NotADirectory);
 	fn_path!(not_a_file, ErrorInner::NotAFile);
+	fn_path!(invalid_config, ErrorInner::InvalidConfig);
+
+	/// Create a new [Self] as [ErrorInner::DownloadFailed]
+	#[must_use]
+	pub fn download_failed(err: DownloadError) -> Self {
+		return Self::new(ErrorInner::DownloadFailed(Box::new(err)));
+	}
+
+	/// Get the [DownloadError] if this is a [ErrorInner::DownloadFailed]
+	#[must_use]
+	pub fn as_download_error(&self) -> Option<&DownloadError> {
+		return match &self.source {
+			ErrorInner::DownloadFailed(v) => Some(v),
+			_ => None,
+		};
+	}
+
+	/// Create a new [Self] as [ErrorInner::Cancelled]
+	#[must_use]
+	pub fn cancelled() -> Self {
+		return Self::new(ErrorInner::Cancelled);
+	}
+
+	/// Get whether this is a [ErrorInner::Cancelled]
+	#[must_use]
+	pub fn is_cancelled(&self) -> bool {
+		return matches!(self.source, ErrorInner::Cancelled);
+	}
+
+	/// Set the exit code of youtube-dl if this is a [ErrorInner::DownloadFailed], otherwise return [Self] unchanged
+	#[must_use]
+	pub(crate) fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+		if let ErrorInner::DownloadFailed(v) = &mut self.source {
+			v.exit_code = exit_code;
+		}
+
+		return self;
+	}
 
 	/// Map a [std::thread::JoinHandle::join] error to a [Error] with a thread name
 	fn map_thread_join<N: AsRef<str>>(name: N) -> impl Fn(Box<dyn std::any::Any + Send + 'static>) -> Self {
@@ -174,9 +212,19 @@ pub enum ErrorInner {
 	/// Variant for when a file path was expected but did not exist yet or was not a file
 	#[error("NotAFile: {0}; Path: \"{1}\"")]
 	NotAFile(String, String),
+	/// Variant for when a config file could not be parsed or has invalid values
+	#[error("InvalidConfig: {0}; Path: \"{1}\"")]
+	InvalidConfig(String, String),
 	/// Variant for thread join errors
 	#[error("ThreadJoinError: name: \"{1}\" original error: {0}")]
 	ThreadJoinError(String, String),
+	/// Variant for when youtube-dl reported a error while downloading
+	/// Boxed, because it would otherwise make every [Error] a lot larger
+	#[error("DownloadFailed: {0}")]
+	DownloadFailed(Box<DownloadError>),
+	/// Variant for when a operation was cancelled by a [`crate::main::download::CancellationToken`]
+	#[error("Cancelled")]
+	Cancelled,
 	/// Variant for Other messages
 	#[error("Other: {0}")]
 	Other(String),
@@ -190,19 +238,226 @@ impl PartialEq for ErrorInner {
 			(Self::FromStringUTF8Error(l0), Self::FromStringUTF8Error(r0)) => return l0 == r0,
 			(Self::SQLConnectionError(l0), Self::SQLConnectionError(r0)) => return l0 == r0,
 			(Self::SQLOperationError(l0), Self::SQLOperationError(r0)) => return l0 == r0,
+			(Self::DownloadFailed(l0), Self::DownloadFailed(r0)) => return l0 == r0,
+			(Self::Cancelled, Self::Cancelled) => return true,
 
 			(Self::CommandNotSuccesful(l0), Self::CommandNotSuccesful(r0))
 			| (Self::NoCapturesFound(l0), Self::NoCapturesFound(r0))
 			| (Self::Other(l0), Self::Other(r0))
 			| (Self::UnexpectedEOF(l0), Self::UnexpectedEOF(r0)) => return l0 == r0,
 			(Self::NotADirectory(l0, l1), Self::NotADirectory(r0, r1))
-			| (Self::NotAFile(l0, l1), Self::NotAFile(r0, r1)) => return l0 == r0 && l1 == r1,
+			| (Self::NotAFile(l0, l1), Self::NotAFile(r0, r1))
+			| (Self::InvalidConfig(l0, l1), Self::InvalidConfig(r0, r1)) => return l0 == r0 && l1 == r1,
 
 			(_, _) => return false,
 		}
 	}
 }
 
+/// Category of a [DownloadError], classified from the youtube-dl error message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadErrorCategory {
+	/// The connection failed, timed out or the server responded with a error
+	Network,
+	/// The media is not available in the current country
+	GeoBlocked,
+	/// The media is DRM protected, which youtube-dl does not support
+	Drm,
+	/// There is no space left on the device
+	DiskFull,
+	/// youtube-dl had no output for too long and was stopped, not classified from a message
+	Stalled,
+	/// The media is private, removed or otherwise not available
+	Unavailable,
+	/// Any error that does not fit into the other categories
+	Other,
+}
+
+impl DownloadErrorCategory {
+	/// Patterns (lower-case) of youtube-dl error messages and the category they belong to, checked in order
+	const PATTERNS: &'static [(&'static str, Self)] = &[
+		("no space left on device", Self::DiskFull),
+		("[errno 28]", Self::DiskFull),
+		("drm", Self::Drm),
+		("available in your country", Self::GeoBlocked),
+		("not available from your location", Self::GeoBlocked),
+		("geo restriction", Self::GeoBlocked),
+		("geo-restrict", Self::GeoBlocked),
+		("private video", Self::Unavailable),
+		("video unavailable", Self::Unavailable),
+		("has been removed", Self::Unavailable),
+		("this video is unavailable", Self::Unavailable),
+		("timed out", Self::Network),
+		("unable to download", Self::Network),
+		("urlopen error", Self::Network),
+		("http error", Self::Network),
+		("connection reset", Self::Network),
+		("connection refused", Self::Network),
+		("name resolution", Self::Network),
+		("network is unreachable", Self::Network),
+	];
+
+	/// Classify a youtube-dl error message
+	#[must_use]
+	pub fn from_message(msg: &str) -> Self {
+		let msg = msg.to_lowercase();
+
+		return Self::PATTERNS
+			.iter()
+			.find(|(pattern, _)| return msg.contains(pattern))
+			.map_or(Self::Other, |(_, category)| return *category);
+	}
+
+	/// Get a actionable hint for the category, if there is one
+	#[must_use]
+	pub fn hint(self) -> Option<&'static str> {
+		return match self {
+			Self::Network => {
+				Some("check the network connection and retry later, or lower the speed with \"--limit-rate\"")
+			},
+			Self::GeoBlocked => {
+				Some("the media is geo-blocked, try using a proxy or VPN (like with \"--extra-ytdl-args=--proxy=...\")")
+			},
+			Self::Drm => Some("the media is DRM protected, which youtube-dl cannot download"),
+			Self::Stalled => Some("the media stalled, retry later or increase \"--stall-timeout\""),
+			Self::DiskFull => {
+				Some("free up some space on the device of the temporary directory or the output directory")
+			},
+			Self::Unavailable | Self::Other => None,
+		};
+	}
+}
+
+impl std::fmt::Display for DownloadErrorCategory {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return f.write_str(match self {
+			Self::Network => "network",
+			Self::GeoBlocked => "geo-blocked",
+			Self::Drm => "drm",
+			Self::DiskFull => "disk-full",
+			Self::Stalled => "stalled",
+			Self::Unavailable => "unavailable",
+			Self::Other => "other",
+		});
+	}
+}
+
+/// Details of a error youtube-dl reported while downloading, see [ErrorInner::DownloadFailed]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadError {
+	/// The error message as youtube-dl printed it
+	pub message:   String,
+	/// The category of the error, classified from the message
+	pub category:  DownloadErrorCategory,
+	/// The URL that was being downloaded
+	pub url:       Option<String>,
+	/// The id of the media that failed, if known
+	pub media_id:  Option<String>,
+	/// The exit code of youtube-dl, if it exited normally
+	pub exit_code: Option<i32>,
+}
+
+impl DownloadError {
+	/// Create a new [DownloadError] from a youtube-dl error line, like "ERROR: [youtube] someid: Video unavailable"
+	/// The category and media id are parsed from the line
+	#[must_use]
+	pub fn from_line<M: Into<String>>(line: M) -> Self {
+		let message = line.into();
+
+		return Self {
+			category: DownloadErrorCategory::from_message(&message),
+			media_id: parse_error_media_id(&message).map(str::to_owned),
+			message,
+			url: None,
+			exit_code: None,
+		};
+	}
+
+	/// Create a new [DownloadError] for a media which had no output for `elapsed` and was stopped
+	#[must_use]
+	pub fn stalled(elapsed: std::time::Duration) -> Self {
+		return Self {
+			message:   format!("Stalled: no output for {}s", elapsed.as_secs()),
+			category:  DownloadErrorCategory::Stalled,
+			url:       None,
+			media_id:  None,
+			exit_code: None,
+		};
+	}
+
+	/// Set the URL that was being downloaded
+	#[must_use]
+	pub fn with_url<U: Into<String>>(mut self, url: U) -> Self {
+		self.url = Some(url.into());
+
+		return self;
+	}
+
+	/// Set the media id, if none has been parsed from the message
+	#[must_use]
+	pub fn with_fallback_media_id(mut self, media_id: Option<String>) -> Self {
+		if self.media_id.is_none() {
+			self.media_id = media_id;
+		}
+
+		return self;
+	}
+
+	/// Get whether the error is likely transient (like a timeout, a HTTP 5xx error or a stall), so that retrying later may succeed
+	/// HTTP 4xx errors (like 403 or 404) are not transient, because they would fail again
+	#[must_use]
+	pub fn is_transient(&self) -> bool {
+		return match self.category {
+			DownloadErrorCategory::Network => !self.message.to_lowercase().contains("http error 4"),
+			DownloadErrorCategory::Stalled => true,
+			_ => false,
+		};
+	}
+
+	/// Get whether youtube-dl was stopped because the media stalled, see [DownloadError::stalled]
+	#[must_use]
+	pub fn is_stalled(&self) -> bool {
+		return self.category == DownloadErrorCategory::Stalled;
+	}
+
+	/// Get whether there is no space left on the device, in which case every following media would fail too
+	#[must_use]
+	pub fn is_disk_full(&self) -> bool {
+		return self.category == DownloadErrorCategory::DiskFull;
+	}
+}
+
+impl std::fmt::Display for DownloadError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}; Category: {}", self.message, self.category)?;
+
+		if let Some(media_id) = &self.media_id {
+			write!(f, "; Media: \"{}\"", media_id)?;
+		}
+		if let Some(url) = &self.url {
+			write!(f, "; URL: \"{}\"", url)?;
+		}
+		if let Some(exit_code) = self.exit_code {
+			write!(f, "; Exit code: {}", exit_code)?;
+		}
+
+		return Ok(());
+	}
+}
+
+/// Get the media id from a youtube-dl error line, like "ERROR: [youtube] someid: Video unavailable" -> "someid"
+fn parse_error_media_id(line: &str) -> Option<&str> {
+	let rest = line.strip_prefix("ERROR:")?.trim_start().strip_prefix('[')?;
+	let (_, rest) = rest.split_once("] ")?;
+	let (id, _) = rest.split_once(": ")?;
+
+	if id.is_empty() || id.contains(char::is_whitespace) {
+		return None;
+	}
+
+	return Some(id);
+}
+
 /// Custom [std::thread::JoinHandle::join] implementation to return a [Error] with thread name
 pub trait CustomThreadJoin<T> {
 	/// Custom thread join method for libytdlr so that errors are automatically mapped to the current error type and have the named from the thread
@@ -256,3 +511,101 @@ impl<T> IOErrorToError<T> for Result<T, std::io::Error> {
 		};
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_category_from_message() {
+		assert_eq!(
+			DownloadErrorCategory::Network,
+			DownloadErrorCategory::from_message(
+				"ERROR: [provider] id: Unable to download webpage: The read operation timed out"
+			)
+		);
+		assert_eq!(
+			DownloadErrorCategory::GeoBlocked,
+			DownloadErrorCategory::from_message(
+				"ERROR: [youtube] id: The uploader has not made this video available in your country"
+			)
+		);
+		assert_eq!(
+			DownloadErrorCategory::Drm,
+			DownloadErrorCategory::from_message("ERROR: [provider] id: This video is DRM protected")
+		);
+		assert_eq!(
+			DownloadErrorCategory::DiskFull,
+			DownloadErrorCategory::from_message("ERROR: unable to write data: [Errno 28] No space left on device")
+		);
+		assert_eq!(
+			DownloadErrorCategory::Unavailable,
+			DownloadErrorCategory::from_message(
+				"ERROR: [youtube] id: Private video. Sign in if you've been granted access"
+			)
+		);
+		assert_eq!(
+			DownloadErrorCategory::Other,
+			DownloadErrorCategory::from_message("ERROR: [provider] id: somekinda error")
+		);
+	}
+
+	#[test]
+	fn test_download_error_from_line() {
+		let err =
+			DownloadError::from_line("ERROR: [youtube] someid: Video unavailable").with_url("https://example.com");
+		assert_eq!(DownloadErrorCategory::Unavailable, err.category);
+		assert_eq!(Some("someid".to_owned()), err.media_id);
+		assert_eq!(Some("https://example.com".to_owned()), err.url);
+
+		// no media id in the line, so the fallback is used
+		let err = DownloadError::from_line("ERROR: unable to write data: [Errno 28] No space left on device")
+			.with_fallback_media_id(Some("otherid".to_owned()));
+		assert_eq!(Some("otherid".to_owned()), err.media_id);
+
+		let err = crate::Error::download_failed(err).with_exit_code(Some(1));
+		assert_eq!(Some(1), err.as_download_error().and_then(|v| return v.exit_code));
+		assert!(crate::Error::other("some").as_download_error().is_none());
+	}
+
+	#[test]
+	fn test_download_error_is_transient() {
+		assert!(DownloadError::from_line(
+			"ERROR: [provider] id: Unable to download webpage: The read operation timed out"
+		)
+		.is_transient());
+		assert!(DownloadError::from_line(
+			"ERROR: [youtube] someid: Unable to download API page: HTTP Error 503: Service Unavailable"
+		)
+		.is_transient());
+		assert!(
+			!DownloadError::from_line("ERROR: unable to download video data: HTTP Error 403: Forbidden").is_transient()
+		);
+		assert!(!DownloadError::from_line("ERROR: [youtube] someid: Video unavailable").is_transient());
+		assert!(DownloadError::stalled(std::time::Duration::from_secs(60)).is_transient());
+	}
+
+	#[test]
+	fn test_download_error_stalled() {
+		let err = DownloadError::stalled(std::time::Duration::from_secs(61))
+			.with_fallback_media_id(Some("someid".to_owned()));
+		assert!(err.is_stalled());
+		assert_eq!(Some("someid".to_owned()), err.media_id);
+		assert_eq!(
+			"Stalled: no output for 61s; Category: stalled; Media: \"someid\"",
+			err.to_string()
+		);
+		assert!(!DownloadError::from_line("ERROR: [youtube] someid: Video unavailable").is_stalled());
+	}
+
+	#[test]
+	fn test_download_error_disk_full() {
+		assert!(
+			DownloadError::from_line("ERROR: unable to write data: [Errno 28] No space left on device").is_disk_full()
+		);
+		assert!(
+			!DownloadError::from_line("ERROR: unable to write data: [Errno 28] No space left on device").is_transient()
+		);
+		assert!(!DownloadError::from_line("ERROR: [youtube] someid: Video unavailable").is_disk_full());
+	}
+}
@@ -8,6 +8,7 @@
 #[macro_use]
 extern crate log;
 
+pub mod config;
 pub mod data;
 pub mod error;
 pub mod main;
@@ -0,0 +1,263 @@
+//! Module for storing audio fingerprints and finding duplicate media by them
+
+use diesel::prelude::*;
+
+use crate::{
+	data::{
+		sql_models::{
+			DbFingerprint,
+			InsFingerprint,
+			Media,
+		},
+		sql_schema::{
+			fingerprints,
+			media_archive,
+		},
+	},
+	spawn::fpcalc::Fingerprint,
+};
+
+/// Maximal difference in seconds between the durations of 2 fingerprints to be considered for comparison
+pub const MAX_DURATION_DIFFERENCE: u32 = 10;
+
+/// Maximal offset (in fingerprint items) tried when aligning 2 fingerprints
+/// One item is roughly 0.12 seconds
+const MAX_ALIGN_OFFSET: usize = 80;
+
+/// Minimal amount of overlapping items for 2 fingerprints to be compared
+const MIN_OVERLAP: usize = 20;
+
+/// Calculate the similarity of 2 raw fingerprints, from `0.0` (nothing in common) to `1.0` (identical)
+/// Both fingerprints are aligned at the offset with the best similarity, up to [`MAX_ALIGN_OFFSET`]
+#[must_use]
+pub fn fingerprint_similarity(a: &[u32], b: &[u32]) -> f64 {
+	let mut best: f64 = 0.0;
+
+	for offset in 0..=MAX_ALIGN_OFFSET {
+		// try shifting both ways
+		for (first, second) in [(a, b), (b, a)] {
+			let Some(first) = first.get(offset..) else {
+				continue;
+			};
+			let overlap = first.len().min(second.len());
+
+			if overlap < MIN_OVERLAP {
+				continue;
+			}
+
+			let differing_bits: u64 = first
+				.iter()
+				.zip(second.iter())
+				.map(|(a, b)| return u64::from((a ^ b).count_ones()))
+				.sum();
+
+			#[allow(clippy::cast_precision_loss)]
+			let similarity = 1.0 - (differing_bits as f64 / (overlap as f64 * 32.0));
+
+			best = best.max(similarity);
+		}
+	}
+
+	return best;
+}
+
+/// Store the fingerprint of media `media_archive_id`, replacing a existing fingerprint
+pub fn store_fingerprint(
+	connection: &mut SqliteConnection,
+	media_archive_id: i64,
+	fingerprint: &Fingerprint,
+) -> Result<(), crate::Error> {
+	let raw = fingerprint.raw_to_string();
+	let duration = i32::try_from(fingerprint.duration)
+		.map_err(|_| return crate::Error::other("Fingerprint duration is too large"))?;
+	let insert = InsFingerprint::new(media_archive_id, duration, &raw);
+
+	diesel::insert_into(fingerprints::table)
+		.values(&insert)
+		.on_conflict(fingerprints::media_archive_id)
+		.do_update()
+		.set(&insert)
+		.execute(connection)?;
+
+	return Ok(());
+}
+
+/// Find all media with a fingerprint similar to `fingerprint` (at least `min_similarity`), excluding media `exclude_id`
+/// Returns the found media with their similarity, most similar first
+pub fn find_similar(
+	connection: &mut SqliteConnection,
+	fingerprint: &Fingerprint,
+	exclude_id: Option<i64>,
+	min_similarity: f64,
+) -> Result<Vec<(Media, f64)>, crate::Error> {
+	let duration = i32::try_from(fingerprint.duration).unwrap_or(i32::MAX);
+	#[allow(clippy::cast_possible_wrap)]
+	let max_diff = MAX_DURATION_DIFFERENCE as i32;
+
+	let mut query = fingerprints::table
+		.inner_join(media_archive::table)
+		.filter(fingerprints::duration.between(duration.saturating_sub(max_diff), duration.saturating_add(max_diff)))
+		.into_boxed();
+
+	if let Some(exclude_id) = exclude_id {
+		query = query.filter(fingerprints::media_archive_id.ne(exclude_id));
+	}
+
+	let candidates: Vec<(DbFingerprint, Media)> = query.load(connection)?;
+
+	let mut found = Vec::new();
+
+	for (candidate, media) in candidates {
+		let raw = match Fingerprint::parse_raw(&candidate.fingerprint) {
+			Ok(v) => v,
+			Err(err) => {
+				warn!(
+					"Ignoring invalid stored fingerprint for \"{}\": {}",
+					media.media_id, err
+				);
+				continue;
+			},
+		};
+
+		let similarity = fingerprint_similarity(&fingerprint.fingerprint, &raw);
+
+		if similarity >= min_similarity {
+			found.push((media, similarity));
+		}
+	}
+
+	found.sort_by(|a, b| return b.1.total_cmp(&a.1));
+
+	return Ok(found);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::main::test_utils::{
+		create_connection,
+		insert_media,
+	};
+
+	/// Test helper to generate a deterministic pseudo-random raw fingerprint
+	fn generate_raw(seed: u32, len: usize) -> Vec<u32> {
+		let mut state = seed;
+		return (0..len)
+			.map(|_| {
+				// simple xorshift
+				state ^= state << 13;
+				state ^= state >> 17;
+				state ^= state << 5;
+				return state;
+			})
+			.collect();
+	}
+
+	mod similarity {
+		use super::*;
+
+		#[test]
+		fn test_identical() {
+			let raw = generate_raw(1, 100);
+			assert_eq!(1.0, fingerprint_similarity(&raw, &raw));
+		}
+
+		#[test]
+		fn test_offset() {
+			let raw = generate_raw(1, 200);
+			assert_eq!(1.0, fingerprint_similarity(&raw[10..], &raw));
+			assert_eq!(1.0, fingerprint_similarity(&raw, &raw[10..]));
+		}
+
+		#[test]
+		fn test_different() {
+			let similarity = fingerprint_similarity(&generate_raw(1, 100), &generate_raw(2, 100));
+			assert!(similarity < 0.7, "Expected {similarity} to be below 0.7");
+		}
+
+		#[test]
+		fn test_too_short() {
+			let raw = generate_raw(1, 10);
+			assert_eq!(0.0, fingerprint_similarity(&raw, &raw));
+		}
+	}
+
+	mod find_similar {
+		use super::*;
+
+		#[test]
+		fn test_basic() {
+			let (mut connection, _tempdir) = create_connection();
+
+			let id1 = insert_media(&mut connection, "id1", "youtube", "id1");
+			let id2 = insert_media(&mut connection, "id2", "soundcloud", "id2");
+			let id3 = insert_media(&mut connection, "id3", "youtube", "id3");
+			let id4 = insert_media(&mut connection, "id4", "youtube", "id4");
+
+			let song = Fingerprint {
+				duration:    180,
+				fingerprint: generate_raw(1, 200),
+			};
+
+			store_fingerprint(&mut connection, id1, &song).expect("Expected store to be ok");
+			// same song on a different provider, slightly offset and different length
+			store_fingerprint(
+				&mut connection,
+				id2,
+				&Fingerprint {
+					duration:    182,
+					fingerprint: song.fingerprint[5..].to_vec(),
+				},
+			)
+			.expect("Expected store to be ok");
+			// different song with the same duration
+			store_fingerprint(
+				&mut connection,
+				id3,
+				&Fingerprint {
+					duration:    180,
+					fingerprint: generate_raw(2, 200),
+				},
+			)
+			.expect("Expected store to be ok");
+			// same song, but duration too different
+			store_fingerprint(
+				&mut connection,
+				id4,
+				&Fingerprint {
+					duration:    300,
+					fingerprint: song.fingerprint.clone(),
+				},
+			)
+			.expect("Expected store to be ok");
+
+			let found = find_similar(&mut connection, &song, Some(id1), 0.9).expect("Expected find to be ok");
+
+			assert_eq!(1, found.len());
+			assert_eq!("id2", found[0].0.media_id);
+			assert_eq!(1.0, found[0].1);
+		}
+
+		#[test]
+		fn test_store_replaces() {
+			let (mut connection, _tempdir) = create_connection();
+
+			let id1 = insert_media(&mut connection, "id1", "youtube", "id1");
+
+			let first = Fingerprint {
+				duration:    100,
+				fingerprint: generate_raw(1, 100),
+			};
+			let second = Fingerprint {
+				duration:    100,
+				fingerprint: generate_raw(2, 100),
+			};
+
+			store_fingerprint(&mut connection, id1, &first).expect("Expected store to be ok");
+			store_fingerprint(&mut connection, id1, &second).expect("Expected store to be ok");
+
+			assert!(find_similar(&mut connection, &first, None, 0.9).unwrap().is_empty());
+			assert_eq!(1, find_similar(&mut connection, &second, None, 0.9).unwrap().len());
+		}
+	}
+}
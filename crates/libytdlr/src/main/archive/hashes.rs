@@ -0,0 +1,228 @@
+//! Module for calculating and storing content hashes of media files and finding duplicate media by them
+
+use std::{
+	fs::File,
+	io::BufReader,
+	path::Path,
+};
+
+use data_encoding::HEXLOWER;
+use diesel::prelude::*;
+use sha2::{
+	Digest,
+	Sha256,
+};
+
+use crate::{
+	data::{
+		cache::media_provider::canonical_provider,
+		sql_models::Media,
+		sql_schema::media_archive,
+	},
+	error::IOErrorToError,
+};
+
+/// Calculate the SHA-256 content hash of the file at `path`, the file is read buffered instead of all at once
+/// Returns the hash as lowercase hex
+pub fn sha256_file<P: AsRef<Path>>(path: P) -> Result<String, crate::Error> {
+	let path = path.as_ref();
+	let mut reader = BufReader::new(File::open(path).attach_path_err(path)?);
+	let mut hasher = Sha256::new();
+
+	std::io::copy(&mut reader, &mut hasher).attach_path_err(path)?;
+
+	return Ok(HEXLOWER.encode(&hasher.finalize()));
+}
+
+/// Store `hash` as the content hash of the media with `provider` and `media_id`, replacing a existing hash
+/// The provider is canonicalized like on insertion (see [`canonical_provider`])
+/// Returns `false` if the media is not in the archive
+pub fn store_content_hash(
+	connection: &mut SqliteConnection,
+	provider: &str,
+	media_id: &str,
+	hash: &str,
+) -> Result<bool, crate::Error> {
+	let provider = canonical_provider(provider).unwrap_or(provider);
+
+	let updated = diesel::update(
+		media_archive::table
+			.filter(media_archive::provider.eq(provider))
+			.filter(media_archive::media_id.eq(media_id)),
+	)
+	.set(media_archive::content_hash.eq(hash))
+	.execute(connection)?;
+
+	return Ok(updated > 0);
+}
+
+/// Find all media with the content hash `hash`, excluding the media with `provider` and `media_id`
+/// Returns the found media in order of insertion
+pub fn find_by_content_hash(
+	connection: &mut SqliteConnection,
+	hash: &str,
+	provider: &str,
+	media_id: &str,
+) -> Result<Vec<Media>, crate::Error> {
+	let provider = canonical_provider(provider).unwrap_or(provider);
+
+	return Ok(media_archive::table
+		.filter(media_archive::content_hash.eq(hash))
+		.filter(
+			media_archive::provider
+				.ne(provider)
+				.or(media_archive::media_id.ne(media_id)),
+		)
+		.order(media_archive::_id.asc())
+		.load::<Media>(connection)?);
+}
+
+/// Find all groups of media which have the same content hash
+/// Returns the groups sorted by hash, with the media of each group in order of insertion
+pub fn find_content_duplicates(connection: &mut SqliteConnection) -> Result<Vec<(String, Vec<Media>)>, crate::Error> {
+	let duplicate_hashes = media_archive::table
+		.filter(media_archive::content_hash.is_not_null())
+		.group_by(media_archive::content_hash)
+		.having(diesel::dsl::count_star().gt(1))
+		.select(media_archive::content_hash)
+		.load::<Option<String>>(connection)?;
+
+	let media = media_archive::table
+		.filter(media_archive::content_hash.eq_any(duplicate_hashes.into_iter().flatten()))
+		.order((media_archive::content_hash.asc(), media_archive::_id.asc()))
+		.load::<Media>(connection)?;
+
+	let mut groups: Vec<(String, Vec<Media>)> = Vec::new();
+
+	for media in media {
+		let Some(hash) = media.content_hash.clone() else {
+			continue;
+		};
+
+		match groups.last_mut() {
+			Some((last_hash, group)) if *last_hash == hash => group.push(media),
+			_ => groups.push((hash, vec![media])),
+		}
+	}
+
+	return Ok(groups);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		data::sql_models::InsMedia,
+		main::{
+			archive::import::insert_insmedia,
+			test_utils::create_connection,
+		},
+	};
+
+	/// Test helper to insert a media with a content hash
+	fn insert_hashed(connection: &mut SqliteConnection, id: &str, provider: &str, hash: &str) {
+		insert_insmedia(&InsMedia::new(id, provider, id), connection).expect("Expected media to be inserted");
+		assert_eq!(
+			Ok(true),
+			store_content_hash(connection, provider, id, hash),
+			"Expected the hash to be stored"
+		);
+	}
+
+	#[test]
+	fn test_sha256_file() {
+		let (_connection, tempdir) = create_connection();
+		let empty_path = tempdir.as_ref().join("empty file.mp3");
+		let abc_path = tempdir.as_ref().join("abc.mp3");
+		std::fs::write(&empty_path, "").expect("Expected file to be written");
+		std::fs::write(&abc_path, "abc").expect("Expected file to be written");
+
+		assert_eq!(
+			Ok("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_owned()),
+			sha256_file(&empty_path)
+		);
+		assert_eq!(
+			Ok("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_owned()),
+			sha256_file(&abc_path)
+		);
+		assert!(sha256_file(tempdir.as_ref().join("missing.mp3")).is_err());
+	}
+
+	#[test]
+	fn test_find_by_content_hash() {
+		let (mut connection, _tempdir) = create_connection();
+
+		insert_hashed(&mut connection, "id1", "youtube", "aaaa");
+		insert_hashed(&mut connection, "id2", "youtube", "aaaa");
+		insert_hashed(&mut connection, "id1", "soundcloud", "aaaa");
+		insert_hashed(&mut connection, "id3", "youtube", "bbbb");
+
+		let found = find_by_content_hash(&mut connection, "aaaa", "youtube", "id1").expect("Expected find to be ok");
+
+		assert_eq!(
+			vec![
+				("youtube".to_owned(), "id2".to_owned()),
+				("soundcloud".to_owned(), "id1".to_owned())
+			],
+			found
+				.into_iter()
+				.map(|v| return (v.provider, v.media_id))
+				.collect::<Vec<_>>()
+		);
+
+		assert!(find_by_content_hash(&mut connection, "bbbb", "youtube", "id3")
+			.expect("Expected find to be ok")
+			.is_empty());
+	}
+
+	#[test]
+	fn test_store_content_hash() {
+		let (mut connection, _tempdir) = create_connection();
+
+		assert_eq!(
+			Ok(false),
+			store_content_hash(&mut connection, "youtube", "missing", "aaaa")
+		);
+
+		// the provider is canonicalized like on insertion
+		insert_hashed(&mut connection, "id1", "youtube:tab", "aaaa");
+		insert_hashed(&mut connection, "id1", "youtube", "bbbb");
+
+		// inserting the media again without a hash keeps the existing hash
+		insert_insmedia(&InsMedia::new("id1", "youtube", "New Title"), &mut connection)
+			.expect("Expected media to be updated");
+
+		let media = media_archive::table
+			.load::<Media>(&mut connection)
+			.expect("Expected media to load");
+
+		assert_eq!(1, media.len());
+		assert_eq!(Some("bbbb".to_owned()), media[0].content_hash);
+	}
+
+	#[test]
+	fn test_find_content_duplicates() {
+		let (mut connection, _tempdir) = create_connection();
+
+		insert_hashed(&mut connection, "id1", "youtube", "bbbb");
+		insert_hashed(&mut connection, "id2", "youtube", "aaaa");
+		insert_hashed(&mut connection, "id3", "youtube", "bbbb");
+		insert_hashed(&mut connection, "id4", "youtube", "cccc");
+		insert_hashed(&mut connection, "id5", "youtube", "aaaa");
+		insert_insmedia(&InsMedia::new("id6", "youtube", "id6"), &mut connection)
+			.expect("Expected media to be inserted");
+
+		let groups = find_content_duplicates(&mut connection).expect("Expected find to be ok");
+
+		assert_eq!(
+			vec![
+				("aaaa".to_owned(), vec!["id2".to_owned(), "id5".to_owned()]),
+				("bbbb".to_owned(), vec!["id1".to_owned(), "id3".to_owned()])
+			],
+			groups
+				.into_iter()
+				.map(|(hash, group)| return (hash, group.into_iter().map(|v| return v.media_id).collect()))
+				.collect::<Vec<(String, Vec<String>)>>()
+		);
+	}
+}
@@ -1,11 +1,13 @@
 //! Module for importing a archive into the current one
 
+use chrono::NaiveDateTime;
 use diesel::{
 	prelude::*,
 	upsert::excluded,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Deserialize;
 use std::{
 	fs::File,
 	io::{
@@ -58,7 +60,12 @@ pub enum ArchiveType {
 	/// JSON YTDL-R Archive
 	JSON,
 	/// SQLite YTDL-R Archive (currently unused)
+	/// May also be a Newpipe database, see [`is_newpipe_database`]
 	SQLite,
+	/// FreeTube history export (NeDB, one JSON object per line)
+	FreeTube,
+	/// ZIP file, like a Newpipe export (which is not supported directly)
+	Zip,
 }
 
 /// Detect what archive type the input reader's file is
@@ -77,9 +84,21 @@ pub fn detect_archive_type<T: BufRead>(reader: &mut T) -> Result<ArchiveType, cr
 	let trimmed = as_string.trim_start().as_bytes();
 
 	if trimmed.starts_with(b"{") {
+		// FreeTube history has one complete object per line, which will (likely) not be the case for a YTDL-R Archive
+		let first_line = as_string.trim_start().lines().next().unwrap_or_default();
+		if let Ok(entry) = serde_json::from_str::<serde_json::Value>(first_line) {
+			if entry.get(FREETUBE_ID_KEY).is_some() {
+				return Ok(ArchiveType::FreeTube);
+			}
+		}
+
 		return Ok(ArchiveType::JSON);
 	}
 
+	if trimmed.starts_with(b"PK\x03\x04") {
+		return Ok(ArchiveType::Zip);
+	}
+
 	if trimmed.starts_with(b"SQLite format") {
 		return Ok(ArchiveType::SQLite);
 	}
@@ -101,7 +120,17 @@ pub fn import_any_archive<S: FnMut(ImportProgress)>(
 
 	return match detect_archive_type(&mut reader)? {
 		ArchiveType::JSON => import_ytdlr_json_archive(&mut reader, merge_to, pgcb),
-		ArchiveType::SQLite => import_ytdlr_sqlite_archive(input_path, merge_to, pgcb),
+		ArchiveType::SQLite => {
+			if is_newpipe_database(input_path)? {
+				import_newpipe_database(input_path, merge_to, pgcb)
+			} else {
+				import_ytdlr_sqlite_archive(input_path, merge_to, pgcb)
+			}
+		},
+		ArchiveType::FreeTube => import_freetube_history(&mut reader, merge_to, pgcb),
+		ArchiveType::Zip => Err(crate::Error::other(
+			"ZIP files cannot be imported, for a Newpipe export extract and import the \"newpipe.db\" from it",
+		)),
 		// Assume "Unknown" is a YTDL Archive (plain text)
 		ArchiveType::Unknown => import_ytdl_archive(&mut reader, merge_to, pgcb),
 	};
@@ -159,6 +188,190 @@ pub fn import_ytdlr_sqlite_archive<S: FnMut(ImportProgress)>(
 	return Ok(());
 }
 
+/// Regex to get the youtube media id from a url, like "https://www.youtube.com/watch?v=ID" or "https://youtu.be/ID"
+/// 1. capture group is the ID
+static YOUTUBE_URL_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?i)(?:[?&]v=|youtu\.be/|/shorts/|/live/)([\w\-]{11})").unwrap();
+});
+
+/// The Newpipe "service_id" of youtube, other services are not imported, because their media id cannot be derived from the url
+const NEWPIPE_SERVICE_YOUTUBE: i32 = 0;
+
+/// A watched stream of a Newpipe database, see [`import_newpipe_database`]
+#[derive(Debug, Clone, PartialEq, QueryableByName)]
+struct NewpipeStream {
+	/// The service the stream is from, see [`NEWPIPE_SERVICE_YOUTUBE`]
+	#[diesel(sql_type = diesel::sql_types::Integer)]
+	service_id: i32,
+	/// The url of the stream
+	#[diesel(sql_type = diesel::sql_types::Text)]
+	url:        String,
+	/// The title of the stream
+	#[diesel(sql_type = diesel::sql_types::Text)]
+	title:      String,
+	/// The uploader of the stream
+	#[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Text>)]
+	uploader:   Option<String>,
+	/// The duration of the stream in seconds
+	#[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::Integer>)]
+	duration:   Option<i32>,
+}
+
+/// Check whether the SQLite file at `input_path` is a Newpipe database (the "newpipe.db" of a Newpipe export)
+/// Does not apply any migrations to the file
+pub fn is_newpipe_database(input_path: &Path) -> Result<bool, crate::Error> {
+	let mut connection = SqliteConnection::establish(&input_path.to_string_lossy())?;
+
+	let tables = diesel::sql_query(
+		"SELECT COUNT(*) AS count FROM sqlite_master WHERE type = 'table' AND name IN ('streams', 'stream_history')",
+	)
+	.get_result::<SqliteCount>(&mut connection)?;
+
+	return Ok(tables.count == 2);
+}
+
+/// Result of a "COUNT" query
+#[derive(Debug, Clone, Copy, PartialEq, QueryableByName)]
+struct SqliteCount {
+	/// The count
+	#[diesel(sql_type = diesel::sql_types::BigInt)]
+	count: i64,
+}
+
+/// Import the watch history of a Newpipe database (the "newpipe.db" of a Newpipe export)
+/// Only youtube media are imported, other services are skipped (see [`NEWPIPE_SERVICE_YOUTUBE`])
+///
+/// This function modifies the input `merge_to` archive, and so will return `()`
+pub fn import_newpipe_database<S: FnMut(ImportProgress)>(
+	input_path: &Path,
+	merge_to: &mut SqliteConnection,
+	mut pgcb: S,
+) -> Result<(), crate::Error> {
+	log::debug!("import newpipe database");
+
+	// not using "sqlite_connect", because that would apply the archive migrations to the newpipe database
+	let mut input_connection = SqliteConnection::establish(&input_path.to_string_lossy())?;
+
+	pgcb(ImportProgress::Starting);
+
+	let streams = diesel::sql_query(
+		"SELECT s.service_id, s.url, s.title, s.uploader, s.duration FROM streams s INNER JOIN stream_history h ON h.stream_id = s.uid GROUP BY s.uid ORDER BY MIN(h.access_date) ASC",
+	)
+	.load::<NewpipeStream>(&mut input_connection)?;
+
+	pgcb(ImportProgress::SizeHint(streams.len()));
+
+	let mut affected_rows = 0usize;
+
+	for (index, stream) in streams.iter().enumerate() {
+		let media_id = (stream.service_id == NEWPIPE_SERVICE_YOUTUBE)
+			.then(|| return YOUTUBE_URL_ID_REGEX.captures(&stream.url))
+			.flatten();
+
+		let Some(media_id) = media_id else {
+			log::info!("Skipping unsupported Newpipe stream \"{}\"", stream.url);
+
+			continue;
+		};
+
+		let insmedia = InsMedia {
+			uploader: stream.uploader.as_deref(),
+			// newpipe uses 0 for unknown durations (like for livestreams)
+			duration_sec: stream.duration.filter(|v| return *v > 0),
+			..InsMedia::new(&media_id[1], "youtube", &stream.title)
+		};
+
+		affected_rows += insert_insmedia(&insmedia, merge_to)?;
+		pgcb(ImportProgress::Increase(1, index));
+	}
+
+	pgcb(ImportProgress::Finished(affected_rows));
+
+	return Ok(());
+}
+
+/// The key of the youtube media id in a FreeTube history entry, also used to detect FreeTube history
+const FREETUBE_ID_KEY: &str = "videoId";
+
+/// A entry of a FreeTube history export, see [`import_freetube_history`]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct FreeTubeHistoryEntry {
+	/// The youtube media id
+	#[serde(rename = "videoId")]
+	video_id:       String,
+	/// The title of the media
+	title:          Option<String>,
+	/// The channel name of the media
+	author:         Option<String>,
+	/// The duration of the media in seconds, which may be a number or a string depending on the FreeTube version
+	#[serde(rename = "lengthSeconds")]
+	length_seconds: Option<serde_json::Value>,
+}
+
+impl FreeTubeHistoryEntry {
+	/// Get the duration in seconds, if it is a valid positive number
+	fn duration_sec(&self) -> Option<i32> {
+		let duration = match self.length_seconds.as_ref()? {
+			serde_json::Value::Number(v) => v.as_i64()?,
+			serde_json::Value::String(v) => v.parse().ok()?,
+			_ => return None,
+		};
+
+		return i32::try_from(duration).ok().filter(|v| return *v > 0);
+	}
+}
+
+/// Import a FreeTube history export (NeDB, one JSON object per line)
+/// Lines which are not history entries (like NeDB deletion markers) are skipped
+///
+/// This function modifies the input `merge_to` archive, and so will return `()`
+pub fn import_freetube_history<T: BufRead, S: FnMut(ImportProgress)>(
+	reader: &mut T,
+	merge_to: &mut SqliteConnection,
+	mut pgcb: S,
+) -> Result<(), crate::Error> {
+	log::debug!("import freetube history");
+
+	pgcb(ImportProgress::Starting);
+
+	let mut affected_rows = 0usize;
+
+	for (index, line) in reader.lines().enumerate() {
+		let line = line.attach_location_err("import freetube line iter")?;
+		let line = line.trim();
+
+		if line.is_empty() {
+			continue;
+		}
+
+		let entry = match serde_json::from_str::<FreeTubeHistoryEntry>(line) {
+			Ok(v) => v,
+			Err(err) => {
+				log::info!("Skipping FreeTube line that is not a history entry: {}", err);
+
+				continue;
+			},
+		};
+
+		let insmedia = InsMedia {
+			uploader: entry.author.as_deref(),
+			duration_sec: entry.duration_sec(),
+			..InsMedia::new(
+				&entry.video_id,
+				"youtube",
+				entry.title.as_deref().unwrap_or(UNKNOWN_NONE_PROVIDED),
+			)
+		};
+
+		affected_rows += insert_insmedia(&insmedia, merge_to)?;
+		pgcb(ImportProgress::Increase(1, index));
+	}
+
+	pgcb(ImportProgress::Finished(affected_rows));
+
+	return Ok(());
+}
+
 /// Regex for removing known file extension from imported filenames
 /// for [import_ytdlr_json_archive]
 static REMOVE_KNOWN_FILEEXTENSION: Lazy<Regex> = Lazy::new(|| {
@@ -268,26 +481,61 @@ pub fn import_ytdl_archive<T: BufRead, S: FnMut(ImportProgress)>(
 	return Ok(());
 }
 
+diesel::sql_function! {
+	/// The SQL "coalesce" function, returning the first argument that is not "NULL"
+	fn coalesce<T: diesel::sql_types::SingleValue>(x: diesel::sql_types::Nullable<T>, y: diesel::sql_types::Nullable<T>) -> diesel::sql_types::Nullable<T>;
+}
+
 /// Helper function to have a unified insertion command for all imports or functions that like to use this method
+/// On conflict the title is updated, and the metadata (like uploader) is updated if the new entry has it
+///
+/// The provider is canonicalized before inserting (see [`InsMedia::canonicalized`])
 ///
 /// This function is also meant as a workaround to <https://github.com/diesel-rs/diesel/discussions/3115#discussioncomment-2509301> because bulk inserts with "on_conflict" in sqlite are not supported
 #[inline]
 pub fn insert_insmedia(input: &InsMedia, connection: &mut SqliteConnection) -> Result<usize, crate::Error> {
 	return diesel::insert_into(media_archive::table)
-		.values(input)
+		.values(input.canonicalized())
 		.on_conflict((media_archive::media_id, media_archive::provider))
 		.do_update()
-		.set(media_archive::title.eq(excluded(media_archive::title)))
+		.set((
+			media_archive::title.eq(excluded(media_archive::title)),
+			// keep the existing metadata, if the new entry does not have it (like when it was not downloaded with a ".info.json")
+			media_archive::uploader.eq(coalesce(excluded(media_archive::uploader), media_archive::uploader)),
+			media_archive::upload_date.eq(coalesce(
+				excluded(media_archive::upload_date),
+				media_archive::upload_date,
+			)),
+			media_archive::duration_sec.eq(coalesce(
+				excluded(media_archive::duration_sec),
+				media_archive::duration_sec,
+			)),
+			media_archive::webpage_url.eq(coalesce(
+				excluded(media_archive::webpage_url),
+				media_archive::webpage_url,
+			)),
+			media_archive::filesize_bytes.eq(coalesce(
+				excluded(media_archive::filesize_bytes),
+				media_archive::filesize_bytes,
+			)),
+			media_archive::content_hash.eq(coalesce(
+				excluded(media_archive::content_hash),
+				media_archive::content_hash,
+			)),
+			// inserting a media again restores it, if it was removed
+			media_archive::removed_at.eq(None::<NaiveDateTime>),
+		))
 		.execute(connection)
 		.map_err(|err| return crate::Error::from(err));
 }
 
 /// Helper function to have a unified insertion command for all imports or functions that like to use this method
 /// This function does NOT update on conflict and ignores such values
+/// The provider is canonicalized before inserting (see [`InsMedia::canonicalized`])
 #[inline]
 pub fn insert_insmedia_noupdate(input: &InsMedia, connection: &mut SqliteConnection) -> Result<usize, crate::Error> {
 	return diesel::insert_into(media_archive::table)
-		.values(input)
+		.values(input.canonicalized())
 		.on_conflict((media_archive::media_id, media_archive::provider))
 		.do_nothing()
 		.execute(connection)
@@ -406,6 +654,25 @@ mod test {
 			assert!(ret1.is_ok());
 			assert_eq!(Ok(ArchiveType::Unknown), ret1);
 		}
+
+		#[test]
+		fn test_detect_freetube() {
+			let string0 = "{\"videoId\":\"aaaaaaaaaaa\",\"title\":\"Some Title\"}\n{\"videoId\":\"bbbbbbbbbbb\"}\n";
+
+			assert_eq!(Ok(ArchiveType::FreeTube), detect_archive_type(&mut string0.as_bytes()));
+
+			// a YTDL-R Archive in a single line is still detected as JSON
+			let string1 = r#"{"version":"0.1.0","videos":[]}"#;
+
+			assert_eq!(Ok(ArchiveType::JSON), detect_archive_type(&mut string1.as_bytes()));
+		}
+
+		#[test]
+		fn test_detect_zip() {
+			let string0 = "PK\x03\x04somezipdata";
+
+			assert_eq!(Ok(ArchiveType::Zip), detect_archive_type(&mut string0.as_bytes()));
+		}
 	}
 
 	mod import_any_archive {
@@ -572,6 +839,66 @@ mod test {
 
 			assert_eq!(cmp_vec, found.iter().map(Video::from).collect::<Vec<Video>>());
 		}
+
+		#[test]
+		fn test_insert_canonicalizes_provider() {
+			let (mut connection0, _tempdir) = create_connection();
+
+			insert_insmedia(&InsMedia::new("someid", "youtube:tab", "sometitle"), &mut connection0)
+				.expect("Expected a successful insert");
+			// the same media from another extractor of the same family conflicts
+			assert_eq!(
+				0,
+				insert_insmedia_noupdate(&InsMedia::new("someid", "youtube", "othertitle"), &mut connection0)
+					.expect("Expected a successful insert")
+			);
+
+			let found = media_archive::dsl::media_archive
+				.load::<Media>(&mut connection0)
+				.expect("Expected a successfully query");
+
+			assert_eq!(1, found.len());
+			assert_eq!("youtube", found[0].provider);
+			assert_eq!(Some("youtube:tab".to_owned()), found[0].extractor);
+			assert_eq!("sometitle", found[0].title);
+		}
+
+		#[test]
+		fn test_insert_keeps_metadata() {
+			let (mut connection0, _tempdir) = create_connection();
+
+			insert_insmedia(
+				&InsMedia {
+					uploader: Some("Someone"),
+					duration_sec: Some(61),
+					filesize_bytes: Some(1024),
+					..InsMedia::new("someid", "youtube", "sometitle")
+				},
+				&mut connection0,
+			)
+			.expect("Expected a successful insert");
+			// a update without metadata should not remove the existing metadata
+			insert_insmedia(
+				&InsMedia {
+					upload_date: Some("2023-05-24"),
+					..InsMedia::new("someid", "youtube", "othertitle")
+				},
+				&mut connection0,
+			)
+			.expect("Expected a successful insert");
+
+			let found = media_archive::dsl::media_archive
+				.load::<Media>(&mut connection0)
+				.expect("Expected a successfully query");
+
+			assert_eq!(1, found.len());
+			assert_eq!("othertitle", found[0].title);
+			assert_eq!(Some("Someone".to_owned()), found[0].uploader);
+			assert_eq!(Some("2023-05-24".to_owned()), found[0].upload_date);
+			assert_eq!(Some(61), found[0].duration_sec);
+			assert_eq!(Some(1024), found[0].filesize_bytes);
+			assert_eq!(None, found[0].webpage_url);
+		}
 	}
 
 	mod import_ytdl_archive {
@@ -832,4 +1159,149 @@ mod test {
 			assert_eq!(cmp_vec, found.iter().map(Video::from).collect::<Vec<Video>>());
 		}
 	}
+	mod import_newpipe_database {
+		use super::*;
+
+		/// Test helper to create a Newpipe database with the tables used by the import in `temp_dir`
+		/// `streams` is a list of "(uid, service_id, url, title, duration)", `history` a list of "(stream_id, access_date)"
+		fn create_newpipe_database(
+			streams: &[(i32, i32, &str, &str, i32)],
+			history: &[(i32, i64)],
+			temp_dir: &Path,
+		) -> PathBuf {
+			let path = temp_dir.join("newpipe.db");
+			let mut connection =
+				SqliteConnection::establish(path.to_str().unwrap()).expect("Expected SQLite to successfully start");
+
+			diesel::sql_query("CREATE TABLE streams (uid INTEGER PRIMARY KEY, service_id INTEGER NOT NULL, url TEXT NOT NULL, title TEXT NOT NULL, duration INTEGER NOT NULL, uploader TEXT)")
+				.execute(&mut connection)
+				.expect("Expected table to be created");
+			diesel::sql_query("CREATE TABLE stream_history (stream_id INTEGER NOT NULL, access_date INTEGER NOT NULL, repeat_count INTEGER NOT NULL DEFAULT 1)")
+				.execute(&mut connection)
+				.expect("Expected table to be created");
+
+			for (uid, service_id, url, title, duration) in streams {
+				diesel::sql_query(format!(
+					"INSERT INTO streams (uid, service_id, url, title, duration, uploader) VALUES ({uid}, {service_id}, '{url}', '{title}', {duration}, 'Someone')"
+				))
+				.execute(&mut connection)
+				.expect("Expected stream to be inserted");
+			}
+
+			for (stream_id, access_date) in history {
+				diesel::sql_query(format!(
+					"INSERT INTO stream_history (stream_id, access_date) VALUES ({stream_id}, {access_date})"
+				))
+				.execute(&mut connection)
+				.expect("Expected history to be inserted");
+			}
+
+			return path;
+		}
+
+		#[test]
+		fn test_is_newpipe_database() {
+			let (_connection0, tempdir) = create_connection();
+			let newpipe_path = create_newpipe_database(&[], &[], tempdir.as_ref());
+
+			assert!(is_newpipe_database(&newpipe_path).unwrap());
+			assert!(!is_newpipe_database(&tempdir.as_ref().join("sqlite.db")).unwrap());
+		}
+
+		#[test]
+		fn test_any_to_newpipe() {
+			let (mut connection0, tempdir) = create_connection();
+			let pgcounter = RwLock::new(Vec::<ImportProgress>::new());
+
+			let newpipe_path = create_newpipe_database(
+				&[
+					(1, 0, "https://www.youtube.com/watch?v=aaaaaaaaaaa", "Title 1", 61),
+					(2, 1, "https://soundcloud.com/someone/sometrack", "Title 2", 120),
+					(3, 0, "https://www.youtube.com/watch?v=bbbbbbbbbbb", "Title 3", 0),
+					// not watched, like a stream from the subscription feed
+					(4, 0, "https://www.youtube.com/watch?v=ccccccccccc", "Title 4", 10),
+				],
+				&[(3, 100), (1, 200), (2, 300), (3, 400)],
+				tempdir.as_ref(),
+			);
+
+			import_any_archive(&newpipe_path, &mut connection0, callback_counter(&pgcounter))
+				.expect("Expected the import to be ok");
+
+			let found = media_archive::dsl::media_archive
+				.order(media_archive::_id.asc())
+				.load::<Media>(&mut connection0)
+				.expect("Expected a successfully query");
+
+			// ordered by the first watch
+			assert_eq!(
+				vec![
+					Video::new("bbbbbbbbbbb", Provider::from("youtube")).with_filename("Title 3"),
+					Video::new("aaaaaaaaaaa", Provider::from("youtube")).with_filename("Title 1"),
+				],
+				found.iter().map(Video::from).collect::<Vec<Video>>()
+			);
+			assert_eq!(None, found[0].duration_sec);
+			assert_eq!(Some(61), found[1].duration_sec);
+			assert_eq!(Some("Someone".to_owned()), found[1].uploader);
+			assert_eq!(
+				&vec![
+					ImportProgress::Starting,
+					ImportProgress::SizeHint(3),
+					ImportProgress::Increase(1, 0),
+					ImportProgress::Increase(1, 1),
+					ImportProgress::Finished(2)
+				],
+				pgcounter.read().expect("failed to read").deref()
+			);
+		}
+	}
+
+	mod import_freetube_history {
+		use super::*;
+
+		#[test]
+		fn test_any_to_freetube() {
+			let (mut connection0, tempdir) = create_connection();
+			let pgcounter = RwLock::new(Vec::<ImportProgress>::new());
+
+			let string0 = r#"{"videoId":"aaaaaaaaaaa","title":"Title 1","author":"Someone","lengthSeconds":61,"timeWatched":1,"_id":"aaaaaaaaaaa"}
+{"videoId":"bbbbbbbbbbb","title":"Title 2","lengthSeconds":"0:00","_id":"bbbbbbbbbbb"}
+{"$$deleted":true,"_id":"ccccccccccc"}
+{"videoId":"ddddddddddd","lengthSeconds":"120","_id":"ddddddddddd"}
+"#;
+			let input_data_path = create_input_data(string0, tempdir.as_ref());
+
+			import_any_archive(&input_data_path, &mut connection0, callback_counter(&pgcounter))
+				.expect("Expected the import to be ok");
+
+			let found = media_archive::dsl::media_archive
+				.order(media_archive::_id.asc())
+				.load::<Media>(&mut connection0)
+				.expect("Expected a successfully query");
+
+			assert_eq!(
+				vec![
+					Video::new("aaaaaaaaaaa", Provider::from("youtube")).with_filename("Title 1"),
+					Video::new("bbbbbbbbbbb", Provider::from("youtube")).with_filename("Title 2"),
+					Video::new("ddddddddddd", Provider::from("youtube")).with_filename(UNKNOWN_NONE_PROVIDED),
+				],
+				found.iter().map(Video::from).collect::<Vec<Video>>()
+			);
+			assert_eq!(Some("Someone".to_owned()), found[0].uploader);
+			assert_eq!(Some(61), found[0].duration_sec);
+			assert_eq!(None, found[1].duration_sec);
+			assert_eq!(Some(120), found[2].duration_sec);
+			assert_eq!(
+				&vec![
+					ImportProgress::Starting,
+					ImportProgress::Increase(1, 0),
+					ImportProgress::Increase(1, 1),
+					ImportProgress::Increase(1, 3),
+					ImportProgress::Finished(3)
+				],
+				pgcounter.read().expect("failed to read").deref()
+			);
+		}
+	}
 }
@@ -0,0 +1,92 @@
+//! Module for adding media to the Archive without downloading them (like `ytdlr archive ingest`)
+//!
+//! Useful to mark a whole playlist or channel as "already downloaded", the media can be listed with [`crate::main::diff::list_playlist`]
+
+use diesel::prelude::*;
+
+use super::import::{
+	insert_insmedia,
+	ImportProgress,
+};
+use crate::data::{
+	cache::media_info::MediaInfo,
+	sql_models::InsMedia,
+};
+
+/// Insert all `medias` into the Archive, media without a title are inserted with [`crate::data::UNKNOWN_NONE_PROVIDED`] as title
+/// Existing entries only get their title updated
+/// Returns the count of affected rows
+pub fn ingest_media<S: FnMut(ImportProgress)>(
+	medias: &[MediaInfo],
+	connection: &mut SqliteConnection,
+	mut pgcb: S,
+) -> Result<usize, crate::Error> {
+	pgcb(ImportProgress::Starting);
+	pgcb(ImportProgress::SizeHint(medias.len()));
+
+	let affected_rows = connection.transaction::<_, crate::Error, _>(|transaction| {
+		let mut affected_rows = 0usize;
+
+		for (index, media) in medias.iter().enumerate() {
+			affected_rows += insert_insmedia(&InsMedia::from(media), transaction)?;
+
+			pgcb(ImportProgress::Increase(1, index));
+		}
+
+		return Ok(affected_rows);
+	})?;
+
+	pgcb(ImportProgress::Finished(affected_rows));
+
+	return Ok(affected_rows);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		data::{
+			sql_models::Media,
+			sql_schema::media_archive,
+			UNKNOWN_NONE_PROVIDED,
+		},
+		main::test_utils::create_connection,
+	};
+
+	#[test]
+	fn test_ingest_media() {
+		let (mut connection, _tempdir) = create_connection();
+		let mut events = Vec::new();
+
+		let affected = ingest_media(
+			&[
+				MediaInfo::new("id1", "Youtube").with_title("Title 1"),
+				MediaInfo::new("id2", "Youtube"),
+			],
+			&mut connection,
+			|imp| return events.push(imp),
+		)
+		.unwrap();
+
+		assert_eq!(2, affected);
+		assert_eq!(
+			vec![
+				ImportProgress::Starting,
+				ImportProgress::SizeHint(2),
+				ImportProgress::Increase(1, 0),
+				ImportProgress::Increase(1, 1),
+				ImportProgress::Finished(2),
+			],
+			events
+		);
+
+		let found = media_archive::table
+			.order(media_archive::media_id.asc())
+			.load::<Media>(&mut connection)
+			.unwrap();
+		assert_eq!(2, found.len());
+		assert_eq!("youtube", found[0].provider);
+		assert_eq!("Title 1", found[0].title);
+		assert_eq!(UNKNOWN_NONE_PROVIDED, found[1].title);
+	}
+}
@@ -0,0 +1,145 @@
+//! Module for organizing archive entries with labels (like "music", "keep", "to-edit")
+
+use diesel::prelude::*;
+
+use crate::data::{
+	sql_models::InsLabel,
+	sql_schema::labels,
+};
+
+/// Normalize a label to the form it is stored as (trimmed and lowercase)
+/// Errors if the label is empty or contains whitespace or ","
+pub fn normalize_label(label: &str) -> Result<String, crate::Error> {
+	let label = label.trim().to_lowercase();
+
+	if label.is_empty() {
+		return Err(crate::Error::other("Label cannot be empty"));
+	}
+
+	if label.contains(|c: char| return c.is_whitespace() || c == ',') {
+		return Err(crate::Error::other(format!(
+			"Label cannot contain whitespace or \",\", found \"{label}\""
+		)));
+	}
+
+	return Ok(label);
+}
+
+/// Add all `add_labels` to all entries in `media_archive_ids`, already existing labels are ignored
+/// Returns the count of newly added labels
+pub fn add_labels<S: AsRef<str>>(
+	connection: &mut SqliteConnection,
+	media_archive_ids: &[i64],
+	add_labels: &[S],
+) -> Result<usize, crate::Error> {
+	let mut count = 0;
+
+	// done in a transaction, because sqlite does not support bulk inserts with "on_conflict"
+	connection.transaction::<_, crate::Error, _>(|connection| {
+		for id in media_archive_ids {
+			for label in add_labels {
+				count += diesel::insert_into(labels::table)
+					.values(InsLabel::new(*id, label.as_ref()))
+					.on_conflict_do_nothing()
+					.execute(connection)?;
+			}
+		}
+
+		return Ok(());
+	})?;
+
+	return Ok(count);
+}
+
+/// Remove all `remove_labels` from all entries in `media_archive_ids`
+/// Returns the count of removed labels
+pub fn remove_labels<S: AsRef<str>>(
+	connection: &mut SqliteConnection,
+	media_archive_ids: &[i64],
+	remove_labels: &[S],
+) -> Result<usize, crate::Error> {
+	let remove_labels: Vec<&str> = remove_labels.iter().map(|v| return v.as_ref()).collect();
+
+	return diesel::delete(
+		labels::table
+			.filter(labels::media_archive_id.eq_any(media_archive_ids))
+			.filter(labels::label.eq_any(remove_labels)),
+	)
+	.execute(connection)
+	.map_err(|err| return crate::Error::from(err));
+}
+
+/// Get all labels of the entry `media_archive_id`, sorted by name
+pub fn get_labels(connection: &mut SqliteConnection, media_archive_id: i64) -> Result<Vec<String>, crate::Error> {
+	return labels::table
+		.filter(labels::media_archive_id.eq(media_archive_id))
+		.select(labels::label)
+		.order(labels::label.asc())
+		.load(connection)
+		.map_err(|err| return crate::Error::from(err));
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::main::test_utils::{
+		create_connection,
+		insert_media,
+	};
+
+	mod normalize_label {
+		use super::*;
+
+		#[test]
+		fn test_basic() {
+			assert_eq!("music", normalize_label("music").unwrap());
+			assert_eq!("to-edit", normalize_label("  To-Edit ").unwrap());
+		}
+
+		#[test]
+		fn test_invalid() {
+			assert!(normalize_label("").is_err());
+			assert!(normalize_label("   ").is_err());
+			assert!(normalize_label("two words").is_err());
+			assert!(normalize_label("a,b").is_err());
+		}
+	}
+
+	mod add_remove_labels {
+		use super::*;
+
+		#[test]
+		fn test_basic() {
+			let (mut connection, _tempdir) = create_connection();
+
+			let id1 = insert_media(&mut connection, "id1", "youtube", "id1");
+			let id2 = insert_media(&mut connection, "id2", "youtube", "id2");
+
+			assert_eq!(
+				4,
+				add_labels(&mut connection, &[id1, id2], &["music", "keep"]).expect("Expected add to be ok")
+			);
+			// adding already existing labels should not add anything
+			assert_eq!(
+				0,
+				add_labels(&mut connection, &[id1], &["music"]).expect("Expected add to be ok")
+			);
+
+			assert_eq!(
+				vec!["keep".to_owned(), "music".to_owned()],
+				get_labels(&mut connection, id1).unwrap()
+			);
+
+			assert_eq!(
+				1,
+				remove_labels(&mut connection, &[id2], &["keep", "not-existing"]).expect("Expected remove to be ok")
+			);
+
+			assert_eq!(
+				vec!["keep".to_owned(), "music".to_owned()],
+				get_labels(&mut connection, id1).unwrap()
+			);
+			assert_eq!(vec!["music".to_owned()], get_labels(&mut connection, id2).unwrap());
+		}
+	}
+}
@@ -0,0 +1,354 @@
+//! Module for archive maintenance, like merging duplicate entries, canonicalizing providers and optimizing the database
+
+// the "_id" field of "Media" is named after the database column
+#![allow(clippy::used_underscore_binding)]
+
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+
+use crate::{
+	data::{
+		cache::media_provider::normalize_provider,
+		sql_models::{
+			InsMedia,
+			Media,
+		},
+		sql_schema::{
+			fingerprints,
+			labels,
+			media_archive,
+		},
+		UNKNOWN_NONE_PROVIDED,
+	},
+	main::archive::import::ImportProgress,
+};
+
+/// Get the key media are considered duplicates by, which is the canonical (or trimmed and lowercased) provider and the trimmed media_id
+/// The media_id is not lowercased, because some providers have case-sensitive ids (like youtube)
+fn duplicate_key(media: &Media) -> (String, String) {
+	return (normalize_provider(&media.provider), media.media_id.trim().to_owned());
+}
+
+/// Find all groups of duplicate entries (see [`duplicate_key`]), each sorted by "_id"
+/// Groups are sorted by the "_id" of their first entry
+fn find_duplicates(connection: &mut SqliteConnection) -> Result<Vec<Vec<Media>>, crate::Error> {
+	let all = media_archive::table
+		.order(media_archive::_id.asc())
+		.load::<Media>(connection)?;
+
+	let mut groups: HashMap<(String, String), Vec<Media>> = HashMap::new();
+
+	for media in all {
+		groups.entry(duplicate_key(&media)).or_default().push(media);
+	}
+
+	let mut duplicates: Vec<Vec<Media>> = groups.into_values().filter(|v| return v.len() > 1).collect();
+	duplicates.sort_by_key(|v| return v[0]._id);
+
+	return Ok(duplicates);
+}
+
+/// Get the index of the entry to keep in `group`
+/// Prefers entries with a real title over [`UNKNOWN_NONE_PROVIDED`], then the oldest entry
+fn keeper_index(group: &[Media]) -> usize {
+	return group
+		.iter()
+		.position(|v| return v.title != UNKNOWN_NONE_PROVIDED)
+		.unwrap_or(0);
+}
+
+/// Merge a single group of duplicates into the entry chosen by [`keeper_index`]
+/// Labels and fingerprints of the removed entries are moved to the kept entry (if it does not already have one)
+/// Returns the count of removed entries
+fn merge_group(connection: &mut SqliteConnection, group: &[Media]) -> Result<usize, crate::Error> {
+	let keep = &group[keeper_index(group)];
+	let remove_ids: Vec<i64> = group
+		.iter()
+		.filter(|v| return v._id != keep._id)
+		.map(|v| return v._id)
+		.collect();
+
+	// move labels, ignoring labels the kept entry already has
+	let moved_labels = labels::table
+		.filter(labels::media_archive_id.eq_any(&remove_ids))
+		.select(labels::label)
+		.load::<String>(connection)?;
+	crate::main::archive::labels::add_labels(connection, &[keep._id], &moved_labels)?;
+	diesel::delete(labels::table.filter(labels::media_archive_id.eq_any(&remove_ids))).execute(connection)?;
+
+	// move the first fingerprint found, if the kept entry does not have one
+	let has_fingerprint = fingerprints::table
+		.filter(fingerprints::media_archive_id.eq(keep._id))
+		.count()
+		.get_result::<i64>(connection)?
+		> 0;
+	if !has_fingerprint {
+		let moved_fingerprint = fingerprints::table
+			.filter(fingerprints::media_archive_id.eq_any(&remove_ids))
+			.order(fingerprints::media_archive_id.asc())
+			.select(fingerprints::media_archive_id)
+			.first::<i64>(connection)
+			.optional()?;
+
+		if let Some(from_id) = moved_fingerprint {
+			diesel::update(fingerprints::table.filter(fingerprints::media_archive_id.eq(from_id)))
+				.set(fingerprints::media_archive_id.eq(keep._id))
+				.execute(connection)?;
+		}
+	}
+	diesel::delete(fingerprints::table.filter(fingerprints::media_archive_id.eq_any(&remove_ids)))
+		.execute(connection)?;
+
+	let removed =
+		diesel::delete(media_archive::table.filter(media_archive::_id.eq_any(&remove_ids))).execute(connection)?;
+
+	// normalize the kept entry, now that no other entry can conflict with it
+	let (provider, media_id) = duplicate_key(keep);
+	let extractor = InsMedia::from(keep).canonicalized().extractor;
+	diesel::update(media_archive::table.filter(media_archive::_id.eq(keep._id)))
+		.set((
+			media_archive::provider.eq(provider),
+			media_archive::media_id.eq(media_id),
+			media_archive::extractor.eq(extractor),
+		))
+		.execute(connection)?;
+
+	return Ok(removed);
+}
+
+/// Merge all duplicate entries (same provider and media_id, ignoring case of the provider and surrounding whitespace)
+/// which may have been added by imports of older versions
+/// Providers of the same extractor family (like "youtube" and "youtube:tab") are considered the same, see [`canonical_provider`]
+///
+/// Calls `pgcb` with [`ImportProgress`], where the size hint and increases are the duplicate groups
+/// and [`ImportProgress::Finished`] has the count of removed entries
+///
+/// Returns the count of removed entries
+pub fn merge_duplicates<S: FnMut(ImportProgress)>(
+	connection: &mut SqliteConnection,
+	mut pgcb: S,
+) -> Result<usize, crate::Error> {
+	pgcb(ImportProgress::Starting);
+
+	let removed = connection.transaction::<_, crate::Error, _>(|connection| {
+		let duplicates = find_duplicates(connection)?;
+		pgcb(ImportProgress::SizeHint(duplicates.len()));
+
+		let mut removed = 0;
+
+		for (index, group) in duplicates.iter().enumerate() {
+			removed += merge_group(connection, group)?;
+			pgcb(ImportProgress::Increase(1, index));
+		}
+
+		return Ok(removed);
+	})?;
+
+	pgcb(ImportProgress::Finished(removed));
+
+	return Ok(removed);
+}
+
+/// Replace the provider of all entries which are part of a known extractor family with the canonical provider (see [`canonical_provider`]),
+/// keeping the original provider as the extractor, which may have been added by older versions
+/// Entries for which a entry with the canonical provider already exists are skipped, those are merged by [`merge_duplicates`]
+///
+/// Returns the count of changed entries
+pub fn canonicalize_providers(connection: &mut SqliteConnection) -> Result<usize, crate::Error> {
+	return connection.transaction::<_, crate::Error, _>(|connection| {
+		let all = media_archive::table
+			.order(media_archive::_id.asc())
+			.load::<Media>(connection)?;
+
+		let mut changed = 0;
+
+		for media in &all {
+			let canonicalized = InsMedia::from(media).canonicalized();
+
+			if canonicalized.provider == media.provider {
+				continue;
+			}
+
+			let conflicts = media_archive::table
+				.filter(media_archive::media_id.eq(&media.media_id))
+				.filter(media_archive::provider.eq(canonicalized.provider))
+				.count()
+				.get_result::<i64>(connection)?
+				> 0;
+			if conflicts {
+				continue;
+			}
+
+			changed += diesel::update(media_archive::table.filter(media_archive::_id.eq(media._id)))
+				.set((
+					media_archive::provider.eq(canonicalized.provider),
+					media_archive::extractor.eq(canonicalized.extractor),
+				))
+				.execute(connection)?;
+		}
+
+		return Ok(changed);
+	});
+}
+
+/// Rebuild the database file to reclaim unused space ("VACUUM") and update the query planner statistics ("ANALYZE")
+/// Cannot be run inside a transaction
+pub fn optimize(connection: &mut SqliteConnection) -> Result<(), crate::Error> {
+	diesel::sql_query("VACUUM").execute(connection)?;
+	diesel::sql_query("ANALYZE").execute(connection)?;
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		main::{
+			archive::{
+				fingerprints::store_fingerprint,
+				labels::{
+					add_labels,
+					get_labels,
+				},
+			},
+			test_utils::{
+				create_connection,
+				insert_media_raw,
+			},
+		},
+		spawn::fpcalc::Fingerprint,
+	};
+
+	#[test]
+	fn test_merge_duplicates() {
+		let (mut connection, _tempdir) = create_connection();
+
+		let unknown = insert_media_raw(&mut connection, "id1", "Youtube", UNKNOWN_NONE_PROVIDED);
+		let titled = insert_media_raw(&mut connection, "id1", "youtube", "Some Title");
+		let spaced = insert_media_raw(&mut connection, " id1", "youtube ", UNKNOWN_NONE_PROVIDED);
+		// not a duplicate, because media ids are case-sensitive
+		insert_media_raw(&mut connection, "ID1", "youtube", "Other Title");
+		insert_media_raw(&mut connection, "id2", "soundcloud", "Another Title");
+
+		add_labels(&mut connection, &[unknown], &["music"]).unwrap();
+		add_labels(&mut connection, &[titled], &["keep"]).unwrap();
+		add_labels(&mut connection, &[spaced], &["music"]).unwrap();
+		store_fingerprint(
+			&mut connection,
+			unknown,
+			&Fingerprint {
+				duration:    10,
+				fingerprint: vec![1, 2, 3],
+			},
+		)
+		.unwrap();
+
+		let mut events = Vec::new();
+		assert_eq!(2, merge_duplicates(&mut connection, |imp| events.push(imp)).unwrap());
+		assert_eq!(
+			vec![
+				ImportProgress::Starting,
+				ImportProgress::SizeHint(1),
+				ImportProgress::Increase(1, 0),
+				ImportProgress::Finished(2),
+			],
+			events
+		);
+
+		let all = media_archive::table
+			.order(media_archive::_id.asc())
+			.load::<Media>(&mut connection)
+			.unwrap();
+		assert_eq!(3, all.len());
+		assert_eq!(titled, all[0]._id);
+		assert_eq!("Some Title", all[0].title);
+		assert_eq!(
+			vec!["keep".to_owned(), "music".to_owned()],
+			get_labels(&mut connection, titled).unwrap()
+		);
+		assert_eq!(
+			vec![titled],
+			fingerprints::table
+				.select(fingerprints::media_archive_id)
+				.load::<i64>(&mut connection)
+				.unwrap()
+		);
+
+		// running again should not find anything
+		assert_eq!(0, merge_duplicates(&mut connection, |_| {}).unwrap());
+	}
+
+	#[test]
+	fn test_merge_normalizes_keeper() {
+		let (mut connection, _tempdir) = create_connection();
+
+		insert_media_raw(&mut connection, "id1", "Youtube", "Some Title");
+		insert_media_raw(&mut connection, "id1", "youtube", UNKNOWN_NONE_PROVIDED);
+
+		assert_eq!(1, merge_duplicates(&mut connection, |_| {}).unwrap());
+
+		let all = media_archive::table.load::<Media>(&mut connection).unwrap();
+		assert_eq!(1, all.len());
+		assert_eq!("youtube", all[0].provider);
+		assert_eq!("Some Title", all[0].title);
+	}
+
+	#[test]
+	fn test_merge_extractor_family() {
+		let (mut connection, _tempdir) = create_connection();
+
+		insert_media_raw(&mut connection, "id1", "youtube:tab", "Some Title");
+		insert_media_raw(&mut connection, "id1", "youtube", UNKNOWN_NONE_PROVIDED);
+
+		assert_eq!(1, merge_duplicates(&mut connection, |_| {}).unwrap());
+
+		let all = media_archive::table.load::<Media>(&mut connection).unwrap();
+		assert_eq!(1, all.len());
+		assert_eq!("youtube", all[0].provider);
+		assert_eq!(Some("youtube:tab".to_owned()), all[0].extractor);
+		assert_eq!("Some Title", all[0].title);
+	}
+
+	#[test]
+	fn test_canonicalize_providers() {
+		let (mut connection, _tempdir) = create_connection();
+
+		let tab = insert_media_raw(&mut connection, "id1", "youtube:tab", "Title A");
+		let music = insert_media_raw(&mut connection, "id2", "YoutubeMusic", "Title B");
+		// skipped, because "youtube id3" already exists
+		insert_media_raw(&mut connection, "id3", "youtube:tab", "Title C");
+		insert_media_raw(&mut connection, "id3", "youtube", "Title C");
+		insert_media_raw(&mut connection, "id4", "soundcloud", "Title D");
+
+		assert_eq!(2, canonicalize_providers(&mut connection).unwrap());
+
+		let changed = media_archive::table
+			.filter(media_archive::_id.eq_any([tab, music]))
+			.order(media_archive::_id.asc())
+			.select((media_archive::provider, media_archive::extractor))
+			.load::<(String, Option<String>)>(&mut connection)
+			.unwrap();
+		assert_eq!(
+			vec![
+				("youtube".to_owned(), Some("youtube:tab".to_owned())),
+				("youtube".to_owned(), Some("YoutubeMusic".to_owned())),
+			],
+			changed
+		);
+
+		// the remaining entry is merged by "merge_duplicates"
+		assert_eq!(1, merge_duplicates(&mut connection, |_| {}).unwrap());
+		assert_eq!(0, canonicalize_providers(&mut connection).unwrap());
+	}
+
+	#[test]
+	fn test_optimize() {
+		let (mut connection, _tempdir) = create_connection();
+
+		insert_media_raw(&mut connection, "id1", "youtube", "Some Title");
+
+		assert!(optimize(&mut connection).is_ok());
+	}
+}
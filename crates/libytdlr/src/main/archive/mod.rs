@@ -1,3 +1,11 @@
 //! Module for all Archive related functionality (like `ytldr archive ...`)
 
+pub mod fingerprints;
+pub mod hashes;
 pub mod import;
+pub mod ingest;
+pub mod labels;
+pub mod maintenance;
+pub mod remove;
+pub mod search;
+pub mod stats;
@@ -0,0 +1,233 @@
+//! Module for removing entries from the Archive and for the blacklist (like `ytdlr archive remove`)
+//!
+//! Blacklisted media is added to the generated youtube-dl archive, so that youtube-dl skips it like archived media
+// the "_id" field of "Media" is named after the database column
+#![allow(clippy::used_underscore_binding)]
+
+use diesel::prelude::*;
+
+use crate::data::{
+	cache::media_provider::normalize_provider,
+	sql_models::{
+		Blacklisted,
+		InsBlacklisted,
+		Media,
+	},
+	sql_schema::{
+		blacklist,
+		fingerprints,
+		labels,
+		media_archive,
+	},
+};
+
+/// Find all Archive entries with `media_id` whose provider is the same as `provider` (see [`normalize_provider`])
+pub fn find_entries(
+	connection: &mut SqliteConnection,
+	provider: &str,
+	media_id: &str,
+) -> Result<Vec<Media>, crate::Error> {
+	let provider = normalize_provider(provider);
+
+	// the provider is compared after loading, because the archive may contain non-normalized providers
+	return Ok(media_archive::table
+		.filter(media_archive::media_id.eq(media_id.trim()))
+		.order(media_archive::_id.asc())
+		.load::<Media>(connection)?
+		.into_iter()
+		.filter(|v| return normalize_provider(&v.provider) == provider)
+		.collect());
+}
+
+/// Mark all entries in `media_archive_ids` as removed, but keep them as tombstones
+/// Tombstones are still added to the generated youtube-dl archive, so that syncs do not download them again, but are not found by searches
+/// Returns the count of newly removed entries, entries which already are tombstones are ignored
+pub fn tombstone_entries(connection: &mut SqliteConnection, media_archive_ids: &[i64]) -> Result<usize, crate::Error> {
+	return Ok(diesel::update(
+		media_archive::table
+			.filter(media_archive::_id.eq_any(media_archive_ids))
+			.filter(media_archive::removed_at.is_null()),
+	)
+	.set(media_archive::removed_at.eq(diesel::dsl::now))
+	.execute(connection)?);
+}
+
+/// Remove all entries in `media_archive_ids` from the Archive, including their labels and fingerprints
+/// Unlike [`tombstone_entries`], the entries are completely deleted
+/// Returns the count of removed entries
+pub fn remove_entries(connection: &mut SqliteConnection, media_archive_ids: &[i64]) -> Result<usize, crate::Error> {
+	return connection.transaction::<_, crate::Error, _>(|connection| {
+		diesel::delete(labels::table.filter(labels::media_archive_id.eq_any(media_archive_ids))).execute(connection)?;
+		diesel::delete(fingerprints::table.filter(fingerprints::media_archive_id.eq_any(media_archive_ids)))
+			.execute(connection)?;
+
+		return Ok(
+			diesel::delete(media_archive::table.filter(media_archive::_id.eq_any(media_archive_ids)))
+				.execute(connection)?,
+		);
+	});
+}
+
+/// Add all `entries` as "(provider, media_id)" to the blacklist, already blacklisted entries are ignored
+/// Returns the count of newly blacklisted entries
+pub fn add_to_blacklist<P: AsRef<str>, I: AsRef<str>>(
+	connection: &mut SqliteConnection,
+	entries: &[(P, I)],
+) -> Result<usize, crate::Error> {
+	let mut count = 0;
+
+	// done in a transaction, because sqlite does not support bulk inserts with "on_conflict"
+	connection.transaction::<_, crate::Error, _>(|connection| {
+		for (provider, media_id) in entries {
+			let provider = normalize_provider(provider.as_ref());
+
+			count += diesel::insert_into(blacklist::table)
+				.values(InsBlacklisted::new(media_id.as_ref().trim(), &provider))
+				.on_conflict_do_nothing()
+				.execute(connection)?;
+		}
+
+		return Ok(());
+	})?;
+
+	return Ok(count);
+}
+
+/// Get all blacklisted entries, oldest first
+pub fn get_blacklist(connection: &mut SqliteConnection) -> Result<Vec<Blacklisted>, crate::Error> {
+	return blacklist::table
+		.order((
+			blacklist::inserted_at.asc(),
+			blacklist::provider.asc(),
+			blacklist::media_id.asc(),
+		))
+		.load(connection)
+		.map_err(|err| return crate::Error::from(err));
+}
+
+/// Get whether `media_id` is blacklisted
+/// If `provider` is [`None`], any provider matches (like for youtube-dl lines that do not contain the provider)
+pub fn is_blacklisted(
+	connection: &mut SqliteConnection,
+	provider: Option<&str>,
+	media_id: &str,
+) -> Result<bool, crate::Error> {
+	let mut query = blacklist::table
+		.filter(blacklist::media_id.eq(media_id.trim()))
+		.into_boxed();
+
+	if let Some(provider) = provider {
+		query = query.filter(blacklist::provider.eq(normalize_provider(provider)));
+	}
+
+	return Ok(diesel::select(diesel::dsl::exists(query)).get_result(connection)?);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		data::sql_models::{
+			InsFingerprint,
+			InsMedia,
+		},
+		main::{
+			archive::{
+				import::insert_insmedia,
+				labels::{
+					add_labels,
+					get_labels,
+				},
+			},
+			test_utils::create_connection,
+		},
+	};
+
+	#[test]
+	fn test_find_and_remove_entries() {
+		let (mut connection, _tempdir) = create_connection();
+
+		insert_insmedia(&InsMedia::new("id1", "youtube", "Title 1"), &mut connection).unwrap();
+		insert_insmedia(&InsMedia::new("id2", "youtube", "Title 2"), &mut connection).unwrap();
+		insert_insmedia(&InsMedia::new("id1", "soundcloud", "Title 3"), &mut connection).unwrap();
+
+		let found = find_entries(&mut connection, "Youtube:Tab", "id1").unwrap();
+		assert_eq!(1, found.len());
+		assert_eq!("Title 1", found[0].title);
+		assert!(find_entries(&mut connection, "bandcamp", "id1").unwrap().is_empty());
+
+		let id = found[0]._id;
+		add_labels(&mut connection, &[id], &["music"]).unwrap();
+		diesel::insert_into(fingerprints::table)
+			.values(InsFingerprint::new(id, 10, "1,2,3"))
+			.execute(&mut connection)
+			.unwrap();
+
+		assert_eq!(1, remove_entries(&mut connection, &[id]).unwrap());
+
+		assert!(find_entries(&mut connection, "youtube", "id1").unwrap().is_empty());
+		assert!(get_labels(&mut connection, id).unwrap().is_empty());
+		assert_eq!(
+			0,
+			fingerprints::table.count().get_result::<i64>(&mut connection).unwrap()
+		);
+		assert_eq!(
+			2,
+			media_archive::table.count().get_result::<i64>(&mut connection).unwrap()
+		);
+	}
+
+	#[test]
+	fn test_tombstone_entries() {
+		let (mut connection, _tempdir) = create_connection();
+
+		insert_insmedia(&InsMedia::new("id1", "youtube", "Title 1"), &mut connection).unwrap();
+		insert_insmedia(&InsMedia::new("id2", "youtube", "Title 2"), &mut connection).unwrap();
+
+		let id = find_entries(&mut connection, "youtube", "id1").unwrap()[0]._id;
+		assert_eq!(1, tombstone_entries(&mut connection, &[id]).unwrap());
+		// already removed entries are not counted again
+		assert_eq!(0, tombstone_entries(&mut connection, &[id]).unwrap());
+
+		// the tombstone is kept, so that it can still be found and purged
+		let found = find_entries(&mut connection, "youtube", "id1").unwrap();
+		assert_eq!(1, found.len());
+		assert!(found[0].removed_at.is_some());
+		assert!(find_entries(&mut connection, "youtube", "id2").unwrap()[0]
+			.removed_at
+			.is_none());
+
+		// inserting the media again restores it
+		insert_insmedia(&InsMedia::new("id1", "youtube", "Title 1"), &mut connection).unwrap();
+		assert!(find_entries(&mut connection, "youtube", "id1").unwrap()[0]
+			.removed_at
+			.is_none());
+
+		assert_eq!(1, tombstone_entries(&mut connection, &[id]).unwrap());
+		assert_eq!(1, remove_entries(&mut connection, &[id]).unwrap());
+		assert!(find_entries(&mut connection, "youtube", "id1").unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_blacklist() {
+		let (mut connection, _tempdir) = create_connection();
+
+		assert_eq!(
+			2,
+			add_to_blacklist(&mut connection, &[("Youtube", "id1"), ("soundcloud", "id2")]).unwrap()
+		);
+		// already blacklisted entries are ignored
+		assert_eq!(0, add_to_blacklist(&mut connection, &[("youtube:tab", "id1")]).unwrap());
+
+		assert!(is_blacklisted(&mut connection, Some("youtube"), "id1").unwrap());
+		assert!(is_blacklisted(&mut connection, None, "id2").unwrap());
+		assert!(!is_blacklisted(&mut connection, Some("soundcloud"), "id1").unwrap());
+		assert!(!is_blacklisted(&mut connection, None, "id3").unwrap());
+
+		let blacklist = get_blacklist(&mut connection).unwrap();
+		assert_eq!(2, blacklist.len());
+		assert!(blacklist
+			.iter()
+			.any(|v| return v.provider == "youtube" && v.media_id == "id1"));
+	}
+}
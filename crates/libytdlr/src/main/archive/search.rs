@@ -0,0 +1,600 @@
+//! Module for searching the Archive with a typed query builder (like `ytdlr archive search`)
+
+use std::{
+	fmt::Display,
+	str::FromStr,
+};
+
+use diesel::{
+	prelude::*,
+	sqlite::Sqlite,
+};
+
+use crate::data::{
+	sql_models::Media,
+	sql_schema::{
+		labels,
+		media_archive,
+	},
+};
+
+/// The Archive columns that can be searched and sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchColumn {
+	/// For the SQL column "provider"
+	Provider,
+	/// For the SQL column "media_id"
+	MediaId,
+	/// For the SQL column "title"
+	Title,
+	/// For the SQL column "inserted_at"
+	InsertedAt,
+}
+
+impl Display for SearchColumn {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return write!(
+			f,
+			"{}",
+			match *self {
+				SearchColumn::Provider => "Provider",
+				SearchColumn::MediaId => "MediaId",
+				SearchColumn::InsertedAt => "InsertedAt",
+				SearchColumn::Title => "Title",
+			}
+		);
+	}
+}
+
+impl FromStr for SearchColumn {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		return Ok(match s.to_lowercase().as_str() {
+			"provider" => Self::Provider,
+			"mediaid"
+			// may be confused with the row-id
+			| "id" => Self::MediaId,
+			"insertedat"
+			| "inserted" => Self::InsertedAt,
+			"title" => Self::Title,
+			_ => return Err(crate::Error::other(format!("Unknown column \"{}\"", s))),
+		});
+	}
+}
+
+/// Comparison operators for date columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateOperator {
+	/// Equal to, "="
+	#[default]
+	Eq,
+	/// Less than, "<"
+	Lt,
+	/// Less than or equal to, "<="
+	Le,
+	/// Greater than, ">"
+	Gt,
+	/// Greater than or equal to, ">="
+	Ge,
+}
+
+impl DateOperator {
+	/// Split a operator prefix (like ">=") from `input`
+	/// Returns [`DateOperator::Eq`] and `input` unchanged if there is no operator prefix
+	#[must_use]
+	pub fn split_from(input: &str) -> (Self, &str) {
+		// the 2-character operators need to be checked first
+		for (prefix, op) in [
+			(">=", DateOperator::Ge),
+			("<=", DateOperator::Le),
+			("<", DateOperator::Lt),
+			(">", DateOperator::Gt),
+			("=", DateOperator::Eq),
+		] {
+			if let Some(rest) = input.strip_prefix(prefix) {
+				return (op, rest);
+			}
+		}
+
+		return (DateOperator::Eq, input);
+	}
+}
+
+/// The direction to sort results in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortDirection {
+	/// Sort ascending (oldest / lowest first)
+	#[default]
+	Asc,
+	/// Sort descending (newest / highest first)
+	Desc,
+}
+
+/// A single column filter of a [`SearchQuery`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchFilter {
+	/// The column starts with the value (SQL "LIKE value%"), for [`SearchColumn::InsertedAt`] this is the same as [`DateOperator::Eq`]
+	StartsWith(SearchColumn, String),
+	/// Compare the "inserted_at" column with the value (like "2023-05")
+	InsertedAt(DateOperator, String),
+	/// The column contains all words of the value in order, with anything between them (see [`fuzzy_like_pattern`])
+	/// For [`SearchColumn::InsertedAt`] this is the same as [`DateOperator::Eq`]
+	Fuzzy(SearchColumn, String),
+	/// The row-id ("_id") is any of the values, like from [`crate::main::check_archive::find_file_entries`]
+	RowIds(Vec<i64>),
+}
+
+impl SearchFilter {
+	/// Create a filter for `column` from the user input `value`
+	/// For [`SearchColumn::InsertedAt`] the value may be prefixed with a [`DateOperator`], like ">=2023-05"
+	#[must_use]
+	pub fn parse(column: SearchColumn, value: &str) -> Self {
+		if column == SearchColumn::InsertedAt {
+			let (op, value) = DateOperator::split_from(value);
+
+			return Self::InsertedAt(op, value.to_owned());
+		}
+
+		return Self::StartsWith(column, value.to_owned());
+	}
+
+	/// Create a fuzzy filter for `column` from the user input `value`
+	/// For [`SearchColumn::InsertedAt`] this is the same as [`SearchFilter::parse`], because dates cannot be fuzzy matched
+	#[must_use]
+	pub fn parse_fuzzy(column: SearchColumn, value: &str) -> Self {
+		if column == SearchColumn::InsertedAt {
+			return Self::parse(column, value);
+		}
+
+		return Self::Fuzzy(column, value.to_owned());
+	}
+}
+
+/// Get the SQL "LIKE" pattern for fuzzy matching `value`, where all words have to be present in order with anything between them
+/// Like "some title" -> "%some%title%"
+#[must_use]
+pub fn fuzzy_like_pattern(value: &str) -> String {
+	let mut pattern = String::from("%");
+
+	for word in value.split_whitespace() {
+		pattern.push_str(word);
+		pattern.push('%');
+	}
+
+	return pattern;
+}
+
+/// Get whether `haystack` fuzzy matches `value`, the same way as [`fuzzy_like_pattern`] but case-insensitive for all characters
+#[must_use]
+pub fn fuzzy_matches(haystack: &str, value: &str) -> bool {
+	let haystack = haystack.to_lowercase();
+	let mut rest = haystack.as_str();
+
+	for word in value.split_whitespace() {
+		let word = word.to_lowercase();
+
+		match rest.find(&word) {
+			Some(index) => rest = &rest[index + word.len()..],
+			None => return false,
+		}
+	}
+
+	return true;
+}
+
+/// Builder for searching the Archive
+/// Column filters are combined with "OR", labels are combined with "AND" (with each other and with the column filters)
+/// By default results are sorted by insertion order (row-id) ascending, without limit and offset
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchQuery {
+	/// The column filters, any of them has to match
+	filters:            Vec<SearchFilter>,
+	/// The labels a entry needs to have, all of them have to match
+	labels:             Vec<String>,
+	/// The column and direction to sort by, [`None`] to sort by row-id
+	sort:               Option<(SearchColumn, SortDirection)>,
+	/// The maximal amount of results
+	limit:              Option<i64>,
+	/// The amount of results to skip
+	offset:             Option<i64>,
+	/// Also return removed entries which are kept as tombstones (see [`crate::main::archive::remove::tombstone_entries`])
+	include_tombstones: bool,
+}
+
+impl SearchQuery {
+	/// Create a new empty query, which matches all entries
+	#[must_use]
+	pub fn new() -> Self {
+		return Self::default();
+	}
+
+	/// Add a filter for `column` from the user input `value`, see [`SearchFilter::parse`]
+	#[must_use]
+	pub fn filter(self, column: SearchColumn, value: &str) -> Self {
+		return self.with_filter(SearchFilter::parse(column, value));
+	}
+
+	/// Add a fuzzy filter for `column` from the user input `value`, see [`SearchFilter::parse_fuzzy`]
+	#[must_use]
+	pub fn fuzzy_filter(self, column: SearchColumn, value: &str) -> Self {
+		return self.with_filter(SearchFilter::parse_fuzzy(column, value));
+	}
+
+	/// Add a already built filter
+	#[must_use]
+	pub fn with_filter(mut self, filter: SearchFilter) -> Self {
+		self.filters.push(filter);
+
+		return self;
+	}
+
+	/// Add a label all results need to have
+	/// The label is expected to already be normalized, see [`crate::main::archive::labels::normalize_label`]
+	#[must_use]
+	pub fn label<S: Into<String>>(mut self, label: S) -> Self {
+		self.labels.push(label.into());
+
+		return self;
+	}
+
+	/// Sort the results by `column` in `direction`
+	#[must_use]
+	pub fn sort(mut self, column: SearchColumn, direction: SortDirection) -> Self {
+		self.sort = Some((column, direction));
+
+		return self;
+	}
+
+	/// Set the maximal amount of results
+	#[must_use]
+	pub fn limit(mut self, limit: i64) -> Self {
+		self.limit = Some(limit);
+
+		return self;
+	}
+
+	/// Set the amount of results to skip
+	#[must_use]
+	pub fn offset(mut self, offset: i64) -> Self {
+		self.offset = Some(offset);
+
+		return self;
+	}
+
+	/// Set whether removed entries which are kept as tombstones are also returned, by default they are not
+	#[must_use]
+	pub fn include_tombstones(mut self, include: bool) -> Self {
+		self.include_tombstones = include;
+
+		return self;
+	}
+
+	/// Build the diesel query, for cases where [`Self::load`] is not enough (like only selecting the row-id)
+	#[must_use]
+	pub fn build(&self) -> media_archive::BoxedQuery<'_, Sqlite> {
+		let mut query = media_archive::table.into_boxed();
+
+		for filter in &self.filters {
+			query = match filter {
+				SearchFilter::StartsWith(column, value) => {
+					let like = format!("{value}%");
+
+					match column {
+						SearchColumn::Provider => query.or_filter(media_archive::provider.like(like)),
+						SearchColumn::MediaId => query.or_filter(media_archive::media_id.like(like)),
+						SearchColumn::Title => query.or_filter(media_archive::title.like(like)),
+						// "LIKE" is not supported for dates, so it is the same as "DateOperator::Eq"
+						SearchColumn::InsertedAt => query.or_filter(media_archive::inserted_at.eq(value.as_str())),
+					}
+				},
+				SearchFilter::Fuzzy(column, value) => {
+					let like = fuzzy_like_pattern(value);
+
+					match column {
+						SearchColumn::Provider => query.or_filter(media_archive::provider.like(like)),
+						SearchColumn::MediaId => query.or_filter(media_archive::media_id.like(like)),
+						SearchColumn::Title => query.or_filter(media_archive::title.like(like)),
+						// "LIKE" is not supported for dates, so it is the same as "DateOperator::Eq"
+						SearchColumn::InsertedAt => query.or_filter(media_archive::inserted_at.eq(value.as_str())),
+					}
+				},
+				SearchFilter::RowIds(ids) => query.or_filter(media_archive::_id.eq_any(ids)),
+				SearchFilter::InsertedAt(op, value) => {
+					let value = value.as_str();
+
+					match op {
+						DateOperator::Eq => query.or_filter(media_archive::inserted_at.eq(value)),
+						DateOperator::Lt => query.or_filter(media_archive::inserted_at.lt(value)),
+						DateOperator::Le => query.or_filter(media_archive::inserted_at.le(value)),
+						DateOperator::Gt => query.or_filter(media_archive::inserted_at.gt(value)),
+						DateOperator::Ge => query.or_filter(media_archive::inserted_at.ge(value)),
+					}
+				},
+			};
+		}
+
+		// like the labels, this is "AND"ed with all column filters
+		if !self.include_tombstones {
+			query = query.filter(media_archive::removed_at.is_null());
+		}
+
+		// label filters have to be applied after the column filters, so that they are "AND"ed with all column filters
+		for label in &self.labels {
+			query = query.filter(
+				media_archive::_id.eq_any(
+					labels::table
+						.filter(labels::label.eq(label))
+						.select(labels::media_archive_id),
+				),
+			);
+		}
+
+		query = match self.sort {
+			None => query.order(media_archive::_id.asc()),
+			Some((column, direction)) => {
+				let query = match (column, direction) {
+					(SearchColumn::Provider, SortDirection::Asc) => query.order(media_archive::provider.asc()),
+					(SearchColumn::Provider, SortDirection::Desc) => query.order(media_archive::provider.desc()),
+					(SearchColumn::MediaId, SortDirection::Asc) => query.order(media_archive::media_id.asc()),
+					(SearchColumn::MediaId, SortDirection::Desc) => query.order(media_archive::media_id.desc()),
+					(SearchColumn::Title, SortDirection::Asc) => query.order(media_archive::title.asc()),
+					(SearchColumn::Title, SortDirection::Desc) => query.order(media_archive::title.desc()),
+					(SearchColumn::InsertedAt, SortDirection::Asc) => query.order(media_archive::inserted_at.asc()),
+					(SearchColumn::InsertedAt, SortDirection::Desc) => query.order(media_archive::inserted_at.desc()),
+				};
+
+				// keep the order stable for equal values
+				query.then_order_by(media_archive::_id.asc())
+			},
+		};
+
+		if let Some(limit) = self.limit {
+			query = query.limit(limit);
+		}
+
+		if let Some(offset) = self.offset {
+			// sqlite does not support a "OFFSET" without a "LIMIT", where "-1" means no limit
+			if self.limit.is_none() {
+				query = query.limit(-1);
+			}
+
+			query = query.offset(offset);
+		}
+
+		return query;
+	}
+
+	/// Run the query and load all matching entries
+	pub fn load(&self, connection: &mut SqliteConnection) -> Result<Vec<Media>, crate::Error> {
+		return self
+			.build()
+			.load(connection)
+			.map_err(|err| return crate::Error::from(err));
+	}
+
+	/// Run the query and load only the row-ids (`_id`) of all matching entries
+	pub fn load_ids(&self, connection: &mut SqliteConnection) -> Result<Vec<i64>, crate::Error> {
+		return self
+			.build()
+			.select(media_archive::_id)
+			.load(connection)
+			.map_err(|err| return crate::Error::from(err));
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		data::sql_models::InsMedia,
+		main::{
+			archive::{
+				import::insert_insmedia,
+				labels::add_labels,
+			},
+			test_utils::create_connection,
+		},
+	};
+
+	/// Test helper to insert media with the given (provider, id, title) and a fixed "inserted_at"
+	fn insert_media(connection: &mut SqliteConnection, entries: &[(&str, &str, &str, &str)]) {
+		for (provider, id, title, inserted_at) in entries {
+			insert_insmedia(&InsMedia::new(id, provider, title), connection).expect("Expected media to be inserted");
+			diesel::update(media_archive::table.filter(media_archive::media_id.eq(id)))
+				.set(media_archive::inserted_at.eq(inserted_at))
+				.execute(connection)
+				.expect("Expected inserted_at to be updated");
+		}
+	}
+
+	/// Test helper to get the media ids of the results
+	fn ids(connection: &mut SqliteConnection, query: &SearchQuery) -> Vec<String> {
+		return query
+			.load(connection)
+			.expect("Expected query to succeed")
+			.into_iter()
+			.map(|v| return v.media_id)
+			.collect();
+	}
+
+	#[test]
+	fn test_date_operator_split_from() {
+		assert_eq!((DateOperator::Ge, "2023-05"), DateOperator::split_from(">=2023-05"));
+		assert_eq!((DateOperator::Le, "2023"), DateOperator::split_from("<=2023"));
+		assert_eq!((DateOperator::Lt, "2023"), DateOperator::split_from("<2023"));
+		assert_eq!((DateOperator::Gt, "2023"), DateOperator::split_from(">2023"));
+		assert_eq!((DateOperator::Eq, "2023"), DateOperator::split_from("=2023"));
+		assert_eq!((DateOperator::Eq, "2023"), DateOperator::split_from("2023"));
+	}
+
+	#[test]
+	fn test_search_column_from_str() {
+		assert_eq!(SearchColumn::MediaId, "id".parse().unwrap());
+		assert_eq!(SearchColumn::InsertedAt, "InsertedAt".parse().unwrap());
+		assert!("unknown".parse::<SearchColumn>().is_err());
+	}
+
+	#[test]
+	fn test_load() {
+		let (mut connection, _tempdir) = create_connection();
+		insert_media(
+			&mut connection,
+			&[
+				("youtube", "a1", "Some Song", "2023-01-01 00:00:00"),
+				("youtube", "b2", "Another Song", "2023-06-01 00:00:00"),
+				("soundcloud", "c3", "Some Other", "2024-01-01 00:00:00"),
+			],
+		);
+
+		assert_eq!(vec!["a1", "b2", "c3"], ids(&mut connection, &SearchQuery::new()));
+		// column filters are "OR"ed
+		assert_eq!(
+			vec!["a1", "c3"],
+			ids(
+				&mut connection,
+				&SearchQuery::new()
+					.filter(SearchColumn::Title, "Some")
+					.filter(SearchColumn::Provider, "sound")
+			)
+		);
+		assert_eq!(
+			vec!["b2", "c3"],
+			ids(
+				&mut connection,
+				&SearchQuery::new().filter(SearchColumn::InsertedAt, ">=2023-05")
+			)
+		);
+		// sort, limit and offset
+		assert_eq!(
+			vec!["b2"],
+			ids(
+				&mut connection,
+				&SearchQuery::new()
+					.sort(SearchColumn::InsertedAt, SortDirection::Desc)
+					.limit(1)
+					.offset(1)
+			)
+		);
+		assert_eq!(
+			vec!["c3", "b2"],
+			ids(
+				&mut connection,
+				&SearchQuery::new()
+					.sort(SearchColumn::Title, SortDirection::Desc)
+					.offset(1)
+			)
+		);
+	}
+
+	#[test]
+	fn test_fuzzy() {
+		assert_eq!("%some%title%", fuzzy_like_pattern(" some  title"));
+		assert_eq!("%", fuzzy_like_pattern(""));
+
+		assert!(fuzzy_matches("Some Good Title", "some title"));
+		assert!(fuzzy_matches("Ärger im Büro", "ärger büro"));
+		// words have to be in order
+		assert!(!fuzzy_matches("Some Good Title", "title some"));
+		assert!(!fuzzy_matches("Some Good Title", "other"));
+	}
+
+	#[test]
+	fn test_load_fuzzy() {
+		let (mut connection, _tempdir) = create_connection();
+		insert_media(
+			&mut connection,
+			&[
+				("youtube", "a1", "Some Good Song", "2023-01-01 00:00:00"),
+				("youtube", "b2", "Another Song", "2023-06-01 00:00:00"),
+				("soundcloud", "c3", "Some Other", "2024-01-01 00:00:00"),
+			],
+		);
+
+		assert_eq!(
+			vec!["a1"],
+			ids(
+				&mut connection,
+				&SearchQuery::new().fuzzy_filter(SearchColumn::Title, "some song")
+			)
+		);
+		assert_eq!(
+			vec!["a1", "b2"],
+			ids(
+				&mut connection,
+				&SearchQuery::new().fuzzy_filter(SearchColumn::Title, "SONG")
+			)
+		);
+		assert_eq!(
+			vec!["c3"],
+			ids(
+				&mut connection,
+				&SearchQuery::new().fuzzy_filter(SearchColumn::Provider, "cloud")
+			)
+		);
+		// row-ids are "OR"ed with the other filters
+		assert_eq!(
+			vec!["a1", "c3"],
+			ids(
+				&mut connection,
+				&SearchQuery::new()
+					.fuzzy_filter(SearchColumn::Provider, "cloud")
+					.with_filter(SearchFilter::RowIds(vec![1]))
+			)
+		);
+	}
+
+	#[test]
+	fn test_load_labels() {
+		let (mut connection, _tempdir) = create_connection();
+		insert_media(
+			&mut connection,
+			&[
+				("youtube", "a1", "Some Song", "2023-01-01 00:00:00"),
+				("youtube", "b2", "Another Song", "2023-06-01 00:00:00"),
+			],
+		);
+		let all_ids = SearchQuery::new()
+			.load_ids(&mut connection)
+			.expect("Expected query to succeed");
+		add_labels(&mut connection, &all_ids[1..], &["music"]).expect("Expected labels to be added");
+
+		assert_eq!(vec!["b2"], ids(&mut connection, &SearchQuery::new().label("music")));
+		// labels are "AND"ed with the column filters
+		assert!(ids(
+			&mut connection,
+			&SearchQuery::new().filter(SearchColumn::MediaId, "a").label("music")
+		)
+		.is_empty());
+	}
+	#[test]
+	fn test_load_tombstones() {
+		let (mut connection, _tempdir) = create_connection();
+		insert_media(
+			&mut connection,
+			&[
+				("youtube", "a1", "Some Song", "2023-01-01 00:00:00"),
+				("youtube", "a2", "Another Song", "2023-06-01 00:00:00"),
+			],
+		);
+		diesel::update(media_archive::table.filter(media_archive::media_id.eq("a1")))
+			.set(media_archive::removed_at.eq(diesel::dsl::now))
+			.execute(&mut connection)
+			.expect("Expected removed_at to be updated");
+
+		// tombstones are "AND"ed with the column filters
+		assert_eq!(
+			vec!["a2"],
+			ids(
+				&mut connection,
+				&SearchQuery::new()
+					.filter(SearchColumn::MediaId, "a1")
+					.filter(SearchColumn::Title, "Another")
+			)
+		);
+		assert_eq!(
+			vec!["a1", "a2"],
+			ids(&mut connection, &SearchQuery::new().include_tombstones(true))
+		);
+	}
+}
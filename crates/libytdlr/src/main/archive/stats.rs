@@ -0,0 +1,365 @@
+//! Module for aggregate statistics of the Archive (like `ytdlr archive stats`)
+
+use chrono::{
+	Datelike,
+	Months,
+	NaiveDate,
+};
+use diesel::{
+	dsl::count_star,
+	prelude::*,
+	sql_types::{
+		BigInt,
+		Text,
+	},
+};
+use serde::Serialize;
+
+use crate::data::sql_schema::media_archive;
+
+/// Count of how many media are in the Archive for a single key (like a provider or a month)
+#[derive(Debug, Clone, PartialEq, Serialize, QueryableByName)]
+pub struct StatsCount {
+	/// The key that is counted, like "youtube", "2024-01" or "2024-01-31"
+	#[diesel(sql_type = Text)]
+	pub key:   String,
+	/// The count of media for the key
+	#[diesel(sql_type = BigInt)]
+	pub count: i64,
+}
+
+impl StatsCount {
+	/// Create a new instance of [`StatsCount`]
+	pub fn new<K: Into<String>>(key: K, count: i64) -> Self {
+		return Self { key: key.into(), count };
+	}
+}
+
+/// The total size and duration of the media in the Archive, only media where it is known are counted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, QueryableByName)]
+pub struct StatsTotals {
+	/// The count of media with a known size
+	#[diesel(sql_type = BigInt)]
+	pub sized_count:        i64,
+	/// The total size of all media in bytes
+	#[diesel(sql_type = BigInt)]
+	pub total_bytes:        i64,
+	/// The total duration of all media in seconds
+	#[diesel(sql_type = BigInt)]
+	pub total_duration_sec: i64,
+}
+
+/// The result of [`archive_stats`]
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct ArchiveStats {
+	/// The count of all media in the Archive
+	pub total:     i64,
+	/// The total size and duration of the media in the Archive
+	pub totals:    StatsTotals,
+	/// The count of media per provider, sorted by the highest count first
+	pub providers: Vec<StatsCount>,
+	/// The count of media added per month (formatted like "2024-01") for the last 12 months, oldest month first
+	/// Months without added media are included with a count of 0
+	pub months:    Vec<StatsCount>,
+	/// The days (formatted like "2024-01-31") with the most media added, sorted by the highest count first
+	pub top_days:  Vec<StatsCount>,
+}
+
+/// The size of the media in the Archive for a single key (like a provider or a uploader), only media with a known size are counted
+#[derive(Debug, Clone, PartialEq, Serialize, QueryableByName)]
+pub struct StatsSize {
+	/// The key that is summed, like "youtube" or the name of a uploader
+	#[diesel(sql_type = Text)]
+	pub key:         String,
+	/// The count of media with a known size for the key
+	#[diesel(sql_type = BigInt)]
+	pub count:       i64,
+	/// The total size of the media of the key in bytes
+	#[diesel(sql_type = BigInt)]
+	pub total_bytes: i64,
+}
+
+impl StatsSize {
+	/// Create a new instance of [`StatsSize`]
+	pub fn new<K: Into<String>>(key: K, count: i64, total_bytes: i64) -> Self {
+		return Self {
+			key: key.into(),
+			count,
+			total_bytes,
+		};
+	}
+}
+
+/// A single media with its size, for [`DiskStats::largest`]
+#[derive(Debug, Clone, PartialEq, Serialize, QueryableByName)]
+pub struct StatsMediaSize {
+	/// The provider of the media
+	#[diesel(sql_type = Text)]
+	pub provider:       String,
+	/// The ID of the media given used by the provider
+	#[diesel(sql_type = Text)]
+	pub media_id:       String,
+	/// The title of the media
+	#[diesel(sql_type = Text)]
+	pub title:          String,
+	/// The size of the media file in bytes at download time
+	#[diesel(sql_type = BigInt)]
+	pub filesize_bytes: i64,
+}
+
+/// The result of [`disk_stats`]
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct DiskStats {
+	/// The total size of the media in the Archive
+	pub totals:    StatsTotals,
+	/// The size of the media per provider, sorted by the largest size first
+	pub providers: Vec<StatsSize>,
+	/// The uploaders with the largest size of media, sorted by the largest size first
+	pub uploaders: Vec<StatsSize>,
+	/// The largest media, sorted by the largest size first
+	pub largest:   Vec<StatsMediaSize>,
+}
+
+/// The count of months in [`ArchiveStats::months`]
+const STATS_MONTHS: u32 = 12;
+
+/// Get the aggregate statistics of the Archive
+/// `today` is the day the last month of [`ArchiveStats::months`] is based on, `top_days` is the maximal count of [`ArchiveStats::top_days`]
+pub fn archive_stats(
+	connection: &mut SqliteConnection,
+	today: NaiveDate,
+	top_days: i64,
+) -> Result<ArchiveStats, crate::Error> {
+	let total = media_archive::table.count().get_result::<i64>(connection)?;
+
+	let providers = media_archive::table
+		.group_by(media_archive::provider)
+		.select((media_archive::provider, count_star()))
+		.order((count_star().desc(), media_archive::provider.asc()))
+		.load::<(String, i64)>(connection)?
+		.into_iter()
+		.map(|(key, count)| return StatsCount::new(key, count))
+		.collect();
+
+	return Ok(ArchiveStats {
+		total,
+		totals: count_totals(connection)?,
+		providers,
+		months: count_months(connection, today)?,
+		top_days: count_top_days(connection, top_days)?,
+	});
+}
+
+/// Get the storage used by the media in the Archive, from the size recorded at download time
+/// `limit` is the maximal count of [`DiskStats::uploaders`] and [`DiskStats::largest`]
+pub fn disk_stats(connection: &mut SqliteConnection, limit: i64) -> Result<DiskStats, crate::Error> {
+	let providers = diesel::sql_query(
+		"SELECT provider AS key, COUNT(*) AS count, SUM(filesize_bytes) AS total_bytes FROM media_archive WHERE filesize_bytes IS NOT NULL GROUP BY key ORDER BY total_bytes DESC, key ASC",
+	)
+	.load::<StatsSize>(connection)?;
+
+	let uploaders = diesel::sql_query(
+		"SELECT uploader AS key, COUNT(*) AS count, SUM(filesize_bytes) AS total_bytes FROM media_archive WHERE filesize_bytes IS NOT NULL AND uploader IS NOT NULL GROUP BY key ORDER BY total_bytes DESC, key ASC LIMIT ?",
+	)
+	.bind::<BigInt, _>(limit)
+	.load::<StatsSize>(connection)?;
+
+	// ties are sorted by insertion, so that the order is stable
+	let largest = diesel::sql_query(
+		"SELECT provider, media_id, title, filesize_bytes FROM media_archive WHERE filesize_bytes IS NOT NULL ORDER BY filesize_bytes DESC, _id ASC LIMIT ?",
+	)
+	.bind::<BigInt, _>(limit)
+	.load::<StatsMediaSize>(connection)?;
+
+	return Ok(DiskStats {
+		totals: count_totals(connection)?,
+		providers,
+		uploaders,
+		largest,
+	});
+}
+
+/// Sum the size and duration of all media in the Archive
+fn count_totals(connection: &mut SqliteConnection) -> Result<StatsTotals, crate::Error> {
+	return Ok(diesel::sql_query(
+		"SELECT COUNT(filesize_bytes) AS sized_count, COALESCE(SUM(filesize_bytes), 0) AS total_bytes, COALESCE(SUM(duration_sec), 0) AS total_duration_sec FROM media_archive",
+	)
+	.get_result::<StatsTotals>(connection)?);
+}
+
+/// Count the media added per month for the last [`STATS_MONTHS`] months up to (and including) the month of `today`
+fn count_months(connection: &mut SqliteConnection, today: NaiveDate) -> Result<Vec<StatsCount>, crate::Error> {
+	// using "unwrap", because the first day of a month always exists and going back 11 months is always in range for valid dates
+	let first_month = today
+		.with_day(1)
+		.and_then(|v| return v.checked_sub_months(Months::new(STATS_MONTHS - 1)))
+		.unwrap();
+
+	let counted = diesel::sql_query(
+		"SELECT strftime('%Y-%m', inserted_at) AS key, COUNT(*) AS count FROM media_archive WHERE inserted_at >= ? GROUP BY key",
+	)
+	.bind::<Text, _>(first_month.format("%Y-%m-%d").to_string())
+	.load::<StatsCount>(connection)?;
+
+	// add months without any media, so that there are no gaps
+	return Ok((0..STATS_MONTHS)
+		.map(|offset| {
+			let key = (first_month + Months::new(offset)).format("%Y-%m").to_string();
+			let count = counted
+				.iter()
+				.find(|v| return v.key == key)
+				.map_or(0, |v| return v.count);
+
+			return StatsCount::new(key, count);
+		})
+		.collect());
+}
+
+/// Get the `limit` days with the most media added, ties are sorted by the newest day first
+fn count_top_days(connection: &mut SqliteConnection, limit: i64) -> Result<Vec<StatsCount>, crate::Error> {
+	return Ok(diesel::sql_query(
+		"SELECT strftime('%Y-%m-%d', inserted_at) AS key, COUNT(*) AS count FROM media_archive GROUP BY key ORDER BY count DESC, key DESC LIMIT ?",
+	)
+	.bind::<BigInt, _>(limit)
+	.load::<StatsCount>(connection)?);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		data::sql_models::InsMedia,
+		main::{
+			archive::import::insert_insmedia,
+			test_utils::create_connection,
+		},
+	};
+
+	#[test]
+	fn test_empty_archive() {
+		let (mut connection, _tempdir) = create_connection();
+		let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+
+		let stats = archive_stats(&mut connection, today, 5).unwrap();
+
+		assert_eq!(0, stats.total);
+		assert_eq!(StatsTotals::default(), stats.totals);
+		assert!(stats.providers.is_empty());
+		assert!(stats.top_days.is_empty());
+		assert_eq!(12, stats.months.len());
+		assert_eq!(StatsCount::new("2023-04", 0), stats.months[0]);
+		assert_eq!(StatsCount::new("2024-03", 0), stats.months[11]);
+	}
+
+	#[test]
+	fn test_archive_stats() {
+		let (mut connection, _tempdir) = create_connection();
+
+		for (provider, id, inserted_at) in [
+			("youtube", "a1", "2022-12-31 10:00:00"),
+			("youtube", "a2", "2024-01-05 10:00:00"),
+			("youtube", "a3", "2024-01-05 12:00:00"),
+			("youtube", "a4", "2024-03-01 00:00:00"),
+			("soundcloud", "b1", "2024-01-20 10:00:00"),
+			("soundcloud", "b2", "2024-01-20 11:00:00"),
+			("bandcamp", "c1", "2023-06-10 10:00:00"),
+		] {
+			// only the youtube media have a known size and duration
+			let known = provider == "youtube";
+			insert_insmedia(
+				&InsMedia {
+					duration_sec: known.then_some(60),
+					filesize_bytes: known.then_some(1024),
+					..InsMedia::new(id, provider, "Some Title")
+				},
+				&mut connection,
+			)
+			.unwrap();
+			diesel::update(media_archive::table.filter(media_archive::media_id.eq(id)))
+				.set(media_archive::inserted_at.eq(inserted_at))
+				.execute(&mut connection)
+				.unwrap();
+		}
+
+		let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+		let stats = archive_stats(&mut connection, today, 2).unwrap();
+
+		assert_eq!(7, stats.total);
+		assert_eq!(
+			StatsTotals {
+				sized_count:        4,
+				total_bytes:        4096,
+				total_duration_sec: 240,
+			},
+			stats.totals
+		);
+		assert_eq!(
+			vec![
+				StatsCount::new("youtube", 4),
+				StatsCount::new("soundcloud", 2),
+				StatsCount::new("bandcamp", 1),
+			],
+			stats.providers
+		);
+
+		// the media from 2022 is outside of the last 12 months
+		assert_eq!(12, stats.months.len());
+		assert_eq!(StatsCount::new("2023-04", 0), stats.months[0]);
+		assert_eq!(StatsCount::new("2023-06", 1), stats.months[2]);
+		assert_eq!(StatsCount::new("2024-01", 4), stats.months[9]);
+		assert_eq!(StatsCount::new("2024-02", 0), stats.months[10]);
+		assert_eq!(StatsCount::new("2024-03", 1), stats.months[11]);
+		assert_eq!(6, stats.months.iter().map(|v| return v.count).sum::<i64>());
+
+		// ties are sorted by the newest day first
+		assert_eq!(
+			vec![StatsCount::new("2024-01-20", 2), StatsCount::new("2024-01-05", 2)],
+			stats.top_days
+		);
+	}
+	#[test]
+	fn test_disk_stats() {
+		let (mut connection, _tempdir) = create_connection();
+
+		for (provider, id, uploader, filesize_bytes) in [
+			("youtube", "a1", Some("Channel A"), Some(100)),
+			("youtube", "a2", Some("Channel A"), Some(300)),
+			("youtube", "a3", Some("Channel B"), Some(500)),
+			("youtube", "a4", None, None),
+			("soundcloud", "b1", None, Some(2000)),
+			("bandcamp", "c1", Some("Channel C"), None),
+		] {
+			insert_insmedia(
+				&InsMedia {
+					uploader,
+					filesize_bytes,
+					..InsMedia::new(id, provider, id)
+				},
+				&mut connection,
+			)
+			.unwrap();
+		}
+
+		let stats = disk_stats(&mut connection, 2).unwrap();
+
+		assert_eq!(4, stats.totals.sized_count);
+		assert_eq!(2900, stats.totals.total_bytes);
+		// media without a known size are not counted
+		assert_eq!(
+			vec![StatsSize::new("soundcloud", 1, 2000), StatsSize::new("youtube", 3, 900)],
+			stats.providers
+		);
+		assert_eq!(
+			vec![StatsSize::new("Channel B", 1, 500), StatsSize::new("Channel A", 2, 400)],
+			stats.uploaders
+		);
+		assert_eq!(
+			vec![("b1".to_owned(), 2000), ("a3".to_owned(), 500)],
+			stats
+				.largest
+				.into_iter()
+				.map(|v| return (v.media_id, v.filesize_bytes))
+				.collect::<Vec<_>>()
+		);
+	}
+}
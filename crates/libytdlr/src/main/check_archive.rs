@@ -0,0 +1,328 @@
+//! Module for checking the archive against the media files on disk
+
+// the "_id" field of "Media" is named after the database column
+#![allow(clippy::used_underscore_binding)]
+
+use std::{
+	collections::{
+		HashMap,
+		HashSet,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::{
+	data::{
+		cache::media_info::{
+			MediaInfo,
+			SIDECAR_EXTENSION,
+		},
+		sql_models::Media,
+		sql_schema::media_archive,
+	},
+	error::IOErrorToError,
+};
+
+/// Maximal number suffix (like "Title 2.mkv") that is removed when matching a file by title
+/// Should be the same as the maximal number used when moving media to a already existing path
+const MAX_NUMBER_SUFFIX: usize = 30;
+
+/// A archive entry for which no file could be found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MissingEntry {
+	/// The provider of the entry
+	pub provider: String,
+	/// The id of the entry
+	pub media_id: String,
+	/// The title of the entry
+	pub title:    String,
+}
+
+impl From<Media> for MissingEntry {
+	fn from(v: Media) -> Self {
+		return Self {
+			provider: v.provider,
+			media_id: v.media_id,
+			title:    v.title,
+		};
+	}
+}
+
+/// The result of [`check_archive`]
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct CheckArchiveResult {
+	/// Count of archive entries which have a file
+	pub found:     usize,
+	/// Archive entries which have no file, in the order they were inserted
+	pub missing:   Vec<MissingEntry>,
+	/// Files which could not be matched to any archive entry, sorted by path
+	pub untracked: Vec<PathBuf>,
+}
+
+/// Find all media files in `dirs` (recursively), ignoring hidden files and sidecar files
+/// The returned paths are sorted
+pub fn find_library_files<P: AsRef<Path>>(dirs: &[P]) -> Result<Vec<PathBuf>, crate::Error> {
+	let mut files = Vec::new();
+	let mut pending: Vec<PathBuf> = dirs.iter().map(|v| return v.as_ref().to_path_buf()).collect();
+
+	while let Some(dir) = pending.pop() {
+		if !dir.is_dir() {
+			return Err(crate::Error::not_a_directory(
+				"Library path does not exist or is not a directory",
+				dir,
+			));
+		}
+
+		for entry in std::fs::read_dir(&dir).attach_path_err(&dir)? {
+			let path = entry.attach_path_err(&dir)?.path();
+			let Some(name) = path.file_name().and_then(|v| return v.to_str()) else {
+				continue;
+			};
+
+			if name.starts_with('.') {
+				continue;
+			}
+
+			if path.is_dir() {
+				pending.push(path);
+			} else if !name.ends_with(SIDECAR_EXTENSION) {
+				files.push(path);
+			}
+		}
+	}
+
+	files.sort();
+
+	return Ok(files);
+}
+
+/// Get the title a media file is named after, like "Some Title.mkv" -> "Some Title"
+/// Also returns the title without a number suffix, like "Some Title 2.mkv" -> "Some Title"
+fn file_titles(path: &Path) -> Vec<String> {
+	let Some(stem) = path.file_stem().and_then(|v| return v.to_str()) else {
+		return Vec::new();
+	};
+
+	let mut titles = vec![stem.to_owned()];
+
+	if let Some((base, number)) = stem.rsplit_once(' ') {
+		if number
+			.parse::<usize>()
+			.is_ok_and(|v| return (1..=MAX_NUMBER_SUFFIX).contains(&v))
+		{
+			titles.push(base.to_owned());
+		}
+	}
+
+	return titles;
+}
+
+/// Convert a archive title to the title used in filenames, see "convert_mediainfo_to_filename"
+fn title_to_file_title(title: &str) -> String {
+	return title.replace('/', "⧸");
+}
+
+/// Index of files by the archive entries they may belong to
+struct FileIndex {
+	/// index of "(provider, id)" to file index
+	by_id:    HashMap<(String, String), Vec<usize>>,
+	/// index of file title to file index
+	by_title: HashMap<String, Vec<usize>>,
+}
+
+impl FileIndex {
+	/// Index all `files`, see [`check_archive`] for how files are matched
+	fn new(files: &[PathBuf]) -> Self {
+		let mut by_id: HashMap<(String, String), Vec<usize>> = HashMap::new();
+		let mut by_title: HashMap<String, Vec<usize>> = HashMap::new();
+
+		for (index, path) in files.iter().enumerate() {
+			let parsed = path
+				.file_name()
+				.and_then(|v| return v.to_str())
+				.and_then(|v| return MediaInfo::try_from_filename(&v));
+
+			if let Some(media) = parsed {
+				by_id
+					.entry((media.provider.as_str().to_lowercase(), media.id))
+					.or_default()
+					.push(index);
+			} else {
+				for title in file_titles(path) {
+					by_title.entry(title).or_default().push(index);
+				}
+			}
+		}
+
+		return Self { by_id, by_title };
+	}
+
+	/// Get the indexes of the files which belong to `entry`
+	fn find(&self, entry: &Media) -> Option<&Vec<usize>> {
+		return self
+			.by_id
+			.get(&(entry.provider.to_lowercase(), entry.media_id.clone()))
+			.or_else(|| return self.by_title.get(&title_to_file_title(&entry.title)));
+	}
+}
+
+/// Check which archive entries have no file in `files` and which `files` are not in the archive
+///
+/// A file matches a entry if it is still named like youtube-dl output (see [`MediaInfo::try_from_filename`])
+/// or if it is named after the title of the entry (like it is when moved to the final directory)
+pub fn check_archive(connection: &mut SqliteConnection, files: &[PathBuf]) -> Result<CheckArchiveResult, crate::Error> {
+	let index = FileIndex::new(files);
+
+	let entries = media_archive::table
+		.order(media_archive::_id.asc())
+		.load::<Media>(connection)?;
+
+	let mut result = CheckArchiveResult::default();
+	let mut matched: HashSet<usize> = HashSet::new();
+
+	for entry in entries {
+		if let Some(found) = index.find(&entry) {
+			matched.extend(found);
+			result.found += 1;
+		} else {
+			result.missing.push(entry.into());
+		}
+	}
+
+	result.untracked = files
+		.iter()
+		.enumerate()
+		.filter(|(index, _)| return !matched.contains(index))
+		.map(|(_, path)| return path.clone())
+		.collect();
+
+	return Ok(result);
+}
+
+/// Get the row-ids ("_id") of all archive entries which have a file in `files`, matched the same way as [`check_archive`]
+pub fn find_file_entries(connection: &mut SqliteConnection, files: &[PathBuf]) -> Result<Vec<i64>, crate::Error> {
+	let index = FileIndex::new(files);
+
+	let entries = media_archive::table
+		.order(media_archive::_id.asc())
+		.load::<Media>(connection)?;
+
+	return Ok(entries
+		.into_iter()
+		.filter(|v| return index.find(v).is_some())
+		.map(|v| return v._id)
+		.collect());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{
+		data::sql_models::InsMedia,
+		main::{
+			archive::import::insert_insmedia,
+			test_utils::create_connection,
+		},
+	};
+
+	#[test]
+	fn test_file_titles() {
+		assert_eq!(vec!["Some Title".to_owned()], file_titles(Path::new("Some Title.mkv")));
+		assert_eq!(
+			vec!["Some Title 2".to_owned(), "Some Title".to_owned()],
+			file_titles(Path::new("Some Title 2.mkv"))
+		);
+		// numbers higher than the maximal suffix are not a suffix
+		assert_eq!(
+			vec!["Some Title 2024".to_owned()],
+			file_titles(Path::new("Some Title 2024.mkv"))
+		);
+	}
+
+	#[test]
+	fn test_find_library_files() {
+		let (_connection, tempdir) = create_connection();
+		let library = tempdir.path().join("library");
+		std::fs::create_dir_all(library.join("sub")).unwrap();
+
+		for name in [
+			"Title A.mkv",
+			"Title A.mkv.ytdlr.json",
+			".hidden.mkv",
+			"sub/Title B.mp3",
+		] {
+			std::fs::write(library.join(name), "").unwrap();
+		}
+
+		assert_eq!(
+			vec![library.join("Title A.mkv"), library.join("sub/Title B.mp3")],
+			find_library_files(&[&library]).unwrap()
+		);
+		assert!(find_library_files(&[tempdir.path().join("not-existing")]).is_err());
+	}
+
+	#[test]
+	fn test_check_archive() {
+		let (mut connection, _tempdir) = create_connection();
+
+		for (id, provider, title) in [
+			("id1", "youtube", "Title A"),
+			("id2", "youtube", "Title/B"),
+			("id3", "soundcloud", "Title C"),
+			("id4", "youtube", "Title D"),
+			("id5", "youtube", "Title E"),
+		] {
+			insert_insmedia(&InsMedia::new(id, provider, title), &mut connection).unwrap();
+		}
+
+		let files = vec![
+			PathBuf::from("/lib/Title A.mkv"),
+			PathBuf::from("/lib/Title⧸B.mkv"),
+			PathBuf::from("/lib/'soundcloud'-'id3'-Other Title.mp3"),
+			PathBuf::from("/lib/Title E 1.mkv"),
+			PathBuf::from("/lib/Unknown.mkv"),
+		];
+
+		let result = check_archive(&mut connection, &files).unwrap();
+
+		assert_eq!(4, result.found);
+		assert_eq!(
+			vec![MissingEntry {
+				provider: "youtube".to_owned(),
+				media_id: "id4".to_owned(),
+				title:    "Title D".to_owned(),
+			}],
+			result.missing
+		);
+		assert_eq!(vec![PathBuf::from("/lib/Unknown.mkv")], result.untracked);
+	}
+
+	#[test]
+	fn test_find_file_entries() {
+		let (mut connection, _tempdir) = create_connection();
+
+		for (id, provider, title) in [
+			("id1", "youtube", "Title A"),
+			("id2", "youtube", "Title B"),
+			("id3", "soundcloud", "Title C"),
+		] {
+			insert_insmedia(&InsMedia::new(id, provider, title), &mut connection).unwrap();
+		}
+
+		let files = vec![
+			PathBuf::from("/lib/Title B.mkv"),
+			PathBuf::from("/lib/'soundcloud'-'id3'-Other Title.mp3"),
+			PathBuf::from("/lib/Unknown.mkv"),
+		];
+
+		// the row-ids are in insertion order, starting at 1
+		assert_eq!(vec![2, 3], find_file_entries(&mut connection, &files).unwrap());
+		assert!(find_file_entries(&mut connection, &[]).unwrap().is_empty());
+	}
+}
@@ -0,0 +1,220 @@
+//! Module for comparing a playlist against the archive, without downloading anything
+
+use std::{
+	ffi::OsStr,
+	process::Stdio,
+};
+
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+	data::{
+		cache::media_info::MediaInfo,
+		sql_schema::media_archive,
+	},
+	error::IOErrorToError,
+	spawn::ytdl::base_ytdl,
+};
+
+/// Prefix used for the custom print of each playlist entry
+const DIFF_ENTRY_PREFIX: &str = "DIFF_ENTRY";
+
+/// Titles youtube-dl uses for entries which are still listed in a playlist, but cannot be accessed anymore
+const UNAVAILABLE_TITLES: &[&str] = &["[Deleted video]", "[Private video]"];
+/// Availability values which mark a entry as not accessible anymore
+const UNAVAILABLE_AVAILABILITY: &[&str] = &["private", "needs_auth"];
+
+/// A single entry of a playlist, as listed by youtube-dl
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistEntry {
+	/// The Media information of the entry, always has a title
+	pub media:     MediaInfo,
+	/// Whether the entry can still be downloaded (not deleted or private)
+	pub available: bool,
+}
+
+/// The result of comparing a playlist against the archive
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlaylistDiff {
+	/// Available playlist entries which are not in the archive yet (what a download would fetch)
+	pub missing:     Vec<MediaInfo>,
+	/// Archived media which is still listed in the playlist, but is not available anymore (like deleted or private)
+	///
+	/// Media that has been fully removed from the playlist cannot be detected, because the archive does not store playlist membership
+	pub disappeared: Vec<MediaInfo>,
+	/// Count of available playlist entries which are already in the archive
+	pub archived:    usize,
+	/// Count of unavailable playlist entries which are not in the archive (and cannot be downloaded anymore)
+	pub unavailable: usize,
+}
+
+/// List all entries of the playlist at `url` with the youtube-dl binary `ytdl_bin`, without downloading anything (using "--flat-playlist")
+/// A non-playlist `url` will result in a single entry
+pub fn list_playlist(ytdl_bin: &OsStr, url: &str) -> Result<Vec<PlaylistEntry>, crate::Error> {
+	let mut cmd = base_ytdl(ytdl_bin);
+	cmd.arg("--flat-playlist")
+		.arg("--print")
+		// "ie_key" is used, because in "--flat-playlist" mode "extractor" would be the playlist's extractor (like "youtube:tab")
+		.arg(format!(
+			"{DIFF_ENTRY_PREFIX} '%(ie_key,extractor_key)s' '%(id)s' '%(availability)s' %(title)s"
+		))
+		.arg(url);
+
+	let command_output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("ytdl spawn")?
+		.wait_with_output()
+		.attach_location_err("ytdl wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"youtube-dl did not successfully exit! stderr: {}",
+			String::from_utf8_lossy(&command_output.stderr).trim()
+		)));
+	}
+
+	let as_string = String::from_utf8(command_output.stdout)?;
+
+	return Ok(as_string.lines().filter_map(parse_playlist_entry).collect());
+}
+
+/// Parse a single line printed by [`list_playlist`]
+/// Returns [`None`] if the line is not a playlist entry line
+fn parse_playlist_entry(input: &str) -> Option<PlaylistEntry> {
+	/// Regex to get all information from a "DIFF_ENTRY" line
+	/// cap1: provider, cap2: id, cap3: availability, cap4: title
+	static DIFF_ENTRY_REGEX: Lazy<Regex> = Lazy::new(|| {
+		return Regex::new(r"(?m)^DIFF_ENTRY '([^']+)' '([^']+)' '([^']*)' (.*)$").unwrap();
+	});
+
+	let cap = DIFF_ENTRY_REGEX.captures(input)?;
+	let title = cap[4].trim();
+	let available = !UNAVAILABLE_AVAILABILITY.contains(&&cap[3]) && !UNAVAILABLE_TITLES.contains(&title);
+
+	return Some(PlaylistEntry {
+		media: MediaInfo::new(&cap[2], &cap[1]).with_title(title),
+		available,
+	});
+}
+
+/// Compare the given playlist `entries` against the archive in `connection`
+pub fn diff_playlist(
+	connection: &mut SqliteConnection,
+	entries: Vec<PlaylistEntry>,
+) -> Result<PlaylistDiff, crate::Error> {
+	let mut diff = PlaylistDiff::default();
+
+	for entry in entries {
+		let in_archive: bool = diesel::select(diesel::dsl::exists(
+			media_archive::dsl::media_archive
+				.filter(media_archive::media_id.eq(&entry.media.id))
+				.filter(media_archive::provider.eq(entry.media.provider.as_str())),
+		))
+		.get_result(connection)?;
+
+		match (in_archive, entry.available) {
+			(true, true) => diff.archived += 1,
+			(true, false) => diff.disappeared.push(entry.media),
+			(false, true) => diff.missing.push(entry.media),
+			(false, false) => diff.unavailable += 1,
+		}
+	}
+
+	return Ok(diff);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	mod parse_playlist_entry {
+		use super::*;
+
+		#[test]
+		fn test_basic() {
+			assert_eq!(
+				Some(PlaylistEntry {
+					media:     MediaInfo::new("-----------", "youtube").with_title("Some Title Here"),
+					available: true,
+				}),
+				parse_playlist_entry("DIFF_ENTRY 'Youtube' '-----------' 'NA' Some Title Here")
+			);
+		}
+
+		#[test]
+		fn test_unavailable() {
+			assert_eq!(
+				Some(false),
+				parse_playlist_entry("DIFF_ENTRY 'Youtube' '-----------' 'NA' [Deleted video]")
+					.map(|v| return v.available)
+			);
+			assert_eq!(
+				Some(false),
+				parse_playlist_entry("DIFF_ENTRY 'Youtube' '-----------' 'private' Some Title Here")
+					.map(|v| return v.available)
+			);
+		}
+
+		#[test]
+		fn test_other_lines() {
+			assert_eq!(None, parse_playlist_entry("[youtube:tab] Downloading webpage"));
+			assert_eq!(None, parse_playlist_entry(""));
+		}
+	}
+
+	mod diff_playlist {
+		use super::*;
+		use crate::{
+			data::sql_models::InsMedia,
+			main::{
+				archive::import::insert_insmedia,
+				test_utils::create_connection,
+			},
+		};
+
+		/// Test helper to create a [`PlaylistEntry`]
+		fn entry(id: &str, available: bool) -> PlaylistEntry {
+			return PlaylistEntry {
+				media: MediaInfo::new(id, "youtube").with_title(id),
+				available,
+			};
+		}
+
+		#[test]
+		fn test_basic() {
+			let (mut connection, _tempdir) = create_connection();
+
+			insert_insmedia(&InsMedia::new("archived1", "youtube", "archived1"), &mut connection).unwrap();
+			insert_insmedia(&InsMedia::new("gone1", "youtube", "gone1"), &mut connection).unwrap();
+			// same id, but different provider, should not count as archived
+			insert_insmedia(&InsMedia::new("new1", "soundcloud", "new1"), &mut connection).unwrap();
+
+			let res = diff_playlist(
+				&mut connection,
+				vec![
+					entry("archived1", true),
+					entry("gone1", false),
+					entry("new1", true),
+					entry("deleted1", false),
+				],
+			)
+			.expect("Expected diff_playlist to be ok");
+
+			assert_eq!(
+				PlaylistDiff {
+					missing:     vec![MediaInfo::new("new1", "youtube").with_title("new1")],
+					disappeared: vec![MediaInfo::new("gone1", "youtube").with_title("gone1")],
+					archived:    1,
+					unavailable: 1,
+				},
+				res
+			);
+		}
+	}
+}
@@ -5,17 +5,33 @@ use std::{
 		BufWriter,
 		Write as _,
 	},
-	path::Path,
+	path::{
+		Path,
+		PathBuf,
+	},
 };
 
 use diesel::SqliteConnection;
 
 use crate::{
 	error::IOErrorToError as _,
-	main::download::get_archive_name,
+	main::download::{
+		get_archive_name,
+		PATHS_SUBTITLE_DIR,
+		PATHS_TEMP_DIR,
+		PATHS_THUMBNAIL_DIR,
+	},
 };
 
-use super::download_options::DownloadOptions;
+use super::download_options::{
+	CookieSource,
+	DownloadOptions,
+	FormatConstraints,
+	PlaylistOrder,
+};
+
+/// The interval in seconds in which youtube-dl checks whether a scheduled livestream has started (yt-dlp "--wait-for-video")
+const LIVE_WAIT_RETRY_SECS: &str = "60";
 
 /// Internal Struct for easily adding various types that resolve to [`OsString`] and output a [`Vec<OsString>`]
 /// exists because [std::process::Command] is too overkill to use for a argument collection for having to use [duct] later
@@ -58,54 +74,153 @@ pub fn assemble_ytdl_command<A: DownloadOptions>(
 	connection: Option<&mut SqliteConnection>,
 	options: &A,
 ) -> Result<Vec<OsString>, crate::Error> {
+	return assemble_ytdl_command_impl(connection, options, false).map(|(args, _)| return args);
+}
+
+/// Assemble all ytdl command arguments like [`assemble_ytdl_command`], but without writing anything to disk
+/// Returns the arguments and the lines the ytdl archive would contain, if a ytdl archive would be generated
+pub fn assemble_ytdl_command_dry_run<A: DownloadOptions>(
+	connection: Option<&mut SqliteConnection>,
+	options: &A,
+) -> Result<(Vec<OsString>, Option<Vec<String>>), crate::Error> {
+	return assemble_ytdl_command_impl(connection, options, true);
+}
+
+/// Internal implementation for [`assemble_ytdl_command`] and [`assemble_ytdl_command_dry_run`]
+/// The ytdl archive lines are only returned if `dry_run` is set, otherwise they are written to the ytdl archive file
+fn assemble_ytdl_command_impl<A: DownloadOptions>(
+	connection: Option<&mut SqliteConnection>,
+	options: &A,
+	dry_run: bool,
+) -> Result<(Vec<OsString>, Option<Vec<String>>), crate::Error> {
 	let mut ytdl_args = ArgsHelper::new();
 
 	let output_dir = options.download_path();
 	debug!("YTDL Output dir is \"{}\"", output_dir.to_string_lossy());
 
-	std::fs::create_dir_all(output_dir).attach_path_err(output_dir)?;
+	if !dry_run {
+		std::fs::create_dir_all(output_dir).attach_path_err(output_dir)?;
+	}
 
 	// set a custom format the videos will be in for consistent parsing
-	let output_format = output_dir.join("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s");
+	// the format is relative to the paths set in "add_paths"
+	let output_format = "'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s";
 
-	generate_archive(&mut ytdl_args, connection, options, output_dir)?;
+	let archive_lines = generate_archive(&mut ytdl_args, connection, options, output_dir, dry_run)?;
 
+	// prefer the probed capabilities, because forks may not follow the yt-dlp version dates
 	// using unwrap, because it is checked via tests that this statement compiles and is meant to be static
 	// 2023.3.24 is the date of the commit that added "--no-quiet"
-	if options.ytdl_version() >= chrono::NaiveDate::from_ymd_opt(2023, 3, 24).unwrap() {
+	let supports_no_quiet = options.ytdl_capabilities().map_or_else(
+		|| return options.ytdl_version() >= chrono::NaiveDate::from_ymd_opt(2023, 3, 24).unwrap(),
+		|v| return v.supports("--no-quiet"),
+	);
+	if supports_no_quiet {
 		// required to get messages about when a element is skipped because of the archive
 		ytdl_args.arg("--no-quiet"); // requires a yet unreleased version of yt-dlp (higher than 2023.03.04)
 	}
 
+	let format_mode = if options.audio_only() {
+		FormatMode::AudioOnly
+	} else if options.remux_video() {
+		FormatMode::Merge
+	} else {
+		FormatMode::Single
+	};
+
+	// set the format that should be downloaded
+	ytdl_args
+		.arg("-f")
+		.arg(format_selector(format_mode, &options.format_constraints()));
+
 	// apply options to make output audio-only
 	if options.audio_only() {
-		// set the format that should be downloaded
-		ytdl_args.arg("-f").arg("bestaudio/best");
 		// set ytdl to always extract the audio, if it is not already audio-only
 		ytdl_args.arg("-x");
 		// set the output audio format
 		ytdl_args.arg("--audio-format").arg(options.get_audio_format());
-	} else {
-		// set the format that should be downloaded
-		ytdl_args.arg("-f").arg("bestvideo+bestaudio/best");
+	} else if options.remux_video() && !options.remux_after_download() {
 		// set final consistent output format
 		ytdl_args.arg("--remux-video").arg(options.get_video_format());
 	}
 
-	// embed the videoo thumbnail if available into the output container
-	ytdl_args.arg("--embed-thumbnail");
+	if options.embed_thumbnail() {
+		// embed the videoo thumbnail if available into the output container
+		ytdl_args.arg("--embed-thumbnail");
+	}
 
 	// add metadata to the container if the container supports it
-	ytdl_args.arg("--add-metadata");
+	// only when ffmpeg already post-processes the media, because adding metadata requires ffmpeg
+	if options.audio_only() || options.remux_video() {
+		ytdl_args.arg("--add-metadata");
+	}
+
+	if options.embed_thumbnail() {
+		// the following is mainly because of https://github.com/yt-dlp/yt-dlp/issues/4227
+		ytdl_args.arg("--convert-thumbnails").arg("webp>jpg"); // convert webp thumbnails to jpg
+
+		// write the media's thumbnail as a seperate file
+		ytdl_args.arg("--write-thumbnail");
+	}
 
-	// the following is mainly because of https://github.com/yt-dlp/yt-dlp/issues/4227
-	ytdl_args.arg("--convert-thumbnails").arg("webp>jpg"); // convert webp thumbnails to jpg
+	// set the order the playlist entries are downloaded in
+	match options.playlist_order() {
+		PlaylistOrder::Default => (),
+		PlaylistOrder::Reverse => {
+			ytdl_args.arg("--playlist-reverse");
+		},
+		PlaylistOrder::Random => {
+			ytdl_args.arg("--playlist-random");
+		},
+	}
 
-	// write the media's thumbnail as a seperate file
-	ytdl_args.arg("--write-thumbnail");
+	// limit which and how many entries of a playlist are downloaded
+	if let Some(playlist_items) = options.playlist_items() {
+		ytdl_args.arg("--playlist-items").arg(playlist_items);
+	}
+
+	if let Some(max_downloads) = options.max_downloads() {
+		ytdl_args.arg("--max-downloads").arg(max_downloads.to_string());
+	}
+
+	// throttle the download, so that other users of the network are not impacted
+	if let Some(limit_rate) = options.limit_rate() {
+		ytdl_args.arg("--limit-rate").arg(limit_rate.to_string());
+	}
+
+	// download fragments of fragmented formats (like HLS or DASH) in parallel
+	if let Some(concurrent_fragments) = options.concurrent_fragments() {
+		ytdl_args
+			.arg("--concurrent-fragments")
+			.arg(concurrent_fragments.to_string());
+	}
+
+	// download livestreams from their start instead of the current time, and wait for scheduled livestreams to begin
+	if options.live() {
+		ytdl_args.arg("--live-from-start");
+		ytdl_args.arg("--wait-for-video").arg(LIVE_WAIT_RETRY_SECS);
+	}
+
+	// load cookies for media that requires a login
+	match options.cookies() {
+		None => (),
+		Some(CookieSource::File(path)) => {
+			ytdl_args.arg("--cookies").arg(path);
+		},
+		Some(CookieSource::Browser(browser)) => {
+			ytdl_args.arg("--cookies-from-browser").arg(browser);
+		},
+	}
 
 	add_subs(&mut ytdl_args, options);
 
+	if options.write_info_json() {
+		// write the metadata of the media, which is read after the download to enrich the archive
+		ytdl_args.arg("--write-info-json");
+		// the metadata of playlists is not used, and would otherwise be left in the download directory
+		ytdl_args.arg("--no-write-playlist-metafiles");
+	}
+
 	add_prints(&mut ytdl_args);
 
 	// ensure ytdl is printing progress reports
@@ -116,7 +231,10 @@ pub fn assemble_ytdl_command<A: DownloadOptions>(
 	// ensure it is not in simulate mode (for example set via extra arguments)
 	ytdl_args.arg("--no-simulate");
 
-	// set the output directory for ytdl
+	// set the output directories for ytdl
+	add_paths(&mut ytdl_args, output_dir, options.subs_external());
+
+	// set the output format for ytdl
 	ytdl_args.arg("-o").arg(output_format);
 
 	// apply all extra arguments
@@ -127,19 +245,95 @@ pub fn assemble_ytdl_command<A: DownloadOptions>(
 	// apply the url to download as the last argument
 	ytdl_args.arg(options.get_url());
 
-	return Ok(ytdl_args.into());
+	return Ok((ytdl_args.into(), archive_lines));
+}
+
+/// Assemble the arguments to only download the media with `media_ids` of a url (like a playlist), empty if `media_ids` is empty
+/// Note that yt-dlp matches if any "--match-filters" matches, so this does not restrict a filter given via the extra arguments
+pub fn assemble_media_id_filter(media_ids: &[String]) -> Vec<OsString> {
+	if media_ids.is_empty() {
+		return Vec::new();
+	}
+
+	let ids = media_ids
+		.iter()
+		.map(|v| return regex::escape(v))
+		.collect::<Vec<String>>()
+		.join("|");
+
+	return vec![
+		OsString::from("--match-filters"),
+		OsString::from(format!("id~='^(?:{ids})$'")),
+	];
+}
+
+/// The kind of format that is selected by [`format_selector`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FormatMode {
+	/// Select the best audio, which is extracted from a video if there is no audio-only format
+	AudioOnly,
+	/// Select the best video and the best audio, which are merged
+	Merge,
+	/// Select a format that is already merged, because merging requires ffmpeg
+	Single,
+}
+
+/// Generate the youtube-dl format selector ("-f") for `mode` with `constraints` applied
+/// Without any constraints this results in "bestaudio/best", "bestvideo+bestaudio/best" and "best"
+fn format_selector(mode: FormatMode, constraints: &FormatConstraints) -> String {
+	// the "?" allows formats where the value is unknown
+	let filesize_filter = constraints
+		.max_filesize
+		.map(|v| return format!("[filesize<=?{v}]"))
+		.unwrap_or_default();
+	let video_filter = format!(
+		"{}{}",
+		constraints
+			.max_height
+			.map(|v| return format!("[height<=?{v}]"))
+			.unwrap_or_default(),
+		filesize_filter
+	);
+
+	let (preferred, fallback) = match mode {
+		FormatMode::AudioOnly => (
+			constraints
+				.prefer_codec
+				.map(|v| return format!("bestaudio[acodec^={v}]{filesize_filter}")),
+			format!("bestaudio{filesize_filter}/best{filesize_filter}"),
+		),
+		FormatMode::Merge => (
+			constraints
+				.prefer_codec
+				.map(|v| return format!("bestvideo[vcodec^={v}]{video_filter}+bestaudio{filesize_filter}")),
+			format!("bestvideo{video_filter}+bestaudio{filesize_filter}/best{video_filter}"),
+		),
+		FormatMode::Single => (
+			constraints
+				.prefer_codec
+				.map(|v| return format!("best[vcodec^={v}]{video_filter}")),
+			format!("best{video_filter}"),
+		),
+	};
+
+	return match preferred {
+		Some(preferred) => format!("{preferred}/{fallback}"),
+		None => fallback,
+	};
 }
 
 /// Generate the ytdl archive, if necessary
+/// With `dry_run`, no file is written and the lines the ytdl archive would contain are returned instead
 fn generate_archive<A: DownloadOptions>(
 	ytdl_args: &mut ArgsHelper,
 	connection: Option<&mut SqliteConnection>,
 	options: &A,
 	output_dir: &Path,
-) -> Result<(), crate::Error> {
+	dry_run: bool,
+) -> Result<Option<Vec<String>>, crate::Error> {
 	// no connection, nothing to generate
 	let Some(connection) = connection else {
-		return Ok(());
+		return Ok(None);
 	};
 
 	debug!("Found connection, generating archive");
@@ -147,28 +341,94 @@ fn generate_archive<A: DownloadOptions>(
 	// we have a connection, but the implementor didnt want a ytdl archive file or arguments
 	// Note: if this returns none, this means there will be no ytdlr archive file or argument,
 	// which also means that ytdl will not output a ytdl archive
+	// get the expected amount of lines before generating them, because "gen_archive" borrows the connection
+	let expected_count = options.gen_archive_count(connection);
+
 	let Some(archive_lines) = options.gen_archive(connection) else {
 		debug!("Found connection, but didnt generate any lines.");
-		return Ok(());
+		return Ok(None);
 	};
 
 	let archive_file_path = get_archive_name(output_dir);
 
+	if dry_run {
+		let lines = archive_lines.collect();
+		ytdl_args.arg("--download-archive").arg(&archive_file_path);
+
+		return Ok(Some(lines));
+	}
+
+	// write to a temporary file first and rename it afterwards, so that a crash while writing does not leave a truncated archive
+	let archive_file_path_tmp = {
+		let mut tmp = archive_file_path.clone().into_os_string();
+		tmp.push(".tmp");
+
+		PathBuf::from(tmp)
+	};
+
 	// write all lines to the file and drop the handle before giving the argument
-	{
+	let written_count = {
 		let mut archive_write_handle =
-			BufWriter::new(File::create(&archive_file_path).attach_path_err(&archive_file_path)?);
+			BufWriter::new(File::create(&archive_file_path_tmp).attach_path_err(&archive_file_path_tmp)?);
+		let mut written_count: usize = 0;
 
 		for archive_line in archive_lines {
 			archive_write_handle
 				.write_all(archive_line.as_bytes())
-				.attach_path_err(&archive_file_path)?;
+				.attach_path_err(&archive_file_path_tmp)?;
+			written_count += 1;
+		}
+
+		// make sure everything is actually on disk before renaming
+		archive_write_handle
+			.into_inner()
+			.map_err(|err| return err.into_error())
+			.attach_path_err(&archive_file_path_tmp)?
+			.sync_all()
+			.attach_path_err(&archive_file_path_tmp)?;
+
+		written_count
+	};
+
+	std::fs::rename(&archive_file_path_tmp, &archive_file_path).attach_path_err(&archive_file_path)?;
+
+	if let Some(expected_count) = expected_count {
+		if expected_count != written_count {
+			warn!(
+				"Generated ytdl archive has {} lines, but expected {} lines from the archive, some media may be downloaded again",
+				written_count, expected_count
+			);
 		}
 	}
 
 	ytdl_args.arg("--download-archive").arg(&archive_file_path);
 
-	return Ok(());
+	return Ok(None);
+}
+
+/// Add the output paths for ytdl
+/// Separates intermediate files (like fragments, thumbnails and subtitles) into sub-directories,
+/// so that only final media files are in the `output_dir`
+/// With `subs_external`, subtitles are final files and so are also put into the `output_dir`
+fn add_paths(ytdl_args: &mut ArgsHelper, output_dir: &Path, subs_external: bool) {
+	let subtitle_dir = if subs_external {
+		output_dir.to_owned()
+	} else {
+		output_dir.join(PATHS_SUBTITLE_DIR)
+	};
+
+	for (path_type, path) in [
+		("home", output_dir.to_owned()),
+		("temp", output_dir.join(PATHS_TEMP_DIR)),
+		("thumbnail", output_dir.join(PATHS_THUMBNAIL_DIR)),
+		("subtitle", subtitle_dir),
+	] {
+		let mut arg = OsString::from(path_type);
+		arg.push(":");
+		arg.push(path);
+
+		ytdl_args.arg("--paths").arg(arg);
+	}
 }
 
 /// Add subtitle arguments, if necessary
@@ -177,6 +437,16 @@ fn add_subs<A: DownloadOptions>(ytdl_args: &mut ArgsHelper, options: &A) {
 		return;
 	};
 
+	if options.subs_external() {
+		// write subtitles as separate files only, which are kept next to the media
+		ytdl_args.arg("--write-subs");
+		ytdl_args.arg("--sub-langs").arg(sub_langs);
+		// prefer formats that are widely supported by players, without requiring ffmpeg to convert
+		ytdl_args.arg("--sub-format").arg("srt/vtt/best");
+
+		return;
+	}
+
 	// add subtitles directly into the downloaded file - if available
 	ytdl_args.arg("--embed-subs");
 
@@ -205,6 +475,8 @@ fn add_prints(ytdl_args: &mut ArgsHelper) {
 	ytdl_args
 		.arg("--print")
 		.arg("before_dl:PARSE_START '%(extractor)s' '%(id)s' %(title)s");
+	// print the formats to download, to know how many separate downloads (like video and audio) the media has
+	ytdl_args.arg("--print").arg("before_dl:PARSE_FORMATS '%(format_id)s'");
 	// print once after the video got fully processed to get a consistent end point
 	ytdl_args
 		.arg("--print")
@@ -227,9 +499,12 @@ mod test {
 		TempDir,
 	};
 
-	use crate::main::download::test_utils::{
-		create_connection,
-		TestOptions,
+	use crate::{
+		main::download::test_utils::{
+			create_connection,
+			TestOptions,
+		},
+		spawn::ytdl::YtdlCapabilities,
 	};
 
 	use super::*;
@@ -307,14 +582,24 @@ mod test {
 				OsString::from("--print"),
 				OsString::from("before_dl:PARSE_START '%(extractor)s' '%(id)s' %(title)s"),
 				OsString::from("--print"),
+				OsString::from("before_dl:PARSE_FORMATS '%(format_id)s'"),
+				OsString::from("--print"),
 				OsString::from("after_video:PARSE_END '%(extractor)s' '%(id)s'"),
 				OsString::from("--print"),
 				OsString::from("after_move:MOVE '%(extractor)s' '%(id)s' %(filepath)s"),
 				OsString::from("--progress"),
 				OsString::from("--newline"),
 				OsString::from("--no-simulate"),
+				OsString::from("--paths"),
+				OsString::from(format!("home:{}", dl_dir.display())),
+				OsString::from("--paths"),
+				OsString::from(format!("temp:{}", dl_dir.join("temp").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("thumbnail:{}", dl_dir.join("thumbnail").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("subtitle:{}", dl_dir.join("subtitle").display())),
 				OsString::from("-o"),
-				dl_dir.join("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s").into(),
+				OsString::from("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s"),
 				OsString::from("someURL"),
 			]
 		);
@@ -355,14 +640,24 @@ mod test {
 				OsString::from("--print"),
 				OsString::from("before_dl:PARSE_START '%(extractor)s' '%(id)s' %(title)s"),
 				OsString::from("--print"),
+				OsString::from("before_dl:PARSE_FORMATS '%(format_id)s'"),
+				OsString::from("--print"),
 				OsString::from("after_video:PARSE_END '%(extractor)s' '%(id)s'"),
 				OsString::from("--print"),
 				OsString::from("after_move:MOVE '%(extractor)s' '%(id)s' %(filepath)s"),
 				OsString::from("--progress"),
 				OsString::from("--newline"),
 				OsString::from("--no-simulate"),
+				OsString::from("--paths"),
+				OsString::from(format!("home:{}", dl_dir.display())),
+				OsString::from("--paths"),
+				OsString::from(format!("temp:{}", dl_dir.join("temp").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("thumbnail:{}", dl_dir.join("thumbnail").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("subtitle:{}", dl_dir.join("subtitle").display())),
 				OsString::from("-o"),
-				dl_dir.join("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s").into(),
+				OsString::from("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s"),
 				OsString::from("someURL"),
 			]
 		);
@@ -420,6 +715,136 @@ mod test {
 		assert_eq!(ret, vec![OsString::from("--remux-video"), OsString::from("webm>mp4")]);
 	}
 
+	#[test]
+	fn test_playlist_order() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+
+		let has_arg = |options: &TestOptions, arg: &str| {
+			return assemble_ytdl_command(None, options)
+				.expect("Expected assemble to be ok")
+				.contains(&OsString::from(arg));
+		};
+
+		assert!(!has_arg(&options, "--playlist-reverse"));
+		assert!(!has_arg(&options, "--playlist-random"));
+
+		options.playlist_order = PlaylistOrder::Reverse;
+		assert!(has_arg(&options, "--playlist-reverse"));
+		assert!(!has_arg(&options, "--playlist-random"));
+
+		options.playlist_order = PlaylistOrder::Random;
+		assert!(!has_arg(&options, "--playlist-reverse"));
+		assert!(has_arg(&options, "--playlist-random"));
+	}
+
+	#[test]
+	fn test_write_info_json() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+
+		let has_arg = |options: &TestOptions, arg: &str| {
+			return assemble_ytdl_command(None, options)
+				.expect("Expected assemble to be ok")
+				.contains(&OsString::from(arg));
+		};
+
+		assert!(!has_arg(&options, "--write-info-json"));
+		assert!(!has_arg(&options, "--no-write-playlist-metafiles"));
+
+		options.write_info_json = true;
+		assert!(has_arg(&options, "--write-info-json"));
+		assert!(has_arg(&options, "--no-write-playlist-metafiles"));
+	}
+
+	#[test]
+	fn test_no_embed_thumbnail() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+
+		let has_arg = |options: &TestOptions, arg: &str| {
+			return assemble_ytdl_command(None, options)
+				.expect("Expected assemble to be ok")
+				.contains(&OsString::from(arg));
+		};
+
+		assert!(has_arg(&options, "--embed-thumbnail"));
+		assert!(has_arg(&options, "--write-thumbnail"));
+		assert!(has_arg(&options, "--convert-thumbnails"));
+
+		options.embed_thumbnail = false;
+		assert!(!has_arg(&options, "--embed-thumbnail"));
+		assert!(!has_arg(&options, "--write-thumbnail"));
+		assert!(!has_arg(&options, "--convert-thumbnails"));
+		// metadata should still be added
+		assert!(has_arg(&options, "--add-metadata"));
+	}
+
+	#[test]
+	fn test_no_remux() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+		options.remux_video = false;
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to be ok");
+		assert!(!ret.contains(&OsString::from("--remux-video")));
+		assert!(!ret.contains(&OsString::from("--add-metadata")));
+		assert!(ret
+			.windows(2)
+			.any(|v| return v == [OsString::from("-f"), OsString::from("best")]));
+
+		// audio-only should not be affected
+		options.audio_only = true;
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to be ok");
+		assert!(ret.contains(&OsString::from("-x")));
+		assert!(ret.contains(&OsString::from("--add-metadata")));
+	}
+
+	#[test]
+	fn test_remux_after_download() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+		options.remux_after_download = true;
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to be ok");
+		// still merged and with metadata, but not remuxed by youtube-dl
+		assert!(!ret.contains(&OsString::from("--remux-video")));
+		assert!(ret.contains(&OsString::from("--add-metadata")));
+		assert!(!ret
+			.windows(2)
+			.any(|v| return v == [OsString::from("-f"), OsString::from("best")]));
+	}
+
 	#[test]
 	fn test_extra_arguments() {
 		let (dl_dir, _tempdir) = create_dl_dir();
@@ -454,14 +879,24 @@ mod test {
 				OsString::from("--print"),
 				OsString::from("before_dl:PARSE_START '%(extractor)s' '%(id)s' %(title)s"),
 				OsString::from("--print"),
+				OsString::from("before_dl:PARSE_FORMATS '%(format_id)s'"),
+				OsString::from("--print"),
 				OsString::from("after_video:PARSE_END '%(extractor)s' '%(id)s'"),
 				OsString::from("--print"),
 				OsString::from("after_move:MOVE '%(extractor)s' '%(id)s' %(filepath)s"),
 				OsString::from("--progress"),
 				OsString::from("--newline"),
 				OsString::from("--no-simulate"),
+				OsString::from("--paths"),
+				OsString::from(format!("home:{}", dl_dir.display())),
+				OsString::from("--paths"),
+				OsString::from(format!("temp:{}", dl_dir.join("temp").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("thumbnail:{}", dl_dir.join("thumbnail").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("subtitle:{}", dl_dir.join("subtitle").display())),
 				OsString::from("-o"),
-				dl_dir.join("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s").into(),
+				OsString::from("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s"),
 				OsString::from("hello1"),
 				OsString::from("someURL"),
 			]
@@ -476,7 +911,7 @@ mod test {
 			Vec::default(),
 			test_dir.clone(),
 			"someURL".to_owned(),
-			vec!["line 1".to_owned(), "line 2".to_owned()],
+			vec!["line 1\n".to_owned(), "line 2\n".to_owned()],
 		);
 
 		let ret = assemble_ytdl_command(Some(&mut connection), &options);
@@ -486,11 +921,19 @@ mod test {
 
 		let pid = std::process::id();
 
+		let archive_path = test_dir.join(format!("ytdl_archive_{pid}.txt"));
+		assert_eq!(
+			"line 1\nline 2\n",
+			std::fs::read_to_string(&archive_path).expect("Expected archive file to be readable")
+		);
+		// the temporary file should have been renamed
+		assert!(!test_dir.join(format!("ytdl_archive_{pid}.txt.tmp")).exists());
+
 		assert_eq!(
 			ret,
 			vec![
 				OsString::from("--download-archive"),
-				test_dir.join(format!("ytdl_archive_{pid}.txt")).as_os_str().to_owned(),
+				archive_path.as_os_str().to_owned(),
 				OsString::from("--no-quiet"),
 				OsString::from("-f"),
 				OsString::from("bestvideo+bestaudio/best"),
@@ -506,22 +949,84 @@ mod test {
 				OsString::from("--print"),
 				OsString::from("before_dl:PARSE_START '%(extractor)s' '%(id)s' %(title)s"),
 				OsString::from("--print"),
+				OsString::from("before_dl:PARSE_FORMATS '%(format_id)s'"),
+				OsString::from("--print"),
 				OsString::from("after_video:PARSE_END '%(extractor)s' '%(id)s'"),
 				OsString::from("--print"),
 				OsString::from("after_move:MOVE '%(extractor)s' '%(id)s' %(filepath)s"),
 				OsString::from("--progress"),
 				OsString::from("--newline"),
 				OsString::from("--no-simulate"),
+				OsString::from("--paths"),
+				OsString::from(format!("home:{}", test_dir.display())),
+				OsString::from("--paths"),
+				OsString::from(format!("temp:{}", test_dir.join("temp").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("thumbnail:{}", test_dir.join("thumbnail").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("subtitle:{}", test_dir.join("subtitle").display())),
 				OsString::from("-o"),
-				test_dir
-					.join("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s")
-					.as_os_str()
-					.to_owned(),
+				OsString::from("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s"),
 				OsString::from("someURL"),
 			]
 		);
 	}
 
+	#[test]
+	fn test_archive_dry_run() {
+		let (mut connection, _tempdir, test_dir) = create_connection();
+		let options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			test_dir.clone(),
+			"someURL".to_owned(),
+			vec!["line 1\n".to_owned(), "line 2\n".to_owned()],
+		);
+
+		let (args, archive_lines) =
+			assemble_ytdl_command_dry_run(Some(&mut connection), &options).expect("Expected assemble to be ok");
+
+		let archive_path = test_dir.join(format!("ytdl_archive_{}.txt", std::process::id()));
+		// nothing should have been written
+		assert!(!archive_path.exists());
+		assert_eq!(Some(vec!["line 1\n".to_owned(), "line 2\n".to_owned()]), archive_lines);
+
+		// the arguments should be the same as without dry-run
+		let ret = assemble_ytdl_command(Some(&mut connection), &options).expect("Expected assemble to be ok");
+		assert_eq!(ret, args);
+
+		// no ytdl archive without a connection
+		let (_, archive_lines) = assemble_ytdl_command_dry_run(None, &options).expect("Expected assemble to be ok");
+		assert_eq!(None, archive_lines);
+	}
+
+	#[test]
+	fn test_subs_external() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+		options.sub_langs = Some("en".to_owned());
+		options.subs_external = true;
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to be ok");
+		assert!(!ret.contains(&OsString::from("--embed-subs")));
+		assert!(!ret.contains(&OsString::from("--ppa")));
+		assert!(ret.contains(&OsString::from("--write-subs")));
+		assert!(ret
+			.windows(2)
+			.any(|v| return v == [OsString::from("--sub-format"), OsString::from("srt/vtt/best")]));
+
+		// the subtitles are kept next to the media
+		let mut subtitle_path = OsString::from("subtitle:");
+		subtitle_path.push(&dl_dir);
+		assert!(ret.contains(&subtitle_path));
+	}
+
 	#[test]
 	fn test_all_options_together() {
 		let (mut connection, _tempdir, test_dir) = create_connection();
@@ -572,17 +1077,24 @@ mod test {
 				OsString::from("--print"),
 				OsString::from("before_dl:PARSE_START '%(extractor)s' '%(id)s' %(title)s"),
 				OsString::from("--print"),
+				OsString::from("before_dl:PARSE_FORMATS '%(format_id)s'"),
+				OsString::from("--print"),
 				OsString::from("after_video:PARSE_END '%(extractor)s' '%(id)s'"),
 				OsString::from("--print"),
 				OsString::from("after_move:MOVE '%(extractor)s' '%(id)s' %(filepath)s"),
 				OsString::from("--progress"),
 				OsString::from("--newline"),
 				OsString::from("--no-simulate"),
+				OsString::from("--paths"),
+				OsString::from(format!("home:{}", test_dir.display())),
+				OsString::from("--paths"),
+				OsString::from(format!("temp:{}", test_dir.join("temp").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("thumbnail:{}", test_dir.join("thumbnail").display())),
+				OsString::from("--paths"),
+				OsString::from(format!("subtitle:{}", test_dir.join("subtitle").display())),
 				OsString::from("-o"),
-				test_dir
-					.join("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s")
-					.as_os_str()
-					.to_owned(),
+				OsString::from("'%(extractor)s'-'%(id)s'-%(title).150B.%(ext)s"),
 				OsString::from("hello1"),
 				OsString::from("someURL"),
 			]
@@ -632,4 +1144,270 @@ mod test {
 			assert!(ret.contains(&OsString::from("--no-quiet")));
 		}
 	}
+
+	#[test]
+	fn test_playlist_selection() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		assert!(!ret.contains(&OsString::from("--playlist-items")));
+		assert!(!ret.contains(&OsString::from("--max-downloads")));
+
+		options.playlist_items = Some("1-3,7".to_owned());
+		options.max_downloads = Some(2);
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		let index = ret
+			.iter()
+			.position(|v| return v == "--playlist-items")
+			.expect("Expected \"--playlist-items\" to be added");
+		assert_eq!(OsString::from("1-3,7"), ret[index + 1]);
+		let index = ret
+			.iter()
+			.position(|v| return v == "--max-downloads")
+			.expect("Expected \"--max-downloads\" to be added");
+		assert_eq!(OsString::from("2"), ret[index + 1]);
+	}
+
+	#[test]
+	fn test_limit_rate() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		assert!(!ret.contains(&OsString::from("--limit-rate")));
+
+		options.limit_rate = Some(2 * 1024 * 1024);
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		let index = ret
+			.iter()
+			.position(|v| return v == "--limit-rate")
+			.expect("Expected \"--limit-rate\" to be added");
+		assert_eq!(OsString::from("2097152"), ret[index + 1]);
+	}
+
+	#[test]
+	fn test_format_selector() {
+		let none = FormatConstraints::default();
+		assert_eq!("bestaudio/best", format_selector(FormatMode::AudioOnly, &none));
+		assert_eq!("bestvideo+bestaudio/best", format_selector(FormatMode::Merge, &none));
+		assert_eq!("best", format_selector(FormatMode::Single, &none));
+
+		let height = FormatConstraints {
+			max_height: Some(720),
+			..Default::default()
+		};
+		// audio has no height
+		assert_eq!("bestaudio/best", format_selector(FormatMode::AudioOnly, &height));
+		assert_eq!(
+			"bestvideo[height<=?720]+bestaudio/best[height<=?720]",
+			format_selector(FormatMode::Merge, &height)
+		);
+		assert_eq!("best[height<=?720]", format_selector(FormatMode::Single, &height));
+
+		let all = FormatConstraints {
+			max_height:   Some(1080),
+			max_filesize: Some(104_857_600),
+			prefer_codec: Some("avc1"),
+		};
+		// the preferred codec applies to the audio codec for audio-only
+		let audio = FormatConstraints {
+			prefer_codec: Some("opus"),
+			..all
+		};
+		assert_eq!(
+			"bestaudio[acodec^=opus][filesize<=?104857600]/bestaudio[filesize<=?104857600]/best[filesize<=?104857600]",
+			format_selector(FormatMode::AudioOnly, &audio)
+		);
+		assert_eq!(
+			"bestvideo[vcodec^=avc1][height<=?1080][filesize<=?104857600]+bestaudio[filesize<=?104857600]/bestvideo[height<=?1080][filesize<=?104857600]+bestaudio[filesize<=?104857600]/best[height<=?1080][filesize<=?104857600]",
+			format_selector(FormatMode::Merge, &all)
+		);
+		assert_eq!(
+			"best[vcodec^=avc1][height<=?1080][filesize<=?104857600]/best[height<=?1080][filesize<=?104857600]",
+			format_selector(FormatMode::Single, &all)
+		);
+	}
+
+	#[test]
+	fn test_format_constraints() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+		options.max_height = Some(480);
+		options.prefer_codec = Some("vp9".to_owned());
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		let index = ret
+			.iter()
+			.position(|v| return v == "-f")
+			.expect("Expected \"-f\" to be added");
+		assert_eq!(
+			OsString::from(
+				"bestvideo[vcodec^=vp9][height<=?480]+bestaudio/bestvideo[height<=?480]+bestaudio/best[height<=?480]"
+			),
+			ret[index + 1]
+		);
+	}
+
+	#[test]
+	fn test_assemble_media_id_filter() {
+		assert!(assemble_media_id_filter(&[]).is_empty());
+
+		assert_eq!(
+			vec![
+				OsString::from("--match-filters"),
+				OsString::from("id~='^(?:someid1|some\\-id\\.2)$'")
+			],
+			assemble_media_id_filter(&["someid1".to_owned(), "some-id.2".to_owned()])
+		);
+	}
+
+	#[test]
+	fn test_concurrent_fragments() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		assert!(!ret.contains(&OsString::from("--concurrent-fragments")));
+
+		options.concurrent_fragments = Some(4);
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		let index = ret
+			.iter()
+			.position(|v| return v == "--concurrent-fragments")
+			.expect("Expected \"--concurrent-fragments\" to be added");
+		assert_eq!(OsString::from("4"), ret[index + 1]);
+	}
+
+	#[test]
+	fn test_live() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		assert!(!ret.contains(&OsString::from("--live-from-start")));
+		assert!(!ret.contains(&OsString::from("--wait-for-video")));
+
+		options.live = true;
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		assert!(ret.contains(&OsString::from("--live-from-start")));
+		let index = ret
+			.iter()
+			.position(|v| return v == "--wait-for-video")
+			.expect("Expected \"--wait-for-video\" to be added");
+		assert_eq!(OsString::from(LIVE_WAIT_RETRY_SECS), ret[index + 1]);
+	}
+
+	#[test]
+	fn test_cookies() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+
+		let mut options = TestOptions::new_assemble(
+			false,
+			Vec::default(),
+			dl_dir.clone(),
+			"someURL".to_owned(),
+			Vec::default(),
+		);
+		options.cookies_file = Some(PathBuf::from("/path/to/cookies.txt"));
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		let index = ret
+			.iter()
+			.position(|v| return v == "--cookies")
+			.expect("Expected \"--cookies\" to be added");
+		assert_eq!(OsString::from("/path/to/cookies.txt"), ret[index + 1]);
+		assert!(!ret.contains(&OsString::from("--cookies-from-browser")));
+
+		options.cookies_file = None;
+		options.cookies_browser = Some("firefox".to_owned());
+
+		let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+		let index = ret
+			.iter()
+			.position(|v| return v == "--cookies-from-browser")
+			.expect("Expected \"--cookies-from-browser\" to be added");
+		assert_eq!(OsString::from("firefox"), ret[index + 1]);
+		assert!(!ret.contains(&OsString::from("--cookies")));
+	}
+
+	#[test]
+	fn test_quiet_capabilities() {
+		let (dl_dir, _tempdir) = create_dl_dir();
+
+		// capabilities take precedence over a new version
+		{
+			let options = TestOptions::new_assemble(
+				true,
+				Vec::default(),
+				dl_dir.clone(),
+				"someURL".to_owned(),
+				Vec::default(),
+			)
+			.with_capabilities(YtdlCapabilities::from_help(
+				"    -f, --format FORMAT    Video format code",
+			));
+
+			let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+
+			assert!(!ret.contains(&OsString::from("--no-quiet")));
+		}
+
+		// capabilities take precedence over a old version
+		{
+			#[allow(clippy::zero_prefixed_literal)]
+			let options = TestOptions::new_assemble(
+				true,
+				Vec::default(),
+				dl_dir.clone(),
+				"someURL".to_owned(),
+				Vec::default(),
+			)
+			.with_version(chrono::NaiveDate::from_ymd_opt(2023, 3, 04).unwrap())
+			.with_capabilities(YtdlCapabilities::from_help("    --no-quiet    Deactivate quiet mode"));
+
+			let ret = assemble_ytdl_command(None, &options).expect("Expected assemble to pass");
+
+			assert!(ret.contains(&OsString::from("--no-quiet")));
+		}
+	}
 }
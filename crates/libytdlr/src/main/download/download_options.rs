@@ -1,18 +1,68 @@
 //! Module for various Context traits
 
 use std::{
-	ffi::OsStr,
-	path::Path,
+	ffi::{
+		OsStr,
+		OsString,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
 };
 
 use diesel::SqliteConnection;
 
+use crate::spawn::ytdl::{
+	ytdl_parse_version_naivedate,
+	ytdl_version,
+	YtdlCapabilities,
+	YTDL_BIN_NAME,
+};
+
 /// The Format argument to use for the command.
 ///
 /// See [yt-dlp Post-Processing Options](https://github.com/yt-dlp/yt-dlp?tab=readme-ov-file#post-processing-options) `--remux-video`
 /// for possible rules.
 pub type FormatArgument<'a> = &'a str;
 
+/// The order in which the entries of a playlist are downloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaylistOrder {
+	/// Download in the order the playlist lists them
+	#[default]
+	Default,
+	/// Download in the reverse order the playlist lists them (like "oldest first" for a channel)
+	Reverse,
+	/// Download in a random order
+	Random,
+}
+
+/// Where youtube-dl should load cookies from, for media that requires a login (like age-restricted or members-only media)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieSource<'a> {
+	/// Load cookies from a Netscape-formatted cookies file (yt-dlp "--cookies")
+	File(&'a Path),
+	/// Load cookies from a browser (yt-dlp "--cookies-from-browser"), like "firefox" or "chrome:Profile 1"
+	Browser(&'a str),
+}
+
+/// Constraints for the format youtube-dl selects, see [`DownloadOptions::format_constraints`]
+///
+/// Media for which no format matches the constraints fails to download
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FormatConstraints<'a> {
+	/// The maximal video height in pixels (like "720")
+	/// Formats with a unknown height are still allowed
+	pub max_height:   Option<u32>,
+	/// The maximal size in bytes of each format downloaded (video and audio are separate formats when merging)
+	/// Formats with a unknown size are still allowed
+	pub max_filesize: Option<u64>,
+	/// The codec to prefer, matched as a prefix of the video codec (or the audio codec for audio-only), like "avc1", "vp9" or "opus"
+	/// Other codecs are used if no format with this codec is available
+	pub prefer_codec: Option<&'a str>,
+}
+
 /// Options specific for the [`crate::main::download::download_single`] function
 pub trait DownloadOptions {
 	/// Get if the "audio-only" flag should be added
@@ -32,6 +82,14 @@ pub trait DownloadOptions {
 	fn gen_archive<'a>(&'a self, connection: &'a mut SqliteConnection)
 		-> Option<Box<dyn Iterator<Item = String> + 'a>>;
 
+	/// Get the amount of lines [`DownloadOptions::gen_archive`] is expected to output
+	/// Used to verify that the written ytdl archive is complete
+	///
+	/// Returning [None] skips the verification
+	fn gen_archive_count(&self, _connection: &mut SqliteConnection) -> Option<usize> {
+		return None;
+	}
+
 	/// Get the URL to download
 	fn get_url(&self) -> &str;
 
@@ -48,9 +106,20 @@ pub trait DownloadOptions {
 	/// [None] disables adding subtitles
 	fn sub_langs(&self) -> Option<&str>;
 
+	/// Get whether subtitles (see [`DownloadOptions::sub_langs`]) are written as separate files next to the media instead of being embedded
+	/// The subtitle files are named like the media, with the language and subtitle extension added (like "media.en.vtt")
+	fn subs_external(&self) -> bool;
+
+	/// Get whether to write the ".info.json" of every media next to it, which is used to add metadata (like the uploader) to the archive
+	/// see [`crate::data::cache::info_json::InfoJson`]
+	fn write_info_json(&self) -> bool;
+
 	/// Get the current youtube-dl version in use as a chrono date
 	fn ytdl_version(&self) -> chrono::NaiveDate;
 
+	/// Get the youtube-dl binary to spawn, like [`YTDL_BIN_NAME`] or the binary returned by [`crate::spawn::ytdl::require_ytdl_installed`]
+	fn ytdl_bin(&self) -> &OsStr;
+
 	/// Get the format for audio-only/audio-extract downloads
 	///
 	/// Only set extensions supported by youtube-dl
@@ -60,4 +129,571 @@ pub trait DownloadOptions {
 	///
 	/// Only set extensions supported by youtube-dl
 	fn get_video_format(&self) -> FormatArgument;
+
+	/// Get whether to error if any youtube-dl line could not be parsed
+	/// With this returning `false`, only a warning is printed once a threshold of unparsed lines has been reached
+	fn strict_parse(&self) -> bool;
+
+	/// Get the maximum amount of bytes that may be downloaded for the current URL
+	/// Once reached, no new media will be started and the command will be stopped
+	/// [None] disables the limit
+	fn max_download_bytes(&self) -> Option<u64>;
+
+	/// Get the time youtube-dl may have no output while downloading or post-processing a media before it is considered stalled
+	/// [None] disables the stall detection
+	fn stall_timeout(&self) -> Option<std::time::Duration>;
+
+	/// Get whether to stop the command once a media has been detected as stalled
+	/// The stalled media is then retried like a transient error (see [`DownloadOptions::retries`])
+	/// Only has a effect if [`DownloadOptions::stall_timeout`] is set
+	fn stall_kill(&self) -> bool;
+
+	/// Get the order in which the entries of a playlist should be downloaded
+	fn playlist_order(&self) -> PlaylistOrder;
+
+	/// Get whether to embed the thumbnail into the media (and write it as a separate file)
+	/// With this returning `false`, no thumbnail is downloaded at all
+	fn embed_thumbnail(&self) -> bool;
+
+	/// Get whether to merge and remux videos into [`DownloadOptions::get_video_format`] and add metadata to them
+	/// With this returning `false`, a single pre-merged format is downloaded as-is, which does not require ffmpeg
+	/// Only has a effect if [`DownloadOptions::audio_only`] is `false`, because extracting audio always requires ffmpeg
+	fn remux_video(&self) -> bool;
+
+	/// Get whether videos are remuxed by the caller after the download (like with [`crate::main::remux::remux_if_needed`]) instead of by youtube-dl
+	/// With this returning `true`, videos are still merged, but kept in the container youtube-dl merged them into
+	/// Only has a effect if [`DownloadOptions::remux_video`] is `true`
+	fn remux_after_download(&self) -> bool;
+
+	/// Get the options supported by the youtube-dl in use, see [`crate::spawn::ytdl::ytdl_capabilities`]
+	/// With this returning [`None`], optional options are gated by [`DownloadOptions::ytdl_version`] only
+	fn ytdl_capabilities(&self) -> Option<&YtdlCapabilities>;
+
+	/// Get where youtube-dl should load cookies from
+	/// [None] disables loading cookies
+	fn cookies(&self) -> Option<CookieSource<'_>>;
+
+	/// Get which entries of a playlist to download, in the yt-dlp "--playlist-items" format (like "1,3,5-7" or "-5:")
+	/// [None] downloads all entries
+	fn playlist_items(&self) -> Option<&str>;
+
+	/// Get the maximal count of media to download for the current URL (yt-dlp "--max-downloads")
+	/// [None] does not limit the count
+	fn max_downloads(&self) -> Option<usize>;
+
+	/// Get the maximal download rate in bytes per second (yt-dlp "--limit-rate")
+	/// [None] does not limit the rate
+	fn limit_rate(&self) -> Option<u64>;
+
+	/// Get the constraints for the format to download, which are translated into a youtube-dl format selector ("-f")
+	fn format_constraints(&self) -> FormatConstraints<'_>;
+
+	/// Get how many fragments of a fragmented format (like HLS or DASH) youtube-dl downloads at the same time (yt-dlp "--concurrent-fragments")
+	/// [None] uses the youtube-dl default
+	fn concurrent_fragments(&self) -> Option<usize>;
+
+	/// Get how often media that failed with a transient error (like a timeout) are retried, see [`crate::error::DownloadError::is_transient`]
+	/// Only the failed media are downloaded again, with a increasing wait time between the retries
+	fn retries(&self) -> usize;
+
+	/// Get whether livestreams are downloaded from their start, waiting for scheduled livestreams to begin (yt-dlp "--live-from-start" and "--wait-for-video")
+	/// The progress of livestreams is reported as [`super::DownloadProgress::SingleLiveProgress`], because their total size is not known
+	fn live(&self) -> bool;
+}
+
+/// Builder for [`BasicDownloadOptions`], a ready-to-use [`DownloadOptions`] implementation for library consumers
+///
+/// All options not set use the same defaults as the ytdlr binary, no archive is used
+///
+/// ```no_run
+/// use libytdlr::main::download::{download_single, DownloadOptionsBuilder};
+///
+/// let options = DownloadOptionsBuilder::new("https://www.youtube.com/watch?v=-----------", "/tmp/download")
+/// 	.with_audio_only(true)
+/// 	.build();
+/// let mut media = Vec::new();
+/// download_single(None, &options, |_| {}, &mut media, None).expect("Download failed");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadOptionsBuilder {
+	/// The options being built
+	options:      BasicDownloadOptions,
+	/// The youtube-dl version, detected in [`DownloadOptionsBuilder::build`] if not set
+	ytdl_version: Option<chrono::NaiveDate>,
+}
+
+impl DownloadOptionsBuilder {
+	/// Create a new builder to download `url` into `download_path`
+	#[must_use]
+	pub fn new<U: Into<String>, P: Into<PathBuf>>(url: U, download_path: P) -> Self {
+		return Self {
+			options:      BasicDownloadOptions {
+				url:                  url.into(),
+				download_path:        download_path.into(),
+				audio_only:           false,
+				audio_format:         "best".to_owned(),
+				video_format:         "mkv".to_owned(),
+				sub_langs:            None,
+				subs_external:        false,
+				write_info_json:      false,
+				extra_arguments:      Vec::new(),
+				print_command_log:    false,
+				save_command_log:     false,
+				embed_thumbnail:      true,
+				remux_video:          true,
+				remux_after_download: false,
+				max_height:           None,
+				max_filesize:         None,
+				prefer_codec:         None,
+				concurrent_fragments: None,
+				retries:              0,
+				live:                 false,
+				ytdl_version:         *super::MINIMAL_YTDL_VERSION,
+				ytdl_bin:             OsString::from(YTDL_BIN_NAME),
+			},
+			ytdl_version: None,
+		};
+	}
+
+	/// Set the URL to download
+	#[must_use]
+	pub fn with_url<U: Into<String>>(mut self, url: U) -> Self {
+		self.options.url = url.into();
+
+		return self;
+	}
+
+	/// Set the path to where the media should be downloaded to
+	#[must_use]
+	pub fn with_download_path<P: Into<PathBuf>>(mut self, download_path: P) -> Self {
+		self.options.download_path = download_path.into();
+
+		return self;
+	}
+
+	/// Set whether to only download the audio, see [`DownloadOptions::audio_only`]
+	#[must_use]
+	pub fn with_audio_only(mut self, audio_only: bool) -> Self {
+		self.options.audio_only = audio_only;
+
+		return self;
+	}
+
+	/// Set the formats for audio-only and video downloads, see [`DownloadOptions::get_audio_format`] and [`DownloadOptions::get_video_format`]
+	/// Defaults to "best" and "mkv"
+	#[must_use]
+	pub fn with_formats<A: Into<String>, V: Into<String>>(mut self, audio_format: A, video_format: V) -> Self {
+		self.options.audio_format = audio_format.into();
+		self.options.video_format = video_format.into();
+
+		return self;
+	}
+
+	/// Set which subtitle languages to download, see [`DownloadOptions::sub_langs`]
+	#[must_use]
+	pub fn with_sub_langs<S: Into<String>>(mut self, sub_langs: Option<S>) -> Self {
+		self.options.sub_langs = sub_langs.map(Into::into);
+
+		return self;
+	}
+
+	/// Set whether to write subtitles as separate files, see [`DownloadOptions::subs_external`]
+	#[must_use]
+	pub fn with_subs_external(mut self, subs_external: bool) -> Self {
+		self.options.subs_external = subs_external;
+
+		return self;
+	}
+
+	/// Set whether to write the ".info.json" of every media, see [`DownloadOptions::write_info_json`]
+	#[must_use]
+	pub fn with_write_info_json(mut self, write_info_json: bool) -> Self {
+		self.options.write_info_json = write_info_json;
+
+		return self;
+	}
+
+	/// Add a extra argument to the youtube-dl command, see [`DownloadOptions::extra_ytdl_arguments`]
+	#[must_use]
+	pub fn with_extra_argument<A: Into<OsString>>(mut self, argument: A) -> Self {
+		self.options.extra_arguments.push(argument.into());
+
+		return self;
+	}
+
+	/// Set whether to print and save the youtube-dl output, see [`DownloadOptions::print_command_log`] and [`DownloadOptions::save_command_log`]
+	#[must_use]
+	pub fn with_command_log(mut self, print: bool, save: bool) -> Self {
+		self.options.print_command_log = print;
+		self.options.save_command_log = save;
+
+		return self;
+	}
+
+	/// Set whether to embed the thumbnail, see [`DownloadOptions::embed_thumbnail`]
+	#[must_use]
+	pub fn with_embed_thumbnail(mut self, embed_thumbnail: bool) -> Self {
+		self.options.embed_thumbnail = embed_thumbnail;
+
+		return self;
+	}
+
+	/// Set whether to remux videos, see [`DownloadOptions::remux_video`]
+	#[must_use]
+	pub fn with_remux_video(mut self, remux_video: bool) -> Self {
+		self.options.remux_video = remux_video;
+
+		return self;
+	}
+
+	/// Set whether videos are remuxed after the download, see [`DownloadOptions::remux_after_download`]
+	#[must_use]
+	pub fn with_remux_after_download(mut self, remux_after_download: bool) -> Self {
+		self.options.remux_after_download = remux_after_download;
+
+		return self;
+	}
+
+	/// Set the maximal video height in pixels, see [`FormatConstraints::max_height`]
+	#[must_use]
+	pub fn with_max_height(mut self, max_height: Option<u32>) -> Self {
+		self.options.max_height = max_height;
+
+		return self;
+	}
+
+	/// Set the maximal size in bytes of each format, see [`FormatConstraints::max_filesize`]
+	#[must_use]
+	pub fn with_max_filesize(mut self, max_filesize: Option<u64>) -> Self {
+		self.options.max_filesize = max_filesize;
+
+		return self;
+	}
+
+	/// Set the codec to prefer, see [`FormatConstraints::prefer_codec`]
+	#[must_use]
+	pub fn with_prefer_codec<S: Into<String>>(mut self, prefer_codec: Option<S>) -> Self {
+		self.options.prefer_codec = prefer_codec.map(Into::into);
+
+		return self;
+	}
+
+	/// Set how many fragments are downloaded at the same time, see [`DownloadOptions::concurrent_fragments`]
+	#[must_use]
+	pub fn with_concurrent_fragments(mut self, concurrent_fragments: Option<usize>) -> Self {
+		self.options.concurrent_fragments = concurrent_fragments;
+
+		return self;
+	}
+
+	/// Set how often media that failed with a transient error are retried, see [`DownloadOptions::retries`]
+	#[must_use]
+	pub fn with_retries(mut self, retries: usize) -> Self {
+		self.options.retries = retries;
+
+		return self;
+	}
+
+	/// Set whether livestreams are downloaded from their start, see [`DownloadOptions::live`]
+	#[must_use]
+	pub fn with_live(mut self, live: bool) -> Self {
+		self.options.live = live;
+
+		return self;
+	}
+
+	/// Set the youtube-dl binary to spawn, see [`DownloadOptions::ytdl_bin`]
+	#[must_use]
+	pub fn with_ytdl_bin<B: Into<OsString>>(mut self, ytdl_bin: B) -> Self {
+		self.options.ytdl_bin = ytdl_bin.into();
+
+		return self;
+	}
+
+	/// Set the youtube-dl version in use, see [`DownloadOptions::ytdl_version`]
+	#[must_use]
+	pub fn with_ytdl_version(mut self, ytdl_version: chrono::NaiveDate) -> Self {
+		self.ytdl_version = Some(ytdl_version);
+
+		return self;
+	}
+
+	/// Build the options
+	/// If no youtube-dl version has been set, it is detected by running the set youtube-dl binary,
+	/// falling back to [`super::MINIMAL_YTDL_VERSION`] if it cannot be detected
+	#[must_use]
+	pub fn build(self) -> BasicDownloadOptions {
+		let ytdl_version = self.ytdl_version.unwrap_or_else(|| {
+			return ytdl_version(&self.options.ytdl_bin)
+				.and_then(|v| return ytdl_parse_version_naivedate(&v))
+				.unwrap_or_else(|err| {
+					warn!(
+						"Could not determine youtube-dl version, using the minimal version: {}",
+						err
+					);
+
+					return *super::MINIMAL_YTDL_VERSION;
+				});
+		});
+
+		return BasicDownloadOptions {
+			ytdl_version,
+			..self.options
+		};
+	}
+}
+
+/// Basic [`DownloadOptions`] implementation, created with [`DownloadOptionsBuilder`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicDownloadOptions {
+	/// See [`DownloadOptions::get_url`]
+	url:                  String,
+	/// See [`DownloadOptions::download_path`]
+	download_path:        PathBuf,
+	/// See [`DownloadOptions::audio_only`]
+	audio_only:           bool,
+	/// See [`DownloadOptions::get_audio_format`]
+	audio_format:         String,
+	/// See [`DownloadOptions::get_video_format`]
+	video_format:         String,
+	/// See [`DownloadOptions::sub_langs`]
+	sub_langs:            Option<String>,
+	/// See [`DownloadOptions::subs_external`]
+	subs_external:        bool,
+	/// See [`DownloadOptions::write_info_json`]
+	write_info_json:      bool,
+	/// See [`DownloadOptions::extra_ytdl_arguments`]
+	extra_arguments:      Vec<OsString>,
+	/// See [`DownloadOptions::print_command_log`]
+	print_command_log:    bool,
+	/// See [`DownloadOptions::save_command_log`]
+	save_command_log:     bool,
+	/// See [`DownloadOptions::embed_thumbnail`]
+	embed_thumbnail:      bool,
+	/// See [`DownloadOptions::remux_video`]
+	remux_video:          bool,
+	/// See [`DownloadOptions::remux_after_download`]
+	remux_after_download: bool,
+	/// See [`FormatConstraints::max_height`]
+	max_height:           Option<u32>,
+	/// See [`FormatConstraints::max_filesize`]
+	max_filesize:         Option<u64>,
+	/// See [`FormatConstraints::prefer_codec`]
+	prefer_codec:         Option<String>,
+	/// See [`DownloadOptions::concurrent_fragments`]
+	concurrent_fragments: Option<usize>,
+	/// See [`DownloadOptions::retries`]
+	retries:              usize,
+	/// See [`DownloadOptions::live`]
+	live:                 bool,
+	/// See [`DownloadOptions::ytdl_version`]
+	ytdl_version:         chrono::NaiveDate,
+	/// See [`DownloadOptions::ytdl_bin`]
+	ytdl_bin:             OsString,
+}
+
+impl DownloadOptions for BasicDownloadOptions {
+	fn audio_only(&self) -> bool {
+		return self.audio_only;
+	}
+
+	fn extra_ytdl_arguments(&self) -> Vec<&OsStr> {
+		return self.extra_arguments.iter().map(OsString::as_os_str).collect();
+	}
+
+	fn download_path(&self) -> &Path {
+		return &self.download_path;
+	}
+
+	fn gen_archive<'a>(
+		&'a self,
+		_connection: &'a mut SqliteConnection,
+	) -> Option<Box<dyn Iterator<Item = String> + 'a>> {
+		return None;
+	}
+
+	fn get_url(&self) -> &str {
+		return &self.url;
+	}
+
+	fn print_command_log(&self) -> bool {
+		return self.print_command_log;
+	}
+
+	fn save_command_log(&self) -> bool {
+		return self.save_command_log;
+	}
+
+	fn sub_langs(&self) -> Option<&str> {
+		return self.sub_langs.as_deref();
+	}
+
+	fn subs_external(&self) -> bool {
+		return self.subs_external;
+	}
+
+	fn write_info_json(&self) -> bool {
+		return self.write_info_json;
+	}
+
+	fn ytdl_version(&self) -> chrono::NaiveDate {
+		return self.ytdl_version;
+	}
+
+	fn ytdl_bin(&self) -> &OsStr {
+		return &self.ytdl_bin;
+	}
+
+	fn get_audio_format(&self) -> FormatArgument {
+		return &self.audio_format;
+	}
+
+	fn get_video_format(&self) -> FormatArgument {
+		return &self.video_format;
+	}
+
+	fn strict_parse(&self) -> bool {
+		return false;
+	}
+
+	fn max_download_bytes(&self) -> Option<u64> {
+		return None;
+	}
+
+	fn stall_timeout(&self) -> Option<std::time::Duration> {
+		return None;
+	}
+
+	fn stall_kill(&self) -> bool {
+		return false;
+	}
+
+	fn playlist_order(&self) -> PlaylistOrder {
+		return PlaylistOrder::Default;
+	}
+
+	fn embed_thumbnail(&self) -> bool {
+		return self.embed_thumbnail;
+	}
+
+	fn remux_video(&self) -> bool {
+		return self.remux_video;
+	}
+
+	fn remux_after_download(&self) -> bool {
+		return self.remux_after_download;
+	}
+
+	fn ytdl_capabilities(&self) -> Option<&YtdlCapabilities> {
+		return None;
+	}
+
+	fn cookies(&self) -> Option<CookieSource<'_>> {
+		return None;
+	}
+
+	fn playlist_items(&self) -> Option<&str> {
+		return None;
+	}
+
+	fn max_downloads(&self) -> Option<usize> {
+		return None;
+	}
+
+	fn limit_rate(&self) -> Option<u64> {
+		return None;
+	}
+
+	fn format_constraints(&self) -> FormatConstraints<'_> {
+		return FormatConstraints {
+			max_height:   self.max_height,
+			max_filesize: self.max_filesize,
+			prefer_codec: self.prefer_codec.as_deref(),
+		};
+	}
+
+	fn concurrent_fragments(&self) -> Option<usize> {
+		return self.concurrent_fragments;
+	}
+
+	fn retries(&self) -> usize {
+		return self.retries;
+	}
+
+	fn live(&self) -> bool {
+		return self.live;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_builder() {
+		let version = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+		let options = DownloadOptionsBuilder::new("https://example.com/1", "/tmp/download")
+			.with_audio_only(true)
+			.with_formats("opus", "mp4")
+			.with_sub_langs(Some("en"))
+			.with_write_info_json(true)
+			.with_extra_argument("--sleep-interval")
+			.with_extra_argument("5")
+			.with_max_height(Some(720))
+			.with_prefer_codec(Some("opus"))
+			.with_concurrent_fragments(Some(4))
+			.with_retries(3)
+			.with_live(true)
+			.with_ytdl_bin("/venv/bin/yt-dlp")
+			.with_ytdl_version(version)
+			.build();
+
+		assert_eq!("https://example.com/1", options.get_url());
+		assert_eq!(Path::new("/tmp/download"), options.download_path());
+		assert!(options.audio_only());
+		assert_eq!("opus", options.get_audio_format());
+		assert_eq!("mp4", options.get_video_format());
+		assert_eq!(Some("en"), options.sub_langs());
+		assert!(options.write_info_json());
+		assert_eq!(
+			vec![OsStr::new("--sleep-interval"), OsStr::new("5")],
+			options.extra_ytdl_arguments()
+		);
+		assert_eq!(version, options.ytdl_version());
+		assert_eq!(OsStr::new("/venv/bin/yt-dlp"), options.ytdl_bin());
+		assert_eq!(
+			FormatConstraints {
+				max_height:   Some(720),
+				max_filesize: None,
+				prefer_codec: Some("opus"),
+			},
+			options.format_constraints()
+		);
+		assert_eq!(Some(4), options.concurrent_fragments());
+		assert_eq!(3, options.retries());
+		assert!(options.live());
+	}
+
+	#[test]
+	fn test_builder_defaults() {
+		let options = DownloadOptionsBuilder::new("https://example.com/1", "/tmp/download")
+			.with_ytdl_version(*super::super::MINIMAL_YTDL_VERSION)
+			.build();
+
+		assert!(!options.audio_only());
+		assert_eq!("best", options.get_audio_format());
+		assert_eq!("mkv", options.get_video_format());
+		assert_eq!(None, options.sub_langs());
+		assert!(!options.subs_external());
+		assert!(!options.write_info_json());
+		assert!(options.extra_ytdl_arguments().is_empty());
+		assert!(options.embed_thumbnail());
+		assert!(options.remux_video());
+		assert!(!options.remux_after_download());
+		assert_eq!(FormatConstraints::default(), options.format_constraints());
+		assert_eq!(None, options.concurrent_fragments());
+		assert_eq!(0, options.retries());
+		assert!(!options.live());
+		assert_eq!(OsStr::new(YTDL_BIN_NAME), options.ytdl_bin());
+	}
 }
@@ -0,0 +1,230 @@
+//! Module for collecting per-media download statistics, written next to the command log (see [`super::DownloadOptions::save_command_log`])
+
+use std::{
+	fs::{
+		File,
+		OpenOptions,
+	},
+	io::{
+		BufWriter,
+		Write,
+	},
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+use chrono::{
+	DateTime,
+	Utc,
+};
+use serde::Serialize;
+
+use crate::error::IOErrorToError;
+
+/// Statistics of downloading a single media
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaStats {
+	/// The provider of the media
+	pub provider:      String,
+	/// The id of the media
+	pub id:            String,
+	/// The time the media started processing
+	#[serde(serialize_with = "serialize_time")]
+	pub started:       DateTime<Utc>,
+	/// The time the media finished processing (or errored)
+	#[serde(serialize_with = "serialize_time")]
+	pub finished:      DateTime<Utc>,
+	/// The amount of bytes downloaded, as reported by youtube-dl (all formats combined)
+	pub bytes:         u64,
+	/// The average speed in bytes per second over the whole processing time
+	/// [None] if no time has passed
+	pub average_speed: Option<u64>,
+	/// The amount of times youtube-dl retried something for the media (like a fragment or the whole download)
+	pub retries:       usize,
+	/// Whether the media errored instead of finishing
+	pub errored:       bool,
+}
+
+/// Serialize a time as a RFC3339 string
+fn serialize_time<S: serde::Serializer>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+	return serializer.serialize_str(&time.to_rfc3339());
+}
+
+/// Get whether `line` is a youtube-dl retry line, like "[download] Got error: ... Retrying (1/10)..." or "... Retrying fragment 3 (2/10)..."
+pub(crate) fn is_retry_line(line: &str) -> bool {
+	return line.contains("Retrying (") || line.contains("Retrying fragment");
+}
+
+/// Collector for the [`MediaStats`] of the media currently being processed
+#[derive(Debug, Default)]
+pub(crate) struct MediaStatsCollector {
+	/// The stats of the current media, [`MediaStats::finished`] is only set once finished
+	current: Option<MediaStats>,
+}
+
+impl MediaStatsCollector {
+	/// Start collecting for a new media, discarding the stats of a unfinished previous media
+	pub fn start(&mut self, provider: &str, id: &str, now: DateTime<Utc>) {
+		self.current = Some(MediaStats {
+			provider:      provider.to_owned(),
+			id:            id.to_owned(),
+			started:       now,
+			finished:      now,
+			bytes:         0,
+			average_speed: None,
+			retries:       0,
+			errored:       false,
+		});
+	}
+
+	/// Add downloaded bytes to the current media
+	pub fn add_bytes(&mut self, bytes: u64) {
+		if let Some(current) = self.current.as_mut() {
+			current.bytes = current.bytes.saturating_add(bytes);
+		}
+	}
+
+	/// Add a retry to the current media
+	pub fn add_retry(&mut self) {
+		if let Some(current) = self.current.as_mut() {
+			current.retries += 1;
+		}
+	}
+
+	/// Finish the current media, returning its stats
+	/// Returns [`None`] if no media was started
+	pub fn finish(&mut self, now: DateTime<Utc>, errored: bool) -> Option<MediaStats> {
+		let mut stats = self.current.take()?;
+
+		stats.finished = now;
+		stats.errored = errored;
+
+		let millis = u64::try_from((now - stats.started).num_milliseconds()).unwrap_or(0);
+		stats.average_speed = stats.bytes.saturating_mul(1000).checked_div(millis);
+
+		return Some(stats);
+	}
+}
+
+/// A stats file, which has every finished media written as a JSON line
+#[derive(Debug)]
+pub(crate) struct MediaStatsFile {
+	/// The collector for the current media
+	collector: MediaStatsCollector,
+	/// The opened stats file
+	file:      BufWriter<File>,
+	/// The path of the stats file
+	path:      PathBuf,
+}
+
+impl MediaStatsFile {
+	/// Open the stats file for the command log at `log_path`, appending if it already exists
+	/// The stats file is named like the command log, but with the extension "stats.jsonl"
+	pub fn open(log_path: &Path) -> Result<Self, crate::Error> {
+		let path = log_path.with_extension("stats.jsonl");
+		let file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&path)
+			.attach_path_err(&path)?;
+
+		return Ok(Self {
+			collector: MediaStatsCollector::default(),
+			file: BufWriter::new(file),
+			path,
+		});
+	}
+
+	/// See [`MediaStatsCollector::start`]
+	pub fn start(&mut self, provider: &str, id: &str) {
+		self.collector.start(provider, id, Utc::now());
+	}
+
+	/// See [`MediaStatsCollector::add_bytes`]
+	pub fn add_bytes(&mut self, bytes: u64) {
+		self.collector.add_bytes(bytes);
+	}
+
+	/// See [`MediaStatsCollector::add_retry`]
+	pub fn add_retry(&mut self) {
+		self.collector.add_retry();
+	}
+
+	/// Finish the current media and write its stats, if a media was started
+	pub fn finish(&mut self, errored: bool) -> Result<(), crate::Error> {
+		let Some(stats) = self.collector.finish(Utc::now(), errored) else {
+			return Ok(());
+		};
+
+		let mut line = serde_json::to_string(&stats)?;
+		line.push('\n');
+
+		// flush every line, so that the stats are available while still downloading
+		self.file.write_all(line.as_bytes()).attach_path_err(&self.path)?;
+		self.file.flush().attach_path_err(&self.path)?;
+
+		return Ok(());
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_is_retry_line() {
+		assert!(is_retry_line(
+			"[download] Got error: HTTP Error 503: Service Unavailable. Retrying (1/10)..."
+		));
+		assert!(is_retry_line(
+			"[download] Got error: The read operation timed out. Retrying fragment 3 (2/10)..."
+		));
+		assert!(!is_retry_line("[download]  50.0% of 10.00MiB at 1.00MiB/s ETA 00:05"));
+	}
+
+	#[test]
+	fn test_collector() {
+		let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+			.unwrap()
+			.with_timezone(&Utc);
+		let end = DateTime::parse_from_rfc3339("2024-01-01T00:00:04Z")
+			.unwrap()
+			.with_timezone(&Utc);
+		let mut collector = MediaStatsCollector::default();
+
+		// nothing is collected without a started media
+		collector.add_bytes(10);
+		assert_eq!(None, collector.finish(end, false));
+
+		collector.start("youtube", "id1", start);
+		collector.add_bytes(4000);
+		collector.add_bytes(4000);
+		collector.add_retry();
+
+		assert_eq!(
+			Some(MediaStats {
+				provider:      "youtube".to_owned(),
+				id:            "id1".to_owned(),
+				started:       start,
+				finished:      end,
+				bytes:         8000,
+				average_speed: Some(2000),
+				retries:       1,
+				errored:       false,
+			}),
+			collector.finish(end, false)
+		);
+
+		// no time passed, so there is no speed
+		collector.start("youtube", "id2", start);
+		let stats = collector.finish(start, true).unwrap();
+		assert_eq!(None, stats.average_speed);
+		assert!(stats.errored);
+		assert_eq!(
+			r#"{"provider":"youtube","id":"id2","started":"2024-01-01T00:00:00+00:00","finished":"2024-01-01T00:00:00+00:00","bytes":0,"average_speed":null,"retries":0,"errored":true}"#,
+			serde_json::to_string(&stats).unwrap()
+		);
+	}
+}
@@ -1,38 +1,89 @@
 //! Module for handling youtube-dl
 
-use assemble_cmd::assemble_ytdl_command;
+use assemble_cmd::{
+	assemble_media_id_filter,
+	assemble_ytdl_command,
+	assemble_ytdl_command_dry_run,
+};
 use chrono::NaiveDate;
 use diesel::SqliteConnection;
+use media_stats::{
+	is_retry_line,
+	MediaStatsFile,
+};
 use once_cell::sync::Lazy;
 use parse_linetype::{
 	CustomParseType,
 	LineType,
 };
+use serde::Serialize;
 use std::{
+	ffi::OsString,
 	fs::OpenOptions,
 	io::{
 		BufRead,
 		BufReader,
 		BufWriter,
+		Read,
 		Write,
 	},
-	time::Duration,
+	path::{
+		Path,
+		PathBuf,
+	},
+	sync::{
+		atomic::{
+			AtomicBool,
+			Ordering,
+		},
+		mpsc,
+		Arc,
+	},
+	time::{
+		Duration,
+		Instant,
+	},
 };
 
 use crate::{
-	data::cache::media_info::MediaInfo,
-	error::IOErrorToError,
-	spawn::ytdl::YTDL_BIN_NAME,
+	data::cache::{
+		info_json::InfoJson,
+		media_info::MediaInfo,
+		media_stage::MediaStage,
+	},
+	error::{
+		DownloadError,
+		IOErrorToError,
+	},
+	main::archive::import::insert_insmedia,
+	spawn::{
+		ffmpeg::{
+			duration_to_secs,
+			ffmpeg_probe,
+			parse_duration,
+		},
+		priority::priority_program,
+		ytdl::YTDL_BIN_NAME,
+	},
 };
 
 pub use download_options::{
+	BasicDownloadOptions,
+	CookieSource,
 	DownloadOptions,
+	DownloadOptionsBuilder,
 	FormatArgument,
+	FormatConstraints,
+	PlaylistOrder,
 };
+pub use media_stats::MediaStats;
+pub use playlist_items::selected_playlist_count;
 
 mod assemble_cmd;
 mod download_options;
+mod media_stats;
 mod parse_linetype;
+mod playlist_items;
 
 /// The minimal youtube-dl(p) version that is expected to be used.
 ///
@@ -41,37 +92,146 @@ pub static MINIMAL_YTDL_VERSION: Lazy<chrono::NaiveDate> =
 	Lazy::new(|| return chrono::NaiveDate::from_ymd_opt(2023, 3, 3).unwrap());
 
 /// Types for [DownloadProgress::Skipped]
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SkippedType {
 	/// Skipped because of a Error
 	Error,
 	/// Skipped because already being in the archive
 	InArchive,
+	/// Skipped because being on the blacklist (see [`crate::main::archive::remove::add_to_blacklist`])
+	Blacklisted,
+	/// Skipped because the media is not available for download, with the reason why
+	Unavailable(UnavailableReason),
+}
+
+/// Reasons for [`SkippedType::Unavailable`], classified from the youtube-dl error message
+#[derive(Debug, Clone, PartialEq, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnavailableReason {
+	/// The media is private
+	Private,
+	/// The media is only available to members (like channel memberships)
+	MembersOnly,
+	/// The media requires a login (like age-restricted media), see "--cookies"
+	LoginRequired,
+	/// The media is not available in the current country
+	GeoBlocked,
+	/// The media is DRM protected, which youtube-dl does not support
+	Drm,
+	/// The server refused the download (HTTP 403)
+	Forbidden,
+	/// The media has been removed or is otherwise unavailable
+	Removed,
+}
+
+impl std::fmt::Display for UnavailableReason {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return f.write_str(match self {
+			Self::Private => "private",
+			Self::MembersOnly => "members only",
+			Self::LoginRequired => "requires login",
+			Self::GeoBlocked => "geo-blocked",
+			Self::Drm => "drm protected",
+			Self::Forbidden => "forbidden (403)",
+			Self::Removed => "removed",
+		});
+	}
+}
+
+/// Progress of a single file download, as reported by youtube-dl, for [`DownloadProgress::SingleProgress`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TransferProgress {
+	/// The download percentage (not decimal)
+	pub percent: u8,
+	/// The current download speed as displayed by youtube-dl (like "526.19KiB/s"), if known
+	pub speed:   Option<String>,
+	/// The estimated time until the download is finished as displayed by youtube-dl (like "01:16"), if known
+	pub eta:     Option<String>,
+}
+
+impl TransferProgress {
+	/// Create a new instance with just the percentage
+	#[must_use]
+	pub fn new(percent: u8) -> Self {
+		return Self {
+			percent,
+			..Default::default()
+		};
+	}
+
+	/// Set the speed of the current instance
+	#[must_use]
+	pub fn with_speed<S: AsRef<str>>(mut self, speed: S) -> Self {
+		self.speed = Some(speed.as_ref().to_owned());
+
+		return self;
+	}
+
+	/// Set the ETA of the current instance
+	#[must_use]
+	pub fn with_eta<S: AsRef<str>>(mut self, eta: S) -> Self {
+		self.eta = Some(eta.as_ref().to_owned());
+
+		return self;
+	}
+}
+
+/// Progress of a livestream download, which has no known total size, for [`DownloadProgress::SingleLiveProgress`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LiveProgress {
+	/// The amount of bytes downloaded so far
+	pub bytes:   u64,
+	/// The current download speed as displayed by youtube-dl (like "526.19KiB/s"), if known
+	pub speed:   Option<String>,
+	/// The time downloaded so far as displayed by youtube-dl or ffmpeg (like "00:01:23"), if known
+	pub elapsed: Option<String>,
 }
 
 /// Enum for hooks to know what is currently happening
+/// Serializes to a object with a "event" field and named values (like for machine-readable output)
 /// All Variants will have a certian order in which they are called (like AllStarting is always before a SingleStarting)
 /// but not all may be called, like there may be "SingleStarting -> SingleProgress -> Skipped" instead of "SingleStarting -> SingleProgress -> SingleFinished"
 #[derive(Debug, Clone, PartialEq)]
 pub enum DownloadProgress {
 	/// Variant representing that the download of a single url is starting
+	/// See [`DownloadProgress::UrlStarting`]
 	UrlStarting,
 	/// Variant representing a skipped element, may or may not come because of it already being in the archive
 	/// may be called after "SingleStarting" and / or "SingleProcess" instead of "SingleFinished"
-	/// values (skipped_count)
-	Skipped(usize, SkippedType),
+	/// "id" is the id of the skipped media, [`None`] if it could not be found (like a error without a media id)
+	Skipped {
+		/// The amount of skipped media
+		count: usize,
+		/// Why the media was skipped
+		typ:   SkippedType,
+		/// The id of the skipped media
+		id:    Option<String>,
+	},
 	/// Variant representing that a media has started the process
 	/// values: (id, title)
 	SingleStarting(String, String),
 	/// Variant representing that a started media has increased in progress
 	/// "id" may be [`None`] when the previous parsing did not parse a title
 	/// values:  (id, progress)
-	SingleProgress(Option<String>, u8),
+	SingleProgress(Option<String>, TransferProgress),
+	/// Variant representing that a started media which downloads multiple formats separately (like video and audio) has increased in progress
+	/// Used instead of [`DownloadProgress::SingleProgress`] for such media, because every format starts at 0% again
+	/// "phase" is 1-based, "total_phases" may grow while downloading if the amount of formats was not known beforehand
+	/// "id" may be [`None`] when the previous parsing did not parse a title
+	/// values: (id, phase, total_phases, progress)
+	SingleProgressPhase(Option<String>, usize, usize, TransferProgress),
+	/// Variant representing that a file of a started media has finished downloading
+	/// there may be multiple for a single media (like separate video and audio files)
+	/// "id" may be [`None`] when the previous parsing did not parse a title
+	/// values: (id, downloaded bytes)
+	SingleDownloaded(Option<String>, u64),
 	/// Variant representing that a media has finished the process
 	/// the "id" is not guranteed to be the same as in [`DownloadProgress::SingleStarting`]
 	/// will only be called if there was a download AND no error happened
-	/// values: (id)
-	SingleFinished(String),
+	/// "path" is the final file path in the download path (from "MOVE"), [`None`] if it was not printed
+	/// values: (id, path)
+	SingleFinished(String, Option<PathBuf>),
 	/// Variant representing that the download of a single url has finished
 	/// The value in this tuple is the size of actually downloaded media, not just found media
 	/// values: (downloaded media count)
@@ -80,6 +240,164 @@ pub enum DownloadProgress {
 	/// the first (and currently only) value is the count of media in the playlist
 	/// values: (playlist_count)
 	PlaylistInfo(usize),
+	/// Variant representing that youtube-dl has not had any output for longer than [`DownloadOptions::stall_timeout`] while downloading or post-processing a media
+	/// Will only be called once per stall, the command will be stopped afterwards if [`DownloadOptions::stall_kill`] is enabled
+	/// "id" may be [`None`] when the previous parsing did not parse a title
+	/// values: (id, time without output)
+	Stalled(Option<String>, Duration),
+	/// Variant representing that youtube-dl is extracting a playlist, which can take a while for big playlists
+	/// Will be called for every page youtube-dl downloads, before any media of the playlist is started
+	/// values: (page)
+	ExtractingPlaylist(usize),
+	/// Variant representing that a media has entered a ffmpeg post-processing step (like extracting the audio or merging formats)
+	/// Will be called once per step, the step is the name of the yt-dlp post-processor (like "ExtractAudio", "Merger" or "VideoRemuxer")
+	/// "id" may be [`None`] when the previous parsing did not parse a title
+	/// values: (id, step)
+	Postprocessing(Option<String>, String),
+	/// Variant representing that a started livestream has increased in progress
+	/// Used instead of [`DownloadProgress::SingleProgress`], because the total size of a livestream is not known
+	/// "id" may be [`None`] when the previous parsing did not parse a title
+	/// values: (id, progress)
+	SingleLiveProgress(Option<String>, LiveProgress),
+	/// Variant representing that youtube-dl is waiting for a scheduled livestream to begin (see [`DownloadOptions::live`])
+	/// values: (remaining time until the next check as displayed by youtube-dl, like "00:05:00")
+	WaitingForLive(String),
+}
+
+/// Serialized representation of [`DownloadProgress`], to have named fields instead of tuple values
+/// Example: `{"event":"single_progress","id":"-----------","percent":50}`
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum DownloadProgressSer<'a> {
+	UrlStarting,
+	/// See [`DownloadProgress::Skipped`]
+	Skipped {
+		count:  usize,
+		reason: SkippedType,
+		id:     Option<&'a str>,
+	},
+	/// See [`DownloadProgress::SingleStarting`]
+	SingleStarting {
+		id:    &'a str,
+		title: &'a str,
+	},
+	/// See [`DownloadProgress::SingleProgress`]
+	SingleProgress {
+		id:      Option<&'a str>,
+		percent: u8,
+		speed:   Option<&'a str>,
+		eta:     Option<&'a str>,
+	},
+	/// See [`DownloadProgress::SingleProgressPhase`]
+	SingleProgressPhase {
+		id:           Option<&'a str>,
+		phase:        usize,
+		total_phases: usize,
+		percent:      u8,
+		speed:        Option<&'a str>,
+		eta:          Option<&'a str>,
+	},
+	/// See [`DownloadProgress::SingleDownloaded`]
+	SingleDownloaded {
+		id:    Option<&'a str>,
+		bytes: u64,
+	},
+	/// See [`DownloadProgress::SingleFinished`]
+	SingleFinished {
+		id:   &'a str,
+		path: Option<&'a Path>,
+	},
+	/// See [`DownloadProgress::UrlFinished`]
+	UrlFinished {
+		count: usize,
+	},
+	/// See [`DownloadProgress::PlaylistInfo`]
+	PlaylistInfo {
+		count: usize,
+	},
+	/// See [`DownloadProgress::Stalled`]
+	Stalled {
+		id:           Option<&'a str>,
+		elapsed_secs: u64,
+	},
+	/// See [`DownloadProgress::ExtractingPlaylist`]
+	ExtractingPlaylist {
+		page: usize,
+	},
+	/// See [`DownloadProgress::Postprocessing`]
+	Postprocessing {
+		id:   Option<&'a str>,
+		step: &'a str,
+	},
+	/// See [`DownloadProgress::SingleLiveProgress`]
+	SingleLiveProgress {
+		id:      Option<&'a str>,
+		bytes:   u64,
+		speed:   Option<&'a str>,
+		elapsed: Option<&'a str>,
+	},
+	/// See [`DownloadProgress::WaitingForLive`]
+	WaitingForLive {
+		remaining: &'a str,
+	},
+}
+
+impl Serialize for DownloadProgress {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let ser = match self {
+			DownloadProgress::UrlStarting => DownloadProgressSer::UrlStarting,
+			DownloadProgress::Skipped { count, typ, id } => DownloadProgressSer::Skipped {
+				count:  *count,
+				reason: *typ,
+				id:     id.as_deref(),
+			},
+			DownloadProgress::SingleStarting(id, title) => DownloadProgressSer::SingleStarting { id, title },
+			DownloadProgress::SingleProgress(id, progress) => DownloadProgressSer::SingleProgress {
+				id:      id.as_deref(),
+				percent: progress.percent,
+				speed:   progress.speed.as_deref(),
+				eta:     progress.eta.as_deref(),
+			},
+			DownloadProgress::SingleProgressPhase(id, phase, total_phases, progress) => {
+				DownloadProgressSer::SingleProgressPhase {
+					id:           id.as_deref(),
+					phase:        *phase,
+					total_phases: *total_phases,
+					percent:      progress.percent,
+					speed:        progress.speed.as_deref(),
+					eta:          progress.eta.as_deref(),
+				}
+			},
+			DownloadProgress::SingleDownloaded(id, bytes) => DownloadProgressSer::SingleDownloaded {
+				id:    id.as_deref(),
+				bytes: *bytes,
+			},
+			DownloadProgress::SingleFinished(id, path) => DownloadProgressSer::SingleFinished {
+				id,
+				path: path.as_deref(),
+			},
+			DownloadProgress::UrlFinished(count) => DownloadProgressSer::UrlFinished { count: *count },
+			DownloadProgress::PlaylistInfo(count) => DownloadProgressSer::PlaylistInfo { count: *count },
+			DownloadProgress::Stalled(id, elapsed) => DownloadProgressSer::Stalled {
+				id:           id.as_deref(),
+				elapsed_secs: elapsed.as_secs(),
+			},
+			DownloadProgress::ExtractingPlaylist(page) => DownloadProgressSer::ExtractingPlaylist { page: *page },
+			DownloadProgress::Postprocessing(id, step) => DownloadProgressSer::Postprocessing {
+				id: id.as_deref(),
+				step,
+			},
+			DownloadProgress::SingleLiveProgress(id, progress) => DownloadProgressSer::SingleLiveProgress {
+				id:      id.as_deref(),
+				bytes:   progress.bytes,
+				speed:   progress.speed.as_deref(),
+				elapsed: progress.elapsed.as_deref(),
+			},
+			DownloadProgress::WaitingForLive(remaining) => DownloadProgressSer::WaitingForLive { remaining },
+		};
+
+		return ser.serialize(serializer);
+	}
 }
 
 /// Warn if a version lower than the minimal is used
@@ -94,58 +412,277 @@ fn warn_minimal_version(ytdl_version: NaiveDate) {
 	}
 }
 
+/// The wait time before the first retry of [`DownloadOptions::retries`], doubled for every following retry
+const RETRY_BACKOFF_BASE: Duration = Duration::from_secs(5);
+
+/// Get the wait time before retry `retry` (starting at 1), see [`RETRY_BACKOFF_BASE`]
+fn retry_backoff(retry: usize) -> Duration {
+	// cap the exponent, so that a high retry count does not overflow
+	let exponent = u32::try_from(retry.saturating_sub(1)).unwrap_or(u32::MAX).min(10);
+
+	return RETRY_BACKOFF_BASE * 2u32.pow(exponent);
+}
+
+/// Token to cancel a running [`download_single`] from a different thread (like a ctrl-c handler)
+/// Clones share the same state, so cancelling one clone cancels all of them
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+	/// Create a new token which is not cancelled yet
+	#[must_use]
+	pub fn new() -> Self {
+		return Self::default();
+	}
+
+	/// Request cancellation, which cannot be undone
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::Release);
+	}
+
+	/// Get whether cancellation has been requested
+	#[must_use]
+	pub fn is_cancelled(&self) -> bool {
+		return self.0.load(Ordering::Acquire);
+	}
+}
+
+/// Get whether `cancel` is given and has been cancelled
+fn is_cancelled(cancel: Option<&CancellationToken>) -> bool {
+	return cancel.is_some_and(CancellationToken::is_cancelled);
+}
+
+/// Sleep for `duration`, but return early with [`crate::Error::cancelled`] if `cancel` is cancelled
+fn sleep_cancellable(duration: Duration, cancel: Option<&CancellationToken>) -> Result<(), crate::Error> {
+	let until = Instant::now() + duration;
+
+	loop {
+		if is_cancelled(cancel) {
+			return Err(crate::Error::cancelled());
+		}
+
+		let remaining = until.saturating_duration_since(Instant::now());
+
+		if remaining.is_zero() {
+			return Ok(());
+		}
+
+		std::thread::sleep(remaining.min(STALL_CHECK_INTERVAL));
+	}
+}
+
+/// The command [`download_single`] would run, see [`dry_run_command`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunCommand {
+	/// The program that would be run (youtube-dl or a program setting the process priority)
+	pub program:       OsString,
+	/// The arguments the program would be run with
+	pub args:          Vec<OsString>,
+	/// The lines the ytdl archive would contain, [`None`] if no ytdl archive would be generated
+	pub archive_lines: Option<Vec<String>>,
+}
+
+/// Get the youtube-dl command [`download_single`] would run for `options`, without running it or writing anything to disk
+pub fn dry_run_command<A: DownloadOptions>(
+	connection: Option<&mut SqliteConnection>,
+	options: &A,
+) -> Result<DryRunCommand, crate::Error> {
+	let (program, mut args) = priority_program(options.ytdl_bin());
+	let (ytdl_args, archive_lines) = assemble_ytdl_command_dry_run(connection, options)?;
+	args.extend(ytdl_args);
+
+	return Ok(DryRunCommand {
+		program,
+		args,
+		archive_lines,
+	});
+}
+
 /// Download a single URL
 /// Assumes ytdl and ffmpeg have already been checked to exist and work (like using [`crate::spawn::ytdl::ytdl_version`])
 /// Adds all non-skipped Media to the input [`Vec<MediaInfo>`]
+/// If a `connection` is given, every downloaded Media is also inserted into the archive as soon as it is finished
+/// Media that failed with a transient error are downloaded again up to [`DownloadOptions::retries`] times
+/// A media stalling with [`DownloadOptions::stall_kill`] counts as a transient error
+///
+/// If `cancel` is given and gets cancelled, youtube-dl is killed and [`crate::Error::cancelled`] is returned,
+/// media that already finished are still added to `mediainfo_vec`
 pub fn download_single<A: DownloadOptions, C: FnMut(DownloadProgress)>(
-	connection: Option<&mut SqliteConnection>,
+	mut connection: Option<&mut SqliteConnection>,
 	options: &A,
-	pgcb: C,
+	mut pgcb: C,
 	mediainfo_vec: &mut Vec<MediaInfo>,
+	cancel: Option<&CancellationToken>,
 ) -> Result<(), crate::Error> {
 	warn_minimal_version(options.ytdl_version());
 
+	let retries = options.retries();
+	// the media to download, empty for all media of the url
+	let mut only_media_ids: Vec<String> = Vec::new();
+	let mut retry = 0;
+
+	loop {
+		let mut transient_failed = Vec::new();
+		let result = download_single_attempt(
+			connection.as_deref_mut(),
+			options,
+			&mut pgcb,
+			mediainfo_vec,
+			&only_media_ids,
+			&mut transient_failed,
+			cancel,
+		);
+
+		// only youtube-dl errors may be transient, all other errors are returned immediately
+		let retryable = match &result {
+			Ok(()) => true,
+			Err(err) => err.as_download_error().is_some(),
+		};
+		// youtube-dl was stopped at the stalled media, so the media after it have not been tried yet
+		let stalled = result
+			.as_ref()
+			.is_err_and(|err| return err.as_download_error().is_some_and(DownloadError::is_stalled));
+		// retrying would only fail again, until space has been freed
+		let disk_full = result
+			.as_ref()
+			.is_err_and(|err| return err.as_download_error().is_some_and(DownloadError::is_disk_full));
+
+		if !retryable || disk_full || transient_failed.is_empty() || retry >= retries {
+			return result;
+		}
+
+		retry += 1;
+		let backoff = retry_backoff(retry);
+		warn!(
+			"Retrying {} media which failed with a transient error in {}s (retry {retry}/{retries})",
+			transient_failed.len(),
+			backoff.as_secs()
+		);
+		sleep_cancellable(backoff, cancel)?;
+
+		// retry the same media as the attempt, already downloaded media are skipped by the archive
+		if !stalled {
+			only_media_ids = transient_failed;
+		}
+	}
+}
+
+/// Run youtube-dl once for [`download_single`]
+/// If `only_media_ids` is not empty, only those media of the url are downloaded
+/// The ids of media that failed with a transient error are added to `transient_failed`
+fn download_single_attempt<A: DownloadOptions, C: FnMut(DownloadProgress)>(
+	mut connection: Option<&mut SqliteConnection>,
+	options: &A,
+	pgcb: C,
+	mediainfo_vec: &mut Vec<MediaInfo>,
+	only_media_ids: &[String],
+	transient_failed: &mut Vec<String>,
+	cancel: Option<&CancellationToken>,
+) -> Result<(), crate::Error> {
 	let ytdl_child = {
-		let args = assemble_ytdl_command(connection, options)?;
+		let (program, mut args) = priority_program(options.ytdl_bin());
+		args.extend(assemble_ytdl_command(connection.as_deref_mut(), options)?);
+		// the url is the last argument, and options should come before it
+		let url_index = args.len() - 1;
+		args.splice(url_index..url_index, assemble_media_id_filter(only_media_ids));
 
 		// merge stderr into stdout
-		duct::cmd(YTDL_BIN_NAME, args)
-			.stderr_to_stdout()
-			.reader()
-			.attach_location_err("duct ytdl reader")?
+		Arc::new(
+			duct::cmd(program, args)
+				.stderr_to_stdout()
+				// the exit code is checked manually, so that it can be attached to the error
+				.unchecked()
+				.reader()
+				.attach_location_err("duct ytdl reader")?,
+		)
 	};
 
-	let stdout_reader = BufReader::new(&ytdl_child);
+	let stdout_reader = BufReader::new(SharedReader(Arc::clone(&ytdl_child)));
 
-	handle_stdout(options, pgcb, stdout_reader, mediainfo_vec)?;
+	let result = handle_stdout(
+		connection,
+		options,
+		pgcb,
+		stdout_reader,
+		mediainfo_vec,
+		transient_failed,
+		cancel,
+	);
 
-	loop {
+	match result {
+		Ok(true) => {
+			// "kill" also waits for the process to exit
+			ytdl_child.kill().attach_location_err("duct ytdl kill")?;
+
+			return Ok(());
+		},
+		Err(err) if err.is_cancelled() => {
+			info!("Stopping youtube-dl because of cancellation");
+			ytdl_child.kill().attach_location_err("duct ytdl kill")?;
+
+			return Err(err);
+		},
+		// a stalled youtube-dl would never exit on its own, and a full disk would only fail every following media
+		Err(err)
+			if err
+				.as_download_error()
+				.is_some_and(|v| return v.is_stalled() || v.is_disk_full()) =>
+		{
+			ytdl_child.kill().attach_location_err("duct ytdl kill")?;
+
+			return Err(err);
+		},
+		// only youtube-dl errors are reported after youtube-dl exited, all other errors are returned immediately (which kills youtube-dl)
+		Err(err) if err.as_download_error().is_none() => return Err(err),
+		_ => (),
+	}
+
+	let exit_code = loop {
 		// wait loop, because somehow a "ReaderHandle" does not implement "wait", only "try_wait", but have to wait for it to exit here
 		match ytdl_child.try_wait() {
-			Ok(v) => {
-				// only in the "Some" case is the wait actually finished
-				if v.is_some() {
-					break;
-				}
-			},
+			// only in the "Some" case is the wait actually finished
+			Ok(Some(output)) => break output.status.code(),
+			Ok(None) => (),
 			Err(err) => {
 				// ignore duct errors as non-"Err" worthy
-				warn!("youtube-dl exited with a non-0 code: {err}");
-				break;
+				warn!("Waiting for youtube-dl errored: {err}");
+				break None;
 			},
 		}
 
 		std::thread::sleep(Duration::from_millis(100)); // sleep to same some time between the next wait (to not cause constant cpu spike)
+	};
+
+	if let Some(code) = exit_code.filter(|v| return *v != 0) {
+		warn!("youtube-dl exited with a non-0 code: {code}");
 	}
 
+	result.map_err(|err| return err.with_exit_code(exit_code))?;
+
 	return Ok(());
 }
 
+/// Wrapper to read a shared [`duct::ReaderHandle`], so that it can be read in a different thread while still being able to be killed
+struct SharedReader(Arc<duct::ReaderHandle>);
+
+impl Read for SharedReader {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		return (&*self.0).read(buf);
+	}
+}
+
 /// Youtube-DL archive prefix
 pub const YTDL_ARCHIVE_PREFIX: &str = "ytdl_archive_";
 /// Youtube-DL archive extension
 pub const YTDL_ARCHIVE_EXT: &str = ".txt";
 
+/// Sub-directory of the download path for intermediate files (like fragments or not yet post-processed media)
+pub const PATHS_TEMP_DIR: &str = "temp";
+/// Sub-directory of the download path for thumbnail files
+pub const PATHS_THUMBNAIL_DIR: &str = "thumbnail";
+/// Sub-directory of the download path for subtitle files
+pub const PATHS_SUBTITLE_DIR: &str = "subtitle";
+
 /// Consistent way of getting the archive name
 #[must_use]
 pub fn get_archive_name(output_dir: &std::path::Path) -> std::path::PathBuf {
@@ -157,15 +694,220 @@ pub fn get_archive_name(output_dir: &std::path::Path) -> std::path::PathBuf {
 	));
 }
 
+/// Amount of unparsed lines in a single [`handle_stdout`] call at which a warning will be printed
+const UNPARSED_LINES_WARN_THRESHOLD: usize = 5;
+/// Amount of unparsed lines to keep as samples for the warning
+const UNPARSED_LINES_SAMPLES: usize = 3;
+
+/// Statistics about lines that could not be parsed into a [`LineType`]
+/// Used to detect when the youtube-dl output format has changed
+#[derive(Debug, Default, PartialEq)]
+struct UnparsedLineStats {
+	/// Count of all non-empty lines
+	total:    usize,
+	/// Count of all lines that could not be parsed
+	unparsed: usize,
+	/// The first [`UNPARSED_LINES_SAMPLES`] lines that could not be parsed
+	samples:  Vec<String>,
+}
+
+impl UnparsedLineStats {
+	/// Count a line that could be parsed
+	fn add_parsed(&mut self) {
+		self.total += 1;
+	}
+
+	/// Count a line that could not be parsed, and keep it as a sample if there is still space
+	fn add_unparsed(&mut self, line: &str) {
+		self.total += 1;
+		self.unparsed += 1;
+
+		if self.samples.len() < UNPARSED_LINES_SAMPLES {
+			self.samples.push(line.to_owned());
+		}
+	}
+
+	/// Report the statistics, warn if above [`UNPARSED_LINES_WARN_THRESHOLD`]
+	/// Returns a error if `strict` is enabled and any line could not be parsed
+	fn report(&self, strict: bool) -> Result<(), crate::Error> {
+		if self.unparsed == 0 {
+			return Ok(());
+		}
+
+		debug!(
+			"{} of {} youtube-dl lines could not be parsed",
+			self.unparsed, self.total
+		);
+
+		let samples = self
+			.samples
+			.iter()
+			.map(|v| return format!("  {v:#?}"))
+			.collect::<Vec<String>>()
+			.join("\n");
+
+		if strict {
+			return Err(crate::Error::other(format!(
+				"Strict Parse: {} of {} youtube-dl lines could not be parsed, samples:\n{}",
+				self.unparsed, self.total, samples
+			)));
+		}
+
+		if self.unparsed >= UNPARSED_LINES_WARN_THRESHOLD {
+			warn!(
+				"{} of {} youtube-dl lines could not be parsed, the output format of {} may have changed.\nConsider updating ytdlr or reporting this with the following samples:\n{}",
+				self.unparsed, self.total, YTDL_BIN_NAME, samples
+			);
+		}
+
+		return Ok(());
+	}
+}
+
+/// Interval in which it is checked if the download has stalled, while there is no output
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watchdog for how long youtube-dl has not had any output
+#[derive(Debug)]
+struct StallWatchdog {
+	/// The time of the last output
+	last_output: Instant,
+	/// Whether the stall has already been reported since the last output
+	reported:    bool,
+}
+
+impl Default for StallWatchdog {
+	fn default() -> Self {
+		return Self {
+			last_output: Instant::now(),
+			reported:    false,
+		};
+	}
+}
+
+impl StallWatchdog {
+	/// Record that youtube-dl had output
+	fn output(&mut self) {
+		self.last_output = Instant::now();
+		self.reported = false;
+	}
+
+	/// Get the time without output, if it is longer than `timeout` and has not been reported yet
+	fn check_stalled(&mut self, timeout: Duration) -> Option<Duration> {
+		let elapsed = self.last_output.elapsed();
+
+		if self.reported || elapsed < timeout {
+			return None;
+		}
+
+		self.reported = true;
+
+		return Some(elapsed);
+	}
+}
+
+/// Tracking of the ffmpeg (post-processing) phase of the current media
+#[derive(Debug, Default)]
+struct FfmpegPhase {
+	/// The current post-processing step, [`None`] if no step has been found yet
+	step: Option<&'static str>,
+}
+
+impl FfmpegPhase {
+	/// Leave the ffmpeg phase (like when a new download starts)
+	fn leave(&mut self) {
+		self.step = None;
+	}
+
+	/// Set the current post-processing step
+	/// Returns `true` if the step changed
+	fn set_step(&mut self, step: &'static str) -> bool {
+		return self.step.replace(step) != Some(step);
+	}
+}
+
+/// Tracking of the separately downloaded formats (like video and audio, which are merged afterwards) of the current media
+///
+/// Every format starts at 0% again, so the phase is reported with [`DownloadProgress::SingleProgressPhase`]
+#[derive(Debug, Default)]
+struct FormatPhases {
+	/// The amount of formats of the current media, from "PARSE_FORMATS"
+	total:   usize,
+	/// The format currently being downloaded (1-based), 0 if no format has started yet
+	current: usize,
+}
+
+impl FormatPhases {
+	/// Start a new media, which downloads `total` formats
+	fn start(&mut self, total: usize) {
+		self.total = total;
+		self.current = 0;
+	}
+
+	/// Advance to the next format (like on a "[download] Destination: ..." line)
+	/// The total grows if more formats are downloaded than known (like when "PARSE_FORMATS" was not printed)
+	fn advance(&mut self) {
+		self.current += 1;
+		self.total = self.total.max(self.current);
+	}
+
+	/// Get the current phase and the total phases, [`None`] if the media only downloads a single format
+	fn phase(&self) -> Option<(usize, usize)> {
+		if self.total <= 1 {
+			return None;
+		}
+
+		return Some((self.current.max(1), self.total));
+	}
+}
+
+/// Tracking of the highest percentage reported for the format currently being downloaded
+///
+/// With concurrent fragment downloads (see [`DownloadOptions::concurrent_fragments`]) the progress lines are interleaved
+/// and the estimated size changes, which would otherwise make the progress go backwards
+#[derive(Debug, Default)]
+struct MaxProgress {
+	/// The id of the media and the highest percentage reported for it
+	current: Option<(Option<String>, u8)>,
+}
+
+impl MaxProgress {
+	/// Get the percentage to report for `percent` of media `id`, which is never lower than a previously reported percentage of the same format
+	fn update(&mut self, id: Option<&str>, percent: u8) -> u8 {
+		let percent = match &self.current {
+			Some((current_id, max)) if current_id.as_deref() == id => percent.max(*max),
+			_ => percent,
+		};
+
+		self.current = Some((id.map(ToOwned::to_owned), percent));
+
+		return percent;
+	}
+
+	/// Reset the highest percentage, like when a format has finished and the next format (like the audio) starts at 0
+	fn reset(&mut self) {
+		self.current = None;
+	}
+}
+
 /// Helper function to handle the output from a spawned ytdl command
 /// Adds all non-skipped Media to the input [`Vec<MediaInfo>`]
+///
+/// Returns `true` if the reading was stopped early (like [`DownloadOptions::max_download_bytes`] being reached),
+/// in which case the command should be stopped
+/// A media stalling with [`DownloadOptions::stall_kill`] is returned as a [`crate::error::DownloadErrorCategory::Stalled`] error, in which case the command should also be stopped
+/// A error about no space being left is returned immediately as a [`crate::error::DownloadErrorCategory::DiskFull`] error, in which case the command should also be stopped
+/// Returns [`crate::Error::cancelled`] if `cancel` has been cancelled, in which case the command should also be stopped
 #[inline]
-fn handle_stdout<A: DownloadOptions, C: FnMut(DownloadProgress), R: BufRead>(
+fn handle_stdout<A: DownloadOptions, C: FnMut(DownloadProgress), R: BufRead + Send + 'static>(
+	mut connection: Option<&mut SqliteConnection>,
 	options: &A,
 	mut pgcb: C,
 	reader: R,
 	mediainfo_vec: &mut Vec<MediaInfo>,
-) -> Result<(), crate::Error> {
+	transient_failed: &mut Vec<String>,
+	cancel: Option<&CancellationToken>,
+) -> Result<bool, crate::Error> {
 	// report that the downloading is now starting
 	pgcb(DownloadProgress::UrlStarting);
 
@@ -177,10 +919,26 @@ fn handle_stdout<A: DownloadOptions, C: FnMut(DownloadProgress), R: BufRead>(
 	// let mut mediainfo_vec: Vec<MediaInfo> = Vec::with_capacity(options.get_count_estimate());
 	// "current_mediainfo" may not be defined because it cannot be guranteed that a parsed output was emitted
 	let mut current_mediainfo: Option<MediaInfo> = None;
-	// value to determine if a media has actually been downloaded, or just found
-	let mut had_download = false;
 	// store the last error line encountered
 	let mut last_error = None;
+	// statistics of lines that could not be parsed
+	let mut unparsed_stats = UnparsedLineStats::default();
+	// cache the max bytes to not execute the function for every line (should be a static value)
+	let max_download_bytes = options.max_download_bytes();
+	// the amount of bytes downloaded in this call
+	let mut downloaded_bytes: u64 = 0;
+	// store whether the reading has been stopped early
+	let mut stopped_early = false;
+	// cache the stall timeout to not execute the function for every line (should be a static value)
+	let stall_timeout = options.stall_timeout();
+	// how long youtube-dl has not had any output
+	let mut stall_watchdog = StallWatchdog::default();
+	// the post-processing step of the current media
+	let mut ffmpeg_phase = FfmpegPhase::default();
+	// the highest progress of the current format, to not go backwards with concurrent fragments
+	let mut max_progress = MaxProgress::default();
+	// the formats of the current media, which are downloaded one after another
+	let mut format_phases = FormatPhases::default();
 
 	let mut maybe_command_file_log = if options.save_command_log() {
 		let path = options
@@ -203,17 +961,87 @@ fn handle_stdout<A: DownloadOptions, C: FnMut(DownloadProgress), R: BufRead>(
 	} else {
 		None
 	};
+	// the per-media stats, written next to the command log
+	let mut maybe_stats_file = match &maybe_command_file_log {
+		Some((_, path)) => Some(MediaStatsFile::open(path)?),
+		None => None,
+	};
 
-	// HACK: .lines() iter never exits on non-0 exit codes in duct, see https://github.com/oconnor663/duct.rs/issues/112
-	for line in reader.lines() {
-		let line = match line {
-			Ok(v) => v,
-			Err(err) => {
+	// read the lines in a separate thread, so that stalls can be detected while there is no output
+	let (line_tx, line_rx) = mpsc::channel::<std::io::Result<String>>();
+	std::thread::Builder::new()
+		.name("ytdl stdout reader".to_owned())
+		.spawn(move || {
+			// HACK: .lines() iter never exits on non-0 exit codes in duct, see https://github.com/oconnor663/duct.rs/issues/112
+			for line in reader.lines() {
+				// stop reading once the receiver is gone
+				if line_tx.send(line).is_err() {
+					break;
+				}
+			}
+		})
+		.attach_location_err("ytdl stdout reader thread spawn")?;
+
+	loop {
+		if is_cancelled(cancel) {
+			return Err(crate::Error::cancelled());
+		}
+
+		// only wait with a timeout if something has to be checked while there is no output
+		let received = if stall_timeout.is_some() || cancel.is_some() {
+			line_rx.recv_timeout(STALL_CHECK_INTERVAL)
+		} else {
+			line_rx.recv().map_err(|_| return mpsc::RecvTimeoutError::Disconnected)
+		};
+
+		let line = match received {
+			Ok(Ok(v)) => v,
+			Ok(Err(err)) => {
 				debug!("duct lines reader errored: {}", err);
 				break; // handle it as a non-breaking case, because in 99% of cases it is just a error of "command ... exited with code ?"
 			},
+			Err(mpsc::RecvTimeoutError::Disconnected) => break,
+			Err(mpsc::RecvTimeoutError::Timeout) => {
+				// cancellation is checked at the start of the loop
+				// only a media being downloaded or post-processed can stall, not the extraction before it
+				let stalled = stall_timeout
+					.filter(|_| return current_mediainfo.is_some())
+					.and_then(|v| return stall_watchdog.check_stalled(v));
+				if let Some(elapsed) = stalled {
+					let id = current_mediainfo.as_ref().map(|v| return v.id.clone());
+					warn!(
+						"Media \"{}\" has not had any output for {}s",
+						id.as_deref().unwrap_or("unknown"),
+						elapsed.as_secs()
+					);
+					pgcb(DownloadProgress::Stalled(id.clone(), elapsed));
+
+					if options.stall_kill() {
+						info!("Stopping youtube-dl because of the media stalling");
+						let download_error = DownloadError::stalled(elapsed)
+							.with_url(options.get_url())
+							.with_fallback_media_id(id);
+						// the stalled media is retried like a transient error
+						if let Some(media_id) = download_error.media_id.as_ref() {
+							transient_failed.push(media_id.clone());
+						}
+						last_error = Some(crate::Error::download_failed(download_error));
+						// the stalled media is not added, because it has not finished
+						current_mediainfo.take();
+						if let Some(stats_file) = maybe_stats_file.as_mut() {
+							stats_file.finish(true)?;
+						}
+
+						break;
+					}
+				}
+
+				continue;
+			},
 		};
 
+		stall_watchdog.output();
+
 		// only print STDOUT to output when requested
 		if print_stdout {
 			trace!("ytdl [STDOUT]: \"{}\"", line);
@@ -222,45 +1050,183 @@ fn handle_stdout<A: DownloadOptions, C: FnMut(DownloadProgress), R: BufRead>(
 			file.write_all(line.as_bytes()).attach_path_err(&path)?;
 			file.write_all(b"\n").attach_path_err(path)?;
 		}
+		if let Some(stats_file) = maybe_stats_file.as_mut().filter(|_| return is_retry_line(&line)) {
+			stats_file.add_retry();
+		}
 
 		if let Some(linetype) = LineType::try_from_line(&line) {
+			unparsed_stats.add_parsed();
+
+			// dont start a new media once the byte limit has been reached
+			if max_download_bytes.is_some_and(|v| return downloaded_bytes >= v)
+				&& matches!(linetype.try_get_parse_helper(&line), Some(CustomParseType::Start(_)))
+			{
+				info!(
+					"Download byte limit reached ({} bytes downloaded), not starting new media",
+					downloaded_bytes
+				);
+				stopped_early = true;
+
+				break;
+			}
 			// clear last_error line once the linetype is not error anymore (like in playlist to not fail if the playlist is not just skipped / private media)
 			if linetype != LineType::Error {
 				last_error = None;
 			}
 			match linetype {
-				// currently there is nothing that needs to be done with "Ffmpeg" lines
-				LineType::Ffmpeg
-				// currently there is nothing that needs to be done with "ProviderSpecific" Lines, thanks to "--print"
-				| LineType::ProviderSpecific
+				LineType::Ffmpeg => {
+					if let Some(step) = linetype.try_get_postprocessor(&line) {
+						if ffmpeg_phase.set_step(step) {
+							let id = current_mediainfo.as_ref().map(|v| return v.id.clone());
+							pgcb(DownloadProgress::Postprocessing(id, step.to_owned()));
+						}
+					}
+				},
+				// "ProviderSpecific" lines are only used for the playlist extraction progress, everything else is done with "--print"
+				LineType::ProviderSpecific => {
+					if let Some(page) = linetype.try_get_playlist_page(&line) {
+						pgcb(DownloadProgress::ExtractingPlaylist(page));
+					}
+					if let Some(remaining) = linetype.try_get_live_wait(&line) {
+						pgcb(DownloadProgress::WaitingForLive(remaining.to_owned()));
+					}
+				},
 				// currently there is nothing that needs to be done with "Generic" Lines
-				| LineType::Generic => (),
+				LineType::Generic => (),
 				LineType::Download => {
-					had_download = true;
-					if let Some(percent) = linetype.try_get_download_percent(line) {
+					ffmpeg_phase.leave();
+					// mark the media as actually downloaded, instead of just being found
+					if let Some(mi) = current_mediainfo.as_mut() {
+						mi.advance_stage(MediaStage::Downloaded);
+					}
+					if linetype.is_download_destination(&line) {
+						format_phases.advance();
+					}
+					if let Some(mut progress) = linetype.try_get_download_progress(&line) {
 						// convert "current_mediainfo" to a reference and operate on the inner value (if exists) to return just the "id"
 						let id = current_mediainfo.as_ref().map(|v| return v.id.clone());
-						pgcb(DownloadProgress::SingleProgress(id, percent));
+						progress.percent = max_progress.update(id.as_deref(), progress.percent);
+						pgcb(match format_phases.phase() {
+							Some((phase, total_phases)) => {
+								DownloadProgress::SingleProgressPhase(id, phase, total_phases, progress)
+							},
+							None => DownloadProgress::SingleProgress(id, progress),
+						});
+					}
+					if let Some(progress) = linetype.try_get_live_progress(&line) {
+						let id = current_mediainfo.as_ref().map(|v| return v.id.clone());
+						pgcb(DownloadProgress::SingleLiveProgress(id, progress));
+					}
+					if let Some(size) = linetype.try_get_download_size(&line) {
+						// the format has finished, the next format starts at 0 again
+						max_progress.reset();
+						downloaded_bytes = downloaded_bytes.saturating_add(size);
+						if let Some(stats_file) = maybe_stats_file.as_mut() {
+							stats_file.add_bytes(size);
+						}
+						let id = current_mediainfo.as_ref().map(|v| return v.id.clone());
+						pgcb(DownloadProgress::SingleDownloaded(id, size));
+					}
+				},
+				LineType::Custom => {
+					ffmpeg_phase.leave();
+					let previous_id = maybe_stats_file
+						.as_ref()
+						.and(current_mediainfo.as_ref())
+						.map(|v| return v.id.clone());
+					handle_linetype_custom(
+						&linetype,
+						&line,
+						&mut current_mediainfo,
+						&mut pgcb,
+						mediainfo_vec,
+						options.download_path(),
+						connection.as_deref_mut(),
+					);
+					match linetype.try_get_parse_helper(&line) {
+						// a new media has a single format until "PARSE_FORMATS" is found
+						Some(CustomParseType::Start(_)) => format_phases.start(1),
+						Some(CustomParseType::Formats(count)) => format_phases.start(count),
+						_ => (),
+					}
+					if let Some(stats_file) = maybe_stats_file.as_mut() {
+						match (previous_id, current_mediainfo.as_ref()) {
+							// a new media has been started
+							(previous_id, Some(mi)) if previous_id.as_deref() != Some(mi.id.as_str()) => {
+								stats_file.start(mi.provider.as_str(), &mi.id);
+							},
+							// the media has finished
+							(Some(_), None) => stats_file.finish(false)?,
+							_ => (),
+						}
 					}
 				},
-				LineType::Custom => handle_linetype_custom(&linetype, &line, &mut current_mediainfo, &mut pgcb, &mut had_download, mediainfo_vec),
 				LineType::ArchiveSkip => {
-					pgcb(DownloadProgress::Skipped(1, SkippedType::InArchive));
+					let archive_skip = linetype.try_get_archive_skip(&line);
+					// blacklisted media is part of the generated youtube-dl archive, so it is only distinguishable by looking it up
+					let blacklisted = match (archive_skip, connection.as_deref_mut()) {
+						(Some((provider, id)), Some(connection)) => {
+							crate::main::archive::remove::is_blacklisted(connection, provider, id)?
+						},
+						_ => false,
+					};
+					let skipped_type = if blacklisted {
+						SkippedType::Blacklisted
+					} else {
+						SkippedType::InArchive
+					};
+					pgcb(DownloadProgress::Skipped {
+						count: 1,
+						typ:   skipped_type,
+						id:    archive_skip.map(|(_, id)| return id.to_owned()),
+					});
 				},
 				LineType::Error => {
 					// the following is using debug printing, because the line may include escape characters, which would mess-up the printing, but is still good to know when reading
 					warn!("Encountered youtube-dl error: {:#?}", line);
-					last_error = Some(crate::Error::other(line));
-					pgcb(DownloadProgress::Skipped(1, SkippedType::Error));
+					ffmpeg_phase.leave();
+					let skipped_type = linetype
+						.try_get_unavailable_reason(&line)
+						.map_or(SkippedType::Error, SkippedType::Unavailable);
+					let media_id = current_mediainfo.as_ref().map(|v| return v.id.clone());
+					let download_error = DownloadError::from_line(line)
+						.with_url(options.get_url())
+						.with_fallback_media_id(media_id);
+					// media without a known id cannot be retried on their own
+					if let Some(media_id) = download_error
+						.media_id
+						.as_ref()
+						.filter(|_| return download_error.is_transient())
+					{
+						transient_failed.push(media_id.clone());
+					}
+					let disk_full = download_error.is_disk_full();
+					let skipped_id = download_error.media_id.clone();
+					last_error = Some(crate::Error::download_failed(download_error));
+					pgcb(DownloadProgress::Skipped {
+						count: 1,
+						typ:   skipped_type,
+						id:    skipped_id,
+					});
 					current_mediainfo.take(); // replace with none, because this media should not be added
+					if let Some(stats_file) = maybe_stats_file.as_mut() {
+						stats_file.finish(true)?;
+					}
+					// every following media would also fail, so stop instead of trying all of them
+					if disk_full {
+						warn!("Stopping youtube-dl, because there is no space left on the device");
+
+						break;
+					}
 				},
 				LineType::Warning => {
 					// ytdl warnings are non-fatal, but should still be logged
 					warn!("youtube-dl: {:#?}", line);
-				}
+				},
 			}
 		} else if !line.is_empty() {
 			info!("No type has been found for line \"{}\"", line);
+			unparsed_stats.add_unparsed(&line);
 		}
 	}
 
@@ -271,7 +1237,9 @@ fn handle_stdout<A: DownloadOptions, C: FnMut(DownloadProgress), R: BufRead>(
 		return Err(last_error);
 	}
 
-	return Ok(());
+	unparsed_stats.report(options.strict_parse())?;
+
+	return Ok(stopped_early);
 }
 
 /// Handle [LineType::Custom]
@@ -282,8 +1250,9 @@ fn handle_linetype_custom<C: FnMut(DownloadProgress)>(
 	line: &str,
 	current_mediainfo: &mut Option<MediaInfo>,
 	mut pgcb: C,
-	had_download: &mut bool,
 	mediainfo_vec: &mut Vec<MediaInfo>,
+	download_path: &std::path::Path,
+	connection: Option<&mut SqliteConnection>,
 ) {
 	if let Some(parsed_type) = linetype.try_get_parse_helper(line) {
 		match parsed_type {
@@ -309,36 +1278,69 @@ fn handle_linetype_custom<C: FnMut(DownloadProgress)>(
 				debug!("Found PARSE_END: \"{}\" \"{}\"", mi.id, mi.provider);
 
 				if let Some(last_mediainfo) = current_mediainfo.take() {
-					pgcb(DownloadProgress::SingleFinished(mi.id.clone())); // callback inside here, because it should only be triggered if there was a media_info to take
+					// callback inside here, because it should only be triggered if there was a media_info to take
+					pgcb(DownloadProgress::SingleFinished(
+						mi.id.clone(),
+						last_mediainfo.filename.as_ref().map(|v| return download_path.join(v)),
+					));
 					if mi.id != last_mediainfo.id {
 						// warn in the weird case where the "current_mediainfo" and result from PARSE_END dont match
 						warn!("Found PARSE_END, but the ID does dont match with \"current_mediainfo\"!");
 					}
 
 					// do not add videos to "mediainfo_vec", unless the media had actually been downloaded
-					if *had_download {
+					if last_mediainfo.stage >= MediaStage::Downloaded {
+						// insert directly, so that the archive does not miss already downloaded media if the process exits unexpectedly
+						if let Some(connection) = connection {
+							if let Err(err) = insert_insmedia(&(&last_mediainfo).into(), connection) {
+								warn!(
+									"Inserting media \"{}\" into the archive errored: {}",
+									last_mediainfo.id, err
+								);
+							}
+						}
+
 						mediainfo_vec.push(last_mediainfo);
 					}
 				} else {
 					// write a log that PARSE_END was present but was None (like in the case of a Error happening)
 					debug!("Found a PARSE_END, but \"current_mediainfo\" was \"None\"!");
 				}
-
-				// reset the value for the next download
-				*had_download = false;
 			},
 			CustomParseType::Playlist(count) => {
 				debug!("Found PLAYLIST {count}");
 				pgcb(DownloadProgress::PlaylistInfo(count));
 			},
+			// the formats are only used for the progress, which is tracked in "handle_stdout"
+			CustomParseType::Formats(count) => {
+				debug!("Found PARSE_FORMATS {count}");
+			},
 			CustomParseType::Move(mi) => {
 				debug!("Found MOVE: \"{}\" \"{}\" \"{:?}\"", mi.id, mi.provider, mi.filename);
 
 				if let Some(last_mediainfo) = current_mediainfo.as_mut() {
-					last_mediainfo.set_filename(
-						mi.filename
-							.expect("Expected try_get_parse_helper to return a mediainfo with filename"),
-					);
+					let filename = mi
+						.filename
+						.expect("Expected try_get_parse_helper to return a mediainfo with filename");
+
+					// write a sidecar, so that recovery does not depend on the filename being parsable
+					// only done if the file actually exists, as yt-dlp may print paths which have not been written
+					// "filename" is only the file name, so it has to be joined with the download path
+					let media_path = download_path.join(&filename);
+					if media_path.is_file() {
+						// enrich with the metadata before writing the sidecar, so that it is also available on recovery
+						apply_file_metadata(last_mediainfo, &media_path);
+
+						if let Err(err) = last_mediainfo.write_sidecar(&media_path) {
+							warn!(
+								"Writing sidecar for \"{}\" failed: {}",
+								media_path.to_string_lossy(),
+								err
+							);
+						}
+					}
+
+					last_mediainfo.set_filename(filename);
 				} else {
 					warn!("Found MOVE, but did not have a current_mediainfo");
 				}
@@ -347,9 +1349,47 @@ fn handle_linetype_custom<C: FnMut(DownloadProgress)>(
 	}
 }
 
+/// Enrich `media` with the metadata of the downloaded file at `media_path`
+/// The duration is taken from the ".info.json" (see [`InfoJson`]) and probed from the file if not available there
+/// Errors are only logged, because the metadata is not required for the download
+fn apply_file_metadata(media: &mut MediaInfo, media_path: &std::path::Path) {
+	match InfoJson::try_for_media(media_path) {
+		Ok(Some(info)) => media.apply_info_json(&info),
+		Ok(None) => (),
+		Err(err) => warn!(
+			"Reading info json for \"{}\" failed: {}",
+			media_path.to_string_lossy(),
+			err
+		),
+	}
+
+	match std::fs::metadata(media_path) {
+		Ok(metadata) => media.filesize = Some(metadata.len()),
+		Err(err) => warn!(
+			"Reading the size of \"{}\" failed: {}",
+			media_path.to_string_lossy(),
+			err
+		),
+	}
+
+	if media.duration.is_none() {
+		match ffmpeg_probe(media_path) {
+			Ok(probe) => {
+				media.duration = parse_duration(&probe).ok().and_then(duration_to_secs);
+			},
+			Err(err) => debug!(
+				"Probing the duration of \"{}\" failed: {}",
+				media_path.to_string_lossy(),
+				err
+			),
+		}
+	}
+}
+
 #[cfg(test)]
 pub(crate) mod test_utils {
 	use std::{
+		ffi::OsStr,
 		path::PathBuf,
 		sync::{
 			atomic::AtomicUsize,
@@ -367,21 +1407,47 @@ pub(crate) mod test_utils {
 		download_options::{
 			DownloadOptions,
 			FormatArgument,
+			FormatConstraints,
 		},
 		DownloadProgress,
 	};
+	use crate::spawn::ytdl::{
+		YtdlCapabilities,
+		YTDL_BIN_NAME,
+	};
 
 	/// Test Implementation for [`DownloadOptions`]
 	pub struct TestOptions {
-		pub audio_only:        bool,
-		pub extra_arguments:   Vec<PathBuf>,
-		pub download_path:     PathBuf,
-		pub url:               String,
-		pub archive_lines:     Vec<String>,
-		pub print_command_log: bool,
-		pub save_command_log:  bool,
-		pub sub_langs:         Option<String>,
-		pub ytdl_version:      chrono::NaiveDate,
+		pub audio_only:           bool,
+		pub extra_arguments:      Vec<PathBuf>,
+		pub download_path:        PathBuf,
+		pub url:                  String,
+		pub archive_lines:        Vec<String>,
+		pub print_command_log:    bool,
+		pub save_command_log:     bool,
+		pub sub_langs:            Option<String>,
+		pub subs_external:        bool,
+		pub write_info_json:      bool,
+		pub ytdl_version:         chrono::NaiveDate,
+		pub strict_parse:         bool,
+		pub max_download_bytes:   Option<u64>,
+		pub stall_timeout:        Option<std::time::Duration>,
+		pub stall_kill:           bool,
+		pub playlist_order:       super::PlaylistOrder,
+		pub embed_thumbnail:      bool,
+		pub remux_video:          bool,
+		pub remux_after_download: bool,
+		pub ytdl_capabilities:    Option<YtdlCapabilities>,
+		pub cookies_file:         Option<PathBuf>,
+		pub cookies_browser:      Option<String>,
+		pub playlist_items:       Option<String>,
+		pub max_downloads:        Option<usize>,
+		pub limit_rate:           Option<u64>,
+		pub max_height:           Option<u32>,
+		pub max_filesize:         Option<u64>,
+		pub prefer_codec:         Option<String>,
+		pub concurrent_fragments: Option<usize>,
+		pub live:                 bool,
 
 		pub audio_format: FormatArgument<'static>,
 		pub video_format: FormatArgument<'static>,
@@ -421,6 +1487,13 @@ pub(crate) mod test_utils {
 			return self;
 		}
 
+		/// Test with custom ytdl_capabilities
+		pub fn with_capabilities(mut self, ytdl_capabilities: YtdlCapabilities) -> Self {
+			self.ytdl_capabilities = Some(ytdl_capabilities);
+
+			return self;
+		}
+
 		/// Get the test default version
 		pub fn default_version() -> chrono::NaiveDate {
 			// return current date plus 1 year to activate all features for now
@@ -444,15 +1517,36 @@ pub(crate) mod test_utils {
 	impl Default for TestOptions {
 		fn default() -> Self {
 			return Self {
-				audio_only:        false,
-				extra_arguments:   Vec::default(),
-				download_path:     PathBuf::default(),
-				url:               String::default(),
-				archive_lines:     Vec::default(),
-				print_command_log: false,
-				save_command_log:  false,
-				sub_langs:         None,
-				ytdl_version:      Self::default_version(),
+				audio_only:           false,
+				extra_arguments:      Vec::default(),
+				download_path:        PathBuf::default(),
+				url:                  String::default(),
+				archive_lines:        Vec::default(),
+				print_command_log:    false,
+				save_command_log:     false,
+				sub_langs:            None,
+				subs_external:        false,
+				write_info_json:      false,
+				ytdl_version:         Self::default_version(),
+				strict_parse:         false,
+				max_download_bytes:   None,
+				stall_timeout:        None,
+				stall_kill:           false,
+				playlist_order:       super::PlaylistOrder::Default,
+				embed_thumbnail:      true,
+				remux_video:          true,
+				remux_after_download: false,
+				ytdl_capabilities:    None,
+				cookies_file:         None,
+				cookies_browser:      None,
+				playlist_items:       None,
+				max_downloads:        None,
+				limit_rate:           None,
+				max_height:           None,
+				max_filesize:         None,
+				prefer_codec:         None,
+				concurrent_fragments: None,
+				live:                 false,
 
 				audio_format: "mp3",
 				video_format: "mkv",
@@ -497,10 +1591,22 @@ pub(crate) mod test_utils {
 			return self.sub_langs.as_ref().map(String::as_str);
 		}
 
+		fn subs_external(&self) -> bool {
+			return self.subs_external;
+		}
+
+		fn write_info_json(&self) -> bool {
+			return self.write_info_json;
+		}
+
 		fn ytdl_version(&self) -> chrono::NaiveDate {
 			return self.ytdl_version;
 		}
 
+		fn ytdl_bin(&self) -> &OsStr {
+			return OsStr::new(YTDL_BIN_NAME);
+		}
+
 		fn get_audio_format(&self) -> FormatArgument {
 			return self.audio_format;
 		}
@@ -508,6 +1614,82 @@ pub(crate) mod test_utils {
 		fn get_video_format(&self) -> FormatArgument {
 			return self.video_format;
 		}
+
+		fn strict_parse(&self) -> bool {
+			return self.strict_parse;
+		}
+
+		fn max_download_bytes(&self) -> Option<u64> {
+			return self.max_download_bytes;
+		}
+
+		fn stall_timeout(&self) -> Option<std::time::Duration> {
+			return self.stall_timeout;
+		}
+
+		fn stall_kill(&self) -> bool {
+			return self.stall_kill;
+		}
+
+		fn playlist_order(&self) -> super::PlaylistOrder {
+			return self.playlist_order;
+		}
+
+		fn embed_thumbnail(&self) -> bool {
+			return self.embed_thumbnail;
+		}
+
+		fn remux_video(&self) -> bool {
+			return self.remux_video;
+		}
+
+		fn remux_after_download(&self) -> bool {
+			return self.remux_after_download;
+		}
+
+		fn ytdl_capabilities(&self) -> Option<&YtdlCapabilities> {
+			return self.ytdl_capabilities.as_ref();
+		}
+
+		fn cookies(&self) -> Option<super::CookieSource<'_>> {
+			if let Some(path) = &self.cookies_file {
+				return Some(super::CookieSource::File(path));
+			}
+
+			return self.cookies_browser.as_deref().map(super::CookieSource::Browser);
+		}
+
+		fn playlist_items(&self) -> Option<&str> {
+			return self.playlist_items.as_deref();
+		}
+
+		fn max_downloads(&self) -> Option<usize> {
+			return self.max_downloads;
+		}
+
+		fn limit_rate(&self) -> Option<u64> {
+			return self.limit_rate;
+		}
+
+		fn format_constraints(&self) -> FormatConstraints<'_> {
+			return FormatConstraints {
+				max_height:   self.max_height,
+				max_filesize: self.max_filesize,
+				prefer_codec: self.prefer_codec.as_deref(),
+			};
+		}
+
+		fn concurrent_fragments(&self) -> Option<usize> {
+			return self.concurrent_fragments;
+		}
+
+		fn retries(&self) -> usize {
+			return 0;
+		}
+
+		fn live(&self) -> bool {
+			return self.live;
+		}
 	}
 
 	/// Test helper function to create a connection AND get a clean testing dir path
@@ -561,6 +1743,7 @@ mod test {
 	mod handle_stdout {
 		use test_utils::{
 			callback_counter,
+			create_connection,
 			TestOptions,
 		};
 
@@ -571,15 +1754,35 @@ mod test {
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
 				DownloadProgress::SingleStarting("-----------".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 50),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 57),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleFinished("-----------".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(0).with_speed("207.76KiB/s").with_eta("06:27"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(50).with_speed("526.19KiB/s").with_eta("01:16"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(100).with_speed("5.89MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("-----------".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("-----------".to_owned()), 82250301),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(0).with_speed("196.76KiB/s").with_eta("00:18"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(57).with_speed("9.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("-----------".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("-----------".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("-----------".to_owned(), None),
 				DownloadProgress::UrlFinished(1),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -602,10 +1805,13 @@ PARSE_END 'youtube' '-----------'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
 				&options,
 				callback_counter(&expect_index, expected_pg),
 				BufReader::new(input.as_bytes()),
 				&mut media_vec,
+				&mut Vec::new(),
+				None,
 			);
 
 			assert!(res.is_ok());
@@ -613,7 +1819,9 @@ PARSE_END 'youtube' '-----------'
 			assert_eq!(1, media_vec.len());
 
 			assert_eq!(
-				vec![MediaInfo::new("-----------", "youtube").with_title("Some Title Here")],
+				vec![MediaInfo::new("-----------", "youtube")
+					.with_title("Some Title Here")
+					.with_stage(MediaStage::Downloaded)],
 				media_vec
 			);
 		}
@@ -623,25 +1831,65 @@ PARSE_END 'youtube' '-----------'
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
 				DownloadProgress::SingleStarting("----------0".to_owned(), "Some Title Here 0".to_owned()),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 50),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 57),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("----------0".to_owned()), 100),
-				DownloadProgress::SingleFinished("----------0".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(0).with_speed("207.76KiB/s").with_eta("06:27"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(50).with_speed("526.19KiB/s").with_eta("01:16"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(100).with_speed("5.89MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("----------0".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("----------0".to_owned()), 82250301),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(0).with_speed("196.76KiB/s").with_eta("00:18"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(57).with_speed("9.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("----------0".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("----------0".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("----------0".to_owned(), None),
 				DownloadProgress::SingleStarting("----------1".to_owned(), "Some Title Here 1".to_owned()),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 50),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 57),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("----------1".to_owned()), 100),
-				DownloadProgress::SingleFinished("----------1".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("----------1".to_owned()),
+					TransferProgress::new(0).with_speed("207.76KiB/s").with_eta("06:27"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------1".to_owned()),
+					TransferProgress::new(50).with_speed("526.19KiB/s").with_eta("01:16"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------1".to_owned()),
+					TransferProgress::new(100).with_speed("5.89MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("----------1".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("----------1".to_owned()), 82250301),
+				DownloadProgress::SingleProgress(
+					Some("----------1".to_owned()),
+					TransferProgress::new(0).with_speed("196.76KiB/s").with_eta("00:18"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------1".to_owned()),
+					TransferProgress::new(57).with_speed("9.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------1".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("----------1".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("----------1".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("----------1".to_owned(), None),
 				DownloadProgress::UrlFinished(2),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -674,22 +1922,348 @@ PARSE_END 'soundcloud' '----------1'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
+				&options,
+				callback_counter(&expect_index, expected_pg),
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert!(res.is_ok());
+
+			assert_eq!(2, media_vec.len());
+
+			assert_eq!(
+				vec![
+					MediaInfo::new("----------0", "youtube")
+						.with_title("Some Title Here 0")
+						.with_stage(MediaStage::Downloaded),
+					MediaInfo::new("----------1", "soundcloud")
+						.with_title("Some Title Here 1")
+						.with_stage(MediaStage::Downloaded)
+				],
+				media_vec
+			);
+		}
+
+		#[test]
+		fn test_insert_into_archive() {
+			use crate::data::{
+				sql_models::Media,
+				sql_schema::media_archive,
+			};
+			use diesel::prelude::*;
+
+			let (mut connection, _tempdir, _) = create_connection();
+			let options = TestOptions::new_handle_stdout(false);
+
+			// the second media is only found (no download), so should not be inserted
+			let input = r#"
+PARSE_START 'youtube' '----------0' Some Title Here 0
+[download]   0.0% of 78.44MiB at 207.76KiB/s ETA 06:27
+[download] 100% of 78.44MiB in 00:07
+PARSE_END 'youtube' '----------0'
+PARSE_START 'soundcloud' '----------1' Some Title Here 1
+PARSE_END 'soundcloud' '----------1'
+			"#;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+
+			let res = handle_stdout(
+				Some(&mut connection),
+				&options,
+				|_| {},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert!(res.is_ok());
+			assert_eq!(1, media_vec.len());
+
+			let archived = media_archive::table
+				.load::<Media>(&mut connection)
+				.expect("Expected the archive to be readable");
+
+			assert_eq!(1, archived.len());
+			assert_eq!("----------0", archived[0].media_id);
+			assert_eq!("youtube", archived[0].provider);
+			assert_eq!("Some Title Here 0", archived[0].title);
+		}
+
+		#[test]
+		fn test_max_download_bytes() {
+			let expected_pg = &vec![
+				DownloadProgress::UrlStarting,
+				DownloadProgress::SingleStarting("----------0".to_owned(), "Some Title Here 0".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(0).with_speed("196.76KiB/s").with_eta("00:18"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(57).with_speed("9.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("----------0".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("----------0".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("----------0".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("----------0".to_owned(), None),
+				DownloadProgress::UrlFinished(1),
+			];
+			let expect_index = Arc::new(AtomicUsize::new(0));
+
+			let mut options = TestOptions::new_handle_stdout(false);
+			options.max_download_bytes = Some(1024);
+
+			let input = r#"
+PARSE_START 'youtube' '----------0' Some Title Here 0
+[download]   0.0% of 3.47MiB at 196.76KiB/s ETA 00:18
+[download]  57.6% of 3.47MiB at  9.57MiB/s ETA 00:00
+[download] 100% of 3.47MiB at 10.57MiB/s ETA 00:00
+[download] 100% of 3.47MiB in 00:00
+PARSE_END 'youtube' '----------0'
+PARSE_START 'soundcloud' '----------1' Some Title Here 1
+[download]   0.0% of 3.47MiB at 196.76KiB/s ETA 00:18
+[download] 100% of 3.47MiB in 00:00
+PARSE_END 'soundcloud' '----------1'
+			"#;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				callback_counter(&expect_index, expected_pg),
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			// "true" because the reading was stopped early
+			assert!(res.expect("Expected handle_stdout to be ok"));
+
+			assert_eq!(
+				vec![MediaInfo::new("----------0", "youtube")
+					.with_title("Some Title Here 0")
+					.with_stage(MediaStage::Downloaded)],
+				media_vec
+			);
+		}
+
+		/// Reader which returns its parts in order, sleeping for the given duration before each part
+		struct DelayedReader(std::collections::VecDeque<(Duration, &'static str)>);
+
+		impl Read for DelayedReader {
+			fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+				let Some((delay, part)) = self.0.pop_front() else {
+					return Ok(0);
+				};
+
+				std::thread::sleep(delay);
+				// the parts are expected to be small enough to fit into the buffer
+				buf[..part.len()].copy_from_slice(part.as_bytes());
+
+				return Ok(part.len());
+			}
+		}
+
+		/// Test helper to run [`handle_stdout`] with a stall in the ffmpeg phase and return all progress events
+		fn run_stalled(
+			options: &TestOptions,
+			media_vec: &mut Vec<MediaInfo>,
+			transient_failed: &mut Vec<String>,
+		) -> (Result<bool, crate::Error>, Vec<DownloadProgress>) {
+			let reader = DelayedReader(
+				[
+					(
+						Duration::ZERO,
+						"PARSE_START 'youtube' '----------0' Some Title Here 0\n[download] 100% of 3.47MiB in 00:00\n[Merger] Merging formats into \"/tmp/a.mkv\"\n",
+					),
+					// longer than "STALL_CHECK_INTERVAL"
+					(STALL_CHECK_INTERVAL + Duration::from_millis(500), "PARSE_END 'youtube' '----------0'\n"),
+				]
+				.into(),
+			);
+
+			let mut events = Vec::new();
+			let res = handle_stdout(
+				None,
+				options,
+				|v| events.push(v),
+				BufReader::new(reader),
+				media_vec,
+				transient_failed,
+				None,
+			);
+
+			return (res, events);
+		}
+
+		#[test]
+		fn test_stalled() {
+			let mut options = TestOptions::new_handle_stdout(false);
+			options.stall_timeout = Some(Duration::ZERO);
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+
+			let (res, events) = run_stalled(&options, &mut media_vec, &mut Vec::new());
+
+			// "false" because the reading was not stopped
+			assert!(!res.expect("Expected handle_stdout to be ok"));
+
+			assert_eq!(
+				1,
+				events
+					.iter()
+					.filter(|v| return matches!(v, DownloadProgress::Stalled(Some(id), _) if id == "----------0"))
+					.count()
+			);
+			assert_eq!(Some(&DownloadProgress::UrlFinished(1)), events.last());
+			assert_eq!(1, media_vec.len());
+		}
+
+		#[test]
+		fn test_stalled_kill() {
+			let mut options = TestOptions::new_handle_stdout(false);
+			options.stall_timeout = Some(Duration::ZERO);
+			options.stall_kill = true;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			let mut transient_failed = Vec::new();
+
+			let (res, events) = run_stalled(&options, &mut media_vec, &mut transient_failed);
+
+			let err = res.expect_err("Expected handle_stdout to fail with a stall");
+			let download_err = err.as_download_error().expect("Expected a DownloadError");
+			assert!(download_err.is_stalled());
+			assert_eq!(Some("----------0"), download_err.media_id.as_deref());
+			// the stalled media is retried like a transient error
+			assert_eq!(vec!["----------0".to_owned()], transient_failed);
+
+			assert!(events
+				.iter()
+				.any(|v| return matches!(v, DownloadProgress::Stalled(Some(id), _) if id == "----------0")));
+			assert_eq!(Some(&DownloadProgress::UrlFinished(0)), events.last());
+			assert!(media_vec.is_empty());
+		}
+
+		#[test]
+		fn test_stalled_not_before_media() {
+			let mut options = TestOptions::new_handle_stdout(false);
+			options.stall_timeout = Some(Duration::ZERO);
+			options.stall_kill = true;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			// no media has been started yet, like while extracting a playlist
+			let reader = DelayedReader(
+				[
+					(STALL_CHECK_INTERVAL + Duration::from_millis(500), "PARSE_START 'youtube' '----------0' Some Title Here 0\n[download] 100% of 3.47MiB in 00:00\nPARSE_END 'youtube' '----------0'\n"),
+				]
+				.into(),
+			);
+
+			let mut events = Vec::new();
+			let res = handle_stdout(
+				None,
+				&options,
+				|v| events.push(v),
+				BufReader::new(reader),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert!(!res.expect("Expected handle_stdout to be ok"));
+			assert!(!events.iter().any(|v| return matches!(v, DownloadProgress::Stalled(..))));
+			assert_eq!(1, media_vec.len());
+		}
+
+		#[test]
+		fn test_cancelled() {
+			let options = TestOptions::new_handle_stdout(false);
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			// the second media only finishes after the token has been cancelled
+			let reader = DelayedReader(
+				[
+					(
+						Duration::ZERO,
+						"PARSE_START 'youtube' '----------0' Some Title Here 0\n[download] 100% of 3.47MiB in 00:00\nPARSE_END 'youtube' '----------0'\n",
+					),
+					(
+						Duration::from_millis(500),
+						"PARSE_START 'youtube' '----------1' Some Title Here 1\n",
+					),
+					(Duration::from_secs(5), "PARSE_END 'youtube' '----------1'\n"),
+				]
+				.into(),
+			);
+
+			let cancel = CancellationToken::new();
+			let cancel_clone = cancel.clone();
+			std::thread::spawn(move || {
+				std::thread::sleep(Duration::from_millis(1000));
+				cancel_clone.cancel();
+			});
+
+			let res = handle_stdout(
+				None,
 				&options,
-				callback_counter(&expect_index, expected_pg),
-				BufReader::new(input.as_bytes()),
+				|_| {},
+				BufReader::new(reader),
 				&mut media_vec,
+				&mut Vec::new(),
+				Some(&cancel),
 			);
 
-			assert!(res.is_ok());
+			assert!(res.expect_err("Expected handle_stdout to be cancelled").is_cancelled());
+			// only the media which finished before the cancellation is added
+			assert_eq!(1, media_vec.len());
+			assert_eq!("----------0", media_vec[0].id);
+		}
 
-			assert_eq!(2, media_vec.len());
+		#[test]
+		fn test_postprocessing() {
+			let options = TestOptions::new_handle_stdout(false);
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			let input = "PARSE_START 'youtube' '----------0' Some Title Here 0
+[download] 100% of 3.47MiB in 00:00
+[Merger] Merging formats into \"/tmp/a.mkv\"
+[ffmpeg] Some ffmpeg output
+[Merger] Some other Merger output
+[EmbedThumbnail] ffmpeg: Adding thumbnail to \"/tmp/a.mkv\"
+PARSE_END 'youtube' '----------0'
+";
 
+			let mut events = Vec::new();
+			handle_stdout(
+				None,
+				&options,
+				|v| events.push(v),
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			)
+			.expect("Expected handle_stdout to be ok");
+
+			// every step is only reported once, when it is entered
+			let steps: Vec<&DownloadProgress> = events
+				.iter()
+				.filter(|v| return matches!(v, DownloadProgress::Postprocessing(..)))
+				.collect();
 			assert_eq!(
 				vec![
-					MediaInfo::new("----------0", "youtube").with_title("Some Title Here 0"),
-					MediaInfo::new("----------1", "soundcloud").with_title("Some Title Here 1")
+					&DownloadProgress::Postprocessing(Some("----------0".to_owned()), "Merger".to_owned()),
+					&DownloadProgress::Postprocessing(Some("----------0".to_owned()), "EmbedThumbnail".to_owned()),
 				],
-				media_vec
+				steps
 			);
 		}
 
@@ -698,16 +2272,40 @@ PARSE_END 'soundcloud' '----------1'
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
 				DownloadProgress::SingleStarting("-----------".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 50),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 57),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleFinished("-----------".to_owned()),
-				DownloadProgress::Skipped(1, SkippedType::InArchive),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(0).with_speed("207.76KiB/s").with_eta("06:27"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(50).with_speed("526.19KiB/s").with_eta("01:16"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(100).with_speed("5.89MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("-----------".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("-----------".to_owned()), 82250301),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(0).with_speed("196.76KiB/s").with_eta("00:18"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(57).with_speed("9.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("-----------".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("-----------".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("-----------".to_owned(), None),
+				DownloadProgress::Skipped {
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someId".to_owned()),
+				},
 				DownloadProgress::UrlFinished(1),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -731,10 +2329,13 @@ PARSE_END 'youtube' '-----------'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
 				&options,
 				callback_counter(&expect_index, expected_pg),
 				BufReader::new(input.as_bytes()),
 				&mut media_vec,
+				&mut Vec::new(),
+				None,
 			);
 
 			assert!(res.is_ok());
@@ -742,7 +2343,9 @@ PARSE_END 'youtube' '-----------'
 			assert_eq!(1, media_vec.len());
 
 			assert_eq!(
-				vec![MediaInfo::new("-----------", "youtube").with_title("Some Title Here")],
+				vec![MediaInfo::new("-----------", "youtube")
+					.with_title("Some Title Here")
+					.with_stage(MediaStage::Downloaded)],
 				media_vec
 			);
 		}
@@ -752,15 +2355,38 @@ PARSE_END 'youtube' '-----------'
 		fn test_skip_error_and_normal() {
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
+				DownloadProgress::ExtractingPlaylist(0),
 				DownloadProgress::PlaylistInfo(4), // "[] Playlist ...: Downloading ... items of ..."
-				DownloadProgress::Skipped(1, SkippedType::InArchive), // one archive skip
-				DownloadProgress::Skipped(1, SkippedType::InArchive), // one archive skip
-				DownloadProgress::Skipped(1, SkippedType::Error), // one error skip
+				DownloadProgress::Skipped {
+					// one archive skip
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someid1".to_owned()),
+				},
+				DownloadProgress::Skipped {
+					// one archive skip
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someid2".to_owned()),
+				},
+				DownloadProgress::Skipped {
+					// one error skip
+					count: 1,
+					typ:   SkippedType::Error,
+					id:    Some("someid3".to_owned()),
+				},
 				DownloadProgress::SingleStarting("someid4".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 100),
-				DownloadProgress::SingleFinished("someid4".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("someid4".to_owned()),
+					TransferProgress::new(0).with_speed("10.57MiB/s").with_eta("09:37"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("someid4".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("someid4".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("someid4".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("someid4".to_owned(), Some(PathBuf::from("somewhere"))),
 				DownloadProgress::UrlFinished(1),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -795,10 +2421,13 @@ PARSE_END 'aprovider' 'someid4'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
 				&options,
 				callback_counter(&expect_index, expected_pg),
 				BufReader::new(input.as_bytes()),
 				&mut media_vec,
+				&mut Vec::new(),
+				None,
 			);
 
 			assert!(res.is_ok());
@@ -808,7 +2437,8 @@ PARSE_END 'aprovider' 'someid4'
 			assert_eq!(
 				vec![MediaInfo::new("someid4", "aprovider")
 					.with_title("Some Title Here")
-					.with_filename("somewhere")],
+					.with_filename("somewhere")
+					.with_stage(MediaStage::Downloaded)],
 				media_vec
 			);
 		}
@@ -818,15 +2448,35 @@ PARSE_END 'aprovider' 'someid4'
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
 				DownloadProgress::SingleStarting("-----------".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 50),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 57),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("-----------".to_owned()), 100),
-				DownloadProgress::SingleFinished("-----------".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(0).with_speed("207.76KiB/s").with_eta("06:27"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(50).with_speed("526.19KiB/s").with_eta("01:16"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(100).with_speed("5.89MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("-----------".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("-----------".to_owned()), 82250301),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(0).with_speed("196.76KiB/s").with_eta("00:18"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(57).with_speed("9.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("-----------".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("-----------".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("-----------".to_owned(), None),
 				DownloadProgress::UrlFinished(1),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -851,10 +2501,13 @@ PARSE_END 'youtube' '-----------'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
 				&options,
 				callback_counter(&expect_index, expected_pg),
 				BufReader::new(input.as_bytes()),
 				&mut media_vec,
+				&mut Vec::new(),
+				None,
 			);
 
 			assert!(res.is_ok());
@@ -862,7 +2515,9 @@ PARSE_END 'youtube' '-----------'
 			assert_eq!(1, media_vec.len());
 
 			assert_eq!(
-				vec![MediaInfo::new("-----------", "youtube").with_title("Some Title Here")],
+				vec![MediaInfo::new("-----------", "youtube")
+					.with_title("Some Title Here")
+					.with_stage(MediaStage::Downloaded)],
 				media_vec
 			);
 		}
@@ -872,21 +2527,32 @@ PARSE_END 'youtube' '-----------'
 		fn test_error_while_downloading() {
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
+				DownloadProgress::ExtractingPlaylist(0),
 				DownloadProgress::PlaylistInfo(4), // "[] Playlist ...: Downloading ... items of ..."
 				DownloadProgress::SingleStarting("someid1".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("someid1".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("someid1".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("someid1".to_owned()), 100),
-				DownloadProgress::SingleFinished("someid1".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("someid1".to_owned()),
+					TransferProgress::new(0).with_speed("10.57MiB/s").with_eta("09:37"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("someid1".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("someid1".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("someid1".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("someid1".to_owned(), Some(PathBuf::from("somewhere"))),
 				DownloadProgress::SingleStarting("someid2".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("someid2".to_owned()), 2),
-				DownloadProgress::Skipped(1, SkippedType::Error), // one error skip
-				DownloadProgress::SingleStarting("someid3".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("someid3".to_owned()), 0),
-				DownloadProgress::Skipped(1, SkippedType::Error), // one error skip
-				DownloadProgress::SingleStarting("someid4".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 0),
-				DownloadProgress::Skipped(1, SkippedType::Error), // one error skip
+				DownloadProgress::SingleProgress(
+					Some("someid2".to_owned()),
+					TransferProgress::new(2).with_speed("4.18MiB/s").with_eta("01:09"),
+				),
+				DownloadProgress::Skipped {
+					// one error skip
+					count: 1,
+					typ:   SkippedType::Error,
+					id:    Some("someid2".to_owned()),
+				},
+				// the following media are not tried, because there is no space left
 				DownloadProgress::UrlFinished(1),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -949,20 +2615,27 @@ PARSE_END 'aprovider' 'someid4'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
 				&options,
 				callback_counter(&expect_index, expected_pg),
 				BufReader::new(input.as_bytes()),
 				&mut media_vec,
+				&mut Vec::new(),
+				None,
 			);
 
-			assert!(res.is_ok());
+			assert!(res
+				.expect_err("Expected the disk full error")
+				.as_download_error()
+				.is_some_and(DownloadError::is_disk_full));
 
 			assert_eq!(1, media_vec.len());
 
 			assert_eq!(
 				vec![MediaInfo::new("someid1", "aprovider")
 					.with_title("Some Title Here")
-					.with_filename("somewhere")],
+					.with_filename("somewhere")
+					.with_stage(MediaStage::Downloaded)],
 				media_vec
 			);
 		}
@@ -972,15 +2645,38 @@ PARSE_END 'aprovider' 'someid4'
 		fn test_playlistsize_from_playlist_downloading_items() {
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
+				DownloadProgress::ExtractingPlaylist(0),
 				DownloadProgress::PlaylistInfo(4), // "[] Playlist ...: Downloading ... items of ..."
-				DownloadProgress::Skipped(1, SkippedType::InArchive), // one archive skip
-				DownloadProgress::Skipped(1, SkippedType::InArchive), // one archive skip
-				DownloadProgress::Skipped(1, SkippedType::Error), // one error skip
+				DownloadProgress::Skipped {
+					// one archive skip
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someid1".to_owned()),
+				},
+				DownloadProgress::Skipped {
+					// one archive skip
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someid2".to_owned()),
+				},
+				DownloadProgress::Skipped {
+					// one error skip
+					count: 1,
+					typ:   SkippedType::Error,
+					id:    Some("someid3".to_owned()),
+				},
 				DownloadProgress::SingleStarting("someid4".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 100),
-				DownloadProgress::SingleFinished("someid4".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("someid4".to_owned()),
+					TransferProgress::new(0).with_speed("10.57MiB/s").with_eta("09:37"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("someid4".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("someid4".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("someid4".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("someid4".to_owned(), Some(PathBuf::from("somewhere"))),
 				DownloadProgress::UrlFinished(1),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -1015,10 +2711,13 @@ PARSE_END 'aprovider' 'someid4'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
 				&options,
 				callback_counter(&expect_index, expected_pg),
 				BufReader::new(input.as_bytes()),
 				&mut media_vec,
+				&mut Vec::new(),
+				None,
 			);
 
 			assert!(res.is_ok());
@@ -1028,7 +2727,8 @@ PARSE_END 'aprovider' 'someid4'
 			assert_eq!(
 				vec![MediaInfo::new("someid4", "aprovider")
 					.with_title("Some Title Here")
-					.with_filename("somewhere")],
+					.with_filename("somewhere")
+					.with_stage(MediaStage::Downloaded)],
 				media_vec
 			);
 		}
@@ -1038,16 +2738,39 @@ PARSE_END 'aprovider' 'someid4'
 		fn test_playlistsize_from_custom_playlist() {
 			let expected_pg = &vec![
 				DownloadProgress::UrlStarting,
+				DownloadProgress::ExtractingPlaylist(0),
 				DownloadProgress::PlaylistInfo(4), // "[] Playlist ...: Downloading ... items of ..."
-				DownloadProgress::Skipped(1, SkippedType::InArchive), // one archive skip
-				DownloadProgress::Skipped(1, SkippedType::InArchive), // one archive skip
-				DownloadProgress::Skipped(1, SkippedType::Error), // one error skip
+				DownloadProgress::Skipped {
+					// one archive skip
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someid1".to_owned()),
+				},
+				DownloadProgress::Skipped {
+					// one archive skip
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someid2".to_owned()),
+				},
+				DownloadProgress::Skipped {
+					// one error skip
+					count: 1,
+					typ:   SkippedType::Error,
+					id:    Some("someid3".to_owned()),
+				},
 				DownloadProgress::PlaylistInfo(4), // custom "PLAYLIST ''" line
 				DownloadProgress::SingleStarting("someid4".to_owned(), "Some Title Here".to_owned()),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 0),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 100),
-				DownloadProgress::SingleProgress(Some("someid4".to_owned()), 100),
-				DownloadProgress::SingleFinished("someid4".to_owned()),
+				DownloadProgress::SingleProgress(
+					Some("someid4".to_owned()),
+					TransferProgress::new(0).with_speed("10.57MiB/s").with_eta("09:37"),
+				),
+				DownloadProgress::SingleProgress(
+					Some("someid4".to_owned()),
+					TransferProgress::new(100).with_speed("10.57MiB/s").with_eta("00:00"),
+				),
+				DownloadProgress::SingleProgress(Some("someid4".to_owned()), TransferProgress::new(100)),
+				DownloadProgress::SingleDownloaded(Some("someid4".to_owned()), 3638558),
+				DownloadProgress::SingleFinished("someid4".to_owned(), Some(PathBuf::from("somewhere"))),
 				DownloadProgress::UrlFinished(1),
 			];
 			let expect_index = Arc::new(AtomicUsize::new(0));
@@ -1083,10 +2806,13 @@ PARSE_END 'aprovider' 'someid4'
 			let mut media_vec: Vec<MediaInfo> = Vec::new();
 
 			let res = handle_stdout(
+				None,
 				&options,
 				callback_counter(&expect_index, expected_pg),
 				BufReader::new(input.as_bytes()),
 				&mut media_vec,
+				&mut Vec::new(),
+				None,
 			);
 
 			assert!(res.is_ok());
@@ -1096,9 +2822,496 @@ PARSE_END 'aprovider' 'someid4'
 			assert_eq!(
 				vec![MediaInfo::new("someid4", "aprovider")
 					.with_title("Some Title Here")
-					.with_filename("somewhere")],
+					.with_filename("somewhere")
+					.with_stage(MediaStage::Downloaded)],
 				media_vec
 			);
 		}
+
+		/// Test that a error as the last line is returned as a structured download error
+		#[test]
+		fn test_last_error_is_download_error() {
+			let expected_pg = &[
+				DownloadProgress::UrlStarting,
+				DownloadProgress::SingleStarting("someid1".to_owned(), "Some Title Here".to_owned()),
+				DownloadProgress::Skipped {
+					count: 1,
+					typ:   SkippedType::Error,
+					id:    Some("someid1".to_owned()),
+				},
+				DownloadProgress::UrlFinished(0),
+			];
+			let expect_index = Arc::new(AtomicUsize::new(0));
+
+			let mut options = TestOptions::new_handle_stdout(false);
+			options.url = "https://aprovider.com/video/someid1".to_owned();
+
+			let input = r#"
+[aprovider] Extracting URL: https://aprovider.com/video/someid1
+PARSE_START 'aprovider' 'someid1' Some Title Here
+ERROR: unable to write data: [Errno 28] No space left on device
+"#;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				callback_counter(&expect_index, expected_pg),
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			let err = res.expect_err("Expected the last error to be returned");
+			let download_err = err.as_download_error().expect("Expected a download error");
+			assert_eq!(crate::error::DownloadErrorCategory::DiskFull, download_err.category);
+			assert_eq!(Some("someid1".to_owned()), download_err.media_id);
+			assert_eq!(Some("https://aprovider.com/video/someid1".to_owned()), download_err.url);
+			assert!(media_vec.is_empty());
+		}
+
+		/// Test that the media stats are written next to the command log
+		#[test]
+		fn test_media_stats_file() {
+			let (_connection, _tempdir, download_path) = test_utils::create_connection();
+
+			let options = TestOptions {
+				download_path: download_path.clone(),
+				save_command_log: true,
+				..Default::default()
+			};
+
+			let input = r#"
+PARSE_START 'aprovider' 'someid1' Some Title Here
+[download] Got error: HTTP Error 503: Service Unavailable. Retrying (1/10)...
+[download] 100% of 1.00KiB in 00:00
+[download] 100% of 2.00KiB in 00:00
+MOVE 'aprovider' 'someid1' /path/to/somewhere
+PARSE_END 'aprovider' 'someid1'
+PARSE_START 'aprovider' 'someid2' Some Title Here
+ERROR: [aprovider] someid2: somekinda error
+"#;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				|_| {},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert!(res.is_err());
+
+			let stats_path = download_path.join(format!("yt-dl_{}.stats.jsonl", std::process::id()));
+			let stats: Vec<serde_json::Value> = std::fs::read_to_string(&stats_path)
+				.expect("Expected the stats file to exist")
+				.lines()
+				.map(|v| return serde_json::from_str(v).expect("Expected every line to be JSON"))
+				.collect();
+
+			assert_eq!(2, stats.len());
+			assert_eq!("someid1", stats[0]["id"]);
+			assert_eq!(3072, stats[0]["bytes"]);
+			assert_eq!(1, stats[0]["retries"]);
+			assert_eq!(false, stats[0]["errored"]);
+			assert_eq!("someid2", stats[1]["id"]);
+			assert_eq!(true, stats[1]["errored"]);
+		}
+
+		/// Test that interleaved progress lines of concurrent fragments do not make the progress go backwards
+		#[test]
+		fn test_concurrent_fragments_progress() {
+			let options = TestOptions::new_handle_stdout(false);
+
+			let input = r#"
+PARSE_START 'aprovider' 'someid1' Some Title Here
+[download]  10.0% of ~  50.00MiB at  2.00MiB/s ETA 00:20 (frag 4/40)
+[download]  30.0% of ~  50.00MiB at  2.00MiB/s ETA 00:15 (frag 12/40)
+[download]  25.0% of ~  60.00MiB at  2.00MiB/s ETA 00:18 (frag 13/40)
+[download] 100% of   60.00MiB in 00:30
+[download]  20.0% of ~   5.00MiB at  1.00MiB/s ETA 00:04 (frag 2/10)
+[download]  15.0% of ~   6.00MiB at  1.00MiB/s ETA 00:05 (frag 3/10)
+[download] 100% of    6.00MiB in 00:05
+PARSE_END 'aprovider' 'someid1'
+PARSE_START 'aprovider' 'someid2' Some Title Here
+[download]   5.0% of ~  50.00MiB at  2.00MiB/s ETA 00:20 (frag 2/40)
+"#;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			let mut percents = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				|v| {
+					if let DownloadProgress::SingleProgress(_, progress) = v {
+						percents.push(progress.percent);
+					}
+				},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert!(res.is_ok());
+			// the progress resets after a format finished and for a new media
+			assert_eq!(vec![10, 30, 30, 100, 20, 20, 100, 5], percents);
+		}
+
+		#[test]
+		fn test_format_phases() {
+			let options = TestOptions::new_handle_stdout(false);
+
+			let input = r#"
+PARSE_START 'aprovider' 'someid1' Some Title Here
+PARSE_FORMATS '137+140'
+[download] Destination: Some Title Here [someid1].f137.mp4
+[download]  50.0% of 78.44MiB at 526.19KiB/s ETA 01:16
+[download] 100% of 78.44MiB in 00:07
+[download] Destination: Some Title Here [someid1].f140.m4a
+[download]  50.0% of 3.47MiB at 526.19KiB/s ETA 00:03
+[download] 100% of 3.47MiB in 00:00
+PARSE_END 'aprovider' 'someid1'
+PARSE_START 'aprovider' 'someid2' Some Title Here
+PARSE_FORMATS '22'
+[download] Destination: Some Title Here [someid2].mp4
+[download]  50.0% of 3.47MiB at 526.19KiB/s ETA 00:03
+PARSE_END 'aprovider' 'someid2'
+"#;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			let mut phases = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				|v| match v {
+					DownloadProgress::SingleProgressPhase(_, phase, total_phases, progress) => {
+						phases.push((Some((phase, total_phases)), progress.percent));
+					},
+					DownloadProgress::SingleProgress(_, progress) => phases.push((None, progress.percent)),
+					_ => (),
+				},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert!(res.is_ok());
+			// the second media only has a single format, so it has no phases
+			assert_eq!(
+				vec![
+					(Some((1, 2)), 50),
+					(Some((1, 2)), 100),
+					(Some((2, 2)), 50),
+					(Some((2, 2)), 100),
+					(None, 50),
+				],
+				phases
+			);
+		}
+
+		#[test]
+		fn test_live_progress() {
+			let options = TestOptions::new_handle_stdout(false);
+
+			let input = r#"
+[wait] Waiting for 00:05:00 - Press Ctrl+C to try now
+PARSE_START 'aprovider' 'someid1' Some Title Here
+[download]    1.00MiB at    1.00MiB/s (00:00:01) (frag 1)
+[download]    3.00MiB at    1.50MiB/s (00:00:02) (frag 3)
+[download] 100% of    3.00MiB in 00:00:02 at 1.50MiB/s
+PARSE_END 'aprovider' 'someid1'
+"#;
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			let mut events = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				|v| {
+					if matches!(
+						v,
+						DownloadProgress::WaitingForLive(_) | DownloadProgress::SingleLiveProgress(..)
+					) {
+						events.push(v);
+					}
+				},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert!(res.is_ok());
+			assert_eq!(
+				vec![
+					DownloadProgress::WaitingForLive("00:05:00".to_owned()),
+					DownloadProgress::SingleLiveProgress(
+						Some("someid1".to_owned()),
+						LiveProgress {
+							bytes:   1_048_576,
+							speed:   Some("1.00MiB/s".to_owned()),
+							elapsed: Some("00:00:01".to_owned()),
+						}
+					),
+					DownloadProgress::SingleLiveProgress(
+						Some("someid1".to_owned()),
+						LiveProgress {
+							bytes:   3_145_728,
+							speed:   Some("1.50MiB/s".to_owned()),
+							elapsed: Some("00:00:02".to_owned()),
+						}
+					),
+				],
+				events
+			);
+		}
+	}
+	mod unparsed_line_stats {
+		use test_utils::TestOptions;
+
+		use super::*;
+
+		#[test]
+		fn test_samples_limited() {
+			let mut stats = UnparsedLineStats::default();
+
+			stats.add_parsed();
+			for i in 0..5 {
+				stats.add_unparsed(&format!("line {i}"));
+			}
+
+			assert_eq!(
+				UnparsedLineStats {
+					total:    6,
+					unparsed: 5,
+					samples:  vec!["line 0".to_owned(), "line 1".to_owned(), "line 2".to_owned()],
+				},
+				stats
+			);
+		}
+
+		#[test]
+		fn test_report() {
+			let mut stats = UnparsedLineStats::default();
+
+			stats.add_parsed();
+			assert!(stats.report(true).is_ok());
+
+			stats.add_unparsed("some unknown line");
+			assert!(stats.report(false).is_ok());
+			assert_eq!(
+				Err(crate::Error::other(
+					"Strict Parse: 1 of 2 youtube-dl lines could not be parsed, samples:\n  \"some unknown line\""
+				)),
+				stats.report(true)
+			);
+		}
+
+		#[test]
+		fn test_handle_stdout_strict() {
+			let options = TestOptions {
+				strict_parse: true,
+				..Default::default()
+			};
+
+			let input = "[download] 100% of 3.47MiB in 00:00\nsome unknown line\n";
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				|_| {},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut Vec::new(),
+				None,
+			);
+
+			assert_eq!(
+				Err(crate::Error::other(
+					"Strict Parse: 1 of 2 youtube-dl lines could not be parsed, samples:\n  \"some unknown line\""
+				)),
+				res
+			);
+		}
+
+		#[test]
+		fn test_handle_stdout_transient_failed() {
+			let options = TestOptions::default();
+
+			let input = "ERROR: [youtube] someid1: Unable to download webpage: The read operation timed out\nERROR: [youtube] someid2: Private video. Sign in if you've been granted access to this video\nERROR: [youtube] someid3: Unable to download webpage: HTTP Error 503: Service Unavailable\n";
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			let mut transient_failed = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				|_| {},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut transient_failed,
+				None,
+			);
+
+			// the last line is a error, so the result is that error
+			assert!(res.expect_err("Expected the last error").as_download_error().is_some());
+			assert_eq!(vec!["someid1".to_owned(), "someid3".to_owned()], transient_failed);
+		}
+
+		#[test]
+		fn test_handle_stdout_disk_full() {
+			let options = TestOptions::default();
+
+			let input = "ERROR: unable to write data: [Errno 28] No space left on device
+[download] 100% of 3.47MiB in 00:00
+ERROR: [youtube] someid2: Unable to download webpage: The read operation timed out
+";
+
+			let mut media_vec: Vec<MediaInfo> = Vec::new();
+			let mut transient_failed = Vec::new();
+
+			let res = handle_stdout(
+				None,
+				&options,
+				|_| {},
+				BufReader::new(input.as_bytes()),
+				&mut media_vec,
+				&mut transient_failed,
+				None,
+			);
+
+			// the reading stops at the first error about no space being left, so the following lines are not handled
+			assert!(res
+				.expect_err("Expected the disk full error")
+				.as_download_error()
+				.is_some_and(DownloadError::is_disk_full));
+			assert!(transient_failed.is_empty());
+		}
+	}
+
+	#[test]
+	fn test_format_phases() {
+		let mut phases = FormatPhases::default();
+
+		phases.start(2);
+		assert_eq!(Some((1, 2)), phases.phase());
+		phases.advance();
+		assert_eq!(Some((1, 2)), phases.phase());
+		phases.advance();
+		assert_eq!(Some((2, 2)), phases.phase());
+
+		// the total grows if more formats are downloaded than known
+		phases.start(1);
+		phases.advance();
+		assert_eq!(None, phases.phase());
+		phases.advance();
+		assert_eq!(Some((2, 2)), phases.phase());
+	}
+
+	#[test]
+	fn test_retry_backoff() {
+		assert_eq!(Duration::from_secs(5), retry_backoff(1));
+		assert_eq!(Duration::from_secs(10), retry_backoff(2));
+		assert_eq!(Duration::from_secs(20), retry_backoff(3));
+		assert_eq!(retry_backoff(11), retry_backoff(usize::MAX));
+	}
+
+	#[test]
+	fn test_sleep_cancellable() {
+		assert!(sleep_cancellable(Duration::from_millis(10), None).is_ok());
+
+		let cancel = CancellationToken::new();
+		assert!(sleep_cancellable(Duration::from_millis(10), Some(&cancel)).is_ok());
+
+		// a clone shares the state
+		cancel.clone().cancel();
+		assert!(cancel.is_cancelled());
+		assert!(sleep_cancellable(Duration::from_secs(60), Some(&cancel))
+			.expect_err("Expected sleep to be cancelled")
+			.is_cancelled());
+	}
+
+	mod download_progress_serialize {
+		use super::*;
+
+		#[test]
+		fn test_serialize() {
+			assert_eq!(
+				r#"{"event":"url_starting"}"#,
+				serde_json::to_string(&DownloadProgress::UrlStarting).unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"single_progress","id":"-----------","percent":50,"speed":"526.19KiB/s","eta":null}"#,
+				serde_json::to_string(&DownloadProgress::SingleProgress(
+					Some("-----------".to_owned()),
+					TransferProgress::new(50).with_speed("526.19KiB/s")
+				))
+				.unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"single_progress_phase","id":null,"phase":2,"total_phases":2,"percent":50,"speed":null,"eta":"00:05"}"#,
+				serde_json::to_string(&DownloadProgress::SingleProgressPhase(
+					None,
+					2,
+					2,
+					TransferProgress::new(50).with_eta("00:05")
+				))
+				.unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"single_finished","id":"-----------","path":null}"#,
+				serde_json::to_string(&DownloadProgress::SingleFinished("-----------".to_owned(), None)).unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"skipped","count":1,"reason":"in_archive","id":"someid"}"#,
+				serde_json::to_string(&DownloadProgress::Skipped {
+					count: 1,
+					typ:   SkippedType::InArchive,
+					id:    Some("someid".to_owned()),
+				})
+				.unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"stalled","id":null,"elapsed_secs":90}"#,
+				serde_json::to_string(&DownloadProgress::Stalled(None, Duration::from_secs(90))).unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"extracting_playlist","page":3}"#,
+				serde_json::to_string(&DownloadProgress::ExtractingPlaylist(3)).unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"postprocessing","id":null,"step":"Merger"}"#,
+				serde_json::to_string(&DownloadProgress::Postprocessing(None, "Merger".to_owned())).unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"single_live_progress","id":null,"bytes":1024,"speed":null,"elapsed":"00:00:22"}"#,
+				serde_json::to_string(&DownloadProgress::SingleLiveProgress(
+					None,
+					LiveProgress {
+						bytes:   1024,
+						speed:   None,
+						elapsed: Some("00:00:22".to_owned()),
+					}
+				))
+				.unwrap()
+			);
+			assert_eq!(
+				r#"{"event":"waiting_for_live","remaining":"00:05:00"}"#,
+				serde_json::to_string(&DownloadProgress::WaitingForLive("00:05:00".to_owned())).unwrap()
+			);
+		}
 	}
 }
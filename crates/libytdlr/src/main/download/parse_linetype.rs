@@ -1,6 +1,11 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use super::{
+	LiveProgress,
+	TransferProgress,
+	UnavailableReason,
+};
 use crate::data::cache::media_info::MediaInfo;
 
 /// Helper Enum for differentiating [`LineType::Custom`] types like "PARSE_START" and "PARSE_END"
@@ -10,6 +15,8 @@ pub enum CustomParseType {
 	End(MediaInfo),
 	Playlist(usize),
 	Move(MediaInfo),
+	/// The amount of formats the current media downloads separately (like video and audio)
+	Formats(usize),
 }
 
 /// Line type for a ytdl output line
@@ -17,7 +24,7 @@ pub enum CustomParseType {
 pub enum LineType {
 	/// Variant for FFmpeg processing lines
 	Ffmpeg,
-	/// Variant for ytdl download progress lines
+	/// Variant for ytdl download progress lines, including the ffmpeg progress lines of livestreams (like "frame=  120 ... size=    1024kB time=00:00:04.00 ...")
 	Download,
 	/// Variant for provider specific lines (like youtube counting website)
 	ProviderSpecific,
@@ -33,6 +40,24 @@ pub enum LineType {
 	ArchiveSkip,
 }
 
+/// Names of yt-dlp post-processors which run ffmpeg, like "[Merger] Merging formats into ..."
+const FFMPEG_POSTPROCESSOR_NAMES: &[&str] = &[
+	"Merger",
+	"ExtractAudio",
+	"VideoRemuxer",
+	"VideoConvertor",
+	"EmbedThumbnail",
+	"EmbedSubtitle",
+	"FixupM3u8",
+	"FixupM4a",
+	"FixupStretched",
+	"FixupDuration",
+	"FixupTimestamp",
+	"FixupDuplicateMoov",
+	"SplitChapters",
+	"ModifyChapters",
+];
+
 impl LineType {
 	/// Try to get the correct Variant for a input line
 	/// Will return [`None`] if no type has been found
@@ -53,6 +78,10 @@ impl LineType {
 		static YTDL_PLAYLIST_REGEX: Lazy<Regex> = Lazy::new(|| {
 			return Regex::new(r"(?m)^\[[\w:]+\] Playlist [^:]+:").unwrap();
 		});
+		/// regex to check for ffmpeg progress lines, which are output when ffmpeg downloads a livestream
+		static FFMPEG_PROGRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"^(?:frame=\s*\d+\s.*)?size=\s*\S+\s+time=").unwrap();
+		});
 
 		// check if the line is from a provider-like output
 		if let Some(cap) = BASIC_TYPE_REGEX.captures(input) {
@@ -72,7 +101,11 @@ impl LineType {
 				return Some(Self::Download);
 			}
 
-			if name == "ffmpeg" {
+			if name == "ffmpeg"
+				|| FFMPEG_POSTPROCESSOR_NAMES
+					.iter()
+					.any(|v| return v.eq_ignore_ascii_case(name))
+			{
 				return Some(Self::Ffmpeg);
 			}
 
@@ -110,34 +143,241 @@ impl LineType {
 			return Some(Self::Warning);
 		}
 
+		if FFMPEG_PROGRESS_REGEX.is_match(input) {
+			return Some(Self::Download);
+		}
+
 		// if nothing above matches, return None, because no type has been found
 		return None;
 	}
 
-	/// Try to get the download precent from input
+	/// Check whether input is the start of a new format download, like `[download] Destination: /tmp/Some Title [someid].f137.mp4`
+	/// Returns `false` if not being of variant [`LineType::Download`]
+	pub fn is_download_destination(&self, input: &str) -> bool {
+		// this function only works with Download lines
+		if self != &Self::Download {
+			return false;
+		}
+
+		return input.starts_with("[download] Destination: ");
+	}
+
+	/// Try to get the download progress (percentage, speed and ETA) from input, like `[download]  50.0% of 78.44MiB at 526.19KiB/s ETA 01:16`
+	/// Speed and ETA are [`None`] if youtube-dl does not know them yet ("Unknown") or if the line does not contain them (like the finished line)
 	/// Returns [`None`] if not being of variant [`LineType::Download`] or if not percentage can be found or could not be parsed
-	pub fn try_get_download_percent<I: AsRef<str>>(&self, input: I) -> Option<u8> {
+	pub fn try_get_download_progress<I: AsRef<str>>(&self, input: I) -> Option<TransferProgress> {
 		// this function only works with Download lines
 		if self != &Self::Download {
 			return None;
 		}
 
-		/// Regex to parse the download percentage from a line
-		/// cap1: precentage(not decimal)
-		static DOWNLOAD_PERCENTAGE_REGEX: Lazy<Regex> = Lazy::new(|| {
-			return Regex::new(r"(?mi)^\[download\]\s+(\d{1,3})(?:\.\d)?%").unwrap();
+		/// Regex to parse the download percentage, speed and ETA from a line
+		/// cap1: precentage(not decimal), cap2: speed (optional), cap3: eta (optional)
+		static DOWNLOAD_PROGRESS_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(
+				r"(?mi)^\[download\]\s+(\d{1,3})(?:\.\d)?%(?:.*?\bat\s+(\S+(?: B/s)?))?(?:.*?\bETA\s+(\S+))?",
+			)
+			.unwrap();
 		});
 
-		let input = input.as_ref();
+		let cap = DOWNLOAD_PROGRESS_REGEX.captures(input.as_ref())?;
+		let percent = cap[1].parse::<u8>().ok()?;
+		// youtube-dl prints "Unknown" (like "Unknown B/s") when it does not know the value yet
+		let known = |v: &str| return !v.starts_with("Unknown");
+
+		return Some(TransferProgress {
+			percent,
+			speed: cap
+				.get(2)
+				.map(|v| return v.as_str())
+				.filter(|v| return known(v))
+				.map(ToOwned::to_owned),
+			eta: cap
+				.get(3)
+				.map(|v| return v.as_str())
+				.filter(|v| return known(v))
+				.map(ToOwned::to_owned),
+		});
+	}
+
+	/// Try to get the size of a finished download from input, like `[download] 100% of 3.47MiB in 00:00`
+	/// Returns [`None`] if not being of variant [`LineType::Download`] or if the line is not a finished download line
+	pub fn try_get_download_size<I: AsRef<str>>(&self, input: I) -> Option<u64> {
+		// this function only works with Download lines
+		if self != &Self::Download {
+			return None;
+		}
+
+		/// Regex to parse the size of a finished download from a line
+		/// cap1: size number, cap2: size unit
+		static DOWNLOAD_SIZE_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(
+				r"(?mi)^\[download\]\s+100(?:\.0)?% of\s+~?\s*(\d+(?:\.\d+)?)(B|KiB|MiB|GiB|TiB)\s+in\b",
+			)
+			.unwrap();
+		});
+
+		let cap = DOWNLOAD_SIZE_REGEX.captures(input.as_ref())?;
 
-		if let Some(cap) = DOWNLOAD_PERCENTAGE_REGEX.captures(input) {
-			let percent_str = &cap[1];
+		return size_to_bytes(&cap[1], &cap[2]);
+	}
 
-			// directly use the "Result" returned by "from_str_radix" and convert it to a "Option"
-			return percent_str.parse::<u8>().ok();
+	/// Try to get the progress of a livestream download from input, which has no percentage because the total size is not known
+	/// Handles youtube-dl lines like `[download]   45.21MiB at    2.01MiB/s (00:00:22) (frag 45)`
+	/// and ffmpeg lines like `frame=  120 fps= 30 q=-1.0 size=    1024kB time=00:00:04.00 bitrate=2097.2kbits/s speed=1.01x`
+	/// Returns [`None`] if not being of variant [`LineType::Download`] or if the line is not a livestream progress line
+	pub fn try_get_live_progress<I: AsRef<str>>(&self, input: I) -> Option<LiveProgress> {
+		// this function only works with Download lines
+		if self != &Self::Download {
+			return None;
 		}
 
-		return None;
+		/// Regex to parse the downloaded size, speed and elapsed time from a youtube-dl livestream line
+		/// cap1: size number, cap2: size unit, cap3: speed, cap4: elapsed (optional)
+		static YTDL_LIVE_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(
+				r"(?i)^\[download\]\s+~?\s*(\d+(?:\.\d+)?)(B|KiB|MiB|GiB|TiB)\s+at\s+(\S+(?: B/s)?)(?:\s+\((\d+(?::\d+)+)\))?",
+			)
+			.unwrap();
+		});
+		/// Regex to parse the downloaded size and elapsed time from a ffmpeg progress line
+		/// cap1: size number, cap2: size unit, cap3: elapsed
+		static FFMPEG_LIVE_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"size=\s*(\d+)(B|kB|KiB|MiB|GiB)\s+time=\s*(\d+(?::\d+)+)").unwrap();
+		});
+
+		// ffmpeg overwrites its progress line with "\r", only the latest state is of interest
+		let input = input.as_ref().rsplit('\r').find(|v| return !v.trim().is_empty())?;
+
+		if let Some(cap) = YTDL_LIVE_REGEX.captures(input) {
+			return Some(LiveProgress {
+				bytes:   size_to_bytes(&cap[1], &cap[2])?,
+				speed:   Some(cap[3].to_owned()).filter(|v| return !v.starts_with("Unknown")),
+				elapsed: cap.get(4).map(|v| return v.as_str().to_owned()),
+			});
+		}
+
+		let cap = FFMPEG_LIVE_REGEX.captures(input)?;
+
+		return Some(LiveProgress {
+			bytes:   size_to_bytes(&cap[1], &cap[2])?,
+			speed:   None,
+			elapsed: Some(cap[3].to_owned()),
+		});
+	}
+
+	/// Try to get the provider and id of a archive skip line, like `[youtube] someid: has already been recorded in the archive`
+	/// The provider is [`None`] if the line does not contain it (like `[download] someid: ...`)
+	/// Returns [`None`] if not being of variant [`LineType::ArchiveSkip`] or if the line could not be parsed
+	pub fn try_get_archive_skip<'a>(&self, input: &'a str) -> Option<(Option<&'a str>, &'a str)> {
+		// this function only works with ArchiveSkip lines
+		if self != &Self::ArchiveSkip {
+			return None;
+		}
+
+		/// Regex to get the provider and id from a archive skip line
+		/// cap1: provider, cap2: id
+		static ARCHIVE_SKIP_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"(?m)^\[(\w+)\] ([^:]+): has already been recorded in the archive$").unwrap();
+		});
+
+		let cap = ARCHIVE_SKIP_REGEX.captures(input)?;
+		let provider = cap.get(1)?.as_str();
+		let id = cap.get(2)?.as_str();
+
+		return Some((Some(provider).filter(|v| return *v != "download"), id));
+	}
+
+	/// Try to get the name of the post-processing step of a ffmpeg post-processor line, like `[Merger] Merging formats into "..."`
+	/// Returns [`None`] if not being of variant [`LineType::Ffmpeg`] or if the line is not from a known post-processor (like plain `[ffmpeg]` lines)
+	pub fn try_get_postprocessor(&self, input: &str) -> Option<&'static str> {
+		// this function only works with Ffmpeg lines
+		if self != &Self::Ffmpeg {
+			return None;
+		}
+
+		let (name, _) = input.strip_prefix('[')?.split_once(']')?;
+
+		return FFMPEG_POSTPROCESSOR_NAMES
+			.iter()
+			.find(|v| return v.eq_ignore_ascii_case(name))
+			.copied();
+	}
+
+	/// Try to get the page number of a playlist extraction line, like `[youtube:tab] someplaylist: Downloading page 2`
+	/// Returns [`None`] if not being of variant [`LineType::ProviderSpecific`] or if the line is not a playlist page line
+	pub fn try_get_playlist_page(&self, input: &str) -> Option<usize> {
+		// this function only works with ProviderSpecific lines
+		if self != &Self::ProviderSpecific {
+			return None;
+		}
+
+		/// Regex to get the page number from a playlist page line, also matching lines like "Downloading API JSON page 2"
+		/// cap1: page
+		static PLAYLIST_PAGE_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"(?mi)^\[[\w:]+\] .*Downloading (?:[\w ]+ )?page (\d+)").unwrap();
+		});
+
+		return PLAYLIST_PAGE_REGEX.captures(input)?.get(1)?.as_str().parse().ok();
+	}
+
+	/// Try to get the remaining time youtube-dl waits for a scheduled livestream to begin, like `[wait] Waiting for 00:05:00 - Press Ctrl+C to try now`
+	/// Also handles the countdown lines like `[wait] Remaining time until next attempt: 00:04:59`
+	/// Returns [`None`] if not being of variant [`LineType::ProviderSpecific`] or if the line is not a wait line
+	pub fn try_get_live_wait<'a>(&self, input: &'a str) -> Option<&'a str> {
+		// this function only works with ProviderSpecific lines
+		if self != &Self::ProviderSpecific {
+			return None;
+		}
+
+		/// Regex to get the remaining time from a wait line
+		/// cap1: remaining time
+		static LIVE_WAIT_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"^\[wait\] (?:Waiting for|Remaining time until next attempt:)\s+(\d+(?::\d+)+)")
+				.unwrap();
+		});
+
+		// the countdown overwrites itself with "\r", only the latest state is of interest
+		let input = input.rsplit('\r').find(|v| return !v.trim().is_empty())?;
+
+		return Some(LIVE_WAIT_REGEX.captures(input)?.get(1)?.as_str());
+	}
+
+	/// Try to get why a media is not available from a error line, like `ERROR: [youtube] someid: Private video. Sign in if you've been granted access to this video`
+	/// Returns [`None`] if not being of variant [`LineType::Error`] or if the error is not about the media being unavailable
+	pub fn try_get_unavailable_reason(&self, input: &str) -> Option<UnavailableReason> {
+		// this function only works with Error lines
+		if self != &Self::Error {
+			return None;
+		}
+
+		/// Patterns (lower-case) of youtube-dl error messages and the reason they belong to, checked in order
+		const PATTERNS: &[(&str, UnavailableReason)] = &[
+			("members-only", UnavailableReason::MembersOnly),
+			("members only", UnavailableReason::MembersOnly),
+			("join this channel", UnavailableReason::MembersOnly),
+			("private video", UnavailableReason::Private),
+			("drm", UnavailableReason::Drm),
+			("available in your country", UnavailableReason::GeoBlocked),
+			("not available from your location", UnavailableReason::GeoBlocked),
+			("geo restriction", UnavailableReason::GeoBlocked),
+			("geo-restrict", UnavailableReason::GeoBlocked),
+			("sign in to confirm your age", UnavailableReason::LoginRequired),
+			("requires authentication", UnavailableReason::LoginRequired),
+			("login required", UnavailableReason::LoginRequired),
+			("use --cookies", UnavailableReason::LoginRequired),
+			("http error 403", UnavailableReason::Forbidden),
+			("video unavailable", UnavailableReason::Removed),
+			("has been removed", UnavailableReason::Removed),
+			("this video is unavailable", UnavailableReason::Removed),
+		];
+
+		let input = input.to_lowercase();
+
+		return PATTERNS
+			.iter()
+			.find(|(pattern, _)| return input.contains(pattern))
+			.map(|(_, reason)| return *reason);
 	}
 
 	/// Try to parse the custom parse-helpers like "PARSE_START"
@@ -156,6 +396,10 @@ impl LineType {
 		static PARSE_PLAYLIST_REGEX: Lazy<Regex> = Lazy::new(|| {
 			return Regex::new(r"(?mi)^PLAYLIST '(\d+)'$").unwrap();
 		});
+		/// Regex to get all information from the Parsing helper "PARSE_FORMATS"
+		static PARSE_FORMATS_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"(?mi)^PARSE_FORMATS '([^']*)'$").unwrap();
+		});
 		/// Regex to get all information from the Parsing helper "MOVE"
 		static PARSE_MOVE_REGEX: Lazy<Regex> = Lazy::new(|| {
 			return Regex::new(r"(?mi)^MOVE '([^']+)' '([^']+)' (.+)$").unwrap();
@@ -187,6 +431,11 @@ impl LineType {
 			}
 		}
 
+		// handle "PARSE_FORMATS" lines, where merged formats are joined with "+" (like "137+140")
+		if let Some(cap) = PARSE_FORMATS_REGEX.captures(input) {
+			return Some(CustomParseType::Formats(cap[1].split('+').count()));
+		}
+
 		// handle "MOVE" lines
 		// cannot be merged easily with "PARSE_END", because of https://github.com/yt-dlp/yt-dlp/issues/7197#issuecomment-1572066439
 		if let Some(cap) = PARSE_MOVE_REGEX.captures(input) {
@@ -234,6 +483,24 @@ impl LineType {
 	}
 }
 
+/// Convert a size as displayed by youtube-dl or ffmpeg (like "3.47" and "MiB") to bytes
+/// ffmpeg's "kB" is treated as "KiB", because ffmpeg uses a multiplier of 1024 for it
+/// Returns [`None`] if the number could not be parsed or the unit is unknown
+fn size_to_bytes(number: &str, unit: &str) -> Option<u64> {
+	let size = number.parse::<f64>().ok()?;
+	let multiplier: f64 = match unit {
+		"B" => 1.0,
+		"KiB" | "kB" => 1024.0,
+		"MiB" => 1024.0 * 1024.0,
+		"GiB" => 1024.0 * 1024.0 * 1024.0,
+		"TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+		_ => return None,
+	};
+
+	// truncation is wanted, because bytes are whole numbers
+	return Some((size * multiplier) as u64);
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -255,6 +522,12 @@ mod tests {
 		let input = "[ffmpeg] Merging formats into \"/tmp/rust-yt-dl.webm\"";
 		assert_eq!(Some(LineType::Ffmpeg), LineType::try_from_line(input));
 
+		let input = "[Merger] Merging formats into \"/tmp/rust-yt-dl.webm\"";
+		assert_eq!(Some(LineType::Ffmpeg), LineType::try_from_line(input));
+
+		let input = "[ExtractAudio] Destination: /tmp/rust-yt-dl.mp3";
+		assert_eq!(Some(LineType::Ffmpeg), LineType::try_from_line(input));
+
 		let input = "Deleting original file /tmp/rust-yt-dl.f303 (pass -k to keep)";
 		assert_eq!(Some(LineType::Generic), LineType::try_from_line(input));
 
@@ -279,6 +552,128 @@ mod tests {
 
 		let input = "[download] someid: has already been recorded in the archive";
 		assert_eq!(Some(LineType::ArchiveSkip), LineType::try_from_line(input));
+
+		let input = "frame=  120 fps= 30 q=-1.0 size=    1024kB time=00:00:04.00 bitrate=2097.2kbits/s speed=1.01x";
+		assert_eq!(Some(LineType::Download), LineType::try_from_line(input));
+
+		let input = "size=    1024KiB time=00:00:04.00 bitrate=2097.2kbits/s speed=1.01x";
+		assert_eq!(Some(LineType::Download), LineType::try_from_line(input));
+
+		let input = "[wait] Waiting for 00:05:00 - Press Ctrl+C to try now";
+		assert_eq!(Some(LineType::ProviderSpecific), LineType::try_from_line(input));
+	}
+
+	#[test]
+	fn test_linetype_archive_skip() {
+		let input = "[youtube] some-id_1: has already been recorded in the archive";
+		assert_eq!(
+			Some((Some("youtube"), "some-id_1")),
+			LineType::ArchiveSkip.try_get_archive_skip(input)
+		);
+
+		let input = "[download] someid: has already been recorded in the archive";
+		assert_eq!(
+			Some((None, "someid")),
+			LineType::ArchiveSkip.try_get_archive_skip(input)
+		);
+
+		assert_eq!(None, LineType::Download.try_get_archive_skip(input));
+	}
+
+	#[test]
+	fn test_try_get_postprocessor() {
+		let input = "[Merger] Merging formats into \"/tmp/rust-yt-dl.webm\"";
+		assert_eq!(Some("Merger"), LineType::Ffmpeg.try_get_postprocessor(input));
+
+		let input = "[ExtractAudio] Destination: /tmp/rust-yt-dl.mp3";
+		assert_eq!(Some("ExtractAudio"), LineType::Ffmpeg.try_get_postprocessor(input));
+
+		let input = "[VideoRemuxer] Remuxing video from webm to mkv; Destination: /tmp/rust-yt-dl.mkv";
+		assert_eq!(Some(LineType::Ffmpeg), LineType::try_from_line(input));
+		assert_eq!(Some("VideoRemuxer"), LineType::Ffmpeg.try_get_postprocessor(input));
+
+		// plain "ffmpeg" lines are not a step
+		let input = "[ffmpeg] Merging formats into \"/tmp/rust-yt-dl.webm\"";
+		assert_eq!(None, LineType::Ffmpeg.try_get_postprocessor(input));
+
+		assert_eq!(
+			None,
+			LineType::Download.try_get_postprocessor("[Merger] Merging formats into \"/tmp/rust-yt-dl.webm\"")
+		);
+	}
+
+	#[test]
+	fn test_try_get_playlist_page() {
+		let input = "[aprovider] someplaylist: Downloading page 0";
+		assert_eq!(Some(LineType::ProviderSpecific), LineType::try_from_line(input));
+		assert_eq!(Some(0), LineType::ProviderSpecific.try_get_playlist_page(input));
+
+		let input = "[youtube:tab] UUxxxxxxxxxxxxxxxxxxxxxx page 1: Downloading API JSON page 2";
+		assert_eq!(Some(2), LineType::ProviderSpecific.try_get_playlist_page(input));
+
+		let input = "[soundcloud:user] 123456: Downloading track page 12";
+		assert_eq!(Some(12), LineType::ProviderSpecific.try_get_playlist_page(input));
+
+		// not a page line
+		let input = "[aprovider] someid3: Downloading JSON metadata";
+		assert_eq!(None, LineType::ProviderSpecific.try_get_playlist_page(input));
+
+		// should early-return because of not being the correct variant
+		let input = "[download] Downloading page 2";
+		assert_eq!(None, LineType::Download.try_get_playlist_page(input));
+	}
+
+	#[test]
+	fn test_try_get_unavailable_reason() {
+		let input = "ERROR: [youtube] someid: Private video. Sign in if you've been granted access to this video";
+		assert_eq!(
+			Some(UnavailableReason::Private),
+			LineType::Error.try_get_unavailable_reason(input)
+		);
+
+		let input = "ERROR: [youtube] someid: Join this channel to get access to members-only content like this video, and other exclusive perks.";
+		assert_eq!(
+			Some(UnavailableReason::MembersOnly),
+			LineType::Error.try_get_unavailable_reason(input)
+		);
+
+		let input = "ERROR: [youtube] someid: The uploader has not made this video available in your country";
+		assert_eq!(
+			Some(UnavailableReason::GeoBlocked),
+			LineType::Error.try_get_unavailable_reason(input)
+		);
+
+		let input = "ERROR: [SomeProvider] someid: This video is DRM protected";
+		assert_eq!(
+			Some(UnavailableReason::Drm),
+			LineType::Error.try_get_unavailable_reason(input)
+		);
+
+		let input = "ERROR: [youtube] someid: Sign in to confirm your age. This video may be inappropriate for some users. Use --cookies-from-browser or --cookies for the authentication.";
+		assert_eq!(
+			Some(UnavailableReason::LoginRequired),
+			LineType::Error.try_get_unavailable_reason(input)
+		);
+
+		let input = "ERROR: unable to download video data: HTTP Error 403: Forbidden";
+		assert_eq!(
+			Some(UnavailableReason::Forbidden),
+			LineType::Error.try_get_unavailable_reason(input)
+		);
+
+		let input = "ERROR: [youtube] someid: Video unavailable";
+		assert_eq!(
+			Some(UnavailableReason::Removed),
+			LineType::Error.try_get_unavailable_reason(input)
+		);
+
+		// not a reason for the media being unavailable
+		let input = "ERROR: [provider] id: Unable to download webpage: The read operation timed out";
+		assert_eq!(None, LineType::Error.try_get_unavailable_reason(input));
+
+		// should early-return because of not being the correct variant
+		let input = "WARNING: [youtube] someid: Private video";
+		assert_eq!(None, LineType::Warning.try_get_unavailable_reason(input));
 	}
 
 	#[test]
@@ -287,42 +682,227 @@ mod tests {
 
 		let linetype = LineType::try_from_line(input).unwrap();
 		assert_eq!(LineType::Download, linetype);
-		assert_eq!(Some(0), linetype.try_get_download_percent(input))
+		assert_eq!(
+			Some(0),
+			linetype.try_get_download_progress(input).map(|v| return v.percent)
+		);
 	}
 
 	#[test]
-	fn test_try_get_download_percent() {
+	fn test_try_get_download_progress_percent() {
 		// should try to apply the regex, but would not find anything
 		let input = "[download] Downloading playlist: test";
-		assert_eq!(None, LineType::Download.try_get_download_percent(input));
+		assert_eq!(
+			None,
+			LineType::Download
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
 
 		// should find "0"
 		let input = "[download]   0.0% of 51.32MiB at 160.90KiB/s ETA 05:29";
-		assert_eq!(Some(0), LineType::Download.try_get_download_percent(input));
+		assert_eq!(
+			Some(0),
+			LineType::Download
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
 
 		// should find "1"
 		let input = "[download]   1.0% of  290.41MiB at  562.77KiB/s ETA 08:43";
-		assert_eq!(Some(1), LineType::Download.try_get_download_percent(input));
+		assert_eq!(
+			Some(1),
+			LineType::Download
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
 
 		// should find "1"
 		let input = "[download]   1.1% of  290.41MiB at  568.08KiB/s ETA 08:37";
-		assert_eq!(Some(1), LineType::Download.try_get_download_percent(input));
+		assert_eq!(
+			Some(1),
+			LineType::Download
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
 
 		// should find "75"
 		let input = "[download]  75.6% of 51.32MiB at  2.32MiB/s ETA 00:05";
-		assert_eq!(Some(75), LineType::Download.try_get_download_percent(input));
+		assert_eq!(
+			Some(75),
+			LineType::Download
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
 
 		// should find "100"
 		let input = "[download] 100% of 2.16MiB in 00:00";
-		assert_eq!(Some(100), LineType::Download.try_get_download_percent(input));
+		assert_eq!(
+			Some(100),
+			LineType::Download
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
 
 		// should early-return because not correct variant
 		let input = "something else";
-		assert_eq!(None, LineType::Generic.try_get_download_percent(input));
+		assert_eq!(
+			None,
+			LineType::Generic
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
 
 		// test out-of-u8-bounds
 		let input = "[download] 256% of 2.16MiB in 00:00";
-		assert_eq!(None, LineType::Download.try_get_download_percent(input));
+		assert_eq!(
+			None,
+			LineType::Download
+				.try_get_download_progress(input)
+				.map(|v| return v.percent)
+		);
+	}
+
+	#[test]
+	fn test_try_get_download_progress() {
+		let input = "[download]  50.0% of 78.44MiB at 526.19KiB/s ETA 01:16";
+		assert_eq!(
+			Some(TransferProgress::new(50).with_speed("526.19KiB/s").with_eta("01:16")),
+			LineType::Download.try_get_download_progress(input)
+		);
+
+		// should handle extra spacing
+		let input = "[download]   0.1% of  3.47MiB at  10.57MiB/s ETA 09:00";
+		assert_eq!(
+			Some(TransferProgress::new(0).with_speed("10.57MiB/s").with_eta("09:00")),
+			LineType::Download.try_get_download_progress(input)
+		);
+
+		// unknown values should not be set
+		let input = "[download]   0.0% of  7.00MiB at  Unknown B/s ETA Unknown";
+		assert_eq!(
+			Some(TransferProgress::new(0)),
+			LineType::Download.try_get_download_progress(input)
+		);
+
+		// the finished line does not have a speed or ETA
+		let input = "[download] 100% of 3.47MiB in 00:00";
+		assert_eq!(
+			Some(TransferProgress::new(100)),
+			LineType::Download.try_get_download_progress(input)
+		);
+
+		// should try to apply the regex, but would not find anything
+		let input = "[download] Downloading playlist: test";
+		assert_eq!(None, LineType::Download.try_get_download_progress(input));
+
+		// should early-return because not correct variant
+		let input = "[download]  50.0% of 78.44MiB at 526.19KiB/s ETA 01:16";
+		assert_eq!(None, LineType::Generic.try_get_download_progress(input));
+	}
+
+	#[test]
+	fn test_try_get_download_size() {
+		// should not find anything, because it is not finished
+		let input = "[download]  75.6% of 51.32MiB at  2.32MiB/s ETA 00:05";
+		assert_eq!(None, LineType::Download.try_get_download_size(input));
+
+		// should not find anything, because it is not the final line
+		let input = "[download] 100% of 3.47MiB at 10.57MiB/s ETA 00:00";
+		assert_eq!(None, LineType::Download.try_get_download_size(input));
+
+		// should find the size
+		let input = "[download] 100% of 3.47MiB in 00:00";
+		assert_eq!(Some(3_638_558), LineType::Download.try_get_download_size(input));
+
+		// should find the size with newer yt-dlp output
+		let input = "[download] 100% of  512.00KiB in 00:00:01 at 400.00KiB/s";
+		assert_eq!(Some(524_288), LineType::Download.try_get_download_size(input));
+
+		// should find the size with a estimate
+		let input = "[download] 100.0% of ~ 1.00GiB in 00:01:00";
+		assert_eq!(Some(1_073_741_824), LineType::Download.try_get_download_size(input));
+
+		// should early-return because not correct variant
+		let input = "[download] 100% of 3.47MiB in 00:00";
+		assert_eq!(None, LineType::Generic.try_get_download_size(input));
+	}
+
+	#[test]
+	fn test_try_get_live_progress() {
+		let input = "[download]   45.21MiB at    2.01MiB/s (00:00:22) (frag 45)";
+		assert_eq!(
+			Some(LiveProgress {
+				bytes:   47_406_120,
+				speed:   Some("2.01MiB/s".to_owned()),
+				elapsed: Some("00:00:22".to_owned()),
+			}),
+			LineType::Download.try_get_live_progress(input)
+		);
+
+		// unknown speed should not be set
+		let input = "[download]  512.00KiB at  Unknown B/s (00:00:01)";
+		assert_eq!(
+			Some(LiveProgress {
+				bytes:   524_288,
+				speed:   None,
+				elapsed: Some("00:00:01".to_owned()),
+			}),
+			LineType::Download.try_get_live_progress(input)
+		);
+
+		// should use the latest ffmpeg progress
+		let input = "frame=   30 fps= 30 q=-1.0 size=     256kB time=00:00:01.00 bitrate=2097.2kbits/s speed=1.01x\rframe=  120 fps= 30 q=-1.0 size=    1024kB time=00:00:04.00 bitrate=2097.2kbits/s speed=1.01x\r";
+		assert_eq!(
+			Some(LiveProgress {
+				bytes:   1_048_576,
+				speed:   None,
+				elapsed: Some("00:00:04".to_owned()),
+			}),
+			LineType::Download.try_get_live_progress(input)
+		);
+
+		// should not find anything for lines with a percentage
+		let input = "[download]  50.0% of 78.44MiB at 526.19KiB/s ETA 01:16";
+		assert_eq!(None, LineType::Download.try_get_live_progress(input));
+
+		let input = "[download] Destination: /tmp/some.mp4";
+		assert_eq!(None, LineType::Download.try_get_live_progress(input));
+
+		// should early-return because not correct variant
+		let input = "[download]   45.21MiB at    2.01MiB/s (00:00:22) (frag 45)";
+		assert_eq!(None, LineType::Generic.try_get_live_progress(input));
+	}
+
+	#[test]
+	fn test_is_download_destination() {
+		let input = "[download] Destination: /tmp/Some Title [someid].f137.mp4";
+		assert!(LineType::Download.is_download_destination(input));
+
+		let input = "[download]  50.0% of 78.44MiB at 526.19KiB/s ETA 01:16";
+		assert!(!LineType::Download.is_download_destination(input));
+
+		// should early-return because not correct variant
+		let input = "[ExtractAudio] Destination: /tmp/rust-yt-dl.mp3";
+		assert!(!LineType::Ffmpeg.is_download_destination(input));
+	}
+
+	#[test]
+	fn test_try_get_live_wait() {
+		let input = "[wait] Waiting for 00:05:00 - Press Ctrl+C to try now";
+		assert_eq!(Some("00:05:00"), LineType::ProviderSpecific.try_get_live_wait(input));
+
+		// should use the latest countdown
+		let input =
+			"[wait] Remaining time until next attempt: 00:04:59\r[wait] Remaining time until next attempt: 00:04:58\r";
+		assert_eq!(Some("00:04:58"), LineType::ProviderSpecific.try_get_live_wait(input));
+
+		let input = "[youtube] -----------: Downloading webpage";
+		assert_eq!(None, LineType::ProviderSpecific.try_get_live_wait(input));
+
+		// should early-return because not correct variant
+		let input = "[wait] Waiting for 00:05:00 - Press Ctrl+C to try now";
+		assert_eq!(None, LineType::Download.try_get_live_wait(input));
 	}
 
 	#[test]
@@ -347,6 +927,19 @@ mod tests {
 			LineType::Custom.try_get_parse_helper(input)
 		);
 
+		// should find "PARSE_FORMATS" and count the merged formats
+		let input = "PARSE_FORMATS '137+140'";
+		assert_eq!(
+			Some(CustomParseType::Formats(2)),
+			LineType::Custom.try_get_parse_helper(input)
+		);
+
+		let input = "PARSE_FORMATS '22'";
+		assert_eq!(
+			Some(CustomParseType::Formats(1)),
+			LineType::Custom.try_get_parse_helper(input)
+		);
+
 		// should not match the regex
 		let input = "PARSE";
 		assert_eq!(None, LineType::Custom.try_get_parse_helper(input));
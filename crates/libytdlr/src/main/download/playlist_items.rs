@@ -0,0 +1,123 @@
+//! Module for estimating how many entries of a playlist are selected with "--playlist-items" and "--max-downloads"
+
+use std::collections::BTreeSet;
+
+/// Get how many entries of a playlist with `playlist_count` entries will be downloaded
+/// with the given `playlist_items` (see [`super::DownloadOptions::playlist_items`]) and `max_downloads`
+///
+/// If `playlist_items` cannot be parsed, all entries are assumed to be selected
+#[must_use]
+pub fn selected_playlist_count(
+	playlist_items: Option<&str>,
+	max_downloads: Option<usize>,
+	playlist_count: usize,
+) -> usize {
+	let count = playlist_items
+		.and_then(|v| return count_playlist_items(v, playlist_count))
+		.unwrap_or(playlist_count);
+
+	return max_downloads.map_or(count, |v| return count.min(v));
+}
+
+/// Count the distinct entries selected by a yt-dlp "--playlist-items" spec, like "1,3,5-7" or "-5::2"
+/// Returns [`None`] if the spec cannot be parsed
+fn count_playlist_items(spec: &str, playlist_count: usize) -> Option<usize> {
+	let mut selected: BTreeSet<usize> = BTreeSet::new();
+
+	for part in spec.split(',').map(str::trim).filter(|v| return !v.is_empty()) {
+		let (start, end, step) = parse_item_range(part)?;
+		let start = resolve_index(start, playlist_count, 1)?;
+		let end = resolve_index(end, playlist_count, playlist_count)?;
+
+		if step == 0 {
+			return None;
+		}
+
+		selected.extend(
+			(start..=end)
+				.step_by(step)
+				.filter(|v| return (1..=playlist_count).contains(v)),
+		);
+	}
+
+	return Some(selected.len());
+}
+
+/// Split a single "--playlist-items" part into "(start, end, step)"
+/// A single index (like "3") is returned as a range of only that index
+fn parse_item_range(part: &str) -> Option<(&str, &str, usize)> {
+	if part.contains(':') {
+		let mut pieces = part.splitn(3, ':');
+		let start = pieces.next().unwrap_or_default();
+		let end = pieces.next().unwrap_or_default();
+		let step = match pieces.next() {
+			Some("") | None => 1,
+			Some(v) => v.parse().ok()?,
+		};
+
+		return Some((start, end, step));
+	}
+
+	// "1-3" is the same as "1:3", but a leading "-" is a negative index (like "-3")
+	if let Some(index) = part
+		.char_indices()
+		.skip(1)
+		.find(|(_, c)| return *c == '-')
+		.map(|(i, _)| return i)
+	{
+		return Some((&part[..index], &part[index + 1..], 1));
+	}
+
+	return Some((part, part, 1));
+}
+
+/// Resolve a 1-based (or negative, from the end) index, using `default` if `index` is empty
+fn resolve_index(index: &str, playlist_count: usize, default: usize) -> Option<usize> {
+	if index.is_empty() {
+		return Some(default);
+	}
+
+	let index: i64 = index.parse().ok()?;
+
+	if index < 0 {
+		let from_end = usize::try_from(index.unsigned_abs()).ok()?;
+
+		// negative indexes before the first entry select nothing, so clamp them to a index that is filtered out
+		return Some((playlist_count + 1).saturating_sub(from_end));
+	}
+
+	return usize::try_from(index).ok();
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_count_playlist_items() {
+		assert_eq!(Some(1), count_playlist_items("3", 10));
+		assert_eq!(Some(5), count_playlist_items("1,3,5-7", 10));
+		assert_eq!(Some(3), count_playlist_items("1:3", 10));
+		assert_eq!(Some(5), count_playlist_items("::2", 10));
+		assert_eq!(Some(3), count_playlist_items("-3:", 10));
+		assert_eq!(Some(1), count_playlist_items("-1", 10));
+		// overlapping ranges are counted once
+		assert_eq!(Some(4), count_playlist_items("1-3,2-4", 10));
+		// entries outside of the playlist are not counted
+		assert_eq!(Some(2), count_playlist_items("9-15", 10));
+		assert_eq!(Some(0), count_playlist_items("-20", 10));
+
+		assert_eq!(None, count_playlist_items("a-b", 10));
+		assert_eq!(None, count_playlist_items("1:5:0", 10));
+	}
+
+	#[test]
+	fn test_selected_playlist_count() {
+		assert_eq!(10, selected_playlist_count(None, None, 10));
+		assert_eq!(3, selected_playlist_count(Some("1-3"), None, 10));
+		assert_eq!(2, selected_playlist_count(Some("1-3"), Some(2), 10));
+		assert_eq!(5, selected_playlist_count(None, Some(5), 10));
+		// unparsable specs fall back to the whole playlist
+		assert_eq!(10, selected_playlist_count(Some("invalid"), None, 10));
+	}
+}
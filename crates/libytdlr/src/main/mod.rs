@@ -1,5 +1,13 @@
 //! Module for all the main functionality in the library (to keep everything sorted)
 pub mod archive;
+pub mod check_archive;
+pub mod diff;
 pub mod download;
+pub mod postprocess;
+pub mod remux;
 pub mod rethumbnail;
+pub mod search;
 pub mod sql_utils;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod url_preprocess;
@@ -0,0 +1,3 @@
+//! Module for optional post-processing steps applied to downloaded media
+pub mod loudnorm;
+pub mod replaygain;
@@ -0,0 +1,448 @@
+//! Module for normalizing the loudness of audio media with the ffmpeg "loudnorm" filter (two-pass)
+//!
+//! The first pass measures the loudness of the media, the second pass applies the normalization with the measured values,
+//! which is more accurate than the single-pass (dynamic) mode
+
+use std::{
+	path::{
+		Path,
+		PathBuf,
+	},
+	process::Stdio,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{
+	error::IOErrorToError,
+	spawn::ffmpeg::{
+		base_ffmpeg_hidebanner,
+		ffmpeg_probe,
+		unsuccessfull_command_exit,
+	},
+};
+
+/// The loudness to normalize to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnormTarget {
+	/// The integrated loudness in LUFS
+	pub integrated:     f64,
+	/// The maximal true peak in dBTP
+	pub true_peak:      f64,
+	/// The loudness range in LU
+	pub loudness_range: f64,
+}
+
+impl Default for LoudnormTarget {
+	fn default() -> Self {
+		return Self {
+			integrated:     -16.0,
+			true_peak:      -1.5,
+			loudness_range: 11.0,
+		};
+	}
+}
+
+impl LoudnormTarget {
+	/// Get the "loudnorm" filter options for this target
+	fn filter_options(&self) -> String {
+		return format!(
+			"I={}:TP={}:LRA={}",
+			self.integrated, self.true_peak, self.loudness_range
+		);
+	}
+}
+
+/// The loudness of a media, measured by the first "loudnorm" pass
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnormMeasurement {
+	/// The measured integrated loudness
+	pub input_i:       f64,
+	/// The measured true peak
+	pub input_tp:      f64,
+	/// The measured loudness range
+	pub input_lra:     f64,
+	/// The measured threshold
+	pub input_thresh:  f64,
+	/// The offset gain for the second pass
+	pub target_offset: f64,
+}
+
+impl LoudnormMeasurement {
+	/// Check whether all measured values are usable for the second pass
+	/// Media that is completely silent is measured as "-inf"
+	#[must_use]
+	pub fn is_usable(&self) -> bool {
+		return [
+			self.input_i,
+			self.input_tp,
+			self.input_lra,
+			self.input_thresh,
+			self.target_offset,
+		]
+		.iter()
+		.all(|v| return v.is_finite());
+	}
+
+	/// Get the "loudnorm" filter options to apply this measurement in the second pass
+	fn filter_options(&self) -> String {
+		return format!(
+			"measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+			self.input_i, self.input_tp, self.input_lra, self.input_thresh, self.target_offset
+		);
+	}
+}
+
+/// The JSON output of the "loudnorm" filter, where all values are strings
+#[derive(Debug, Deserialize)]
+struct RawMeasurement {
+	input_i:       String,
+	input_tp:      String,
+	input_lra:     String,
+	input_thresh:  String,
+	target_offset: String,
+}
+
+/// Parse the measurement from the ffmpeg output of the first pass, which is the last JSON object in the output
+fn parse_measurement(input: &str) -> Result<LoudnormMeasurement, crate::Error> {
+	let json = input
+		.rfind('{')
+		.and_then(|start| return input[start..].find('}').map(|end| return &input[start..=start + end]))
+		.ok_or_else(|| return crate::Error::no_captures("loudnorm did not output a measurement"))?;
+
+	let raw: RawMeasurement = serde_json::from_str(json)?;
+
+	let parse = |value: &str| {
+		return value
+			.trim()
+			.parse::<f64>()
+			.map_err(|_| return crate::Error::other(format!("loudnorm measurement \"{value}\" is not a number")));
+	};
+
+	return Ok(LoudnormMeasurement {
+		input_i:       parse(&raw.input_i)?,
+		input_tp:      parse(&raw.input_tp)?,
+		input_lra:     parse(&raw.input_lra)?,
+		input_thresh:  parse(&raw.input_thresh)?,
+		target_offset: parse(&raw.target_offset)?,
+	});
+}
+
+/// Regex to parse the first audio stream of "input #0" from ffmpeg output
+/// cap1: codec name, cap2: sample rate, cap3: bitrate in kb/s (optional)
+static FFMPEG_PARSE_AUDIO_STREAM: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(
+		r"(?mi)^\s*stream #0:\d+(?:\[\w+\])?(?:\([^)]*\))?: audio: (\w+)[^\n]*?, (\d+) hz(?:[^\n]*?, (\d+) kb/s)?",
+	)
+	.unwrap();
+});
+
+/// The audio stream of a media, to re-encode it like it was
+#[derive(Debug, Clone, PartialEq)]
+struct AudioStream {
+	/// The codec name, like "opus"
+	codec:       String,
+	/// The sample rate in Hz
+	sample_rate: u32,
+	/// The bitrate in kb/s, if known
+	bitrate:     Option<u32>,
+}
+
+/// Parse the first audio stream from the output of [`ffmpeg_probe`]
+fn parse_audio_stream(input: &str) -> Option<AudioStream> {
+	let cap = FFMPEG_PARSE_AUDIO_STREAM.captures(input)?;
+
+	return Some(AudioStream {
+		codec:       cap[1].to_owned(),
+		sample_rate: cap[2].parse().ok()?,
+		bitrate:     cap.get(3).and_then(|v| return v.as_str().parse().ok()),
+	});
+}
+
+/// Get the ffmpeg encoder to re-encode `codec` with, because applying a filter requires re-encoding
+fn audio_encoder(codec: &str) -> Option<&'static str> {
+	return match codec {
+		"mp3" => Some("libmp3lame"),
+		"aac" => Some("aac"),
+		"opus" => Some("libopus"),
+		"vorbis" => Some("libvorbis"),
+		"flac" => Some("flac"),
+		_ => None,
+	};
+}
+
+/// Run the first "loudnorm" pass on `media` to measure its loudness
+pub fn measure_loudness(media: &Path, target: &LoudnormTarget) -> Result<LoudnormMeasurement, crate::Error> {
+	let mut cmd = base_ffmpeg_hidebanner(false);
+	cmd.arg("-i").arg(media);
+	cmd.arg("-af");
+	cmd.arg(format!("loudnorm={}:print_format=json", target.filter_options()));
+	// only analyze, dont write any output
+	cmd.args(["-vn", "-f", "null", "-"]);
+
+	let command_output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::null())
+		.stdin(Stdio::null())
+		.output()
+		.attach_location_err("ffmpeg loudnorm measure")?;
+
+	let output = String::from_utf8_lossy(&command_output.stderr);
+
+	if !command_output.status.success() {
+		return Err(unsuccessfull_command_exit(command_output.status, &output));
+	}
+
+	return parse_measurement(&output);
+}
+
+/// Normalize the loudness of the audio in `media` to `target` in-place, with a temporary file until finished
+/// Returns `false` if the media was not changed, because it is silent
+pub fn loudnorm(media: &Path, target: &LoudnormTarget) -> Result<bool, crate::Error> {
+	let probe = ffmpeg_probe(media)?;
+	let stream = parse_audio_stream(&probe)
+		.ok_or_else(|| return crate::Error::other(format!("\"{}\" has no audio stream", media.display())))?;
+	let encoder = audio_encoder(&stream.codec).ok_or_else(|| {
+		return crate::Error::other(format!("Normalizing audio codec \"{}\" is not supported", stream.codec));
+	})?;
+
+	let measurement = measure_loudness(media, target)?;
+
+	if !measurement.is_usable() {
+		debug!("Not normalizing \"{}\", because it is silent", media.display());
+
+		return Ok(false);
+	}
+
+	let output_tmp = tmp_path(media);
+	debug!(
+		"Normalizing \"{}\" from {} LUFS to {} LUFS",
+		media.display(),
+		measurement.input_i,
+		target.integrated
+	);
+
+	let mut cmd = base_ffmpeg_hidebanner(true);
+	cmd.arg("-i").arg(media);
+	// keep all streams (like thumbnails) and metadata, only re-encode the audio
+	cmd.args(["-map", "0", "-c", "copy", "-c:a", encoder]);
+	cmd.arg("-af");
+	cmd.arg(format!(
+		"loudnorm={}:{}",
+		target.filter_options(),
+		measurement.filter_options()
+	));
+	// "loudnorm" upsamples to 192kHz, so keep the original sample rate
+	cmd.arg("-ar").arg(stream.sample_rate.to_string());
+
+	if let Some(bitrate) = stream.bitrate {
+		cmd.arg("-b:a").arg(format!("{bitrate}k"));
+	}
+
+	cmd.arg(&output_tmp);
+
+	let command_output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::null())
+		.stdin(Stdio::null())
+		.output()
+		.attach_location_err("ffmpeg loudnorm apply")?;
+
+	if !command_output.status.success() {
+		// dont leave a partial output behind
+		let _ = std::fs::remove_file(&output_tmp);
+
+		return Err(unsuccessfull_command_exit(
+			command_output.status,
+			&String::from_utf8_lossy(&command_output.stderr),
+		));
+	}
+
+	std::fs::rename(&output_tmp, media).attach_path_err(output_tmp)?;
+
+	return Ok(true);
+}
+
+/// Get the temporary path to write the normalized `media` to, which is `media` with the current pid added to the stem
+fn tmp_path(media: &Path) -> PathBuf {
+	let mut stem = media.file_stem().unwrap_or_default().to_os_string();
+	// add the current pid, so multiple instances can run at the same time
+	stem.push(format!("_{}", std::process::id()));
+
+	if let Some(ext) = media.extension() {
+		stem.push(".");
+		stem.push(ext);
+	}
+
+	return media.with_file_name(stem);
+}
+
+/// Enum to represent why the callback was called plus extra arguments
+#[derive(Debug, PartialEq, Clone)]
+pub enum LoudnormProgress {
+	/// Variant that indicates that the batch has started [amount of media]
+	/// Will always be called
+	Starting(usize),
+	/// Variant that indicates that a media has started processing [index, media path]
+	SingleStarting(usize, PathBuf),
+	/// Variant that indicates that a media was skipped, because it is silent [index]
+	SingleSkipped(usize),
+	/// Variant that indicates that a media has successfully finished [index]
+	SingleFinished(usize),
+	/// Variant that indicates that a media has failed, the error will be returned in the result [index]
+	SingleFailed(usize),
+	/// Variant that indicates that the batch has finished [successfull media, failed media]
+	/// Will always be called
+	Finished(usize, usize),
+}
+
+/// Normalize the loudness of all `media` to `target` with [`loudnorm`]
+/// A failure on one media will not abort processing the other media
+///
+/// Returns all errors that occured, with the index of the media they occured on
+pub fn loudnorm_many<C: FnMut(LoudnormProgress)>(
+	media: &[PathBuf],
+	target: &LoudnormTarget,
+	mut pgcb: C,
+) -> Vec<(usize, crate::Error)> {
+	pgcb(LoudnormProgress::Starting(media.len()));
+
+	let mut errors = Vec::new();
+	let mut successfull = 0;
+
+	for (index, path) in media.iter().enumerate() {
+		pgcb(LoudnormProgress::SingleStarting(index, path.clone()));
+
+		match loudnorm(path, target) {
+			Ok(true) => {
+				successfull += 1;
+				pgcb(LoudnormProgress::SingleFinished(index));
+			},
+			Ok(false) => pgcb(LoudnormProgress::SingleSkipped(index)),
+			Err(err) => {
+				warn!("Loudnorm for \"{}\" failed: {}", path.display(), err);
+				errors.push((index, err));
+				pgcb(LoudnormProgress::SingleFailed(index));
+			},
+		}
+	}
+
+	pgcb(LoudnormProgress::Finished(successfull, errors.len()));
+
+	return errors;
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_parse_measurement() {
+		let ffmpeg_output = r#"Input #0, mp3, from 'test.mp3':
+  Stream #0:0: Audio: mp3, 44100 Hz, stereo, fltp, 128 kb/s
+[Parsed_loudnorm_0 @ 0x55d3c8e0c2c0]
+{
+	"input_i" : "-27.61",
+	"input_tp" : "-4.47",
+	"input_lra" : "18.06",
+	"input_thresh" : "-39.20",
+	"output_i" : "-16.58",
+	"output_tp" : "-1.50",
+	"output_lra" : "14.78",
+	"output_thresh" : "-27.71",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.58"
+}
+"#;
+
+		let measurement = parse_measurement(ffmpeg_output).expect("Expected the measurement to parse");
+
+		assert_eq!(
+			LoudnormMeasurement {
+				input_i:       -27.61,
+				input_tp:      -4.47,
+				input_lra:     18.06,
+				input_thresh:  -39.20,
+				target_offset: 0.58,
+			},
+			measurement
+		);
+		assert!(measurement.is_usable());
+		assert_eq!(
+			"measured_I=-27.61:measured_TP=-4.47:measured_LRA=18.06:measured_thresh=-39.2:offset=0.58:linear=true",
+			measurement.filter_options()
+		);
+
+		assert!(parse_measurement("no json here").is_err());
+	}
+
+	#[test]
+	fn test_parse_measurement_silent() {
+		let ffmpeg_output = r#"{
+	"input_i" : "-inf",
+	"input_tp" : "-inf",
+	"input_lra" : "0.00",
+	"input_thresh" : "-70.00",
+	"output_i" : "-inf",
+	"output_tp" : "-inf",
+	"output_lra" : "0.00",
+	"output_thresh" : "-70.00",
+	"normalization_type" : "dynamic",
+	"target_offset" : "inf"
+}"#;
+
+		let measurement = parse_measurement(ffmpeg_output).expect("Expected the measurement to parse");
+
+		assert!(!measurement.is_usable());
+	}
+
+	#[test]
+	fn test_parse_audio_stream() {
+		assert_eq!(None, parse_audio_stream("hello"));
+
+		let ffmpeg_output_mp3 = r#"Input #0, mp3, from 'test.mp3':
+  Duration: 00:03:00.00, start: 0.025057, bitrate: 131 kb/s
+  Stream #0:0: Audio: mp3, 44100 Hz, stereo, fltp, 128 kb/s
+  Stream #0:1: Video: mjpeg (Baseline), yuvj420p(pc, bt470bg/unknown/unknown), 1280x720, 90k tbr, 90k tbn (attached pic)
+"#;
+
+		assert_eq!(
+			Some(AudioStream {
+				codec:       "mp3".to_owned(),
+				sample_rate: 44100,
+				bitrate:     Some(128),
+			}),
+			parse_audio_stream(ffmpeg_output_mp3)
+		);
+
+		let ffmpeg_output_opus = r#"Input #0, ogg, from 'test.opus':
+  Duration: 00:03:00.00, start: 0.007500, bitrate: 131 kb/s
+  Stream #0:0(eng): Audio: opus, 48000 Hz, stereo, fltp
+"#;
+
+		assert_eq!(
+			Some(AudioStream {
+				codec:       "opus".to_owned(),
+				sample_rate: 48000,
+				bitrate:     None,
+			}),
+			parse_audio_stream(ffmpeg_output_opus)
+		);
+	}
+
+	#[test]
+	fn test_audio_encoder() {
+		assert_eq!(Some("libmp3lame"), audio_encoder("mp3"));
+		assert_eq!(Some("libopus"), audio_encoder("opus"));
+		assert_eq!(None, audio_encoder("pcm_s16le"));
+	}
+
+	#[test]
+	fn test_tmp_path() {
+		let expected = format!("/music/song_{}.mp3", std::process::id());
+
+		assert_eq!(PathBuf::from(expected), tmp_path(Path::new("/music/song.mp3")));
+	}
+}
@@ -0,0 +1,315 @@
+//! Module for calculating the ReplayGain (track gain and peak) of audio media with the ffmpeg "ebur128" filter and writing it as tags
+
+use std::{
+	ffi::OsStr,
+	path::{
+		Path,
+		PathBuf,
+	},
+	process::Stdio,
+};
+
+use lofty::{
+	config::WriteOptions,
+	file::TaggedFileExt,
+	probe::Probe,
+	tag::{
+		ItemKey,
+		TagExt,
+	},
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+	error::IOErrorToError,
+	spawn::ffmpeg::{
+		base_ffmpeg_hidebanner,
+		unsuccessfull_command_exit,
+	},
+};
+
+/// The reference loudness of ReplayGain 2.0 in LUFS
+pub const REPLAYGAIN_REFERENCE: f64 = -18.0;
+
+/// File extensions which ReplayGain tags can be written to
+const SUPPORTED_EXTENSIONS: &[&str] = &["mp3", "opus", "ogg", "flac"];
+
+/// The ReplayGain of a single track
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayGain {
+	/// The gain to apply to reach [`REPLAYGAIN_REFERENCE`] in dB
+	pub gain: f64,
+	/// The true peak as a linear amplitude (where "1.0" is full scale)
+	pub peak: f64,
+}
+
+impl ReplayGain {
+	/// Calculate the ReplayGain from the integrated loudness in LUFS and the true peak in dBFS
+	#[must_use]
+	pub fn from_loudness(integrated: f64, true_peak: f64) -> Self {
+		return Self {
+			gain: REPLAYGAIN_REFERENCE - integrated,
+			peak: 10f64.powf(true_peak / 20.0),
+		};
+	}
+
+	/// Get the value of the "REPLAYGAIN_TRACK_GAIN" tag, like "-1.40 dB"
+	#[must_use]
+	pub fn gain_tag(&self) -> String {
+		return format!("{:.2} dB", self.gain);
+	}
+
+	/// Get the value of the "REPLAYGAIN_TRACK_PEAK" tag, like "0.912011"
+	#[must_use]
+	pub fn peak_tag(&self) -> String {
+		return format!("{:.6}", self.peak);
+	}
+}
+
+/// Check whether ReplayGain tags can be written to `media`, based on its extension
+#[must_use]
+pub fn is_supported(media: &Path) -> bool {
+	return media
+		.extension()
+		.and_then(OsStr::to_str)
+		.is_some_and(|v| return SUPPORTED_EXTENSIONS.contains(&v.to_lowercase().as_str()));
+}
+
+/// Regex to parse the integrated loudness from the "ebur128" summary
+/// cap1: integrated loudness in LUFS
+static EBUR128_INTEGRATED: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?m)^\s*I:\s+(-?[\d.]+|-inf) LUFS").unwrap();
+});
+
+/// Regex to parse the true peak from the "ebur128" summary
+/// cap1: true peak in dBFS
+static EBUR128_PEAK: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?m)^\s*Peak:\s+(-?[\d.]+|-inf) dBFS").unwrap();
+});
+
+/// Parse the [`ReplayGain`] from the ffmpeg output of the "ebur128" filter, which is the last summary in the output
+/// Returns [`None`] for silent media, because no gain can be calculated for them
+fn parse_ebur128(input: &str) -> Result<Option<ReplayGain>, crate::Error> {
+	// the summary is the last part of the output, the values before are the running measurements
+	let summary = input.rfind("Summary:").map_or(input, |v| return &input[v..]);
+
+	let parse = |regex: &Regex, name: &str| {
+		let value = regex
+			.captures(summary)
+			.map(|v| return v[1].to_owned())
+			.ok_or_else(|| return crate::Error::no_captures(format!("ebur128 did not output a {name}")))?;
+
+		return value
+			.parse::<f64>()
+			.map_err(|_| return crate::Error::other(format!("ebur128 {name} \"{value}\" is not a number")));
+	};
+
+	let integrated = parse(&EBUR128_INTEGRATED, "integrated loudness")?;
+	let peak = parse(&EBUR128_PEAK, "true peak")?;
+
+	if !integrated.is_finite() || !peak.is_finite() {
+		return Ok(None);
+	}
+
+	return Ok(Some(ReplayGain::from_loudness(integrated, peak)));
+}
+
+/// Measure the [`ReplayGain`] of `media` with the ffmpeg "ebur128" filter
+/// Returns [`None`] for silent media
+pub fn measure_replaygain(media: &Path) -> Result<Option<ReplayGain>, crate::Error> {
+	let mut cmd = base_ffmpeg_hidebanner(false);
+	cmd.arg("-i").arg(media);
+	cmd.args(["-af", "ebur128=peak=true"]);
+	// only analyze, dont write any output
+	cmd.args(["-vn", "-f", "null", "-"]);
+
+	let command_output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::null())
+		.stdin(Stdio::null())
+		.output()
+		.attach_location_err("ffmpeg ebur128")?;
+
+	let output = String::from_utf8_lossy(&command_output.stderr);
+
+	if !command_output.status.success() {
+		return Err(unsuccessfull_command_exit(command_output.status, &output));
+	}
+
+	return parse_ebur128(&output);
+}
+
+/// Write `gain` as the ReplayGain track tags of `media`, keeping all other tags
+pub fn write_replaygain_tags(media: &Path, gain: &ReplayGain) -> Result<(), crate::Error> {
+	let mut tagged_file = Probe::open(media)
+		.map_err(|err| return crate::Error::other(format!("LoftyError: {}", err)))?
+		.read()
+		.map_err(|err| return crate::Error::other(format!("LoftyError: {}", err)))?;
+
+	// use the primary tag format, or the first found
+	let tag = match tagged_file.primary_tag_mut() {
+		Some(v) => v,
+		None => tagged_file
+			.first_tag_mut()
+			.ok_or_else(|| return crate::Error::other(format!("No tags in file \"{}\"", media.display())))?,
+	};
+
+	tag.insert_text(ItemKey::ReplayGainTrackGain, gain.gain_tag());
+	tag.insert_text(ItemKey::ReplayGainTrackPeak, gain.peak_tag());
+
+	tag.save_to_path(media, WriteOptions::default())
+		.map_err(|err| return crate::Error::other(format!("Could not save tags to file: {}", err)))?;
+
+	return Ok(());
+}
+
+/// Measure the [`ReplayGain`] of `media` and write it as tags
+/// Returns `false` if no tags were written, because the media is silent
+pub fn replaygain(media: &Path) -> Result<bool, crate::Error> {
+	if !is_supported(media) {
+		return Err(crate::Error::other(format!(
+			"Writing ReplayGain tags to \"{}\" is not supported",
+			media.display()
+		)));
+	}
+
+	let Some(gain) = measure_replaygain(media)? else {
+		debug!(
+			"Not writing ReplayGain for \"{}\", because it is silent",
+			media.display()
+		);
+
+		return Ok(false);
+	};
+
+	debug!(
+		"Writing ReplayGain {} (peak {}) to \"{}\"",
+		gain.gain_tag(),
+		gain.peak_tag(),
+		media.display()
+	);
+
+	write_replaygain_tags(media, &gain)?;
+
+	return Ok(true);
+}
+
+/// Enum to represent why the callback was called plus extra arguments
+#[derive(Debug, PartialEq, Clone)]
+pub enum ReplayGainProgress {
+	/// Variant that indicates that the batch has started [amount of media]
+	/// Will always be called
+	Starting(usize),
+	/// Variant that indicates that a media has started processing [index, media path]
+	SingleStarting(usize, PathBuf),
+	/// Variant that indicates that a media was skipped, because it is silent [index]
+	SingleSkipped(usize),
+	/// Variant that indicates that a media has successfully finished [index]
+	SingleFinished(usize),
+	/// Variant that indicates that a media has failed, the error will be returned in the result [index]
+	SingleFailed(usize),
+	/// Variant that indicates that the batch has finished [successfull media, failed media]
+	/// Will always be called
+	Finished(usize, usize),
+}
+
+/// Write the ReplayGain tags of all `media` with [`replaygain`]
+/// A failure on one media will not abort processing the other media
+///
+/// Returns all errors that occured, with the index of the media they occured on
+pub fn replaygain_many<C: FnMut(ReplayGainProgress)>(media: &[PathBuf], mut pgcb: C) -> Vec<(usize, crate::Error)> {
+	pgcb(ReplayGainProgress::Starting(media.len()));
+
+	let mut errors = Vec::new();
+	let mut successfull = 0;
+
+	for (index, path) in media.iter().enumerate() {
+		pgcb(ReplayGainProgress::SingleStarting(index, path.clone()));
+
+		match replaygain(path) {
+			Ok(true) => {
+				successfull += 1;
+				pgcb(ReplayGainProgress::SingleFinished(index));
+			},
+			Ok(false) => pgcb(ReplayGainProgress::SingleSkipped(index)),
+			Err(err) => {
+				warn!("ReplayGain for \"{}\" failed: {}", path.display(), err);
+				errors.push((index, err));
+				pgcb(ReplayGainProgress::SingleFailed(index));
+			},
+		}
+	}
+
+	pgcb(ReplayGainProgress::Finished(successfull, errors.len()));
+
+	return errors;
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_parse_ebur128() {
+		let ffmpeg_output = r"Input #0, mp3, from 'test.mp3':
+  Stream #0:0: Audio: mp3, 44100 Hz, stereo, fltp, 128 kb/s
+[Parsed_ebur128_0 @ 0x5581c5a8c340] t: 0.1       TARGET:-23 LUFS    M:-120.7 S:-120.7     I: -70.0 LUFS       LRA:   0.0 LU  FTPK: -5.2 dBFS  TPK: -5.2 dBFS
+[Parsed_ebur128_0 @ 0x5581c5a8c340] Summary:
+
+  Integrated loudness:
+    I:         -19.4 LUFS
+    Threshold: -29.8 LUFS
+
+  Loudness range:
+    LRA:         6.6 LU
+    Threshold:  -39.8 LUFS
+    LRA low:    -24.1 LUFS
+    LRA high:   -17.5 LUFS
+
+  True peak:
+    Peak:       -0.8 dBFS
+";
+
+		let gain = parse_ebur128(ffmpeg_output)
+			.expect("Expected the summary to parse")
+			.expect("Expected the media to not be silent");
+
+		assert_eq!("1.40 dB", gain.gain_tag());
+		assert_eq!("0.912011", gain.peak_tag());
+
+		assert!(parse_ebur128("no summary here").is_err());
+	}
+
+	#[test]
+	fn test_parse_ebur128_silent() {
+		let ffmpeg_output = r"[Parsed_ebur128_0 @ 0x5581c5a8c340] Summary:
+
+  Integrated loudness:
+    I:         -70.0 LUFS
+    Threshold:   0.0 LUFS
+
+  True peak:
+    Peak:       -inf dBFS
+";
+
+		assert_eq!(Ok(None), parse_ebur128(ffmpeg_output));
+	}
+
+	#[test]
+	fn test_from_loudness() {
+		let gain = ReplayGain::from_loudness(-14.0, 0.0);
+
+		assert_eq!("-4.00 dB", gain.gain_tag());
+		assert_eq!("1.000000", gain.peak_tag());
+	}
+
+	#[test]
+	fn test_is_supported() {
+		assert!(is_supported(Path::new("/music/song.mp3")));
+		assert!(is_supported(Path::new("/music/song.OPUS")));
+		assert!(is_supported(Path::new("/music/song.flac")));
+		assert!(!is_supported(Path::new("/music/song.m4a")));
+		assert!(!is_supported(Path::new("/music/song")));
+	}
+}
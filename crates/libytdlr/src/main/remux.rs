@@ -0,0 +1,138 @@
+//! Module for remuxing downloaded videos only when needed, based on the codecs of their streams
+
+use std::{
+	ffi::OsStr,
+	path::{
+		Path,
+		PathBuf,
+	},
+	process::Stdio,
+};
+
+use crate::{
+	data::cache::media_info::MediaInfo,
+	error::IOErrorToError,
+	spawn::ffmpeg::{
+		ffmpeg_probe,
+		parse_stream_codecs,
+		unsuccessfull_command_exit,
+	},
+};
+
+/// Codecs that can be stored in a "webm" container
+const WEBM_CODECS: &[&str] = &["vp8", "vp9", "av1", "vorbis", "opus", "webvtt"];
+/// Codecs that can be stored in a "mp4" container
+const MP4_CODECS: &[&str] = &[
+	"h264", "hevc", "av1", "vp9", "aac", "mp3", "opus", "flac", "alac", "ac3", "eac3", "mov_text", "mjpeg", "png",
+];
+
+/// Check whether all `codecs` can be stored in `container` (like "webm")
+/// "mkv" can store any codec, unknown containers are never considered to support any codecs
+#[must_use]
+pub fn container_supports(container: &str, codecs: &[&str]) -> bool {
+	let supported = match container {
+		"mkv" => return true,
+		"webm" => WEBM_CODECS,
+		"mp4" => MP4_CODECS,
+		_ => return false,
+	};
+
+	return codecs.iter().all(|v| return supported.contains(v));
+}
+
+/// Select the container to remux a video with `extension` and `codecs` into
+/// Returns [`None`] if the extension is already one of the `targets` and supports all codecs (or no target supports the codecs),
+/// otherwise the first of the `targets` that supports all codecs
+#[must_use]
+pub fn select_remux_target<'a>(extension: &str, codecs: &[&str], targets: &[&'a str]) -> Option<&'a str> {
+	if targets.contains(&extension) && container_supports(extension, codecs) {
+		return None;
+	}
+
+	return targets.iter().find(|v| return container_supports(v, codecs)).copied();
+}
+
+/// Probe the streams of `media` and remux it into the first of `targets` that supports them, if it is not already in a compatible target
+/// The original file is removed and its sidecar (if any) is moved to the new path
+/// Returns the path of the remuxed file, or [`None`] if no remux was necessary
+pub fn remux_if_needed(media: &Path, targets: &[&str]) -> Result<Option<PathBuf>, crate::Error> {
+	let extension = media.extension().and_then(OsStr::to_str).unwrap_or_default();
+	let probe = ffmpeg_probe(media)?;
+	let codecs = parse_stream_codecs(&probe);
+
+	let Some(target) = select_remux_target(extension, &codecs, targets) else {
+		trace!("No remux necessary for \"{}\"", media.display());
+
+		return Ok(None);
+	};
+
+	let output = media.with_extension(target);
+	debug!(
+		"Remuxing \"{}\" into \"{target}\" for codecs {codecs:?}",
+		media.display()
+	);
+
+	let mut cmd = crate::spawn::ffmpeg::base_ffmpeg_hidebanner(true);
+	cmd.arg("-i").arg(media);
+	// keep all streams and only change the container
+	cmd.args(["-map", "0", "-c", "copy"]);
+	cmd.arg(&output);
+
+	let command_output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::null())
+		.stdin(Stdio::null())
+		.output()
+		.attach_location_err("ffmpeg remux")?;
+
+	if !command_output.status.success() {
+		// dont leave a partial output behind
+		let _ = std::fs::remove_file(&output);
+
+		return Err(unsuccessfull_command_exit(
+			command_output.status,
+			&String::from_utf8_lossy(&command_output.stderr),
+		));
+	}
+
+	std::fs::remove_file(media).attach_path_err(media)?;
+
+	let sidecar = MediaInfo::sidecar_path(media);
+	if sidecar.exists() {
+		let new_sidecar = MediaInfo::sidecar_path(&output);
+		std::fs::rename(&sidecar, &new_sidecar).attach_path_err(&sidecar)?;
+	}
+
+	return Ok(Some(output));
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_container_supports() {
+		assert!(container_supports("mkv", &["h264", "none"]));
+		assert!(container_supports("webm", &["av1", "opus"]));
+		assert!(!container_supports("webm", &["h264", "aac"]));
+		assert!(container_supports("mp4", &["h264", "aac"]));
+		assert!(!container_supports("mp4", &["vp9", "vorbis"]));
+		assert!(!container_supports("avi", &[]));
+	}
+
+	#[test]
+	fn test_select_remux_target() {
+		let targets = ["webm", "mkv"];
+
+		// already compatible
+		assert_eq!(None, select_remux_target("webm", &["vp9", "opus"], &targets));
+		assert_eq!(None, select_remux_target("mkv", &["h264", "aac"], &targets));
+		// prefer webm for av1 / vp9
+		assert_eq!(Some("webm"), select_remux_target("mp4", &["av1", "opus"], &targets));
+		// fall back to mkv for incompatible codecs
+		assert_eq!(Some("mkv"), select_remux_target("mp4", &["h264", "aac"], &targets));
+		assert_eq!(Some("mkv"), select_remux_target("webm", &["vp9", "aac"], &targets));
+		// no target supports the codecs
+		assert_eq!(None, select_remux_target("mkv", &["h264"], &["webm"]));
+	}
+}
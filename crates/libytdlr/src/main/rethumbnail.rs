@@ -10,6 +10,7 @@ use std::{
 	io::{
 		BufRead,
 		BufReader,
+		Write,
 	},
 	path::{
 		Path,
@@ -34,7 +35,11 @@ use crate::{
 		CustomThreadJoin,
 		IOErrorToError,
 	},
-	spawn::ffmpeg::unsuccessfull_command_exit,
+	spawn::{
+		curl::curl_download,
+		ffmpeg::unsuccessfull_command_exit,
+		ytdl::ytdl_thumbnail_url,
+	},
 };
 
 /// Re-Apply a thumbnail from `image` onto `media` as `output`
@@ -99,6 +104,181 @@ pub fn re_thumbnail_with_tmp(media: &Path, image: &Path, output: &Path) -> Resul
 	return Ok(());
 }
 
+/// Re-Apply the thumbnail of the original media at `media_url` onto `media` as `output`
+/// The thumbnail URL is requested from the youtube-dl binary `ytdl_bin` and downloaded, then applied like [`re_thumbnail_with_tmp`]
+pub fn re_thumbnail_from_url(
+	ytdl_bin: &OsStr,
+	media: &Path,
+	media_url: &str,
+	output: &Path,
+) -> Result<(), crate::Error> {
+	let thumbnail_url = ytdl_thumbnail_url(ytdl_bin, media_url)?;
+
+	let tmp_dir = std::env::temp_dir().join("libytdlr-imageconvert");
+	std::fs::create_dir_all(&tmp_dir).attach_path_err(&tmp_dir)?;
+
+	// add the current pid, so multiple instances can run at the same time
+	let image_path = tmp_dir.join(format!(
+		"thumbnail_{}.{}",
+		std::process::id(),
+		thumbnail_extension(&thumbnail_url)
+	));
+
+	let res = curl_download(&thumbnail_url, &image_path)
+		.and_then(|()| return re_thumbnail_with_tmp(media, &image_path, output));
+
+	// remove the downloaded image, regardless of whether applying it was successfull
+	if image_path.exists() {
+		std::fs::remove_file(&image_path).attach_path_err(&image_path)?;
+	}
+
+	return res;
+}
+
+/// Get the file extension of the image at `url`, ignoring query and fragment
+/// Returns "image" if there is no usable extension, so that it will always be converted by [`convert_image_to_jpg`]
+fn thumbnail_extension(url: &str) -> &str {
+	let path = url.split(['?', '#']).next().unwrap_or_default();
+
+	return path
+		.rsplit('/')
+		.next()
+		.and_then(|v| return v.rsplit_once('.'))
+		.map(|(_, ext)| return ext)
+		.filter(|v| return !v.is_empty() && v.len() <= 4 && v.chars().all(|c| return c.is_ascii_alphanumeric()))
+		.unwrap_or("image");
+}
+
+/// Enum to represent why the callback was called plus extra arguments
+#[derive(Debug, PartialEq, Clone)]
+pub enum ReThumbnailProgress {
+	/// Variant that indicates that the batch has started [amount of items]
+	/// Will always be called
+	Starting(usize),
+	/// Variant that indicates that a item has started processing [index, media path]
+	SingleStarting(usize, PathBuf),
+	/// Variant that indicates that a item was skipped, because no image could be found [index]
+	SingleSkipped(usize),
+	/// Variant that indicates that a item has successfully finished [index]
+	SingleFinished(usize),
+	/// Variant that indicates that a item has failed, the error will be returned in the result [index]
+	SingleFailed(usize),
+	/// Variant that indicates that the batch has finished [successfull items, failed items]
+	/// Will always be called
+	Finished(usize, usize),
+}
+
+/// A single item to process in [`re_thumbnail_many`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct ReThumbnailItem {
+	/// The media file to apply the thumbnail to
+	pub media:     PathBuf,
+	/// The image to use, if [`None`] it will be searched for with [`find_image_in`]
+	pub image:     Option<PathBuf>,
+	/// The directory to search the image in first, the directory of `media` is always searched afterwards
+	pub image_dir: Option<PathBuf>,
+	/// The output path, if [`None`] it will be the same as `media`
+	pub output:    Option<PathBuf>,
+}
+
+impl ReThumbnailItem {
+	/// Create a new instance, without a image and output
+	pub fn new<P: AsRef<Path>>(media: P) -> Self {
+		return Self {
+			media:     media.as_ref().to_owned(),
+			image:     None,
+			image_dir: None,
+			output:    None,
+		};
+	}
+
+	/// Set the image to use
+	#[must_use]
+	pub fn with_image<P: AsRef<Path>>(mut self, image: P) -> Self {
+		self.image = Some(image.as_ref().to_owned());
+
+		return self;
+	}
+
+	/// Set the directory to search the image in
+	#[must_use]
+	pub fn with_image_dir<P: AsRef<Path>>(mut self, image_dir: P) -> Self {
+		self.image_dir = Some(image_dir.as_ref().to_owned());
+
+		return self;
+	}
+
+	/// Set the output path to use
+	#[must_use]
+	pub fn with_output<P: AsRef<Path>>(mut self, output: P) -> Self {
+		self.output = Some(output.as_ref().to_owned());
+
+		return self;
+	}
+}
+
+/// Find the image for a [`ReThumbnailItem`], first in the `image_dir` (if set), then in the directory of the media
+fn find_item_image(item: &ReThumbnailItem) -> Result<Option<PathBuf>, crate::Error> {
+	if let Some(image_dir) = &item.image_dir {
+		if let Some(image) = find_image_in(&item.media, image_dir)? {
+			return Ok(Some(image));
+		}
+	}
+
+	return find_image(&item.media);
+}
+
+/// Re-Apply thumbnails to all `items` with [`re_thumbnail_with_tmp`]
+/// A failure on one item will not abort processing the other items
+///
+/// Returns all errors that occured, with the index of the item they occured on
+pub fn re_thumbnail_many<C: FnMut(ReThumbnailProgress)>(
+	items: &[ReThumbnailItem],
+	mut pgcb: C,
+) -> Vec<(usize, crate::Error)> {
+	pgcb(ReThumbnailProgress::Starting(items.len()));
+
+	let mut errors = Vec::new();
+	let mut successfull = 0;
+
+	for (index, item) in items.iter().enumerate() {
+		pgcb(ReThumbnailProgress::SingleStarting(index, item.media.clone()));
+
+		let image = match &item.image {
+			Some(v) => Ok(Some(Cow::Borrowed(v.as_path()))),
+			None => find_item_image(item).map(|v| return v.map(Cow::Owned)),
+		};
+
+		let res = match image {
+			Ok(Some(image)) => re_thumbnail_with_tmp(&item.media, &image, item.output.as_ref().unwrap_or(&item.media)),
+			Ok(None) => {
+				debug!("No image found for \"{}\", skipping", item.media.display());
+				pgcb(ReThumbnailProgress::SingleSkipped(index));
+
+				continue;
+			},
+			Err(err) => Err(err),
+		};
+
+		match res {
+			Ok(()) => {
+				successfull += 1;
+				pgcb(ReThumbnailProgress::SingleFinished(index));
+			},
+			Err(err) => {
+				// not warned about, because the errors are returned for the caller to report
+				debug!("ReThumbnail for \"{}\" failed: {}", item.media.display(), err);
+				errors.push((index, err));
+				pgcb(ReThumbnailProgress::SingleFailed(index));
+			},
+		}
+	}
+
+	pgcb(ReThumbnailProgress::Finished(successfull, errors.len()));
+
+	return errors;
+}
+
 /// Re-Apply a thumbnail from `image` onto `media` as `output`
 /// Will not apply any image conversion
 ///
@@ -114,9 +294,12 @@ pub fn re_thumbnail(media: &Path, image: &Path, output: &Path) -> Result<(), cra
 	let ffmpeg_output = crate::spawn::ffmpeg::ffmpeg_probe(media)?;
 	let container_formats = crate::spawn::ffmpeg::parse_format(&ffmpeg_output)?;
 
-	if container_formats.contains(&"ogg") | container_formats.contains(&"flac") {
+	if container_formats.contains(&"ogg") {
 		return rethumbnail_ogg(media, image, output);
 	}
+	if container_formats.contains(&"flac") {
+		return rethumbnail_flac(media, image, output);
+	}
 	if container_formats.contains(&"matroska") {
 		return rethumbnail_mkv(media, image, output);
 	} else if container_formats.contains(&"mp3") {
@@ -131,14 +314,222 @@ pub fn re_thumbnail(media: &Path, image: &Path, output: &Path) -> Result<(), cra
 	)));
 }
 
-/// Rethumbnail for container format "ogg" (using lofty)
+/// Rethumbnail for container format "flac" (using lofty)
 #[inline]
+fn rethumbnail_flac(media: &Path, image: &Path, output: &Path) -> Result<(), crate::Error> {
+	trace!("Using lofty flac rethumbnail");
+
+	return lofty_common(media, image, output);
+}
+
+/// Vorbis comment key for a cover, see <https://wiki.xiph.org/VorbisComment#Cover_art>
+const METADATA_BLOCK_PICTURE: &str = "METADATA_BLOCK_PICTURE";
+
+/// Rethumbnail for container format "ogg" (like opus and vorbis)
+///
+/// ffmpeg does not support embedding a image stream into a ogg file, so the image is converted to a FLAC picture block
+/// and added as a base64 "METADATA_BLOCK_PICTURE" vorbis comment, which is what players read covers from in ogg files
 fn rethumbnail_ogg(media: &Path, image: &Path, output: &Path) -> Result<(), crate::Error> {
-	trace!("Using lofty ogg rethumbnail");
+	trace!("Using ffmpeg ogg rethumbnail");
 
-	// ffmpeg somehow does not support embedding a mjpeg to a ogg/opus file, so we have to use lofty
+	let image_data = std::fs::read(image).attach_path_err(image)?;
+	let picture_block = flac_picture_block(&image_data).ok_or_else(|| {
+		return crate::Error::other(format!(
+			"Unsupported image format (only jpeg and png are supported) for \"{}\"",
+			image.display()
+		));
+	})?;
 
-	return lofty_common(media, image, output);
+	// the picture is too large for a command argument, so it is passed in as a ffmetadata file
+	let metadata_path = {
+		let mut path = output.as_os_str().to_owned();
+		path.push(".ffmetadata");
+
+		PathBuf::from(path)
+	};
+
+	let res = rethumbnail_ogg_with_metadata(media, &picture_block, &metadata_path, output);
+
+	if metadata_path.exists() {
+		if let Err(err) = std::fs::remove_file(&metadata_path) {
+			warn!("Removing the temporary metadata file failed: {}", err);
+		}
+	}
+
+	return res;
+}
+
+/// Apply `picture_block` to `media` as `output`, using `metadata_path` as the temporary ffmetadata file
+fn rethumbnail_ogg_with_metadata(
+	media: &Path,
+	picture_block: &[u8],
+	metadata_path: &Path,
+	output: &Path,
+) -> Result<(), crate::Error> {
+	// export the existing metadata of the audio stream (where ogg stores its metadata), so that it is kept
+	let mut cmd = crate::spawn::ffmpeg::base_ffmpeg_hidebanner(true);
+	cmd.arg("-i").arg(media);
+	cmd.args(["-map_metadata", "0:s:a:0", "-f", "ffmetadata"]);
+	cmd.arg(metadata_path);
+
+	re_thumbnail_with_command(cmd)?;
+
+	// ffmpeg reads existing covers as a separate stream instead of metadata, but remove them in case they were kept
+	let mut metadata = std::fs::read_to_string(metadata_path)
+		.attach_path_err(metadata_path)?
+		.lines()
+		.filter(|v| return !v.starts_with(METADATA_BLOCK_PICTURE))
+		.collect::<Vec<&str>>()
+		.join("\n");
+	metadata.push('\n');
+	metadata.push_str(METADATA_BLOCK_PICTURE);
+	metadata.push('=');
+	metadata.push_str(&escape_ffmetadata(&data_encoding::BASE64.encode(picture_block)));
+	metadata.push('\n');
+
+	File::create(metadata_path)
+		.and_then(|mut v| return v.write_all(metadata.as_bytes()))
+		.attach_path_err(metadata_path)?;
+
+	let mut cmd = crate::spawn::ffmpeg::base_ffmpeg_hidebanner(true);
+	cmd.arg("-i").arg(media); // set media file as input "0"
+	cmd.arg("-i").arg(metadata_path); // set metadata file as input "1"
+	cmd.args([
+		"-map",
+		"0:a", // only map the audio, because existing covers are read as a (unsupported) video stream
+		"-map_metadata:s:a:0",
+		"1:g", // set the metadata of the audio stream from the metadata file
+		"-c",
+		"copy", // copy everything instead of re-encoding
+	]);
+
+	cmd.arg(output); // set output path
+
+	return re_thumbnail_with_command(cmd);
+}
+
+/// Escape a value for a ffmpeg ffmetadata file, where "=", ";", "#", "\\" and new-lines have to be escaped
+fn escape_ffmetadata(value: &str) -> String {
+	let mut escaped = String::with_capacity(value.len());
+
+	for c in value.chars() {
+		if matches!(c, '=' | ';' | '#' | '\\' | '\n') {
+			escaped.push('\\');
+		}
+
+		escaped.push(c);
+	}
+
+	return escaped;
+}
+
+/// Basic information about a image, for [`flac_picture_block`]
+#[derive(Debug, PartialEq)]
+struct ImageInfo {
+	/// The mime type of the image
+	mime:   &'static str,
+	/// The width in pixels
+	width:  u32,
+	/// The height in pixels
+	height: u32,
+	/// The bits per pixel
+	depth:  u32,
+}
+
+/// Read the [`ImageInfo`] from the header of a jpeg or png image
+/// Returns [`None`] if the image is not a jpeg or png or the header is invalid
+fn image_info(data: &[u8]) -> Option<ImageInfo> {
+	/// Read a big-endian u16 at `pos`
+	fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+		return data.get(pos..pos + 2).map(|v| return u16::from_be_bytes([v[0], v[1]]));
+	}
+	/// Read a big-endian u32 at `pos`
+	fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+		return data
+			.get(pos..pos + 4)
+			.map(|v| return u32::from_be_bytes([v[0], v[1], v[2], v[3]]));
+	}
+
+	// png, the first chunk is always "IHDR"
+	if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+		if data.get(12..16)? != b"IHDR" {
+			return None;
+		}
+
+		let bit_depth = u32::from(*data.get(24)?);
+		let channels = match data.get(25)? {
+			2 => 3,
+			4 => 2,
+			6 => 4,
+			// grayscale and indexed
+			_ => 1,
+		};
+
+		return Some(ImageInfo {
+			mime:   "image/png",
+			width:  read_u32(data, 16)?,
+			height: read_u32(data, 20)?,
+			depth:  bit_depth * channels,
+		});
+	}
+
+	// jpeg, find the "SOF" (start of frame) segment, which contains the size
+	if data.starts_with(&[0xFF, 0xD8]) {
+		let mut pos = 2;
+
+		loop {
+			if *data.get(pos)? != 0xFF {
+				return None;
+			}
+
+			let marker = *data.get(pos + 1)?;
+
+			// "SOF0" to "SOF15", except "DHT", "JPG" and "DAC", which are in the same range
+			if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+				return Some(ImageInfo {
+					mime:   "image/jpeg",
+					width:  u32::from(read_u16(data, pos + 7)?),
+					height: u32::from(read_u16(data, pos + 5)?),
+					depth:  u32::from(*data.get(pos + 4)?) * u32::from(*data.get(pos + 9)?),
+				});
+			}
+
+			// fill bytes and markers without a length
+			if marker == 0xFF || marker == 0x01 || (0xD0..=0xD8).contains(&marker) {
+				pos += if marker == 0xFF { 1 } else { 2 };
+				continue;
+			}
+
+			pos += 2 + usize::from(read_u16(data, pos + 2)?);
+		}
+	}
+
+	return None;
+}
+
+/// Create a FLAC picture block (as used by "METADATA_BLOCK_PICTURE") with `data` as a front cover
+/// See <https://xiph.org/flac/format.html#metadata_block_picture>
+/// Returns [`None`] if the image is not a supported format, see [`image_info`]
+fn flac_picture_block(data: &[u8]) -> Option<Vec<u8>> {
+	let info = image_info(data)?;
+	let data_len = u32::try_from(data.len()).ok()?;
+
+	let mut block = Vec::with_capacity(data.len() + 64);
+	// picture type 3 is the front cover
+	block.extend_from_slice(&3u32.to_be_bytes());
+	block.extend_from_slice(&u32::try_from(info.mime.len()).ok()?.to_be_bytes());
+	block.extend_from_slice(info.mime.as_bytes());
+	// empty description
+	block.extend_from_slice(&0u32.to_be_bytes());
+	block.extend_from_slice(&info.width.to_be_bytes());
+	block.extend_from_slice(&info.height.to_be_bytes());
+	block.extend_from_slice(&info.depth.to_be_bytes());
+	// count of colors, only used for indexed images
+	block.extend_from_slice(&0u32.to_be_bytes());
+	block.extend_from_slice(&data_len.to_be_bytes());
+	block.extend_from_slice(data);
+
+	return Some(block);
 }
 
 /// Common code for lofty reading & writing tags & pictures
@@ -291,6 +682,12 @@ const IMAGE_EXTENSIONS: &[&str] = &["jpg", "png", "webp"];
 /// Find a image based on the input's media_path
 /// Returns [`Some`] with a path to the image found, otherwise [`None`] if none was found
 pub fn find_image(media_path: &Path) -> Result<Option<PathBuf>, crate::Error> {
+	return find_image_in(media_path, media_path.parent().unwrap_or(Path::new("")));
+}
+
+/// Find a image based on the input's media_path, but search in `image_dir` instead of next to the media
+/// Returns [`Some`] with a path to the image found, otherwise [`None`] if none was found
+pub fn find_image_in(media_path: &Path, image_dir: &Path) -> Result<Option<PathBuf>, crate::Error> {
 	if !media_path.exists() {
 		return Err(crate::Error::custom_ioerror_path(
 			std::io::ErrorKind::NotFound,
@@ -303,9 +700,13 @@ pub fn find_image(media_path: &Path) -> Result<Option<PathBuf>, crate::Error> {
 		return Err(crate::Error::not_a_file("media_path is not a file!", media_path));
 	}
 
+	let Some(file_name) = media_path.file_name() else {
+		return Ok(None);
+	};
+
 	// test for all extensions in IMAGE_EXTENSIONS
 	for test_ext in IMAGE_EXTENSIONS {
-		let mut image_path = media_path.to_owned();
+		let mut image_path = image_dir.join(file_name);
 		image_path.set_extension(test_ext);
 
 		// if file is found, return it
@@ -420,6 +821,30 @@ mod test {
 		return (testdir.as_ref().to_owned(), testdir);
 	}
 
+	mod thumbnail_extension {
+		use super::*;
+
+		#[test]
+		fn test_extensions() {
+			assert_eq!(
+				"webp",
+				thumbnail_extension("https://i.ytimg.com/vi_webp/ID/maxresdefault.webp")
+			);
+			assert_eq!(
+				"jpg",
+				thumbnail_extension("https://i.ytimg.com/vi/ID/hqdefault.jpg?sqp=abc&rs=def")
+			);
+			assert_eq!("png", thumbnail_extension("https://example.com/a.b/thumb.png#frag"));
+		}
+
+		#[test]
+		fn test_no_extension() {
+			assert_eq!("image", thumbnail_extension("https://example.com/a.b/thumb"));
+			assert_eq!("image", thumbnail_extension("https://example.com/thumb.no/t"));
+			assert_eq!("image", thumbnail_extension("https://example.com/thumb.toolong"));
+		}
+	}
+
 	mod re_thumbnail {
 		use super::*;
 
@@ -495,6 +920,191 @@ mod test {
 		}
 	}
 
+	mod find_image_in {
+		use super::*;
+
+		#[test]
+		fn test_find_image_in_other_dir() {
+			let (workdir, _tempdir) = create_dir("findimagein");
+
+			let image_dir = workdir.join("thumbnail");
+			std::fs::create_dir_all(&image_dir).expect("Expected create_dir_all to be successfull");
+
+			let media_path = workdir.join("somefile.mkv");
+			std::fs::File::create(&media_path).expect("Expected File::create to be successfull");
+			// a image next to the media should not be found
+			std::fs::File::create(workdir.join("somefile.jpg")).expect("Expected File::create to be successfull");
+
+			let image_path = image_dir.join("somefile.png");
+			std::fs::File::create(&image_path).expect("Expected File::create to be successfull");
+
+			let result = find_image_in(&media_path, &image_dir);
+
+			assert!(result.is_ok());
+
+			let result = result.expect("Expected is_ok assert to throw");
+			assert_eq!(Some(image_path), result);
+		}
+
+		#[test]
+		fn test_find_image_in_none() {
+			let (workdir, _tempdir) = create_dir("findimagein");
+
+			let media_path = workdir.join("somefile.mkv");
+			std::fs::File::create(&media_path).expect("Expected File::create to be successfull");
+
+			let result = find_image_in(&media_path, &workdir.join("thumbnail"));
+
+			assert!(result.is_ok());
+
+			let result = result.expect("Expected is_ok assert to throw");
+			assert_eq!(None, result);
+		}
+	}
+
+	mod re_thumbnail_many {
+		use super::*;
+
+		#[test]
+		fn test_skip_and_aggregate_errors() {
+			let (workdir, _tempdir) = create_dir("rethumbnailmany");
+
+			// media without a image, should be skipped
+			let no_image_path = workdir.join("noimage.mp3");
+			std::fs::File::create(&no_image_path).expect("Expected File::create to be successfull");
+			// media that does not exist, should fail
+			let missing_path = workdir.join("missing.mp3");
+
+			let items = [
+				ReThumbnailItem::new(&missing_path),
+				ReThumbnailItem::new(&no_image_path),
+				ReThumbnailItem::new(&missing_path),
+			];
+
+			let mut events = Vec::new();
+			let errors = re_thumbnail_many(&items, |imp| events.push(imp));
+
+			assert_eq!(
+				vec![
+					ReThumbnailProgress::Starting(3),
+					ReThumbnailProgress::SingleStarting(0, missing_path.clone()),
+					ReThumbnailProgress::SingleFailed(0),
+					ReThumbnailProgress::SingleStarting(1, no_image_path.clone()),
+					ReThumbnailProgress::SingleSkipped(1),
+					ReThumbnailProgress::SingleStarting(2, missing_path.clone()),
+					ReThumbnailProgress::SingleFailed(2),
+					ReThumbnailProgress::Finished(0, 2),
+				],
+				events
+			);
+
+			assert_eq!(
+				vec![0, 2],
+				errors.iter().map(|(index, _)| return *index).collect::<Vec<usize>>()
+			);
+			assert_eq!(
+				crate::Error::custom_ioerror_path(
+					std::io::ErrorKind::NotFound,
+					"media_path does not exist!",
+					&missing_path
+				),
+				errors[0].1
+			);
+		}
+
+		#[test]
+		fn test_empty() {
+			let mut events = Vec::new();
+			let errors = re_thumbnail_many(&[], |imp| events.push(imp));
+
+			assert!(errors.is_empty());
+			assert_eq!(
+				vec![ReThumbnailProgress::Starting(0), ReThumbnailProgress::Finished(0, 0)],
+				events
+			);
+		}
+	}
+
+	mod ogg_picture {
+		use super::*;
+
+		/// Test helper to create the header of a png image
+		fn png_header(width: u32, height: u32) -> Vec<u8> {
+			let mut data = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0DIHDR".to_vec();
+			data.extend_from_slice(&width.to_be_bytes());
+			data.extend_from_slice(&height.to_be_bytes());
+			// bit depth 8, color type 6 (rgba)
+			data.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+			return data;
+		}
+
+		#[test]
+		fn test_image_info_png() {
+			assert_eq!(
+				Some(ImageInfo {
+					mime:   "image/png",
+					width:  640,
+					height: 480,
+					depth:  32,
+				}),
+				image_info(&png_header(640, 480))
+			);
+		}
+
+		#[test]
+		fn test_image_info_jpeg() {
+			let data = [
+				0xFF, 0xD8, // SOI
+				0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, // APP0 with 2 bytes of data
+				0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0x68, 0x02, 0x80, 0x03, // SOF0, 8 bit, 360x640, 3 components
+			];
+
+			assert_eq!(
+				Some(ImageInfo {
+					mime:   "image/jpeg",
+					width:  640,
+					height: 360,
+					depth:  24,
+				}),
+				image_info(&data)
+			);
+		}
+
+		#[test]
+		fn test_image_info_unsupported() {
+			assert_eq!(None, image_info(b"RIFF\x00\x00\x00\x00WEBP"));
+			assert_eq!(None, image_info(&[0xFF, 0xD8, 0xFF]));
+			assert_eq!(None, image_info(&[]));
+		}
+
+		#[test]
+		fn test_flac_picture_block() {
+			let data = png_header(2, 3);
+			let block = flac_picture_block(&data).expect("Expected a picture block");
+
+			let mut expected = Vec::new();
+			expected.extend_from_slice(&[0, 0, 0, 3]);
+			expected.extend_from_slice(&[0, 0, 0, 9]);
+			expected.extend_from_slice(b"image/png");
+			expected.extend_from_slice(&[0, 0, 0, 0]);
+			expected.extend_from_slice(&[0, 0, 0, 2]);
+			expected.extend_from_slice(&[0, 0, 0, 3]);
+			expected.extend_from_slice(&[0, 0, 0, 32]);
+			expected.extend_from_slice(&[0, 0, 0, 0]);
+			expected.extend_from_slice(&u32::try_from(data.len()).unwrap().to_be_bytes());
+			expected.extend_from_slice(&data);
+
+			assert_eq!(expected, block);
+		}
+
+		#[test]
+		fn test_escape_ffmetadata() {
+			assert_eq!("abc+/12\\=\\=", escape_ffmetadata("abc+/12=="));
+			assert_eq!("a\\;b\\#c\\\\d\\\ne", escape_ffmetadata("a;b#c\\d\ne"));
+		}
+	}
+
 	mod convert_image_to_jpg {
 		use super::*;
 
@@ -0,0 +1,171 @@
+//! Module for searching media on a provider by search terms, without downloading anything
+
+use std::{
+	ffi::OsStr,
+	process::Stdio,
+	str::FromStr,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+	data::cache::media_info::MediaInfo,
+	error::IOErrorToError,
+	spawn::ytdl::base_ytdl,
+};
+
+/// Prefix used for the custom print of each search result
+const SEARCH_ENTRY_PREFIX: &str = "SEARCH_ENTRY";
+
+/// Providers youtube-dl can search on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchProvider {
+	/// Search on youtube ("ytsearch")
+	#[default]
+	Youtube,
+	/// Search on soundcloud ("scsearch")
+	Soundcloud,
+}
+
+impl SearchProvider {
+	/// Get the youtube-dl search prefix for this provider
+	#[must_use]
+	pub fn search_prefix(self) -> &'static str {
+		return match self {
+			SearchProvider::Youtube => "ytsearch",
+			SearchProvider::Soundcloud => "scsearch",
+		};
+	}
+}
+
+impl FromStr for SearchProvider {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		return match s.to_lowercase().as_str() {
+			"youtube" => Ok(SearchProvider::Youtube),
+			"soundcloud" => Ok(SearchProvider::Soundcloud),
+			_ => Err(crate::Error::other(format!(
+				"Unknown search provider \"{s}\", expected \"youtube\" or \"soundcloud\""
+			))),
+		};
+	}
+}
+
+/// A single search result, as listed by youtube-dl
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+	/// The Media information of the result, always has a title
+	pub media:    MediaInfo,
+	/// The URL to download the result with
+	pub url:      String,
+	/// The duration of the media as displayed by youtube-dl (like "3:45"), if known
+	pub duration: Option<String>,
+}
+
+/// Get the youtube-dl search "URL" for searching `count` results for `terms` on `provider`, like "ytsearch5:some song"
+#[must_use]
+pub fn search_query(provider: SearchProvider, terms: &str, count: usize) -> String {
+	return format!("{}{}:{}", provider.search_prefix(), count, terms.trim());
+}
+
+/// Search `count` results for `terms` on `provider` with the youtube-dl binary `ytdl_bin`, without downloading anything (using "--flat-playlist")
+pub fn search(
+	ytdl_bin: &OsStr,
+	provider: SearchProvider,
+	terms: &str,
+	count: usize,
+) -> Result<Vec<SearchResult>, crate::Error> {
+	let mut cmd = base_ytdl(ytdl_bin);
+	cmd.arg("--flat-playlist")
+		.arg("--print")
+		// "ie_key" is used, because in "--flat-playlist" mode "extractor" would be the search's extractor (like "youtube:search")
+		.arg(format!(
+			"{SEARCH_ENTRY_PREFIX} '%(ie_key,extractor_key)s' '%(id)s' '%(duration_string)s' %(url,webpage_url)s %(title)s"
+		))
+		.arg(search_query(provider, terms, count));
+
+	let command_output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("ytdl spawn")?
+		.wait_with_output()
+		.attach_location_err("ytdl wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"youtube-dl did not successfully exit! stderr: {}",
+			String::from_utf8_lossy(&command_output.stderr).trim()
+		)));
+	}
+
+	let as_string = String::from_utf8(command_output.stdout)?;
+
+	return Ok(as_string.lines().filter_map(parse_search_entry).collect());
+}
+
+/// Parse a single line printed by [`search`]
+/// Returns [`None`] if the line is not a search entry line
+fn parse_search_entry(input: &str) -> Option<SearchResult> {
+	/// Regex to get all information from a "SEARCH_ENTRY" line
+	/// cap1: provider, cap2: id, cap3: duration, cap4: url, cap5: title
+	static SEARCH_ENTRY_REGEX: Lazy<Regex> = Lazy::new(|| {
+		return Regex::new(r"(?m)^SEARCH_ENTRY '([^']+)' '([^']+)' '([^']*)' (\S+) (.*)$").unwrap();
+	});
+
+	let cap = SEARCH_ENTRY_REGEX.captures(input)?;
+	// youtube-dl prints "NA" for fields that are not available
+	let duration = Some(&cap[3])
+		.filter(|v| return !v.is_empty() && *v != "NA")
+		.map(ToOwned::to_owned);
+
+	return Some(SearchResult {
+		media: MediaInfo::new(&cap[2], &cap[1]).with_title(cap[5].trim()),
+		url: cap[4].to_owned(),
+		duration,
+	});
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_search_query() {
+		assert_eq!(
+			"ytsearch5:some song",
+			search_query(SearchProvider::Youtube, " some song ", 5)
+		);
+		assert_eq!("scsearch1:other", search_query(SearchProvider::Soundcloud, "other", 1));
+	}
+
+	#[test]
+	fn test_search_provider_from_str() {
+		assert_eq!(SearchProvider::Youtube, "YouTube".parse().unwrap());
+		assert_eq!(SearchProvider::Soundcloud, "soundcloud".parse().unwrap());
+		assert!("vimeo".parse::<SearchProvider>().is_err());
+	}
+
+	#[test]
+	fn test_parse_search_entry() {
+		assert_eq!(
+			Some(SearchResult {
+				media:    MediaInfo::new("-----------", "Youtube").with_title("Some Title Here"),
+				url:      "https://www.youtube.com/watch?v=-----------".to_owned(),
+				duration: Some("3:45".to_owned()),
+			}),
+			parse_search_entry(
+				"SEARCH_ENTRY 'Youtube' '-----------' '3:45' https://www.youtube.com/watch?v=----------- Some Title Here"
+			)
+		);
+		assert_eq!(
+			Some(None),
+			parse_search_entry("SEARCH_ENTRY 'Soundcloud' '1234' 'NA' https://api.soundcloud.com/tracks/1234 Title")
+				.map(|v| return v.duration)
+		);
+		assert_eq!(None, parse_search_entry("[youtube:search] Extracting URL"));
+	}
+}
@@ -79,7 +79,7 @@ pub fn migrate_and_connect<S: FnMut(ImportProgress)>(
 		let mut sqlite_path_reader = BufReader::new(File::open(&migrate_to_path).attach_path_err(&migrate_to_path)?);
 		return Ok(
 			match detect_archive_type(&mut sqlite_path_reader)? {
-				ArchiveType::Unknown => return Err(crate::Error::other(format!("Migrate-To Path already exists, but is of unknown type! Path: \"{}\"", migrate_to_path.to_string_lossy()))),
+				ArchiveType::Unknown | ArchiveType::FreeTube | ArchiveType::Zip => return Err(crate::Error::other(format!("Migrate-To Path already exists, but is of unknown type! Path: \"{}\"", migrate_to_path.to_string_lossy()))),
 				ArchiveType::JSON => return Err(crate::Error::other(format!("Migrate-To Path already exists and is a JSON archive, please rename it and retry the migration! Path: \"{}\"", migrate_to_path.to_string_lossy()))),
 				ArchiveType::SQLite => {
 					// this has to be done before, because the following ".into" call will move the value
@@ -94,7 +94,8 @@ pub fn migrate_and_connect<S: FnMut(ImportProgress)>(
 	let mut input_archive_reader = BufReader::new(File::open(archive_path).attach_path_err(archive_path)?);
 
 	return Ok(match detect_archive_type(&mut input_archive_reader)? {
-		ArchiveType::Unknown => {
+		// other applications exports can only be imported
+		ArchiveType::Unknown | ArchiveType::FreeTube | ArchiveType::Zip => {
 			return Err(crate::Error::other(
 				"Unknown Archive type to migrate, maybe try importing",
 			))
@@ -121,6 +122,75 @@ pub fn migrate_and_connect<S: FnMut(ImportProgress)>(
 	});
 }
 
+/// Status of the embedded migrations for a SQLite archive
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MigrationStatus {
+	/// Versions of all migrations already applied to the archive, in applied order
+	pub applied: Vec<String>,
+	/// Names of all embedded migrations not yet applied to the archive
+	pub pending: Vec<String>,
+}
+
+impl MigrationStatus {
+	/// Get the schema version of the archive, which is the newest applied migration version
+	/// Returns [`None`] if no migration has been applied yet
+	#[must_use]
+	pub fn schema_version(&self) -> Option<&str> {
+		return self.applied.iter().max().map(String::as_str);
+	}
+}
+
+/// Get the [`MigrationStatus`] of the SQLite archive at `sqlite_path`, without applying any migrations
+/// Errors if the path does not exist or is not a SQLite archive
+pub fn migration_status<P: AsRef<Path>>(sqlite_path: P) -> Result<MigrationStatus, crate::Error> {
+	use diesel_migrations::MigrationHarness;
+
+	let sqlite_path = sqlite_path.as_ref();
+
+	// dont create a new archive just to get the status
+	if !sqlite_path.is_file() {
+		return Err(crate::Error::not_a_file(
+			"Archive Path does not exist or is not a file!",
+			sqlite_path,
+		));
+	}
+
+	let mut sqlite_path_reader = BufReader::new(File::open(sqlite_path).attach_path_err(sqlite_path)?);
+	if detect_archive_type(&mut sqlite_path_reader)? != ArchiveType::SQLite {
+		return Err(crate::Error::other(format!(
+			"Archive is not a SQLite archive, it will be migrated on the next connect! Path: \"{}\"",
+			sqlite_path.to_string_lossy()
+		)));
+	}
+
+	let mut connection = match sqlite_path.to_str() {
+		Some(path) => SqliteConnection::establish(path)?,
+		None => {
+			return Err(crate::Error::other(format!(
+				"SQLite only accepts UTF-8 Paths, and given path failed to be converted to a string without being lossy, Path (converted lossy): \"{}\"",
+				sqlite_path.to_string_lossy()
+			)))
+		},
+	};
+
+	let map_err = |err| return crate::Error::other(format!("Getting SQL Migrations Errored! Error:\n{err}"));
+
+	let applied = connection
+		.applied_migrations()
+		.map_err(map_err)?
+		.iter()
+		.map(ToString::to_string)
+		.collect();
+	let pending = connection
+		.pending_migrations(MIGRATIONS)
+		.map_err(map_err)?
+		.iter()
+		.map(|v| return v.name().to_string())
+		.collect();
+
+	return Ok(MigrationStatus { applied, pending });
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
@@ -471,4 +541,82 @@ mod test {
 			assert_eq!(0, pgcounter.read().expect("read failed").len());
 		}
 	}
+
+	mod migration_status {
+		use super::*;
+		use diesel::migration::MigrationSource;
+
+		#[test]
+		fn test_all_applied() {
+			let testdir = TempBuilder::new()
+				.prefix("ytdl-test-sqliteMigrationStatus-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let path = testdir.as_ref().join("sqlite.db");
+
+			{
+				// create database file with all migrations applied
+				assert!(sqlite_connect(&path).is_ok());
+			}
+
+			let res = migration_status(&path).expect("Expected migration_status to be ok");
+
+			let embedded_count = MigrationSource::<diesel::sqlite::Sqlite>::migrations(&MIGRATIONS)
+				.expect("Expected embedded migrations to be readable")
+				.len();
+			assert_eq!(embedded_count, res.applied.len());
+			assert!(res.pending.is_empty());
+			assert!(res.schema_version().is_some());
+		}
+
+		#[test]
+		fn test_none_applied() {
+			let testdir = TempBuilder::new()
+				.prefix("ytdl-test-sqliteMigrationStatus-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let path = testdir.as_ref().join("sqlite.db");
+
+			{
+				// create a non-empty database without applying any migrations
+				let mut connection =
+					SqliteConnection::establish(path.to_str().unwrap()).expect("Expected SQLite to successfully start");
+				diesel::sql_query("CREATE TABLE some_table (id INTEGER PRIMARY KEY)")
+					.execute(&mut connection)
+					.expect("Expected table to be created");
+			}
+
+			let res = migration_status(&path).expect("Expected migration_status to be ok");
+
+			assert!(res.applied.is_empty());
+			assert_eq!(
+				vec![
+					"0000-00-00-000000_init".to_owned(),
+					"2026-10-16-000000_labels".to_owned(),
+					"2026-10-16-000001_fingerprints".to_owned(),
+					"2026-10-16-000002_extractor".to_owned(),
+					"2026-10-16-000003_blacklist".to_owned(),
+					"2026-10-16-000004_info_json".to_owned(),
+					"2026-10-16-000005_sizes".to_owned(),
+					"2026-10-16-000006_content_hash".to_owned(),
+					"2026-10-16-000007_tombstones".to_owned()
+				],
+				res.pending
+			);
+			assert_eq!(None, res.schema_version());
+		}
+
+		#[test]
+		fn test_not_existing() {
+			let testdir = TempBuilder::new()
+				.prefix("ytdl-test-sqliteMigrationStatus-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let path = testdir.as_ref().join("sqlite.db");
+
+			assert!(migration_status(&path).is_err());
+			// the status should not create a new archive
+			assert!(!path.exists());
+		}
+	}
 }
@@ -0,0 +1,57 @@
+//! Module for test helpers which are shared between the tests of multiple modules
+
+use crate::{
+	data::{
+		sql_models::InsMedia,
+		sql_schema::media_archive,
+	},
+	main::archive::import::insert_insmedia,
+};
+use diesel::prelude::*;
+use tempfile::{
+	Builder as TempBuilder,
+	TempDir,
+};
+
+/// Test helper function to create a connection AND get a clean testing dir path
+pub fn create_connection() -> (SqliteConnection, TempDir) {
+	let testdir = TempBuilder::new()
+		.prefix("ytdl-test-archive-")
+		.tempdir()
+		.expect("Expected a temp dir to be created");
+	let path = testdir.as_ref().join("sqlite.db");
+
+	return (
+		crate::main::sql_utils::sqlite_connect(path).expect("Expected SQLite to successfully start"),
+		testdir,
+	);
+}
+
+/// Test helper to insert a media with [`insert_insmedia`] (which canonicalizes the provider) and get its "_id"
+pub fn insert_media(connection: &mut SqliteConnection, id: &str, provider: &str, title: &str) -> i64 {
+	let media = InsMedia::new(id, provider, title);
+	insert_insmedia(&media, connection).expect("Expected media to be inserted");
+
+	return media_row_id(connection, id, media.canonicalized().provider);
+}
+
+/// Test helper to insert a media and get its "_id"
+/// Does not canonicalize the provider, like older versions did not
+pub fn insert_media_raw(connection: &mut SqliteConnection, id: &str, provider: &str, title: &str) -> i64 {
+	diesel::insert_into(media_archive::table)
+		.values(InsMedia::new(id, provider, title))
+		.execute(connection)
+		.expect("Expected media to be inserted");
+
+	return media_row_id(connection, id, provider);
+}
+
+/// Get the "_id" of the media with `id` and `provider`
+fn media_row_id(connection: &mut SqliteConnection, id: &str, provider: &str) -> i64 {
+	return media_archive::table
+		.filter(media_archive::media_id.eq(id))
+		.filter(media_archive::provider.eq(provider))
+		.select(media_archive::_id)
+		.first::<i64>(connection)
+		.expect("Expected media to exist");
+}
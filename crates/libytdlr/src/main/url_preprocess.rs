@@ -0,0 +1,413 @@
+//! Module for preprocessing (normalizing / rewriting) input URLs before they are downloaded
+//!
+//! This helps having consistent URLs for the same media, like "youtu.be/ID" and "youtube.com/shorts/ID" both becoming "youtube.com/watch?v=ID"
+
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::spawn::curl::curl_resolve_redirect;
+
+/// Query parameters that are only used for tracking and are removed for all hosts
+const TRACKING_PARAMS: &[&str] = &["fbclid", "gclid", "igshid", "mc_cid", "mc_eid"];
+
+/// Query parameter prefixes that are only used for tracking and are removed for all hosts
+const TRACKING_PARAM_PREFIXES: &[&str] = &["utm_"];
+
+/// Query parameters that are only used for tracking, but only removed for the given host (and its sub-domains)
+const HOST_TRACKING_PARAMS: &[(&str, &[&str])] = &[
+	("youtube.com", &["si", "feature", "pp"]),
+	("soundcloud.com", &["si", "ref"]),
+];
+
+/// Hosts of known URL shorteners, which just redirect to the actual URL
+const REDIRECT_SHORTENERS: &[&str] = &[
+	"bit.ly",
+	"buff.ly",
+	"cutt.ly",
+	"goo.gl",
+	"is.gd",
+	"ow.ly",
+	"rebrand.ly",
+	"t.co",
+	"tinyurl.com",
+];
+
+/// Regex to get the host of a URL
+/// cap1: host (may include a port)
+static URL_HOST_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"^(?:[a-zA-Z][a-zA-Z0-9+.-]*://)?(?:[^@/?#]*@)?([^/?#]+)").unwrap();
+});
+
+/// Regex to get the scheme of a URL, also matches pseudo-URLs like "ytsearch5:terms"
+/// cap1: scheme
+static URL_SCHEME_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"^([a-zA-Z][a-zA-Z0-9+.-]*):").unwrap();
+});
+
+/// Regex for paths starting with a windows drive letter, like "C:\" or "C:/"
+static WINDOWS_DRIVE_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"^[a-zA-Z]:[\\/]").unwrap();
+});
+
+/// Regex for youtube URLs that only contain the id in the path, like "youtu.be/ID", "youtube.com/shorts/ID" or "youtube.com/live/ID"
+/// cap1: media id
+/// cap2: query (without "?"), may not exist
+/// cap3: fragment (with "#"), may not exist
+static YOUTUBE_PATH_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(
+		r"(?i)^(?:https?://)?(?:(?:www\.)?youtu\.be|(?:www\.|m\.|music\.)?youtube\.com/(?:shorts|live))/([\w-]+)/?(?:\?([^#]*))?(#.*)?$",
+	)
+	.unwrap();
+});
+
+/// Regex for youtube watch and playlist URLs on a non-default host, like "music.youtube.com" or "m.youtube.com"
+/// cap1: path with query
+static YOUTUBE_ALT_HOST_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?i)^(?:https?://)?(?:m|music)\.youtube\.com/((?:watch|playlist)\?.*)$").unwrap();
+});
+
+/// A user-defined regex rewrite for URLs, in the format "REGEX=>REPLACEMENT"
+#[derive(Debug, Clone)]
+pub struct UrlRewrite {
+	/// The regex to match in the URL
+	pub regex:       Regex,
+	/// The replacement for the match, may contain captures like "$1"
+	pub replacement: String,
+}
+
+impl UrlRewrite {
+	/// Apply the rewrite to `url`, returning `url` unchanged if the regex does not match
+	#[must_use]
+	pub fn apply(&self, url: &str) -> String {
+		return self.regex.replace(url, self.replacement.as_str()).into_owned();
+	}
+}
+
+// custom, because "Regex" does not implement "PartialEq"
+impl PartialEq for UrlRewrite {
+	fn eq(&self, other: &Self) -> bool {
+		return self.regex.as_str() == other.regex.as_str() && self.replacement == other.replacement;
+	}
+}
+
+impl FromStr for UrlRewrite {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (regex, replacement) = s
+			.split_once("=>")
+			.ok_or_else(|| return crate::Error::other(format!("Expected \"REGEX=>REPLACEMENT\", got \"{s}\"")))?;
+
+		let regex = Regex::new(regex)
+			.map_err(|err| return crate::Error::other(format!("Invalid URL rewrite regex \"{regex}\": {err}")))?;
+
+		return Ok(Self {
+			regex,
+			replacement: replacement.to_owned(),
+		});
+	}
+}
+
+/// The preprocessing to apply to input URLs, see [`UrlPreprocessor::process`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UrlPreprocessor {
+	/// Expand youtube URLs to canonical watch URLs and remove tracking parameters
+	pub normalize:         bool,
+	/// Resolve known URL shorteners to the URL they redirect to (requires "curl")
+	pub resolve_redirects: bool,
+	/// User-defined rewrites, applied in order after all other steps
+	pub rewrites:          Vec<UrlRewrite>,
+}
+
+impl UrlPreprocessor {
+	/// Apply all enabled steps to `url`
+	/// Steps that fail (like resolving a redirect) are logged and skipped
+	#[must_use]
+	pub fn process(&self, url: &str) -> String {
+		let mut url = url.trim().to_owned();
+
+		if self.resolve_redirects && is_redirect_shortener(&url) {
+			match curl_resolve_redirect(&url) {
+				Ok(resolved) => url = resolved,
+				Err(err) => warn!("Resolving redirect of \"{}\" failed: {}", url, err),
+			}
+		}
+
+		if self.normalize {
+			url = strip_tracking_params(&canonicalize_youtube(&url));
+		}
+
+		for rewrite in &self.rewrites {
+			url = rewrite.apply(&url);
+		}
+
+		return url;
+	}
+}
+
+/// Get whether `url` is obviously a local path instead of a URL, like "/music/file.mp3", "./file", "~/file" or "file:///file"
+fn is_local_path(url: &str) -> bool {
+	return url.starts_with(['/', '\\', '.', '~'])
+		|| WINDOWS_DRIVE_REGEX.is_match(url)
+		|| URL_SCHEME_REGEX
+			.captures(url)
+			.is_some_and(|cap| return cap[1].eq_ignore_ascii_case("file"));
+}
+
+/// Validate that `url` can be given to youtube-dl, which means it has a scheme (like "https://") and is not a local path
+/// Should be used after [`UrlPreprocessor::process`], because it may add a missing scheme (like for "youtu.be/ID")
+pub fn validate_url(url: &str) -> Result<(), crate::Error> {
+	if url.is_empty() {
+		return Err(crate::Error::other("URL is empty"));
+	}
+
+	if is_local_path(url) {
+		return Err(crate::Error::other(format!(
+			"\"{url}\" looks like a local path, not a URL"
+		)));
+	}
+
+	if !URL_SCHEME_REGEX.is_match(url) {
+		return Err(crate::Error::other(format!(
+			"\"{url}\" has no scheme, like \"https://\""
+		)));
+	}
+
+	return Ok(());
+}
+
+/// Get the lowercase host of `url`, without port
+fn url_host(url: &str) -> Option<String> {
+	let host = URL_HOST_REGEX.captures(url)?.get(1)?.as_str();
+	let host = host.split_once(':').map_or(host, |v| return v.0);
+
+	return Some(host.to_lowercase());
+}
+
+/// Get whether `host` is `domain` or a sub-domain of it
+fn host_matches(host: &str, domain: &str) -> bool {
+	return host == domain || host.strip_suffix(domain).is_some_and(|v| return v.ends_with('.'));
+}
+
+/// Get whether `url` is from a known URL shortener, see [`REDIRECT_SHORTENERS`]
+#[must_use]
+pub fn is_redirect_shortener(url: &str) -> bool {
+	return url_host(url).is_some_and(|host| {
+		return REDIRECT_SHORTENERS
+			.iter()
+			.any(|shortener| return host_matches(&host, shortener));
+	});
+}
+
+/// Expand youtube URLs to the canonical "https://www.youtube.com/watch?v=ID" (or ".../playlist?list=ID") form
+/// Other query parameters (like "list" or "t") are kept
+/// Returns `url` unchanged if it is not a youtube URL that needs expanding
+#[must_use]
+pub fn canonicalize_youtube(url: &str) -> String {
+	if let Some(cap) = YOUTUBE_PATH_ID_REGEX.captures(url) {
+		let mut canonical = format!("https://www.youtube.com/watch?v={}", &cap[1]);
+
+		if let Some(query) = cap.get(2).filter(|v| return !v.as_str().is_empty()) {
+			canonical.push('&');
+			canonical.push_str(query.as_str());
+		}
+
+		if let Some(fragment) = cap.get(3) {
+			canonical.push_str(fragment.as_str());
+		}
+
+		return canonical;
+	}
+
+	if let Some(cap) = YOUTUBE_ALT_HOST_REGEX.captures(url) {
+		return format!("https://www.youtube.com/{}", &cap[1]);
+	}
+
+	return url.to_owned();
+}
+
+/// Get whether the query parameter `key` is only used for tracking on `host`
+fn is_tracking_param(host: Option<&str>, key: &str) -> bool {
+	let key = key.to_lowercase();
+
+	if TRACKING_PARAMS.contains(&key.as_str()) || TRACKING_PARAM_PREFIXES.iter().any(|v| return key.starts_with(v)) {
+		return true;
+	}
+
+	let Some(host) = host else {
+		return false;
+	};
+
+	return HOST_TRACKING_PARAMS
+		.iter()
+		.any(|(domain, params)| return host_matches(host, domain) && params.contains(&key.as_str()));
+}
+
+/// Remove all known tracking query parameters from `url`, see [`TRACKING_PARAMS`] and [`HOST_TRACKING_PARAMS`]
+/// Returns `url` unchanged if it does not have a query
+#[must_use]
+pub fn strip_tracking_params(url: &str) -> String {
+	let (rest, fragment) = match url.split_once('#') {
+		Some((rest, fragment)) => (rest, Some(fragment)),
+		None => (url, None),
+	};
+
+	let Some((base, query)) = rest.split_once('?') else {
+		return url.to_owned();
+	};
+
+	let host = url_host(base);
+
+	let params: Vec<&str> = query
+		.split('&')
+		.filter(|param| {
+			let key = param.split_once('=').map_or(*param, |v| return v.0);
+
+			return !key.is_empty() && !is_tracking_param(host.as_deref(), key);
+		})
+		.collect();
+
+	let mut stripped = base.to_owned();
+
+	if !params.is_empty() {
+		stripped.push('?');
+		stripped.push_str(&params.join("&"));
+	}
+
+	if let Some(fragment) = fragment {
+		stripped.push('#');
+		stripped.push_str(fragment);
+	}
+
+	return stripped;
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_canonicalize_youtube() {
+		assert_eq!(
+			"https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+			canonicalize_youtube("https://youtu.be/dQw4w9WgXcQ")
+		);
+		assert_eq!(
+			"https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42&si=abc",
+			canonicalize_youtube("youtu.be/dQw4w9WgXcQ?t=42&si=abc")
+		);
+		assert_eq!(
+			"https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+			canonicalize_youtube("https://www.youtube.com/shorts/dQw4w9WgXcQ/")
+		);
+		assert_eq!(
+			"https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+			canonicalize_youtube("https://m.youtube.com/live/dQw4w9WgXcQ")
+		);
+		assert_eq!(
+			"https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PL1",
+			canonicalize_youtube("https://music.youtube.com/watch?v=dQw4w9WgXcQ&list=PL1")
+		);
+		assert_eq!(
+			"https://www.youtube.com/playlist?list=PL1",
+			canonicalize_youtube("https://music.youtube.com/playlist?list=PL1")
+		);
+		// already canonical or not youtube
+		assert_eq!(
+			"https://www.youtube.com/watch?v=dQw4w9WgXcQ",
+			canonicalize_youtube("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+		);
+		assert_eq!(
+			"https://music.youtube.com/browse/MPREb_1",
+			canonicalize_youtube("https://music.youtube.com/browse/MPREb_1")
+		);
+		assert_eq!(
+			"https://soundcloud.com/artist/song",
+			canonicalize_youtube("https://soundcloud.com/artist/song")
+		);
+	}
+
+	#[test]
+	fn test_strip_tracking_params() {
+		assert_eq!(
+			"https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42",
+			strip_tracking_params("https://www.youtube.com/watch?v=dQw4w9WgXcQ&si=abc&feature=share&t=42")
+		);
+		assert_eq!(
+			"https://soundcloud.com/artist/song",
+			strip_tracking_params("https://soundcloud.com/artist/song?si=abc&utm_source=clipboard&utm_medium=text")
+		);
+		assert_eq!(
+			"https://example.com/video?id=1#comments",
+			strip_tracking_params("https://example.com/video?fbclid=abc&id=1#comments")
+		);
+		// "si" is only tracking on known hosts
+		assert_eq!(
+			"https://example.com/video?si=1",
+			strip_tracking_params("https://example.com/video?si=1")
+		);
+		assert_eq!(
+			"https://example.com/video",
+			strip_tracking_params("https://example.com/video")
+		);
+	}
+
+	#[test]
+	fn test_validate_url() {
+		assert!(validate_url("https://www.youtube.com/watch?v=abc").is_ok());
+		assert!(validate_url("http://example.com").is_ok());
+		assert!(validate_url("ytsearch5:some terms").is_ok());
+
+		assert!(validate_url("").is_err());
+		assert!(validate_url("www.youtube.com/watch?v=abc").is_err());
+		assert!(validate_url("/music/file.mp3").is_err());
+		assert!(validate_url("./file.mp3").is_err());
+		assert!(validate_url("~/file.mp3").is_err());
+		assert!(validate_url("file:///music/file.mp3").is_err());
+		assert!(validate_url("C:\\music\\file.mp3").is_err());
+		assert!(validate_url("C:/music/file.mp3").is_err());
+	}
+
+	#[test]
+	fn test_is_redirect_shortener() {
+		assert!(is_redirect_shortener("https://bit.ly/abc"));
+		assert!(is_redirect_shortener("t.co/abc"));
+		assert!(!is_redirect_shortener("https://www.youtube.com/watch?v=abc"));
+		assert!(!is_redirect_shortener("https://notbit.ly/abc"));
+	}
+
+	#[test]
+	fn test_url_rewrite() {
+		let rewrite: UrlRewrite = r"^https://invidious\.example\.com/=>https://www.youtube.com/"
+			.parse()
+			.expect("Expected rewrite to parse");
+		assert_eq!(
+			"https://www.youtube.com/watch?v=abc",
+			rewrite.apply("https://invidious.example.com/watch?v=abc")
+		);
+		assert_eq!("https://example.com/", rewrite.apply("https://example.com/"));
+
+		assert!("no-separator".parse::<UrlRewrite>().is_err());
+		assert!("(invalid=>x".parse::<UrlRewrite>().is_err());
+	}
+
+	#[test]
+	fn test_process() {
+		let preprocessor = UrlPreprocessor {
+			normalize:         true,
+			resolve_redirects: false,
+			rewrites:          vec![r"watch\?v=(\w+)=>watch?v=$1&custom=1".parse().unwrap()],
+		};
+
+		assert_eq!(
+			"https://www.youtube.com/watch?v=abc&custom=1",
+			preprocessor.process(" https://youtu.be/abc?si=tracking ")
+		);
+		assert_eq!(
+			"https://youtu.be/abc?si=tracking",
+			UrlPreprocessor::default().process("https://youtu.be/abc?si=tracking")
+		);
+	}
+}
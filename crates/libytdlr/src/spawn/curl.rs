@@ -0,0 +1,145 @@
+//! Module that contains all logic for spawning the "curl" command
+use std::{
+	io::Write,
+	path::Path,
+	process::{
+		Command,
+		Output,
+		Stdio,
+	},
+};
+
+use crate::error::IOErrorToError;
+
+/// Maximal time in seconds a single curl request may take
+const CURL_MAX_TIME: &str = "15";
+
+/// Create a Command with basic curl options
+#[inline]
+#[must_use]
+pub fn base_curl() -> Command {
+	let mut cmd = Command::new("curl");
+	cmd.args(["--silent", "--show-error", "--max-time", CURL_MAX_TIME]);
+
+	return cmd;
+}
+
+/// Follow all redirects of `url` and return the final url
+pub fn curl_resolve_redirect(url: &str) -> Result<String, crate::Error> {
+	let mut cmd = base_curl();
+	// only request the headers, the final url is written after them
+	cmd.args(["--head", "--location", "--write-out", "\n%{url_effective}"]);
+	cmd.arg(url);
+
+	let command_output: Output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("curl spawn")?
+		.wait_with_output()
+		.attach_location_err("curl wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"curl did not successfully exit! Exit Status: {}\nOutput:\n{}",
+			command_output.status,
+			String::from_utf8_lossy(&command_output.stderr).trim()
+		)));
+	}
+
+	return parse_effective_url(&String::from_utf8(command_output.stdout)?);
+}
+
+/// POST `body` as JSON to `url`, erroring if the request failed or the server responded with a error status
+pub fn curl_post_json(url: &str, body: &str) -> Result<(), crate::Error> {
+	let mut cmd = base_curl();
+	cmd.args([
+		"--fail",
+		"--output",
+		"/dev/null",
+		"--header",
+		"Content-Type: application/json",
+	]);
+	// read the body from stdin, so that it does not show up in the process list
+	cmd.args(["--data-binary", "@-"]);
+	cmd.arg(url);
+
+	let mut child = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::null())
+		.stdin(Stdio::piped())
+		.spawn()
+		.attach_location_err("curl spawn")?;
+
+	// take stdin, so that it is closed once written
+	if let Some(mut stdin) = child.stdin.take() {
+		stdin.write_all(body.as_bytes()).attach_location_err("curl stdin")?;
+	}
+
+	let command_output: Output = child.wait_with_output().attach_location_err("curl wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"curl did not successfully exit! Exit Status: {}\nOutput:\n{}",
+			command_output.status,
+			String::from_utf8_lossy(&command_output.stderr).trim()
+		)));
+	}
+
+	return Ok(());
+}
+
+/// Download `url` into the file at `output`, following redirects and erroring if the server responded with a error status
+pub fn curl_download(url: &str, output: &Path) -> Result<(), crate::Error> {
+	let mut cmd = base_curl();
+	cmd.args(["--fail", "--location", "--output"]);
+	cmd.arg(output);
+	cmd.arg(url);
+
+	let command_output: Output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::null())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("curl spawn")?
+		.wait_with_output()
+		.attach_location_err("curl wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"curl did not successfully exit! Exit Status: {}\nOutput:\n{}",
+			command_output.status,
+			String::from_utf8_lossy(&command_output.stderr).trim()
+		)));
+	}
+
+	return Ok(());
+}
+
+/// Internal Function to get the effective url from the output of [`curl_resolve_redirect`], which is the last line
+fn parse_effective_url(input: &str) -> Result<String, crate::Error> {
+	return input
+		.lines()
+		.last()
+		.map(str::trim)
+		.filter(|v| return !v.is_empty())
+		.map(ToOwned::to_owned)
+		.ok_or_else(|| return crate::Error::no_captures("curl did not output a effective url"));
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_parse_effective_url() {
+		assert_eq!(
+			parse_effective_url(
+				"HTTP/2 301\r\nlocation: https://example.com/\r\n\r\nHTTP/2 200\r\ncontent-type: text/html\r\n\r\n\nhttps://example.com/"
+			),
+			Ok("https://example.com/".to_owned())
+		);
+		assert!(parse_effective_url("HTTP/2 200\r\n\r\n\n").is_err());
+	}
+}
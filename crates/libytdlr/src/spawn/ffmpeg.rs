@@ -1,5 +1,6 @@
 //! Module that contains all logic for spawning the "ffmpeg" command
 use std::{
+	collections::HashMap,
 	ffi::OsStr,
 	os::unix::process::ExitStatusExt,
 	process::{
@@ -14,11 +15,11 @@ use regex::Regex;
 
 use crate::error::IOErrorToError;
 
-/// Create a Command with basic ffmpeg options
+/// Create a Command with basic ffmpeg options, with the set [`super::priority::process_priority`]
 #[inline]
 #[must_use]
 pub fn base_ffmpeg(overwrite: bool) -> Command {
-	let mut cmd = Command::new("ffmpeg");
+	let mut cmd = super::priority::priority_command("ffmpeg");
 
 	if overwrite {
 		cmd.arg("-y"); // always overwrite output path
@@ -177,6 +178,112 @@ pub fn parse_format(input: &str) -> Result<Vec<&str>, crate::Error> {
 	return Ok(formats_vec);
 }
 
+/// Regex to parse the duration from "input #0" from ffmpeg output
+static FFMPEG_PARSE_DURATION: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?mi)^\s*duration: (\d+:\d{2}:\d{2}(?:\.\d+)?),").unwrap();
+});
+
+/// Parse the output from [ffmpeg_probe] to only get the duration for Input 0
+/// Returns the duration as a string in format `HH:MM:SS.ms`
+#[inline]
+pub fn parse_duration(input: &str) -> Result<&str, crate::Error> {
+	let duration = FFMPEG_PARSE_DURATION
+		.captures_iter(input)
+		.next()
+		.ok_or_else(|| return crate::Error::no_captures("FFMPEG Duration could not be determined (1)"))?
+		.get(1)
+		.ok_or_else(|| return crate::Error::no_captures("FFMPEG Duration could not be determined (2)"))?;
+
+	return Ok(duration.as_str());
+}
+
+/// Convert a duration from [parse_duration] (format `HH:MM:SS.ms`) into whole seconds, rounded
+/// Returns [`None`] if the duration is not in the expected format
+#[must_use]
+pub fn duration_to_secs(duration: &str) -> Option<u32> {
+	let mut parts = duration.splitn(3, ':');
+	let hours: u32 = parts.next()?.parse().ok()?;
+	let minutes: u32 = parts.next()?.parse().ok()?;
+	let seconds: f64 = parts.next()?.parse().ok()?;
+
+	if !seconds.is_finite() || seconds < 0.0 {
+		return None;
+	}
+
+	// casting is safe, because the seconds part is always below 60
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+	let seconds = seconds.round() as u32;
+
+	return hours.checked_mul(3600)?.checked_add(minutes * 60)?.checked_add(seconds);
+}
+
+/// Regex to parse the codec of every stream of "input #0" from ffmpeg output
+/// cap1: codec name (like "vp9" or "opus")
+static FFMPEG_PARSE_STREAM_CODEC: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?mi)^\s*stream #0:\d+(?:\[\w+\])?(?:\([^)]*\))?: \w+: (\w+)").unwrap();
+});
+
+/// Parse the output from [ffmpeg_probe] to get the codec of every stream of Input 0, in stream order
+/// Attachments without a known codec are returned as "none"
+#[inline]
+pub fn parse_stream_codecs(input: &str) -> Vec<&str> {
+	return FFMPEG_PARSE_STREAM_CODEC
+		.captures_iter(input)
+		.filter_map(|v| return v.get(1))
+		.map(|v| return v.as_str())
+		.collect();
+}
+
+/// Parse the output from [ffmpeg_probe] to get the metadata (tags) of Input 0, with lowercased keys
+/// This includes the metadata of the container and of all streams (some containers, like "ogg", store the tags in the streams),
+/// if a key exists multiple times, the first value is used
+pub fn parse_metadata(input: &str) -> HashMap<String, String> {
+	let mut metadata = HashMap::new();
+	// the indentation of the current "Metadata:" line, if in a metadata block
+	let mut block_indent: Option<usize> = None;
+
+	for line in input.lines().skip_while(|v| return !v.starts_with("Input #0")).skip(1) {
+		// only Input 0 is parsed
+		if line.starts_with("Input #") || line.starts_with("Output #") {
+			break;
+		}
+
+		let indent = line.len() - line.trim_start().len();
+		let trimmed = line.trim();
+
+		if trimmed == "Metadata:" {
+			block_indent = Some(indent);
+
+			continue;
+		}
+
+		match block_indent {
+			Some(block) if indent > block => (),
+			_ => {
+				block_indent = None;
+
+				continue;
+			},
+		}
+
+		let Some((key, value)) = trimmed.split_once(':') else {
+			continue;
+		};
+		let key = key.trim();
+
+		// continuation lines of multi-line values have a empty key
+		if key.is_empty() {
+			continue;
+		}
+
+		metadata
+			.entry(key.to_lowercase())
+			.or_insert_with(|| return value.trim().to_owned());
+	}
+
+	return metadata;
+}
+
 #[cfg(test)]
 mod test {
 	use super::ffmpeg_version;
@@ -277,6 +384,120 @@ Stream #0:0: Audio: mp3, 48000 Hz, stereo, fltp, 128 kb/s
 		assert_eq!(super::parse_format(ffmpeg_output_mp3), Ok(vec!["mp3"]));
 	}
 
+	#[test]
+	pub fn test_parse_duration_invalid_input() {
+		assert_eq!(
+			super::parse_duration("hello"),
+			Err(crate::Error::no_captures("FFMPEG Duration could not be determined (1)"))
+		);
+	}
+
+	#[test]
+	pub fn test_parse_duration_valid_static_input() {
+		let ffmpeg_output_mp3 = r#"Input #0, mp3, from 'testep1.mp3':
+Metadata:
+	title           : Some Title
+	encoder         : Lavf59.27.100
+Duration: 00:00:01.03, start: 0.023021, bitrate: 147 kb/s
+Stream #0:0: Audio: mp3, 48000 Hz, stereo, fltp, 128 kb/s
+"#;
+
+		assert_eq!(super::parse_duration(ffmpeg_output_mp3), Ok("00:00:01.03"));
+
+		let ffmpeg_output_mkv = r#"Input #0, matroska,webm, from 'test.mkv':
+	Metadata:
+	title           : Some Title
+	Duration: 00:03:00.00, start: -0.007000, bitrate: 1371 kb/s
+"#;
+
+		assert_eq!(super::parse_duration(ffmpeg_output_mkv), Ok("00:03:00.00"));
+	}
+
+	#[test]
+	pub fn test_duration_to_secs() {
+		assert_eq!(Some(1), super::duration_to_secs("00:00:01.03"));
+		assert_eq!(Some(180), super::duration_to_secs("00:03:00.00"));
+		assert_eq!(Some(3723), super::duration_to_secs("01:02:02.50"));
+		assert_eq!(None, super::duration_to_secs("hello"));
+		assert_eq!(None, super::duration_to_secs("00:03"));
+	}
+
+	#[test]
+	pub fn test_parse_metadata() {
+		assert!(super::parse_metadata("hello").is_empty());
+
+		let ffmpeg_output_mp3 = r#"Input #0, mp3, from 'test.mp3':
+  Metadata:
+    title           : Some: Title
+    ARTIST          : Some Artist
+    album           : Some Album
+    comment         : first line
+                    : second line
+  Duration: 00:03:00.00, start: 0.025057, bitrate: 131 kb/s
+  Stream #0:0: Audio: mp3, 44100 Hz, stereo, fltp, 128 kb/s
+  Stream #0:1: Video: mjpeg (Baseline), yuvj420p(pc, bt470bg/unknown/unknown), 1280x720, 90k tbr, 90k tbn (attached pic)
+    Metadata:
+      title           : Cover (front)
+      comment         : Cover (front)
+"#;
+
+		let metadata = super::parse_metadata(ffmpeg_output_mp3);
+
+		assert_eq!(Some("Some: Title"), metadata.get("title").map(String::as_str));
+		assert_eq!(Some("Some Artist"), metadata.get("artist").map(String::as_str));
+		assert_eq!(Some("Some Album"), metadata.get("album").map(String::as_str));
+		assert_eq!(Some("first line"), metadata.get("comment").map(String::as_str));
+		assert_eq!(None, metadata.get("duration"));
+
+		let ffmpeg_output_opus = r#"Input #0, ogg, from 'test.opus':
+  Duration: 00:03:00.00, start: 0.007500, bitrate: 131 kb/s
+  Stream #0:0(eng): Audio: opus, 48000 Hz, stereo, fltp
+    Metadata:
+      ARTIST          : Stream Artist
+      ALBUM           : Stream Album
+Input #1, ogg, from 'other.opus':
+  Metadata:
+    title           : Other
+"#;
+
+		let metadata = super::parse_metadata(ffmpeg_output_opus);
+
+		assert_eq!(Some("Stream Artist"), metadata.get("artist").map(String::as_str));
+		assert_eq!(Some("Stream Album"), metadata.get("album").map(String::as_str));
+		assert_eq!(None, metadata.get("title"));
+	}
+
+	#[test]
+	pub fn test_parse_stream_codecs() {
+		assert!(super::parse_stream_codecs("hello").is_empty());
+
+		let ffmpeg_output_webm = r#"Input #0, matroska,webm, from 'test.webm':
+  Metadata:
+    ENCODER         : Lavf60.16.100
+  Duration: 00:03:00.00, start: -0.007000, bitrate: 1371 kb/s
+  Stream #0:0(eng): Video: vp9 (Profile 0), yuv420p(tv, bt709), 1920x1080, SAR 1:1 DAR 16:9, 30 fps, 30 tbr, 1k tbn (default)
+  Stream #0:1(eng): Audio: opus, 48000 Hz, stereo, fltp (default)
+  Stream #0:2: Attachment: none
+"#;
+
+		assert_eq!(
+			vec!["vp9", "opus", "none"],
+			super::parse_stream_codecs(ffmpeg_output_webm)
+		);
+
+		let ffmpeg_output_mp4 = r#"Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'test.mp4':
+  Duration: 00:00:10.00, start: 0.000000, bitrate: 500 kb/s
+  Stream #0:0[0x1](und): Video: h264 (High) (avc1 / 0x31637661), yuv420p(tv, bt709, progressive), 1280x720, 400 kb/s, 30 fps (default)
+  Stream #0:1[0x2](und): Audio: aac (LC) (mp4a / 0x6134706D), 44100 Hz, stereo, fltp, 128 kb/s (default)
+  Stream #0:2: Video: mjpeg (Baseline), yuvj420p(pc, bt470bg/unknown/unknown), 1280x720, 90k tbr, 90k tbn (attached pic)
+"#;
+
+		assert_eq!(
+			vec!["h264", "aac", "mjpeg"],
+			super::parse_stream_codecs(ffmpeg_output_mp4)
+		);
+	}
+
 	#[test]
 	#[ignore = "CI Install not present currently"]
 	pub fn test_ffmpeg_spawn() {
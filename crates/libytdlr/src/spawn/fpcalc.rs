@@ -0,0 +1,234 @@
+//! Module that contains all logic for spawning the "fpcalc" command (from chromaprint)
+use std::{
+	ffi::OsStr,
+	process::{
+		Command,
+		Output,
+		Stdio,
+	},
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::error::IOErrorToError;
+
+/// Create a Command with basic fpcalc options
+#[inline]
+#[must_use]
+pub fn base_fpcalc() -> Command {
+	return Command::new("fpcalc");
+}
+
+/// Regex to parse the version from a "fpcalc -version" output
+/// cap1: version
+static FPCALC_VERSION_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?mi)^fpcalc version ([a-z0-9.-]+)").unwrap();
+});
+
+/// Test if fpcalc is installed and reachable and return the version found.
+///
+/// This function is not automatically called in the library, it is recommended to run this in any binary trying to use fingerprinting.
+pub fn require_fpcalc_installed() -> Result<String, crate::Error> {
+	return match fpcalc_version() {
+		Ok(v) => Ok(v),
+		Err(err) => {
+			log::error!("Could not start or find fpcalc! Error: {}", err);
+
+			return Err(crate::Error::custom_ioerror_location(
+				std::io::ErrorKind::NotFound,
+				"fpcalc Version could not be determined, is it (chromaprint) installed and reachable?",
+				"fpcalc in PATH",
+			));
+		},
+	};
+}
+
+/// Get Version of `fpcalc`
+#[inline]
+pub fn fpcalc_version() -> Result<String, crate::Error> {
+	let mut cmd = base_fpcalc();
+	cmd.arg("-version");
+
+	let command_output: Output = cmd
+		.stderr(Stdio::null())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("fpcalc spawn")?
+		.wait_with_output()
+		.attach_location_err("fpcalc wait_with_output")?;
+
+	let as_string = String::from_utf8(command_output.stdout)?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"fpcalc did not successfully exit! Exit Status: {}",
+			command_output.status
+		)));
+	}
+
+	return fpcalc_parse_version(&as_string);
+}
+
+/// Internal Function to parse the input to a fpcalc version with regex
+#[inline]
+fn fpcalc_parse_version(input: &str) -> Result<String, crate::Error> {
+	return Ok(FPCALC_VERSION_REGEX
+		.captures_iter(input)
+		.next()
+		.ok_or_else(|| return crate::Error::no_captures("fpcalc Version could not be determined"))?[1]
+		.to_owned());
+}
+
+/// A Chromaprint (AcoustID) audio fingerprint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+	/// The Duration of the fingerprinted audio in seconds
+	pub duration:    u32,
+	/// The raw fingerprint values
+	pub fingerprint: Vec<u32>,
+}
+
+impl Fingerprint {
+	/// Convert the raw fingerprint to a comma-separated string, like "fpcalc -raw" outputs
+	#[must_use]
+	pub fn raw_to_string(&self) -> String {
+		return self
+			.fingerprint
+			.iter()
+			.map(ToString::to_string)
+			.collect::<Vec<String>>()
+			.join(",");
+	}
+
+	/// Parse a comma-separated raw fingerprint string, like "fpcalc -raw" outputs
+	pub fn parse_raw(input: &str) -> Result<Vec<u32>, crate::Error> {
+		return input
+			.trim()
+			.split(',')
+			.map(|v| {
+				return v
+					.trim()
+					.parse::<u32>()
+					.map_err(|err| return crate::Error::other(format!("Invalid Fingerprint value \"{v}\": {err}")));
+			})
+			.collect();
+	}
+}
+
+/// Calculate the raw fingerprint of the audio at `file`
+pub fn fpcalc_fingerprint<P: AsRef<OsStr>>(file: P) -> Result<Fingerprint, crate::Error> {
+	let mut cmd = base_fpcalc();
+	cmd.arg("-raw");
+	cmd.arg(file);
+
+	let command_output: Output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("fpcalc spawn")?
+		.wait_with_output()
+		.attach_location_err("fpcalc wait_with_output")?;
+
+	if !command_output.status.success() {
+		let stderr = String::from_utf8_lossy(&command_output.stderr);
+		debug!("fpcalc STDERR Output: {}", stderr);
+
+		return Err(crate::Error::command_unsuccessful(format!(
+			"fpcalc did not successfully exit! Exit Status: {}\nOutput:\n{}",
+			command_output.status,
+			stderr.trim()
+		)));
+	}
+
+	return parse_fpcalc_output(&String::from_utf8(command_output.stdout)?);
+}
+
+/// Internal Function to parse the "fpcalc -raw" output to a [`Fingerprint`]
+fn parse_fpcalc_output(input: &str) -> Result<Fingerprint, crate::Error> {
+	let mut duration = None;
+	let mut fingerprint = None;
+
+	for line in input.lines() {
+		if let Some(value) = line.strip_prefix("DURATION=") {
+			// fpcalc may output the duration as a float in newer versions
+			duration = Some(
+				value
+					.trim()
+					.parse::<f64>()
+					.map_err(|err| return crate::Error::other(format!("Invalid fpcalc Duration \"{value}\": {err}")))?
+					.round() as u32,
+			);
+		} else if let Some(value) = line.strip_prefix("FINGERPRINT=") {
+			fingerprint = Some(Fingerprint::parse_raw(value)?);
+		}
+	}
+
+	let (Some(duration), Some(fingerprint)) = (duration, fingerprint) else {
+		return Err(crate::Error::no_captures(
+			"fpcalc output did not contain a DURATION and FINGERPRINT",
+		));
+	};
+
+	return Ok(Fingerprint { duration, fingerprint });
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_fpcalc_parse_version() {
+		assert_eq!(
+			fpcalc_parse_version("fpcalc version 1.5.1 (FFmpeg Lavc60.3.100 Lavf60.3.100 SwR4.10.100)\n"),
+			Ok("1.5.1".to_owned())
+		);
+		assert_eq!(
+			fpcalc_parse_version("hello"),
+			Err(crate::Error::no_captures("fpcalc Version could not be determined"))
+		);
+	}
+
+	#[test]
+	fn test_parse_fpcalc_output() {
+		assert_eq!(
+			parse_fpcalc_output("DURATION=183\nFINGERPRINT=3891254723,3891254721,4294967295\n"),
+			Ok(Fingerprint {
+				duration:    183,
+				fingerprint: vec![3891254723, 3891254721, 4294967295],
+			})
+		);
+		assert_eq!(
+			parse_fpcalc_output("FILE=/some/file.mp3\nDURATION=60.54\nFINGERPRINT=1,2\n").map(|v| return v.duration),
+			Ok(61)
+		);
+	}
+
+	#[test]
+	fn test_parse_fpcalc_output_invalid() {
+		assert!(parse_fpcalc_output("DURATION=183\n").is_err());
+		assert!(parse_fpcalc_output("FINGERPRINT=1,2\n").is_err());
+		assert!(parse_fpcalc_output("DURATION=183\nFINGERPRINT=1,a,2\n").is_err());
+	}
+
+	#[test]
+	fn test_raw_roundtrip() {
+		let fingerprint = Fingerprint {
+			duration:    1,
+			fingerprint: vec![1, 20, 300],
+		};
+		assert_eq!("1,20,300", fingerprint.raw_to_string());
+		assert_eq!(
+			fingerprint.fingerprint,
+			Fingerprint::parse_raw(&fingerprint.raw_to_string()).unwrap()
+		);
+	}
+
+	#[test]
+	#[ignore = "CI Install not present currently"]
+	fn test_fpcalc_spawn() {
+		assert!(fpcalc_version().is_ok());
+	}
+}
@@ -1,5 +1,8 @@
 //! index of spawning commands
 
+pub mod curl;
 pub mod editor;
 pub mod ffmpeg;
+pub mod fpcalc;
+pub mod priority;
 pub mod ytdl;
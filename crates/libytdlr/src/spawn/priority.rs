@@ -0,0 +1,185 @@
+//! Module for running spawned processes (like ytdl and ffmpeg) with a lower CPU / IO priority
+use std::{
+	ffi::{
+		OsStr,
+		OsString,
+	},
+	process::Command,
+	str::FromStr,
+};
+
+use once_cell::sync::OnceCell;
+
+/// IO Scheduling classes that can be set with "ionice"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoniceClass {
+	/// Only get IO time when no other process needs it
+	Idle,
+	/// Normal scheduling, but with the lowest priority level
+	BestEffort,
+}
+
+impl IoniceClass {
+	/// Get the arguments for "ionice" to set this class
+	fn ionice_args(self) -> &'static [&'static str] {
+		return match self {
+			IoniceClass::Idle => &["-c", "3"],
+			IoniceClass::BestEffort => &["-c", "2", "-n", "7"],
+		};
+	}
+}
+
+impl FromStr for IoniceClass {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		return match s.to_lowercase().as_str() {
+			"idle" => Ok(IoniceClass::Idle),
+			"best-effort" => Ok(IoniceClass::BestEffort),
+			_ => Err(crate::Error::other(format!(
+				"Unknown ionice class \"{s}\", expected \"idle\" or \"best-effort\""
+			))),
+		};
+	}
+}
+
+/// Priority spawned processes should run with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProcessPriority {
+	/// Niceness to run with (-20 to 19, higher is lower priority), applied with "nice"
+	pub nice:   Option<i8>,
+	/// IO Scheduling class to run with, applied with "ionice" (only on linux)
+	pub ionice: Option<IoniceClass>,
+}
+
+/// The global priority for all spawned processes, see [`set_process_priority`]
+static PROCESS_PRIORITY: OnceCell<ProcessPriority> = OnceCell::new();
+
+/// Set the priority which ytdl and ffmpeg processes are spawned with
+/// Can only be set once, later calls are ignored
+pub fn set_process_priority(priority: ProcessPriority) {
+	if PROCESS_PRIORITY.set(priority).is_err() {
+		warn!("Process priority was already set, ignoring new value");
+	}
+}
+
+/// Get the priority which ytdl and ffmpeg processes are spawned with
+#[must_use]
+pub fn process_priority() -> ProcessPriority {
+	return PROCESS_PRIORITY.get().copied().unwrap_or_default();
+}
+
+/// Get the program and the arguments to use before the actual arguments, to run `program` with the global [`process_priority`]
+/// Example: `yt-dlp` with niceness 10 will return `("nice", ["-n", "10", "yt-dlp"])`
+#[must_use]
+pub fn priority_program<S: AsRef<OsStr>>(program: S) -> (OsString, Vec<OsString>) {
+	return wrap_program(&process_priority(), program.as_ref());
+}
+
+/// Create a new [`Command`] for `program`, which will run with the global [`process_priority`]
+#[must_use]
+pub fn priority_command<S: AsRef<OsStr>>(program: S) -> Command {
+	let (program, args) = priority_program(program);
+	let mut cmd = Command::new(program);
+	cmd.args(args);
+
+	return cmd;
+}
+
+/// Internal function to wrap `program` with "nice" and "ionice" as set in `priority`
+fn wrap_program(priority: &ProcessPriority, program: &OsStr) -> (OsString, Vec<OsString>) {
+	let mut parts: Vec<OsString> = Vec::new();
+
+	if let Some(nice) = priority.nice {
+		parts.extend(["nice".into(), "-n".into(), nice.to_string().into()]);
+	}
+
+	if let Some(ionice) = priority.ionice {
+		if cfg!(target_os = "linux") {
+			parts.push("ionice".into());
+			parts.extend(ionice.ionice_args().iter().map(OsString::from));
+		} else {
+			debug!("ionice is only supported on linux, ignoring");
+		}
+	}
+
+	parts.push(program.to_owned());
+
+	let program = parts.remove(0);
+
+	return (program, parts);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_wrap_program_none() {
+		assert_eq!(
+			(OsString::from("yt-dlp"), Vec::<OsString>::new()),
+			wrap_program(&ProcessPriority::default(), OsStr::new("yt-dlp"))
+		);
+	}
+
+	#[test]
+	fn test_wrap_program_nice() {
+		assert_eq!(
+			(
+				OsString::from("nice"),
+				vec![OsString::from("-n"), OsString::from("10"), OsString::from("ffmpeg")]
+			),
+			wrap_program(
+				&ProcessPriority {
+					nice:   Some(10),
+					ionice: None,
+				},
+				OsStr::new("ffmpeg")
+			)
+		);
+	}
+
+	#[test]
+	#[cfg(target_os = "linux")]
+	fn test_wrap_program_nice_ionice() {
+		assert_eq!(
+			(
+				OsString::from("nice"),
+				["-n", "19", "ionice", "-c", "3", "yt-dlp"]
+					.iter()
+					.map(OsString::from)
+					.collect::<Vec<_>>()
+			),
+			wrap_program(
+				&ProcessPriority {
+					nice:   Some(19),
+					ionice: Some(IoniceClass::Idle),
+				},
+				OsStr::new("yt-dlp")
+			)
+		);
+		assert_eq!(
+			(
+				OsString::from("ionice"),
+				["-c", "2", "-n", "7", "yt-dlp"]
+					.iter()
+					.map(OsString::from)
+					.collect::<Vec<_>>()
+			),
+			wrap_program(
+				&ProcessPriority {
+					nice:   None,
+					ionice: Some(IoniceClass::BestEffort),
+				},
+				OsStr::new("yt-dlp")
+			)
+		);
+	}
+
+	#[test]
+	fn test_ionice_class_from_str() {
+		assert_eq!(IoniceClass::Idle, "idle".parse().unwrap());
+		assert_eq!(IoniceClass::BestEffort, "Best-Effort".parse().unwrap());
+		assert!("realtime".parse::<IoniceClass>().is_err());
+	}
+}
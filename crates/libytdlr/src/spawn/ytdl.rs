@@ -1,8 +1,15 @@
 //! Module that contains all logic for spawning the "ytdl" command
-use std::process::{
-	Command,
-	Output,
-	Stdio,
+use std::{
+	collections::HashSet,
+	ffi::{
+		OsStr,
+		OsString,
+	},
+	process::{
+		Command,
+		Output,
+		Stdio,
+	},
 };
 
 use once_cell::sync::Lazy;
@@ -10,36 +17,64 @@ use regex::Regex;
 
 use crate::error::IOErrorToError;
 
-use super::ffmpeg::require_ffmpeg_installed;
-
 /// Binary name to spawn for the youtube-dl process
 pub const YTDL_BIN_NAME: &str = "yt-dlp";
 
-/// Create a new [YTDL_BIN_NAME] [Command] instance
+/// Binary names to try if [YTDL_BIN_NAME] could not be found and no binary has been explicitly given to [`require_ytdl_installed`]
+/// These are the names of the standalone yt-dlp release binaries
+pub const YTDL_BIN_ALTERNATIVES: &[&str] = &["yt-dlp_linux", "yt-dlp_macos", "yt-dlp_x86"];
+
+/// Create a new `bin` [Command] instance, with the set [`super::priority::process_priority`]
 #[inline]
 #[must_use]
-pub fn base_ytdl() -> Command {
-	return Command::new(YTDL_BIN_NAME);
+pub fn base_ytdl(bin: &OsStr) -> Command {
+	return super::priority::priority_command(bin);
 }
 
-/// Test if ytdl is installed and reachable, including required dependencies like ffmpeg and return the version found.
+/// Test if ytdl is installed and reachable and return the binary to use and the version found.
+/// `bin` is the binary to test, like a specific yt-dlp binary, one in a python venv or a mock youtube-dl for testing without network access.
+/// If `bin` is [`None`], [YTDL_BIN_NAME] is tested and if it could not be found, the [YTDL_BIN_ALTERNATIVES] are tried
+/// and the first working one is returned.
+/// Does not test for ffmpeg, use [`super::ffmpeg::require_ffmpeg_installed`] if features requiring ffmpeg are used.
 ///
 /// This function is not automatically called in the library, it is recommended to run this in any binary trying to run libytdlr.
-pub fn require_ytdl_installed() -> Result<String, crate::Error> {
-	require_ffmpeg_installed()?;
-
-	return match ytdl_version() {
-		Ok(v) => Ok(v),
-		Err(err) => {
-			log::error!("Could not start or find youtube-dl! Error: {}", err);
-
-			return Err(crate::Error::custom_ioerror_location(
-				std::io::ErrorKind::NotFound,
-				"Youtube-DL(p) Version could not be determined, is it installed and reachable?",
-				format!("{} in PATH", YTDL_BIN_NAME),
-			));
-		},
+pub fn require_ytdl_installed(bin: Option<&OsStr>) -> Result<(OsString, String), crate::Error> {
+	let (err, location) = if let Some(bin) = bin {
+		// only look for alternatives if the binary was not explicitly given
+		match ytdl_version(bin) {
+			Ok(v) => return Ok((bin.to_owned(), v)),
+			Err(err) => (err, format!("\"{}\"", bin.to_string_lossy())),
+		}
+	} else {
+		let err = match ytdl_version(OsStr::new(YTDL_BIN_NAME)) {
+			Ok(v) => return Ok((OsString::from(YTDL_BIN_NAME), v)),
+			Err(err) => err,
+		};
+
+		for alternative in YTDL_BIN_ALTERNATIVES {
+			if let Ok(version) = ytdl_version(OsStr::new(alternative)) {
+				info!(
+					"\"{}\" could not be found, using \"{}\" instead",
+					YTDL_BIN_NAME, alternative
+				);
+
+				return Ok((OsString::from(alternative), version));
+			}
+		}
+
+		(
+			err,
+			format!("{} (or {}) in PATH", YTDL_BIN_NAME, YTDL_BIN_ALTERNATIVES.join(", ")),
+		)
 	};
+
+	log::error!("Could not start or find youtube-dl! Error: {}", err);
+
+	return Err(crate::Error::custom_ioerror_location(
+		std::io::ErrorKind::NotFound,
+		"Youtube-DL(p) Version could not be determined, is it installed and reachable?",
+		location,
+	));
 }
 
 /// Regex to parse the version from a "youtube-dl --version" output
@@ -48,10 +83,9 @@ static YTDL_VERSION_REGEX: Lazy<Regex> = Lazy::new(|| {
 	return Regex::new(r"(?mi)^(\d{4}\.\d{1,2}\.\d{1,2})").unwrap();
 });
 
-/// Get Version of `ffmpeg`
-#[inline]
-pub fn ytdl_version() -> Result<String, crate::Error> {
-	let mut cmd = base_ytdl();
+/// Get Version of the youtube-dl binary `bin`
+pub fn ytdl_version(bin: &OsStr) -> Result<String, crate::Error> {
+	let mut cmd = base_ytdl(bin);
 	cmd.arg("--version");
 
 	let command_output: Output = cmd
@@ -72,6 +106,64 @@ pub fn ytdl_version() -> Result<String, crate::Error> {
 	return ytdl_parse_version(&as_string);
 }
 
+/// Run the self-update of the youtube-dl binary `bin` ("--update"), with the output shown directly
+/// Only standalone release binaries support self-updating, other installations (like via pip) will error
+pub fn ytdl_update(bin: &OsStr) -> Result<(), crate::Error> {
+	let mut cmd = base_ytdl(bin);
+	cmd.arg("--update");
+
+	let status = cmd
+		.stdin(Stdio::null())
+		.status()
+		.attach_location_err("ytdl update spawn")?;
+
+	if !status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"youtube-dl --update did not successfully exit ({status})"
+		)));
+	}
+
+	return Ok(());
+}
+
+/// Get the thumbnail URL of the single media at `url` from the youtube-dl binary `bin`, without downloading anything
+pub fn ytdl_thumbnail_url(bin: &OsStr, url: &str) -> Result<String, crate::Error> {
+	let mut cmd = base_ytdl(bin);
+	cmd.args(["--skip-download", "--no-playlist", "--print", "thumbnail"]);
+	cmd.arg(url);
+
+	let command_output: Output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("ytdl spawn")?
+		.wait_with_output()
+		.attach_location_err("ytdl wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"youtube-dl did not successfully exit! Exit Status: {}\nOutput:\n{}",
+			command_output.status,
+			String::from_utf8_lossy(&command_output.stderr).trim()
+		)));
+	}
+
+	return ytdl_parse_thumbnail_url(&String::from_utf8(command_output.stdout)?);
+}
+
+/// Internal Function to get the thumbnail url from the output of [`ytdl_thumbnail_url`], which is the last line
+/// youtube-dl prints "NA" when the media does not have a thumbnail
+fn ytdl_parse_thumbnail_url(input: &str) -> Result<String, crate::Error> {
+	return input
+		.lines()
+		.map(str::trim)
+		.rfind(|v| return !v.is_empty())
+		.filter(|v| return *v != "NA")
+		.map(ToOwned::to_owned)
+		.ok_or_else(|| return crate::Error::no_captures("youtube-dl did not output a thumbnail url"));
+}
+
 /// Internal Function to parse the input to a ytdl version with regex
 #[inline]
 fn ytdl_parse_version(input: &str) -> Result<String, crate::Error> {
@@ -93,8 +185,70 @@ pub fn ytdl_parse_version_naivedate(input: &str) -> Result<chrono::NaiveDate, cr
 	return Ok(date);
 }
 
+/// The options supported by the installed youtube-dl, as listed by "--help"
+/// Used to detect support for optional options, instead of youtube-dl failing with a "no such option" error
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct YtdlCapabilities {
+	/// All long options (like "--no-quiet") listed in the help
+	options: HashSet<String>,
+}
+
+impl YtdlCapabilities {
+	/// Parse the capabilities from a "youtube-dl --help" output
+	#[must_use]
+	pub fn from_help(input: &str) -> Self {
+		/// Regex to get the long options from a help line, like "    -f, --format FORMAT    Video format code"
+		/// cap1: long option
+		static HELP_OPTION_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"(?m)^\s+(?:-\w, )?(--[\w-]+)").unwrap();
+		});
+
+		return Self {
+			options: HELP_OPTION_REGEX
+				.captures_iter(input)
+				.map(|cap| return cap[1].to_owned())
+				.collect(),
+		};
+	}
+
+	/// Get whether the long option `option` (like "--no-quiet") is supported
+	/// A value (like in "--impersonate=chrome") is ignored
+	#[must_use]
+	pub fn supports(&self, option: &str) -> bool {
+		let option = option.split_once('=').map_or(option, |(option, _)| return option);
+
+		return self.options.contains(option);
+	}
+}
+
+/// Get the capabilities of the youtube-dl binary `bin`
+/// This spawns youtube-dl, so the result should be kept instead of calling this repeatedly
+pub fn ytdl_capabilities(bin: &OsStr) -> Result<YtdlCapabilities, crate::Error> {
+	let mut cmd = base_ytdl(bin);
+	cmd.arg("--help");
+
+	let command_output: Output = cmd
+		.stderr(Stdio::null())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("ytdl spawn")?
+		.wait_with_output()
+		.attach_location_err("ytdl wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(
+			"youtube-dl --help did not successfully exit!",
+		));
+	}
+
+	return Ok(YtdlCapabilities::from_help(&String::from_utf8(command_output.stdout)?));
+}
+
 #[cfg(test)]
 mod test {
+	use std::ffi::OsStr;
+
 	use chrono::NaiveDate;
 
 	use crate::spawn::ytdl::ytdl_parse_version_naivedate;
@@ -116,10 +270,55 @@ mod test {
 		assert_eq!(super::ytdl_parse_version(ytdl_output), Ok("2021.12.27".to_owned()));
 	}
 
+	#[test]
+	fn test_ytdl_parse_thumbnail_url() {
+		assert_eq!(
+			super::ytdl_parse_thumbnail_url("WARNING: something\nhttps://i.ytimg.com/vi_webp/ID/maxresdefault.webp\n"),
+			Ok("https://i.ytimg.com/vi_webp/ID/maxresdefault.webp".to_owned())
+		);
+		assert!(super::ytdl_parse_thumbnail_url("NA\n").is_err());
+		assert!(super::ytdl_parse_thumbnail_url("\n").is_err());
+	}
+
+	#[test]
+	fn test_ytdl_version_missing() {
+		assert!(ytdl_version(OsStr::new("/non-existing/yt-dlp")).is_err());
+	}
+
+	#[test]
+	fn test_require_ytdl_installed_missing() {
+		// an explicitly given binary does not fall back to the alternatives
+		assert!(super::require_ytdl_installed(Some(OsStr::new("/non-existing/yt-dlp"))).is_err());
+	}
+
 	#[test]
 	#[ignore = "CI Install not present currently"]
 	fn test_ytdl_spawn() {
-		assert!(ytdl_version().is_ok());
+		assert!(ytdl_version(OsStr::new(super::YTDL_BIN_NAME)).is_ok());
+	}
+
+	#[test]
+	fn test_capabilities_from_help() {
+		let help = "Usage: yt-dlp [OPTIONS] URL [URL...]
+
+Options:
+  General Options:
+    -h, --help                      Print this help text and exit
+    --no-quiet                      Deactivate quiet mode. (Default)
+    --impersonate CLIENT[:OS]       Client to impersonate for requests
+    -f, --format FORMAT             Video format code, see \"FORMAT SELECTION\"
+                                    for more details, like --download-sections
+";
+		let capabilities = super::YtdlCapabilities::from_help(help);
+
+		assert!(capabilities.supports("--help"));
+		assert!(capabilities.supports("--no-quiet"));
+		assert!(capabilities.supports("--impersonate"));
+		assert!(capabilities.supports("--impersonate=chrome"));
+		assert!(capabilities.supports("--format"));
+		// only mentioned in a description
+		assert!(!capabilities.supports("--download-sections"));
+		assert!(!capabilities.supports("--not-existing"));
 	}
 
 	#[test]
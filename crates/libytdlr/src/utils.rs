@@ -1,5 +1,8 @@
 //! Module for utility functions, that may be used in various other modules
 
+pub mod disk_space;
+pub mod url_ident;
+
 use std::path::{
 	Path,
 	PathBuf,
@@ -57,6 +60,79 @@ pub fn to_absolute<P: AsRef<Path>>(input: P) -> std::io::Result<PathBuf> {
 	return converted.absolutize().map(|v| return v.to_path_buf());
 }
 
+/// Check if a process with the given `pid` is currently running
+/// This is a lightweight alternative to listing all processes, which is only necessary to check liveness
+///
+/// Uses `/proc` where available (like linux), `OpenProcess` on windows and falls back to `kill(pid, 0)` on other unix systems
+#[must_use]
+pub fn is_process_alive(pid: u32) -> bool {
+	#[cfg(windows)]
+	{
+		return is_process_alive_windows(pid);
+	}
+
+	#[cfg(not(windows))]
+	{
+		let proc_path = Path::new("/proc");
+		if proc_path.is_dir() {
+			return proc_path.join(pid.to_string()).exists();
+		}
+
+		// "0" and pids which do not fit are not valid and would otherwise be interpreted as process groups
+		let Some(pid) = libc::pid_t::try_from(pid).ok().filter(|v| return *v > 0) else {
+			return false;
+		};
+
+		// signal "0" is not sent, but only checks if the process exists (and can be signaled)
+		// SAFETY: "kill" with signal "0" has no side-effects and does not access memory
+		let result = unsafe { libc::kill(pid, 0) };
+
+		return kill_result_is_alive(result, std::io::Error::last_os_error().raw_os_error());
+	}
+}
+
+/// Get whether the process is alive from the result of "kill(pid, 0)" and its "errno"
+/// A process of another user cannot be signaled (EPERM), but still exists
+#[cfg(not(windows))]
+fn kill_result_is_alive(result: i32, errno: Option<i32>) -> bool {
+	return result == 0 || errno == Some(libc::EPERM);
+}
+
+/// Windows implementation of [`is_process_alive`], by opening the process and checking if it has exited
+#[cfg(windows)]
+fn is_process_alive_windows(pid: u32) -> bool {
+	use std::ffi::c_void;
+
+	/// Access right to only query limited information, see https://learn.microsoft.com/en-us/windows/win32/procthread/process-security-and-access-rights
+	const PROCESS_QUERY_LIMITED_INFORMATION: u32 = 0x1000;
+	/// Exit code returned by "GetExitCodeProcess" while the process is still running
+	const STILL_ACTIVE: u32 = 259;
+	/// Error returned by "OpenProcess" when the process exists, but may not be opened
+	const ERROR_ACCESS_DENIED: u32 = 5;
+
+	#[link(name = "kernel32")]
+	extern "system" {
+		fn OpenProcess(desired_access: u32, inherit_handle: i32, process_id: u32) -> *mut c_void;
+		fn GetExitCodeProcess(process: *mut c_void, exit_code: *mut u32) -> i32;
+		fn CloseHandle(handle: *mut c_void) -> i32;
+		fn GetLastError() -> u32;
+	}
+
+	// SAFETY: the handle is checked to be valid before use and closed exactly once
+	unsafe {
+		let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+		if handle.is_null() {
+			return GetLastError() == ERROR_ACCESS_DENIED;
+		}
+
+		let mut exit_code: u32 = 0;
+		let res = GetExitCodeProcess(handle, &mut exit_code);
+		CloseHandle(handle);
+
+		return res != 0 && exit_code == STILL_ACTIVE;
+	}
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
@@ -106,6 +182,28 @@ mod test {
 		}
 	}
 
+	mod is_process_alive {
+		use super::*;
+
+		#[test]
+		fn basic_func() {
+			// the current process should always be alive
+			assert!(is_process_alive(std::process::id()));
+
+			// a pid this high should not exist
+			assert!(!is_process_alive(u32::MAX));
+		}
+
+		#[cfg(not(windows))]
+		#[test]
+		fn kill_result() {
+			assert!(kill_result_is_alive(0, None));
+			// process of another user
+			assert!(kill_result_is_alive(-1, Some(libc::EPERM)));
+			assert!(!kill_result_is_alive(-1, Some(libc::ESRCH)));
+		}
+	}
+
 	mod to_absolute {
 		use super::*;
 
@@ -0,0 +1,119 @@
+//! Module for getting the available space of filesystems (with the POSIX "df" command)
+
+use std::{
+	path::Path,
+	process::{
+		Command,
+		Output,
+		Stdio,
+	},
+};
+
+use crate::error::IOErrorToError;
+
+/// A filesystem which has less space available than required, see [`check_available_space`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowSpace<'a> {
+	/// The path that was checked
+	pub path:      &'a Path,
+	/// The available space of the filesystem of the path in bytes
+	pub available: u64,
+}
+
+/// Get the available space in bytes of the filesystem `path` is on
+/// If `path` does not exist yet, the closest existing parent is used (like a output directory that will be created)
+pub fn available_space(path: &Path) -> Result<u64, crate::Error> {
+	// only a relative path can have no existing parent, which is then relative to the current directory
+	let existing = path
+		.ancestors()
+		.find(|v| return v.exists())
+		.unwrap_or_else(|| return Path::new("."));
+
+	// "-P" for the POSIX output format (one line per filesystem), "-k" for 1024 byte blocks
+	let mut cmd = Command::new("df");
+	cmd.args(["-P", "-k"]);
+	cmd.arg(existing);
+
+	let command_output: Output = cmd
+		.stderr(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stdin(Stdio::null())
+		.spawn()
+		.attach_location_err("df spawn")?
+		.wait_with_output()
+		.attach_location_err("df wait_with_output")?;
+
+	if !command_output.status.success() {
+		return Err(crate::Error::command_unsuccessful(format!(
+			"df did not successfully exit! Exit Status: {}\nOutput:\n{}",
+			command_output.status,
+			String::from_utf8_lossy(&command_output.stderr).trim()
+		)));
+	}
+
+	return parse_df_available(&String::from_utf8_lossy(&command_output.stdout));
+}
+
+/// Check that the filesystems of all `paths` have at least `min_bytes` available
+/// Returns all paths which have less space available, paths on the same filesystem are each returned
+pub fn check_available_space<'a>(paths: &[&'a Path], min_bytes: u64) -> Result<Vec<LowSpace<'a>>, crate::Error> {
+	let mut low = Vec::new();
+
+	for path in paths {
+		let available = available_space(path)?;
+
+		if available < min_bytes {
+			low.push(LowSpace { path, available });
+		}
+	}
+
+	return Ok(low);
+}
+
+/// Internal Function to get the available bytes from the output of "df -P -k"
+/// The available blocks are the column before the capacity (like "42%"), because the filesystem name and the mount point may contain spaces
+fn parse_df_available(input: &str) -> Result<u64, crate::Error> {
+	// the first line is the header
+	let line = input
+		.lines()
+		.nth(1)
+		.ok_or_else(|| return crate::Error::no_captures("df did not output a filesystem"))?;
+
+	let columns: Vec<&str> = line.split_whitespace().collect();
+	let available = columns
+		.iter()
+		.position(|v| return v.len() > 1 && v.ends_with('%'))
+		.and_then(|v| return v.checked_sub(1))
+		.map(|v| return columns[v])
+		.ok_or_else(|| return crate::Error::no_captures(format!("df output \"{line}\" has no available column")))?;
+
+	let blocks = available
+		.parse::<u64>()
+		.map_err(|_| return crate::Error::other(format!("df available \"{available}\" is not a number")))?;
+
+	return Ok(blocks.saturating_mul(1024));
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_parse_df_available() {
+		assert_eq!(
+			Ok(2048 * 1024),
+			parse_df_available(
+				"Filesystem     1024-blocks      Used Available Capacity Mounted on\n/dev/sda1         10000000   7997952      2048     100% /\n"
+			)
+		);
+		// the filesystem name and mount point may contain spaces
+		assert_eq!(
+			Ok(500 * 1024),
+			parse_df_available(
+				"Filesystem 1024-blocks Used Available Capacity Mounted on\nmy share 1000 500 500 50% /mnt/my share\n"
+			)
+		);
+		assert!(parse_df_available("Filesystem 1024-blocks Used Available Capacity Mounted on\n").is_err());
+		assert!(parse_df_available("").is_err());
+	}
+}
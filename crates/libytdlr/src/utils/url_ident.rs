@@ -0,0 +1,172 @@
+//! Module for getting the provider and media id of known URL shapes, without having to ask youtube-dl
+//!
+//! This allows looking up URLs in the Archive directly, like "https://youtu.be/ID" being "youtube:ID"
+
+use std::fmt::Display;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// The provider and media id of a URL, like they are stored in the Archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlIdent {
+	/// The provider of the media, like the youtube-dl extractor
+	pub provider: &'static str,
+	/// The id of the media
+	pub media_id: String,
+}
+
+impl Display for UrlIdent {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return write!(f, "{}:{}", self.provider, self.media_id);
+	}
+}
+
+/// Known URL shapes of single media, as "(provider, regex)"
+/// cap1: media id
+///
+/// URLs that only contain a slug (like "soundcloud.com/artist/song") are not known, because the id youtube-dl uses is not part of them
+static URL_SHAPES: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+	return [
+		(
+			"youtube",
+			r"(?i)^(?:https?://)?(?:www\.|m\.|music\.)?youtube(?:-nocookie)?\.com/(?:watch\?(?:[^#]*&)?v=|shorts/|live/|embed/|v/)([\w-]{11})(?:[?&#/]|$)",
+		),
+		("youtube", r"(?i)^(?:https?://)?(?:www\.)?youtu\.be/([\w-]{11})(?:[?#/]|$)"),
+		(
+			"soundcloud",
+			r"(?i)^(?:https?://)?api(?:-v2)?\.soundcloud\.com/tracks/(\d+)(?:[?#/]|$)",
+		),
+		(
+			"vimeo",
+			r"(?i)^(?:https?://)?(?:www\.|player\.)?vimeo\.com/(?:video/)?(\d+)(?:[?#/]|$)",
+		),
+		(
+			"dailymotion",
+			r"(?i)^(?:https?://)?(?:www\.)?(?:dailymotion\.com/video|dai\.ly)/([a-z0-9]+)(?:[?#/_]|$)",
+		),
+		(
+			"niconico",
+			r"(?i)^(?:https?://)?(?:www\.|sp\.)?nicovideo\.jp/watch/((?:sm|nm|so)\d+)(?:[?#/]|$)",
+		),
+	]
+	.into_iter()
+	.map(|(provider, regex)| return (provider, Regex::new(regex).unwrap()))
+	.collect();
+});
+
+/// Get the provider and media id of `url`, if it is a known URL shape of a single media (see [`URL_SHAPES`])
+/// Returns [`None`] for unknown URLs and URLs of multiple media (like playlists or channels)
+#[must_use]
+pub fn identify_url(url: &str) -> Option<UrlIdent> {
+	let url = url.trim();
+
+	return URL_SHAPES.iter().find_map(|(provider, regex)| {
+		return regex.captures(url).map(|cap| {
+			return UrlIdent {
+				provider,
+				media_id: cap[1].to_owned(),
+			};
+		});
+	});
+}
+
+/// Get a URL of the single media `media_id` of `provider`, the reverse of [`identify_url`]
+/// Returns [`None`] for providers which are not known in [`URL_SHAPES`]
+#[must_use]
+pub fn media_url(provider: &str, media_id: &str) -> Option<String> {
+	let url = match provider.to_lowercase().as_str() {
+		"youtube" => format!("https://www.youtube.com/watch?v={media_id}"),
+		"soundcloud" => format!("https://api.soundcloud.com/tracks/{media_id}"),
+		"vimeo" => format!("https://vimeo.com/{media_id}"),
+		"dailymotion" => format!("https://www.dailymotion.com/video/{media_id}"),
+		"niconico" => format!("https://www.nicovideo.jp/watch/{media_id}"),
+		_ => return None,
+	};
+
+	return Some(url);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Helper to get the display form of [`identify_url`]
+	fn ident(url: &str) -> Option<String> {
+		return identify_url(url).map(|v| return v.to_string());
+	}
+
+	#[test]
+	fn test_youtube() {
+		let expected = Some("youtube:dQw4w9WgXcQ".to_owned());
+
+		assert_eq!(expected, ident("https://www.youtube.com/watch?v=dQw4w9WgXcQ"));
+		assert_eq!(
+			expected,
+			ident("https://www.youtube.com/watch?feature=share&v=dQw4w9WgXcQ&t=42")
+		);
+		assert_eq!(
+			expected,
+			ident("https://music.youtube.com/watch?v=dQw4w9WgXcQ&list=PL1")
+		);
+		assert_eq!(expected, ident("youtu.be/dQw4w9WgXcQ?si=abc"));
+		assert_eq!(expected, ident("https://www.youtube.com/shorts/dQw4w9WgXcQ"));
+		assert_eq!(expected, ident("https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ"));
+		assert_eq!(expected, ident(" https://m.youtube.com/live/dQw4w9WgXcQ/ "));
+
+		// playlists and channels are not a single media
+		assert_eq!(None, ident("https://www.youtube.com/playlist?list=PL1"));
+		assert_eq!(None, ident("https://www.youtube.com/@channel"));
+		// ids are always 11 characters
+		assert_eq!(None, ident("https://www.youtube.com/watch?v=short"));
+		assert_eq!(None, ident("https://www.youtube.com/watch?v=dQw4w9WgXcQtoolong"));
+	}
+
+	#[test]
+	fn test_other_providers() {
+		assert_eq!(
+			Some("soundcloud:123456".to_owned()),
+			ident("https://api.soundcloud.com/tracks/123456")
+		);
+		assert_eq!(None, ident("https://soundcloud.com/artist/song"));
+		assert_eq!(Some("vimeo:76979871".to_owned()), ident("https://vimeo.com/76979871"));
+		assert_eq!(
+			Some("vimeo:76979871".to_owned()),
+			ident("https://player.vimeo.com/video/76979871?h=abc")
+		);
+		assert_eq!(
+			Some("dailymotion:x7tgad0".to_owned()),
+			ident("https://www.dailymotion.com/video/x7tgad0")
+		);
+		assert_eq!(Some("dailymotion:x7tgad0".to_owned()), ident("https://dai.ly/x7tgad0"));
+		assert_eq!(
+			Some("niconico:sm9".to_owned()),
+			ident("https://www.nicovideo.jp/watch/sm9")
+		);
+		assert_eq!(None, ident("https://example.com/watch?v=dQw4w9WgXcQ"));
+		assert_eq!(None, ident(""));
+	}
+
+	#[test]
+	fn test_media_url() {
+		for (provider, media_id) in [
+			("youtube", "dQw4w9WgXcQ"),
+			("soundcloud", "123456"),
+			("vimeo", "76979871"),
+			("dailymotion", "x7tgad0"),
+			("niconico", "sm9"),
+		] {
+			let url = media_url(provider, media_id).expect("Expected provider to be known");
+			let ident = identify_url(&url).expect("Expected url to be identified");
+
+			assert_eq!(provider, ident.provider);
+			assert_eq!(media_id, ident.media_id);
+		}
+
+		assert_eq!(
+			Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()),
+			media_url("Youtube", "dQw4w9WgXcQ")
+		);
+		assert_eq!(None, media_url("unknown", "1"));
+	}
+}
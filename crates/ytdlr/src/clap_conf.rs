@@ -2,6 +2,10 @@
 
 #![deny(missing_docs)] // comments are used for "--help" generation, so it should always be defined
 
+use crate::{
+	messages::Lang,
+	utils::FileType,
+};
 use clap::{
 	ArgAction,
 	Parser,
@@ -10,11 +14,41 @@ use clap::{
 };
 use clap_complete::Shell;
 use is_terminal::IsTerminal;
+use libytdlr::{
+	chrono::{
+		NaiveTime,
+		TimeDelta,
+	},
+	config::{
+		Config,
+		Profile,
+	},
+	main::{
+		archive::search::SearchColumn,
+		download::{
+			CookieSource,
+			FormatConstraints,
+			PlaylistOrder,
+		},
+		search::SearchProvider,
+		url_preprocess::UrlRewrite,
+	},
+	spawn::{
+		priority::IoniceClass,
+		ytdl::require_ytdl_installed,
+	},
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::{
 	collections::HashSet,
 	error::Error,
+	ffi::OsString,
 	fmt::Display,
-	path::PathBuf,
+	path::{
+		Path,
+		PathBuf,
+	},
 	str::FromStr,
 };
 
@@ -32,24 +66,58 @@ trait Check {
 pub struct CliDerive {
 	/// Set Loggin verbosity (0 - Default - WARN, 1 - INFO, 2 - DEBUG, 3 - TRACE)
 	#[arg(short, long, action = ArgAction::Count, env = "YTDL_VERBOSITY")]
-	pub verbosity:    u8,
+	pub verbosity:      u8,
 	/// Temporary directory path to store intermediate files (like downloaded files before being moved)
 	#[arg(long = "tmp", env = "YTDL_TMP")]
-	pub tmp_path:     Option<PathBuf>,
+	pub tmp_path:       Option<PathBuf>,
 	/// Request vscode lldb debugger before continuing to execute.
 	/// Only available in debug target
 	#[arg(long)]
 	#[cfg(debug_assertions)]
-	pub debugger:     bool,
+	pub debugger:       bool,
 	/// Archive path to use, if a archive should be used
 	#[arg(long = "archive", env = "YTDL_ARCHIVE")]
-	pub archive_path: Option<PathBuf>,
+	pub archive_path:   Option<PathBuf>,
+	/// Add a named archive to select with "--archive-name" or "--archive-route", can be specified multiple times
+	/// Example: --named-archive "music=~/music.db"
+	#[arg(long = "named-archive", value_parser = parse_key_val::<String, PathBuf>)]
+	pub named_archives: Vec<(String, PathBuf)>,
+	/// Select which named archive to use, takes priority over "--archive-route" and "--archive"
+	#[arg(long = "archive-name", env = "YTDL_ARCHIVE_NAME")]
+	pub archive_name:   Option<String>,
+	/// Route media of a provider to a named archive, can be specified multiple times
+	/// Providers are matched case-insensitive, URLs match by their provider (like "youtube" for "https://youtu.be/...")
+	/// Unmatched media will use "--archive"
+	/// Example: --archive-route "soundcloud=music"
+	#[arg(long = "archive-route", value_parser = parse_key_val::<String, String>)]
+	pub archive_routes: Vec<(String, String)>,
 	/// Explicitly set interactive / not interactive
 	#[arg(long = "interactive")]
-	pub explicit_tty: Option<bool>,
+	pub explicit_tty:   Option<bool>,
+	/// Answer every prompt with its default, errors if a prompt has no default
+	/// Unlike "--interactive false", the edit and finish stages are still run (with their defaults)
+	#[arg(short = 'y', long = "assume-yes", env = "YTDL_ASSUME_YES")]
+	pub assume_yes:     bool,
 	/// Force Color to be active in any mode
 	#[arg(long = "color")]
-	pub force_color:  bool,
+	pub force_color:    bool,
+	/// Set the language of user-facing messages, by default detected from "LC_ALL", "LC_MESSAGES" or "LANG"
+	#[arg(long = "lang", env = "YTDL_LANG", value_enum)]
+	pub lang:           Option<Lang>,
+	/// Run spawned ytdl and ffmpeg processes with the given niceness (-20 to 19, higher is lower CPU priority)
+	#[arg(long = "nice", env = "YTDL_NICE", value_parser = clap::value_parser!(i8).range(-20..=19), allow_negative_numbers = true)]
+	pub nice:           Option<i8>,
+	/// Run spawned ytdl and ffmpeg processes with the given IO scheduling class, only supported on linux
+	/// Supported classes: "idle", "best-effort" (lowest level)
+	#[arg(long = "ionice", env = "YTDL_IONICE")]
+	pub ionice:         Option<IoniceClass>,
+	/// Config file to load defaults from, instead of the default "ytdlr/config.toml" in the config directory
+	/// Values from the config are only used if not specified on the command-line or environment
+	#[arg(long = "config", env = "YTDL_CONFIG")]
+	pub config_path:    Option<PathBuf>,
+	/// Run the given executable instead of "yt-dlp", like a specific yt-dlp binary, one in a python venv or a mock for testing
+	#[arg(long = "ytdl-bin", env = "YTDL_BIN", alias = "mock-ytdl")]
+	pub ytdl_bin:       Option<PathBuf>,
 
 	#[command(subcommand)]
 	pub subcommands: SubCommands,
@@ -60,11 +128,83 @@ impl CliDerive {
 	pub fn custom_parse() -> Result<Self, crate::Error> {
 		let mut parsed = Self::parse();
 
+		// apply the config before "check", so that values from the config are also fixed and validated
+		parsed.load_config()?;
 		Check::check(&mut parsed)?;
 
 		return Ok(parsed);
 	}
 
+	/// Load the config from "--config" or the default path (if it exists) and apply it with [`Self::apply_config`]
+	fn load_config(&mut self) -> Result<(), crate::Error> {
+		let config = if let Some(path) = self.config_path.as_ref() {
+			let path = crate::utils::fix_path(path).ok_or_else(|| {
+				return crate::Error::other("Config Path was provided, but could not be expanded / fixed");
+			})?;
+
+			Config::load(&path)?
+		} else {
+			let Some(config) = Config::load_default()? else {
+				return Ok(());
+			};
+
+			config
+		};
+
+		self.apply_config(config)?;
+
+		return Ok(());
+	}
+
+	/// Apply all values from `config`, which are not already set (command-line arguments take priority)
+	fn apply_config(&mut self, config: Config) -> Result<(), crate::Error> {
+		self.archive_path = self.archive_path.take().or(config.archive_path);
+		self.tmp_path = self.tmp_path.take().or(config.tmp_path);
+		self.ytdl_bin = self.ytdl_bin.take().or(config.ytdl_bin);
+
+		let download = match &mut self.subcommands {
+			SubCommands::Download(download) => Some(download),
+			SubCommands::Watch(watch) => {
+				// subscriptions are only taken from the config if none are given on the command-line, to not mix them
+				if !watch.has_subscriptions() {
+					watch.subscriptions = config.subscriptions.unwrap_or_default();
+				}
+
+				Some(&mut watch.download)
+			},
+			_ => None,
+		};
+
+		if let Some(download) = download {
+			download.audio_editor = download.audio_editor.take().or(config.audio_editor);
+			download.video_editor = download.video_editor.take().or(config.video_editor);
+			download.tagger_editor = download.tagger_editor.take().or(config.tagger_editor);
+			download.player_editor = download.player_editor.take().or(config.player_editor);
+			download.output_path = download.output_path.take().or(config.output_path);
+
+			// routes are only taken from the config if none are given on the command-line, to not mix them
+			if download.output_routes.is_empty() {
+				download.output_routes = config.output_routes.unwrap_or_default().into_iter().collect();
+			}
+			download.sub_langs = download.sub_langs.take().or(config.sub_langs);
+
+			if download.profiles.is_empty() {
+				download.profiles = config
+					.profiles
+					.unwrap_or_default()
+					.into_iter()
+					.map(|(provider, profile)| {
+						let profile = DownloadProfile::from_config(&provider, profile)?;
+
+						return Ok((provider.to_lowercase(), profile));
+					})
+					.collect::<Result<_, crate::Error>>()?;
+			}
+		}
+
+		return Ok(());
+	}
+
 	/// Get if the mode is interactive or not
 	#[must_use]
 	pub fn is_interactive(&self) -> bool {
@@ -81,6 +221,69 @@ impl CliDerive {
 		return self.force_color | self.is_interactive();
 	}
 
+	/// Get the directory "download" downloads into, inside "--tmp" or the system temporary directory
+	#[must_use]
+	pub fn download_tmp_path(&self) -> PathBuf {
+		return self
+			.tmp_path
+			.as_ref()
+			.map_or_else(|| return std::env::temp_dir(), |v| return v.clone())
+			.join("ytdl_rust_tmp");
+	}
+
+	/// Check that youtube-dl is installed and return the binary to spawn and its version
+	/// Uses "--ytdl-bin" if set, otherwise "yt-dlp" or one of the standalone release binaries found in PATH
+	pub fn require_ytdl(&self) -> Result<(OsString, String), crate::Error> {
+		return require_ytdl_installed(self.ytdl_bin.as_deref().map(Path::as_os_str));
+	}
+
+	/// Get the archive path to use when no provider or URL is known
+	/// Resolves "--archive-name", otherwise uses "--archive"
+	#[must_use]
+	pub fn resolve_archive_path(&self) -> Option<&Path> {
+		if let Some(name) = self.archive_name.as_ref() {
+			return self.named_archive(name);
+		}
+
+		return self.archive_path.as_deref();
+	}
+
+	/// Get the archive path to use for media of the given provider, resolving "--archive-route" rules
+	/// Falls back to [`Self::resolve_archive_path`] if "--archive-name" is set or no rule matches
+	#[must_use]
+	pub fn resolve_archive_path_for_provider(&self, provider: &str) -> Option<&Path> {
+		if self.archive_name.is_none() {
+			if let Some((_, name)) = self
+				.archive_routes
+				.iter()
+				.find(|(rule, _)| return rule.eq_ignore_ascii_case(provider))
+			{
+				return self.named_archive(name);
+			}
+		}
+
+		return self.resolve_archive_path();
+	}
+
+	/// Get the archive path to use for the given URL, resolving "--archive-route" rules
+	/// The URL is matched by its provider (see [`crate::utils::url_provider`]), like finished media, so that both use the same archive
+	#[must_use]
+	pub fn resolve_archive_path_for_url(&self, url: &str) -> Option<&Path> {
+		return match crate::utils::url_provider(url) {
+			Some(provider) => self.resolve_archive_path_for_provider(&provider),
+			None => self.resolve_archive_path(),
+		};
+	}
+
+	/// Get the path of the named archive "name"
+	fn named_archive(&self, name: &str) -> Option<&Path> {
+		return self
+			.named_archives
+			.iter()
+			.find(|(archive_name, _)| return archive_name == name)
+			.map(|(_, path)| return path.as_path());
+	}
+
 	/// Get if debug is enabled
 	/// Only able to be "true" in "debug" target
 	#[must_use]
@@ -103,6 +306,31 @@ impl Check for CliDerive {
 			None => None,
 		};
 
+		// apply "expand_tilde" to all named archives
+		for (_, path) in &mut self.named_archives {
+			*path = crate::utils::fix_path(path.as_path()).ok_or_else(|| {
+				return crate::Error::other("Named Archive Path was provided, but could not be expanded / fixed");
+			})?;
+		}
+
+		// lowercase route providers, because providers are matched case-insensitive
+		for (provider, _) in &mut self.archive_routes {
+			*provider = provider.to_lowercase();
+		}
+
+		// check that all referenced named archives exist
+		for name in self
+			.archive_name
+			.iter()
+			.chain(self.archive_routes.iter().map(|(_, name)| return name))
+		{
+			if self.named_archive(name).is_none() {
+				return Err(crate::Error::other(format!(
+					"Archive name \"{name}\" is used, but no \"--named-archive\" with that name exists"
+				)));
+			}
+		}
+
 		// apply "expand_tilde" to archive_path
 		self.tmp_path = match self.tmp_path.take() {
 			// this has to be so round-about, because i dont know of a function that would allow functionality like "and_then" but instead of returning the same value, it would return a result
@@ -117,6 +345,7 @@ impl Check for CliDerive {
 }
 
 #[derive(Debug, Subcommand, Clone, PartialEq)]
+#[allow(clippy::large_enum_variant)] // only constructed once per run, so the size difference does not matter
 pub enum SubCommands {
 	/// The main purpose of the binary, download a URL(s)
 	Download(CommandDownload),
@@ -125,12 +354,27 @@ pub enum SubCommands {
 	/// Re-Thumbnail specific files
 	#[command(alias = "rethumbnail")] // alias, otherwise only "re-thumbnail" would be the only valid option
 	ReThumbnail(CommandReThumbnail),
+	/// Re-Thumbnail many files at once, with automatically found images
+	#[command(alias = "rethumbnail-batch")] // alias, for consistency with "rethumbnail"
+	ReThumbnailBatch(CommandReThumbnailBatch),
+	/// List which media of a playlist are not yet in the archive, without downloading anything
+	Diff(CommandDiff),
+	/// List which archive entries have no file in the library directories
+	CheckArchive(CommandCheckArchive),
+	/// Watch a directory for dropped link files (".url", ".webloc", ".txt") and download the links in them
+	Watch(CommandWatch),
+	/// Run the full download pipeline on a small media in a scratch directory, reporting which stage fails
+	#[command(name = "selftest", alias = "doctor")]
+	SelfTest(CommandSelfTest),
 	/// Generate shell completions
 	Completions(CommandCompletions),
-	/// Unicode Terminal testing options
-	#[cfg(debug_assertions)]
-	#[command(name = "unicode-test")]
-	UnicodeTerminalTest(CommandUnicodeTerminalTest),
+	/// Check how the terminal displays unicode, to debug miscounted widths (like a broken progress-bar truncation)
+	#[command(name = "term-check", alias = "unicode-test")]
+	TermCheck(CommandTermCheck),
+	/// Undo the last move to the output directory done with "download --use-trash"
+	Restore(CommandRestore),
+	/// Report the versions of the required tools (youtube-dl and ffmpeg) and whether they are usable
+	CheckDeps(CommandCheckDeps),
 }
 
 impl Check for SubCommands {
@@ -139,9 +383,15 @@ impl Check for SubCommands {
 			SubCommands::Download(v) => return Check::check(v),
 			SubCommands::Archive(v) => return Check::check(v),
 			SubCommands::ReThumbnail(v) => return Check::check(v),
+			SubCommands::ReThumbnailBatch(v) => return Check::check(v),
+			SubCommands::Diff(v) => return Check::check(v),
+			SubCommands::CheckArchive(v) => return Check::check(v),
+			SubCommands::Watch(v) => return Check::check(v),
+			SubCommands::SelfTest(v) => return Check::check(v),
 			SubCommands::Completions(v) => return Check::check(v),
-			#[cfg(debug_assertions)]
-			SubCommands::UnicodeTerminalTest(v) => return Check::check(v),
+			SubCommands::TermCheck(v) => return Check::check(v),
+			SubCommands::Restore(v) => return Check::check(v),
+			SubCommands::CheckDeps(v) => return Check::check(v),
 		}
 	}
 }
@@ -162,15 +412,45 @@ impl Check for ArchiveDerive {
 pub enum ArchiveSubCommands {
 	/// Import a Archive file, be it youtube-dl, ytdlr-json, or ytdlr-sqlite
 	Import(ArchiveImport),
+	/// Add all media of URLs to the Archive without downloading them
+	Ingest(ArchiveIngest),
+	/// Remove entries from the Archive, optionally blacklisting them for future downloads
+	Remove(ArchiveRemove),
 	/// Search the Archive
 	Search(ArchiveSearch),
+	/// Show the applied and pending migrations of a Archive
+	Migrations(ArchiveMigrations),
+	/// Add or remove labels of Archive entries
+	Tag(ArchiveTag),
+	/// Interactively search the Archive and run actions on the results
+	Browse(ArchiveBrowse),
+	/// Merge duplicate Archive entries and optimize the Archive database
+	Maintenance(ArchiveMaintenance),
+	/// Show statistics of the Archive, like media per provider and added media per month
+	Stats(ArchiveStats),
+	/// Generate a youtube-dl archive from the Archive, for use with youtube-dl directly
+	GenYtdlArchive(ArchiveGenYtdlArchive),
+	/// Report whether the media of URLs are already in the Archive, without asking youtube-dl
+	Has(ArchiveHas),
+	/// Report media in the Archive which have the same file content (like re-uploads with a different id)
+	Dupes(ArchiveDupes),
 }
 
 impl Check for ArchiveSubCommands {
 	fn check(&mut self) -> Result<(), crate::Error> {
 		match self {
 			ArchiveSubCommands::Import(v) => return Check::check(v),
+			ArchiveSubCommands::Ingest(v) => return Check::check(v),
+			ArchiveSubCommands::Remove(v) => return Check::check(v),
 			ArchiveSubCommands::Search(v) => return Check::check(v),
+			ArchiveSubCommands::Migrations(v) => return Check::check(v),
+			ArchiveSubCommands::Tag(v) => return Check::check(v),
+			ArchiveSubCommands::Browse(v) => return Check::check(v),
+			ArchiveSubCommands::Maintenance(v) => return Check::check(v),
+			ArchiveSubCommands::Stats(v) => return Check::check(v),
+			ArchiveSubCommands::GenYtdlArchive(v) => return Check::check(v),
+			ArchiveSubCommands::Has(v) => return Check::check(v),
+			ArchiveSubCommands::Dupes(v) => return Check::check(v),
 		}
 	}
 }
@@ -193,51 +473,154 @@ impl Check for ArchiveImport {
 	}
 }
 
-#[derive(ValueEnum, Clone, Debug, PartialEq, Copy)]
-#[value(rename_all = "camelCase")]
-pub enum ArchiveSearchColumn {
-	/// For the SQL column "provider"
-	Provider,
-	/// For the SQL column "media_id"
-	MediaId,
-	/// For the SQL column "title"
-	Title,
-	/// For the SQL column "inserted_at"
-	InsertedAt,
-}
-
-impl Display for ArchiveSearchColumn {
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		return write!(
-			f,
-			"{}",
-			match *self {
-				ArchiveSearchColumn::Provider => "Provider",
-				ArchiveSearchColumn::MediaId => "MediaId",
-				ArchiveSearchColumn::InsertedAt => "InsertedAt",
-				ArchiveSearchColumn::Title => "Title",
-			}
-		);
+/// Add all media of URLs (like a playlist or channel) to the Archive without downloading them
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveIngest {
+	/// The URLs to add the media of
+	#[arg(required(true))]
+	pub urls: Vec<String>,
+}
+
+impl Check for ArchiveIngest {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		if self.urls.iter().any(|v| return v.trim().is_empty()) {
+			return Err(crate::Error::other("URLs cannot be empty"));
+		}
+
+		return Ok(());
+	}
+}
+
+/// Report whether the media of URLs are already in the Archive
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveHas {
+	/// The URLs to check, only URLs of a single media on known providers can be checked (like youtube)
+	#[arg(required(true))]
+	pub urls: Vec<String>,
+}
+
+impl Check for ArchiveHas {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		if self.urls.iter().any(|v| return v.trim().is_empty()) {
+			return Err(crate::Error::other("URLs cannot be empty"));
+		}
+
+		return Ok(());
 	}
 }
 
-impl FromStr for ArchiveSearchColumn {
+/// Report media in the Archive which have the same file content
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveDupes {}
+
+impl Check for ArchiveDupes {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		return Ok(());
+	}
+}
+
+/// A entry to remove with "archive remove"
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoveTarget {
+	/// A entry given as "provider:id"
+	Media {
+		/// The provider of the entry
+		provider: String,
+		/// The id of the entry
+		id:       String,
+	},
+	/// All media of a URL, as listed by youtube-dl
+	Url(String),
+}
+
+impl FromStr for RemoveTarget {
 	type Err = crate::Error;
 
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
-		return Ok(match s.to_lowercase().as_str() {
-			"provider" => Self::Provider,
-			"mediaid"
-			// may be confused with the row-id
-			| "id" => Self::MediaId,
-			"insertedat"
-			| "inserted" => Self::InsertedAt,
-			"title" => Self::Title,
-			_ => return Err(crate::Error::other(format!("Unknown column \"{}\"", s))),
+		let s = s.trim();
+
+		if s.contains("://") {
+			return Ok(Self::Url(s.to_owned()));
+		}
+
+		let (provider, id) = s
+			.split_once(':')
+			.filter(|(provider, id)| return !provider.trim().is_empty() && !id.trim().is_empty())
+			.ok_or_else(|| return crate::Error::other(format!("Expected \"provider:id\" or a URL, got \"{s}\"")))?;
+
+		return Ok(Self::Media {
+			provider: provider.trim().to_owned(),
+			id:       id.trim().to_owned(),
 		});
 	}
 }
 
+/// Remove entries from the Archive, optionally blacklisting them so that future downloads skip them
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveRemove {
+	/// The entries to remove, either as "provider:id" or as a URL (all media of the URL, listed with youtube-dl)
+	/// Example: "youtube:dQw4w9WgXcQ"
+	#[arg(verbatim_doc_comment)]
+	pub targets: Vec<RemoveTarget>,
+
+	/// Query a column to select entries to remove, same format as "archive search", can be specified multiple times
+	/// Example: --query "provider=soundcloud"
+	#[arg(short = 'q', long = "query", value_parser = parse_key_val::<SearchColumn, String>, verbatim_doc_comment)]
+	pub queries: Vec<(SearchColumn, String)>,
+
+	/// Only print what would be removed and blacklisted, without changing the Archive
+	#[arg(long = "dry-run")]
+	pub dry_run: bool,
+
+	/// Add the entries to the blacklist, so that future downloads skip them
+	/// Entries given as "provider:id" or URL are blacklisted even if they are not in the Archive
+	#[arg(long = "blacklist")]
+	pub blacklist: bool,
+
+	/// Delete the entries from the Archive, instead of keeping them as tombstones
+	/// Tombstones are not found by "archive search", but are still skipped by downloads
+	#[arg(long = "purge")]
+	pub purge: bool,
+}
+
+impl Check for ArchiveRemove {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		if self.targets.is_empty() && self.queries.is_empty() {
+			return Err(crate::Error::other(
+				"At least one entry or query to remove is required (\"provider:id\", URL or \"--query\")",
+			));
+		}
+
+		check_unique_query_columns(&self.queries)?;
+
+		return Ok(());
+	}
+}
+
+/// Show the applied and pending migrations of a Archive
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveMigrations {
+	/// The Archive file to show the migrations of, defaults to the global archive
+	pub file_path:    Option<PathBuf>,
+	/// Apply all pending migrations (including JSON to SQLite) before showing the status
+	#[arg(long = "migrate-only")]
+	pub migrate_only: bool,
+}
+
+impl Check for ArchiveMigrations {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		// apply "expand_tilde" to file_path
+		self.file_path = match self.file_path.take() {
+			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
+				return crate::Error::other("Archive Path was provided, but could not be expanded / fixed");
+			})?),
+			None => None,
+		};
+
+		return Ok(());
+	}
+}
+
 /// Parse a key-value pair from the input
 /// from <https://github.com/clap-rs/clap/blob/78bb48b6b8ef4d597b4b30b9add7927a2b0b0d8d/examples/typed-derive.rs#L48-L59>
 fn parse_key_val<T, U>(s: &str) -> Result<(T, U), Box<dyn Error + Send + Sync + 'static>>
@@ -263,23 +646,65 @@ pub enum SearchResultFormat {
 	CSVC,
 	/// Output as CSV, Tab delimited
 	CSVT,
+	/// Output as a JSON array of objects
+	Json,
+}
+
+/// The keys a "archive search" query can use, which are all [`SearchColumn`]s and the "Filename" pseudo-column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchKey {
+	/// A archive column
+	Column(SearchColumn),
+	/// The file name of media files in "--library-dir", which are matched to archive entries like "check-archive" does
+	Filename,
+}
+
+impl Display for SearchKey {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return match self {
+			SearchKey::Column(v) => v.fmt(f),
+			SearchKey::Filename => write!(f, "Filename"),
+		};
+	}
+}
+
+impl std::str::FromStr for SearchKey {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		return match s.to_lowercase().as_str() {
+			"filename" | "file" => Ok(Self::Filename),
+			_ => Ok(Self::Column(s.parse()?)),
+		};
+	}
 }
 
 /// Search the Archive
 #[derive(Debug, Parser, Clone, PartialEq)]
 pub struct ArchiveSearch {
 	/// Query a column with the given search terms, supported columns are (values in parenthesis are aliases):
-	///   Provider, Title, MediaId(id), InsertedAt(inserted)
+	///   Provider, Title, MediaId(id), InsertedAt(inserted), Filename(file)
 	/// columns can be anycase
+	/// "Filename" matches the file names in "--library-dir" and returns the archive entries of the matching files
 	/// Examples:
 	///   "title=some good title"
 	///   title=sometitle
 	///   title="long title"
 	///   "inserted=>=2023-05"
+	///   filename=sometitle --library-dir ~/Music
 	/// Supported Date operators are (omitted defaults to "="):
 	///   >,<,=,>=,<=
-	#[arg(required(true), value_parser = parse_key_val::<ArchiveSearchColumn, String>, verbatim_doc_comment)]
-	pub queries: Vec<(ArchiveSearchColumn, String)>,
+	#[arg(required(true), value_parser = parse_key_val::<SearchKey, String>, verbatim_doc_comment)]
+	pub queries: Vec<(SearchKey, String)>,
+
+	/// Match the words of the queries in order with anything between them (case-insensitive), instead of matching the start
+	/// Example: "title=some title" with "--fuzzy" finds "Some Good Title"
+	#[arg(long = "fuzzy")]
+	pub fuzzy: bool,
+
+	/// Directory to search media files in for the "Filename" query (recursively), can be specified multiple times
+	#[arg(long = "library-dir")]
+	pub library_dirs: Vec<PathBuf>,
 
 	/// Set the limit of returned values
 	#[arg(short = 'l', long = "limit", default_value_t = 10)]
@@ -288,23 +713,204 @@ pub struct ArchiveSearch {
 	/// Set which return format should be used
 	#[arg(short = 'f', long = "result-format", value_enum, default_value_t=SearchResultFormat::Normal)]
 	pub result_format: SearchResultFormat,
+
+	/// Only return entries which have the given label, can be specified multiple times (entries need to have all labels)
+	#[arg(long = "label")]
+	pub labels: Vec<String>,
+
+	/// Also return entries which were removed with "archive remove" and are only kept as tombstones
+	#[arg(long = "include-tombstones")]
+	pub include_tombstones: bool,
 }
 
 impl Check for ArchiveSearch {
 	fn check(&mut self) -> Result<(), crate::Error> {
-		// check that a query for a column is only defined once
-		let mut map = HashSet::new();
+		check_unique_query_columns(&self.queries)?;
 
-		for val in &self.queries {
-			if map.contains(&val.0.to_string()) {
-				return Err(crate::Error::other(format!(
-					"A column query can only be defined once, found duplicate for \"{}\"",
-					val.0
-				)));
-			}
-			map.insert(val.0.to_string());
+		if self.library_dirs.is_empty() && self.queries.iter().any(|v| return v.0 == SearchKey::Filename) {
+			return Err(crate::Error::other(
+				"A \"Filename\" query requires at least one \"--library-dir\"",
+			));
+		}
+
+		self.library_dirs = self
+			.library_dirs
+			.iter()
+			.map(|v| {
+				return crate::utils::fix_path(v)
+					.ok_or_else(|| return crate::Error::other("Failed to fix Library Directory path"));
+			})
+			.collect::<Result<Vec<PathBuf>, crate::Error>>()?;
+
+		self.labels = normalize_labels(&self.labels)?;
+
+		return Ok(());
+	}
+}
+
+/// Check that a query for a column is only defined once
+fn check_unique_query_columns<K: Display>(queries: &[(K, String)]) -> Result<(), crate::Error> {
+	let mut map = HashSet::new();
+
+	for val in queries {
+		if map.contains(&val.0.to_string()) {
+			return Err(crate::Error::other(format!(
+				"A column query can only be defined once, found duplicate for \"{}\"",
+				val.0
+			)));
+		}
+		map.insert(val.0.to_string());
+	}
+
+	return Ok(());
+}
+
+/// Normalize all labels with [`libytdlr::main::archive::labels::normalize_label`]
+fn normalize_labels(labels: &[String]) -> Result<Vec<String>, crate::Error> {
+	return labels
+		.iter()
+		.map(|v| return libytdlr::main::archive::labels::normalize_label(v))
+		.collect();
+}
+
+/// Interactively search the Archive and run actions on the results
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveBrowse {
+	/// Set the limit of shown results for each search
+	#[arg(short = 'l', long = "limit", default_value_t = 20)]
+	pub limit: i64,
+	/// Use a terminal UI, which searches while typing and selects results with the arrow keys
+	/// Falls back to the text prompts if the terminal UI could not be started
+	#[arg(long = "tui", env = "YTDL_TUI")]
+	pub tui:   bool,
+}
+
+impl Check for ArchiveBrowse {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		if self.limit < 1 {
+			return Err(crate::Error::other("Limit needs to be at least 1"));
+		}
+
+		return Ok(());
+	}
+}
+
+/// Merge duplicate Archive entries and optimize the Archive database
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveMaintenance {
+	/// Do not run "VACUUM" and "ANALYZE" on the Archive after merging duplicates
+	#[arg(long = "no-optimize")]
+	pub no_optimize: bool,
+}
+
+impl Check for ArchiveMaintenance {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		return Ok(());
+	}
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq, Copy)]
+#[value(rename_all = "lower")]
+pub enum StatsFormat {
+	/// Output as aligned tables
+	Table,
+	/// Output as CSV with the columns "section,key,count"
+	Csv,
+	/// Output as a single JSON object
+	Json,
+}
+
+/// Show statistics of the Archive
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveStats {
+	/// Set how many days with the most added media are shown
+	#[arg(long = "top-days", default_value_t = 5)]
+	pub top_days: i64,
+
+	/// Show the storage used per provider and uploader and the largest media, instead of the general statistics
+	#[arg(long = "disk")]
+	pub disk: bool,
+
+	/// Set how many uploaders and media are shown with "--disk"
+	#[arg(long = "largest", default_value_t = 10, requires = "disk")]
+	pub largest: i64,
+
+	/// Set which output format should be used
+	#[arg(short = 'f', long = "format", value_enum, default_value_t = StatsFormat::Table)]
+	pub format: StatsFormat,
+}
+
+impl Check for ArchiveStats {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		if self.top_days < 0 {
+			return Err(crate::Error::other("Top Days cannot be negative"));
+		}
+
+		if self.largest < 0 {
+			return Err(crate::Error::other("Largest cannot be negative"));
+		}
+
+		return Ok(());
+	}
+}
+
+/// Generate a youtube-dl archive (like for "--download-archive") from the Archive
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveGenYtdlArchive {
+	/// Set which entries of the Archive are added, the same as "--archive-mode" of "download"
+	/// Blacklisted media is always added
+	#[arg(long = "mode", value_enum, default_value_t = ArchiveMode::default())]
+	pub mode:   ArchiveMode,
+	/// The file to write the youtube-dl archive to, overwriting it if it exists
+	/// Defaults to writing to stdout
+	#[arg(short = 'o', long = "output")]
+	pub output: Option<PathBuf>,
+}
+
+impl Check for ArchiveGenYtdlArchive {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		// apply "expand_tilde" to output
+		self.output = match self.output.take() {
+			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
+				return crate::Error::other("Output Path was provided, but could not be expanded / fixed");
+			})?),
+			None => None,
+		};
+
+		return Ok(());
+	}
+}
+
+/// Add or remove labels of all Archive entries matching the queries
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct ArchiveTag {
+	/// Query a column to select the entries to change, same format as "archive search"
+	/// Example: "provider=soundcloud"
+	#[arg(required(true), value_parser = parse_key_val::<SearchColumn, String>, verbatim_doc_comment)]
+	pub queries: Vec<(SearchColumn, String)>,
+
+	/// Add a label to the matching entries, can be specified multiple times
+	#[arg(long = "add")]
+	pub add: Vec<String>,
+
+	/// Remove a label from the matching entries, can be specified multiple times
+	#[arg(long = "remove")]
+	pub remove: Vec<String>,
+}
+
+impl Check for ArchiveTag {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		check_unique_query_columns(&self.queries)?;
+
+		if self.add.is_empty() && self.remove.is_empty() {
+			return Err(crate::Error::other(
+				"At least one label to add or remove is required (\"--add\" or \"--remove\")",
+			));
 		}
 
+		self.add = normalize_labels(&self.add)?;
+		self.remove = normalize_labels(&self.remove)?;
+
 		return Ok(());
 	}
 }
@@ -337,6 +943,211 @@ pub enum DownloadEditAction {
 	VideoEdit,
 }
 
+#[derive(ValueEnum, Clone, Debug, PartialEq, Copy)]
+#[value(rename_all = "camelCase")]
+pub enum DownloadFinishAction {
+	/// Corresponds to "m"
+	Move,
+	/// Corresponds to "p"
+	Tagger,
+	/// Corresponds to "o"
+	Organize,
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq, Copy)]
+#[value(rename_all = "camelCase")]
+pub enum CollisionPolicy {
+	/// Overwrite the existing file in the output directory
+	Overwrite,
+	/// Append a number to the new file
+	Number,
+	/// Dont move the new file, it will be found again via recovery
+	Skip,
+}
+
+/// Audio formats supported for "--audio-format"
+#[derive(ValueEnum, Clone, Debug, PartialEq, Copy, Default)]
+#[value(rename_all = "lower")]
+pub enum AudioFormat {
+	/// Keep the best audio as youtube-dl downloaded it, without converting
+	#[default]
+	Best,
+	/// MPEG-1 Audio Layer III
+	Mp3,
+	/// AAC in a MPEG-4 container
+	M4a,
+	/// Opus in a ogg container
+	Opus,
+	/// Free Lossless Audio Codec
+	Flac,
+}
+
+impl AudioFormat {
+	/// Get the format as youtube-dl expects it for "--audio-format"
+	pub fn as_str(self) -> &'static str {
+		return match self {
+			Self::Best => "best",
+			Self::Mp3 => "mp3",
+			Self::M4a => "m4a",
+			Self::Opus => "opus",
+			Self::Flac => "flac",
+		};
+	}
+}
+
+/// Video containers supported for "--video-format"
+#[derive(ValueEnum, Clone, Debug, PartialEq, Copy, Default)]
+#[value(rename_all = "lower")]
+pub enum VideoFormat {
+	/// Matroska
+	#[default]
+	Mkv,
+	/// MPEG-4
+	Mp4,
+	/// WebM
+	Webm,
+}
+
+impl VideoFormat {
+	/// Get the format as youtube-dl expects it for "--remux-video"
+	pub fn as_str(self) -> &'static str {
+		return match self {
+			Self::Mkv => "mkv",
+			Self::Mp4 => "mp4",
+			Self::Webm => "webm",
+		};
+	}
+}
+
+#[derive(ValueEnum, Clone, Debug, PartialEq, Copy, Default)]
+#[value(rename_all = "camelCase")]
+pub enum ProgressFormat {
+	/// Show a progress bar (in interactive mode)
+	#[default]
+	Bar,
+	/// Print every download progress event as a JSON object on a single line to stdout
+	Json,
+}
+
+/// A daily time window, like "01:00-07:00", which may wrap around midnight (like "23:00-06:00")
+#[derive(Clone, Debug, PartialEq, Copy)]
+pub struct RunWindow {
+	/// The time the window starts (inclusive)
+	pub start: NaiveTime,
+	/// The time the window ends (exclusive)
+	pub end:   NaiveTime,
+}
+
+impl RunWindow {
+	/// Get whether the given time is inside the window
+	#[must_use]
+	pub fn contains(&self, time: NaiveTime) -> bool {
+		if self.start <= self.end {
+			return self.start <= time && time < self.end;
+		}
+
+		// the window wraps around midnight
+		return time >= self.start || time < self.end;
+	}
+
+	/// Get the duration from the given time until the window starts next
+	/// Returns [`Duration::ZERO`](std::time::Duration::ZERO) if the time is already inside the window
+	#[must_use]
+	pub fn duration_until_start(&self, time: NaiveTime) -> std::time::Duration {
+		if self.contains(time) {
+			return std::time::Duration::ZERO;
+		}
+
+		let mut diff = self.start.signed_duration_since(time);
+
+		// the start is on the next day
+		if diff < TimeDelta::zero() {
+			diff += TimeDelta::days(1);
+		}
+
+		return diff.to_std().unwrap_or_default();
+	}
+}
+
+impl FromStr for RunWindow {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (start, end) = s
+			.split_once('-')
+			.ok_or_else(|| return crate::Error::other(format!("Expected \"HH:MM-HH:MM\", got \"{s}\"")))?;
+
+		let parse_time = |v: &str| {
+			return NaiveTime::parse_from_str(v.trim(), "%H:%M")
+				.map_err(|err| return crate::Error::other(format!("Could not parse \"{v}\" as a time: {err}")));
+		};
+
+		let window = Self {
+			start: parse_time(start)?,
+			end:   parse_time(end)?,
+		};
+
+		if window.start == window.end {
+			return Err(crate::Error::other("Run window start and end cannot be the same"));
+		}
+
+		return Ok(window);
+	}
+}
+
+/// A filter for which editable files are found in recovery
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecoverFilter {
+	/// Only find media of the given provider (case-insensitive)
+	Provider(String),
+	/// Only find media which has been last modified at most this long ago
+	MaxAge(std::time::Duration),
+	/// Only find media of the given type (audio or video)
+	Type(FileType),
+}
+
+impl FromStr for RecoverFilter {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (key, value) = s
+			.split_once('=')
+			.ok_or_else(|| return crate::Error::other(format!("Expected \"KEY=VALUE\", got \"{s}\"")))?;
+
+		return Ok(match key.trim().to_lowercase().as_str() {
+			"provider" => Self::Provider(value.trim().to_lowercase()),
+			"max-age" | "age" => Self::MaxAge(parse_age(value.trim())?),
+			"type" => Self::Type(match value.trim().to_lowercase().as_str() {
+				"audio" => FileType::Audio,
+				"video" => FileType::Video,
+				_ => {
+					return Err(crate::Error::other(format!(
+						"Unknown type \"{value}\", expected \"audio\" or \"video\""
+					)))
+				},
+			}),
+			_ => return Err(crate::Error::other(format!("Unknown filter \"{key}\""))),
+		});
+	}
+}
+
+/// Parse a age like "30m", "12h" or "2d" into a [`Duration`](std::time::Duration), a number without unit is in seconds
+fn parse_age(input: &str) -> Result<std::time::Duration, crate::Error> {
+	let (number, multiplier) = match input.char_indices().last() {
+		Some((pos, 's')) => (&input[..pos], 1),
+		Some((pos, 'm')) => (&input[..pos], 60),
+		Some((pos, 'h')) => (&input[..pos], 60 * 60),
+		Some((pos, 'd')) => (&input[..pos], 60 * 60 * 24),
+		_ => (input, 1),
+	};
+
+	let number: u64 = number
+		.parse()
+		.map_err(|err| return crate::Error::other(format!("Could not parse age \"{input}\": {err}")))?;
+
+	return Ok(std::time::Duration::from_secs(number.saturating_mul(multiplier)));
+}
+
 impl Default for ArchiveMode {
 	fn default() -> Self {
 		return Self::Default;
@@ -349,72 +1160,599 @@ pub struct CommandDownload {
 	/// Audio Editor for audio files when using edits on post-processing
 	/// Must be either a absolute path or findable via PATH
 	#[arg(long, env = "YTDL_AUDIO_EDITOR")]
-	pub audio_editor:              Option<PathBuf>,
+	pub audio_editor: Option<PathBuf>,
 	/// Video Editor for video files when using edits on post-processing
 	/// Must be either a absolute path or findable via PATH
 	#[arg(long, env = "YTDL_VIDEO_EDITOR")]
-	pub video_editor:              Option<PathBuf>,
+	pub video_editor: Option<PathBuf>,
 	/// Tagger Path / Command to use
 	/// Must be either a absolute path or findable via PATH
 	#[arg(long = "tagger", env = "YTDL_TAGGER")]
-	pub tagger_editor:             Option<PathBuf>,
+	pub tagger_editor: Option<PathBuf>,
 	/// Media player Command to use
 	/// Must be either a absolute path or findable via PATH
 	#[arg(long = "player", env = "YTDL_PLAYER")]
-	pub player_editor:             Option<PathBuf>,
+	pub player_editor: Option<PathBuf>,
 	/// Output path for any command that outputs a file
 	#[arg(short, long, env = "YTDL_OUT")]
-	pub output_path:               Option<PathBuf>,
+	pub output_path: Option<PathBuf>,
+	/// Place media of a provider in a directory inside the output path, can be specified multiple times
+	/// Media matches if its provider (like "Youtube" or "TwitchVod") contains PROVIDER (case-insensitive), the first match is used
+	/// Absolute directories are used as-is instead of inside the output path
+	/// Example: --output-route "soundcloud=Music" --output-route "twitch=VODs"
+	#[arg(long = "output-route", value_parser = parse_key_val::<String, PathBuf>)]
+	pub output_routes: Vec<(String, PathBuf)>,
 	/// Disable Re-Applying Thumbnails after a editor has run
 	#[arg(long = "no-reapply-thumbnail", env = "YTDL_DISABLE_REAPPLY_THUMBNAIL")]
 	pub reapply_thumbnail_disable: bool,
 	/// Set download to be audio-only (if its not, it will just extract the audio)
 	#[arg(short = 'a', long = "audio-only")]
-	pub audio_only_enable:         bool,
+	pub audio_only_enable: bool,
 	/// Set which entries should be output to the youtube-dl archive
 	/// This does not affect entries being added to the SQLite archive
 	#[arg(long = "archive-mode", value_enum, default_value_t=ArchiveMode::default())]
-	pub archive_mode:              ArchiveMode,
+	pub archive_mode: ArchiveMode,
 	/// Print Youtube-DL log
 	/// This will still require logging verbosity set to 3 or "RUST_LOG=trace"
 	#[arg(long = "youtubedl-log")]
-	pub print_youtubedl_log:       bool,
+	pub print_youtubedl_log: bool,
 	/// Save Youtube-DL logs to a file
 	/// File will be in the temporary directory, named "yt-dl_PID.log" where the PID is the ytdlr's pid
 	#[arg(long = "youtubedl-logfile")]
-	pub save_youtubedl_log:        bool,
+	pub save_youtubedl_log: bool,
 	/// Disables allowing 0 URL's to just check the recovery
 	#[arg(long = "no-check-recovery")]
-	pub no_check_recovery:         bool,
+	pub no_check_recovery: bool,
+	/// Only print the URLs that would be processed, with the youtube-dl command and ytdl archive lines for each, without downloading anything
+	#[arg(long = "dry-run")]
+	pub dry_run: bool,
 	/// Set to automatically open the tagger in the end
 	/// also overwrites the default option of moving for non-interactive mode
 	#[arg(long = "open-tagger")]
-	pub open_tagger:               bool,
+	pub open_tagger: bool,
 	/// Apply a single action to all media in the edit stage
 	#[arg(long = "edit-action", value_enum)]
-	pub edit_action:               Option<DownloadEditAction>,
+	pub edit_action: Option<DownloadEditAction>,
+	/// Apply a single action in the finish stage instead of asking
+	#[arg(
+		long = "finish-with",
+		env = "YTDL_FINISH_WITH",
+		value_enum,
+		conflicts_with = "open_tagger"
+	)]
+	pub finish_with: Option<DownloadFinishAction>,
+	/// Use a terminal UI for the edit stage, which lists all media instead of asking for every media separately
+	/// Falls back to the text prompts if the terminal UI could not be started
+	#[arg(long = "tui", env = "YTDL_TUI", conflicts_with = "edit_action")]
+	pub tui: bool,
 	/// Set which subtitle languages to download
 	/// see <https://github.com/yt-dlp/yt-dlp#subtitle-options>
 	#[arg(long = "sub-langs", env = "YTDL_SUB_LANGS")]
-	pub sub_langs:                 Option<String>,
-	/// Set the video container download rules
+	pub sub_langs: Option<String>,
+	/// Write subtitles as separate files (preferring ".srt", then ".vtt") instead of embedding them, which are moved along with the media
+	/// Only has a effect if subtitles are downloaded (like with "--sub-langs")
+	#[arg(long = "subs-external", env = "YTDL_SUBS_EXTERNAL")]
+	pub subs_external: bool,
+	/// Write the ".info.json" of every media, which adds metadata (uploader, upload date, duration and page url) to the archive
+	/// The ".info.json" files are moved along with the media
+	#[arg(long = "write-info-json", env = "YTDL_WRITE_INFO_JSON")]
+	pub write_info_json: bool,
+	/// Set the container videos are remuxed into
 	/// see `--remux-video` in <https://github.com/yt-dlp/yt-dlp?tab=readme-ov-file#post-processing-options>
-	#[arg(long = "video-format", default_value_t=String::from("mkv"))]
-	pub video_format:              String,
-	/// Set audio container download rules
-	/// see `--remux-video` in <https://github.com/yt-dlp/yt-dlp?tab=readme-ov-file#post-processing-options>
-	#[arg(long = "audio-format", default_value_t=String::from("best"))]
-	pub audio_format:              String,
+	#[arg(long = "video-format", value_enum, default_value_t = VideoFormat::Mkv)]
+	pub video_format: VideoFormat,
+	/// Set the format audio is converted to in audio-only mode, "best" keeps the format as downloaded
+	/// see `--audio-format` in <https://github.com/yt-dlp/yt-dlp?tab=readme-ov-file#post-processing-options>
+	#[arg(long = "audio-format", value_enum, default_value_t = AudioFormat::Best)]
+	pub audio_format: AudioFormat,
+	/// Set what to do when a file with the same name already exists in the output directory
+	/// If not set, interactive mode will ask and non-interactive mode will use "number"
+	#[arg(long = "collision-policy", value_enum)]
+	pub collision_policy: Option<CollisionPolicy>,
+	/// Keep the original files and overwritten files in a trash directory when moving to the output directory, instead of removing them
+	/// The last move can be undone with "restore"
+	#[arg(long = "use-trash", env = "YTDL_USE_TRASH")]
+	pub use_trash: bool,
+	/// Error if any youtube-dl output line could not be parsed, instead of only warning
+	/// Useful to detect youtube-dl output format changes early, like in CI
+	#[arg(long = "strict-parse")]
+	pub strict_parse: bool,
+	/// Set the maximum total bytes to download across all URLs in this run
+	/// Once reached, the media currently downloading will be finished, but no new media will be started
+	#[arg(long = "max-total-bytes")]
+	pub max_total_bytes: Option<u64>,
+	/// Do not start new URLs once the temporary or output directory has less than this space available, with a optional suffix "K", "M" or "G" (1024 based), like "500M"
+	/// Set to "0" to disable the check
+	#[arg(long = "min-free-space", env = "YTDL_MIN_FREE_SPACE", default_value = "1G", value_parser = crate::utils::parse_byte_size)]
+	pub min_free_space: u64,
+	/// Only start downloading new URLs inside the given daily time window, like "01:00-07:00" (local time)
+	/// Outside of the window, it will wait until the window starts again before starting the next URL
+	#[arg(long = "run-window")]
+	pub run_window: Option<RunWindow>,
+	/// Only find editable files in recovery which match all filters, can be specified multiple times
+	/// Supported filters: "provider=NAME", "max-age=AGE" (like "30m", "12h", "2d"), "type=audio|video"
+	/// Example: --recover-filter "provider=youtube" --recover-filter "max-age=2d"
+	#[arg(long = "recover-filter")]
+	pub recover_filter: Vec<RecoverFilter>,
+	/// Show the last N warning / error log lines (like youtube-dl warnings) under the progress bar instead of above it
+	/// The lines still shown when the download finishes will be printed normally
+	/// Only has a effect in interactive mode
+	#[arg(long = "log-tail")]
+	pub log_tail: Option<usize>,
+	/// Set how download progress is shown, "json" prints every progress event as a JSON line to stdout (for wrapping ytdlr in other tools)
+	/// Other messages are printed to stderr while downloading in "json" mode
+	#[arg(long = "progress-format", value_enum, default_value_t = ProgressFormat::Bar)]
+	pub progress_format: ProgressFormat,
+	/// Write the URLs that failed or were not started in this run to "failed-<timestamp>.txt" in the given directory
+	/// The file can be used again with "--retry-file"
+	#[arg(long = "failed-file-dir")]
+	pub failed_file_dir: Option<PathBuf>,
+	/// Read additional URLs from the given file, one per line (empty lines and lines starting with "#" are ignored)
+	/// Like a file written by "--failed-file-dir"
+	#[arg(long = "retry-file")]
+	pub retry_file: Option<PathBuf>,
+	/// Calculate a audio fingerprint (with "fpcalc" from chromaprint) for downloaded audio and store it in the archive
+	/// Warns when a new media sounds like media already in the archive (like the same song from a different id or provider)
+	#[arg(long = "fingerprint")]
+	pub fingerprint: bool,
+	/// Normalize the loudness of downloaded audio-only media (with the ffmpeg "loudnorm" filter in two passes) before editing
+	/// This re-encodes the audio, keeping its codec, sample rate and bitrate
+	#[arg(long = "normalize-audio", env = "YTDL_NORMALIZE_AUDIO")]
+	pub normalize_audio: bool,
+	/// Calculate the ReplayGain (track gain and peak, with the ffmpeg "ebur128" filter) of audio media and write it as tags before finishing
+	/// Only "mp3", "opus", "ogg" and "flac" files are tagged
+	#[arg(long = "replaygain", env = "YTDL_REPLAYGAIN")]
+	pub replaygain: bool,
+	/// Warn once youtube-dl has not had any output for this many seconds while downloading or post-processing (like merging or remuxing) a media
+	#[arg(long = "stall-timeout")]
+	pub stall_timeout: Option<u64>,
+	/// Stop youtube-dl once a media is stalled (see "--stall-timeout"), the media is then retried like a transient error (see "--retries")
+	#[arg(long = "stall-kill", requires = "stall_timeout")]
+	pub stall_kill: bool,
+	/// Disable resuming media, which has partial files left from a previous process that did not finish (like a crash)
+	/// Partial files are only resumed when no URLs are given (like when only checking the recovery)
+	#[arg(long = "no-resume-partial", env = "YTDL_NO_RESUME_PARTIAL")]
+	pub no_resume_partial: bool,
+	/// Disable embedding the thumbnail into the media (cover art) and re-applying it after editing
+	#[arg(long = "no-embed-thumbnail", env = "YTDL_NO_EMBED_THUMBNAIL")]
+	pub no_embed_thumbnail: bool,
+	/// Download a single pre-merged video format as-is, without merging, remuxing or adding metadata
+	/// This does not require ffmpeg, but the video may be of lower quality and not in "--video-format"
+	#[arg(long = "no-remux", env = "YTDL_NO_REMUX")]
+	pub no_remux: bool,
+	/// Only remux videos when needed, checked by probing their streams after the download instead of always remuxing into "--video-format"
+	/// Videos already in one of these containers (with compatible codecs) are kept as-is, others are remuxed into the first compatible one
+	/// Example: "webm,mkv" to keep AV1 / VP9 videos as WebM and remux everything else into Matroska
+	#[arg(
+		long = "remux-targets",
+		env = "YTDL_REMUX_TARGETS",
+		value_enum,
+		value_delimiter = ',',
+		conflicts_with = "no_remux"
+	)]
+	pub remux_targets: Vec<VideoFormat>,
+	/// Load cookies from a Netscape-formatted cookies file, for media that requires a login (like age-restricted or members-only media)
+	#[arg(long = "cookies", env = "YTDL_COOKIES")]
+	pub cookies: Option<PathBuf>,
+	/// Load cookies from a browser, like "firefox" or "chrome:Profile 1"
+	/// see `--cookies-from-browser` in <https://github.com/yt-dlp/yt-dlp#filesystem-options>
+	#[arg(
+		long = "cookies-from-browser",
+		env = "YTDL_COOKIES_FROM_BROWSER",
+		conflicts_with = "cookies"
+	)]
+	pub cookies_from_browser: Option<String>,
+	/// Append every download event (like starting, progress, finished, skipped, moved and errors) as JSON lines to this file
+	/// Useful for external tools to react to finished media while ytdlr is still running
+	#[arg(long = "event-log", env = "YTDL_EVENT_LOG")]
+	pub event_log: Option<PathBuf>,
+	/// Run this command (with "sh -c") once a media is downloaded and once it is moved to its final path
+	/// The notification is given as JSON on stdin and as environment variables, like "YTDLR_EVENT", "YTDLR_ID", "YTDLR_PROVIDER", "YTDLR_TITLE" and "YTDLR_PATH"
+	#[arg(long = "notify-command", env = "YTDL_NOTIFY_COMMAND")]
+	pub notify_command: Option<String>,
+	/// POST a JSON notification to this URL once a media is downloaded and once it is moved to its final path
+	/// The JSON has the fields "event" ("downloaded" or "moved"), "id", "provider", "title" and "path"
+	#[arg(long = "notify-webhook", env = "YTDL_NOTIFY_WEBHOOK")]
+	pub notify_webhook: Option<String>,
+	/// Disable normalizing input URLs before downloading
+	/// Normalizing expands youtube short / music / shorts URLs to watch URLs and removes tracking parameters (like "utm_source" or "si")
+	#[arg(long = "no-url-normalize", env = "YTDL_NO_URL_NORMALIZE")]
+	pub no_url_normalize: bool,
+	/// Resolve input URLs from known URL shorteners (like "bit.ly") to the URL they redirect to, requires "curl"
+	#[arg(long = "resolve-redirects", env = "YTDL_RESOLVE_REDIRECTS")]
+	pub resolve_redirects: bool,
+	/// Rewrite input URLs with a regex, in the format "REGEX=>REPLACEMENT", can be specified multiple times
+	/// The replacement may use captures like "$1", rewrites are applied in order after normalizing
+	/// Example: --url-rewrite "^https://invidious.example.com/=>https://www.youtube.com/"
+	#[arg(long = "url-rewrite")]
+	pub url_rewrite: Vec<UrlRewrite>,
+	/// Download the entries of playlists in reverse order (like oldest first for a channel)
+	/// The edit and finish stages follow the order the media was downloaded in
+	#[arg(long = "playlist-reverse", conflicts_with = "playlist_random")]
+	pub playlist_reverse: bool,
+	/// Download the entries of playlists in a random order
+	/// The edit and finish stages follow the order the media was downloaded in
+	#[arg(long = "playlist-random")]
+	pub playlist_random: bool,
+	/// Only download the given entries of playlists, like "1,3,5-7" or "-5:" (the last 5 entries)
+	/// see `--playlist-items` in <https://github.com/yt-dlp/yt-dlp#video-selection>
+	#[arg(long = "playlist-items")]
+	pub playlist_items: Option<String>,
+	/// Stop downloading a URL after this many media have been downloaded from it
+	#[arg(long = "max-downloads")]
+	pub max_downloads: Option<usize>,
+	/// Limit the download rate in bytes per second, with a optional suffix "K", "M" or "G" (1024 based), like "500K" or "2M"
+	#[arg(long = "limit-rate", env = "YTDL_LIMIT_RATE", value_parser = crate::utils::parse_byte_size)]
+	pub limit_rate: Option<u64>,
+	/// Only download video formats with at most this height in pixels, like "720" or "1080"
+	/// Formats with a unknown height are still downloaded
+	#[arg(long = "max-height", env = "YTDL_MAX_HEIGHT")]
+	pub max_height: Option<u32>,
+	/// Only download formats with at most this size, with a optional suffix "K", "M" or "G" (1024 based), like "500M"
+	/// When merging, the video and audio are checked separately, formats with a unknown size are still downloaded
+	#[arg(long = "max-filesize", env = "YTDL_MAX_FILESIZE", value_parser = crate::utils::parse_byte_size)]
+	pub max_filesize: Option<u64>,
+	/// Prefer formats with this codec, like "avc1", "vp9" or "av01" (or "opus" and "mp4a" for audio-only), falling back to other codecs
+	/// The codec is matched as a prefix of the video codec (or the audio codec for audio-only)
+	#[arg(long = "prefer-codec", env = "YTDL_PREFER_CODEC")]
+	pub prefer_codec: Option<String>,
+	/// Download this many fragments of fragmented formats (like HLS or DASH) at the same time, which can speed up downloads from some providers
+	/// see `--concurrent-fragments` in <https://github.com/yt-dlp/yt-dlp#download-options>
+	#[arg(long = "concurrent-fragments", env = "YTDL_CONCURRENT_FRAGMENTS")]
+	pub concurrent_fragments: Option<usize>,
+	/// Retry media that failed with a transient error (like a timeout or a server error) this many times, waiting longer between each retry
+	/// Only the failed media of a URL are downloaded again
+	#[arg(long = "retries", env = "YTDL_RETRIES", default_value_t = 0)]
+	pub retries: usize,
+	/// Download livestreams from their start instead of the current time, waiting for scheduled livestreams to begin
+	/// The progress of livestreams is shown as the downloaded bytes and elapsed time, because their total size is not known
+	/// see `--live-from-start` and `--wait-for-video` in <https://github.com/yt-dlp/yt-dlp#download-options>
+	#[arg(long = "live", env = "YTDL_LIVE")]
+	pub live: bool,
+	/// Search for the given terms and download the selected results, in addition to the URLs given
+	/// In interactive mode the results are listed for selection, in non-interactive mode all results are downloaded
+	/// Search "URLs" like "ytsearch5:some song" can also be given directly as URLs, but will not be listed for selection
+	#[arg(long = "search")]
+	pub search: Option<String>,
+	/// Set how many results "--search" finds
+	#[arg(long = "search-count", default_value_t = 5, requires = "search")]
+	pub search_count: usize,
+	/// Set the provider "--search" searches on, either "youtube" or "soundcloud"
+	#[arg(long = "search-provider", default_value = "youtube", requires = "search")]
+	pub search_provider: SearchProvider,
 	/// Add extra arguments to the ytdl command, requires usage of "="
-	/// Example: --extra-ytdl-args="--max-downloads 10"
+	/// Example: --extra-ytdl-args="--sleep-interval 5"
 	#[arg(long = "extra-ytdl-args")]
-	pub extra_ytdl_args:           Vec<String>,
+	pub extra_ytdl_args: Vec<String>,
+
+	/// The URLs to download, options can be overwritten for a single URL with "URL::option,option=value"
+	/// Supported options: "audio_only", "video", "sub_langs=LANGS" (multiple languages separated with "+")
+	/// Example: "https://www.youtube.com/watch?v=-----------::audio_only,sub_langs=en+de"
+	pub urls:          Vec<String>,
+	/// The per-URL overrides, by the index of the URL in "urls", filled by "check"
+	#[arg(skip)]
+	pub url_overrides: Vec<Option<UrlOverrides>>,
+	/// The download profiles by provider, only set from the config
+	#[arg(skip)]
+	pub profiles:      Vec<(String, DownloadProfile)>,
+}
 
-	pub urls: Vec<String>,
+impl CommandDownload {
+	/// Get the action to use in the finish stage without asking, if any is set
+	/// "--open-tagger" is treated like "--finish-with tagger"
+	#[must_use]
+	pub fn finish_action(&self) -> Option<DownloadFinishAction> {
+		if self.open_tagger {
+			return Some(DownloadFinishAction::Tagger);
+		}
+
+		return self.finish_with;
+	}
+
+	/// Get the directory of the first "--output-route" rule which matches `provider`
+	/// The returned path is relative to the output path, unless it is absolute
+	#[must_use]
+	pub fn output_route_for(&self, provider: &str) -> Option<&Path> {
+		let provider = provider.to_lowercase();
+
+		return self
+			.output_routes
+			.iter()
+			.find(|(rule, _)| return provider.contains(rule.as_str()))
+			.map(|(_, dir)| return dir.as_path());
+	}
+
+	/// Add URLs to resume before all other URLs, with the "resume" override set
+	pub fn prepend_resume_urls(&mut self, urls: Vec<String>) {
+		let count = urls.len();
+		let overrides = std::iter::repeat(Some(UrlOverrides {
+			resume: true,
+			..Default::default()
+		}))
+		.take(count);
+
+		self.urls.splice(0..0, urls);
+		self.url_overrides.splice(0..0, overrides);
+	}
+
+	/// Remove all URLs that are the same as a earlier URL, together with their overrides
+	/// Returns the removed URLs
+	pub fn dedup_urls(&mut self) -> Vec<String> {
+		let mut seen = std::collections::HashSet::new();
+		let mut removed = Vec::new();
+		// the overrides may be shorter than the urls when they have not been split yet
+		self.url_overrides.resize(self.urls.len(), None);
+
+		let (urls, url_overrides) = std::mem::take(&mut self.urls)
+			.into_iter()
+			.zip(std::mem::take(&mut self.url_overrides))
+			.filter(|(url, _)| {
+				if seen.insert(url.clone()) {
+					return true;
+				}
+
+				removed.push(url.clone());
+
+				return false;
+			})
+			.unzip();
+
+		self.urls = urls;
+		self.url_overrides = url_overrides;
+
+		return removed;
+	}
+
+	/// Get the order playlist entries should be downloaded in
+	#[must_use]
+	pub fn playlist_order(&self) -> PlaylistOrder {
+		if self.playlist_reverse {
+			return PlaylistOrder::Reverse;
+		}
+
+		if self.playlist_random {
+			return PlaylistOrder::Random;
+		}
+
+		return PlaylistOrder::Default;
+	}
+
+	/// Get the constraints for the format to download, see "--max-height", "--max-filesize" and "--prefer-codec"
+	#[must_use]
+	pub fn format_constraints(&self) -> FormatConstraints<'_> {
+		return FormatConstraints {
+			max_height:   self.max_height,
+			max_filesize: self.max_filesize,
+			prefer_codec: self.prefer_codec.as_deref(),
+		};
+	}
+
+	/// Get where youtube-dl should load cookies from, see "--cookies" and "--cookies-from-browser"
+	#[must_use]
+	pub fn cookie_source(&self) -> Option<CookieSource<'_>> {
+		if let Some(path) = &self.cookies {
+			return Some(CookieSource::File(path));
+		}
+
+		return self.cookies_from_browser.as_deref().map(CookieSource::Browser);
+	}
+
+	/// Get descriptions of all selected features which require ffmpeg for the current "urls" (including per-URL overrides)
+	/// Empty if no feature requires ffmpeg
+	#[must_use]
+	pub fn ffmpeg_features(&self) -> Vec<&'static str> {
+		let mut features = Vec::new();
+
+		if self.urls.is_empty() {
+			return features;
+		}
+
+		let audio_only: Vec<bool> = (0..self.urls.len())
+			.map(|index| {
+				return self
+					.url_overrides(index)
+					.and_then(|v| return v.audio_only)
+					.or_else(|| {
+						return self
+							.profile_for_url(&self.urls[index])
+							.and_then(|v| return v.audio_only);
+					})
+					.unwrap_or(self.audio_only_enable);
+			})
+			.collect();
+
+		if audio_only.iter().any(|v| return *v) {
+			features.push("extracting audio (\"--audio-only\" or \"::audio_only\")");
+
+			if self.normalize_audio {
+				features.push("normalizing audio loudness (\"--normalize-audio\")");
+			}
+
+			if self.replaygain {
+				features.push("calculating ReplayGain (\"--replaygain\")");
+			}
+		}
+
+		if !self.no_remux && audio_only.iter().any(|v| return !*v) {
+			features.push("merging and remuxing video (disable with \"--no-remux\")");
+		}
+
+		if !self.no_embed_thumbnail {
+			features.push("embedding thumbnails (disable with \"--no-embed-thumbnail\")");
+		}
+
+		if self.finish_with == Some(DownloadFinishAction::Organize) {
+			features.push("reading tags to organize media (\"--finish-with organize\")");
+		}
+
+		return features;
+	}
+
+	/// Get the profile of the provider of `url` (see [`crate::utils::url_provider`]), like "soundcloud" for "https://soundcloud.com/..."
+	/// This is a heuristic, because the actual provider is only known once youtube-dl ran
+	#[must_use]
+	pub fn profile_for_url(&self, url: &str) -> Option<&DownloadProfile> {
+		let provider = crate::utils::url_provider(url)?;
+
+		return self
+			.profiles
+			.iter()
+			.find(|(profile_provider, _)| return *profile_provider == provider)
+			.map(|(_, profile)| return profile);
+	}
+
+	/// Get the per-URL overrides for the URL at `index` of "urls", if any
+	#[must_use]
+	pub fn url_overrides(&self, index: usize) -> Option<&UrlOverrides> {
+		return self.url_overrides.get(index).and_then(Option::as_ref);
+	}
+
+	/// Get the URL at `index` of "urls" with its overrides in the "URL::options" format, like it was given
+	#[must_use]
+	pub fn url_with_overrides(&self, index: usize) -> String {
+		let url = &self.urls[index];
+
+		return match self.url_overrides(index) {
+			Some(overrides) => format!("{url}::{overrides}"),
+			None => url.clone(),
+		};
+	}
+}
+
+/// Options that are used for all URLs of a provider, see [`CommandDownload::profile_for_url`]
+/// Per-URL overrides take priority over the profile, the profile takes priority over the command-line options
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DownloadProfile {
+	/// Overwrite "--audio-only"
+	pub audio_only:   Option<bool>,
+	/// Overwrite "--audio-format"
+	pub audio_format: Option<AudioFormat>,
+	/// Overwrite "--video-format"
+	pub video_format: Option<VideoFormat>,
+	/// Overwrite "--sub-langs"
+	pub sub_langs:    Option<String>,
+}
+
+impl DownloadProfile {
+	/// Convert the config `profile` of `provider`, validating the formats
+	pub fn from_config(provider: &str, profile: Profile) -> Result<Self, crate::Error> {
+		let audio_format = profile
+			.audio_format
+			.map(|v| {
+				return AudioFormat::from_str(&v, true).map_err(|_| {
+					return crate::Error::other(format!("Invalid audio_format \"{v}\" in profile \"{provider}\""));
+				});
+			})
+			.transpose()?;
+		let video_format = profile
+			.video_format
+			.map(|v| {
+				return VideoFormat::from_str(&v, true).map_err(|_| {
+					return crate::Error::other(format!("Invalid video_format \"{v}\" in profile \"{provider}\""));
+				});
+			})
+			.transpose()?;
+
+		return Ok(Self {
+			audio_only: profile.audio_only,
+			audio_format,
+			video_format,
+			sub_langs: profile.sub_langs,
+		});
+	}
+}
+
+/// Options that can be overwritten for a single URL, see [`CommandDownload::urls`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct UrlOverrides {
+	/// Overwrite "--audio-only" for the URL
+	pub audio_only: Option<bool>,
+	/// Overwrite "--sub-langs" for the URL
+	pub sub_langs:  Option<String>,
+	/// Explicitly resume partially downloaded files of the URL (yt-dlp "--continue")
+	pub resume:     bool,
+}
+
+impl UrlOverrides {
+	/// Separator between the URL and the overrides
+	const URL_SEPARATOR: &'static str = "::";
+
+	/// Split the overrides from `input`, returning the URL and the overrides if there were any
+	/// The input is only split if the part after the last "::" looks like options, so that URLs containing "::" (like IPv6 hosts) still work
+	pub fn split_from_url(input: &str) -> Result<(String, Option<Self>), crate::Error> {
+		/// Regex to check if a string looks like a list of options
+		static OPTIONS_REGEX: Lazy<Regex> = Lazy::new(|| {
+			return Regex::new(r"^[a-z_]+(=[^,]*)?(,[a-z_]+(=[^,]*)?)*$").unwrap();
+		});
+
+		let Some((url, options)) = input.rsplit_once(Self::URL_SEPARATOR) else {
+			return Ok((input.to_owned(), None));
+		};
+
+		if url.is_empty() || !OPTIONS_REGEX.is_match(options) {
+			return Ok((input.to_owned(), None));
+		}
+
+		return Ok((url.to_owned(), Some(options.parse()?)));
+	}
+}
+
+impl FromStr for UrlOverrides {
+	type Err = crate::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut overrides = Self::default();
+
+		for option in s.split(',') {
+			let (key, value) = option
+				.split_once('=')
+				.map_or((option, None), |(k, v)| return (k, Some(v)));
+
+			match (key, value) {
+				("audio_only", None) => overrides.audio_only = Some(true),
+				("video", None) => overrides.audio_only = Some(false),
+				("sub_langs", Some(langs)) if !langs.is_empty() => overrides.sub_langs = Some(langs.replace('+', ",")),
+				("resume", None) => overrides.resume = true,
+				_ => {
+					return Err(crate::Error::other(format!(
+						"Unknown or invalid URL option \"{option}\", supported are \"audio_only\", \"video\", \"sub_langs=LANGS\" and \"resume\""
+					)))
+				},
+			}
+		}
+
+		return Ok(overrides);
+	}
+}
+
+impl Display for UrlOverrides {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let mut options: Vec<String> = Vec::new();
+
+		match self.audio_only {
+			Some(true) => options.push("audio_only".into()),
+			Some(false) => options.push("video".into()),
+			None => (),
+		}
+
+		if let Some(sub_langs) = self.sub_langs.as_ref() {
+			options.push(format!("sub_langs={}", sub_langs.replace(',', "+")));
+		}
+
+		if self.resume {
+			options.push("resume".into());
+		}
+
+		return write!(f, "{}", options.join(","));
+	}
 }
 
 impl Check for CommandDownload {
 	fn check(&mut self) -> Result<(), crate::Error> {
+		for (provider, _) in &mut self.output_routes {
+			*provider = provider.to_lowercase();
+		}
+
 		// apply "expand_tilde" to archive_path
 		self.output_path = match self.output_path.take() {
 			// this has to be so round-about, because i dont know of a function that would allow functionality like "and_then" but instead of returning the same value, it would return a result
@@ -424,6 +1762,100 @@ impl Check for CommandDownload {
 			None => None,
 		};
 
+		self.failed_file_dir = match self.failed_file_dir.take() {
+			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
+				return crate::Error::other("Failed File Directory was provided, but could not be expanded / fixed");
+			})?),
+			None => None,
+		};
+
+		self.event_log = match self.event_log.take() {
+			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
+				return crate::Error::other("Event Log Path was provided, but could not be expanded / fixed");
+			})?),
+			None => None,
+		};
+
+		if let Some(cookies) = self.cookies.take() {
+			let cookies = crate::utils::fix_path(cookies).ok_or_else(|| {
+				return crate::Error::other("Cookies File was provided, but could not be expanded / fixed");
+			})?;
+
+			if !cookies.is_file() {
+				return Err(crate::Error::not_a_file(
+					"Cookies File does not exist or is not a file",
+					cookies,
+				));
+			}
+
+			self.cookies = Some(cookies);
+		}
+
+		if self
+			.cookies_from_browser
+			.as_ref()
+			.is_some_and(|v| return v.trim().is_empty())
+		{
+			return Err(crate::Error::other("Cookies Browser cannot be empty"));
+		}
+
+		if self.search_count == 0 {
+			return Err(crate::Error::other("Search Count has to be at least 1"));
+		}
+
+		if self.max_downloads == Some(0) {
+			return Err(crate::Error::other("Max Downloads has to be at least 1"));
+		}
+
+		if self.playlist_items.as_ref().is_some_and(|v| return v.trim().is_empty()) {
+			return Err(crate::Error::other("Playlist Items cannot be empty"));
+		}
+
+		if self.max_height == Some(0) {
+			return Err(crate::Error::other("Max Height has to be at least 1"));
+		}
+
+		if self.max_filesize == Some(0) {
+			return Err(crate::Error::other("Max Filesize has to be at least 1"));
+		}
+
+		if self.concurrent_fragments == Some(0) {
+			return Err(crate::Error::other("Concurrent Fragments has to be at least 1"));
+		}
+
+		// the codec is put into the format selector as-is, so only allow characters that codec names consist of
+		if self.prefer_codec.as_ref().is_some_and(|v| {
+			return v.is_empty() || !v.chars().all(|c| return c.is_ascii_alphanumeric() || c == '.');
+		}) {
+			return Err(crate::Error::other(
+				"Prefer Codec has to be a codec name, like \"avc1\" or \"vp9\"",
+			));
+		}
+
+		if self.search.as_ref().is_some_and(|v| return v.trim().is_empty()) {
+			return Err(crate::Error::other("Search Terms cannot be empty"));
+		}
+
+		// add all urls from the retry-file, after the urls from the command-line
+		if let Some(retry_file) = self.retry_file.take() {
+			let retry_file = crate::utils::fix_path(retry_file).ok_or_else(|| {
+				return crate::Error::other("Retry File was provided, but could not be expanded / fixed");
+			})?;
+
+			self.urls.extend(crate::utils::read_url_file(&retry_file)?);
+			self.retry_file = Some(retry_file);
+		}
+
+		// split the per-URL overrides from the urls, also for the urls from the retry-file
+		// already split overrides are kept, so that checking again does not lose them
+		let existing_overrides = std::mem::take(&mut self.url_overrides);
+		for (index, url) in self.urls.iter_mut().enumerate() {
+			let (new_url, overrides) = UrlOverrides::split_from_url(url)?;
+			*url = new_url;
+			self.url_overrides
+				.push(overrides.or_else(|| return existing_overrides.get(index).cloned().flatten()));
+		}
+
 		return Ok(());
 	}
 }
@@ -435,22 +1867,72 @@ impl Default for CommandDownload {
 		return Self {
 			audio_editor: None,
 			output_path: None,
+			output_routes: Vec::new(),
 			video_editor: None,
 			audio_only_enable: false,
 			reapply_thumbnail_disable: false,
 			urls: Vec::new(),
+			url_overrides: Vec::new(),
+			profiles: Vec::new(),
 			archive_mode: ArchiveMode::Default,
 			print_youtubedl_log: false,
 			save_youtubedl_log: false,
 			tagger_editor: None,
 			no_check_recovery: false,
 			open_tagger: false,
+			dry_run: false,
 			sub_langs: None,
+			subs_external: false,
+			write_info_json: false,
 			player_editor: None,
 			extra_ytdl_args: Vec::new(),
 			edit_action: None,
-			video_format: String::from("mkv"),
-			audio_format: String::from("best"),
+			finish_with: None,
+			tui: false,
+			video_format: VideoFormat::Mkv,
+			audio_format: AudioFormat::Best,
+			strict_parse: false,
+			collision_policy: None,
+			use_trash: false,
+			max_total_bytes: None,
+			min_free_space: 0,
+			run_window: None,
+			recover_filter: Vec::new(),
+			log_tail: None,
+			progress_format: ProgressFormat::Bar,
+			failed_file_dir: None,
+			retry_file: None,
+			fingerprint: false,
+			normalize_audio: false,
+			replaygain: false,
+			stall_timeout: None,
+			stall_kill: false,
+			no_resume_partial: false,
+			no_embed_thumbnail: false,
+			no_remux: false,
+			remux_targets: Vec::new(),
+			cookies: None,
+			cookies_from_browser: None,
+			event_log: None,
+			notify_command: None,
+			notify_webhook: None,
+			no_url_normalize: false,
+			resolve_redirects: false,
+			url_rewrite: Vec::new(),
+			search: None,
+			search_count: 5,
+			search_provider: SearchProvider::Youtube,
+			playlist_reverse: false,
+			playlist_random: false,
+			playlist_items: None,
+			max_downloads: None,
+			limit_rate: None,
+			max_height: None,
+			max_filesize: None,
+			prefer_codec: None,
+			concurrent_fragments: None,
+			retries: 0,
+			live: false,
 		};
 	}
 }
@@ -459,40 +1941,228 @@ impl Default for CommandDownload {
 #[derive(Debug, Parser, Clone, PartialEq)]
 pub struct CommandReThumbnail {
 	/// Input Image file to use as a Thumbnail (like a jpg)
-	#[arg(short = 'i', long = "image")]
-	pub input_image_path:  PathBuf,
+	#[arg(short = 'i', long = "image", required_unless_present = "from_url")]
+	pub input_image_path:  Option<PathBuf>,
 	/// Input Media file to apply a Thumbnail on (like a mp3)
 	#[arg(short = 'm', long = "media")]
 	pub input_media_path:  PathBuf,
 	/// Output path of the final file, by default it is the same as "media"
 	#[arg(short = 'o', long = "out")]
 	pub output_media_path: Option<PathBuf>,
+	/// Fetch the Thumbnail from the original URL of the media, which is found in the Archive by the media filename
+	#[arg(long = "from-url", conflicts_with = "input_image_path")]
+	pub from_url:          bool,
+}
+
+impl Check for CommandReThumbnail {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		// apply "expand_tilde" to archive_path
+		self.input_image_path = match self.input_image_path.take() {
+			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
+				return crate::Error::other("Input Image Path was provided, but could not be expanded / fixed");
+			})?),
+			None => None,
+		};
+
+		// apply "expand_tilde" to archive_path
+		self.input_media_path = crate::utils::fix_path(&self.input_media_path).ok_or_else(|| {
+			return crate::Error::other("Input Media Path was provided, but could not be expanded / fixed");
+		})?;
+
+		// apply "expand_tilde" to archive_path
+		self.output_media_path = match self.output_media_path.take() {
+			// this has to be so round-about, because i dont know of a function that would allow functionality like "and_then" but instead of returning the same value, it would return a result
+			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
+				return crate::Error::other("Output Media Path was provided, but could not be expanded / fixed");
+			})?),
+			None => None,
+		};
+
+		// the "fix_path" is done above, to not have to seperate / repeat the processing
+		if self.output_media_path.is_none() {
+			self.output_media_path = Some(self.input_media_path.clone());
+		}
+
+		return Ok(());
+	}
 }
 
-impl Check for CommandReThumbnail {
-	fn check(&mut self) -> Result<(), crate::Error> {
-		// apply "expand_tilde" to archive_path
-		self.input_image_path = crate::utils::fix_path(&self.input_image_path).ok_or_else(|| {
-			return crate::Error::other("Input Image Path was provided, but could not be expanded / fixed");
-		})?;
+/// Run the Re-Apply Thumbnail step for many files, using the image found next to each media file
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct CommandReThumbnailBatch {
+	/// Input Media files to apply a Thumbnail on (like a mp3), the output will be the same file
+	#[arg(required = true)]
+	pub input_media_paths: Vec<PathBuf>,
+}
+
+impl Check for CommandReThumbnailBatch {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		// apply "expand_tilde" to all input_media_paths
+		for path in &mut self.input_media_paths {
+			*path = crate::utils::fix_path(path.as_path()).ok_or_else(|| {
+				return crate::Error::other("Input Media Path was provided, but could not be expanded / fixed");
+			})?;
+		}
+
+		return Ok(());
+	}
+}
+
+/// List which media of a playlist are not yet in the archive, without downloading anything
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct CommandDiff {
+	/// The playlist URL to compare against the archive
+	pub url: String,
+}
+
+impl Check for CommandDiff {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		return Ok(());
+	}
+}
+
+/// List which archive entries have no file in the library directories
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct CommandCheckArchive {
+	/// The directories (searched recursively) the media of the archive is expected in
+	#[arg(required(true))]
+	pub library_dirs: Vec<PathBuf>,
+	/// Also list files which are not in the archive
+	#[arg(long = "untracked")]
+	pub untracked:    bool,
+	/// Print the result as JSON instead of text
+	#[arg(long = "json")]
+	pub json:         bool,
+}
+
+impl Check for CommandCheckArchive {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		for dir in &mut self.library_dirs {
+			*dir = crate::utils::fix_path(&dir)
+				.ok_or_else(|| return crate::Error::other("Library Directory could not be expanded / fixed"))?;
+		}
+
+		return Ok(());
+	}
+}
+
+/// Watch a directory for dropped link files and poll subscription URLs, and download the links found
+#[derive(Debug, Parser, Clone, PartialEq)]
+#[command(mut_arg("urls", |arg| return arg.hide(true)))]
+pub struct CommandWatch {
+	/// The directory to watch for ".url", ".webloc" and ".txt" files
+	pub watch_dir:          Option<PathBuf>,
+	/// Directory where processed files are moved to, defaults to "processed" inside the watched directory
+	#[arg(long = "processed-dir")]
+	pub processed_dir:      Option<PathBuf>,
+	/// Interval in seconds in which the watched directory is checked for new files
+	#[arg(long = "interval", default_value_t = 30)]
+	pub interval:           u64,
+	/// Only process the files currently in the watched directory and poll the subscriptions once and exit
+	#[arg(long = "once")]
+	pub once:               bool,
+	/// Channel or playlist URL to poll for new media, can be specified multiple times
+	/// Defaults to "subscriptions" from the config file
+	#[arg(long = "subscription")]
+	pub subscriptions:      Vec<String>,
+	/// File with channel or playlist URLs to poll for new media, one per line (read again every poll)
+	#[arg(long = "subscriptions-file", env = "YTDL_SUBSCRIPTIONS_FILE")]
+	pub subscriptions_file: Option<PathBuf>,
+	/// Interval in seconds in which the subscriptions are polled for new media
+	#[arg(long = "poll-interval", default_value_t = 3600)]
+	pub poll_interval:      u64,
+	/// Options used for downloading the found links
+	#[command(flatten)]
+	pub download:           CommandDownload,
+}
+
+impl CommandWatch {
+	/// Get the directory where processed files are moved to, [`None`] if no directory is watched
+	pub fn processed_dir(&self) -> Option<PathBuf> {
+		return self.watch_dir.as_ref().map(|watch_dir| {
+			return self
+				.processed_dir
+				.clone()
+				.unwrap_or_else(|| return watch_dir.join("processed"));
+		});
+	}
+
+	/// Get whether there are any subscriptions to poll
+	pub fn has_subscriptions(&self) -> bool {
+		return !self.subscriptions.is_empty() || self.subscriptions_file.is_some();
+	}
+}
+
+impl Check for CommandWatch {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		if !self.download.urls.is_empty() {
+			return Err(crate::Error::other(
+				"Watch does not take URLs directly, drop them as files into the watched directory or use \"--subscription\"",
+			));
+		}
+
+		if self.watch_dir.is_none() && !self.has_subscriptions() {
+			return Err(crate::Error::other(
+				"Watch requires a directory to watch or subscriptions to poll",
+			));
+		}
+
+		if self.poll_interval == 0 {
+			return Err(crate::Error::other("Poll Interval has to be at least 1 second"));
+		}
+
+		if self.download.search.is_some() {
+			return Err(crate::Error::other("Watch does not support searching"));
+		}
+
+		if self.interval == 0 {
+			return Err(crate::Error::other("Interval has to be at least 1 second"));
+		}
 
-		// apply "expand_tilde" to archive_path
-		self.input_media_path = crate::utils::fix_path(&self.input_media_path).ok_or_else(|| {
-			return crate::Error::other("Input Media Path was provided, but could not be expanded / fixed");
-		})?;
+		self.watch_dir = match self.watch_dir.take() {
+			Some(v) => Some(
+				crate::utils::fix_path(v)
+					.ok_or_else(|| return crate::Error::other("Watch Directory could not be expanded / fixed"))?,
+			),
+			None => None,
+		};
 
-		// apply "expand_tilde" to archive_path
-		self.output_media_path = match self.output_media_path.take() {
-			// this has to be so round-about, because i dont know of a function that would allow functionality like "and_then" but instead of returning the same value, it would return a result
+		self.subscriptions_file = match self.subscriptions_file.take() {
 			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
-				return crate::Error::other("Output Media Path was provided, but could not be expanded / fixed");
+				return crate::Error::other("Subscriptions File was provided, but could not be expanded / fixed");
 			})?),
 			None => None,
 		};
 
-		// the "fix_path" is done above, to not have to seperate / repeat the processing
-		if self.output_media_path.is_none() {
-			self.output_media_path = Some(self.input_media_path.clone());
+		self.processed_dir = match self.processed_dir.take() {
+			Some(v) => Some(crate::utils::fix_path(v).ok_or_else(|| {
+				return crate::Error::other("Processed Directory was provided, but could not be expanded / fixed");
+			})?),
+			None => None,
+		};
+
+		return Check::check(&mut self.download);
+	}
+}
+
+/// Run the full download pipeline on a small media in a scratch directory
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct CommandSelfTest {
+	/// The URL of the media to test with, should be a single short media
+	#[arg(long = "url", default_value_t = String::from(SELFTEST_DEFAULT_URL))]
+	pub url:  String,
+	/// Keep the scratch directory after finishing, for inspection
+	#[arg(long = "keep")]
+	pub keep: bool,
+}
+
+/// Default media for the selftest, chosen for being short (19 seconds) and having been available for a long time
+pub const SELFTEST_DEFAULT_URL: &str = "https://www.youtube.com/watch?v=jNQXAC9IVRw";
+
+impl Check for CommandSelfTest {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		if self.url.trim().is_empty() {
+			return Err(crate::Error::other("Selftest URL cannot be empty"));
 		}
 
 		return Ok(());
@@ -526,19 +2196,45 @@ impl Check for CommandCompletions {
 	}
 }
 
-/// Unicode Terminal Testing options
-#[cfg(debug_assertions)]
+/// Terminal Check options
 #[derive(Debug, Parser, Clone, PartialEq)]
-pub struct CommandUnicodeTerminalTest {
-	/// Print full `msg_to_cluster` vec
+pub struct CommandTermCheck {
+	/// Print the code-points, width and ambiguity of every character
 	#[arg(short = 'c', long = "content")]
 	pub print_content: bool,
-	/// The string to test
-	pub string:        String,
+	/// The strings to check, defaults to a set of known problematic strings
+	pub strings:       Vec<String>,
+}
+
+impl Check for CommandTermCheck {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		return Ok(());
+	}
+}
+
+/// Restore options
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct CommandRestore {
+	/// Only print what would be restored, without changing anything
+	#[arg(long = "dry-run")]
+	pub dry_run: bool,
+}
+
+impl Check for CommandRestore {
+	fn check(&mut self) -> Result<(), crate::Error> {
+		return Ok(());
+	}
 }
 
-#[cfg(debug_assertions)]
-impl Check for CommandUnicodeTerminalTest {
+/// Check Dependencies options
+#[derive(Debug, Parser, Clone, PartialEq)]
+pub struct CommandCheckDeps {
+	/// Run the self-update of youtube-dl ("yt-dlp -U") before checking the versions
+	#[arg(long = "update")]
+	pub update: bool,
+}
+
+impl Check for CommandCheckDeps {
 	fn check(&mut self) -> Result<(), crate::Error> {
 		return Ok(());
 	}
@@ -548,7 +2244,10 @@ impl Check for CommandUnicodeTerminalTest {
 #[cfg(test)]
 mod test {
 	use super::*;
-	use std::path::Path;
+	use std::{
+		collections::BTreeMap,
+		path::Path,
+	};
 
 	mod command_download {
 		use super::*;
@@ -564,6 +2263,110 @@ mod test {
 			assert_eq!(original, cloned);
 		}
 
+		#[test]
+		fn test_output_route_for() {
+			let mut download = CommandDownload::parse_from(
+				[
+					"",
+					"--output-route",
+					"SoundCloud=Music",
+					"--output-route",
+					"twitch=/vods",
+				]
+				.iter(),
+			);
+			assert!(download.check().is_ok());
+
+			assert_eq!(Some(Path::new("Music")), download.output_route_for("soundcloud"));
+			assert_eq!(Some(Path::new("/vods")), download.output_route_for("twitchvod"));
+			assert_eq!(None, download.output_route_for("youtube"));
+		}
+
+		#[test]
+		fn test_finish_action() {
+			let download = CommandDownload::default();
+			assert_eq!(None, download.finish_action());
+
+			let download = CommandDownload::parse_from(["", "--finish-with", "move"].iter());
+			assert_eq!(Some(DownloadFinishAction::Move), download.finish_action());
+
+			let download = CommandDownload::parse_from(["", "--open-tagger"].iter());
+			assert_eq!(Some(DownloadFinishAction::Tagger), download.finish_action());
+
+			let download = CommandDownload::parse_from(["", "--finish-with", "organize"].iter());
+			assert_eq!(Some(DownloadFinishAction::Organize), download.finish_action());
+
+			assert!(CommandDownload::try_parse_from(["", "--open-tagger", "--finish-with", "move"].iter()).is_err());
+		}
+
+		#[test]
+		fn test_profile_for_url() {
+			let soundcloud = DownloadProfile {
+				audio_only: Some(true),
+				audio_format: Some(AudioFormat::Mp3),
+				..Default::default()
+			};
+			let download = CommandDownload {
+				urls: vec![
+					"https://soundcloud.com/some/track".to_owned(),
+					"https://www.youtube.com/watch?v=-----------".to_owned(),
+				],
+				profiles: vec![("soundcloud".to_owned(), soundcloud.clone())],
+				..Default::default()
+			};
+
+			assert_eq!(
+				Some(&soundcloud),
+				download.profile_for_url("https://SoundCloud.com/some/track")
+			);
+			assert_eq!(
+				None,
+				download.profile_for_url("https://www.youtube.com/watch?v=-----------")
+			);
+			// only the provider of the host matches, not any host containing the provider
+			assert_eq!(None, download.profile_for_url("https://notsoundcloud.com/some/track"));
+			assert_eq!(
+				None,
+				download.profile_for_url("https://soundcloud.example.com/some/track")
+			);
+			// the audio-only profile is taken into account for the required features
+			assert!(download
+				.ffmpeg_features()
+				.iter()
+				.any(|v| return v.starts_with("extracting audio")));
+		}
+
+		#[test]
+		fn test_download_profile_from_config() {
+			assert_eq!(
+				Ok(DownloadProfile {
+					video_format: Some(VideoFormat::Mp4),
+					sub_langs: Some("en".to_owned()),
+					..Default::default()
+				}),
+				DownloadProfile::from_config(
+					"youtube",
+					Profile {
+						video_format: Some("MP4".to_owned()),
+						sub_langs: Some("en".to_owned()),
+						..Default::default()
+					}
+				)
+			);
+			assert_eq!(
+				Err(crate::Error::other(
+					"Invalid audio_format \"wav\" in profile \"soundcloud\""
+				)),
+				DownloadProfile::from_config(
+					"soundcloud",
+					Profile {
+						audio_format: Some("wav".to_owned()),
+						..Default::default()
+					}
+				)
+			);
+		}
+
 		#[test]
 		fn test_check_outpath_fixed() {
 			// fake home
@@ -586,6 +2389,39 @@ mod test {
 			// check that the cloned(auto fixed) version matches what the manual fix is doing
 			assert_eq!(original, cloned);
 		}
+
+		#[test]
+		fn test_check_retry_file_adds_urls() {
+			let testdir = tempfile::Builder::new()
+				.prefix("ytdlr-test-retry-file-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let retry_file = testdir.as_ref().join("failed.txt");
+			std::fs::write(&retry_file, "https://b.com/2\n# comment\nhttps://c.com/3\n")
+				.expect("Expected file to be written");
+
+			let mut args = CommandDownload {
+				urls: vec!["https://a.com/1".to_owned()],
+				retry_file: Some(retry_file.clone()),
+				..Default::default()
+			};
+			assert!(args.check().is_ok());
+			assert_eq!(
+				vec![
+					"https://a.com/1".to_owned(),
+					"https://b.com/2".to_owned(),
+					"https://c.com/3".to_owned()
+				],
+				args.urls
+			);
+
+			// a missing retry-file should error
+			let mut args = CommandDownload {
+				retry_file: Some(testdir.as_ref().join("missing.txt")),
+				..Default::default()
+			};
+			assert!(args.check().is_err());
+		}
 	}
 
 	mod archive_import {
@@ -621,6 +2457,115 @@ mod test {
 		}
 	}
 
+	mod archive_migrations {
+		use super::*;
+
+		#[test]
+		fn test_check_filepath_fixed() {
+			// fake home
+			let homedir = Path::new("/custom/home");
+			std::env::set_var("HOME", homedir);
+
+			let mut init_default = ArchiveMigrations {
+				file_path:    Some(PathBuf::from("~/archive.db")),
+				migrate_only: false,
+			};
+
+			let mut cloned = init_default.clone();
+			assert!(cloned.check().is_ok());
+
+			// manually fix in the init
+			init_default.file_path = Some(homedir.join("archive.db"));
+			assert_eq!(init_default, cloned);
+		}
+	}
+
+	mod archive_search {
+		use super::*;
+
+		#[test]
+		fn test_search_key_from_str() {
+			assert_eq!(SearchKey::Filename, "File".parse().unwrap());
+			assert_eq!(SearchKey::Column(SearchColumn::Title), "title".parse().unwrap());
+			assert!("unknown".parse::<SearchKey>().is_err());
+		}
+
+		#[test]
+		fn test_check_filename_requires_library_dir() {
+			let mut init_default = ArchiveSearch {
+				queries:            vec![(SearchKey::Filename, "some".to_owned())],
+				fuzzy:              false,
+				library_dirs:       Vec::new(),
+				limit:              10,
+				result_format:      SearchResultFormat::Normal,
+				labels:             Vec::new(),
+				include_tombstones: false,
+			};
+			assert!(init_default.clone().check().is_err());
+
+			init_default.library_dirs = vec![PathBuf::from("/music")];
+			assert!(init_default.check().is_ok());
+		}
+	}
+
+	mod archive_tag {
+		use super::*;
+
+		#[test]
+		fn test_check_labels_normalized() {
+			let mut init_default = ArchiveTag {
+				queries: vec![(SearchColumn::Provider, "youtube".to_owned())],
+				add:     vec!["Music".to_owned()],
+				remove:  vec![" later ".to_owned()],
+			};
+
+			let mut cloned = init_default.clone();
+			assert!(cloned.check().is_ok());
+
+			// manually fix in the init
+			init_default.add = vec!["music".to_owned()];
+			init_default.remove = vec!["later".to_owned()];
+			assert_eq!(init_default, cloned);
+		}
+
+		#[test]
+		fn test_check_errors() {
+			// no labels to add or remove
+			let mut no_labels = ArchiveTag {
+				queries: vec![(SearchColumn::Provider, "youtube".to_owned())],
+				add:     Vec::new(),
+				remove:  Vec::new(),
+			};
+			assert!(no_labels.check().is_err());
+
+			// invalid label
+			let mut invalid_label = ArchiveTag {
+				queries: vec![(SearchColumn::Provider, "youtube".to_owned())],
+				add:     vec!["two words".to_owned()],
+				remove:  Vec::new(),
+			};
+			assert!(invalid_label.check().is_err());
+		}
+	}
+
+	mod archive_stats {
+		use super::*;
+
+		#[test]
+		fn test_parse_disk() {
+			let mut parsed = ArchiveStats::try_parse_from(["", "--disk", "--largest", "3"]).unwrap();
+			assert!(parsed.check().is_ok());
+			assert!(parsed.disk);
+			assert_eq!(3, parsed.largest);
+
+			// "--largest" is only used with "--disk"
+			assert!(ArchiveStats::try_parse_from(["", "--largest", "3"]).is_err());
+
+			let mut negative = ArchiveStats::try_parse_from(["", "--disk", "--largest=-1"]).unwrap();
+			assert!(negative.check().is_err());
+		}
+	}
+
 	mod archive_subcommands {
 		use super::*;
 
@@ -680,19 +2625,372 @@ mod test {
 		}
 	}
 
+	mod url_overrides {
+		use super::*;
+
+		#[test]
+		fn test_split_from_url() {
+			assert_eq!(
+				Ok(("https://example.com/watch?v=1".to_owned(), None)),
+				UrlOverrides::split_from_url("https://example.com/watch?v=1")
+			);
+			assert_eq!(
+				Ok((
+					"https://example.com/watch?v=1".to_owned(),
+					Some(UrlOverrides {
+						audio_only: Some(true),
+						sub_langs:  Some("en,de".to_owned()),
+						resume:     false,
+					})
+				)),
+				UrlOverrides::split_from_url("https://example.com/watch?v=1::audio_only,sub_langs=en+de")
+			);
+			assert_eq!(
+				Ok((
+					"https://example.com/".to_owned(),
+					Some(UrlOverrides {
+						audio_only: Some(false),
+						sub_langs:  None,
+						resume:     false,
+					})
+				)),
+				UrlOverrides::split_from_url("https://example.com/::video")
+			);
+			// ipv6 hosts should not be split
+			assert_eq!(
+				Ok(("http://[::1]:8080/watch".to_owned(), None)),
+				UrlOverrides::split_from_url("http://[::1]:8080/watch")
+			);
+			// unknown options should error instead of being silently ignored
+			assert!(UrlOverrides::split_from_url("https://example.com/::audio").is_err());
+			assert!(UrlOverrides::split_from_url("https://example.com/::sub_langs=").is_err());
+		}
+
+		#[test]
+		fn test_display_roundtrip() {
+			let overrides = UrlOverrides {
+				audio_only: Some(true),
+				sub_langs:  Some("en,de".to_owned()),
+				resume:     true,
+			};
+
+			assert_eq!("audio_only,sub_langs=en+de,resume", overrides.to_string());
+			assert_eq!(Ok(overrides.clone()), overrides.to_string().parse());
+		}
+
+		#[test]
+		fn test_check_download() {
+			let mut download = CommandDownload {
+				urls: vec![
+					"https://example.com/1::audio_only".to_owned(),
+					"https://example.com/2".to_owned(),
+				],
+				..Default::default()
+			};
+
+			assert!(download.check().is_ok());
+			assert_eq!(
+				vec!["https://example.com/1".to_owned(), "https://example.com/2".to_owned()],
+				download.urls
+			);
+			assert_eq!(Some(true), download.url_overrides(0).and_then(|v| return v.audio_only));
+			assert_eq!(None, download.url_overrides(1));
+			assert_eq!(None, download.url_overrides(2));
+			assert_eq!("https://example.com/1::audio_only", download.url_with_overrides(0));
+
+			// checking again should not lose the overrides
+			assert!(download.check().is_ok());
+			assert_eq!(Some(true), download.url_overrides(0).and_then(|v| return v.audio_only));
+
+			download.prepend_resume_urls(vec!["https://example.com/0".to_owned()]);
+			assert_eq!("https://example.com/0::resume", download.url_with_overrides(0));
+			assert_eq!("https://example.com/1::audio_only", download.url_with_overrides(1));
+			assert_eq!("https://example.com/2", download.url_with_overrides(2));
+		}
+
+		#[test]
+		fn test_dedup_urls() {
+			let mut download = CommandDownload {
+				urls: vec![
+					"https://example.com/1::audio_only".to_owned(),
+					"https://example.com/2".to_owned(),
+					"https://example.com/1::video".to_owned(),
+					"https://example.com/2".to_owned(),
+				],
+				..Default::default()
+			};
+			assert!(download.check().is_ok());
+
+			assert_eq!(
+				vec!["https://example.com/1".to_owned(), "https://example.com/2".to_owned()],
+				download.dedup_urls()
+			);
+			// the first url and its overrides are kept
+			assert_eq!("https://example.com/1::audio_only", download.url_with_overrides(0));
+			assert_eq!("https://example.com/2", download.url_with_overrides(1));
+			assert_eq!(2, download.urls.len());
+
+			assert!(download.dedup_urls().is_empty());
+		}
+
+		#[test]
+		fn test_check_cookies() {
+			let tmp = tempfile::Builder::new()
+				.prefix("ytdlr-test-cookies-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let cookies_path = tmp.path().join("cookies.txt");
+
+			let mut download = CommandDownload {
+				cookies: Some(cookies_path.clone()),
+				..Default::default()
+			};
+			assert!(download.check().is_err());
+
+			std::fs::write(&cookies_path, "# Netscape HTTP Cookie File\n").expect("Expected to write cookies file");
+			download.cookies = Some(cookies_path.clone());
+			assert!(download.check().is_ok());
+			assert_eq!(Some(CookieSource::File(&cookies_path)), download.cookie_source());
+
+			let mut download = CommandDownload {
+				cookies_from_browser: Some(" ".to_owned()),
+				..Default::default()
+			};
+			assert!(download.check().is_err());
+			download.cookies_from_browser = Some("firefox".to_owned());
+			assert!(download.check().is_ok());
+			assert_eq!(Some(CookieSource::Browser("firefox")), download.cookie_source());
+
+			// both options cannot be used at the same time
+			assert!(
+				CommandDownload::try_parse_from(["", "--cookies", "a.txt", "--cookies-from-browser", "firefox"])
+					.is_err()
+			);
+		}
+
+		#[test]
+		fn test_formats() {
+			let download =
+				CommandDownload::try_parse_from(["", "--audio-format", "opus", "--video-format", "webm"]).unwrap();
+			assert_eq!(AudioFormat::Opus, download.audio_format);
+			assert_eq!("opus", download.audio_format.as_str());
+			assert_eq!(VideoFormat::Webm, download.video_format);
+			assert_eq!("webm", download.video_format.as_str());
+
+			// unsupported formats are rejected before anything is spawned
+			assert!(CommandDownload::try_parse_from(["", "--audio-format", "wav"]).is_err());
+			assert!(CommandDownload::try_parse_from(["", "--video-format", "avi"]).is_err());
+		}
+
+		#[test]
+		fn test_remux_targets() {
+			let download = CommandDownload::try_parse_from(["", "--remux-targets", "webm,mkv"]).unwrap();
+			assert_eq!(vec![VideoFormat::Webm, VideoFormat::Mkv], download.remux_targets);
+
+			assert!(CommandDownload::try_parse_from(["", "--remux-targets", "webm,avi"]).is_err());
+			assert!(CommandDownload::try_parse_from(["", "--remux-targets", "webm", "--no-remux"]).is_err());
+		}
+
+		#[test]
+		fn test_format_constraints() {
+			let mut download = CommandDownload::try_parse_from([
+				"",
+				"--max-height",
+				"720",
+				"--max-filesize",
+				"100M",
+				"--prefer-codec",
+				"avc1",
+			])
+			.unwrap();
+			assert!(download.check().is_ok());
+			assert_eq!(
+				FormatConstraints {
+					max_height:   Some(720),
+					max_filesize: Some(100 * 1024 * 1024),
+					prefer_codec: Some("avc1"),
+				},
+				download.format_constraints()
+			);
+
+			// codecs are put into the format selector as-is, so selector syntax is rejected
+			let mut download = CommandDownload::try_parse_from(["", "--prefer-codec", "avc1]/best"]).unwrap();
+			assert!(download.check().is_err());
+
+			let mut download = CommandDownload::try_parse_from(["", "--max-height", "0"]).unwrap();
+			assert!(download.check().is_err());
+		}
+
+		#[test]
+		fn test_ffmpeg_features() {
+			let mut download = CommandDownload::default();
+			assert!(download.ffmpeg_features().is_empty());
+
+			download.urls = vec!["https://example.com/1".to_owned()];
+			assert_eq!(2, download.ffmpeg_features().len());
+
+			download.no_remux = true;
+			download.no_embed_thumbnail = true;
+			// normalizing only applies to audio-only media
+			download.normalize_audio = true;
+			assert!(download.ffmpeg_features().is_empty());
+			download.normalize_audio = false;
+
+			// a per-url audio-only override still requires ffmpeg
+			download.urls = vec!["https://example.com/1::audio_only".to_owned()];
+			assert!(download.check().is_ok());
+			assert_eq!(
+				vec!["extracting audio (\"--audio-only\" or \"::audio_only\")"],
+				download.ffmpeg_features()
+			);
+
+			download.normalize_audio = true;
+			download.replaygain = true;
+			assert_eq!(3, download.ffmpeg_features().len());
+		}
+	}
+
+	mod command_self_test {
+		use super::*;
+
+		#[test]
+		fn test_check() {
+			let mut default = CommandSelfTest::parse_from([""].iter());
+			assert_eq!(SELFTEST_DEFAULT_URL, default.url);
+			assert!(default.check().is_ok());
+
+			let mut empty_url = CommandSelfTest::parse_from(["", "--url", " "].iter());
+			assert!(empty_url.check().is_err());
+		}
+	}
+
+	mod run_window {
+		use super::*;
+
+		/// Test helper to create a [`NaiveTime`]
+		fn time(hour: u32, min: u32) -> NaiveTime {
+			return NaiveTime::from_hms_opt(hour, min, 0).unwrap();
+		}
+
+		#[test]
+		fn test_from_str() {
+			assert_eq!(
+				RunWindow {
+					start: time(1, 0),
+					end:   time(7, 30),
+				},
+				RunWindow::from_str("01:00-07:30").unwrap()
+			);
+			assert!(RunWindow::from_str("01:00").is_err());
+			assert!(RunWindow::from_str("01:00-25:00").is_err());
+			assert!(RunWindow::from_str("01:00-01:00").is_err());
+		}
+
+		#[test]
+		fn test_contains() {
+			let window = RunWindow::from_str("01:00-07:00").unwrap();
+			assert!(window.contains(time(1, 0)));
+			assert!(window.contains(time(6, 59)));
+			assert!(!window.contains(time(7, 0)));
+			assert!(!window.contains(time(0, 59)));
+
+			// wrapping around midnight
+			let window = RunWindow::from_str("23:00-06:00").unwrap();
+			assert!(window.contains(time(23, 30)));
+			assert!(window.contains(time(0, 0)));
+			assert!(window.contains(time(5, 59)));
+			assert!(!window.contains(time(6, 0)));
+			assert!(!window.contains(time(22, 59)));
+		}
+
+		#[test]
+		fn test_duration_until_start() {
+			let window = RunWindow::from_str("01:00-07:00").unwrap();
+			assert_eq!(std::time::Duration::ZERO, window.duration_until_start(time(2, 0)));
+			assert_eq!(
+				std::time::Duration::from_secs(30 * 60),
+				window.duration_until_start(time(0, 30))
+			);
+			// start is on the next day
+			assert_eq!(
+				std::time::Duration::from_secs(2 * 60 * 60),
+				window.duration_until_start(time(23, 0))
+			);
+		}
+	}
+
+	mod remove_target {
+		use super::*;
+
+		#[test]
+		fn test_from_str() {
+			assert_eq!(
+				RemoveTarget::Media {
+					provider: "youtube".to_owned(),
+					id:       "some-id".to_owned(),
+				},
+				RemoveTarget::from_str(" youtube:some-id ").unwrap()
+			);
+			assert_eq!(
+				RemoveTarget::Url("https://www.youtube.com/watch?v=some-id".to_owned()),
+				RemoveTarget::from_str("https://www.youtube.com/watch?v=some-id").unwrap()
+			);
+			assert!(RemoveTarget::from_str("some-id").is_err());
+			assert!(RemoveTarget::from_str("youtube:").is_err());
+			assert!(RemoveTarget::from_str(":some-id").is_err());
+		}
+	}
+
+	mod recover_filter {
+		use super::*;
+
+		#[test]
+		fn test_from_str() {
+			assert_eq!(
+				RecoverFilter::Provider("youtube".to_owned()),
+				RecoverFilter::from_str("provider=YouTube").unwrap()
+			);
+			assert_eq!(
+				RecoverFilter::MaxAge(std::time::Duration::from_secs(2 * 60 * 60 * 24)),
+				RecoverFilter::from_str("max-age=2d").unwrap()
+			);
+			assert_eq!(
+				RecoverFilter::MaxAge(std::time::Duration::from_secs(90)),
+				RecoverFilter::from_str("age=90").unwrap()
+			);
+			assert_eq!(
+				RecoverFilter::Type(FileType::Audio),
+				RecoverFilter::from_str("type=audio").unwrap()
+			);
+			assert!(RecoverFilter::from_str("type=image").is_err());
+			assert!(RecoverFilter::from_str("max-age=twod").is_err());
+			assert!(RecoverFilter::from_str("unknown=value").is_err());
+			assert!(RecoverFilter::from_str("provider").is_err());
+		}
+	}
+
 	mod cli_derive {
 		use super::*;
 
 		#[test]
 		fn test_check() {
 			let init_default = CliDerive {
-				verbosity:    0,
-				tmp_path:     None,
-				debugger:     false,
-				archive_path: None,
-				explicit_tty: None,
-				force_color:  false,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   None,
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   None,
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			let mut cloned = init_default.clone();
@@ -707,13 +3005,22 @@ mod test {
 			std::env::set_var("HOME", homedir);
 
 			let mut init_default = CliDerive {
-				verbosity:    0,
-				tmp_path:     None,
-				debugger:     false,
-				archive_path: Some(PathBuf::from("~/somedir")),
-				explicit_tty: None,
-				force_color:  false,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   Some(PathBuf::from("~/somedir")),
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   None,
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			let mut cloned = init_default.clone();
@@ -731,13 +3038,22 @@ mod test {
 			std::env::set_var("HOME", homedir);
 
 			let mut init_default = CliDerive {
-				verbosity:    0,
-				tmp_path:     Some(PathBuf::from("~/somedir")),
-				debugger:     false,
-				archive_path: None,
-				explicit_tty: None,
-				force_color:  false,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       Some(PathBuf::from("~/somedir")),
+				debugger:       false,
+				archive_path:   None,
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   None,
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			let mut cloned = init_default.clone();
@@ -748,28 +3064,148 @@ mod test {
 			assert_eq!(init_default, cloned);
 		}
 
+		#[test]
+		fn test_apply_config() {
+			let mut init_default = CliDerive {
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   Some(PathBuf::from("/from/cli.db")),
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   None,
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload {
+					sub_langs: Some("en".to_owned()),
+					..Default::default()
+				}),
+			};
+
+			init_default
+				.apply_config(Config {
+					archive_path: Some(PathBuf::from("/from/config.db")),
+					tmp_path: Some(PathBuf::from("/from/config/tmp")),
+					ytdl_bin: Some(PathBuf::from("/venv/bin/yt-dlp")),
+					audio_editor: Some(PathBuf::from("audacity")),
+					sub_langs: Some("de".to_owned()),
+					profiles: Some(BTreeMap::from([(
+						"SoundCloud".to_owned(),
+						Profile {
+							audio_only: Some(true),
+							audio_format: Some("mp3".to_owned()),
+							..Default::default()
+						},
+					)])),
+					..Default::default()
+				})
+				.unwrap();
+
+			// command-line values take priority
+			assert_eq!(Some(PathBuf::from("/from/cli.db")), init_default.archive_path);
+			// unset values are taken from the config
+			assert_eq!(Some(PathBuf::from("/from/config/tmp")), init_default.tmp_path);
+			assert_eq!(Some(PathBuf::from("/venv/bin/yt-dlp")), init_default.ytdl_bin);
+
+			let SubCommands::Download(download) = init_default.subcommands else {
+				panic!("Expected subcommand to be Download");
+			};
+			assert_eq!(Some("en".to_owned()), download.sub_langs);
+			assert_eq!(Some(PathBuf::from("audacity")), download.audio_editor);
+			assert_eq!(None, download.output_path);
+			assert_eq!(
+				vec![(
+					"soundcloud".to_owned(),
+					DownloadProfile {
+						audio_only: Some(true),
+						audio_format: Some(AudioFormat::Mp3),
+						..Default::default()
+					}
+				)],
+				download.profiles
+			);
+		}
+
+		#[test]
+		fn test_load_config_watch() {
+			let testdir = tempfile::Builder::new()
+				.prefix("ytdlr-test-load-config-watch-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let config_path = testdir.as_ref().join("config.toml");
+			std::fs::write(
+				&config_path,
+				"audio_editor = \"audacity\"\nsub_langs = \"de\"\nsubscriptions = [\"https://a.com/channel\"]\n",
+			)
+			.expect("Expected file to be written");
+
+			let mut parsed = CliDerive::try_parse_from([
+				"ytdlr",
+				"--config",
+				config_path.to_str().expect("Expected the path to be UTF-8"),
+				"watch",
+				"--once",
+				"--sub-langs",
+				"en",
+			])
+			.unwrap();
+			parsed.load_config().unwrap();
+
+			let SubCommands::Watch(watch) = parsed.subcommands else {
+				panic!("Expected subcommand to be Watch");
+			};
+			assert_eq!(vec!["https://a.com/channel".to_owned()], watch.subscriptions);
+			// the download options of watch also use the config, with command-line values taking priority
+			assert_eq!(Some(PathBuf::from("audacity")), watch.download.audio_editor);
+			assert_eq!(Some("en".to_owned()), watch.download.sub_langs);
+		}
+
 		#[test]
 		fn test_is_interactive_explicit() {
 			let explicit_disable = CliDerive {
-				verbosity:    0,
-				tmp_path:     None,
-				debugger:     false,
-				archive_path: None,
-				explicit_tty: Some(false),
-				force_color:  false,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   None,
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   Some(false),
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			assert!(!explicit_disable.is_interactive());
 
 			let explicit_enable = CliDerive {
-				verbosity:    0,
-				tmp_path:     None,
-				debugger:     false,
-				archive_path: None,
-				explicit_tty: Some(true),
-				force_color:  false,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   None,
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   Some(true),
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			assert!(explicit_enable.is_interactive());
@@ -778,13 +3214,22 @@ mod test {
 		#[test]
 		fn test_enable_colors_forced() {
 			let explicit_disable = CliDerive {
-				verbosity:    0,
-				tmp_path:     None,
-				debugger:     false,
-				archive_path: None,
-				explicit_tty: None,
-				force_color:  true,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   None,
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   None,
+				force_color:    true,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			assert!(explicit_disable.enable_colors());
@@ -793,29 +3238,142 @@ mod test {
 		#[test]
 		fn test_enable_colors_forced_interactive() {
 			let explicit_disable = CliDerive {
-				verbosity:    0,
-				tmp_path:     None,
-				debugger:     false,
-				archive_path: None,
-				explicit_tty: Some(false),
-				force_color:  false,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   None,
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   Some(false),
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			assert!(!explicit_disable.enable_colors());
 
 			let explicit_enable = CliDerive {
-				verbosity:    0,
-				tmp_path:     None,
-				debugger:     false,
-				archive_path: None,
-				explicit_tty: Some(true),
-				force_color:  false,
-				subcommands:  SubCommands::Download(CommandDownload::default()),
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   None,
+				named_archives: Vec::new(),
+				archive_name:   None,
+				archive_routes: Vec::new(),
+				explicit_tty:   Some(true),
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
 			};
 
 			assert!(explicit_enable.enable_colors());
 		}
+
+		#[test]
+		fn test_resolve_archive_path() {
+			let mut init_default = CliDerive {
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   Some(PathBuf::from("/default.db")),
+				named_archives: vec![
+					("music".to_owned(), PathBuf::from("/music.db")),
+					("videos".to_owned(), PathBuf::from("/videos.db")),
+				],
+				archive_name:   None,
+				archive_routes: vec![
+					("SoundCloud".to_owned(), "music".to_owned()),
+					("youtube".to_owned(), "videos".to_owned()),
+				],
+				explicit_tty:   None,
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
+			};
+			assert!(init_default.check().is_ok());
+
+			assert_eq!(Some(Path::new("/default.db")), init_default.resolve_archive_path());
+			assert_eq!(
+				Some(Path::new("/music.db")),
+				init_default.resolve_archive_path_for_provider("soundcloud")
+			);
+			assert_eq!(
+				Some(Path::new("/videos.db")),
+				init_default.resolve_archive_path_for_provider("youtube")
+			);
+			assert_eq!(
+				Some(Path::new("/default.db")),
+				init_default.resolve_archive_path_for_provider("vimeo")
+			);
+			assert_eq!(
+				Some(Path::new("/music.db")),
+				init_default.resolve_archive_path_for_url("https://soundcloud.com/some/track")
+			);
+			// a URL uses the same archive as the finished media of its provider
+			assert_eq!(
+				Some(Path::new("/videos.db")),
+				init_default.resolve_archive_path_for_url("https://www.youtube.com/watch?v=soundcloud")
+			);
+			assert_eq!(
+				Some(Path::new("/videos.db")),
+				init_default.resolve_archive_path_for_url("https://youtu.be/-----------")
+			);
+			assert_eq!(
+				Some(Path::new("/default.db")),
+				init_default.resolve_archive_path_for_url("https://vimeo.com/123456")
+			);
+
+			// "--archive-name" takes priority over everything
+			init_default.archive_name = Some("videos".to_owned());
+			assert_eq!(Some(Path::new("/videos.db")), init_default.resolve_archive_path());
+			assert_eq!(
+				Some(Path::new("/videos.db")),
+				init_default.resolve_archive_path_for_provider("soundcloud")
+			);
+		}
+
+		#[test]
+		fn test_check_unknown_archive_name() {
+			let mut init_default = CliDerive {
+				verbosity:      0,
+				tmp_path:       None,
+				debugger:       false,
+				archive_path:   None,
+				named_archives: vec![("music".to_owned(), PathBuf::from("/music.db"))],
+				archive_name:   None,
+				archive_routes: vec![("soundcloud".to_owned(), "unknown".to_owned())],
+				explicit_tty:   None,
+				force_color:    false,
+				assume_yes:     false,
+				lang:           None,
+				nice:           None,
+				ionice:         None,
+				config_path:    None,
+				ytdl_bin:       None,
+				subcommands:    SubCommands::Download(CommandDownload::default()),
+			};
+			assert!(init_default.check().is_err());
+
+			init_default.archive_routes = Vec::new();
+			init_default.archive_name = Some("unknown".to_owned());
+			assert!(init_default.check().is_err());
+		}
 	}
 
 	mod command_re_thumbnail {
@@ -825,9 +3383,10 @@ mod test {
 		fn test_check() {
 			// initial value
 			let mut init_default = CommandReThumbnail {
-				input_image_path:  PathBuf::from("/hello/image.jpg"),
+				input_image_path:  Some(PathBuf::from("/hello/image.jpg")),
 				input_media_path:  PathBuf::from("/hello/media.mp3"),
 				output_media_path: None,
+				from_url:          false,
 			};
 
 			let mut cloned = init_default.clone();
@@ -847,9 +3406,10 @@ mod test {
 
 			// initial value
 			let mut init_default = CommandReThumbnail {
-				input_image_path:  PathBuf::from("~/image.jpg"),
+				input_image_path:  Some(PathBuf::from("~/image.jpg")),
 				input_media_path:  PathBuf::from("~/media.mp3"),
 				output_media_path: None,
+				from_url:          false,
 			};
 
 			let mut cloned = init_default.clone();
@@ -857,7 +3417,7 @@ mod test {
 			assert!(cloned.check().is_ok());
 
 			// manually fix in the init
-			init_default.input_image_path = homedir.join("image.jpg");
+			init_default.input_image_path = Some(homedir.join("image.jpg"));
 			init_default.input_media_path = homedir.join("media.mp3");
 			init_default.output_media_path = Some(init_default.input_media_path.clone());
 			// compare cloned manual and function execution
@@ -872,9 +3432,10 @@ mod test {
 
 			// initial value
 			let mut init_default = CommandReThumbnail {
-				input_image_path:  PathBuf::from("~/image.jpg"),
+				input_image_path:  Some(PathBuf::from("~/image.jpg")),
 				input_media_path:  PathBuf::from("~/media.mp3"),
 				output_media_path: Some(PathBuf::from("~/out.mp3")),
+				from_url:          false,
 			};
 
 			let mut cloned = init_default.clone();
@@ -882,11 +3443,54 @@ mod test {
 			assert!(cloned.check().is_ok());
 
 			// manually fix in the init
-			init_default.input_image_path = homedir.join("image.jpg");
+			init_default.input_image_path = Some(homedir.join("image.jpg"));
 			init_default.input_media_path = homedir.join("media.mp3");
 			init_default.output_media_path = Some(homedir.join("out.mp3"));
 			// compare cloned manual and function execution
 			assert_eq!(init_default, cloned);
 		}
+
+		#[test]
+		fn test_from_url() {
+			let parsed = CommandReThumbnail::try_parse_from(["", "--media", "/hello/media.mp3", "--from-url"]).unwrap();
+			assert!(parsed.from_url);
+			assert_eq!(None, parsed.input_image_path);
+
+			// either a image or "--from-url" is required, but not both
+			assert!(CommandReThumbnail::try_parse_from(["", "--media", "/hello/media.mp3"]).is_err());
+			assert!(CommandReThumbnail::try_parse_from([
+				"",
+				"--media",
+				"/hello/media.mp3",
+				"--image",
+				"/hello/image.jpg",
+				"--from-url"
+			])
+			.is_err());
+		}
+	}
+	mod command_re_thumbnail_batch {
+		use super::*;
+
+		#[test]
+		fn test_check_inputpaths_fixed() {
+			// fake home
+			let homedir = Path::new("/custom/home");
+			std::env::set_var("HOME", homedir);
+
+			// initial value
+			let mut init_default = CommandReThumbnailBatch {
+				input_media_paths: vec![PathBuf::from("~/media.mp3"), PathBuf::from("/hello/media.mkv")],
+			};
+
+			let mut cloned = init_default.clone();
+			// test to run the check and transform
+			assert!(cloned.check().is_ok());
+
+			// manually fix in the init
+			init_default.input_media_paths = vec![homedir.join("media.mp3"), PathBuf::from("/hello/media.mkv")];
+			// compare cloned manual and function execution
+			assert_eq!(init_default, cloned);
+		}
 	}
 }
@@ -0,0 +1,284 @@
+use std::io::Write;
+
+use indicatif::ProgressBar;
+use ratatui::crossterm::event::{
+	self,
+	Event,
+};
+
+use crate::{
+	clap_conf::{
+		ArchiveBrowse,
+		CliDerive,
+	},
+	commands::{
+		download::{
+			check_termination,
+			tui::{
+				TuiTerminal,
+				INPUT_POLL_INTERVAL,
+			},
+		},
+		search::format_media_normal,
+	},
+	utils,
+};
+use diesel::prelude::*;
+use libytdlr::{
+	data::{
+		sql_models::Media,
+		sql_schema::media_archive,
+	},
+	diesel,
+	error::IOErrorToError,
+	main::archive::labels::{
+		add_labels,
+		get_labels,
+		normalize_label,
+		remove_labels,
+	},
+	main::archive::remove::tombstone_entries,
+};
+
+mod tui;
+
+use tui::{
+	BrowseKeyOutcome,
+	BrowseTuiState,
+};
+
+/// Prefix to force a search, for searching text that would otherwise be a command (like a number)
+const FORCE_SEARCH_PREFIX: char = '/';
+
+/// Handler function for the "archive browse" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_browse(main_args: &CliDerive, sub_args: &ArchiveBrowse) -> Result<(), crate::Error> {
+	if !main_args.is_interactive() {
+		return Err(crate::Error::other("Browse is only available in interactive mode"));
+	}
+
+	if utils::assume_yes() {
+		return Err(crate::Error::other("Browse cannot be used with \"--assume-yes\""));
+	}
+
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Browse!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	crate::utils::set_progressbar(&bar, main_args);
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	if sub_args.tui {
+		match TuiTerminal::init() {
+			Ok(tui) => return browse_tui(tui, &mut connection, sub_args.limit),
+			Err(err) => println!("Could not start the terminal UI, falling back to text prompts: {err}"),
+		}
+	}
+
+	println!(
+		"Type to search by title or media id, a number to select a result, \"q\" to quit\n\
+		Prefix the search with \"{FORCE_SEARCH_PREFIX}\" to search for a number or \"q\""
+	);
+
+	let mut results: Vec<Media> = Vec::new();
+
+	loop {
+		print!("browse> ");
+		// ensure the message is printed before reading
+		std::io::stdout().flush().attach_location_err("stdout flush")?;
+
+		let raw_input = utils::read_line_terminable()?;
+
+		// a empty read without a new-line means STDIN has been closed
+		if raw_input.is_empty() {
+			break;
+		}
+
+		let input = raw_input.trim();
+
+		if input.is_empty() {
+			continue;
+		}
+
+		if input == "q" {
+			break;
+		}
+
+		if let Ok(number) = input.parse::<usize>() {
+			let Some(index) = number.checked_sub(1).filter(|i| return *i < results.len()) else {
+				println!("No result with number {number}");
+				continue;
+			};
+
+			if media_actions(&mut connection, &results[index])? {
+				// removed media should not be selectable anymore, the numbers of the other results stay the same on purpose
+				println!("Result {number} has been removed, search again to update the numbers");
+			}
+			continue;
+		}
+
+		let search = input.strip_prefix(FORCE_SEARCH_PREFIX).unwrap_or(input);
+		results = search_media(&mut connection, search, sub_args.limit)?;
+
+		if results.is_empty() {
+			println!("No Results found");
+			continue;
+		}
+
+		for (index, media) in results.iter().enumerate() {
+			println!("{:>3}: {}", index + 1, format_media_normal(&mut connection, media)?);
+		}
+	}
+
+	return Ok(());
+}
+
+/// Browse the archive in a terminal UI, which searches while typing
+/// The actions for a selected media are run with the text prompts while the terminal UI is suspended
+fn browse_tui(mut tui: TuiTerminal, connection: &mut SqliteConnection, limit: i64) -> Result<(), crate::Error> {
+	let mut state = BrowseTuiState::default();
+	let mut results = search_media(connection, state.search(), limit)?;
+	state.set_results(format_results(connection, &results)?);
+
+	loop {
+		check_termination()?;
+
+		tui.draw(|frame| return state.draw(frame))?;
+
+		// poll instead of blocking, so that termination requests are still handled
+		if !event::poll(INPUT_POLL_INTERVAL).attach_location_err("tui event poll")? {
+			continue;
+		}
+
+		let Event::Key(key) = event::read().attach_location_err("tui event read")? else {
+			continue;
+		};
+
+		match state.handle_key(key) {
+			BrowseKeyOutcome::None => continue,
+			BrowseKeyOutcome::Done => return Ok(()),
+			BrowseKeyOutcome::Search => (),
+			BrowseKeyOutcome::Select => {
+				let Some(media) = state.selected().and_then(|i| return results.get(i)) else {
+					continue;
+				};
+
+				tui.suspend()?;
+				let removed = media_actions(connection, media);
+				tui.resume()?;
+
+				if removed? {
+					state.set_status(format!("Removed \"{}\"", media.title));
+				}
+			},
+		}
+
+		// search again, because either the search or the media have changed
+		results = search_media(connection, state.search(), limit)?;
+		state.set_results(format_results(connection, &results)?);
+	}
+}
+
+/// Format all `results` for the terminal UI
+fn format_results(connection: &mut SqliteConnection, results: &[Media]) -> Result<Vec<String>, crate::Error> {
+	return results
+		.iter()
+		.map(|media| return format_media_normal(connection, media))
+		.collect();
+}
+
+/// Search for all media that contain `search` in the title or have `search` as the media id, newest first
+/// Removed media which are kept as tombstones are not found
+fn search_media(connection: &mut SqliteConnection, search: &str, limit: i64) -> Result<Vec<Media>, crate::Error> {
+	return media_archive::table
+		.filter(
+			media_archive::title
+				.like(format!("%{search}%"))
+				.or(media_archive::media_id.eq(search)),
+		)
+		.filter(media_archive::removed_at.is_null())
+		.order(media_archive::_id.desc())
+		.limit(limit)
+		.load::<Media>(connection)
+		.map_err(|err| return crate::Error::from(err));
+}
+
+/// Ask for and run actions for a selected media, until going back or the media is removed
+/// Returns `true` if the media has been removed
+fn media_actions(connection: &mut SqliteConnection, media: &Media) -> Result<bool, crate::Error> {
+	println!("Selected: {}", format_media_normal(connection, media)?);
+
+	loop {
+		match utils::get_input(
+			"Show [u]rl, change [t]ags, [r]emove or go [b]ack?",
+			&["u", "t", "r", "B"],
+			"b",
+		)?
+		.as_str()
+		{
+			"u" => match utils::media_url(&media.provider, &media.media_id) {
+				Some(url) => println!("{url}"),
+				None => println!(
+					"No URL known for provider \"{}\", media id: \"{}\"",
+					media.provider, media.media_id
+				),
+			},
+			"t" => change_labels(connection, media)?,
+			"r" => {
+				if utils::get_input("Remove this media from the archive?", &["y", "N"], "n")? != "y" {
+					continue;
+				}
+
+				// the same as "archive remove", which keeps a tombstone so that the media is not downloaded again
+				tombstone_entries(connection, &[media._id])?;
+				println!("Removed \"{}\" from the archive", media.title);
+
+				return Ok(true);
+			},
+			"b" => return Ok(false),
+			_ => unreachable!("get_input should only return a OK value from the possible array"),
+		}
+	}
+}
+
+/// Ask for labels to add or remove for a media and apply them
+fn change_labels(connection: &mut SqliteConnection, media: &Media) -> Result<(), crate::Error> {
+	let current_labels = get_labels(connection, media._id)?;
+	if current_labels.is_empty() {
+		println!("Current Labels: (none)");
+	} else {
+		println!("Current Labels: {}", current_labels.join(", "));
+	}
+	print!("Labels to add, prefix with \"-\" to remove (separated by spaces): ");
+	// ensure the message is printed before reading
+	std::io::stdout().flush().attach_location_err("stdout flush")?;
+
+	let input = utils::read_line_terminable()?;
+
+	let mut add = Vec::new();
+	let mut remove = Vec::new();
+
+	for label in input.split_whitespace() {
+		let (list, label) = match label.strip_prefix('-') {
+			Some(label) => (&mut remove, label),
+			None => (&mut add, label),
+		};
+
+		match normalize_label(label) {
+			Ok(label) => list.push(label),
+			Err(err) => {
+				println!("Ignoring invalid label \"{label}\": {err}");
+			},
+		}
+	}
+
+	let removed = remove_labels(connection, &[media._id], &remove)?;
+	let added = add_labels(connection, &[media._id], &add)?;
+
+	println!("Added {added} and removed {removed} Labels");
+
+	return Ok(());
+}
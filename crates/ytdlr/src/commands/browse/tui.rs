@@ -0,0 +1,241 @@
+//! Module for the terminal UI of "archive browse", which searches while typing and selects results with the arrow keys
+//!
+//! The actions for a selected media are run outside of the terminal UI with the text prompts
+
+use ratatui::{
+	crossterm::event::{
+		KeyCode,
+		KeyEvent,
+		KeyEventKind,
+		KeyModifiers,
+	},
+	layout::{
+		Constraint,
+		Layout,
+	},
+	style::{
+		Modifier,
+		Style,
+	},
+	widgets::{
+		Block,
+		Borders,
+		List,
+		ListItem,
+		ListState,
+		Paragraph,
+	},
+	Frame,
+};
+
+/// The key bindings shown at the bottom of the browse TUI
+const BROWSE_KEYS: &str = "Type to search, Up/Down: select, Enter: actions, Esc: quit";
+
+/// What a key press in the browse TUI results in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowseKeyOutcome {
+	/// Nothing has to be done besides re-drawing
+	None,
+	/// The search has changed and the results have to be searched again
+	Search,
+	/// Run the actions on the selected result
+	Select,
+	/// Browsing is done
+	Done,
+}
+
+/// State of the browse TUI, separate from the terminal so that it can be tested
+#[derive(Debug, Default)]
+pub struct BrowseTuiState {
+	/// The current search
+	search:   String,
+	/// The formatted results of the current search
+	results:  Vec<String>,
+	/// The index of the selected result
+	selected: usize,
+	/// The message of the status line
+	status:   String,
+}
+
+impl BrowseTuiState {
+	/// Get the current search
+	pub fn search(&self) -> &str {
+		return &self.search;
+	}
+
+	/// Get the index of the selected result, [`None`] if there are no results
+	pub fn selected(&self) -> Option<usize> {
+		if self.results.is_empty() {
+			return None;
+		}
+
+		return Some(self.selected);
+	}
+
+	/// Set the formatted results of the current search
+	/// The selection is kept if possible, so that the selection stays after running actions
+	pub fn set_results(&mut self, results: Vec<String>) {
+		self.selected = self.selected.min(results.len().saturating_sub(1));
+		self.results = results;
+	}
+
+	/// Set the message of the status line
+	pub fn set_status<S: Into<String>>(&mut self, status: S) {
+		self.status = status.into();
+	}
+
+	/// Handle a key press
+	pub fn handle_key(&mut self, key: KeyEvent) -> BrowseKeyOutcome {
+		// ignore key releases and repeats, which some platforms report as separate events
+		if key.kind != KeyEventKind::Press {
+			return BrowseKeyOutcome::None;
+		}
+
+		// "Ctrl+C" does not send a signal in raw mode
+		if key.modifiers.contains(KeyModifiers::CONTROL) {
+			if key.code == KeyCode::Char('c') {
+				return BrowseKeyOutcome::Done;
+			}
+
+			return BrowseKeyOutcome::None;
+		}
+
+		self.status.clear();
+
+		return match key.code {
+			KeyCode::Esc => BrowseKeyOutcome::Done,
+			KeyCode::Enter => {
+				if self.results.is_empty() {
+					BrowseKeyOutcome::None
+				} else {
+					BrowseKeyOutcome::Select
+				}
+			},
+			KeyCode::Down => {
+				if self.selected + 1 < self.results.len() {
+					self.selected += 1;
+				}
+
+				BrowseKeyOutcome::None
+			},
+			KeyCode::Up => {
+				self.selected = self.selected.saturating_sub(1);
+
+				BrowseKeyOutcome::None
+			},
+			KeyCode::Backspace => {
+				if self.search.pop().is_none() {
+					return BrowseKeyOutcome::None;
+				}
+
+				self.selected = 0;
+
+				BrowseKeyOutcome::Search
+			},
+			KeyCode::Char(char) => {
+				self.search.push(char);
+				self.selected = 0;
+
+				BrowseKeyOutcome::Search
+			},
+			_ => BrowseKeyOutcome::None,
+		};
+	}
+
+	/// Draw the state onto `frame`
+	pub fn draw(&self, frame: &mut Frame) {
+		let [search_area, list_area, status_area, keys_area] = Layout::vertical([
+			Constraint::Length(3),
+			Constraint::Min(1),
+			Constraint::Length(1),
+			Constraint::Length(1),
+		])
+		.areas(frame.area());
+
+		frame.render_widget(
+			Paragraph::new(self.search.as_str()).block(Block::default().borders(Borders::ALL).title("Search")),
+			search_area,
+		);
+
+		let items: Vec<ListItem> = self.results.iter().map(|v| return ListItem::new(v.as_str())).collect();
+		let list = List::new(items)
+			.block(
+				Block::default()
+					.borders(Borders::ALL)
+					.title(format!("Results ({})", self.results.len())),
+			)
+			.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+			.highlight_symbol("> ");
+		let mut list_state = ListState::default().with_selected(self.selected());
+
+		frame.render_stateful_widget(list, list_area, &mut list_state);
+		frame.render_widget(Paragraph::new(self.status.as_str()), status_area);
+		frame.render_widget(
+			Paragraph::new(BROWSE_KEYS).style(Style::default().add_modifier(Modifier::DIM)),
+			keys_area,
+		);
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Test helper to create a key press
+	fn press(code: KeyCode) -> KeyEvent {
+		return KeyEvent::new(code, KeyModifiers::NONE);
+	}
+
+	#[test]
+	fn test_search() {
+		let mut state = BrowseTuiState::default();
+
+		// there is nothing to select without results
+		assert_eq!(None, state.selected());
+		assert_eq!(BrowseKeyOutcome::None, state.handle_key(press(KeyCode::Enter)));
+		assert_eq!(BrowseKeyOutcome::None, state.handle_key(press(KeyCode::Backspace)));
+
+		assert_eq!(BrowseKeyOutcome::Search, state.handle_key(press(KeyCode::Char('a'))));
+		assert_eq!(BrowseKeyOutcome::Search, state.handle_key(press(KeyCode::Char('b'))));
+		assert_eq!("ab", state.search());
+		assert_eq!(BrowseKeyOutcome::Search, state.handle_key(press(KeyCode::Backspace)));
+		assert_eq!("a", state.search());
+
+		// "q" is part of the search, only "Esc" and "Ctrl+C" quit
+		assert_eq!(BrowseKeyOutcome::Search, state.handle_key(press(KeyCode::Char('q'))));
+		assert_eq!(BrowseKeyOutcome::Done, state.handle_key(press(KeyCode::Esc)));
+		assert_eq!(
+			BrowseKeyOutcome::Done,
+			state.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+		);
+		assert_eq!("aq", state.search());
+
+		// releases are ignored
+		let mut release = press(KeyCode::Char('x'));
+		release.kind = KeyEventKind::Release;
+		assert_eq!(BrowseKeyOutcome::None, state.handle_key(release));
+	}
+
+	#[test]
+	fn test_selection() {
+		let mut state = BrowseTuiState::default();
+		state.set_results(vec!["first".to_owned(), "second".to_owned(), "third".to_owned()]);
+		assert_eq!(Some(0), state.selected());
+
+		assert_eq!(BrowseKeyOutcome::None, state.handle_key(press(KeyCode::Up)));
+		assert_eq!(Some(0), state.selected());
+		state.handle_key(press(KeyCode::Down));
+		state.handle_key(press(KeyCode::Down));
+		state.handle_key(press(KeyCode::Down));
+		assert_eq!(Some(2), state.selected());
+		assert_eq!(BrowseKeyOutcome::Select, state.handle_key(press(KeyCode::Enter)));
+
+		// the selection is kept for new results, as long as it exists
+		state.set_results(vec!["first".to_owned(), "second".to_owned()]);
+		assert_eq!(Some(1), state.selected());
+
+		// a changed search starts at the first result again
+		state.handle_key(press(KeyCode::Char('a')));
+		assert_eq!(Some(0), state.selected());
+	}
+}
@@ -0,0 +1,64 @@
+use indicatif::ProgressBar;
+use libytdlr::main::check_archive::{
+	check_archive,
+	find_library_files,
+};
+
+use crate::{
+	clap_conf::{
+		CliDerive,
+		CommandCheckArchive,
+	},
+	utils,
+};
+
+/// Handler function for the "check-archive" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_check_archive(main_args: &CliDerive, sub_args: &CommandCheckArchive) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Check-Archive!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	let files = find_library_files(&sub_args.library_dirs)?;
+	let files_len = files.len();
+
+	let mut result = check_archive(&mut connection, &files)?;
+
+	if !sub_args.untracked {
+		result.untracked.clear();
+	}
+
+	if sub_args.json {
+		println!("{}", serde_json::to_string(&result)?);
+
+		return Ok(());
+	}
+
+	for entry in &result.missing {
+		println!("- [{}:{}] {}", entry.provider, entry.media_id, entry.title);
+	}
+
+	for path in &result.untracked {
+		println!("? {}", path.display());
+	}
+
+	println!(
+		"{} archive entries found in {} files: {} missing{}",
+		result.found,
+		files_len,
+		result.missing.len(),
+		if sub_args.untracked {
+			format!(", {} files not in archive", result.untracked.len())
+		} else {
+			String::new()
+		}
+	);
+
+	return Ok(());
+}
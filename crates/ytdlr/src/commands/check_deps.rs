@@ -0,0 +1,181 @@
+//! Module for the "check-deps" command, which reports the versions of the required tools
+
+use std::fmt::Display;
+
+use libytdlr::{
+	chrono::NaiveDate,
+	main::download::MINIMAL_YTDL_VERSION,
+	spawn::{
+		ffmpeg::ffmpeg_version,
+		ytdl::{
+			ytdl_parse_version_naivedate,
+			ytdl_update,
+			ytdl_version,
+		},
+	},
+};
+
+use crate::clap_conf::{
+	CliDerive,
+	CommandCheckDeps,
+};
+
+/// The status of a dependency
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DepStatus {
+	/// The dependency is installed and can be used
+	Ok,
+	/// The dependency is installed, but older than the minimal version
+	Outdated,
+	/// The dependency could not be found or started
+	Missing,
+}
+
+impl Display for DepStatus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return write!(
+			f,
+			"{}",
+			match self {
+				DepStatus::Ok => "ok",
+				DepStatus::Outdated => "outdated",
+				DepStatus::Missing => "missing",
+			}
+		);
+	}
+}
+
+/// The checked state of a single dependency
+#[derive(Debug, PartialEq)]
+struct DepReport {
+	/// The name of the dependency
+	name:    &'static str,
+	/// The found version, if the dependency could be started
+	version: Option<String>,
+	/// The status of the dependency
+	status:  DepStatus,
+}
+
+impl DepReport {
+	/// Format the report as a line, which is tab-separated for scripting: "name<TAB>version<TAB>status"
+	/// The version is "-" if it could not be determined
+	fn fmt_line(&self) -> String {
+		return format!(
+			"{}\t{}\t{}",
+			self.name,
+			self.version.as_deref().unwrap_or("-"),
+			self.status
+		);
+	}
+}
+
+/// Get the report for youtube-dl from the result of getting its version
+/// Versions lower than `minimal` (or which cannot be parsed as a date) are reported as outdated
+fn ytdl_report(version: Option<String>, minimal: NaiveDate) -> DepReport {
+	let status = match version.as_deref() {
+		None => DepStatus::Missing,
+		Some(v) if ytdl_parse_version_naivedate(v).is_ok_and(|v| return v >= minimal) => DepStatus::Ok,
+		Some(_) => DepStatus::Outdated,
+	};
+
+	return DepReport {
+		name: "yt-dlp",
+		version,
+		status,
+	};
+}
+
+/// Get the report for ffmpeg from the result of getting its version
+/// ffmpeg has no minimal version, so it is only checked to be present
+fn ffmpeg_report(version: Option<String>) -> DepReport {
+	let status = if version.is_some() {
+		DepStatus::Ok
+	} else {
+		DepStatus::Missing
+	};
+
+	return DepReport {
+		name: "ffmpeg",
+		version,
+		status,
+	};
+}
+
+/// Handler function for the "check-deps" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_check_deps(main_args: &CliDerive, sub_args: &CommandCheckDeps) -> Result<(), crate::Error> {
+	// resolve the binary first, so that "--update" updates the standalone binary found as a fallback
+	let found_ytdl_version = if let Ok((ytdl_bin, version)) = main_args.require_ytdl() {
+		if sub_args.update {
+			// the update output goes to stderr, to keep stdout for the report
+			eprintln!("Updating \"{}\"", ytdl_bin.to_string_lossy());
+			ytdl_update(&ytdl_bin)?;
+
+			// the version changed with a successful update
+			ytdl_version(&ytdl_bin).ok()
+		} else {
+			Some(version)
+		}
+	} else {
+		if sub_args.update {
+			eprintln!("youtube-dl could not be found, not updating");
+		}
+
+		None
+	};
+
+	let reports = [
+		ytdl_report(found_ytdl_version, *MINIMAL_YTDL_VERSION),
+		ffmpeg_report(ffmpeg_version().ok()),
+	];
+
+	for report in &reports {
+		println!("{}", report.fmt_line());
+	}
+
+	let failed: Vec<&str> = reports
+		.iter()
+		.filter(|v| return v.status != DepStatus::Ok)
+		.map(|v| return v.name)
+		.collect();
+
+	if !failed.is_empty() {
+		return Err(crate::Error::other(format!(
+			"Dependencies are missing or outdated: {}",
+			failed.join(", ")
+		)));
+	}
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_ytdl_report() {
+		let minimal = NaiveDate::from_ymd_opt(2023, 3, 3).unwrap();
+
+		assert_eq!(
+			"yt-dlp\t2024.01.01\tok",
+			ytdl_report(Some("2024.01.01".to_owned()), minimal).fmt_line()
+		);
+		assert_eq!(
+			"yt-dlp\t2023.03.03\tok",
+			ytdl_report(Some("2023.03.03".to_owned()), minimal).fmt_line()
+		);
+		assert_eq!(
+			"yt-dlp\t2022.12.31\toutdated",
+			ytdl_report(Some("2022.12.31".to_owned()), minimal).fmt_line()
+		);
+		assert_eq!("yt-dlp\t-\tmissing", ytdl_report(None, minimal).fmt_line());
+	}
+
+	#[test]
+	fn test_ffmpeg_report() {
+		assert_eq!("ffmpeg\t6.1.1\tok", ffmpeg_report(Some("6.1.1".to_owned())).fmt_line());
+		assert_eq!("ffmpeg\t-\tmissing", ffmpeg_report(None).fmt_line());
+	}
+}
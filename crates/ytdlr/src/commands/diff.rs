@@ -0,0 +1,65 @@
+use indicatif::ProgressBar;
+use libytdlr::main::diff::{
+	diff_playlist,
+	list_playlist,
+};
+
+use crate::{
+	clap_conf::{
+		CliDerive,
+		CommandDiff,
+	},
+	utils,
+};
+
+/// Handler function for the "diff" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_diff(main_args: &CliDerive, sub_args: &CommandDiff) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path_for_url(&sub_args.url) else {
+		return Err(crate::Error::other("Archive is required for Diff!"));
+	};
+
+	let (ytdl_bin, _) = main_args.require_ytdl()?;
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	println!("Listing playlist \"{}\"", sub_args.url);
+
+	let entries = list_playlist(&ytdl_bin, &sub_args.url)?;
+	let entries_len = entries.len();
+
+	let diff = diff_playlist(&mut connection, entries)?;
+
+	for media in &diff.missing {
+		println!(
+			"+ [{}:{}] {}",
+			media.provider,
+			media.id,
+			media.title.as_deref().unwrap_or_default()
+		);
+	}
+
+	for media in &diff.disappeared {
+		println!(
+			"- [{}:{}] {}",
+			media.provider,
+			media.id,
+			media.title.as_deref().unwrap_or_default()
+		);
+	}
+
+	println!(
+		"{} entries in playlist: {} not in archive, {} already archived, {} archived but unavailable, {} unavailable and not archived",
+		entries_len,
+		diff.missing.len(),
+		diff.archived,
+		diff.disappeared.len(),
+		diff.unavailable
+	);
+
+	return Ok(());
+}
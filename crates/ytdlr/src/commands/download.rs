@@ -1,11 +1,29 @@
 use crate::{
 	clap_conf::{
 		CliDerive,
+		CollisionPolicy,
 		CommandDownload,
 		DownloadEditAction,
+		DownloadFinishAction,
+		ProgressFormat,
+		RunWindow,
+		VideoFormat,
 	},
 	commands::download::quirks::apply_metadata,
+	event_log,
+	logger,
+	messages::{
+		tr,
+		trf,
+		Msg,
+	},
+	notification,
 	state::DownloadState,
+	trash::{
+		self,
+		TrashEntry,
+		TrashOperation,
+	},
 	utils,
 };
 use colored::{
@@ -14,31 +32,86 @@ use colored::{
 };
 use diesel::SqliteConnection;
 use indicatif::{
+	HumanBytes,
+	HumanDuration,
 	ProgressBar,
 	ProgressDrawTarget,
+	ProgressState,
 	ProgressStyle,
 };
 use libytdlr::{
+	chrono::Local,
 	data::{
 		self,
-		cache::media_info::MediaInfo,
+		cache::{
+			media_info::MediaInfo,
+			media_stage::MediaStage,
+		},
 	},
 	diesel,
 	error::IOErrorToError,
 	main::{
 		self,
+		archive::hashes::{
+			find_by_content_hash,
+			sha256_file,
+			store_content_hash,
+		},
 		download::{
 			DownloadOptions as _,
+			DownloadProgress,
+			LiveProgress,
+			SkippedType,
+			TransferProgress,
+			PATHS_TEMP_DIR,
+			PATHS_THUMBNAIL_DIR,
 			YTDL_ARCHIVE_PREFIX,
 		},
+		postprocess::{
+			loudnorm::{
+				loudnorm_many,
+				LoudnormProgress,
+				LoudnormTarget,
+			},
+			replaygain::{
+				self,
+				replaygain_many,
+				ReplayGainProgress,
+			},
+		},
+		rethumbnail::{
+			ReThumbnailItem,
+			ReThumbnailProgress,
+		},
+		url_preprocess::{
+			validate_url,
+			UrlPreprocessor,
+		},
+	},
+	spawn::{
+		ffmpeg,
+		fpcalc::require_fpcalc_installed,
+		ytdl::{
+			self,
+			YtdlCapabilities,
+		},
 	},
-	spawn::ytdl::require_ytdl_installed,
+	utils::disk_space::check_available_space,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::{
+	borrow::Cow,
 	cell::RefCell,
-	collections::HashMap,
+	collections::{
+		HashMap,
+		HashSet,
+	},
+	ffi::{
+		OsStr,
+		OsString,
+	},
+	fmt::Display,
 	io::{
 		BufRead,
 		BufReader,
@@ -52,6 +125,8 @@ use std::{
 	time::Duration,
 };
 
+pub mod tui;
+
 /// Static for easily referencing the 100% length for a progressbar
 const PG_PERCENT_100: u64 = 100;
 /// Static size the Download Progress Style will take (plus some spacers)
@@ -66,7 +141,7 @@ struct Recovery {
 }
 
 /// Helper to quickly check for termination
-fn check_termination() -> Result<(), crate::Error> {
+pub(crate) fn check_termination() -> Result<(), crate::Error> {
 	// handle terminate
 	if crate::TERMINATE
 		.read()
@@ -79,6 +154,37 @@ fn check_termination() -> Result<(), crate::Error> {
 	return Ok(());
 }
 
+/// Wait until the current local time is inside of "run_window"
+/// Termination requests are checked while waiting
+fn wait_for_run_window(run_window: &RunWindow) -> Result<(), crate::Error> {
+	let wait = run_window.duration_until_start(Local::now().time());
+
+	if wait.is_zero() {
+		return Ok(());
+	}
+
+	println!(
+		"Outside of the run window, waiting {} until {} before starting the next URL",
+		HumanDuration(wait),
+		run_window.start.format("%H:%M")
+	);
+
+	loop {
+		check_termination()?;
+
+		let remaining = run_window.duration_until_start(Local::now().time());
+
+		if remaining.is_zero() {
+			break;
+		}
+
+		// sleep in small steps to not delay termination requests
+		std::thread::sleep(remaining.min(Duration::from_secs(1)));
+	}
+
+	return Ok(());
+}
+
 impl Recovery {
 	/// Recovery file prefix
 	const RECOVERY_PREFIX: &'static str = "recovery_";
@@ -171,24 +277,37 @@ impl Recovery {
 	#[inline]
 	pub fn fmt_line(media: &data::cache::media_info::MediaInfo) -> String {
 		return format!(
-			"'{}'-'{}'-{}\n",
+			"'{}'-'{}'-'{}'-{}\n",
 			media.provider,
 			media.id,
+			media.stage,
 			media.title.as_ref().expect("Expected downloaded media to have a title")
 		);
 	}
 
 	/// Try to create a MediaInfo from a given line
 	pub fn try_from_line(line: &str) -> Option<data::cache::media_info::MediaInfo> {
-		/// Regex for getting the provider,id,title from a line in a recovery format
-		/// cap1: provider, cap2: id, cap3: title
+		/// Regex for getting the provider,id,stage,title from a line in a recovery format
+		/// the stage is optional, because older recovery files did not contain it
+		/// cap1: provider, cap2: id, cap3: stage (optional), cap4: title
 		static FROM_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
-			return Regex::new(r"(?mi)^'([^']+)'-'([^']+)'-(.+)$").unwrap();
+			return Regex::new(
+				r"(?mi)^'([^']+)'-'([^']+)'-(?:'(discovered|downloaded|reviewed|edited|moved|archived)'-)?(.+)$",
+			)
+			.unwrap();
 		});
 
 		let cap = FROM_LINE_REGEX.captures(line)?;
-
-		return Some(data::cache::media_info::MediaInfo::new(&cap[2], &cap[1]).with_title(&cap[3]));
+		let stage = cap
+			.get(3)
+			.and_then(|v| return v.as_str().parse::<MediaStage>().ok())
+			.unwrap_or_default();
+
+		return Some(
+			data::cache::media_info::MediaInfo::new(&cap[2], &cap[1])
+				.with_title(&cap[4])
+				.with_stage(stage),
+		);
 	}
 
 	/// Try to read the recovery from the given path
@@ -244,16 +363,14 @@ impl Recovery {
 #[derive(Debug, PartialEq)]
 struct MediaHelper {
 	/// The actual [`MediaInfo`] that is stored
-	data:    MediaInfo,
+	data:  MediaInfo,
 	/// The order of which it was added / downloaded in (used for editing loop)
-	order:   usize,
-	/// Extra Comment if necessary
-	comment: Option<String>,
+	order: usize,
 }
 
 impl MediaHelper {
-	pub fn new(data: MediaInfo, order: usize, comment: Option<String>) -> Self {
-		return Self { data, order, comment };
+	pub fn new(data: MediaInfo, order: usize) -> Self {
+		return Self { data, order };
 	}
 }
 
@@ -285,28 +402,38 @@ impl MediaInfoArr {
 
 	/// Insert a [`MediaInfo`] into the map, updating the old value if existed and returing the old value
 	pub fn insert(&mut self, mediainfo: MediaInfo) -> Option<MediaHelper> {
-		return self._insert(mediainfo, None);
+		return self._insert(mediainfo);
 	}
 	/// Insert a [`MediaInfo`] into the map, updating the old value if existed and returing the old value
-	/// with a comment
-	pub fn insert_with_comment<C>(&mut self, mediainfo: MediaInfo, comment: C) -> Option<MediaHelper>
-	where
-		C: Into<String>,
-	{
+	/// for media that was not downloaded in the current run (like from a recovery file), which may not be in the archive yet
+	pub fn insert_recovered(&mut self, mediainfo: MediaInfo) -> Option<MediaHelper> {
 		self.has_maybe_uninserted = true;
-		return self._insert(mediainfo, Some(comment.into()));
+		return self._insert(mediainfo);
 	}
 
-	/// Helper for [`Self::insert`] and [`Self::insert_with_comment`] to only have one implementation
-	fn _insert(&mut self, mediainfo: MediaInfo, comment: Option<String>) -> Option<MediaHelper> {
-		let order = self.next_order;
-		self.next_order += 1;
-
+	/// Helper for [`Self::insert`] and [`Self::insert_recovered`] to only have one implementation
+	/// Updating a existing value keeps its order, so that the order always is the order the media was first added in
+	fn _insert(&mut self, mediainfo: MediaInfo) -> Option<MediaHelper> {
 		let key = format!("{}-{}", mediainfo.provider.as_ref(), mediainfo.id);
 
-		return self
-			.mediainfo_map
-			.insert(key, MediaHelper::new(mediainfo, order, comment));
+		let order = if let Some(existing) = self.mediainfo_map.get(&key) {
+			existing.order
+		} else {
+			let order = self.next_order;
+			self.next_order += 1;
+
+			order
+		};
+
+		return self.mediainfo_map.insert(key, MediaHelper::new(mediainfo, order));
+	}
+
+	/// Get a value inside the HashMap
+	pub fn get<K>(&self, key: K) -> Option<&MediaHelper>
+	where
+		K: AsRef<str>,
+	{
+		return self.mediainfo_map.get(key.as_ref());
 	}
 
 	/// Get a value inside the HashMap mutably
@@ -336,6 +463,16 @@ impl MediaInfoArr {
 
 		return vec;
 	}
+
+	/// Get the keys of the current HashMap, sorted like [`Self::as_sorted_vec`]
+	/// useful to modify the values in order, without holding references into the HashMap
+	pub fn sorted_keys(&self) -> Vec<String> {
+		let mut vec: Vec<(&String, &MediaHelper)> = self.mediainfo_map.iter().collect();
+
+		vec.sort_by(|a, b| return a.1.order.cmp(&b.1.order));
+
+		return vec.into_iter().map(|(key, _)| return key.clone()).collect();
+	}
 }
 
 /// Truncate the given message to a lower size so that the progressbar does not do new-lines
@@ -356,6 +493,62 @@ where
 	return utils::truncate_message_display_pos(msg, display_width_available, true).to_string();
 }
 
+/// Get the progressbar message for a media that is downloading, like "526.19KiB/s ETA 01:16 Some Title"
+/// Speed and ETA are only added if known
+fn progress_message(title: &str, progress: &TransferProgress) -> String {
+	let mut parts: Vec<String> = Vec::with_capacity(3);
+
+	if let Some(speed) = progress.speed.as_ref() {
+		parts.push(speed.clone());
+	}
+
+	if let Some(eta) = progress.eta.as_ref() {
+		parts.push(format!("ETA {eta}"));
+	}
+
+	parts.push(title.to_owned());
+
+	return parts.join(" ");
+}
+
+/// Get the progressbar position for a format of a media that downloads multiple formats (like video and audio)
+/// All formats are combined into a single 0-100 scale, each format taking a equal part, like "75" for 50% of the second of 2 formats
+fn phase_position(phase: usize, total_phases: usize, percent: u8) -> u64 {
+	let total_phases = total_phases.max(1) as u64;
+	let done = (phase.max(1) as u64 - 1).min(total_phases - 1);
+
+	return (done * PG_PERCENT_100 + u64::from(percent).min(PG_PERCENT_100)) / total_phases;
+}
+
+/// Get the message for a skipped media, like "Skipped \"someid\" (already in the archive)"
+/// "media" is used instead of the id if it is not known
+fn skipped_message(id: Option<&str>, reason: &str) -> String {
+	return match id {
+		Some(id) => format!("Skipped \"{id}\" ({reason})"),
+		None => format!("Skipped media ({reason})"),
+	};
+}
+
+/// Get the progressbar message for a livestream that is downloading, like "45.21 MiB 00:00:22 2.01MiB/s Some Title"
+/// Elapsed time and speed are only added if known
+fn live_progress_message(title: &str, progress: &LiveProgress) -> String {
+	let mut parts: Vec<String> = Vec::with_capacity(4);
+
+	parts.push(HumanBytes(progress.bytes).to_string());
+
+	if let Some(elapsed) = progress.elapsed.as_ref() {
+		parts.push(elapsed.clone());
+	}
+
+	if let Some(speed) = progress.speed.as_ref() {
+		parts.push(speed.clone());
+	}
+
+	parts.push(title.to_owned());
+
+	return parts.join(" ");
+}
+
 /// Find all files that match the temporary ytdl archive name, and remove all whose pid is not alive anymore
 fn find_and_remove_tmp_archive_files(path: &Path) -> Result<(), crate::Error> {
 	if !path.is_dir() {
@@ -365,9 +558,6 @@ fn find_and_remove_tmp_archive_files(path: &Path) -> Result<(), crate::Error> {
 		));
 	}
 
-	let mut s = sysinfo::System::new();
-	s.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-
 	for file in path.read_dir().attach_path_err(path)?.filter_map(|res| {
 		let entry = res.ok()?;
 
@@ -396,7 +586,7 @@ fn find_and_remove_tmp_archive_files(path: &Path) -> Result<(), crate::Error> {
 			cap.get(1).expect("Expected group 1 to always exist").as_str()
 		};
 		let pid_of_file = {
-			let res = pid_str.parse::<usize>();
+			let res = pid_str.parse::<u32>();
 			if res.is_err() {
 				continue;
 			}
@@ -404,7 +594,7 @@ fn find_and_remove_tmp_archive_files(path: &Path) -> Result<(), crate::Error> {
 		};
 		// check that the pid of the file is actually not running anymore
 		// and just ignore them if the process exists
-		if s.process(sysinfo::Pid::from(pid_of_file)).is_some() {
+		if libytdlr::utils::is_process_alive(pid_of_file) {
 			info!("Found tmp yt-dl archive file for pid {pid_of_file}, but the process still existed");
 			continue;
 		}
@@ -418,13 +608,190 @@ fn find_and_remove_tmp_archive_files(path: &Path) -> Result<(), crate::Error> {
 	return Ok(());
 }
 
+/// Minimal time a partial file has to be unmodified, to be considered left-over from a process that did not finish
+/// This is to not take partial files of a other process that is still downloading
+const PARTIAL_MIN_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Find media which have partial files (".part") left in the temporary directory of `download_path` from a process that did not finish
+/// Media that is already in the archive is finished, so its partial files are only removed
+/// Returns the URLs of the media to resume
+fn find_partial_downloads(main_args: &CliDerive, download_path: &Path) -> Result<Vec<String>, crate::Error> {
+	use diesel::prelude::*;
+	use libytdlr::data::sql_schema::media_archive;
+
+	let temp_path = download_path.join(PATHS_TEMP_DIR);
+
+	if !temp_path.is_dir() {
+		return Ok(Vec::new());
+	}
+
+	// all partial files by media, keyed by "provider-id" to only resume a media once (like separate video and audio files)
+	let mut partial_media: HashMap<String, (MediaInfo, Vec<PathBuf>)> = HashMap::new();
+
+	for entry in temp_path.read_dir().attach_path_err(&temp_path)? {
+		let Ok(entry) = entry else {
+			continue;
+		};
+		let path = entry.path();
+		let Some(file_name) = path.file_name().and_then(|v| return v.to_str()) else {
+			continue;
+		};
+
+		// yt-dlp uses ".part" for partial files and ".part-FragN" for partial fragments
+		let Some(media_file_name) = file_name
+			.strip_suffix(".part")
+			.or_else(|| return file_name.split_once(".part-Frag").map(|v| return v.0))
+		else {
+			continue;
+		};
+
+		let is_old_enough = entry
+			.metadata()
+			.and_then(|v| return v.modified())
+			.ok()
+			.and_then(|v| return v.elapsed().ok())
+			.is_some_and(|v| return v >= PARTIAL_MIN_AGE);
+
+		if !is_old_enough {
+			debug!("Ignoring recently modified partial file \"{}\"", path.display());
+			continue;
+		}
+
+		let Some(media) = MediaInfo::try_from_filename(&media_file_name) else {
+			debug!(
+				"Ignoring partial file which does not match the output format \"{}\"",
+				path.display()
+			);
+			continue;
+		};
+
+		partial_media
+			.entry(format!("{}-{}", media.provider, media.id))
+			.or_insert_with(|| return (media, Vec::new()))
+			.1
+			.push(path);
+	}
+
+	let bar = ProgressBar::hidden();
+	let mut connections: HashMap<&Path, SqliteConnection> = HashMap::new();
+	let mut resume_urls = Vec::new();
+
+	for (media, files) in partial_media.into_values() {
+		let in_archive = match main_args.resolve_archive_path_for_provider(media.provider.as_str()) {
+			Some(ap) => {
+				let connection = match connections.entry(ap) {
+					std::collections::hash_map::Entry::Occupied(v) => v.into_mut(),
+					std::collections::hash_map::Entry::Vacant(v) => {
+						v.insert(utils::handle_connect(ap, &bar, main_args)?.1)
+					},
+				};
+
+				media_archive::table
+					.filter(media_archive::provider.eq(media.provider.as_str()))
+					.filter(media_archive::media_id.eq(&media.id))
+					.count()
+					.get_result::<i64>(connection)?
+					> 0
+			},
+			None => false,
+		};
+
+		if in_archive {
+			info!(
+				"Removing partial files of \"{}-{}\", because it is already in the archive",
+				media.provider, media.id
+			);
+
+			for file in files {
+				if let Err(err) = std::fs::remove_file(&file) {
+					warn!("Removing partial file \"{}\" failed: {}", file.display(), err);
+				}
+			}
+
+			continue;
+		}
+
+		if let Some(url) = utils::media_url(media.provider.as_str(), &media.id) {
+			resume_urls.push(url);
+		} else {
+			warn!(
+				"Found partial files for \"{}-{}\", but the URL for the provider is not known, it has to be resumed manually",
+				media.provider, media.id
+			);
+		}
+	}
+
+	// sort for a consistent order, because the directory read order is not defined
+	resume_urls.sort();
+
+	return Ok(resume_urls);
+}
+
 /// Handler function for the "download" subcommand
 /// This function is mainly to keep the code structured and sorted
 #[inline]
 pub fn command_download(main_args: &CliDerive, sub_args: &CommandDownload) -> Result<(), crate::Error> {
-	let ytdl_version = require_ytdl_installed()?;
+	let (ytdl_bin, ytdl_version) = main_args.require_ytdl()?;
+
+	if sub_args.fingerprint {
+		require_fpcalc_installed()?;
+	}
+
+	// normalize and rewrite the urls before anything uses them
+	let mut preprocessed_args = preprocess_urls(sub_args)?;
+
+	if let Some(terms) = sub_args.search.as_deref() {
+		let selected = search_urls(main_args, sub_args, &ytdl_bin, terms)?;
+
+		// dont fall back to only checking the recovery, because urls were requested
+		if selected.is_empty() && preprocessed_args.urls.is_empty() {
+			println!("No Search Results selected, nothing to download");
 
-	let only_recovery = sub_args.urls.is_empty();
+			return Ok(());
+		}
+
+		preprocessed_args.urls.extend(selected);
+	}
+
+	let only_recovery = preprocessed_args.urls.is_empty();
+
+	if preprocessed_args.dry_run {
+		return dry_run_download(main_args, &preprocessed_args, ytdl_bin, &ytdl_version);
+	}
+
+	let tmp_path = main_args.download_tmp_path();
+
+	std::fs::create_dir_all(&tmp_path).attach_path_err(&tmp_path)?;
+
+	// resume media from a process that did not finish
+	// only do this in "only_recovery" mode (no urls) to not accidentally take the partial files of other processes
+	if only_recovery && !preprocessed_args.no_resume_partial {
+		let resume_urls = find_partial_downloads(main_args, &tmp_path)?;
+
+		if !resume_urls.is_empty() {
+			println!("Resuming {} partially downloaded Media", resume_urls.len());
+
+			preprocessed_args.prepend_resume_urls(resume_urls);
+		}
+	}
+
+	let sub_args = &preprocessed_args;
+
+	let ffmpeg_features = sub_args.ffmpeg_features();
+	if !ffmpeg_features.is_empty() && ffmpeg::ffmpeg_version().is_err() {
+		return Err(crate::Error::other(format!(
+			"Could not start or find ffmpeg, which is required for:\n{}",
+			ffmpeg_features
+				.iter()
+				.map(|v| return format!("  - {v}"))
+				.collect::<Vec<String>>()
+				.join("\n")
+		)));
+	}
+
+	if let Some(event_log_path) = sub_args.event_log.as_deref() {
+		event_log::open(event_log_path)?;
+	}
 
 	if only_recovery {
 		if sub_args.no_check_recovery {
@@ -432,8 +799,9 @@ pub fn command_download(main_args: &CliDerive, sub_args: &CommandDownload) -> Re
 		}
 
 		println!(
-			"{} No URLs were provided, only checking recovery! To disable allowing 0 URLs, use \"--no-check-recovery\"",
-			"WARN".color(Color::TrueColor { r: 255, g: 135, b: 0 })
+			"{} {}",
+			"WARN".color(Color::TrueColor { r: 255, g: 135, b: 0 }),
+			tr(Msg::OnlyRecovery)
 		);
 	}
 
@@ -444,19 +812,53 @@ pub fn command_download(main_args: &CliDerive, sub_args: &CommandDownload) -> Re
 			.expect("Expected ProgressStyle template to be valid")
 			.progress_chars("#>-");
 	});
+	/// ProgressBar Style for download with the log tail, like [`DOWNLOAD_STYLE`] but with the log tail lines under it
+	static DOWNLOAD_TAIL_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+		return ProgressStyle::default_bar()
+			.template("{prefix:.dim} [{elapsed_precise}] {wide_bar:.cyan/blue} {msg}\n{log_tail}")
+			.expect("Expected ProgressStyle template to be valid")
+			.with_key("log_tail", |_: &ProgressState, w: &mut dyn std::fmt::Write| {
+				return logger::write_log_tail(w);
+			})
+			.progress_chars("#>-");
+	});
 
-	let tmp_path = main_args
-		.tmp_path
-		.as_ref()
-		.map_or_else(|| return std::env::temp_dir(), |v| return v.clone())
-		.join("ytdl_rust_tmp");
+	/// ProgressBar Style for downloading livestreams, will look like `[0/0] [00:00:00] ⠁ CustomMsg`, because there is no total size for a bar
+	static LIVE_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+		return ProgressStyle::default_spinner()
+			.template("{prefix:.dim} [{elapsed_precise}] {spinner:.cyan} {msg}")
+			.expect("Expected ProgressStyle template to be valid");
+	});
+	/// ProgressBar Style for downloading livestreams with the log tail, like [`LIVE_STYLE`] but with the log tail lines under it
+	static LIVE_TAIL_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+		return ProgressStyle::default_spinner()
+			.template("{prefix:.dim} [{elapsed_precise}] {spinner:.cyan} {msg}\n{log_tail}")
+			.expect("Expected ProgressStyle template to be valid")
+			.with_key("log_tail", |_: &ProgressState, w: &mut dyn std::fmt::Write| {
+				return logger::write_log_tail(w);
+			});
+	});
 
-	std::fs::create_dir_all(&tmp_path).attach_path_err(&tmp_path)?;
+	let style = match (sub_args.live, sub_args.log_tail.is_some()) {
+		(false, false) => DOWNLOAD_STYLE.clone(),
+		(false, true) => DOWNLOAD_TAIL_STYLE.clone(),
+		(true, false) => LIVE_STYLE.clone(),
+		(true, true) => LIVE_TAIL_STYLE.clone(),
+	};
+	let pgbar: ProgressBar = ProgressBar::new(PG_PERCENT_100).with_style(style);
+	// the progressbar is replaced by the progress output in non-bar formats, so keep it hidden
+	if ProgressOutput::new(sub_args.progress_format).uses_bar() {
+		utils::set_progressbar(&pgbar, main_args);
+	}
 
-	let pgbar: ProgressBar = ProgressBar::new(PG_PERCENT_100).with_style(DOWNLOAD_STYLE.clone());
-	utils::set_progressbar(&pgbar, main_args);
+	let ytdl_capabilities = probe_ytdl_capabilities(&ytdl_bin);
+	let mut download_state = DownloadState::new(sub_args, tmp_path, ytdl_bin, &ytdl_version, ytdl_capabilities);
 
-	let mut download_state = DownloadState::new(sub_args, tmp_path, &ytdl_version);
+	notification::open(
+		sub_args.notify_command.clone(),
+		sub_args.notify_webhook.clone(),
+		download_state.download_path(),
+	);
 
 	// already create the vec for finished media, so that the finished ones can be stored in case of error
 	let mut finished_media = MediaInfoArr::new();
@@ -469,8 +871,11 @@ pub fn command_download(main_args: &CliDerive, sub_args: &CommandDownload) -> Re
 	// recover files that are not in a recovery but are still considered editable
 	// only do this in "only_recovery" mode (no urls) to not accidentally use from other processes
 	if only_recovery {
-		for media in utils::find_editable_files(download_state.download_path())? {
-			finished_media.insert_with_comment(media, "Found Editable File");
+		for mut media in utils::find_editable_files_filtered(download_state.download_path(), &sub_args.recover_filter)?
+		{
+			// a file existing in the download directory means it has at least been downloaded
+			media.advance_stage(MediaStage::Downloaded);
+			finished_media.insert_recovered(media);
 		}
 	}
 
@@ -481,15 +886,16 @@ pub fn command_download(main_args: &CliDerive, sub_args: &CommandDownload) -> Re
 
 	// TODO: consider cross-checking archive if the files from recovery are already in the archive and get a proper title
 
-	match download_wrapper(
+	let failed_urls = match download_wrapper(
 		main_args,
 		sub_args,
 		&pgbar,
 		&mut download_state,
 		&mut finished_media,
-		only_recovery,
+		// resumed urls are still downloaded in "only_recovery" mode
+		sub_args.urls.is_empty(),
 	) {
-		Ok(()) => (),
+		Ok(v) => v,
 		Err(err) => {
 			let res = recovery.write_recovery(&finished_media);
 
@@ -500,7 +906,7 @@ pub fn command_download(main_args: &CliDerive, sub_args: &CommandDownload) -> Re
 
 			return Err(err);
 		},
-	}
+	};
 
 	// do some cleanup
 	// remove the recovery file, because of a successfull finish
@@ -511,9 +917,127 @@ pub fn command_download(main_args: &CliDerive, sub_args: &CommandDownload) -> Re
 		Recovery::remove_file(&file);
 	}
 
+	event_log::close();
+	notification::close();
+
+	// the media of all other urls is already handled, but the run should still be reported as failed
+	if failed_urls > 0 {
+		return Err(crate::Error::other(format!(
+			"{} of {} URLs failed to download",
+			failed_urls,
+			sub_args.urls.len()
+		)));
+	}
+
+	return Ok(());
+}
+
+/// Get the capabilities of the youtube-dl binary `ytdl_bin`
+/// The capabilities are optional, the version gates are used if they cannot be determined
+fn probe_ytdl_capabilities(ytdl_bin: &OsStr) -> Option<YtdlCapabilities> {
+	return match ytdl::ytdl_capabilities(ytdl_bin) {
+		Ok(v) => Some(v),
+		Err(err) => {
+			debug!(
+				"Could not determine youtube-dl capabilities, using version gates only: {}",
+				err
+			);
+
+			None
+		},
+	};
+}
+
+/// Print what "download" would do for all URLs, without downloading anything (see "--dry-run")
+/// Prints the URLs that would be processed, with the youtube-dl command and the ytdl archive lines for each
+fn dry_run_download(
+	main_args: &CliDerive,
+	sub_args: &CommandDownload,
+	ytdl_bin: OsString,
+	ytdl_version: &str,
+) -> Result<(), crate::Error> {
+	if sub_args.urls.is_empty() {
+		println!("No URLs would be processed, only the recovery would be checked");
+
+		return Ok(());
+	}
+
+	let ytdl_capabilities = probe_ytdl_capabilities(&ytdl_bin);
+	let mut download_state = DownloadState::new(
+		sub_args,
+		main_args.download_tmp_path(),
+		ytdl_bin,
+		ytdl_version,
+		ytdl_capabilities,
+	);
+	let bar = ProgressBar::hidden();
+	// the currently connected archive (and the path it was resolved as), like in "do_download"
+	let mut current_archive: Option<(PathBuf, SqliteConnection)> = None;
+	let url_len = sub_args.urls.len();
+
+	println!("Dry-Run: {url_len} URLs would be processed");
+
+	for (index, url) in sub_args.urls.iter().enumerate() {
+		download_state.set_current_url(url);
+		download_state.set_url_overrides(sub_args.url_overrides(index));
+		download_state.set_profile(sub_args.profile_for_url(url));
+		download_state.set_max_download_bytes(sub_args.max_total_bytes);
+
+		match main_args.resolve_archive_path_for_url(url) {
+			Some(ap) => {
+				let is_connected = matches!(current_archive.as_ref(), Some((path, _)) if path == ap);
+
+				if !is_connected {
+					current_archive = Some((ap.to_path_buf(), utils::handle_connect(ap, &bar, main_args)?.1));
+				}
+			},
+			None => current_archive = None,
+		}
+		let connection = current_archive.as_mut().map(|(_, connection)| return connection);
+
+		let command = libytdlr::main::download::dry_run_command(connection, &download_state)?;
+
+		println!("\nURL {}/{}: {}", index + 1, url_len, url);
+		println!(
+			"Command: {}",
+			std::iter::once(&command.program)
+				.chain(command.args.iter())
+				.map(|v| return quote_arg(v))
+				.collect::<Vec<String>>()
+				.join(" ")
+		);
+
+		match command.archive_lines {
+			None => println!("No ytdl archive would be written"),
+			Some(lines) => {
+				println!("ytdl archive lines that would be written ({}):", lines.len());
+				for line in lines {
+					println!("  {}", line.trim_end());
+				}
+			},
+		}
+	}
+
 	return Ok(());
 }
 
+/// Quote `arg` for printing, so that the printed command can be copied into a shell
+/// Arguments that only contain characters without special meaning are not quoted
+fn quote_arg(arg: &std::ffi::OsStr) -> String {
+	let arg = arg.to_string_lossy();
+
+	let is_plain = !arg.is_empty()
+		&& arg
+			.chars()
+			.all(|c| return c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/' | ':' | '=' | ',' | '+'));
+
+	if is_plain {
+		return arg.into_owned();
+	}
+
+	return format!("'{}'", arg.replace('\'', r"'\''"));
+}
+
 /// Helper enum to decide what to do in the finish media loop (to not have to nest calls)
 #[derive(Debug, PartialEq)]
 enum EditCtrl {
@@ -524,21 +1048,80 @@ enum EditCtrl {
 }
 
 /// Wrapper for [`command_download`] to house the part where in case of error a recovery needs to be written
+/// Returns the count of URLs that failed to download
 fn download_wrapper(
 	main_args: &CliDerive,
 	sub_args: &CommandDownload,
 	pgbar: &ProgressBar,
 	download_state: &mut DownloadState,
 	finished_media: &mut MediaInfoArr,
-	only_recovery: bool,
-) -> Result<(), crate::Error> {
-	if only_recovery {
-		info!("Skipping download because of \"only_recovery\"");
+	skip_download: bool,
+) -> Result<usize, crate::Error> {
+	let mut failed_count = 0;
+	// the media that was already known before downloading (like from a recovery), which is not post-processed again
+	let previous_keys: HashSet<String> = finished_media.sorted_keys().into_iter().collect();
+
+	if skip_download {
+		info!("Skipping download because there are no URLs");
 	} else {
-		do_download(main_args, sub_args, pgbar, download_state, finished_media)?;
+		// only keep the log tail while downloading, because only then the progressbar is drawn continuously
+		let log_tail = sub_args.log_tail.filter(|_| {
+			return main_args.is_interactive() && ProgressOutput::new(sub_args.progress_format).uses_bar();
+		});
+		if let Some(size) = log_tail {
+			logger::enable_log_tail(size);
+		}
+
+		let mut summary = DownloadSummary::default();
+		let res = do_download(main_args, sub_args, pgbar, download_state, finished_media, &mut summary);
+
+		if log_tail.is_some() {
+			logger::disable_log_tail();
+		}
+
+		// a summary is only useful if there is more than one url
+		if summary.results.len() > 1 {
+			ProgressOutput::new(sub_args.progress_format).status(format!("Summary:\n{}", summary.format_table()));
+		}
+
+		failed_count = summary.failed_count();
+
+		if let Some(failed_file_dir) = sub_args.failed_file_dir.as_ref() {
+			// keep the per-url overrides, so that retrying uses the same options
+			let failed_urls: Vec<String> = summary
+				.failed_indexes(sub_args.urls.len())
+				.into_iter()
+				.map(|index| return sub_args.url_with_overrides(index))
+				.collect();
+
+			if !failed_urls.is_empty() {
+				// only warn, because the original error (if any) is more important
+				match utils::write_failed_url_file(failed_file_dir, &failed_urls) {
+					Ok(path) => println!(
+						"Wrote {} failed or not started URLs to \"{}\", use \"--retry-file\" to retry them",
+						failed_urls.len(),
+						path.display()
+					),
+					Err(err) => warn!("Writing failed URLs file failed: {}", err),
+				}
+			}
+		}
+
+		res?;
 	}
 
 	let download_path = download_state.download_path();
+
+	if sub_args.normalize_audio {
+		let new_keys: Vec<String> = finished_media
+			.sorted_keys()
+			.into_iter()
+			.filter(|v| return !previous_keys.contains(v))
+			.collect();
+
+		normalize_media(download_path, finished_media, &new_keys);
+	}
+
 	// determines whether the "reverse" argument for "edit_media" is set
 	let mut looped_once = false;
 
@@ -553,7 +1136,7 @@ fn download_wrapper(
 		}
 	}
 
-	return Ok(());
+	return Ok(failed_count);
 }
 
 /// Characters to use if a state for the ProgressBar is unknown
@@ -787,120 +1370,479 @@ impl Default for DownloadInfo {
 	}
 }
 
-/// Do the download for all provided URL's
-fn do_download(
-	main_args: &CliDerive,
-	sub_args: &CommandDownload,
-	pgbar: &ProgressBar,
-	download_state: &mut DownloadState,
-	finished_media: &mut MediaInfoArr,
-) -> Result<(), crate::Error> {
-	let mut maybe_connection: Option<SqliteConnection> = if let Some(ap) = main_args.archive_path.as_ref() {
-		Some(utils::handle_connect(ap, pgbar, main_args)?.1)
-	} else {
-		None
-	};
+/// Output for the download progress, replaces the progressbar output when not [`ProgressFormat::Bar`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ProgressOutput {
+	/// The format to output in
+	format: ProgressFormat,
+}
 
-	// store "download_state" in a refcell, because rust complains that a borrow is made in "download_pgcb" and also later used while still in scope
-	let download_state_cell: RefCell<&mut DownloadState> = RefCell::new(download_state);
-	let download_info: RefCell<DownloadInfo> = RefCell::new(DownloadInfo::default());
-	let url_len = sub_args.urls.len();
-	set_progressbar_prefix(pgbar, &download_info.borrow().url_specific);
-	// track total count finished (no error)
-	let total_count = std::sync::atomic::AtomicUsize::new(0);
-	let download_pgcb = |dpg| match dpg {
-		main::download::DownloadProgress::UrlStarting => {
-			pgbar.reset();
-			pgbar.set_message(""); // because pgbar is not hidden and "reset" seemingly does not clear the message
-			let url_index = download_info.borrow().url_index;
-			download_info.borrow_mut().reset_for_new_url(url_index);
-		},
-		main::download::DownloadProgress::SingleStarting(id, title) => {
-			let mut download_info_borrowed = download_info.borrow_mut();
-			download_info_borrowed.url_specific.inc_current_playlist_pos(1);
+impl ProgressOutput {
+	/// Create a new output for the given format
+	fn new(format: ProgressFormat) -> Self {
+		return Self { format };
+	}
 
-			download_info_borrowed.set_single_specific(DownloadInfoSingleSpecific::new(id, title));
+	/// Get whether the progressbar should be used
+	fn uses_bar(self) -> bool {
+		return self.format == ProgressFormat::Bar;
+	}
 
-			// set to default estimate, if by the time the first is starting it has not been got
-			if !download_info_borrowed.url_specific.playlist_estimate.has_been_set() {
-				download_info_borrowed
-					.url_specific
-					.set_playlist_estimate(DEFAULT_COUNT_ESTIMATE);
-			}
+	/// Print a status message (like which URL is starting)
+	/// Printed to stderr in [`ProgressFormat::Json`] so that stdout only contains JSON lines
+	fn status<D: Display>(self, msg: D) {
+		match self.format {
+			ProgressFormat::Bar => println!("{msg}"),
+			ProgressFormat::Json => eprintln!("{msg}"),
+		}
+	}
 
-			pgbar.reset();
-			pgbar.set_length(PG_PERCENT_100); // reset length, because it may get changed because of connection insert
-			let download_info_borrowed = &download_info_borrowed.url_specific;
-			set_progressbar_prefix(pgbar, download_info_borrowed);
-			// steady-ticks have to be re-done after every "pgbar.finish" because the ticker will exit once it notices the state is "finished"
-			pgbar.enable_steady_tick(Duration::from_secs(1));
-			let title = download_info_borrowed.single_specific.as_ref().unwrap().title.as_str();
-			pgbar.set_message(truncate_message_term_width(&title));
-			pgbar.println(format!("Downloading: {}", &title));
-		},
-		main::download::DownloadProgress::SingleProgress(_maybe_id, percent) => {
-			pgbar.set_position(percent.into());
-		},
-		main::download::DownloadProgress::SingleFinished(_id) => {
-			// dont hide the progressbar so that the cli does not appear to do nothing
-			pgbar.reset();
-			pgbar.set_message(""); // because pgbar is not hidden and "reset" seemingly does not clear the message
-			pgbar.println(format!("Finished Downloading: {}", download_info.borrow().get_title()));
-			download_info.borrow_mut().reset_single_specific();
-			set_progressbar_prefix(pgbar, &download_info.borrow().url_specific);
-		},
-		main::download::DownloadProgress::UrlFinished(new_count) => {
-			pgbar.finish_and_clear();
-			let total = total_count.fetch_add(new_count, std::sync::atomic::Ordering::AcqRel) + new_count;
-			// print how many media has been downloaded since last "AllStarting" and how many in total in this run
-			let url_index = download_info.borrow().url_index;
-			pgbar.println(format!(
-				"Finished Downloading {new_count} new Media (For a total of {total} Media) (url {}/{})",
-				url_index, url_len
-			));
-			download_info.borrow_mut().reset_for_new_url(url_index);
-		},
-		main::download::DownloadProgress::PlaylistInfo(new_count) => {
-			let mut borrow = download_info.borrow_mut();
-			let borrow = &mut borrow.url_specific;
-			// only assign a playlist estimate count once for the current URL
-			if !borrow.get_count_store().has_been_set() {
-				borrow.set_playlist_estimate(new_count);
+	/// Report a download progress event, only outputs something in [`ProgressFormat::Json`]
+	fn event(self, dpg: &DownloadProgress) {
+		if self.format != ProgressFormat::Json {
+			return;
+		}
+
+		let mut stdout = std::io::stdout().lock();
+		// write the whole line at once, so that the JSON lines are not mixed with other output
+		let res = serde_json::to_writer(&mut stdout, dpg)
+			.map_err(std::io::Error::from)
+			.and_then(|()| return stdout.write_all(b"\n"))
+			.and_then(|()| return stdout.flush());
+
+		if let Err(err) = res {
+			warn!("Writing JSON progress failed: {}", err);
+		}
+	}
+}
+
+/// The result of downloading a single URL, see [`DownloadSummary`]
+#[derive(Debug, Clone, PartialEq, Default)]
+struct UrlResult {
+	/// The URL that was downloaded
+	url:                 String,
+	/// Count of media that have been downloaded
+	downloaded:          usize,
+	/// Count of media that have been skipped because they are already in the archive
+	skipped_archive:     usize,
+	/// Count of media that have been skipped because they are blacklisted
+	skipped_blacklist:   usize,
+	/// Count of media that have been skipped because they are not available (like private or geo-blocked)
+	skipped_unavailable: usize,
+	/// Count of media that have been skipped because of a error
+	errors:              usize,
+	/// The error that stopped the download of the URL, if any
+	error:               Option<String>,
+}
+
+impl UrlResult {
+	/// Create a new empty result for `url`
+	fn new<U: Into<String>>(url: U) -> Self {
+		return Self {
+			url: url.into(),
+			..Default::default()
+		};
+	}
+}
+
+/// The results of all URLs in a run, so that failed URLs do not abort the whole run
+#[derive(Debug, Clone, PartialEq, Default)]
+struct DownloadSummary {
+	/// The results of all started URLs, in the order of the URLs
+	results: Vec<UrlResult>,
+}
+
+impl DownloadSummary {
+	/// Get the count of URLs that failed
+	fn failed_count(&self) -> usize {
+		return self.results.iter().filter(|v| return v.error.is_some()).count();
+	}
+
+	/// Get the indexes of the URLs that failed or were not started, out of `url_len` URLs
+	fn failed_indexes(&self, url_len: usize) -> Vec<usize> {
+		return (0..url_len)
+			.filter(|index| return self.results.get(*index).map_or(true, |v| return v.error.is_some()))
+			.collect();
+	}
+
+	/// Format the results as a table, with a row for every URL
+	fn format_table(&self) -> String {
+		let header = [
+			"URL",
+			"Downloaded",
+			"In Archive",
+			"Blacklisted",
+			"Unavailable",
+			"Errors",
+			"Status",
+		];
+		let rows: Vec<[String; 7]> = self
+			.results
+			.iter()
+			.map(|v| {
+				return [
+					v.url.clone(),
+					v.downloaded.to_string(),
+					v.skipped_archive.to_string(),
+					v.skipped_blacklist.to_string(),
+					v.skipped_unavailable.to_string(),
+					v.errors.to_string(),
+					if v.error.is_some() { "failed" } else { "ok" }.to_owned(),
+				];
+			})
+			.collect();
+
+		let mut widths = header.map(|v| return v.chars().count());
+		for row in &rows {
+			for (width, cell) in widths.iter_mut().zip(row) {
+				*width = (*width).max(cell.chars().count());
 			}
+		}
+
+		let format_row = |cells: &[&str]| {
+			return cells
+				.iter()
+				.zip(widths)
+				.map(|(cell, width)| return format!("{cell:width$}"))
+				.collect::<Vec<String>>()
+				.join("  ")
+				.trim_end()
+				.to_owned();
+		};
+
+		let mut lines = vec![format_row(&header)];
+		lines.extend(rows.iter().map(|row| {
+			return format_row(&row.iter().map(String::as_str).collect::<Vec<&str>>());
+		}));
+
+		return lines.join("\n");
+	}
+}
+
+/// Get the directory media is moved to when finishing, "--output" or "ytdlr-out" in the download directory of the user
+fn final_dir_path(sub_args: &CommandDownload) -> PathBuf {
+	return sub_args.output_path.as_ref().map_or_else(
+		|| {
+			return dirs::download_dir()
+				.unwrap_or_else(|| return PathBuf::from("."))
+				.join("ytdlr-out");
 		},
-		// remove skipped medias from the count estimate (for the progress-bar)
-		main::download::DownloadProgress::Skipped(skipped_count, _skipped_type) => {
-			let mut download_info_borrow = download_info.borrow_mut();
-			download_info_borrow.url_specific.dec_playlist_estimate(skipped_count);
-
-			// decrease playlist count too in case of error, because otherwise it could be playlist_count > count_estimate
-			// like 20 > 10
-			if download_info_borrow.url_specific.set_for_current_single {
-				download_info_borrow.url_specific.dec_current_playlist_pos(1);
-			}
+		|v| return v.clone(),
+	);
+}
 
-			download_info_borrow.reset_single_specific();
+/// Check that the temporary download directory and all output directories have at least "--min-free-space" available
+/// Returns a description of the directories with less space available, if any
+/// Errors while getting the available space are only logged, because the download may still work
+fn check_free_space(sub_args: &CommandDownload, download_path: &Path) -> Option<String> {
+	if sub_args.min_free_space == 0 {
+		return None;
+	}
 
-			pgbar.reset(); // reset so that it can work both with "SingleStarting" happening or not
-			pgbar.set_message(""); // because pgbar is not hidden and "reset" seemingly does not clear the message
+	let final_dir_path = final_dir_path(sub_args);
+	// absolute routes replace the output directory when joined
+	let route_paths: Vec<PathBuf> = sub_args
+		.output_routes
+		.iter()
+		.map(|(_, dir)| return final_dir_path.join(dir))
+		.collect();
+	let mut paths = vec![download_path, final_dir_path.as_path()];
+	paths.extend(route_paths.iter().map(PathBuf::as_path));
+
+	return match check_available_space(&paths, sub_args.min_free_space) {
+		Ok(low) if low.is_empty() => None,
+		Ok(low) => Some(
+			low.iter()
+				.map(|v| return format!("\"{}\" has {} available", v.path.display(), HumanBytes(v.available)))
+				.collect::<Vec<String>>()
+				.join(", "),
+		),
+		Err(err) => {
+			warn!("Checking the available space failed: {}", err);
 
-			// set prefix so that the progressbar is shown while skipping elements, to not have the cli appear as "doing nothing"
-			set_progressbar_prefix(pgbar, &download_info_borrow.url_specific);
+			return None;
 		},
 	};
+}
+
+/// Do the download for all provided URL's
+/// A URL that fails does not stop the other URLs, the results of every URL are added to `summary`
+fn do_download(
+	main_args: &CliDerive,
+	sub_args: &CommandDownload,
+	pgbar: &ProgressBar,
+	download_state: &mut DownloadState,
+	finished_media: &mut MediaInfoArr,
+	summary: &mut DownloadSummary,
+) -> Result<(), crate::Error> {
+	// the currently connected archive (and the path it was resolved as), only re-connected when a url is routed to a different archive
+	let mut current_archive: Option<(PathBuf, SqliteConnection)> = None;
+
+	// store "download_state" in a refcell, because rust complains that a borrow is made in "download_pgcb" and also later used while still in scope
+	let download_state_cell: RefCell<&mut DownloadState> = RefCell::new(download_state);
+	let download_info: RefCell<DownloadInfo> = RefCell::new(DownloadInfo::default());
+	let url_len = sub_args.urls.len();
+	set_progressbar_prefix(pgbar, &download_info.borrow().url_specific);
+	// track total count finished (no error)
+	let total_count = std::sync::atomic::AtomicUsize::new(0);
+	// track total bytes downloaded in this run (for "--max-total-bytes")
+	let total_bytes = std::sync::atomic::AtomicU64::new(0);
+	// the result of the current url, for the summary
+	let url_result: RefCell<UrlResult> = RefCell::new(UrlResult::default());
+	let output = ProgressOutput::new(sub_args.progress_format);
+
+	/// ProgressBar Style for extracting a playlist, will look like `[0/0] [00:00:00] ⠁ CustomMsg`
+	static EXTRACT_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+		return ProgressStyle::default_spinner()
+			.template("{prefix:.dim} [{elapsed_precise}] {spinner:.cyan} {msg}")
+			.expect("Expected ProgressStyle template to be valid");
+	});
+	// the style to restore once the playlist extraction or the wait for a livestream is done
+	let bar_style = pgbar.style();
+	// track whether the spinner for the playlist extraction or the wait for a livestream is shown
+	let extracting = std::cell::Cell::new(false);
+
+	let download_pgcb = |dpg| {
+		output.event(&dpg);
+		event_log::write_progress(&dpg);
+		notification::on_progress(&dpg);
+
+		// any other event means the playlist extraction or the wait for a livestream is done
+		if extracting.get()
+			&& !matches!(
+				dpg,
+				main::download::DownloadProgress::ExtractingPlaylist(_)
+					| main::download::DownloadProgress::WaitingForLive(_)
+			) {
+			pgbar.set_style(bar_style.clone());
+			extracting.set(false);
+		}
+
+		match dpg {
+			main::download::DownloadProgress::UrlStarting => {
+				pgbar.reset();
+				pgbar.set_message(""); // because pgbar is not hidden and "reset" seemingly does not clear the message
+				let url_index = download_info.borrow().url_index;
+				download_info.borrow_mut().reset_for_new_url(url_index);
+			},
+			main::download::DownloadProgress::SingleStarting(id, title) => {
+				let mut download_info_borrowed = download_info.borrow_mut();
+				download_info_borrowed.url_specific.inc_current_playlist_pos(1);
+
+				download_info_borrowed.set_single_specific(DownloadInfoSingleSpecific::new(id, title));
+
+				// set to default estimate, if by the time the first is starting it has not been got
+				if !download_info_borrowed.url_specific.playlist_estimate.has_been_set() {
+					download_info_borrowed
+						.url_specific
+						.set_playlist_estimate(DEFAULT_COUNT_ESTIMATE);
+				}
+
+				pgbar.reset();
+				pgbar.set_length(PG_PERCENT_100); // reset length, because it may get changed because of connection insert
+				let download_info_borrowed = &download_info_borrowed.url_specific;
+				set_progressbar_prefix(pgbar, download_info_borrowed);
+				// steady-ticks have to be re-done after every "pgbar.finish" because the ticker will exit once it notices the state is "finished"
+				pgbar.enable_steady_tick(Duration::from_secs(1));
+				let title = download_info_borrowed.single_specific.as_ref().unwrap().title.as_str();
+				pgbar.set_message(truncate_message_term_width(&title));
+				pgbar.println(format!("Downloading: {}", &title));
+			},
+			main::download::DownloadProgress::SingleProgress(_maybe_id, progress) => {
+				pgbar.set_position(progress.percent.into());
+				pgbar.set_message(truncate_message_term_width(&progress_message(
+					download_info.borrow().get_title(),
+					&progress,
+				)));
+			},
+			main::download::DownloadProgress::SingleProgressPhase(_maybe_id, phase, total_phases, progress) => {
+				pgbar.set_position(phase_position(phase, total_phases, progress.percent));
+				pgbar.set_message(truncate_message_term_width(&format!(
+					"[{phase}/{total_phases}] {}",
+					progress_message(download_info.borrow().get_title(), &progress)
+				)));
+			},
+			main::download::DownloadProgress::SingleDownloaded(_maybe_id, bytes) => {
+				total_bytes.fetch_add(bytes, std::sync::atomic::Ordering::AcqRel);
+			},
+			main::download::DownloadProgress::SingleFinished(_id, _path) => {
+				// dont hide the progressbar so that the cli does not appear to do nothing
+				pgbar.reset();
+				pgbar.set_message(""); // because pgbar is not hidden and "reset" seemingly does not clear the message
+				pgbar.println(format!("Finished Downloading: {}", download_info.borrow().get_title()));
+				download_info.borrow_mut().reset_single_specific();
+				set_progressbar_prefix(pgbar, &download_info.borrow().url_specific);
+			},
+			main::download::DownloadProgress::UrlFinished(new_count) => {
+				url_result.borrow_mut().downloaded += new_count;
+				pgbar.finish_and_clear();
+				let total = total_count.fetch_add(new_count, std::sync::atomic::Ordering::AcqRel) + new_count;
+				// print how many media has been downloaded since last "AllStarting" and how many in total in this run
+				let url_index = download_info.borrow().url_index;
+				pgbar.println(format!(
+					"Finished Downloading {new_count} new Media (For a total of {total} Media) (url {}/{})",
+					url_index, url_len
+				));
+				download_info.borrow_mut().reset_for_new_url(url_index);
+			},
+			main::download::DownloadProgress::PlaylistInfo(new_count) => {
+				let mut borrow = download_info.borrow_mut();
+				let borrow = &mut borrow.url_specific;
+				// only assign a playlist estimate count once for the current URL
+				if !borrow.get_count_store().has_been_set() {
+					// only the selected entries of the playlist are downloaded
+					borrow.set_playlist_estimate(main::download::selected_playlist_count(
+						sub_args.playlist_items.as_deref(),
+						sub_args.max_downloads,
+						new_count,
+					));
+				}
+			},
+			main::download::DownloadProgress::ExtractingPlaylist(page) => {
+				if !extracting.replace(true) {
+					pgbar.set_style(EXTRACT_STYLE.clone());
+					pgbar.enable_steady_tick(Duration::from_millis(100));
+				}
+
+				pgbar.set_message(format!("Extracting Playlist (page {page})"));
+			},
+			main::download::DownloadProgress::WaitingForLive(remaining) => {
+				if !extracting.replace(true) {
+					pgbar.set_style(EXTRACT_STYLE.clone());
+					pgbar.enable_steady_tick(Duration::from_millis(100));
+				}
+
+				pgbar.set_message(format!(
+					"Waiting for the livestream to begin (next check in {remaining})"
+				));
+			},
+			main::download::DownloadProgress::SingleLiveProgress(_maybe_id, progress) => {
+				pgbar.set_message(truncate_message_term_width(&live_progress_message(
+					download_info.borrow().get_title(),
+					&progress,
+				)));
+			},
+			main::download::DownloadProgress::Postprocessing(_maybe_id, step) => {
+				// the download itself is done, so show that the media is not stalled while ffmpeg is running
+				pgbar.set_position(PG_PERCENT_100);
+				pgbar.set_message(truncate_message_term_width(&format!(
+					"{} (post-processing: {step})",
+					download_info.borrow().get_title()
+				)));
+			},
+			main::download::DownloadProgress::Stalled(_maybe_id, elapsed) => {
+				pgbar.println(format!(
+					"Stalled: {} has not had any output for {}",
+					download_info.borrow().get_title(),
+					HumanDuration(elapsed)
+				));
+			},
+			// remove skipped medias from the count estimate (for the progress-bar)
+			main::download::DownloadProgress::Skipped {
+				count: skipped_count,
+				typ: skipped_type,
+				id,
+			} => {
+				match skipped_type {
+					SkippedType::InArchive => {
+						url_result.borrow_mut().skipped_archive += skipped_count;
+						pgbar.println(skipped_message(id.as_deref(), "already in the archive"));
+					},
+					SkippedType::Blacklisted => {
+						url_result.borrow_mut().skipped_blacklist += skipped_count;
+						pgbar.println(skipped_message(id.as_deref(), "blacklisted"));
+					},
+					SkippedType::Unavailable(reason) => {
+						url_result.borrow_mut().skipped_unavailable += skipped_count;
+						pgbar.println(skipped_message(id.as_deref(), &format!("unavailable, {reason}")));
+					},
+					SkippedType::Error => url_result.borrow_mut().errors += skipped_count,
+				}
+
+				let mut download_info_borrow = download_info.borrow_mut();
+				download_info_borrow.url_specific.dec_playlist_estimate(skipped_count);
+
+				// decrease playlist count too in case of error, because otherwise it could be playlist_count > count_estimate
+				// like 20 > 10
+				if download_info_borrow.url_specific.set_for_current_single {
+					download_info_borrow.url_specific.dec_current_playlist_pos(1);
+				}
+
+				download_info_borrow.reset_single_specific();
+
+				pgbar.reset(); // reset so that it can work both with "SingleStarting" happening or not
+				pgbar.set_message(""); // because pgbar is not hidden and "reset" seemingly does not clear the message
+
+				// set prefix so that the progressbar is shown while skipping elements, to not have the cli appear as "doing nothing"
+				set_progressbar_prefix(pgbar, &download_info_borrow.url_specific);
+			},
+		}
+	};
+
+	// cancels a running youtube-dl once termination has been requested
+	let cancel = crate::TERMINATE
+		.read()
+		.map_err(|err| return crate::Error::other(format!("{err}")))?
+		.cancellation_token();
 
 	for (index, url) in sub_args.urls.iter().enumerate() {
 		// handle terminate
 		check_termination()?;
 
+		// only start new URLs inside of the run window
+		if let Some(run_window) = sub_args.run_window.as_ref() {
+			wait_for_run_window(run_window)?;
+		}
+
 		// index plus one, to match .len, to not have 0-index for display
 		let index_p = index + 1;
 
+		// stop gracefully before youtube-dl runs out of space in the middle of a media
+		if let Some(low) = check_free_space(sub_args, download_state_cell.borrow().download_path()) {
+			output.status(format!(
+				"Not enough free space, at least {} is required: {} (skipping {} remaining URLs)",
+				HumanBytes(sub_args.min_free_space),
+				low,
+				url_len - index
+			));
+
+			break;
+		}
+
 		download_info.borrow_mut().url_index = index_p;
 
-		println!("Starting download of \"{}\" ({}/{})", url, index_p, url_len);
+		// the remaining bytes for this run, "saturating_sub" because the last media may overshoot the quota
+		let remaining_bytes = sub_args
+			.max_total_bytes
+			.map(|v| return v.saturating_sub(total_bytes.load(std::sync::atomic::Ordering::Acquire)));
+
+		output.status(format!("Starting download of \"{}\" ({}/{})", url, index_p, url_len));
+		*url_result.borrow_mut() = UrlResult::new(url);
 
 		download_state_cell.borrow_mut().set_current_url(url);
+		download_state_cell
+			.borrow_mut()
+			.set_url_overrides(sub_args.url_overrides(index));
+
+		let profile = sub_args.profile_for_url(url);
+		if profile.is_some() {
+			info!("Using the download profile for the provider of \"{url}\"");
+		}
+		download_state_cell.borrow_mut().set_profile(profile);
+
+		// connect to the archive this url is routed to
+		match main_args.resolve_archive_path_for_url(url) {
+			Some(ap) => {
+				let is_connected = matches!(current_archive.as_ref(), Some((path, _)) if path == ap);
+
+				if !is_connected {
+					current_archive = Some((ap.to_path_buf(), utils::handle_connect(ap, pgbar, main_args)?.1));
+				}
+			},
+			None => current_archive = None,
+		}
+		let mut maybe_connection = current_archive.as_mut().map(|(_, connection)| return connection);
+		download_state_cell.borrow_mut().set_max_download_bytes(remaining_bytes);
 
 		// the array where finished "current_mediainfo" gets appended to
 		// for performance / allocation efficiency, a count is requested from options
@@ -908,20 +1850,33 @@ fn do_download(
 
 		// dont error immediately on error
 		let res = libytdlr::main::download::download_single(
-			maybe_connection.as_mut(),
+			maybe_connection.as_deref_mut(),
 			*download_state_cell.borrow(),
 			download_pgcb,
 			&mut new_media,
+			Some(&cancel),
 		);
 
-		// still add all finished media to the archive
-		if let Some(ref mut connection) = maybe_connection {
+		// youtube-dl kept the container it merged into, so only remux the videos that need it
+		if !sub_args.remux_targets.is_empty() {
+			remux_media(
+				download_state_cell.borrow().download_path(),
+				&sub_args.remux_targets,
+				&mut new_media,
+				pgbar,
+			);
+		}
+
+		// finished media are already inserted into the archive by "download_single" as soon as they are finished
+		if let Some(connection) = maybe_connection.filter(|_| return sub_args.fingerprint) {
 			pgbar.reset();
 			pgbar.set_length(new_media.len().try_into().expect("Failed to convert usize to u64"));
 			for media in &new_media {
 				pgbar.inc(1);
-				if let Err(err) = libytdlr::main::archive::import::insert_insmedia(&media.into(), connection) {
-					warn!("Inserting media errored: {}", err);
+				if let Err(err) =
+					fingerprint_media(connection, download_state_cell.borrow().download_path(), media, pgbar)
+				{
+					warn!("Fingerprinting media errored: {}", err);
 				}
 			}
 			pgbar.finish_and_clear();
@@ -935,8 +1890,40 @@ fn do_download(
 			finished_media.insert(media);
 		}
 
-		// now error if there was a error
-		res?;
+		// record the error and continue with the next url, a failed url should not stop the whole run
+		if let Err(err) = &res {
+			// youtube-dl was stopped because of a termination request, the finished media have already been added above
+			if err.is_cancelled() {
+				return Err(crate::Error::other("Termination Requested"));
+			}
+
+			event_log::write_error(url, err);
+			pgbar.println(format!("Downloading \"{url}\" failed: {err}"));
+
+			if let Some(hint) = err.as_download_error().and_then(|v| return v.category.hint()) {
+				pgbar.println(format!("Hint: {hint}"));
+			}
+
+			url_result.borrow_mut().error = Some(err.to_string());
+		}
+
+		summary.results.push(url_result.take());
+
+		// stop gracefully once the quota for this run has been reached
+		if let Some(max_total_bytes) = sub_args.max_total_bytes {
+			let downloaded = total_bytes.load(std::sync::atomic::Ordering::Acquire);
+
+			if downloaded >= max_total_bytes {
+				output.status(format!(
+					"Download quota reached, downloaded {} of {} (skipping {} remaining URLs)",
+					HumanBytes(downloaded),
+					HumanBytes(max_total_bytes),
+					url_len - index_p
+				));
+
+				break;
+			}
+		}
 	}
 
 	// remove ytdl_archive_pid.txt file again, because otherwise over many usages it can become bloated
@@ -951,13 +1938,412 @@ fn do_download(
 	return Ok(());
 }
 
+/// Apply the url preprocessing options of `sub_args` to all urls, returning the modified arguments
+/// Errors if any url is invalid after preprocessing (see [`validate_url`]), duplicate urls are removed with a warning
+fn preprocess_urls(sub_args: &CommandDownload) -> Result<CommandDownload, crate::Error> {
+	let preprocessor = UrlPreprocessor {
+		normalize:         !sub_args.no_url_normalize,
+		resolve_redirects: sub_args.resolve_redirects,
+		rewrites:          sub_args.url_rewrite.clone(),
+	};
+
+	let mut sub_args = sub_args.clone();
+
+	for url in &mut sub_args.urls {
+		let processed = preprocessor.process(url);
+
+		if processed != *url {
+			info!("Rewrote URL \"{}\" to \"{}\"", url, processed);
+			*url = processed;
+		}
+	}
+
+	// report all invalid urls at once, instead of one per run
+	let invalid: Vec<String> = sub_args
+		.urls
+		.iter()
+		.filter_map(|url| return validate_url(url).err().map(|err| return format!("  - {err}")))
+		.collect();
+
+	if !invalid.is_empty() {
+		return Err(crate::Error::other(format!("Invalid URLs:\n{}", invalid.join("\n"))));
+	}
+
+	for url in sub_args.dedup_urls() {
+		warn!("Skipping duplicate URL \"{}\"", url);
+	}
+
+	return Ok(sub_args);
+}
+
+/// Search for `terms` and let the user select which results to download, returning the urls of the selected results
+/// In non-interactive mode all results are selected
+fn search_urls(
+	main_args: &CliDerive,
+	sub_args: &CommandDownload,
+	ytdl_bin: &OsStr,
+	terms: &str,
+) -> Result<Vec<String>, crate::Error> {
+	println!(
+		"Searching for \"{}\"",
+		libytdlr::main::search::search_query(sub_args.search_provider, terms, sub_args.search_count)
+	);
+
+	let results = libytdlr::main::search::search(ytdl_bin, sub_args.search_provider, terms, sub_args.search_count)?;
+
+	if results.is_empty() {
+		println!("No Search Results found");
+
+		return Ok(Vec::new());
+	}
+
+	for (index, result) in results.iter().enumerate() {
+		let title = result.media.title.as_deref().unwrap_or_default();
+
+		match result.duration.as_deref() {
+			Some(duration) => println!("{:>3}: {} ({})", index + 1, title, duration),
+			None => println!("{:>3}: {}", index + 1, title),
+		}
+	}
+
+	if !main_args.is_interactive() {
+		return Ok(results.into_iter().map(|v| return v.url).collect());
+	}
+
+	if utils::assume_yes() {
+		println!("Not downloading any search results, because \"--assume-yes\" uses the default of none");
+
+		return Ok(Vec::new());
+	}
+
+	loop {
+		print!("Select Results to download (like \"1 3 4-5\", \"a\" for all, empty for none): ");
+		// ensure the message is printed before reading
+		std::io::stdout().flush().attach_location_err("stdout flush")?;
+
+		let input = utils::read_line_terminable()?;
+
+		match parse_selection(&input, results.len()) {
+			Ok(selected) => {
+				return Ok(selected.into_iter().map(|i| return results[i].url.clone()).collect());
+			},
+			Err(err) => println!("{err}"),
+		}
+	}
+}
+
+/// Parse a selection like "1 3 4-5" (1-based, separated by spaces or ",") or "a" (all) for `len` items
+/// Returns the 0-based indexes in the order selected, without duplicates
+fn parse_selection(input: &str, len: usize) -> Result<Vec<usize>, crate::Error> {
+	let input = input.trim();
+
+	if input.eq_ignore_ascii_case("a") {
+		return Ok((0..len).collect());
+	}
+
+	let parse_number = |v: &str| {
+		let number = v
+			.trim()
+			.parse::<usize>()
+			.map_err(|_| return crate::Error::other(format!("Invalid Number \"{v}\"")))?;
+
+		if number == 0 || number > len {
+			return Err(crate::Error::other(format!(
+				"Number {number} is out of range, expected 1 to {len}"
+			)));
+		}
+
+		return Ok(number - 1);
+	};
+
+	let mut selected: Vec<usize> = Vec::new();
+
+	for part in input.split(|c: char| return c == ',' || c.is_whitespace()) {
+		if part.is_empty() {
+			continue;
+		}
+
+		// a single number is a range with the same start and end
+		let (start, end) = part.split_once('-').unwrap_or((part, part));
+		let (start, end) = (parse_number(start)?, parse_number(end)?);
+
+		if start > end {
+			return Err(crate::Error::other(format!(
+				"Invalid Range \"{part}\", start is after end"
+			)));
+		}
+
+		for index in start..=end {
+			if !selected.contains(&index) {
+				selected.push(index);
+			}
+		}
+	}
+
+	return Ok(selected);
+}
+
+/// Minimal fingerprint similarity for a archived media to be reported as a likely duplicate
+const DUPLICATE_SIMILARITY: f64 = 0.9;
+
+/// Calculate and store the fingerprint of a newly downloaded audio media
+/// Warns about media in the archive that sound like the new media
+fn fingerprint_media(
+	connection: &mut SqliteConnection,
+	download_path: &Path,
+	media: &MediaInfo,
+	pgbar: &ProgressBar,
+) -> Result<(), crate::Error> {
+	use diesel::prelude::*;
+	use libytdlr::{
+		data::sql_schema::media_archive,
+		main::archive::fingerprints::{
+			find_similar,
+			store_fingerprint,
+		},
+		spawn::fpcalc::fpcalc_fingerprint,
+	};
+
+	let Some(media_filename) = &media.filename else {
+		return Ok(());
+	};
+
+	if !matches!(utils::get_filetype(media_filename), utils::FileType::Audio) {
+		return Ok(());
+	}
+
+	let fingerprint = fpcalc_fingerprint(download_path.join(media_filename))?;
+
+	let media_archive_id: i64 = media_archive::table
+		.filter(media_archive::media_id.eq(&media.id))
+		.filter(media_archive::provider.eq(media.provider.as_str()))
+		.select(media_archive::_id)
+		.first(connection)?;
+
+	store_fingerprint(connection, media_archive_id, &fingerprint)?;
+
+	for (similar, similarity) in find_similar(connection, &fingerprint, Some(media_archive_id), DUPLICATE_SIMILARITY)? {
+		pgbar.println(format!(
+			"Possible Duplicate: \"{}\" sounds like \"{}\" ({}:{}, {:.0}% similar)",
+			media.title.as_deref().unwrap_or(&media.id),
+			similar.title,
+			similar.provider,
+			similar.media_id,
+			similarity * 100.0
+		));
+	}
+
+	return Ok(());
+}
+
+/// Remux all downloaded videos in `media` which are not already in a compatible container of `targets`, see [`libytdlr::main::remux::remux_if_needed`]
+/// Errors are only printed, because the video is still usable in the container it was downloaded in
+fn remux_media(download_path: &Path, targets: &[VideoFormat], media: &mut [MediaInfo], pgbar: &ProgressBar) {
+	let targets: Vec<&str> = targets.iter().map(|v| return v.as_str()).collect();
+
+	for media in media.iter_mut() {
+		let Some(media_filename) = media.filename.as_ref() else {
+			continue;
+		};
+
+		if !matches!(utils::get_filetype(media_filename), utils::FileType::Video) {
+			continue;
+		}
+
+		match libytdlr::main::remux::remux_if_needed(&download_path.join(media_filename), &targets) {
+			Ok(Some(new_path)) => {
+				if let Some(new_filename) = new_path.file_name() {
+					media.set_filename(new_filename);
+				}
+			},
+			Ok(None) => (),
+			Err(err) => {
+				pgbar.println(format!(
+					"Remuxing \"{}\" failed, keeping it as downloaded: {err}",
+					media_filename.display()
+				));
+			},
+		}
+	}
+}
+
+/// Normalize the loudness of all downloaded audio-only media of `keys`, see [`libytdlr::main::postprocess::loudnorm`]
+/// Errors are only printed, because the media is still usable without being normalized
+fn normalize_media(download_path: &Path, final_media: &MediaInfoArr, keys: &[String]) {
+	let paths: Vec<PathBuf> = keys
+		.iter()
+		.filter_map(|key| return final_media.get(key))
+		.filter(|media| return media.data.stage == MediaStage::Downloaded)
+		.filter_map(|media| return media.data.filename.as_ref())
+		.filter(|filename| return matches!(utils::get_filetype(filename), utils::FileType::Audio))
+		.map(|filename| return download_path.join(filename))
+		.filter(|path| return path.exists())
+		.collect();
+
+	if paths.is_empty() {
+		return;
+	}
+
+	let errors = loudnorm_many(&paths, &LoudnormTarget::default(), |imp| match imp {
+		LoudnormProgress::Starting(v) => println!("Normalizing the loudness of {v} audio media"),
+		LoudnormProgress::SingleStarting(i, path) => {
+			println!("[{}/{}] \"{}\"", i + 1, paths.len(), path.display());
+		},
+		LoudnormProgress::SingleSkipped(_) => println!("Media is silent, skipping"),
+		LoudnormProgress::SingleFinished(_) | LoudnormProgress::SingleFailed(_) => (),
+		LoudnormProgress::Finished(successfull, failed) => {
+			println!("Normalized Loudness, Successfull: {successfull}, Failed: {failed}");
+		},
+	});
+
+	for (index, err) in &errors {
+		println!(
+			"Normalizing \"{}\" failed, keeping it as downloaded: {err}",
+			paths[*index].display()
+		);
+	}
+}
+
+/// Write the ReplayGain tags of all audio media which have not been moved yet, see [`libytdlr::main::postprocess::replaygain`]
+/// Errors are only printed, because the media is still usable without the tags
+fn replaygain_media(download_path: &Path, final_media: &MediaInfoArr) {
+	let paths: Vec<PathBuf> = final_media
+		.as_sorted_vec()
+		.into_iter()
+		.filter(|media| return media.data.stage < MediaStage::Moved)
+		.filter_map(|media| return media.data.filename.as_ref())
+		.map(|filename| return download_path.join(filename))
+		.filter(|path| return replaygain::is_supported(path) && path.exists())
+		.collect();
+
+	if paths.is_empty() {
+		return;
+	}
+
+	let errors = replaygain_many(&paths, |imp| match imp {
+		ReplayGainProgress::Starting(v) => println!("Calculating ReplayGain of {v} audio media"),
+		ReplayGainProgress::SingleStarting(i, path) => {
+			println!("[{}/{}] \"{}\"", i + 1, paths.len(), path.display());
+		},
+		ReplayGainProgress::SingleSkipped(_) => println!("Media is silent, skipping"),
+		ReplayGainProgress::SingleFinished(_) | ReplayGainProgress::SingleFailed(_) => (),
+		ReplayGainProgress::Finished(successfull, failed) => {
+			println!("Wrote ReplayGain, Successfull: {successfull}, Failed: {failed}");
+		},
+	});
+
+	for (index, err) in &errors {
+		println!(
+			"Writing ReplayGain of \"{}\" failed, keeping it without: {err}",
+			paths[*index].display()
+		);
+	}
+}
+
+/// Calculate the content hash of all media which have not been moved yet and have a archive, see [`sha256_file`]
+/// Returns the media keys with their hash
+/// Errors are only logged, because a missing hash only means that duplicates of the media cannot be found
+fn hash_media(main_args: &CliDerive, download_path: &Path, final_media: &MediaInfoArr) -> Vec<(String, String)> {
+	let mut hashes = Vec::new();
+
+	for key in final_media.sorted_keys() {
+		let Some(media) = final_media.get(&key).map(|v| return &v.data) else {
+			continue;
+		};
+
+		if media.stage >= MediaStage::Moved
+			|| main_args
+				.resolve_archive_path_for_provider(media.provider.as_str())
+				.is_none()
+		{
+			continue;
+		}
+
+		let Some(media_path) = media
+			.filename
+			.as_ref()
+			.map(|v| return download_path.join(v))
+			.filter(|v| return v.exists())
+		else {
+			continue;
+		};
+
+		match sha256_file(&media_path) {
+			Ok(hash) => hashes.push((key, hash)),
+			Err(err) => warn!("Hashing \"{}\" failed: {}", media_path.display(), err),
+		}
+	}
+
+	return hashes;
+}
+
+/// Store the content hashes of `hashes` (from [`hash_media`]) in the archive of each media
+/// Warns about media in the archive with the same content but a different id (like re-uploads)
+fn store_media_hashes(
+	main_args: &CliDerive,
+	pgbar: &ProgressBar,
+	final_media: &MediaInfoArr,
+	hashes: &[(String, String)],
+) -> Result<(), crate::Error> {
+	// connect to all archives the hashed media is routed to, keyed by the resolved path
+	let mut connections: HashMap<&Path, SqliteConnection> = HashMap::new();
+
+	for (key, hash) in hashes {
+		let Some(media) = final_media.get(key).map(|v| return &v.data) else {
+			continue;
+		};
+		let Some(archive_path) = main_args.resolve_archive_path_for_provider(media.provider.as_str()) else {
+			continue;
+		};
+
+		if !connections.contains_key(archive_path) {
+			connections.insert(archive_path, utils::handle_connect(archive_path, pgbar, main_args)?.1);
+		}
+		let Some(connection) = connections.get_mut(archive_path) else {
+			continue;
+		};
+
+		match store_content_hash(connection, media.provider.as_str(), &media.id, hash) {
+			Ok(true) => (),
+			Ok(false) => {
+				warn!("Not storing hash for \"{}\", because it is not in the archive", key);
+				continue;
+			},
+			Err(err) => {
+				warn!("Storing hash for \"{}\" failed: {}", key, err);
+				continue;
+			},
+		}
+
+		let duplicates = match find_by_content_hash(connection, hash, media.provider.as_str(), &media.id) {
+			Ok(v) => v,
+			Err(err) => {
+				warn!("Finding media with the same hash as \"{}\" failed: {}", key, err);
+				continue;
+			},
+		};
+
+		for duplicate in duplicates {
+			println!(
+				"Possible Duplicate: \"{}\" has the same content as \"{}\" ({}:{})",
+				media.title.as_deref().unwrap_or(&media.id),
+				duplicate.title,
+				duplicate.provider,
+				duplicate.media_id
+			);
+		}
+	}
+
+	return Ok(());
+}
+
 /// Start editing loop for all provided media
 /// set "reverse" to start the editing on the last element
 fn edit_media(
 	main_args: &CliDerive,
 	sub_args: &CommandDownload,
 	download_path: &std::path::Path,
-	final_media: &MediaInfoArr,
+	final_media: &mut MediaInfoArr,
 	reverse: bool,
 ) -> Result<(), crate::Error> {
 	if !main_args.is_interactive() {
@@ -966,7 +2352,7 @@ fn edit_media(
 	}
 
 	if final_media.is_empty() {
-		println!("Skipping asking for media, because there is no media to edit");
+		println!("{}", tr(Msg::NoMediaToEdit));
 		return Ok(());
 	}
 
@@ -976,11 +2362,33 @@ fn edit_media(
 		return Ok(());
 	}
 
-	let media_sorted_vec = final_media.as_sorted_vec();
-	let mut next_index = 0;
+	// editing re-applies metadata and thumbnails with ffmpeg after the editor ran
+	if ffmpeg::ffmpeg_version().is_err() {
+		println!("Skipping editing media, because ffmpeg could not be found, which is required for re-applying metadata and thumbnails");
+		return Ok(());
+	}
 
-	if reverse {
-		next_index = media_sorted_vec.len() - 1; // case of 0 - 1 should be solved by the "is_empty" above
+	if sub_args.tui {
+		match tui::TuiTerminal::init() {
+			Ok(tui) => return edit_media_tui(tui, sub_args, download_path, final_media, reverse),
+			Err(err) => println!("{}", trf(Msg::TuiFallback, &[&err])),
+		}
+	}
+
+	// keys are used instead of references, so that the stage of the media can be updated after editing
+	let media_sorted_keys = final_media.sorted_keys();
+	let mut next_index = if reverse {
+		media_sorted_keys.len() - 1 // case of 0 - 1 should be solved by the "is_empty" above
+	} else {
+		// resume where a earlier run stopped (like from a recovery file), instead of asking for all media again
+		first_unreviewed_index(final_media, &media_sorted_keys)
+	};
+
+	if !reverse && next_index > 0 && next_index < media_sorted_keys.len() {
+		println!(
+			"{}",
+			trf(Msg::ResumeEditing, &[&(next_index + 1), &media_sorted_keys.len()])
+		);
 	}
 
 	// storage for when a element needs to be skipped (like missing filename) to know what should be done
@@ -997,20 +2405,21 @@ fn edit_media(
 			go_back = false;
 		}
 
-		let opt = media_sorted_vec.get(next_index);
+		let opt = media_sorted_keys.get(next_index);
 		next_index += 1;
 
-		let Some(media_helper) = opt else {
+		let Some(media_key) = opt else {
 			break;
 		};
 
-		let media = &media_helper.data;
+		let media = final_media
+			.get(media_key)
+			.expect("Expected key from \"sorted_keys\" to exist")
+			.data
+			.clone();
 		let Some(media_filename) = &media.filename else {
 			// skip asking edit for media's without a filename
-			println!(
-				"\"{}\" did not have a filename, which is required beyond this point, skipping",
-				media.id
-			);
+			println!("{}", trf(Msg::MediaNoFilename, &[&media.id]));
 			println!("debug: {media:#?}");
 
 			// try to go back to the next element
@@ -1025,10 +2434,7 @@ fn edit_media(
 
 		// skip asking edit for media's that dont exist anymore
 		if !media_path.exists() {
-			println!(
-				"\"{}\" did not exist anymore (moved via another invocation or editor rename?), skipping edit",
-				media.id
-			);
+			println!("{}", trf(Msg::MediaNotExisting, &[&media.id]));
 
 			// try to go back to the next element
 			if go_back {
@@ -1052,16 +2458,15 @@ fn edit_media(
 				.into()
 			} else {
 				utils::get_input(
-					&format!(
-						"Edit Media \"{}\"?{}",
-						media
-							.title
-							.as_ref()
-							.expect("Expected MediaInfo to have a title from \"try_from_filename\""),
-						media_helper
-							.comment
-							.as_ref()
-							.map_or(String::new(), |msg| format!(" ({msg})"))
+					&trf(
+						Msg::EditMediaAsk,
+						&[
+							media
+								.title
+								.as_ref()
+								.expect("Expected MediaInfo to have a title from \"try_from_filename\""),
+							&format!(" ({})", media.stage),
+						],
 					),
 					&["h", "y", "N", "a", "v", "p", "b"],
 					"n",
@@ -1069,25 +2474,24 @@ fn edit_media(
 			};
 
 			match input.as_str() {
-				"n" => continue 'media_loop,
+				"n" => {
+					// record the skip, so that a recovery can resume after this media
+					advance_stage_of(final_media, media_key, MediaStage::Reviewed);
+
+					continue 'media_loop;
+				},
 				"y" => match utils::get_filetype(media_filename) {
 					utils::FileType::Video => {
-						println!("Found filetype to be of video");
+						println!("{}", tr(Msg::FiletypeVideo));
 						run_editor_wrap(&sub_args.video_editor, &media_path)?;
 					},
 					utils::FileType::Audio => {
-						println!("Found filetype to be of audio");
+						println!("{}", tr(Msg::FiletypeAudio));
 						run_editor_wrap(&sub_args.audio_editor, &media_path)?;
 					},
 					utils::FileType::Unknown => {
 						// if not FileType could be found, ask user what to do
-						match utils::get_input(
-							"Could not find suitable editor for extension, [a]udio editor, [v]ideo editor, a[b]ort, [n]ext.",
-							&["a", "v", "b", "n"],
-							"",
-						)?
-						.as_str()
-						{
+						match utils::get_input(tr(Msg::UnknownEditorAsk), &["a", "v", "b", "n"], "")?.as_str() {
 							"a" => run_editor_wrap(&sub_args.audio_editor, &media_path)?,
 							"v" => run_editor_wrap(&sub_args.video_editor, &media_path)?,
 							"b" => return Err(crate::Error::other("Abort Selected")),
@@ -1097,17 +2501,7 @@ fn edit_media(
 					},
 				},
 				"h" => {
-					println!(
-						"Help:\n\
-					[h] print help (this)\n\
-					[n] skip element and move onto the next one\n\
-					[y] edit element, automatically choose editor\n\
-					[a] edit element with audio editor\n\
-					[v] edit element with video editor\n\
-					[p] start the element with a media player\n\
-					[b] go back a element\
-					"
-					);
+					println!("{}", tr(Msg::EditMediaHelp));
 					continue 'ask_do_loop;
 				},
 				"a" => {
@@ -1125,7 +2519,7 @@ fn edit_media(
 				"b" => {
 					// QOL message to notify that the earliest index is already in use
 					if next_index == 1 {
-						println!("Cannot go back further");
+						println!("{}", tr(Msg::CannotGoBack));
 					}
 
 					next_index = next_index.saturating_sub(2); // remove the "+1" for the next that was already added, and remove another 1 to get back to the last element
@@ -1137,19 +2531,12 @@ fn edit_media(
 				_ => unreachable!("get_input should only return a OK value from the possible array"),
 			}
 
-			// when getting here, the media needs to be re-thumbnailed
-			debug!("Re-applying thumbnail for media");
-			if let Some(image_path) = libytdlr::main::rethumbnail::find_image(&media_path)? {
-				// re-apply thumbnail to "media_path", and have the output be the same path
-				// "re_thumbnail_with_tmp" will handle that the original will only be overwritten once successfully finished
-				libytdlr::main::rethumbnail::re_thumbnail_with_tmp(&media_path, &image_path, &media_path)?;
-			} else {
-				warn!(
-					"No Image found for media, not re-applying thumbnail! Media: \"{}\"",
-					media
-						.title
-						.as_ref()
-						.expect("Expected MediaInfo to have a title from \"try_from_filename\"")
+			// when getting here, a editor has been run on the media
+			for failure in finish_edit(sub_args, download_path, final_media, media_key, &media, &media_path) {
+				println!(
+					"{} {}",
+					"WARN".color(Color::TrueColor { r: 255, g: 135, b: 0 }),
+					failure
 				);
 			}
 
@@ -1160,6 +2547,135 @@ fn edit_media(
 	return Ok(());
 }
 
+/// Get the index into `keys` of the first media that has not been shown in the edit stage yet
+/// Returns the length of `keys` if all media has already been reviewed
+fn first_unreviewed_index(final_media: &MediaInfoArr, keys: &[String]) -> usize {
+	return keys
+		.iter()
+		.position(|key| {
+			return final_media
+				.get(key)
+				.map_or(true, |media| return media.data.stage < MediaStage::Reviewed);
+		})
+		.unwrap_or(keys.len());
+}
+
+/// Finish a media after a editor has been run on it: mark it as edited and re-apply the thumbnail
+/// Returns the messages of re-thumbnailing failures, which are only reported so that the other media can still be edited
+fn finish_edit(
+	sub_args: &CommandDownload,
+	download_path: &Path,
+	final_media: &mut MediaInfoArr,
+	media_key: &str,
+	media: &MediaInfo,
+	media_path: &Path,
+) -> Vec<String> {
+	advance_stage_of(final_media, media_key, MediaStage::Edited);
+
+	// no thumbnail has been downloaded to be re-applied
+	if sub_args.no_embed_thumbnail {
+		return Vec::new();
+	}
+
+	// when getting here, the media needs to be re-thumbnailed
+	debug!("Re-applying thumbnail for media");
+	// re-apply thumbnail to "media_path", and have the output be the same path
+	// "re_thumbnail_many" will handle that the original will only be overwritten once successfully finished
+	// thumbnails are written into their own directory, see "--paths"
+	let item = ReThumbnailItem::new(media_path).with_image_dir(download_path.join(PATHS_THUMBNAIL_DIR));
+	let errors = main::rethumbnail::re_thumbnail_many(&[item], |imp| {
+		if let ReThumbnailProgress::SingleSkipped(_) = imp {
+			warn!(
+				"No Image found for media, not re-applying thumbnail! Media: \"{}\"",
+				media
+					.title
+					.as_ref()
+					.expect("Expected MediaInfo to have a title from \"try_from_filename\"")
+			);
+		}
+	});
+
+	return errors
+		.into_iter()
+		.map(|(_, err)| return trf(Msg::ReThumbnailFailed, &[&media_path.display(), &err]))
+		.collect();
+}
+
+/// Run the edit loop as a terminal UI (see "--tui"), which lists all media and runs the selected actions
+/// set "reverse" to start the editing on the last element
+fn edit_media_tui(
+	tui: tui::TuiTerminal,
+	sub_args: &CommandDownload,
+	download_path: &Path,
+	final_media: &mut MediaInfoArr,
+	reverse: bool,
+) -> Result<(), crate::Error> {
+	let entries = final_media
+		.sorted_keys()
+		.into_iter()
+		.map(|key| {
+			let media = &final_media
+				.get(&key)
+				.expect("Expected key from \"sorted_keys\" to exist")
+				.data;
+			let title = media.title.clone().unwrap_or_else(|| return media.id.clone());
+
+			return tui::TuiEntry {
+				title,
+				stage: media.stage,
+				key,
+			};
+		})
+		.collect();
+
+	return tui::run_edit_tui(tui, entries, reverse, |media_key, action| {
+		let media = final_media
+			.get(media_key)
+			.expect("Expected key from \"sorted_keys\" to exist")
+			.data
+			.clone();
+		let Some(media_filename) = &media.filename else {
+			return Ok(tui::ActionOutcome::message(trf(Msg::MediaNoFilename, &[&media.id])));
+		};
+
+		let media_path = download_path.join(media_filename);
+
+		if !media_path.exists() {
+			return Ok(tui::ActionOutcome::message(trf(Msg::MediaNotExisting, &[&media.id])));
+		}
+
+		let choice = match action {
+			tui::TuiAction::Play => {
+				utils::run_editor(&sub_args.player_editor, &media_path)?;
+
+				return Ok(tui::ActionOutcome::default());
+			},
+			tui::TuiAction::Edit(choice) => choice,
+		};
+
+		let editor = match (choice, utils::get_filetype(media_filename)) {
+			(tui::EditorChoice::Audio, _) | (tui::EditorChoice::Auto, utils::FileType::Audio) => &sub_args.audio_editor,
+			(tui::EditorChoice::Video, _) | (tui::EditorChoice::Auto, utils::FileType::Video) => &sub_args.video_editor,
+			(tui::EditorChoice::Auto, utils::FileType::Unknown) => {
+				return Ok(tui::ActionOutcome::message(tr(Msg::TuiUnknownFiletype)));
+			},
+		};
+
+		run_editor_wrap(editor, &media_path)?;
+
+		let failures = finish_edit(sub_args, download_path, final_media, media_key, &media, &media_path);
+
+		return Ok(tui::ActionOutcome {
+			stage:   Some(MediaStage::Edited),
+			message: Some(if failures.is_empty() {
+				trf(Msg::TuiEdited, &[&media.title.as_deref().unwrap_or(&media.id)])
+			} else {
+				failures.join("; ")
+			}),
+		});
+	});
+}
+
 /// Wrap [utils::run_editor] calls to apply quirks in all cases - but only when editor is actually run
 fn run_editor_wrap(maybe_editor: &Option<PathBuf>, file: &Path) -> Result<(), crate::Error> {
 	// re-apply full metadata after a editor run, because currently audacity does not properly handle custom tags
@@ -1429,10 +2945,10 @@ fn finish_media(
 	sub_args: &CommandDownload,
 	download_path: &std::path::Path,
 	pgbar: &ProgressBar,
-	final_media: &MediaInfoArr,
+	final_media: &mut MediaInfoArr,
 ) -> Result<EditCtrl, crate::Error> {
 	if final_media.mediainfo_map.is_empty() {
-		println!("No files to move or tag");
+		println!("{}", tr(Msg::NoFilesToFinish));
 		return Ok(EditCtrl::Finished);
 	}
 
@@ -1442,62 +2958,102 @@ fn finish_media(
 	pgbar.set_length(final_media.mediainfo_map.len().try_into().unwrap_or(u64::MAX));
 	pgbar.set_message("Moving files");
 
-	if main_args.is_interactive() && !sub_args.open_tagger {
+	let finish_action = sub_args.finish_action();
+
+	// the finish prompt has no default, so "--assume-yes" uses the non-interactive behavior
+	let finish_action = if main_args.is_interactive() && finish_action.is_none() && !utils::assume_yes() {
 		// the following is used to ask the user what to do with the media-files
 		// current choices are:
 		// move all media that is found to the final_directory (specified via options or defaulted), or
 		// open the tagger and let the tagger handle the moving
-		match utils::get_input(
-			"[m]ove Media to Output Directory or Open [p]icard or go [b]ack to editing?",
-			&["m", "p", "b"],
-			"",
-		)?
-		.as_str()
-		{
-			"m" => finish_with_move(sub_args, download_path, pgbar, final_media)?,
-			"p" => finish_with_tagger(sub_args, download_path, pgbar, final_media)?,
+		match utils::get_input(tr(Msg::FinishMediaAsk), &["m", "o", "p", "b"], "")?.as_str() {
+			"m" => DownloadFinishAction::Move,
+			"o" => DownloadFinishAction::Organize,
+			"p" => DownloadFinishAction::Tagger,
 			"b" => return Ok(EditCtrl::Goback),
 			_ => unreachable!("get_input should only return a OK value from the possible array"),
 		}
 	} else {
-		info!("non-interactive finish media, open_tagger: {}", sub_args.open_tagger);
-		if sub_args.open_tagger {
-			finish_with_tagger(sub_args, download_path, pgbar, final_media)?;
-		} else {
-			finish_with_move(sub_args, download_path, pgbar, final_media)?;
-		}
+		info!("non-interactive finish media, finish action: {:?}", finish_action);
+		// moving is the default for non-interactive mode
+		finish_action.unwrap_or(DownloadFinishAction::Move)
+	};
+
+	// calculate after editing, because editing may change the loudness
+	if sub_args.replaygain {
+		replaygain_media(download_path, final_media);
+	}
+
+	// hash before finishing, because the media may not be in the download directory afterwards
+	let hashes = hash_media(main_args, download_path, final_media);
+
+	match finish_action {
+		DownloadFinishAction::Move => finish_with_move(main_args, sub_args, download_path, pgbar, final_media, false)?,
+		DownloadFinishAction::Organize => {
+			finish_with_move(main_args, sub_args, download_path, pgbar, final_media, true)?;
+		},
+		DownloadFinishAction::Tagger => finish_with_tagger(sub_args, download_path, pgbar, final_media)?,
 	}
 
 	// try to insert media into the archive, if media has maybe not been inserted yet
 	if final_media.has_maybe_uninserted() {
-		let mut maybe_connection: Option<SqliteConnection> = if let Some(ap) = main_args.archive_path.as_ref() {
-			Some(utils::handle_connect(ap, pgbar, main_args)?.1)
-		} else {
-			None
-		};
+		// connect to all archives media is routed to before showing the progress, keyed by the resolved path
+		let mut connections: HashMap<&Path, SqliteConnection> = HashMap::new();
+		for media in final_media.mediainfo_map.values() {
+			if let Some(ap) = main_args.resolve_archive_path_for_provider(media.data.provider.as_str()) {
+				if !connections.contains_key(ap) {
+					connections.insert(ap, utils::handle_connect(ap, pgbar, main_args)?.1);
+				}
+			}
+		}
 
-		if let Some(ref mut connection) = maybe_connection {
-			pgbar.reset();
-			pgbar.set_length(
-				final_media
-					.mediainfo_map
-					.len()
-					.try_into()
-					.expect("Failed to convert usize to u64"),
-			);
-			pgbar.set_message("Inserting missing Entries to Archive");
-			for media in final_media.mediainfo_map.values() {
-				let media = &media.data;
-				pgbar.inc(1);
+		pgbar.reset();
+		pgbar.set_length(
+			final_media
+				.mediainfo_map
+				.len()
+				.try_into()
+				.expect("Failed to convert usize to u64"),
+		);
+		pgbar.set_message("Inserting missing Entries to Archive");
+		for media in final_media.mediainfo_map.values() {
+			let media = &media.data;
+			pgbar.inc(1);
+
+			let maybe_connection = main_args
+				.resolve_archive_path_for_provider(media.provider.as_str())
+				.and_then(|ap| return connections.get_mut(ap));
+
+			if let Some(connection) = maybe_connection {
 				libytdlr::main::archive::import::insert_insmedia_noupdate(&media.into(), connection)?;
 			}
-			pgbar.finish_and_clear();
+		}
+		pgbar.finish_and_clear();
+	}
+
+	// store after inserting, so that recovered media is also in the archive
+	if !hashes.is_empty() {
+		store_media_hashes(main_args, pgbar, final_media, &hashes)?;
+	}
+
+	// media that has been moved and has a archive is now fully finished
+	// media downloaded in the current run has already been inserted into the archive while downloading
+	for media_helper in final_media.mediainfo_map.values_mut() {
+		let has_archive = main_args
+			.resolve_archive_path_for_provider(media_helper.data.provider.as_str())
+			.is_some();
+		if has_archive && media_helper.data.stage >= MediaStage::Moved {
+			media_helper.data.advance_stage(MediaStage::Archived);
 		}
 	}
 
 	// notify the user if there are still files that have not been moved
 	if !utils::find_editable_files(download_path)?.is_empty() {
-		println!("{} Found Editable file(s) that have not been moved.\nConsider running recovery mode if no other ytdlr is running (with 0 URLs)", "WARN".color(Color::TrueColor { r: 255, g: 135, b: 0 }));
+		println!(
+			"{} {}",
+			"WARN".color(Color::TrueColor { r: 255, g: 135, b: 0 }),
+			tr(Msg::EditableFilesRemaining)
+		);
 	}
 
 	return Ok(EditCtrl::Finished);
@@ -1560,33 +3116,225 @@ fn try_gen_final_path(dir_path: &Path, filename: &Path) -> Option<PathBuf> {
 	return Some(to_path);
 }
 
+/// Get the final path for `filename` in `dir_path`, resolving a conflict with a already existing file
+/// Interactive mode will ask what to do (unless a collision policy is set), non-interactive mode will follow the collision policy
+/// Returns [None] if the file should not be moved
+fn resolve_final_path(
+	main_args: &CliDerive,
+	sub_args: &CommandDownload,
+	pgbar: &ProgressBar,
+	from_path: &Path,
+	dir_path: &Path,
+	filename: &Path,
+) -> Result<Option<PathBuf>, crate::Error> {
+	let to_path = dir_path.join(filename);
+
+	if !to_path.exists() {
+		return Ok(Some(to_path));
+	}
+
+	let policy = match sub_args.collision_policy {
+		Some(v) => v,
+		// the collision prompt has no default, so "--assume-yes" uses the non-interactive behavior
+		None if main_args.is_interactive() && !utils::assume_yes() => {
+			pgbar.suspend(|| return ask_collision_policy(from_path, &to_path))?
+		},
+		None => CollisionPolicy::Number,
+	};
+
+	debug!("Collision Policy for \"{}\": {:?}", to_path.display(), policy);
+
+	return Ok(match policy {
+		CollisionPolicy::Overwrite => Some(to_path),
+		CollisionPolicy::Number => try_gen_final_path(dir_path, filename),
+		CollisionPolicy::Skip => {
+			println!(
+				"Skipping moving \"{}\", because it already exists in the output directory",
+				filename.display()
+			);
+
+			None
+		},
+	});
+}
+
+/// Print information about both files and ask the user what to do
+fn ask_collision_policy(from_path: &Path, to_path: &Path) -> Result<CollisionPolicy, crate::Error> {
+	/// Get a short summary of the file, like `1.00 MiB, 00:03:00.00, 2024-01-01 00:00:00`
+	fn file_summary(path: &Path) -> String {
+		let metadata = std::fs::metadata(path).ok();
+		let size = metadata.as_ref().map_or_else(
+			|| return "unknown size".to_owned(),
+			|v| return HumanBytes(v.len()).to_string(),
+		);
+		let date = metadata.and_then(|v| return v.modified().ok()).map_or_else(
+			|| return "unknown date".to_owned(),
+			|v| {
+				return libytdlr::chrono::DateTime::<libytdlr::chrono::Local>::from(v)
+					.format("%Y-%m-%d %H:%M:%S")
+					.to_string();
+			},
+		);
+		let duration = ffmpeg::ffmpeg_probe(path)
+			.ok()
+			.and_then(|v| return ffmpeg::parse_duration(&v).ok().map(str::to_owned))
+			.unwrap_or_else(|| return "unknown duration".to_owned());
+
+		return format!("{size}, {duration}, {date}");
+	}
+
+	println!(
+		"{}",
+		trf(
+			Msg::CollisionExists,
+			&[
+				&to_path.file_name().unwrap_or_default().to_string_lossy(),
+				&file_summary(from_path),
+				&file_summary(to_path),
+			]
+		)
+	);
+
+	loop {
+		match utils::get_input(tr(Msg::CollisionAsk), &["o", "n", "s", "d"], "")?.as_str() {
+			"o" => return Ok(CollisionPolicy::Overwrite),
+			"n" => return Ok(CollisionPolicy::Number),
+			"s" => return Ok(CollisionPolicy::Skip),
+			"d" => {
+				for (name, path) in [("new", from_path), ("existing", to_path)] {
+					match ffmpeg::ffmpeg_probe(path) {
+						Ok(v) => println!("{name}: \"{}\"\n{v}", path.display()),
+						Err(err) => println!("{name}: \"{}\" could not be probed: {err}", path.display()),
+					}
+				}
+			},
+			_ => unreachable!("get_input should only return a OK value from the possible array"),
+		}
+	}
+}
+
+/// Remove the sidecar of a media file which has been moved, only warning on failure
+/// because a left-over sidecar does not affect anything
+fn remove_sidecar_warn(media_path: &Path) {
+	if let Err(err) = MediaInfo::remove_sidecar(media_path) {
+		warn!(
+			"Removing sidecar for \"{}\" failed: {}",
+			media_path.to_string_lossy(),
+			err
+		);
+	}
+}
+
+/// Move the sidecar files (like subtitles, thumbnails and info json) of the media at `from_path` next to `to_path`, renamed to match it
+/// Thumbnails are also searched in the thumbnail directory of `download_path`, see [`utils::find_sidecar_files`]
+/// With a `trash` the sidecar files are recorded in it like the media, so that "restore" also moves them back
+/// Errors are only logged, because the media itself has already been moved
+fn move_sidecar_files(download_path: &Path, from_path: &Path, to_path: &Path, mut trash: Option<&mut TrashOperation>) {
+	let Some(to_stem) = to_path.file_stem() else {
+		return;
+	};
+
+	for (sidecar_path, suffix) in utils::find_sidecar_files(from_path, &[download_path.join(PATHS_THUMBNAIL_DIR)]) {
+		let mut sidecar_name = to_stem.to_os_string();
+		sidecar_name.push(suffix);
+		let sidecar_to = to_path.with_file_name(sidecar_name);
+
+		trace!(
+			"Moving sidecar file \"{}\" to \"{}\"",
+			sidecar_path.to_string_lossy(),
+			sidecar_to.to_string_lossy()
+		);
+
+		let res = match trash.as_deref_mut() {
+			Some(trash) => trash_sidecar_file(trash, &sidecar_path, &sidecar_to),
+			None => trash::move_file(&sidecar_path, &sidecar_to),
+		};
+
+		if let Err(err) = res {
+			warn!(
+				"Moving sidecar file \"{}\" failed: {}",
+				sidecar_path.to_string_lossy(),
+				err
+			);
+		}
+	}
+}
+
+/// Move the sidecar file at `from_path` to `to_path` like the media in [`finish_with_move`], keeping the original and the overwritten file in `trash`
+fn trash_sidecar_file(trash: &mut TrashOperation, from_path: &Path, to_path: &Path) -> Result<(), crate::Error> {
+	let overwritten = if to_path.exists() {
+		Some(trash.trash_overwritten(to_path)?)
+	} else {
+		None
+	};
+
+	if let Err(err) = std::fs::copy(from_path, to_path).attach_path_err(from_path) {
+		// put the trashed file back, because nothing has replaced it
+		if let Some(overwritten) = overwritten {
+			trash::move_file(&overwritten, to_path)?;
+		}
+
+		return Err(err);
+	}
+
+	let original = trash.trash_original(from_path)?;
+
+	return trash.push(TrashEntry {
+		from: from_path.to_owned(),
+		to: to_path.to_owned(),
+		original,
+		overwritten,
+	});
+}
+
+/// Advance the stage of the media with `key` in `final_media`, if it exists
+fn advance_stage_of(final_media: &mut MediaInfoArr, key: &str, stage: MediaStage) {
+	if let Some(media_helper) = final_media.get_mut(key) {
+		media_helper.data.advance_stage(stage);
+	}
+}
+
 /// Move all media in `final_media` to it final resting place in `download_path`
 /// Helper to separate out the possible paths
+/// set "organize" to sort the media into "artist/album" directories, see [`organized_filename`]
 fn finish_with_move(
+	main_args: &CliDerive,
 	sub_args: &CommandDownload,
 	download_path: &std::path::Path,
 	pgbar: &ProgressBar,
-	final_media: &MediaInfoArr,
+	final_media: &mut MediaInfoArr,
+	organize: bool,
 ) -> Result<(), crate::Error> {
-	debug!("Moving all files to the final destination");
+	debug!("Moving all files to the final destination (organize: {organize})");
 
-	let final_dir_path = sub_args.output_path.as_ref().map_or_else(
-		|| {
-			return dirs::download_dir()
-				.unwrap_or_else(|| return PathBuf::from("."))
-				.join("ytdlr-out");
-		},
-		|v| return v.clone(),
-	);
+	let final_dir_path = final_dir_path(sub_args);
 	std::fs::create_dir_all(&final_dir_path).attach_path_err(&final_dir_path)?;
 
 	let mut moved_count = 0usize;
 	pgbar.set_draw_target(ProgressDrawTarget::stderr());
+	let mut trash = sub_args
+		.use_trash
+		.then(|| return TrashOperation::new(&download_path.join(trash::TRASH_DIR_NAME)));
 
-	for media_helper in final_media.mediainfo_map.values() {
+	// finish in the same order as the media was downloaded / edited in
+	for media_key in final_media.sorted_keys() {
 		pgbar.inc(1);
-		let media = &media_helper.data;
-		let Some((media_filename, final_filename)) = utils::convert_mediainfo_to_filename(media) else {
+		let media = &final_media
+			.get(&media_key)
+			.expect("Expected key from \"sorted_keys\" to exist")
+			.data;
+		// media that has already been moved does not exist in the download directory anymore
+		if media.stage >= MediaStage::Moved {
+			debug!("Skipping media \"{}\", because it was already moved", media.id);
+
+			continue;
+		}
+		let converted = if organize {
+			organized_filename(download_path, media)
+		} else {
+			utils::convert_mediainfo_to_filename(media)
+		};
+		let Some((media_filename, final_filename)) = converted else {
 			warn!(
 				"Found MediaInfo which returned \"None\" from \"convert_mediainfo_to_filename\", skipping (id: \"{}\")",
 				media.id
@@ -1595,7 +3343,24 @@ fn finish_with_move(
 			continue;
 		};
 		let from_path = download_path.join(media_filename);
-		let Some(to_path) = try_gen_final_path(&final_dir_path, &final_filename) else {
+		// "join" keeps absolute route directories as-is
+		let mut media_dir_path = match sub_args.output_route_for(media.provider.as_str()) {
+			Some(route_dir) => Cow::Owned(final_dir_path.join(route_dir)),
+			None => Cow::Borrowed(final_dir_path.as_path()),
+		};
+		// organized filenames contain the artist and album directories
+		let final_filename = match (final_filename.parent(), final_filename.file_name()) {
+			(Some(parent), Some(filename)) if !parent.as_os_str().is_empty() => {
+				media_dir_path = Cow::Owned(media_dir_path.join(parent));
+
+				PathBuf::from(filename)
+			},
+			_ => final_filename,
+		};
+		std::fs::create_dir_all(&media_dir_path).attach_path_err(media_dir_path.as_ref())?;
+		let Some(to_path) =
+			resolve_final_path(main_args, sub_args, pgbar, &from_path, &media_dir_path, &final_filename)?
+		else {
 			continue; // file will be found again in the next run via recovery
 		};
 		trace!(
@@ -1603,19 +3368,44 @@ fn finish_with_move(
 			from_path.to_string_lossy(),
 			to_path.to_string_lossy()
 		);
+		// keep the file that would be overwritten, so that the move can be undone
+		let overwritten = match trash.as_ref() {
+			Some(trash) if to_path.exists() => Some(trash.trash_overwritten(&to_path)?),
+			_ => None,
+		};
 		// copy has to be used, because it cannot be ensured the "final_path" is on the same file-system
 		// and a "move"(mv) function does not exist in standard rust
-		match std::fs::copy(&from_path, to_path) {
+		match std::fs::copy(&from_path, &to_path) {
 			Ok(_) => (),
 			Err(err) => {
 				println!("Couldnt move file \"{}\", error: {}", from_path.to_string_lossy(), err);
+				// put the trashed file back, because nothing has replaced it
+				if let Some(overwritten) = overwritten {
+					trash::move_file(&overwritten, &to_path)?;
+				}
 				continue;
 			},
 		};
 
-		trace!("Removing file \"{}\"", from_path.to_string_lossy());
-		// remove the original file, because copy was used
-		std::fs::remove_file(&from_path).attach_path_err(from_path)?;
+		if let Some(trash) = trash.as_mut() {
+			trace!("Trashing file \"{}\"", from_path.to_string_lossy());
+			let original = trash.trash_original(&from_path)?;
+			trash.push(TrashEntry {
+				from: from_path.clone(),
+				to: to_path.clone(),
+				original,
+				overwritten,
+			})?;
+		} else {
+			trace!("Removing file \"{}\"", from_path.to_string_lossy());
+			// remove the original file, because copy was used
+			std::fs::remove_file(&from_path).attach_path_err(&from_path)?;
+		}
+		remove_sidecar_warn(&from_path);
+		move_sidecar_files(download_path, &from_path, &to_path, trash.as_mut());
+		event_log::write_moved(media, &from_path, &to_path);
+		notification::notify_moved(media, &to_path);
+		advance_stage_of(final_media, &media_key, MediaStage::Moved);
 
 		moved_count += 1;
 	}
@@ -1631,12 +3421,29 @@ fn finish_with_move(
 	return Ok(());
 }
 
+/// Get the organized path of `media` (like "artist/album/title.ext"), with the tags read from its file
+/// See [`utils::convert_mediainfo_to_organized`], if the tags could not be read, only the fallbacks are used
+fn organized_filename<'a>(download_path: &Path, media: &'a MediaInfo) -> Option<(&'a PathBuf, PathBuf)> {
+	let media_path = download_path.join(media.filename.as_ref()?);
+
+	let metadata = match ffmpeg::ffmpeg_probe(&media_path) {
+		Ok(probe) => ffmpeg::parse_metadata(&probe),
+		Err(err) => {
+			warn!("Could not read tags of \"{}\": {}", media_path.display(), err);
+
+			HashMap::new()
+		},
+	};
+
+	return utils::convert_mediainfo_to_organized(media, &metadata);
+}
+
 /// Move all media in `final_media` to a temporary `final` directory (still in the tmpdir) and open the tagger
 fn finish_with_tagger(
 	sub_args: &CommandDownload,
 	download_path: &std::path::Path,
 	pgbar: &ProgressBar,
-	final_media: &MediaInfoArr,
+	final_media: &mut MediaInfoArr,
 ) -> Result<(), crate::Error> {
 	debug!("Renaming files for Tagger");
 
@@ -1644,9 +3451,19 @@ fn finish_with_tagger(
 	std::fs::create_dir_all(&final_dir_path).attach_path_err(&final_dir_path)?;
 	pgbar.set_draw_target(ProgressDrawTarget::stderr());
 
-	for media_helper in final_media.mediainfo_map.values() {
+	// finish in the same order as the media was downloaded / edited in
+	for media_key in final_media.sorted_keys() {
 		pgbar.inc(1);
-		let media = &media_helper.data;
+		let media = &final_media
+			.get(&media_key)
+			.expect("Expected key from \"sorted_keys\" to exist")
+			.data;
+		// media that has already been moved does not exist in the download directory anymore
+		if media.stage >= MediaStage::Moved {
+			debug!("Skipping media \"{}\", because it was already moved", media.id);
+
+			continue;
+		}
 		let Some((media_filename, final_filename)) = utils::convert_mediainfo_to_filename(media) else {
 			warn!(
 				"Found MediaInfo which returned \"None\" from \"convert_mediainfo_to_filename\", skipping (id: \"{}\")",
@@ -1660,7 +3477,10 @@ fn finish_with_tagger(
 		let Some(to_path) = try_gen_final_path(&final_dir_path, &final_filename) else {
 			continue; // file will be found again in the next run via recovery
 		};
-		std::fs::rename(&from_path, to_path).attach_path_err(from_path)?;
+		std::fs::rename(&from_path, &to_path).attach_path_err(&from_path)?;
+		remove_sidecar_warn(&from_path);
+		move_sidecar_files(download_path, &from_path, &to_path, None);
+		advance_stage_of(final_media, &media_key, MediaStage::Moved);
 	}
 
 	pgbar.finish_and_clear();
@@ -1685,9 +3505,6 @@ fn try_find_and_read_recovery_files(
 
 	let mut read_files: Vec<PathBuf> = Vec::new();
 
-	let mut s = sysinfo::System::new();
-	s.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
-
 	for file in path.read_dir().attach_path_err(path)?.filter_map(|res| {
 		let entry = res.ok()?;
 
@@ -1707,19 +3524,26 @@ fn try_find_and_read_recovery_files(
 			}
 			opt.unwrap().1 // unwrap because "None" is checked above
 		};
-		let pid_of_file = match pid_str.parse::<usize>() {
-			Err(_) => continue,
-			Ok(v) => v,
+		let Ok(pid_of_file) = pid_str.parse::<u32>() else {
+			continue;
 		};
 		// check that the pid of the file is actually not running anymore
 		// and just ignore them if the process exists
-		if s.process(sysinfo::Pid::from(pid_of_file)).is_some() {
+		if libytdlr::utils::is_process_alive(pid_of_file) {
 			info!("Found recovery file for pid {pid_of_file}, but the process still existed");
 			continue;
 		}
 		// for now just add them regardless if they exist or not in the array
+		// except media that is already fully finished
 		for media in Recovery::read_recovery(&file)? {
-			finished_media_vec.insert_with_comment(media, format!("From Recovery file of pid {pid_of_file}"));
+			if media.stage >= MediaStage::Archived {
+				debug!(
+					"Skipping recovered media \"{}\", because it was already archived",
+					media.id
+				);
+				continue;
+			}
+			finished_media_vec.insert_recovered(media);
 		}
 		read_files.push(file);
 	}
@@ -1732,6 +3556,8 @@ fn try_find_and_read_recovery_files(
 				.expect("Expected MediaInfo to have a filename from \"find_editable_files\"");
 
 			media.data.set_filename(new_media_filename);
+			// a file existing in the download directory means it has at least been downloaded
+			media.data.advance_stage(MediaStage::Downloaded);
 		}
 	}
 
@@ -1742,6 +3568,34 @@ fn try_find_and_read_recovery_files(
 mod test {
 	use super::*;
 
+	mod media_info_arr {
+		use super::*;
+
+		#[test]
+		fn test_order() {
+			let mut arr = MediaInfoArr::new();
+
+			arr.insert(MediaInfo::new("id3", "youtube"));
+			arr.insert(MediaInfo::new("id1", "youtube"));
+			arr.insert(MediaInfo::new("id2", "soundcloud"));
+			// updating a existing media should keep its order
+			arr.insert(MediaInfo::new("id3", "youtube").with_title("Updated"));
+
+			let ids: Vec<&str> = arr
+				.as_sorted_vec()
+				.into_iter()
+				.map(|v| return v.data.id.as_str())
+				.collect();
+
+			assert_eq!(vec!["id3", "id1", "id2"], ids);
+			assert_eq!(
+				Some("Updated"),
+				arr.get_mut("youtube-id3").and_then(|v| return v.data.title.as_deref())
+			);
+			assert_eq!(vec!["youtube-id3", "youtube-id1", "soundcloud-id2"], arr.sorted_keys());
+		}
+	}
+
 	mod recovery {
 		use super::*;
 
@@ -1764,6 +3618,301 @@ mod test {
 				Some(MediaInfo::new("id", "provider").with_title("Some Title ver.2")),
 				Recovery::try_from_line(input)
 			);
+
+			// test a line with a stage
+			let input = "'provider'-'id'-'edited'-Some Title";
+			assert_eq!(
+				Some(
+					MediaInfo::new("id", "provider")
+						.with_title("Some Title")
+						.with_stage(MediaStage::Edited)
+				),
+				Recovery::try_from_line(input)
+			);
+		}
+
+		#[test]
+		fn test_fmt_line_roundtrip() {
+			let media = MediaInfo::new("id", "provider")
+				.with_title("'quoted'-Title")
+				.with_stage(MediaStage::Moved);
+			let line = Recovery::fmt_line(&media);
+
+			assert_eq!("'provider'-'id'-'moved'-'quoted'-Title\n", line);
+			assert_eq!(Some(media), Recovery::try_from_line(line.trim_end()));
+		}
+
+		#[test]
+		fn test_first_unreviewed_index() {
+			let mut arr = MediaInfoArr::new();
+
+			arr.insert(MediaInfo::new("id1", "youtube").with_stage(MediaStage::Edited));
+			arr.insert(MediaInfo::new("id2", "youtube").with_stage(MediaStage::Reviewed));
+			arr.insert(MediaInfo::new("id3", "youtube").with_stage(MediaStage::Downloaded));
+			arr.insert(MediaInfo::new("id4", "youtube").with_stage(MediaStage::Reviewed));
+
+			// the edit stage state survives a recovery roundtrip
+			let mut recovered = MediaInfoArr::new();
+			for media in arr.as_sorted_vec() {
+				let line = Recovery::fmt_line(&media.data.clone().with_title("Title"));
+				recovered.insert_recovered(Recovery::try_from_line(line.trim_end()).expect("Expected a valid line"));
+			}
+
+			assert_eq!(2, first_unreviewed_index(&recovered, &recovered.sorted_keys()));
+
+			advance_stage_of(&mut recovered, "youtube-id3", MediaStage::Reviewed);
+			assert_eq!(4, first_unreviewed_index(&recovered, &recovered.sorted_keys()));
+		}
+	}
+
+	#[test]
+	fn test_quote_arg() {
+		use std::ffi::OsStr;
+
+		assert_eq!("--no-quiet", quote_arg(OsStr::new("--no-quiet")));
+		assert_eq!("home:/tmp/dl", quote_arg(OsStr::new("home:/tmp/dl")));
+		assert_eq!("''", quote_arg(OsStr::new("")));
+		assert_eq!(
+			r"'after_video:PARSE_END '\''%(extractor)s'\'''",
+			quote_arg(OsStr::new("after_video:PARSE_END '%(extractor)s'"))
+		);
+	}
+
+	mod download_summary {
+		use super::*;
+
+		/// Test helper to create a [UrlResult]
+		fn url_result(url: &str, downloaded: usize, error: Option<&str>) -> UrlResult {
+			return UrlResult {
+				downloaded,
+				error: error.map(str::to_owned),
+				..UrlResult::new(url)
+			};
+		}
+
+		#[test]
+		fn test_failed() {
+			let summary = DownloadSummary {
+				results: vec![
+					url_result("https://a", 1, None),
+					url_result("https://b", 0, Some("some error")),
+					url_result("https://c", 2, None),
+				],
+			};
+
+			assert_eq!(1, summary.failed_count());
+			// the 4th url was not started
+			assert_eq!(vec![1, 3], summary.failed_indexes(4));
+		}
+
+		#[test]
+		fn test_format_table() {
+			let summary = DownloadSummary {
+				results: vec![
+					UrlResult {
+						skipped_archive: 10,
+						skipped_unavailable: 1,
+						..url_result("https://example.com/long", 2, None)
+					},
+					UrlResult {
+						errors: 1,
+						..url_result("https://b", 0, Some("some error"))
+					},
+				],
+			};
+
+			assert_eq!(
+				"URL                       Downloaded  In Archive  Blacklisted  Unavailable  Errors  Status\n\
+				 https://example.com/long  2           10          0            1            0       ok\n\
+				 https://b                 0           0           0            0            1       failed",
+				summary.format_table()
+			);
+		}
+	}
+
+	mod parse_selection {
+		use super::*;
+
+		#[test]
+		fn test_basic() {
+			assert_eq!(Ok(vec![0, 2]), parse_selection("1 3", 5));
+			assert_eq!(Ok(vec![3, 1, 2, 4]), parse_selection(" 4, 2-3 5\n", 5));
+			assert_eq!(Ok(vec![0, 1]), parse_selection("1 1 2 1-2", 5));
+			assert_eq!(Ok(vec![0, 1, 2]), parse_selection("A", 3));
+			assert_eq!(Ok(Vec::<usize>::new()), parse_selection("\n", 3));
+		}
+
+		#[test]
+		fn test_invalid() {
+			assert!(parse_selection("0", 3).is_err());
+			assert!(parse_selection("4", 3).is_err());
+			assert!(parse_selection("3-1", 3).is_err());
+			assert!(parse_selection("x", 3).is_err());
+		}
+	}
+
+	mod progress_message {
+		use super::*;
+
+		#[test]
+		fn test_basic() {
+			assert_eq!("Some Title", progress_message("Some Title", &TransferProgress::new(0)));
+			assert_eq!(
+				"526.19KiB/s ETA 01:16 Some Title",
+				progress_message(
+					"Some Title",
+					&TransferProgress::new(50).with_speed("526.19KiB/s").with_eta("01:16")
+				)
+			);
+			assert_eq!(
+				"10.57MiB/s Some Title",
+				progress_message("Some Title", &TransferProgress::new(1).with_speed("10.57MiB/s"))
+			);
+		}
+
+		#[test]
+		fn test_skipped_message() {
+			assert_eq!(
+				"Skipped \"someid\" (already in the archive)",
+				skipped_message(Some("someid"), "already in the archive")
+			);
+			assert_eq!("Skipped media (blacklisted)", skipped_message(None, "blacklisted"));
+		}
+
+		#[test]
+		fn test_phase_position() {
+			assert_eq!(25, phase_position(1, 2, 50));
+			assert_eq!(50, phase_position(1, 2, 100));
+			assert_eq!(75, phase_position(2, 2, 50));
+			assert_eq!(100, phase_position(2, 2, 100));
+			assert_eq!(50, phase_position(1, 1, 50));
+			// out-of-range values should not go over 100
+			assert_eq!(100, phase_position(3, 2, 100));
+		}
+
+		#[test]
+		fn test_live() {
+			assert_eq!(
+				"1.00 MiB Some Title",
+				live_progress_message(
+					"Some Title",
+					&LiveProgress {
+						bytes: 1_048_576,
+						..Default::default()
+					}
+				)
+			);
+			assert_eq!(
+				"45.21 MiB 00:00:22 2.01MiB/s Some Title",
+				live_progress_message(
+					"Some Title",
+					&LiveProgress {
+						bytes:   47_406_120,
+						speed:   Some("2.01MiB/s".to_owned()),
+						elapsed: Some("00:00:22".to_owned()),
+					}
+				)
+			);
+		}
+	}
+
+	mod find_partial_downloads {
+		use super::*;
+		use clap::Parser;
+		use std::{
+			fs::File,
+			time::SystemTime,
+		};
+
+		/// Test helper to create a file in `dir` with a modified time of `age` ago
+		fn create_file_with_age(dir: &Path, name: &str, age: Duration) {
+			let file = File::create(dir.join(name)).expect("Expected file to be created");
+			file.set_modified(SystemTime::now() - age)
+				.expect("Expected modified time to be set");
+		}
+
+		#[test]
+		fn test_find() {
+			let testdir = tempfile::Builder::new()
+				.prefix("ytdl-test-find_partial_downloads-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let main_args = CliDerive::parse_from(["", "--interactive", "false", "download"]);
+
+			// no temp directory yet
+			assert!(find_partial_downloads(&main_args, testdir.path()).unwrap().is_empty());
+
+			let temp_path = testdir.path().join(PATHS_TEMP_DIR);
+			std::fs::create_dir_all(&temp_path).unwrap();
+
+			let old = PARTIAL_MIN_AGE * 2;
+			create_file_with_age(&temp_path, "'youtube'-'----------1'-Some Title.f251.webm.part", old);
+			create_file_with_age(
+				&temp_path,
+				"'youtube'-'----------1'-Some Title.f137.mp4.part-Frag3",
+				old,
+			);
+			// no URL is known for the provider
+			create_file_with_age(&temp_path, "'soundcloud'-'1234'-Other Title.mp3.part", old);
+			// still in use by a other process
+			create_file_with_age(&temp_path, "'youtube'-'----------2'-Recent.webm.part", Duration::ZERO);
+			// not a partial file
+			create_file_with_age(&temp_path, "'youtube'-'----------3'-Finished.webm", old);
+
+			assert_eq!(
+				vec!["https://www.youtube.com/watch?v=----------1".to_owned()],
+				find_partial_downloads(&main_args, testdir.path()).unwrap()
+			);
+		}
+	}
+
+	mod move_sidecar_files {
+		use super::*;
+
+		#[test]
+		fn test_trash_restore() {
+			let testdir = tempfile::Builder::new()
+				.prefix("ytdl-test-move_sidecar_files-")
+				.tempdir()
+				.expect("Expected a temp dir to be created");
+			let download_dir = testdir.path().join("download");
+			let out_dir = testdir.path().join("out");
+			let thumbnail_dir = download_dir.join(PATHS_THUMBNAIL_DIR);
+			std::fs::create_dir_all(&thumbnail_dir).unwrap();
+			std::fs::create_dir_all(&out_dir).unwrap();
+
+			// the media itself has already been moved
+			let from = download_dir.join("'youtube'-'-----------'-Title.mkv");
+			let to = out_dir.join("Title.mkv");
+			std::fs::write(&to, "media").unwrap();
+			std::fs::write(download_dir.join("'youtube'-'-----------'-Title.en.vtt"), "subs").unwrap();
+			std::fs::write(thumbnail_dir.join("'youtube'-'-----------'-Title.jpg"), "thumbnail").unwrap();
+			// a existing sidecar at the destination is overwritten
+			std::fs::write(out_dir.join("Title.en.vtt"), "old subs").unwrap();
+
+			let mut trash = TrashOperation::new(&download_dir.join(trash::TRASH_DIR_NAME));
+			move_sidecar_files(&download_dir, &from, &to, Some(&mut trash));
+
+			assert_eq!(2, trash.entries().len());
+			assert_eq!("subs", std::fs::read_to_string(out_dir.join("Title.en.vtt")).unwrap());
+			assert_eq!("thumbnail", std::fs::read_to_string(out_dir.join("Title.jpg")).unwrap());
+			assert!(!thumbnail_dir.join("'youtube'-'-----------'-Title.jpg").exists());
+
+			assert_eq!(2, trash.restore().unwrap());
+
+			assert_eq!(
+				"subs",
+				std::fs::read_to_string(download_dir.join("'youtube'-'-----------'-Title.en.vtt")).unwrap()
+			);
+			assert_eq!(
+				"thumbnail",
+				std::fs::read_to_string(thumbnail_dir.join("'youtube'-'-----------'-Title.jpg")).unwrap()
+			);
+			assert_eq!(
+				"old subs",
+				std::fs::read_to_string(out_dir.join("Title.en.vtt")).unwrap()
+			);
+			assert!(!out_dir.join("Title.jpg").exists());
 		}
 	}
 
@@ -0,0 +1,486 @@
+//! Module for the "--tui" mode of the edit loop, which lists all downloaded media in a terminal UI instead of asking for every media with a text prompt
+//!
+//! Editors and players are run outside of the terminal UI, which is suspended while they run
+//! The [`TuiTerminal`] is also used by other terminal UIs (like "archive browse")
+
+use std::{
+	io::Stdout,
+	time::Duration,
+};
+
+use libytdlr::{
+	data::cache::media_stage::MediaStage,
+	error::IOErrorToError,
+};
+use ratatui::{
+	backend::CrosstermBackend,
+	crossterm::{
+		event::{
+			self,
+			Event,
+			KeyCode,
+			KeyEvent,
+			KeyEventKind,
+			KeyModifiers,
+		},
+		execute,
+		terminal::{
+			disable_raw_mode,
+			enable_raw_mode,
+			EnterAlternateScreen,
+			LeaveAlternateScreen,
+		},
+	},
+	layout::{
+		Constraint,
+		Layout,
+	},
+	style::{
+		Modifier,
+		Style,
+	},
+	widgets::{
+		Block,
+		Borders,
+		List,
+		ListItem,
+		ListState,
+		Paragraph,
+	},
+	Frame,
+	Terminal,
+};
+
+use crate::messages::{
+	tr,
+	trf,
+	Msg,
+};
+
+/// Interval in which termination requests are checked while waiting for input
+pub const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Which editor to use for [`TuiAction::Edit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorChoice {
+	/// Choose the editor from the filetype, like "y" in the text prompts
+	Auto,
+	/// Use the audio editor
+	Audio,
+	/// Use the video editor
+	Video,
+}
+
+/// Actions of the edit TUI, which are run outside of the terminal UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuiAction {
+	/// Edit the selected media
+	Edit(EditorChoice),
+	/// Play the selected media
+	Play,
+}
+
+/// The result of running a [`TuiAction`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ActionOutcome {
+	/// The new stage of the media, [`None`] if the media has not been changed
+	pub stage:   Option<MediaStage>,
+	/// The message to show in the status line
+	pub message: Option<String>,
+}
+
+impl ActionOutcome {
+	/// Create a outcome which only shows `message`, without changing the media
+	pub fn message<M: Into<String>>(message: M) -> Self {
+		return Self {
+			stage:   None,
+			message: Some(message.into()),
+		};
+	}
+}
+
+/// A media listed in the edit TUI
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuiEntry {
+	/// The key of the media in the finished media
+	pub key:   String,
+	/// The title to display
+	pub title: String,
+	/// The stage of the media
+	pub stage: MediaStage,
+}
+
+/// What a key press in the edit TUI results in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyOutcome {
+	/// Nothing has to be done besides re-drawing
+	None,
+	/// Run the action on the selected media
+	Run(TuiAction),
+	/// Editing is done
+	Done,
+	/// Termination has been requested (like "Ctrl+C", which does not send a signal in raw mode)
+	Abort,
+}
+
+/// State of the edit TUI, separate from the terminal so that it can be tested
+#[derive(Debug)]
+struct EditTuiState {
+	/// All listed media, not empty
+	entries:  Vec<TuiEntry>,
+	/// The index of the selected media
+	selected: usize,
+	/// The message of the status line
+	status:   String,
+}
+
+impl EditTuiState {
+	/// Create a new state for `entries`, selecting the last entry if `reverse` is set
+	fn new(entries: Vec<TuiEntry>, reverse: bool) -> Self {
+		let selected = if reverse { entries.len().saturating_sub(1) } else { 0 };
+
+		return Self {
+			entries,
+			selected,
+			status: String::new(),
+		};
+	}
+
+	/// Get the key of the selected media
+	fn selected_key(&self) -> &str {
+		return &self.entries[self.selected].key;
+	}
+
+	/// Select the next media
+	/// Returns `false` if the last media is already selected
+	fn select_next(&mut self) -> bool {
+		if self.selected + 1 >= self.entries.len() {
+			return false;
+		}
+
+		self.selected += 1;
+
+		return true;
+	}
+
+	/// Select the previous media
+	/// Returns `false` if the first media is already selected
+	fn select_previous(&mut self) -> bool {
+		if self.selected == 0 {
+			return false;
+		}
+
+		self.selected -= 1;
+
+		return true;
+	}
+
+	/// Handle a key press
+	/// The key bindings are the same as for the text prompts, with additional navigation keys
+	fn handle_key(&mut self, key: KeyEvent) -> KeyOutcome {
+		// ignore key releases and repeats, which some platforms report as separate events
+		if key.kind != KeyEventKind::Press {
+			return KeyOutcome::None;
+		}
+
+		if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+			return KeyOutcome::Abort;
+		}
+
+		self.status.clear();
+
+		return match key.code {
+			KeyCode::Enter | KeyCode::Char('y') => KeyOutcome::Run(TuiAction::Edit(EditorChoice::Auto)),
+			KeyCode::Char('a') => KeyOutcome::Run(TuiAction::Edit(EditorChoice::Audio)),
+			KeyCode::Char('v') => KeyOutcome::Run(TuiAction::Edit(EditorChoice::Video)),
+			KeyCode::Char('p') => KeyOutcome::Run(TuiAction::Play),
+			// skipping the last media finishes editing, like in the text prompts
+			KeyCode::Char('n') | KeyCode::Right => {
+				if self.select_next() {
+					KeyOutcome::None
+				} else {
+					KeyOutcome::Done
+				}
+			},
+			KeyCode::Char('b') | KeyCode::Left => {
+				if !self.select_previous() {
+					tr(Msg::CannotGoBack).clone_into(&mut self.status);
+				}
+
+				KeyOutcome::None
+			},
+			KeyCode::Down | KeyCode::Char('j') => {
+				self.select_next();
+
+				KeyOutcome::None
+			},
+			KeyCode::Up | KeyCode::Char('k') => {
+				self.select_previous();
+
+				KeyOutcome::None
+			},
+			KeyCode::Char('q') | KeyCode::Esc => KeyOutcome::Done,
+			_ => KeyOutcome::None,
+		};
+	}
+
+	/// Apply the outcome of running `action` on the selected media
+	/// A edited media moves the selection to the next media, like in the text prompts
+	fn apply_outcome(&mut self, action: TuiAction, outcome: ActionOutcome) {
+		self.status = outcome.message.unwrap_or_default();
+
+		let Some(stage) = outcome.stage else {
+			return;
+		};
+
+		self.entries[self.selected].stage = stage;
+
+		if matches!(action, TuiAction::Edit(_)) {
+			self.select_next();
+		}
+	}
+
+	/// Draw the state onto `frame`
+	fn draw(&self, frame: &mut Frame) {
+		let [list_area, status_area, keys_area] =
+			Layout::vertical([Constraint::Min(1), Constraint::Length(1), Constraint::Length(1)]).areas(frame.area());
+
+		let items: Vec<ListItem> = self
+			.entries
+			.iter()
+			.map(|v| return ListItem::new(format!("{:<10} {}", v.stage.as_str(), v.title)))
+			.collect();
+		let list = List::new(items)
+			.block(
+				Block::default()
+					.borders(Borders::ALL)
+					.title(trf(Msg::TuiTitle, &[&(self.selected + 1), &self.entries.len()])),
+			)
+			.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+			.highlight_symbol("> ");
+		let mut list_state = ListState::default().with_selected(Some(self.selected));
+
+		frame.render_stateful_widget(list, list_area, &mut list_state);
+		frame.render_widget(Paragraph::new(self.status.as_str()), status_area);
+		frame.render_widget(
+			Paragraph::new(tr(Msg::TuiKeys)).style(Style::default().add_modifier(Modifier::DIM)),
+			keys_area,
+		);
+	}
+}
+
+/// The terminal a terminal UI is drawn on, the terminal is restored when dropped
+pub struct TuiTerminal {
+	/// The terminal in raw mode and on the alternate screen
+	terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TuiTerminal {
+	/// Switch the terminal into raw mode and onto the alternate screen
+	pub fn init() -> Result<Self, crate::Error> {
+		enable_raw_mode().attach_location_err("tui enable raw mode")?;
+
+		// create the instance before anything else can fail, so that raw mode is disabled again when dropped
+		let mut tui = Self {
+			terminal: Terminal::new(CrosstermBackend::new(std::io::stdout())).attach_location_err("tui terminal")?,
+		};
+
+		execute!(tui.terminal.backend_mut(), EnterAlternateScreen).attach_location_err("tui enter screen")?;
+		tui.terminal.clear().attach_location_err("tui clear")?;
+
+		return Ok(tui);
+	}
+
+	/// Draw a frame with `render`
+	pub fn draw<F: FnOnce(&mut Frame)>(&mut self, render: F) -> Result<(), crate::Error> {
+		self.terminal.draw(render).attach_location_err("tui draw")?;
+
+		return Ok(());
+	}
+
+	/// Restore the terminal, so that other programs (like a editor) can use it
+	pub fn suspend(&mut self) -> Result<(), crate::Error> {
+		disable_raw_mode().attach_location_err("tui disable raw mode")?;
+		execute!(self.terminal.backend_mut(), LeaveAlternateScreen).attach_location_err("tui leave screen")?;
+		self.terminal.show_cursor().attach_location_err("tui show cursor")?;
+
+		return Ok(());
+	}
+
+	/// Switch the terminal back after [`TuiTerminal::suspend`]
+	pub fn resume(&mut self) -> Result<(), crate::Error> {
+		enable_raw_mode().attach_location_err("tui enable raw mode")?;
+		execute!(self.terminal.backend_mut(), EnterAlternateScreen).attach_location_err("tui enter screen")?;
+		// force a full re-draw, because the screen content is unknown after another program used it
+		self.terminal.clear().attach_location_err("tui clear")?;
+
+		return Ok(());
+	}
+}
+
+impl Drop for TuiTerminal {
+	fn drop(&mut self) {
+		if let Err(err) = self.suspend() {
+			warn!("Failed to restore the terminal: {}", err);
+		}
+	}
+}
+
+/// Run the edit loop as a terminal UI for `entries`, starting on the last entry if `reverse` is set
+/// `run_action` is called with the key of the selected media for every action, while the terminal UI is suspended
+pub fn run_edit_tui<F>(
+	mut tui: TuiTerminal,
+	entries: Vec<TuiEntry>,
+	reverse: bool,
+	mut run_action: F,
+) -> Result<(), crate::Error>
+where
+	F: FnMut(&str, TuiAction) -> Result<ActionOutcome, crate::Error>,
+{
+	if entries.is_empty() {
+		return Ok(());
+	}
+
+	let mut state = EditTuiState::new(entries, reverse);
+
+	loop {
+		super::check_termination()?;
+
+		tui.draw(|frame| return state.draw(frame))?;
+
+		// poll instead of blocking, so that termination requests are still handled
+		if !event::poll(INPUT_POLL_INTERVAL).attach_location_err("tui event poll")? {
+			continue;
+		}
+
+		let Event::Key(key) = event::read().attach_location_err("tui event read")? else {
+			continue;
+		};
+
+		match state.handle_key(key) {
+			KeyOutcome::None => (),
+			KeyOutcome::Done => return Ok(()),
+			KeyOutcome::Abort => return Err(crate::Error::other("Termination Requested")),
+			KeyOutcome::Run(action) => {
+				let media_key = state.selected_key().to_owned();
+
+				tui.suspend()?;
+				let outcome = run_action(&media_key, action);
+				tui.resume()?;
+
+				state.apply_outcome(action, outcome?);
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Test helper to create a state with `count` entries
+	fn state(count: usize, reverse: bool) -> EditTuiState {
+		return EditTuiState::new(
+			(0..count)
+				.map(|v| {
+					return TuiEntry {
+						key:   format!("key{v}"),
+						title: format!("Title {v}"),
+						stage: MediaStage::Downloaded,
+					};
+				})
+				.collect(),
+			reverse,
+		);
+	}
+
+	/// Test helper to create a key press
+	fn press(code: KeyCode) -> KeyEvent {
+		return KeyEvent::new(code, KeyModifiers::NONE);
+	}
+
+	#[test]
+	fn test_navigation() {
+		let mut state = state(3, false);
+		assert_eq!("key0", state.selected_key());
+
+		assert_eq!(KeyOutcome::None, state.handle_key(press(KeyCode::Char('b'))));
+		assert_eq!(tr(Msg::CannotGoBack), state.status);
+
+		assert_eq!(KeyOutcome::None, state.handle_key(press(KeyCode::Down)));
+		assert_eq!(KeyOutcome::None, state.handle_key(press(KeyCode::Char('n'))));
+		assert_eq!("key2", state.selected_key());
+		assert!(state.status.is_empty());
+
+		// moving down stays on the last entry, but skipping it finishes editing
+		assert_eq!(KeyOutcome::None, state.handle_key(press(KeyCode::Down)));
+		assert_eq!("key2", state.selected_key());
+		assert_eq!(KeyOutcome::Done, state.handle_key(press(KeyCode::Char('n'))));
+
+		assert_eq!(KeyOutcome::None, state.handle_key(press(KeyCode::Up)));
+		assert_eq!("key1", state.selected_key());
+
+		// reverse starts on the last entry
+		assert_eq!("key2", self::state(3, true).selected_key());
+	}
+
+	#[test]
+	fn test_actions() {
+		let mut state = state(2, false);
+
+		assert_eq!(
+			KeyOutcome::Run(TuiAction::Edit(EditorChoice::Auto)),
+			state.handle_key(press(KeyCode::Enter))
+		);
+		assert_eq!(
+			KeyOutcome::Run(TuiAction::Edit(EditorChoice::Audio)),
+			state.handle_key(press(KeyCode::Char('a')))
+		);
+		assert_eq!(
+			KeyOutcome::Run(TuiAction::Edit(EditorChoice::Video)),
+			state.handle_key(press(KeyCode::Char('v')))
+		);
+		assert_eq!(
+			KeyOutcome::Run(TuiAction::Play),
+			state.handle_key(press(KeyCode::Char('p')))
+		);
+		assert_eq!(KeyOutcome::Done, state.handle_key(press(KeyCode::Char('q'))));
+		assert_eq!(
+			KeyOutcome::Abort,
+			state.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+		);
+
+		// releases are ignored
+		let mut release = press(KeyCode::Char('q'));
+		release.kind = KeyEventKind::Release;
+		assert_eq!(KeyOutcome::None, state.handle_key(release));
+	}
+
+	#[test]
+	fn test_apply_outcome() {
+		let mut state = state(2, false);
+
+		// playing does not change the media or the selection
+		state.apply_outcome(TuiAction::Play, ActionOutcome::default());
+		assert_eq!("key0", state.selected_key());
+
+		// a edit that could not be done only shows the message
+		state.apply_outcome(
+			TuiAction::Edit(EditorChoice::Auto),
+			ActionOutcome::message("some message"),
+		);
+		assert_eq!("key0", state.selected_key());
+		assert_eq!("some message", state.status);
+
+		state.apply_outcome(
+			TuiAction::Edit(EditorChoice::Audio),
+			ActionOutcome {
+				stage:   Some(MediaStage::Edited),
+				message: None,
+			},
+		);
+		assert_eq!(MediaStage::Edited, state.entries[0].stage);
+		assert_eq!("key1", state.selected_key());
+		assert!(state.status.is_empty());
+	}
+}
@@ -0,0 +1,92 @@
+//! Module for the "archive dupes" command, which reports media in the Archive with the same file content
+
+use indicatif::ProgressBar;
+use libytdlr::{
+	data::sql_models::Media,
+	main::archive::hashes::find_content_duplicates,
+};
+
+use crate::{
+	clap_conf::{
+		ArchiveDupes,
+		CliDerive,
+	},
+	utils,
+};
+
+/// Format a group of media with the same content hash
+/// The first line is the hash, followed by a tab-indented line for each media: "<TAB>provider:id<TAB>title"
+fn fmt_group(hash: &str, group: &[Media]) -> String {
+	let mut output = hash.to_owned();
+
+	for media in group {
+		output.push_str(&format!("\n\t{}:{}\t{}", media.provider, media.media_id, media.title));
+	}
+
+	return output;
+}
+
+/// Handler function for the "archive dupes" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_dupes(main_args: &CliDerive, _sub_args: &ArchiveDupes) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Dupes!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	let groups = find_content_duplicates(&mut connection)?;
+
+	if groups.is_empty() {
+		println!("No media with the same content found");
+		return Ok(());
+	}
+
+	for (hash, group) in &groups {
+		println!("{}", fmt_group(hash, group));
+	}
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Test helper to create a [`Media`] with only the relevant fields set
+	fn media(provider: &str, media_id: &str, title: &str) -> Media {
+		return Media {
+			_id:            1,
+			media_id:       media_id.to_owned(),
+			provider:       provider.to_owned(),
+			title:          title.to_owned(),
+			inserted_at:    libytdlr::chrono::NaiveDateTime::default(),
+			extractor:      None,
+			uploader:       None,
+			upload_date:    None,
+			duration_sec:   None,
+			webpage_url:    None,
+			filesize_bytes: None,
+			content_hash:   Some("aaaa".to_owned()),
+			removed_at:     None,
+		};
+	}
+
+	#[test]
+	fn test_fmt_group() {
+		assert_eq!(
+			"aaaa\n\tyoutube:id1\tSome Title\n\tyoutube:id2\tSome Title (Reupload)",
+			fmt_group(
+				"aaaa",
+				&[
+					media("youtube", "id1", "Some Title"),
+					media("youtube", "id2", "Some Title (Reupload)")
+				]
+			)
+		);
+	}
+}
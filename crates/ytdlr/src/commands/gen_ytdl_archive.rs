@@ -0,0 +1,122 @@
+//! Module for the "archive gen-ytdl-archive" command
+
+use std::{
+	fs::File,
+	io::{
+		BufWriter,
+		Write,
+	},
+};
+
+use indicatif::ProgressBar;
+use libytdlr::{
+	diesel::SqliteConnection,
+	error::IOErrorToError,
+};
+
+use crate::{
+	clap_conf::{
+		ArchiveGenYtdlArchive,
+		ArchiveMode,
+		CliDerive,
+	},
+	state::gen_archive_lines,
+	utils,
+};
+
+/// Write the youtube-dl archive lines for `mode` to `writer`
+/// Returns the count of lines written
+fn write_ytdl_archive<W: Write>(
+	connection: &mut SqliteConnection,
+	mode: ArchiveMode,
+	writer: &mut W,
+) -> Result<usize, crate::Error> {
+	let lines = gen_archive_lines(connection, mode)
+		.ok_or_else(|| return crate::Error::other("Could not read the Archive to generate the youtube-dl archive"))?;
+
+	let mut count = 0usize;
+	for line in lines {
+		writer
+			.write_all(line.as_bytes())
+			.attach_location_err("write ytdl archive line")?;
+		count += 1;
+	}
+
+	writer.flush().attach_location_err("flush ytdl archive")?;
+
+	return Ok(count);
+}
+
+/// Handler function for the "archive gen-ytdl-archive" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_gen_ytdl_archive(main_args: &CliDerive, sub_args: &ArchiveGenYtdlArchive) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Gen-Ytdl-Archive!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	let Some(output) = sub_args.output.as_deref() else {
+		write_ytdl_archive(&mut connection, sub_args.mode, &mut std::io::stdout().lock())?;
+
+		return Ok(());
+	};
+
+	let mut writer = BufWriter::new(File::create(output).attach_path_err(output)?);
+	let count = write_ytdl_archive(&mut connection, sub_args.mode, &mut writer)?;
+
+	println!("Wrote {} entries to \"{}\"", count, output.to_string_lossy());
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use libytdlr::{
+		data::sql_models::InsMedia,
+		main::{
+			archive::{
+				import::insert_insmedia,
+				remove::add_to_blacklist,
+			},
+			sql_utils::sqlite_connect,
+		},
+	};
+
+	#[test]
+	fn test_write_ytdl_archive() {
+		let tmp = tempfile::Builder::new()
+			.prefix("ytdlr-test-genytdlarchive-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let mut connection =
+			sqlite_connect(tmp.path().join("archive.db")).expect("Expected SQLite to successfully start");
+
+		insert_insmedia(&InsMedia::new("id1", "youtube", "Title 1"), &mut connection).unwrap();
+		insert_insmedia(&InsMedia::new("id2", "soundcloud", "Title 2"), &mut connection).unwrap();
+		add_to_blacklist(&mut connection, &[("youtube", "id3")]).unwrap();
+
+		let mut out = Vec::new();
+		assert_eq!(
+			3,
+			write_ytdl_archive(&mut connection, ArchiveMode::All, &mut out).unwrap()
+		);
+		assert_eq!(
+			"youtube id3\nyoutube id1\nsoundcloud id2\n",
+			String::from_utf8(out).unwrap()
+		);
+
+		// the blacklist is always added
+		let mut out = Vec::new();
+		assert_eq!(
+			1,
+			write_ytdl_archive(&mut connection, ArchiveMode::None, &mut out).unwrap()
+		);
+		assert_eq!("youtube id3\n", String::from_utf8(out).unwrap());
+	}
+}
@@ -0,0 +1,114 @@
+//! Module for the "archive has" command, which reports whether URLs are already in the Archive
+
+use std::fmt::Display;
+
+use indicatif::ProgressBar;
+use libytdlr::{
+	main::archive::remove::{
+		find_entries,
+		is_blacklisted,
+	},
+	utils::url_ident::{
+		identify_url,
+		UrlIdent,
+	},
+};
+
+use crate::{
+	clap_conf::{
+		ArchiveHas,
+		CliDerive,
+	},
+	utils,
+};
+
+/// Whether a URL is in the Archive
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HasStatus {
+	/// The media is in the Archive
+	Archived,
+	/// The media is not in the Archive, but blacklisted, so it would also be skipped by "download"
+	Blacklisted,
+	/// The media is not in the Archive
+	Missing,
+	/// The provider and id could not be found from the URL
+	Unknown,
+}
+
+impl Display for HasStatus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return write!(
+			f,
+			"{}",
+			match self {
+				HasStatus::Archived => "archived",
+				HasStatus::Blacklisted => "blacklisted",
+				HasStatus::Missing => "missing",
+				HasStatus::Unknown => "unknown",
+			}
+		);
+	}
+}
+
+/// Format the result for `url` as a line, which is tab-separated for scripting: "url<TAB>provider:id<TAB>status"
+/// The provider and id is "-" if it could not be found
+fn fmt_line(url: &str, ident: Option<&UrlIdent>, status: HasStatus) -> String {
+	return format!(
+		"{}\t{}\t{}",
+		url,
+		ident.map_or_else(|| return "-".to_owned(), ToString::to_string),
+		status
+	);
+}
+
+/// Handler function for the "archive has" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_has(main_args: &CliDerive, sub_args: &ArchiveHas) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Has!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	for url in &sub_args.urls {
+		let ident = identify_url(url);
+
+		let status = match &ident {
+			None => HasStatus::Unknown,
+			Some(ident) if !find_entries(&mut connection, ident.provider, &ident.media_id)?.is_empty() => {
+				HasStatus::Archived
+			},
+			Some(ident) if is_blacklisted(&mut connection, Some(ident.provider), &ident.media_id)? => {
+				HasStatus::Blacklisted
+			},
+			Some(_) => HasStatus::Missing,
+		};
+
+		println!("{}", fmt_line(url, ident.as_ref(), status));
+	}
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_fmt_line() {
+		let url = "https://youtu.be/dQw4w9WgXcQ";
+
+		assert_eq!(
+			"https://youtu.be/dQw4w9WgXcQ\tyoutube:dQw4w9WgXcQ\tarchived",
+			fmt_line(url, identify_url(url).as_ref(), HasStatus::Archived)
+		);
+		assert_eq!(
+			"https://example.com/\t-\tunknown",
+			fmt_line("https://example.com/", None, HasStatus::Unknown)
+		);
+	}
+}
@@ -23,7 +23,7 @@ pub fn command_import(main_args: &CliDerive, sub_args: &ArchiveImport) -> Result
 
 	let input_path = &sub_args.file_path;
 
-	let archive_path = match main_args.archive_path.as_ref() {
+	let archive_path = match main_args.resolve_archive_path() {
 		None => return Err(crate::Error::other("Archive is required for Import!")),
 		Some(v) => v,
 	};
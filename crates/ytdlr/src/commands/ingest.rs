@@ -0,0 +1,92 @@
+//! Module for the "archive ingest" command
+
+use indicatif::{
+	ProgressBar,
+	ProgressStyle,
+};
+use libytdlr::{
+	data::cache::media_info::MediaInfo,
+	main::{
+		archive::{
+			import::ImportProgress,
+			ingest::ingest_media,
+		},
+		diff::list_playlist,
+	},
+};
+use once_cell::sync::Lazy;
+
+use crate::{
+	clap_conf::{
+		ArchiveIngest,
+		CliDerive,
+	},
+	utils,
+};
+
+/// Handler function for the "archive ingest" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_ingest(main_args: &CliDerive, sub_args: &ArchiveIngest) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Ingest!"));
+	};
+
+	let (ytdl_bin, _) = main_args.require_ytdl()?;
+
+	static INGEST_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+		return ProgressStyle::default_bar()
+			.template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+			.expect("Expected ProgressStyle template to be valid")
+			.progress_chars("#>-");
+	});
+
+	let bar: ProgressBar = ProgressBar::hidden().with_style(INGEST_STYLE.clone());
+	crate::utils::set_progressbar(&bar, main_args);
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	let mut medias: Vec<MediaInfo> = Vec::new();
+
+	for url in &sub_args.urls {
+		println!("Listing \"{url}\"");
+
+		let entries = list_playlist(&ytdl_bin, url)?;
+		let entries_len = entries.len();
+		let medias_len = medias.len();
+
+		// unavailable entries (like deleted or private) cannot be downloaded anyway
+		medias.extend(
+			entries
+				.into_iter()
+				.filter(|v| return v.available)
+				.map(|v| return v.media),
+		);
+
+		println!("Found {} entries, {} available", entries_len, medias.len() - medias_len);
+	}
+
+	let pgcb_ingest = |imp| {
+		if main_args.is_interactive() {
+			match imp {
+				ImportProgress::Starting => bar.set_position(0),
+				ImportProgress::SizeHint(v) => bar.set_length(v.try_into().expect("Failed to convert usize to u64")),
+				ImportProgress::Increase(c, _i) => bar.inc(c.try_into().expect("Failed to convert usize to u64")),
+				ImportProgress::Finished(v) => bar.finish_with_message(format!("Finished Ingesting {v} elements")),
+			}
+		} else {
+			match imp {
+				ImportProgress::Starting => println!("Starting Ingest"),
+				ImportProgress::SizeHint(v) => println!("Ingest SizeHint: {v}"),
+				ImportProgress::Increase(c, i) => println!("Ingest Increase: {c}, Current Index: {i}"),
+				ImportProgress::Finished(v) => println!("Ingest Finished, Successfull Ingests: {v}"),
+			}
+		}
+	};
+
+	let affected = ingest_media(&medias, &mut connection, pgcb_ingest)?;
+
+	println!("Added or updated {affected} entries in the Archive");
+
+	return Ok(());
+}
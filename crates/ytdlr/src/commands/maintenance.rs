@@ -0,0 +1,72 @@
+use crate::{
+	clap_conf::{
+		ArchiveMaintenance,
+		CliDerive,
+	},
+	utils,
+};
+use indicatif::{
+	ProgressBar,
+	ProgressStyle,
+};
+use libytdlr::main::archive::{
+	import::ImportProgress,
+	maintenance::{
+		canonicalize_providers,
+		merge_duplicates,
+		optimize,
+	},
+};
+use once_cell::sync::Lazy;
+
+/// Handler function for the "archive maintenance" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_maintenance(main_args: &CliDerive, sub_args: &ArchiveMaintenance) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Maintenance!"));
+	};
+
+	static MAINTENANCE_STYLE: Lazy<ProgressStyle> = Lazy::new(|| {
+		return ProgressStyle::default_bar()
+			.template("[{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+			.expect("Expected ProgressStyle template to be valid")
+			.progress_chars("#>-");
+	});
+
+	let bar: ProgressBar = ProgressBar::hidden().with_style(MAINTENANCE_STYLE.clone());
+	crate::utils::set_progressbar(&bar, main_args);
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	let pgcb_merge = |imp| {
+		if main_args.is_interactive() {
+			match imp {
+				ImportProgress::Starting => bar.set_position(0),
+				ImportProgress::SizeHint(v) => bar.set_length(v.try_into().expect("Failed to convert usize to u64")),
+				ImportProgress::Increase(c, _i) => bar.inc(c.try_into().expect("Failed to convert usize to u64")),
+				ImportProgress::Finished(v) => bar.finish_with_message(format!("Removed {v} duplicate entries")),
+			}
+		} else {
+			match imp {
+				ImportProgress::Starting => println!("Starting Duplicate Merge"),
+				ImportProgress::SizeHint(v) => println!("Duplicate Groups: {v}"),
+				ImportProgress::Increase(c, i) => println!("Merge Increase: {c}, Current Index: {i}"),
+				ImportProgress::Finished(v) => println!("Merge Finished, Removed Entries: {v}"),
+			}
+		}
+	};
+
+	let removed = merge_duplicates(&mut connection, pgcb_merge)?;
+	println!("Removed {removed} duplicate entries");
+
+	let canonicalized = canonicalize_providers(&mut connection)?;
+	println!("Canonicalized the provider of {canonicalized} entries");
+
+	if !sub_args.no_optimize {
+		println!("Optimizing Archive");
+		optimize(&mut connection)?;
+	}
+
+	return Ok(());
+}
@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use indicatif::ProgressBar;
+use libytdlr::main::sql_utils::migration_status;
+
+use crate::{
+	clap_conf::{
+		ArchiveMigrations,
+		CliDerive,
+	},
+	utils,
+};
+
+/// Handler function for the "archive migrations" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_migrations(main_args: &CliDerive, sub_args: &ArchiveMigrations) -> Result<(), crate::Error> {
+	let Some(archive_path) = sub_args
+		.file_path
+		.as_deref()
+		.or_else(|| return main_args.resolve_archive_path())
+	else {
+		return Err(crate::Error::other("Archive is required for Migrations!"));
+	};
+
+	// the path may change when migrating from JSON to SQLite
+	let archive_path = if sub_args.migrate_only {
+		let bar: ProgressBar = ProgressBar::hidden();
+		utils::set_progressbar(&bar, main_args);
+
+		let (new_archive, _connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+		println!("Applied all pending migrations");
+
+		new_archive.into_owned()
+	} else {
+		archive_path.to_path_buf()
+	};
+
+	print_migration_status(&archive_path)?;
+
+	return Ok(());
+}
+
+/// Print the migration status of the given archive
+fn print_migration_status(archive_path: &Path) -> Result<(), crate::Error> {
+	let status = migration_status(archive_path)?;
+
+	println!("Archive: \"{}\"", archive_path.to_string_lossy());
+	println!("Schema Version: {}", status.schema_version().unwrap_or("none"));
+
+	println!("Applied Migrations ({}):", status.applied.len());
+	for version in &status.applied {
+		println!("  {version}");
+	}
+
+	println!("Pending Migrations ({}):", status.pending.len());
+	for name in &status.pending {
+		println!("  {name}");
+	}
+
+	if !status.pending.is_empty() {
+		println!("Pending Migrations will be applied by any command using the archive, or with \"--migrate-only\"");
+	}
+
+	return Ok(());
+}
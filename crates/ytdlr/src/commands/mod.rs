@@ -1,9 +1,24 @@
 //! Module for all (longer) commands
 
+pub mod browse;
+pub mod check_archive;
+pub mod check_deps;
 pub mod completions;
+pub mod diff;
 pub mod download;
+pub mod dupes;
+pub mod gen_ytdl_archive;
+pub mod has;
 pub mod import;
+pub mod ingest;
+pub mod maintenance;
+pub mod migrations;
+pub mod remove;
+pub mod restore;
 pub mod rethumbnail;
 pub mod search;
-#[cfg(debug_assertions)]
-pub mod unicode_test;
+pub mod selftest;
+pub mod stats;
+pub mod tag;
+pub mod term_check;
+pub mod watch;
@@ -0,0 +1,126 @@
+//! Module for the "archive remove" command
+
+use indicatif::ProgressBar;
+use libytdlr::{
+	data::sql_models::Media,
+	main::{
+		archive::remove::{
+			add_to_blacklist,
+			find_entries,
+			remove_entries,
+			tombstone_entries,
+		},
+		diff::list_playlist,
+	},
+};
+
+use crate::{
+	clap_conf::{
+		ArchiveRemove,
+		CliDerive,
+		RemoveTarget,
+	},
+	commands::search::build_search_query,
+	utils,
+};
+
+/// Add `media` to `found`, if it is not already in it
+fn push_unique(found: &mut Vec<Media>, media: Media) {
+	if !found.iter().any(|v| return v._id == media._id) {
+		found.push(media);
+	}
+}
+
+/// Handler function for the "archive remove" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_remove(main_args: &CliDerive, sub_args: &ArchiveRemove) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Remove!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	// the entries to remove from the archive
+	let mut found: Vec<Media> = Vec::new();
+	// the "(provider, id)" to blacklist, which also includes entries that are not in the archive
+	let mut keys: Vec<(String, String)> = Vec::new();
+
+	for target in &sub_args.targets {
+		match target {
+			RemoveTarget::Media { provider, id } => keys.push((provider.clone(), id.clone())),
+			RemoveTarget::Url(url) => {
+				let (ytdl_bin, _) = main_args.require_ytdl()?;
+
+				println!("Listing \"{url}\"");
+
+				keys.extend(
+					list_playlist(&ytdl_bin, url)?
+						.into_iter()
+						.map(|v| return (v.media.provider.as_str().to_owned(), v.media.id)),
+				);
+			},
+		}
+	}
+
+	for (provider, id) in &keys {
+		for media in find_entries(&mut connection, provider, id)? {
+			push_unique(&mut found, media);
+		}
+	}
+
+	// an empty query would match all entries
+	if !sub_args.queries.is_empty() {
+		// tombstones are included, so that they can be purged
+		for media in build_search_query(&sub_args.queries, &[], false)
+			.include_tombstones(sub_args.purge)
+			.load(&mut connection)?
+		{
+			keys.push((media.provider.clone(), media.media_id.clone()));
+			push_unique(&mut found, media);
+		}
+	}
+
+	// already removed entries are only changed when purging
+	if !sub_args.purge {
+		found.retain(|v| return v.removed_at.is_none());
+	}
+
+	for media in &found {
+		println!("- [{}:{}] {}", media.provider, media.media_id, media.title);
+	}
+
+	if sub_args.dry_run {
+		if sub_args.blacklist {
+			println!(
+				"Would remove {} Entries and blacklist {} Entries",
+				found.len(),
+				keys.len()
+			);
+		} else {
+			println!("Would remove {} Entries", found.len());
+		}
+
+		return Ok(());
+	}
+
+	let ids: Vec<i64> = found.iter().map(|v| return v._id).collect();
+	let removed = if sub_args.purge {
+		remove_entries(&mut connection, &ids)?
+	} else {
+		tombstone_entries(&mut connection, &ids)?
+	};
+
+	if sub_args.blacklist {
+		let blacklisted = add_to_blacklist(&mut connection, &keys)?;
+
+		println!("Removed {removed} Entries and blacklisted {blacklisted} new Entries");
+	} else {
+		println!("Removed {removed} Entries");
+	}
+
+	return Ok(());
+}
@@ -0,0 +1,50 @@
+//! Module for the "restore" command, which undoes the last move of "download --use-trash"
+
+use crate::{
+	clap_conf::{
+		CliDerive,
+		CommandRestore,
+	},
+	trash::{
+		TrashOperation,
+		TRASH_DIR_NAME,
+	},
+};
+
+/// Handler function for the "restore" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_restore(main_args: &CliDerive, sub_args: &CommandRestore) -> Result<(), crate::Error> {
+	let trash_path = main_args.download_tmp_path().join(TRASH_DIR_NAME);
+
+	let Some(operation) = TrashOperation::latest(&trash_path)? else {
+		println!("Nothing to restore in \"{}\"", trash_path.display());
+
+		return Ok(());
+	};
+
+	println!("Restoring \"{}\"", operation.dir().display());
+
+	for entry in operation.entries() {
+		match &entry.overwritten {
+			Some(_) => println!(
+				"- \"{}\" (putting back the overwritten file) -> \"{}\"",
+				entry.to.display(),
+				entry.from.display()
+			),
+			None => println!("- \"{}\" -> \"{}\"", entry.to.display(), entry.from.display()),
+		}
+	}
+
+	if sub_args.dry_run {
+		println!("Would restore {} Files", operation.entries().len());
+
+		return Ok(());
+	}
+
+	let restored = operation.restore()?;
+
+	println!("Restored {restored} Files, the media will be found again by the next \"download\" via recovery");
+
+	return Ok(());
+}
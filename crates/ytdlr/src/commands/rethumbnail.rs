@@ -1,28 +1,75 @@
-use std::path::PathBuf;
+use std::path::{
+	Path,
+	PathBuf,
+};
 
-use crate::clap_conf::{
-	CliDerive,
-	CommandReThumbnail,
+use crate::{
+	clap_conf::{
+		CliDerive,
+		CommandReThumbnail,
+		CommandReThumbnailBatch,
+	},
+	utils,
 };
+use indicatif::ProgressBar;
 use libytdlr::{
-	main::rethumbnail::re_thumbnail_with_tmp,
+	main::{
+		archive::search::{
+			SearchFilter,
+			SearchQuery,
+		},
+		check_archive::find_file_entries,
+		rethumbnail::{
+			re_thumbnail_from_url,
+			re_thumbnail_many,
+			re_thumbnail_with_tmp,
+			ReThumbnailItem,
+			ReThumbnailProgress,
+		},
+	},
 	spawn::ffmpeg::require_ffmpeg_installed,
+	utils::url_ident::media_url,
 };
 
 /// Handler function for the "rethumbnail" subcommand
 /// This function is mainly to keep the code structured and sorted
 #[inline]
-pub fn command_rethumbnail(_main_args: &CliDerive, sub_args: &CommandReThumbnail) -> Result<(), crate::Error> {
+pub fn command_rethumbnail(main_args: &CliDerive, sub_args: &CommandReThumbnail) -> Result<(), crate::Error> {
 	require_ffmpeg_installed()?;
 
 	// helper aliases to make it easier to access
-	let input_image_path: &PathBuf = &sub_args.input_image_path;
 	let input_media_path: &PathBuf = &sub_args.input_media_path;
 	let output_media_path: &PathBuf = sub_args
 		.output_media_path
 		.as_ref()
 		.expect("Expected trait \"Check\" to be run on \"CommandReThumbnail\" before this point");
 
+	if sub_args.from_url {
+		let (ytdl_bin, _) = main_args.require_ytdl()?;
+
+		let url = find_media_url(main_args, input_media_path)?;
+
+		println!(
+			"Re-Applying Thumbnail of \"{}\" to media file \"{}\"",
+			url,
+			input_media_path.to_string_lossy()
+		);
+
+		re_thumbnail_from_url(&ytdl_bin, input_media_path, &url, output_media_path)?;
+
+		println!(
+			"Re-Applied Thumbnail to media, as \"{}\"",
+			output_media_path.to_string_lossy()
+		);
+
+		return Ok(());
+	}
+
+	let input_image_path: &PathBuf = sub_args
+		.input_image_path
+		.as_ref()
+		.expect("Expected clap to require \"image\" if \"from-url\" is not set");
+
 	println!(
 		"Re-Applying Thumbnail image \"{}\" to media file \"{}\"",
 		input_image_path.to_string_lossy(),
@@ -38,3 +85,88 @@ pub fn command_rethumbnail(_main_args: &CliDerive, sub_args: &CommandReThumbnail
 
 	return Ok(());
 }
+
+/// Find the original URL of `media_path` by matching its filename against the Archive
+fn find_media_url(main_args: &CliDerive, media_path: &Path) -> Result<String, crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for \"--from-url\"!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	let ids = find_file_entries(&mut connection, &[media_path.to_owned()])?;
+
+	if ids.is_empty() {
+		return Err(crate::Error::other(format!(
+			"No Archive entry found for \"{}\"",
+			media_path.to_string_lossy()
+		)));
+	}
+
+	let found = SearchQuery::new()
+		.with_filter(SearchFilter::RowIds(ids))
+		.load(&mut connection)?;
+
+	let [media] = found.as_slice() else {
+		return Err(crate::Error::other(format!(
+			"Multiple Archive entries found for \"{}\", cannot decide which to use",
+			media_path.to_string_lossy()
+		)));
+	};
+
+	return media
+		.webpage_url
+		.clone()
+		.or_else(|| return media_url(&media.provider, &media.media_id))
+		.ok_or_else(|| {
+			return crate::Error::other(format!(
+				"Archive entry \"{}:{}\" does not have a URL",
+				media.provider, media.media_id
+			));
+		});
+}
+
+/// Handler function for the "rethumbnail-batch" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_rethumbnail_batch(
+	_main_args: &CliDerive,
+	sub_args: &CommandReThumbnailBatch,
+) -> Result<(), crate::Error> {
+	require_ffmpeg_installed()?;
+
+	let items: Vec<ReThumbnailItem> = sub_args
+		.input_media_paths
+		.iter()
+		.map(|v| return ReThumbnailItem::new(v))
+		.collect();
+
+	let errors = re_thumbnail_many(&items, |imp| match imp {
+		ReThumbnailProgress::Starting(v) => println!("Re-Applying Thumbnails to {v} media files"),
+		ReThumbnailProgress::SingleStarting(i, path) => {
+			println!("[{}/{}] \"{}\"", i + 1, items.len(), path.to_string_lossy());
+		},
+		ReThumbnailProgress::SingleSkipped(_) => println!("No Image found for media, skipping"),
+		ReThumbnailProgress::SingleFinished(_) | ReThumbnailProgress::SingleFailed(_) => (),
+		ReThumbnailProgress::Finished(successfull, failed) => {
+			println!("Re-Applied Thumbnails, Successfull: {successfull}, Failed: {failed}");
+		},
+	});
+
+	if errors.is_empty() {
+		return Ok(());
+	}
+
+	for (index, err) in &errors {
+		println!("Failed \"{}\": {}", items[*index].media.to_string_lossy(), err);
+	}
+
+	return Err(crate::Error::other(format!(
+		"{} of {} media files failed to be re-thumbnailed",
+		errors.len(),
+		items.len()
+	)));
+}
@@ -1,35 +1,157 @@
-use indicatif::ProgressBar;
+use std::path::{
+	Path,
+	PathBuf,
+};
+
+use indicatif::{
+	HumanBytes,
+	ProgressBar,
+};
 
 use crate::{
 	clap_conf::{
 		ArchiveSearch,
 		CliDerive,
+		SearchKey,
 		SearchResultFormat,
 	},
 	utils,
 };
-use diesel::prelude::*;
+use diesel::SqliteConnection;
 use libytdlr::{
 	chrono::Utc,
-	data::{
-		sql_models::Media,
-		sql_schema::media_archive,
-	},
+	data::sql_models::Media,
 	diesel,
+	main::{
+		archive::{
+			labels::get_labels,
+			search::{
+				fuzzy_matches,
+				SearchColumn,
+				SearchFilter,
+				SearchQuery,
+			},
+		},
+		check_archive::{
+			find_file_entries,
+			find_library_files,
+		},
+	},
 };
 
-/// Helper function to convert a given input to a "LIKE" query (appending "%")
-fn to_like_query(input: &str) -> String {
-	let mut res: String = input.to_owned();
-	res.push('%');
-	return res;
+/// Build the query for all entries matching any of the `queries` and all of the `labels`
+/// If `fuzzy` is set, the queries are fuzzy matched (see [`SearchQuery::fuzzy_filter`])
+pub fn build_search_query(queries: &[(SearchColumn, String)], labels: &[String], fuzzy: bool) -> SearchQuery {
+	let query = queries.iter().fold(SearchQuery::new(), |query, (column, value)| {
+		if fuzzy {
+			return query.fuzzy_filter(*column, value);
+		}
+
+		return query.filter(*column, value);
+	});
+
+	return labels.iter().fold(query, |query, label| return query.label(label));
+}
+
+/// Get whether the file name of `path` matches `value`, like the column queries do (case-insensitive)
+fn filename_matches(path: &Path, value: &str, fuzzy: bool) -> bool {
+	let Some(name) = path.file_name().map(|v| return v.to_string_lossy()) else {
+		return false;
+	};
+
+	if fuzzy {
+		return fuzzy_matches(&name, value);
+	}
+
+	return name.to_lowercase().starts_with(&value.to_lowercase());
+}
+
+/// Format the known duration and size of `media`, like ` (3:05, 4.00 MiB)`
+/// Empty if neither is known
+fn format_media_sizes(media: &Media) -> String {
+	let parts: Vec<String> = [
+		media
+			.duration_sec
+			.and_then(|v| return u64::try_from(v).ok())
+			.map(utils::format_duration_secs),
+		media
+			.filesize_bytes
+			.and_then(|v| return u64::try_from(v).ok())
+			.map(|v| return HumanBytes(v).to_string()),
+	]
+	.into_iter()
+	.flatten()
+	.collect();
+
+	if parts.is_empty() {
+		return String::new();
+	}
+
+	return format!(" ({})", parts.join(", "));
+}
+
+/// Format a value which may not be known for CSV, unknown values are empty
+fn format_csv_optional<T: ToString>(value: Option<T>) -> String {
+	return value.map(|v| return v.to_string()).unwrap_or_default();
+}
+
+/// Format a media as a JSON object for the "Json" format, unknown values are `null`
+fn format_media_json(media: &Media) -> serde_json::Value {
+	// required, otherwise formatting as "%+" / "RFC3339" is not possible for NaiveDateTime
+	let inserted_at = media
+		.inserted_at
+		.and_local_timezone(Utc)
+		.single()
+		.expect("Expected to properly convert with timezone")
+		.format("%+");
+
+	return serde_json::json!({
+		"provider": media.provider,
+		"media_id": media.media_id,
+		"title": media.title,
+		"inserted_at": inserted_at.to_string(),
+		"duration_sec": media.duration_sec,
+		"filesize_bytes": media.filesize_bytes,
+		"removed_at": media.removed_at.map(|v| return v.and_utc().format("%+").to_string()),
+	});
+}
+
+/// Format a media in the "Normal" format, like `[provider:media_id] [inserted_at] title (duration, size) [labels] [removed]`
+/// The duration and size are only added if known, the labels are only added if the media has any and "[removed]" only for tombstones
+pub fn format_media_normal(connection: &mut SqliteConnection, media: &Media) -> Result<String, crate::Error> {
+	// required, otherwise formatting as "%+" / "RFC3339" is not possible for NaiveDateTime
+	let inserted_at = media
+		.inserted_at
+		.and_local_timezone(Utc)
+		.single()
+		.expect("Expected to properly convert with timezone")
+		.format("%+");
+	let labels = get_labels(connection, media._id)?;
+	let labels = if labels.is_empty() {
+		String::new()
+	} else {
+		format!(" [{}]", labels.join(", "))
+	};
+
+	let removed = if media.removed_at.is_some() { " [removed]" } else { "" };
+
+	return Ok(format!(
+		"[{}:{}] [{}] {}{}{}{}",
+		media.provider,
+		media.media_id,
+		inserted_at,
+		media.title,
+		format_media_sizes(media),
+		labels,
+		removed
+	));
 }
 
 /// Handler function for the "archive search" subcommand
 /// This function is mainly to keep the code structured and sorted
 #[inline]
 pub fn command_search(main_args: &CliDerive, sub_args: &ArchiveSearch) -> Result<(), crate::Error> {
-	let archive_path = match main_args.archive_path.as_ref() {
+	let archive_path = match main_args.resolve_archive_path() {
 		None => return Err(crate::Error::other("Archive is required for Search!")),
 		Some(v) => v,
 	};
@@ -40,42 +162,39 @@ pub fn command_search(main_args: &CliDerive, sub_args: &ArchiveSearch) -> Result
 
 	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
 
-	let mut query = media_archive::dsl::media_archive
-		.into_boxed()
-		.order(media_archive::_id.asc())
-		.limit(sub_args.limit);
+	let column_queries: Vec<(SearchColumn, String)> = sub_args
+		.queries
+		.iter()
+		.filter_map(|(key, value)| {
+			return match key {
+				SearchKey::Column(column) => Some((*column, value.clone())),
+				SearchKey::Filename => None,
+			};
+		})
+		.collect();
 
-	for q in &sub_args.queries {
-		match q.0 {
-			crate::clap_conf::ArchiveSearchColumn::Provider => {
-				query = query.or_filter(media_archive::columns::provider.like(to_like_query(&q.1)));
-			},
-			crate::clap_conf::ArchiveSearchColumn::MediaId => {
-				query = query.or_filter(media_archive::columns::media_id.like(to_like_query(&q.1)));
-			},
-			crate::clap_conf::ArchiveSearchColumn::Title => {
-				query = query.or_filter(media_archive::columns::title.like(to_like_query(&q.1)));
-			},
-			crate::clap_conf::ArchiveSearchColumn::InsertedAt => {
-				let search_query = &q.1;
-				if let Some(search_query) = search_query.strip_prefix(">=") {
-					query = query.or_filter(media_archive::columns::inserted_at.ge(search_query));
-				} else if let Some(search_query) = search_query.strip_prefix("<=") {
-					query = query.or_filter(media_archive::columns::inserted_at.le(search_query));
-				} else if let Some(search_query) = search_query.strip_prefix('<') {
-					query = query.or_filter(media_archive::columns::inserted_at.lt(search_query));
-				} else if let Some(search_query) = search_query.strip_prefix('>') {
-					query = query.or_filter(media_archive::columns::inserted_at.gt(search_query));
-				} else if let Some(search_query) = search_query.strip_prefix('=') {
-					query = query.or_filter(media_archive::columns::inserted_at.eq(search_query));
-				} else {
-					query = query.or_filter(media_archive::columns::inserted_at.eq(&search_query[..]));
-				}
-			},
-		}
+	let mut query = build_search_query(&column_queries, &sub_args.labels, sub_args.fuzzy)
+		.include_tombstones(sub_args.include_tombstones);
+
+	// the "Filename" pseudo-column is resolved to the archive entries of the matching files
+	if let Some((_, value)) = sub_args.queries.iter().find(|v| return v.0 == SearchKey::Filename) {
+		let files: Vec<PathBuf> = find_library_files(&sub_args.library_dirs)?
+			.into_iter()
+			.filter(|v| return filename_matches(v, value, sub_args.fuzzy))
+			.collect();
+
+		query = query.with_filter(SearchFilter::RowIds(find_file_entries(&mut connection, &files)?));
 	}
 
-	let lines_iter = query.load::<Media>(&mut connection)?;
+	let lines_iter = query.limit(sub_args.limit).load(&mut connection)?;
+
+	// always output a array, so that the output can be parsed even without results
+	if sub_args.result_format == SearchResultFormat::Json {
+		let values: Vec<serde_json::Value> = lines_iter.iter().map(format_media_json).collect();
+		println!("{}", serde_json::to_string(&values)?);
+
+		return Ok(());
+	}
 
 	if lines_iter.is_empty() {
 		println!("No Results found");
@@ -84,12 +203,12 @@ pub fn command_search(main_args: &CliDerive, sub_args: &ArchiveSearch) -> Result
 
 	// print header, if header is required
 	match sub_args.result_format {
-		SearchResultFormat::Normal => (),
+		SearchResultFormat::Normal | SearchResultFormat::Json => (),
 		SearchResultFormat::CSVC => {
-			println!("provider,media_id,inserted_at,title");
+			println!("provider,media_id,inserted_at,title,duration_sec,filesize_bytes");
 		},
 		SearchResultFormat::CSVT => {
-			println!("provider\tmedia_id\tinserted_at\ttitle");
+			println!("provider\tmedia_id\tinserted_at\ttitle\tduration_sec\tfilesize_bytes");
 		},
 	}
 
@@ -103,25 +222,123 @@ pub fn command_search(main_args: &CliDerive, sub_args: &ArchiveSearch) -> Result
 			.format("%+");
 		match sub_args.result_format {
 			SearchResultFormat::Normal => {
-				println!(
-					"[{}:{}] [{}] {}",
-					media.provider, media.media_id, inserted_at, media.title
-				);
+				println!("{}", format_media_normal(&mut connection, &media)?);
 			},
 			SearchResultFormat::CSVC => {
 				println!(
-					"{},{},\"{}\",\"{}\"",
-					media.provider, media.media_id, inserted_at, media.title
+					"{},{},\"{}\",\"{}\",{},{}",
+					media.provider,
+					media.media_id,
+					inserted_at,
+					media.title,
+					format_csv_optional(media.duration_sec),
+					format_csv_optional(media.filesize_bytes)
 				);
 			},
 			SearchResultFormat::CSVT => {
 				println!(
-					"{}\t{}\t\"{}\"\t\"{}\"",
-					media.provider, media.media_id, inserted_at, media.title
+					"{}\t{}\t\"{}\"\t\"{}\"\t{}\t{}",
+					media.provider,
+					media.media_id,
+					inserted_at,
+					media.title,
+					format_csv_optional(media.duration_sec),
+					format_csv_optional(media.filesize_bytes)
 				);
 			},
+			// handled before the loop
+			SearchResultFormat::Json => (),
 		}
 	}
 
 	return Ok(());
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_format_media_sizes() {
+		let mut media = Media {
+			_id:            1,
+			media_id:       "someid".to_owned(),
+			provider:       "youtube".to_owned(),
+			title:          "Some Title".to_owned(),
+			inserted_at:    libytdlr::chrono::NaiveDateTime::default(),
+			extractor:      None,
+			uploader:       None,
+			upload_date:    None,
+			duration_sec:   None,
+			webpage_url:    None,
+			filesize_bytes: None,
+			content_hash:   None,
+			removed_at:     None,
+		};
+
+		assert_eq!("", format_media_sizes(&media));
+
+		media.duration_sec = Some(185);
+		assert_eq!(" (3:05)", format_media_sizes(&media));
+
+		media.filesize_bytes = Some(4 * 1024 * 1024);
+		assert_eq!(" (3:05, 4.00 MiB)", format_media_sizes(&media));
+	}
+
+	#[test]
+	fn test_format_media_json() {
+		let mut media = Media {
+			_id:            1,
+			media_id:       "someid".to_owned(),
+			provider:       "youtube".to_owned(),
+			title:          "Some \"Quoted\", Title".to_owned(),
+			inserted_at:    libytdlr::chrono::NaiveDateTime::default(),
+			extractor:      None,
+			uploader:       None,
+			upload_date:    None,
+			duration_sec:   None,
+			webpage_url:    None,
+			filesize_bytes: None,
+			content_hash:   None,
+			removed_at:     None,
+		};
+
+		assert_eq!(
+			r#"{"duration_sec":null,"filesize_bytes":null,"inserted_at":"1970-01-01T00:00:00+00:00","media_id":"someid","provider":"youtube","removed_at":null,"title":"Some \"Quoted\", Title"}"#,
+			format_media_json(&media).to_string()
+		);
+
+		media.duration_sec = Some(185);
+		assert_eq!(Some(185), format_media_json(&media)["duration_sec"].as_i64());
+
+		media.removed_at = Some(libytdlr::chrono::NaiveDateTime::default());
+		assert_eq!(
+			Some("1970-01-01T00:00:00+00:00"),
+			format_media_json(&media)["removed_at"].as_str()
+		);
+	}
+
+	#[test]
+	fn test_filename_matches() {
+		assert!(filename_matches(
+			Path::new("/music/Some Good Title.mp3"),
+			"some good",
+			false
+		));
+		assert!(!filename_matches(
+			Path::new("/music/Some Good Title.mp3"),
+			"good",
+			false
+		));
+		assert!(filename_matches(
+			Path::new("/music/Some Good Title.mp3"),
+			"some title",
+			true
+		));
+		assert!(!filename_matches(
+			Path::new("/music/Some Good Title.mp3"),
+			"music",
+			true
+		));
+	}
+}
@@ -0,0 +1,392 @@
+//! Module for the "selftest" command, which runs the full download pipeline on a small media
+
+use std::{
+	ffi::{
+		OsStr,
+		OsString,
+	},
+	fmt::Display,
+	path::{
+		Path,
+		PathBuf,
+	},
+};
+
+use colored::{
+	Color,
+	Colorize,
+};
+use libytdlr::{
+	chrono,
+	data::cache::media_info::MediaInfo,
+	diesel,
+	error::IOErrorToError,
+	main::{
+		archive::import::insert_insmedia_noupdate,
+		download::{
+			download_single,
+			CookieSource,
+			DownloadOptions,
+			FormatArgument,
+			FormatConstraints,
+			PlaylistOrder,
+			MINIMAL_YTDL_VERSION,
+			PATHS_THUMBNAIL_DIR,
+		},
+		rethumbnail::{
+			re_thumbnail_many,
+			ReThumbnailItem,
+			ReThumbnailProgress,
+		},
+		sql_utils::sqlite_connect,
+	},
+	spawn::{
+		ffmpeg::require_ffmpeg_installed,
+		ytdl::{
+			ytdl_parse_version_naivedate,
+			YtdlCapabilities,
+		},
+	},
+};
+
+use crate::clap_conf::{
+	CliDerive,
+	CommandSelfTest,
+};
+
+/// The stages of the selftest, in the order they are run
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SelfTestStage {
+	/// Check that all required tools are installed
+	Tools,
+	/// Download the media into the scratch directory
+	Download,
+	/// Re-apply the thumbnail to the downloaded media
+	ReThumbnail,
+	/// Move the media to the final directory in the scratch directory
+	Move,
+	/// Insert the media into a scratch archive
+	Archive,
+	/// Remove the scratch directory
+	Removal,
+}
+
+impl Display for SelfTestStage {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		return write!(
+			f,
+			"{}",
+			match self {
+				SelfTestStage::Tools => "tools",
+				SelfTestStage::Download => "download",
+				SelfTestStage::ReThumbnail => "rethumbnail",
+				SelfTestStage::Move => "move",
+				SelfTestStage::Archive => "archive",
+				SelfTestStage::Removal => "removal",
+			}
+		);
+	}
+}
+
+/// Minimal [`DownloadOptions`] for downloading the selftest media
+struct SelfTestOptions<'a> {
+	/// The URL to download
+	url:           &'a str,
+	/// The Path to download to
+	download_path: PathBuf,
+	/// The youtube-dl binary to spawn
+	ytdl_bin:      OsString,
+	/// The youtube-dl version in use
+	ytdl_version:  chrono::NaiveDate,
+}
+
+impl DownloadOptions for SelfTestOptions<'_> {
+	fn audio_only(&self) -> bool {
+		// audio-only to keep the download small and still have ffmpeg convert something
+		return true;
+	}
+
+	fn extra_ytdl_arguments(&self) -> Vec<&OsStr> {
+		return Vec::new();
+	}
+
+	fn download_path(&self) -> &Path {
+		return &self.download_path;
+	}
+
+	fn gen_archive<'a>(
+		&'a self,
+		_connection: &'a mut diesel::SqliteConnection,
+	) -> Option<Box<dyn Iterator<Item = String> + 'a>> {
+		return None;
+	}
+
+	fn get_url(&self) -> &str {
+		return self.url;
+	}
+
+	fn print_command_log(&self) -> bool {
+		return false;
+	}
+
+	fn save_command_log(&self) -> bool {
+		return false;
+	}
+
+	fn sub_langs(&self) -> Option<&str> {
+		return None;
+	}
+
+	fn subs_external(&self) -> bool {
+		return false;
+	}
+
+	fn write_info_json(&self) -> bool {
+		return false;
+	}
+
+	fn ytdl_version(&self) -> chrono::NaiveDate {
+		return self.ytdl_version;
+	}
+
+	fn ytdl_bin(&self) -> &OsStr {
+		return &self.ytdl_bin;
+	}
+
+	fn get_audio_format(&self) -> FormatArgument {
+		return "best";
+	}
+
+	fn get_video_format(&self) -> FormatArgument {
+		return "mkv";
+	}
+
+	fn strict_parse(&self) -> bool {
+		return false;
+	}
+
+	fn max_download_bytes(&self) -> Option<u64> {
+		return None;
+	}
+
+	fn stall_timeout(&self) -> Option<std::time::Duration> {
+		return None;
+	}
+
+	fn stall_kill(&self) -> bool {
+		return false;
+	}
+
+	fn playlist_order(&self) -> PlaylistOrder {
+		return PlaylistOrder::Default;
+	}
+
+	fn embed_thumbnail(&self) -> bool {
+		// required for the "rethumbnail" stage
+		return true;
+	}
+
+	fn remux_video(&self) -> bool {
+		return true;
+	}
+
+	fn remux_after_download(&self) -> bool {
+		return false;
+	}
+
+	fn ytdl_capabilities(&self) -> Option<&YtdlCapabilities> {
+		// the selftest does not use any optional options besides the version-gated ones
+		return None;
+	}
+
+	fn cookies(&self) -> Option<CookieSource<'_>> {
+		return None;
+	}
+
+	fn playlist_items(&self) -> Option<&str> {
+		return None;
+	}
+
+	fn max_downloads(&self) -> Option<usize> {
+		return None;
+	}
+
+	fn limit_rate(&self) -> Option<u64> {
+		return None;
+	}
+
+	fn format_constraints(&self) -> FormatConstraints<'_> {
+		return FormatConstraints::default();
+	}
+
+	fn concurrent_fragments(&self) -> Option<usize> {
+		return None;
+	}
+
+	fn retries(&self) -> usize {
+		return 0;
+	}
+
+	fn live(&self) -> bool {
+		return false;
+	}
+}
+
+/// Handler function for the "selftest" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_selftest(main_args: &CliDerive, sub_args: &CommandSelfTest) -> Result<(), crate::Error> {
+	let scratch_path = main_args
+		.tmp_path
+		.as_ref()
+		.map_or_else(|| return std::env::temp_dir(), |v| return v.clone())
+		.join(format!("ytdlr_selftest_{}", std::process::id()));
+
+	println!(
+		"Running selftest with \"{}\" in \"{}\"",
+		sub_args.url,
+		scratch_path.display()
+	);
+
+	let res = run_pipeline(main_args, sub_args, &scratch_path);
+
+	let removal = if sub_args.keep {
+		println!("Keeping scratch directory \"{}\"", scratch_path.display());
+
+		Ok(())
+	} else {
+		// always try to clean-up, even if a earlier stage failed
+		run_stage(SelfTestStage::Removal, || {
+			if scratch_path.exists() {
+				std::fs::remove_dir_all(&scratch_path).attach_path_err(&scratch_path)?;
+			}
+
+			return Ok(());
+		})
+	};
+
+	// the error of a earlier stage is more important than the removal error
+	res?;
+	removal?;
+
+	println!("Selftest finished successfully");
+
+	return Ok(());
+}
+
+/// Run all stages except [`SelfTestStage::Removal`], stopping at the first failed stage
+fn run_pipeline(main_args: &CliDerive, sub_args: &CommandSelfTest, scratch_path: &Path) -> Result<(), crate::Error> {
+	let (ytdl_bin, ytdl_version) = run_stage(SelfTestStage::Tools, || {
+		let ytdl = main_args.require_ytdl()?;
+		require_ffmpeg_installed()?;
+
+		return Ok(ytdl);
+	})?;
+
+	let download_path = scratch_path.join("download");
+	let media = run_stage(SelfTestStage::Download, || {
+		std::fs::create_dir_all(&download_path).attach_path_err(&download_path)?;
+
+		let options = SelfTestOptions {
+			url:           &sub_args.url,
+			download_path: download_path.clone(),
+			ytdl_bin:      ytdl_bin.clone(),
+			ytdl_version:  ytdl_parse_version_naivedate(&ytdl_version).unwrap_or(*MINIMAL_YTDL_VERSION),
+		};
+		let mut media_vec: Vec<MediaInfo> = Vec::new();
+		download_single(None, &options, |_| {}, &mut media_vec, None)?;
+
+		return media_vec
+			.into_iter()
+			.find(|v| {
+				return v
+					.filename
+					.as_ref()
+					.is_some_and(|filename| return download_path.join(filename).is_file());
+			})
+			.ok_or_else(|| return crate::Error::other("youtube-dl did not download any media file"));
+	})?;
+	let media_filename = media
+		.filename
+		.clone()
+		.expect("Expected the found media to have a filename");
+	let media_path = download_path.join(&media_filename);
+
+	run_stage(SelfTestStage::ReThumbnail, || {
+		let item = ReThumbnailItem::new(&media_path).with_image_dir(download_path.join(PATHS_THUMBNAIL_DIR));
+		let mut skipped = false;
+		let mut errors = re_thumbnail_many(&[item], |imp| {
+			if let ReThumbnailProgress::SingleSkipped(_) = imp {
+				skipped = true;
+			}
+		});
+
+		if let Some((_, err)) = errors.pop() {
+			return Err(err);
+		}
+
+		if skipped {
+			return Err(crate::Error::other(
+				"No thumbnail image was found for the downloaded media",
+			));
+		}
+
+		return Ok(());
+	})?;
+
+	run_stage(SelfTestStage::Move, || {
+		let final_dir_path = scratch_path.join("final");
+		std::fs::create_dir_all(&final_dir_path).attach_path_err(&final_dir_path)?;
+
+		// copy and remove, like when finishing a download, because the final directory may be on a different file-system
+		let to_path = final_dir_path.join(&media_filename);
+		std::fs::copy(&media_path, &to_path).attach_path_err(&media_path)?;
+		std::fs::remove_file(&media_path).attach_path_err(&media_path)?;
+
+		return Ok(());
+	})?;
+
+	run_stage(SelfTestStage::Archive, || {
+		use diesel::prelude::*;
+		use libytdlr::data::sql_schema::media_archive;
+
+		let archive_path = scratch_path.join("archive.db");
+		let mut connection = sqlite_connect(&archive_path)?;
+
+		insert_insmedia_noupdate(&(&media).into(), &mut connection)?;
+
+		let count: i64 = media_archive::dsl::media_archive
+			.filter(media_archive::media_id.eq(&media.id))
+			.filter(media_archive::provider.eq(media.provider.as_str()))
+			.count()
+			.get_result(&mut connection)?;
+
+		if count != 1 {
+			return Err(crate::Error::other("Inserted media could not be found in the archive"));
+		}
+
+		return Ok(());
+	})?;
+
+	return Ok(());
+}
+
+/// Run a single stage, printing whether it succeeded or failed
+/// A returned error has the stage attached to it
+fn run_stage<T, F>(stage: SelfTestStage, stage_fn: F) -> Result<T, crate::Error>
+where
+	F: FnOnce() -> Result<T, crate::Error>,
+{
+	return match stage_fn() {
+		Ok(v) => {
+			println!("[{}] {stage}", "ok".color(Color::Green));
+
+			Ok(v)
+		},
+		Err(err) => {
+			println!("[{}] {stage}: {err}", "FAIL".color(Color::Red));
+
+			Err(crate::Error::other(format!("Selftest failed in stage \"{stage}\"")))
+		},
+	};
+}
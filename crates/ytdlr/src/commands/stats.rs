@@ -0,0 +1,392 @@
+//! Module for the "archive stats" command
+
+use indicatif::{
+	HumanBytes,
+	ProgressBar,
+};
+use libytdlr::{
+	chrono::Utc,
+	main::archive::stats::{
+		archive_stats,
+		disk_stats,
+		ArchiveStats,
+		DiskStats,
+		StatsCount,
+		StatsMediaSize,
+		StatsSize,
+		StatsTotals,
+	},
+};
+
+use crate::{
+	clap_conf::{
+		self,
+		CliDerive,
+		StatsFormat,
+	},
+	utils,
+};
+
+/// Format `counts` as a table with the header `key_header` and "Count", with the columns aligned
+fn format_count_table(key_header: &str, counts: &[StatsCount]) -> String {
+	let key_width = counts
+		.iter()
+		.map(|v| return v.key.chars().count())
+		.chain([key_header.chars().count()])
+		.max()
+		.unwrap_or_default();
+
+	let mut lines = vec![format!("{key_header:key_width$}  Count")];
+	lines.extend(
+		counts
+			.iter()
+			.map(|v| return format!("{:key_width$}  {}", v.key, v.count)),
+	);
+
+	return lines.join("\n");
+}
+
+/// Format the total size and duration of `totals`, out of `total` media
+fn format_totals(totals: &StatsTotals, total: i64) -> String {
+	return format!(
+		"Total Size: {} ({} of {} media with a known size)\nTotal Duration: {}",
+		HumanBytes(u64::try_from(totals.total_bytes).unwrap_or_default()),
+		totals.sized_count,
+		total,
+		utils::format_duration_secs(u64::try_from(totals.total_duration_sec).unwrap_or_default())
+	);
+}
+
+/// Format `stats` as aligned tables, one for each section
+fn format_table(stats: &ArchiveStats) -> String {
+	let mut sections = vec![format!(
+		"Total: {}\n{}",
+		stats.total,
+		format_totals(&stats.totals, stats.total)
+	)];
+
+	if !stats.providers.is_empty() {
+		sections.push(format_count_table("Provider", &stats.providers));
+	}
+
+	sections.push(format_count_table("Month", &stats.months));
+
+	if !stats.top_days.is_empty() {
+		sections.push(format_count_table("Top Day", &stats.top_days));
+	}
+
+	return sections.join("\n\n");
+}
+
+/// Format `stats` as CSV with the columns "section,key,count", the total has a empty key
+/// The totals are in the section "totals", with the key being the total (like "total_bytes")
+fn format_csv(stats: &ArchiveStats) -> String {
+	let mut lines = vec![
+		"section,key,count".to_owned(),
+		format!("total,,{}", stats.total),
+		format!("totals,sized_count,{}", stats.totals.sized_count),
+		format!("totals,total_bytes,{}", stats.totals.total_bytes),
+		format!("totals,total_duration_sec,{}", stats.totals.total_duration_sec),
+	];
+
+	for (section, counts) in [
+		("provider", &stats.providers),
+		("month", &stats.months),
+		("day", &stats.top_days),
+	] {
+		lines.extend(
+			counts
+				.iter()
+				.map(|v| return format!("{},{},{}", section, v.key, v.count)),
+		);
+	}
+
+	return lines.join("\n");
+}
+
+/// Format `rows` as lines with all columns aligned, the first row being the header
+fn format_aligned(rows: &[Vec<String>]) -> String {
+	let mut widths: Vec<usize> = Vec::new();
+
+	for row in rows {
+		for (index, cell) in row.iter().enumerate() {
+			let len = cell.chars().count();
+
+			match widths.get_mut(index) {
+				Some(width) => *width = (*width).max(len),
+				None => widths.push(len),
+			}
+		}
+	}
+
+	return rows
+		.iter()
+		.map(|row| {
+			let last = row.len().saturating_sub(1);
+
+			return row
+				.iter()
+				.enumerate()
+				.map(|(index, cell)| {
+					// dont pad the last column, to not have trailing whitespace
+					if index == last {
+						return cell.clone();
+					}
+
+					return format!("{cell:width$}", width = widths[index]);
+				})
+				.collect::<Vec<String>>()
+				.join("  ");
+		})
+		.collect::<Vec<String>>()
+		.join("\n");
+}
+
+/// Format `sizes` as a table with the header `key_header`, "Media" and "Size", with the columns aligned
+fn format_size_table(key_header: &str, sizes: &[StatsSize]) -> String {
+	let mut rows = vec![vec![key_header.to_owned(), "Media".to_owned(), "Size".to_owned()]];
+	rows.extend(sizes.iter().map(|v| {
+		return vec![
+			v.key.clone(),
+			v.count.to_string(),
+			HumanBytes(u64::try_from(v.total_bytes).unwrap_or_default()).to_string(),
+		];
+	}));
+
+	return format_aligned(&rows);
+}
+
+/// Format `largest` as a table with the size, "provider:id" and title of each media, with the columns aligned
+fn format_largest_table(largest: &[StatsMediaSize]) -> String {
+	let mut rows = vec![vec!["Largest".to_owned(), "Media".to_owned(), "Title".to_owned()]];
+	rows.extend(largest.iter().map(|v| {
+		return vec![
+			HumanBytes(u64::try_from(v.filesize_bytes).unwrap_or_default()).to_string(),
+			format!("{}:{}", v.provider, v.media_id),
+			v.title.clone(),
+		];
+	}));
+
+	return format_aligned(&rows);
+}
+
+/// Format `stats` as aligned tables, one for each section
+fn format_disk_table(stats: &DiskStats) -> String {
+	let mut sections = vec![format!(
+		"Total Size: {} ({} media with a known size)",
+		HumanBytes(u64::try_from(stats.totals.total_bytes).unwrap_or_default()),
+		stats.totals.sized_count
+	)];
+
+	if !stats.providers.is_empty() {
+		sections.push(format_size_table("Provider", &stats.providers));
+	}
+
+	if !stats.uploaders.is_empty() {
+		sections.push(format_size_table("Uploader", &stats.uploaders));
+	}
+
+	if !stats.largest.is_empty() {
+		sections.push(format_largest_table(&stats.largest));
+	}
+
+	return sections.join("\n\n");
+}
+
+/// Format `stats` as CSV with the columns "section,key,count,total_bytes", the totals have a empty key
+/// The largest media are in the section "largest", with the key being "provider:id"
+fn format_disk_csv(stats: &DiskStats) -> String {
+	let mut lines = vec![
+		"section,key,count,total_bytes".to_owned(),
+		format!("totals,,{},{}", stats.totals.sized_count, stats.totals.total_bytes),
+	];
+
+	for (section, sizes) in [("provider", &stats.providers), ("uploader", &stats.uploaders)] {
+		lines.extend(sizes.iter().map(|v| {
+			return format!(
+				"{},\"{}\",{},{}",
+				section,
+				v.key.replace('"', "\"\""),
+				v.count,
+				v.total_bytes
+			);
+		}));
+	}
+
+	lines.extend(
+		stats
+			.largest
+			.iter()
+			.map(|v| return format!("largest,{}:{},1,{}", v.provider, v.media_id, v.filesize_bytes)),
+	);
+
+	return lines.join("\n");
+}
+
+/// Handler function for the "archive stats" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_stats(main_args: &CliDerive, sub_args: &clap_conf::ArchiveStats) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Stats!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	if sub_args.disk {
+		let stats = disk_stats(&mut connection, sub_args.largest)?;
+
+		match sub_args.format {
+			StatsFormat::Table => println!("{}", format_disk_table(&stats)),
+			StatsFormat::Csv => println!("{}", format_disk_csv(&stats)),
+			StatsFormat::Json => println!("{}", serde_json::to_string(&stats)?),
+		}
+
+		return Ok(());
+	}
+
+	let stats = archive_stats(&mut connection, Utc::now().date_naive(), sub_args.top_days)?;
+
+	match sub_args.format {
+		StatsFormat::Table => println!("{}", format_table(&stats)),
+		StatsFormat::Csv => println!("{}", format_csv(&stats)),
+		StatsFormat::Json => println!("{}", serde_json::to_string(&stats)?),
+	}
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// Test helper to get stats with a entry in every section
+	fn test_stats() -> ArchiveStats {
+		return ArchiveStats {
+			total:     3,
+			totals:    StatsTotals {
+				sized_count:        2,
+				total_bytes:        3 * 1024 * 1024,
+				total_duration_sec: 3723,
+			},
+			providers: vec![StatsCount::new("youtube", 2), StatsCount::new("soundcloud", 1)],
+			months:    vec![StatsCount::new("2024-02", 1), StatsCount::new("2024-03", 2)],
+			top_days:  vec![StatsCount::new("2024-03-01", 2)],
+		};
+	}
+
+	#[test]
+	fn test_format_table() {
+		assert_eq!(
+			"Total: 3
+Total Size: 3.00 MiB (2 of 3 media with a known size)
+Total Duration: 1:02:03
+
+Provider    Count
+youtube     2
+soundcloud  1
+
+Month    Count
+2024-02  1
+2024-03  2
+
+Top Day     Count
+2024-03-01  2",
+			format_table(&test_stats())
+		);
+
+		// empty sections besides the months are omitted
+		assert_eq!(
+			"Total: 0\nTotal Size: 0 B (0 of 0 media with a known size)\nTotal Duration: 0:00\n\nMonth  Count",
+			format_table(&ArchiveStats::default())
+		);
+	}
+
+	#[test]
+	fn test_format_csv() {
+		assert_eq!(
+			"section,key,count
+total,,3
+totals,sized_count,2
+totals,total_bytes,3145728
+totals,total_duration_sec,3723
+provider,youtube,2
+provider,soundcloud,1
+month,2024-02,1
+month,2024-03,2
+day,2024-03-01,2",
+			format_csv(&test_stats())
+		);
+	}
+	/// Test helper to get disk stats with a entry in every section
+	fn test_disk_stats() -> DiskStats {
+		return DiskStats {
+			totals:    StatsTotals {
+				sized_count:        3,
+				total_bytes:        3 * 1024 * 1024 + 512,
+				total_duration_sec: 0,
+			},
+			providers: vec![
+				StatsSize::new("youtube", 2, 3 * 1024 * 1024),
+				StatsSize::new("soundcloud", 1, 512),
+			],
+			uploaders: vec![StatsSize::new("Some \"Channel\"", 2, 3 * 1024 * 1024)],
+			largest:   vec![
+				StatsMediaSize {
+					provider:       "youtube".to_owned(),
+					media_id:       "a2".to_owned(),
+					title:          "Second Title".to_owned(),
+					filesize_bytes: 2 * 1024 * 1024,
+				},
+				StatsMediaSize {
+					provider:       "youtube".to_owned(),
+					media_id:       "a1".to_owned(),
+					title:          "First Title".to_owned(),
+					filesize_bytes: 1024 * 1024,
+				},
+			],
+		};
+	}
+
+	#[test]
+	fn test_format_disk_table() {
+		assert_eq!(
+			"Total Size: 3.00 MiB (3 media with a known size)
+
+Provider    Media  Size
+youtube     2      3.00 MiB
+soundcloud  1      512 B
+
+Uploader        Media  Size
+Some \"Channel\"  2      3.00 MiB
+
+Largest   Media       Title
+2.00 MiB  youtube:a2  Second Title
+1.00 MiB  youtube:a1  First Title",
+			format_disk_table(&test_disk_stats())
+		);
+
+		// empty sections are omitted
+		assert_eq!(
+			"Total Size: 0 B (0 media with a known size)",
+			format_disk_table(&DiskStats::default())
+		);
+	}
+
+	#[test]
+	fn test_format_disk_csv() {
+		assert_eq!(
+			"section,key,count,total_bytes
+totals,,3,3146240
+provider,\"youtube\",2,3145728
+provider,\"soundcloud\",1,512
+uploader,\"Some \"\"Channel\"\"\",2,3145728
+largest,youtube:a2,1,2097152
+largest,youtube:a1,1,1048576",
+			format_disk_csv(&test_disk_stats())
+		);
+	}
+}
@@ -0,0 +1,45 @@
+use indicatif::ProgressBar;
+
+use crate::{
+	clap_conf::{
+		ArchiveTag,
+		CliDerive,
+	},
+	commands::search::build_search_query,
+	utils,
+};
+use libytdlr::main::archive::labels::{
+	add_labels,
+	remove_labels,
+};
+
+/// Handler function for the "archive tag" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_tag(main_args: &CliDerive, sub_args: &ArchiveTag) -> Result<(), crate::Error> {
+	let Some(archive_path) = main_args.resolve_archive_path() else {
+		return Err(crate::Error::other("Archive is required for Tag!"));
+	};
+
+	let bar: ProgressBar = ProgressBar::hidden();
+	// dont set progress bar target, only required for handle_connect currently
+
+	let (_new_archive, mut connection) = utils::handle_connect(archive_path, &bar, main_args)?;
+
+	let ids = build_search_query(&sub_args.queries, &[], false).load_ids(&mut connection)?;
+
+	if ids.is_empty() {
+		println!("No Results found");
+		return Ok(());
+	}
+
+	let removed = remove_labels(&mut connection, &ids, &sub_args.remove)?;
+	let added = add_labels(&mut connection, &ids, &sub_args.add)?;
+
+	println!(
+		"Added {added} and removed {removed} Labels for {} matching Entries",
+		ids.len()
+	);
+
+	return Ok(());
+}
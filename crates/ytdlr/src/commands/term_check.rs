@@ -0,0 +1,234 @@
+//! Module for the "term-check" command, which compares the display width ytdlr calculates with what the terminal displays
+//!
+//! There are a lot of unicode and terminal problems, for example a wcwidth and wcswidth mismatch, or some terminals deciding some character is 2 wide instead of 1,
+//! which result in a broken progress-bar truncation, this command exists to find and debug those kinds of problems more easily
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+	clap_conf::{
+		CliDerive,
+		CommandTermCheck,
+	},
+	utils::msg_to_cluster,
+};
+
+/// The strings checked when none is given, covering the usual problem cases
+const DEFAULT_SAMPLES: &[&str] = &[
+	"Plain ASCII Title",
+	// CJK characters, always 2 wide
+	"日本語のタイトル",
+	// emoji, always 2 wide
+	"Emoji \u{1F3B5} Title",
+	// emoji with VS16 ("\u{fe0f}"), displayed as 1 or 2 wide depending on the terminal
+	"Heart \u{2764}\u{FE0F} Title",
+	// combining characters, 0 wide
+	"Combining e\u{301}",
+	// east asian ambiguous width characters, 1 wide or 2 wide in CJK contexts
+	"Ambiguous \u{B1}\u{BD}\u{A7} \u{2192}",
+];
+
+/// Locale prefixes which terminals commonly display ambiguous width characters as 2 wide for
+const CJK_LOCALE_PREFIXES: &[&str] = &["ja", "zh", "ko"];
+
+/// Analysis of a single grapheme (a single displayed character)
+#[derive(Debug, PartialEq)]
+struct GraphemeInfo<'a> {
+	/// The grapheme itself
+	grapheme:  &'a str,
+	/// The display width as counted by ytdlr (including workarounds)
+	width:     usize,
+	/// Whether the grapheme has a east asian ambiguous width, which terminals may display as 2 wide instead of 1
+	ambiguous: bool,
+	/// Whether the grapheme contains VS16 ("\u{fe0f}"), see the "workaround_fe0f" feature
+	has_fe0f:  bool,
+}
+
+/// Analyze every grapheme of `msg`
+fn analyze(msg: &str) -> Vec<GraphemeInfo<'_>> {
+	let mut last_pos = 0;
+
+	return msg_to_cluster(msg)
+		.into_iter()
+		.map(|v| {
+			let width = v.display_pos - last_pos;
+			last_pos = v.display_pos;
+
+			return GraphemeInfo {
+				grapheme: v.full_char,
+				width,
+				ambiguous: v.full_char.width() != v.full_char.width_cjk(),
+				has_fe0f: v.full_char.contains('\u{FE0F}'),
+			};
+		})
+		.collect();
+}
+
+/// Format the code-points of `grapheme`, like "U+0065 U+0301"
+fn codepoints(grapheme: &str) -> String {
+	return grapheme
+		.chars()
+		.map(|v| return format!("U+{:04X}", u32::from(v)))
+		.collect::<Vec<String>>()
+		.join(" ");
+}
+
+/// Get the locale in use, which is the first set of "LC_ALL", "LC_CTYPE" and "LANG"
+fn current_locale() -> Option<String> {
+	return ["LC_ALL", "LC_CTYPE", "LANG"]
+		.iter()
+		.filter_map(|v| return std::env::var(v).ok())
+		.find(|v| return !v.is_empty());
+}
+
+/// Get whether `locale` is a locale which terminals commonly display ambiguous width characters as 2 wide for
+fn is_cjk_locale(locale: &str) -> bool {
+	return CJK_LOCALE_PREFIXES.iter().any(|v| return locale.starts_with(v));
+}
+
+/// Handler function for the "term-check" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_term_check(_main_args: &CliDerive, sub_args: &CommandTermCheck) -> Result<(), crate::Error> {
+	let samples: Vec<&str> = if sub_args.strings.is_empty() {
+		DEFAULT_SAMPLES.to_vec()
+	} else {
+		sub_args.strings.iter().map(String::as_str).collect()
+	};
+
+	let locale = current_locale();
+
+	println!("Terminal Check");
+	println!(
+		"TERM: {}, TERM_PROGRAM: {}, Locale: {}",
+		std::env::var("TERM").unwrap_or_else(|_| return "<unset>".to_owned()),
+		std::env::var("TERM_PROGRAM").unwrap_or_else(|_| return "<unset>".to_owned()),
+		locale.as_deref().unwrap_or("<unset>")
+	);
+	match terminal_size::terminal_size() {
+		Some((w, h)) => println!("Terminal size: {}x{}", w.0, h.0),
+		None => println!("Terminal size: <not a terminal>"),
+	}
+	println!(
+		"Feature \"workaround_fe0f\": {}",
+		if cfg!(feature = "workaround_fe0f") {
+			"enabled"
+		} else {
+			"disabled"
+		}
+	);
+	println!();
+	println!("For every text, the \"|\" below it should be directly after the last character, otherwise the width is miscounted");
+
+	let mut any_fe0f = false;
+	let mut any_ambiguous = false;
+
+	for sample in samples {
+		let graphemes = analyze(sample);
+		let width: usize = graphemes.iter().map(|v| return v.width).sum();
+
+		any_fe0f |= graphemes.iter().any(|v| return v.has_fe0f);
+		any_ambiguous |= graphemes.iter().any(|v| return v.ambiguous);
+
+		println!();
+		println!("Text (raw): {:?}", sample);
+		println!("{}", sample);
+		println!("{}|", " ".repeat(width));
+		println!("ytdlr width: {}, graphemes: {}", width, graphemes.len());
+
+		if sub_args.print_content {
+			for info in &graphemes {
+				println!(
+					"  {:?} {} width {}{}{}",
+					info.grapheme,
+					codepoints(info.grapheme),
+					info.width,
+					if info.ambiguous { " (ambiguous)" } else { "" },
+					if info.has_fe0f { " (fe0f)" } else { "" },
+				);
+			}
+		}
+	}
+
+	if !sub_args.print_content {
+		println!();
+		println!("Per-character analysis: not enabled (use -c)");
+	}
+
+	println!();
+	println!("Suggestions:");
+
+	if any_fe0f {
+		if cfg!(feature = "workaround_fe0f") {
+			println!("- if the \"|\" is 1 too far right for text with \"\\u{{fe0f}}\", build without the \"workaround_fe0f\" feature (\"--no-default-features\")");
+		} else {
+			println!("- if the \"|\" is 1 too far left for text with \"\\u{{fe0f}}\", build with the \"workaround_fe0f\" feature (enabled by default)");
+		}
+	}
+
+	if any_ambiguous {
+		if locale.as_deref().is_some_and(is_cjk_locale) {
+			println!("- the locale is a CJK locale, for which terminals may display ambiguous width characters as 2 wide, but ytdlr always counts them as 1 wide");
+		}
+
+		println!("- if the \"|\" is too far left for text with ambiguous width characters, set the terminal to display them as narrow (like the \"ambiguous-width\" setting of VTE-based terminals)");
+	}
+
+	println!("- if everything else is aligned, the progress-bar truncation should work correctly in this terminal");
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_analyze() {
+		let graphemes = analyze("a\u{B1}e\u{301}\u{1F3B5}");
+
+		assert_eq!(
+			vec![
+				GraphemeInfo {
+					grapheme:  "a",
+					width:     1,
+					ambiguous: false,
+					has_fe0f:  false,
+				},
+				GraphemeInfo {
+					grapheme:  "\u{B1}",
+					width:     1,
+					ambiguous: true,
+					has_fe0f:  false,
+				},
+				GraphemeInfo {
+					grapheme:  "e\u{301}",
+					width:     1,
+					ambiguous: false,
+					has_fe0f:  false,
+				},
+				GraphemeInfo {
+					grapheme:  "\u{1F3B5}",
+					width:     2,
+					ambiguous: false,
+					has_fe0f:  false,
+				},
+			],
+			graphemes
+		);
+
+		assert!(analyze("\u{2764}\u{FE0F}")[0].has_fe0f);
+	}
+
+	#[test]
+	fn test_codepoints() {
+		assert_eq!("U+0065 U+0301", codepoints("e\u{301}"));
+	}
+
+	#[test]
+	fn test_is_cjk_locale() {
+		assert!(is_cjk_locale("ja_JP.UTF-8"));
+		assert!(is_cjk_locale("zh_CN.UTF-8"));
+		assert!(!is_cjk_locale("en_US.UTF-8"));
+	}
+}
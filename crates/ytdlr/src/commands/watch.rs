@@ -0,0 +1,464 @@
+use std::{
+	collections::HashMap,
+	path::{
+		Path,
+		PathBuf,
+	},
+	time::{
+		Duration,
+		Instant,
+		SystemTime,
+	},
+};
+
+use crate::{
+	clap_conf::{
+		CliDerive,
+		CommandWatch,
+	},
+	commands::download::{
+		check_termination,
+		command_download,
+	},
+	utils::read_url_file,
+};
+use libytdlr::error::IOErrorToError;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Minimal time since the last modification of a file before it is processed
+/// To not read files which are still being written (like by a sync tool)
+const MIN_FILE_AGE: Duration = Duration::from_secs(5);
+
+/// Regex to find links in plain text files
+static TEXT_URL_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r#"https?://[^\s<>"']+"#).unwrap();
+});
+
+/// Regex to find the link in a (xml) ".webloc" file
+/// cap1: the url (xml escaped)
+static WEBLOC_URL_REGEX: Lazy<Regex> = Lazy::new(|| {
+	return Regex::new(r"(?s)<key>URL</key>\s*<string>([^<]+)</string>").unwrap();
+});
+
+/// Handler function for the "watch" subcommand
+/// This function is mainly to keep the code structured and sorted
+#[inline]
+pub fn command_watch(main_args: &CliDerive, sub_args: &CommandWatch) -> Result<(), crate::Error> {
+	let watch_dir = sub_args.watch_dir.as_deref();
+	let processed_dir = sub_args.processed_dir();
+
+	if let Some(watch_dir) = watch_dir {
+		if !watch_dir.is_dir() {
+			return Err(crate::Error::not_a_directory(
+				"Watch Directory does not exist or is not a directory",
+				watch_dir,
+			));
+		}
+
+		println!("Watching \"{}\" for link files", watch_dir.to_string_lossy());
+	}
+
+	if let Some(processed_dir) = processed_dir.as_ref() {
+		std::fs::create_dir_all(processed_dir).attach_path_err(processed_dir)?;
+	}
+
+	if sub_args.has_subscriptions() {
+		// without a archive, every poll would download all entries of the subscriptions again
+		if main_args.resolve_archive_path().is_none() {
+			return Err(crate::Error::other(
+				"Archive is required for polling subscriptions, otherwise all media would be downloaded every poll",
+			));
+		}
+
+		println!("Polling subscriptions every {} seconds", sub_args.poll_interval);
+	}
+
+	// files that failed (parsing or downloading), only tried again once they get modified
+	let mut failed_files: HashMap<PathBuf, SystemTime> = HashMap::new();
+	let mut last_poll: Option<Instant> = None;
+	let poll_interval = Duration::from_secs(sub_args.poll_interval);
+
+	loop {
+		check_termination()?;
+
+		if let (Some(watch_dir), Some(processed_dir)) = (watch_dir, processed_dir.as_deref()) {
+			let files: Vec<PathBuf> = find_link_files(watch_dir)?
+				.into_iter()
+				.filter(|(path, modified)| return failed_files.get(path) != Some(modified))
+				.map(|(path, _)| return path)
+				.collect();
+
+			if !files.is_empty() {
+				process_files(main_args, sub_args, &files, processed_dir, &mut failed_files)?;
+			}
+		}
+
+		if sub_args.has_subscriptions() && last_poll.map_or(true, |v| return v.elapsed() >= poll_interval) {
+			last_poll = Some(Instant::now());
+			poll_subscriptions(main_args, sub_args)?;
+		}
+
+		if sub_args.once {
+			break;
+		}
+
+		// without a watched directory, there is nothing to do until the next poll
+		let sleep_secs = if watch_dir.is_some() {
+			sub_args.interval
+		} else {
+			sub_args.poll_interval
+		};
+
+		// sleep in small steps to not delay termination requests
+		for _ in 0..sleep_secs {
+			check_termination()?;
+			std::thread::sleep(Duration::from_secs(1));
+		}
+	}
+
+	return Ok(());
+}
+
+/// Get all subscription URLs, from "--subscription" (or the config) and "--subscriptions-file", without duplicates
+/// The file is read again every time, so that it can be changed while watching
+fn subscription_urls(sub_args: &CommandWatch) -> Result<Vec<String>, crate::Error> {
+	let mut urls: Vec<String> = Vec::new();
+	let from_file = match sub_args.subscriptions_file.as_deref() {
+		Some(path) => read_url_file(path)?,
+		None => Vec::new(),
+	};
+
+	for url in sub_args.subscriptions.iter().cloned().chain(from_file) {
+		if !urls.contains(&url) {
+			urls.push(url);
+		}
+	}
+
+	return Ok(urls);
+}
+
+/// Download all new media of the subscriptions, media already in the archive is skipped by "download"
+/// Failures are logged and tried again on the next poll
+fn poll_subscriptions(main_args: &CliDerive, sub_args: &CommandWatch) -> Result<(), crate::Error> {
+	let urls = match subscription_urls(sub_args) {
+		Ok(v) => v,
+		Err(err) => {
+			error!("Reading the subscriptions failed, trying again next poll: {}", err);
+
+			return Ok(());
+		},
+	};
+
+	if urls.is_empty() {
+		warn!("No subscriptions found to poll");
+
+		return Ok(());
+	}
+
+	println!("Polling {} subscriptions", urls.len());
+
+	let mut download_args = sub_args.download.clone();
+	download_args.urls = urls;
+
+	if let Err(err) = command_download(main_args, &download_args) {
+		// dont continue watching if termination was requested
+		check_termination()?;
+
+		error!("Polling the subscriptions failed, trying again next poll: {}", err);
+	}
+
+	return Ok(());
+}
+
+/// Parse all `files`, download the links found and move successfully processed files to `processed_dir`
+/// Files that failed are added to `failed_files`
+fn process_files(
+	main_args: &CliDerive,
+	sub_args: &CommandWatch,
+	files: &[PathBuf],
+	processed_dir: &Path,
+	failed_files: &mut HashMap<PathBuf, SystemTime>,
+) -> Result<(), crate::Error> {
+	let mut urls: Vec<String> = Vec::new();
+	let mut parsed_files: Vec<&Path> = Vec::new();
+
+	for file in files {
+		match parse_link_file(file) {
+			Ok(found) => {
+				if found.is_empty() {
+					warn!("No links found in \"{}\"", file.to_string_lossy());
+				}
+
+				for url in found {
+					if !urls.contains(&url) {
+						urls.push(url);
+					}
+				}
+
+				parsed_files.push(file);
+			},
+			Err(err) => {
+				warn!("Parsing \"{}\" failed: {}", file.to_string_lossy(), err);
+				mark_failed(failed_files, file);
+			},
+		}
+	}
+
+	if !urls.is_empty() {
+		println!("Found {} links in {} files", urls.len(), parsed_files.len());
+
+		let mut download_args = sub_args.download.clone();
+		download_args.urls = urls;
+
+		if let Err(err) = command_download(main_args, &download_args) {
+			// dont continue watching if termination was requested
+			check_termination()?;
+
+			error!(
+				"Downloading the links failed, files will be tried again once modified: {}",
+				err
+			);
+
+			for file in parsed_files {
+				mark_failed(failed_files, file);
+			}
+
+			return Ok(());
+		}
+	}
+
+	for file in parsed_files {
+		move_processed(file, processed_dir)?;
+	}
+
+	return Ok(());
+}
+
+/// Remember `file` with its current modification time as failed
+fn mark_failed(failed_files: &mut HashMap<PathBuf, SystemTime>, file: &Path) {
+	if let Ok(modified) = std::fs::metadata(file).and_then(|v| return v.modified()) {
+		failed_files.insert(file.to_path_buf(), modified);
+	}
+}
+
+/// Move `file` into `processed_dir`, prefixing the name with a timestamp if it already exists there
+fn move_processed(file: &Path, processed_dir: &Path) -> Result<(), crate::Error> {
+	let file_name = file
+		.file_name()
+		.ok_or_else(|| return crate::Error::other("Expected processed file to have a file name"))?;
+	let mut target = processed_dir.join(file_name);
+
+	if target.exists() {
+		target = processed_dir.join(format!(
+			"{}-{}",
+			libytdlr::chrono::Local::now().format("%Y-%m-%dT%H-%M-%S"),
+			file_name.to_string_lossy()
+		));
+	}
+
+	std::fs::rename(file, &target).attach_path_err(file)?;
+
+	return Ok(());
+}
+
+/// Find all supported link files directly in `dir` which have not been modified in [`MIN_FILE_AGE`], sorted by path
+/// Hidden files (starting with ".") are ignored
+fn find_link_files(dir: &Path) -> Result<Vec<(PathBuf, SystemTime)>, crate::Error> {
+	let now = SystemTime::now();
+	let mut files = Vec::new();
+
+	for entry in std::fs::read_dir(dir).attach_path_err(dir)? {
+		let entry = entry.attach_path_err(dir)?;
+		let path = entry.path();
+
+		let is_hidden = path
+			.file_name()
+			.map_or(true, |v| return v.to_string_lossy().starts_with('.'));
+
+		if is_hidden || link_file_type(&path).is_none() {
+			continue;
+		}
+
+		let metadata = entry.metadata().attach_path_err(&path)?;
+
+		if !metadata.is_file() {
+			continue;
+		}
+
+		let modified = metadata.modified().attach_path_err(&path)?;
+
+		// skip files which may still be written
+		if now.duration_since(modified).unwrap_or_default() < MIN_FILE_AGE {
+			continue;
+		}
+
+		files.push((path, modified));
+	}
+
+	files.sort();
+
+	return Ok(files);
+}
+
+/// Supported link file types
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LinkFileType {
+	/// Windows Internet Shortcut (".url")
+	Url,
+	/// MacOS Website Location (".webloc"), only the xml variant is supported
+	Webloc,
+	/// Plain text file (".txt"), all links in it are used
+	Text,
+}
+
+/// Get the [`LinkFileType`] of `path` by its extension
+fn link_file_type(path: &Path) -> Option<LinkFileType> {
+	return match path.extension()?.to_str()?.to_lowercase().as_str() {
+		"url" => Some(LinkFileType::Url),
+		"webloc" => Some(LinkFileType::Webloc),
+		"txt" => Some(LinkFileType::Text),
+		_ => None,
+	};
+}
+
+/// Read and parse the link file at `path`
+fn parse_link_file(path: &Path) -> Result<Vec<String>, crate::Error> {
+	let file_type =
+		link_file_type(path).ok_or_else(|| return crate::Error::other("Unsupported link file extension"))?;
+	let content = std::fs::read(path).attach_path_err(path)?;
+
+	if file_type == LinkFileType::Webloc && content.starts_with(b"bplist") {
+		return Err(crate::Error::other(
+			"Binary \".webloc\" files are not supported, only the xml variant",
+		));
+	}
+
+	return Ok(parse_links(file_type, &String::from_utf8_lossy(&content)));
+}
+
+/// Parse all links from the `content` of a file with type `file_type`
+fn parse_links(file_type: LinkFileType, content: &str) -> Vec<String> {
+	return match file_type {
+		LinkFileType::Url => content
+			.lines()
+			.filter_map(|line| {
+				let (key, value) = line.trim().split_once('=')?;
+
+				if !key.eq_ignore_ascii_case("url") || value.is_empty() {
+					return None;
+				}
+
+				return Some(value.to_owned());
+			})
+			.collect(),
+		LinkFileType::Webloc => WEBLOC_URL_REGEX
+			.captures_iter(content)
+			.map(|cap| return unescape_xml(cap[1].trim()))
+			.collect(),
+		LinkFileType::Text => TEXT_URL_REGEX
+			.find_iter(content)
+			.map(|v| return v.as_str().to_owned())
+			.collect(),
+	};
+}
+
+/// Unescape the basic xml entities
+fn unescape_xml(input: &str) -> String {
+	return input
+		.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&amp;", "&");
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	mod parse_links {
+		use super::*;
+
+		#[test]
+		fn test_url() {
+			let content = "[InternetShortcut]\r\nURL=https://www.youtube.com/watch?v=abc&t=1\r\nIconIndex=0\r\n";
+			assert_eq!(
+				vec!["https://www.youtube.com/watch?v=abc&t=1".to_owned()],
+				parse_links(LinkFileType::Url, content)
+			);
+		}
+
+		#[test]
+		fn test_webloc() {
+			let content = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>URL</key>
+	<string>https://www.youtube.com/watch?v=abc&amp;t=1</string>
+</dict>
+</plist>
+"#;
+			assert_eq!(
+				vec!["https://www.youtube.com/watch?v=abc&t=1".to_owned()],
+				parse_links(LinkFileType::Webloc, content)
+			);
+		}
+
+		#[test]
+		fn test_text() {
+			let content = "Some Title\nhttps://youtu.be/abc\nlook at this: http://example.com/video?id=1 nice\n";
+			assert_eq!(
+				vec![
+					"https://youtu.be/abc".to_owned(),
+					"http://example.com/video?id=1".to_owned()
+				],
+				parse_links(LinkFileType::Text, content)
+			);
+			assert!(parse_links(LinkFileType::Text, "no links here").is_empty());
+		}
+	}
+
+	#[test]
+	fn test_subscription_urls() {
+		let tmp = tempfile::Builder::new()
+			.prefix("ytdlr-test-watch-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let path = tmp.path().join("subscriptions.txt");
+		std::fs::write(
+			&path,
+			"# channels\nhttps://www.youtube.com/@a\n\nhttps://www.youtube.com/@b\n",
+		)
+		.expect("Expected the file to be written");
+
+		let sub_args = CommandWatch {
+			watch_dir:          None,
+			processed_dir:      None,
+			interval:           30,
+			once:               true,
+			subscriptions:      vec!["https://www.youtube.com/@b".to_owned()],
+			subscriptions_file: Some(path),
+			poll_interval:      3600,
+			download:           crate::clap_conf::CommandDownload::default(),
+		};
+
+		assert_eq!(
+			vec![
+				"https://www.youtube.com/@b".to_owned(),
+				"https://www.youtube.com/@a".to_owned()
+			],
+			subscription_urls(&sub_args).expect("Expected the subscriptions to be read")
+		);
+	}
+
+	#[test]
+	fn test_link_file_type() {
+		assert_eq!(Some(LinkFileType::Url), link_file_type(Path::new("a.URL")));
+		assert_eq!(Some(LinkFileType::Webloc), link_file_type(Path::new("a.webloc")));
+		assert_eq!(Some(LinkFileType::Text), link_file_type(Path::new("/some/a.txt")));
+		assert_eq!(None, link_file_type(Path::new("a.mp3")));
+		assert_eq!(None, link_file_type(Path::new("a")));
+	}
+}
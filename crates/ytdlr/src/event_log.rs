@@ -0,0 +1,156 @@
+//! Module for the event log, which writes download lifecycle events as JSON lines to a file (see "--event-log")
+//! Every line is a JSON object with at least a "event" and a "time" field, like `{"event":"single_starting","id":"...","title":"...","time":"..."}`
+
+use std::{
+	fs::File,
+	io::Write,
+	path::Path,
+	sync::Mutex,
+};
+
+use libytdlr::{
+	chrono,
+	data::cache::media_info::MediaInfo,
+	error::IOErrorToError,
+	main::download::DownloadProgress,
+};
+use serde_json::{
+	json,
+	Value,
+};
+
+/// The currently open event log, see [`open`]
+static EVENT_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+/// Open the event log at `path`, appending if it already exists
+/// All following events are written to it until [`close`] is called
+pub fn open(path: &Path) -> Result<(), crate::Error> {
+	let file = File::options()
+		.create(true)
+		.append(true)
+		.open(path)
+		.attach_path_err(path)?;
+
+	*EVENT_LOG.lock().expect("Expected to acquire the event log lock") = Some(file);
+
+	return Ok(());
+}
+
+/// Close the event log, following events are not written anymore
+pub fn close() {
+	EVENT_LOG.lock().expect("Expected to acquire the event log lock").take();
+}
+
+/// Write `event` as a single line to the event log, if it is open
+/// `event` is expected to be a JSON object, which gets the current "time" added
+fn write(mut event: Value) {
+	let mut guard = EVENT_LOG.lock().expect("Expected to acquire the event log lock");
+	let Some(file) = guard.as_mut() else {
+		return;
+	};
+
+	if let Value::Object(map) = &mut event {
+		map.insert("time".to_owned(), Value::String(chrono::Utc::now().to_rfc3339()));
+	}
+
+	let mut line = event.to_string();
+	line.push('\n');
+
+	// write the whole line at once, so that watchers never read partial lines
+	if let Err(err) = file.write_all(line.as_bytes()) {
+		warn!("Writing to the event log failed: {}", err);
+	}
+}
+
+/// Write a download progress event, in the same format as "--progress-format json"
+pub fn write_progress(dpg: &DownloadProgress) {
+	match serde_json::to_value(dpg) {
+		Ok(v) => write(v),
+		Err(err) => warn!("Serializing event for the event log failed: {}", err),
+	}
+}
+
+/// Write that `media` has been moved from the download directory to its final path
+pub fn write_moved(media: &MediaInfo, from: &Path, to: &Path) {
+	write(json!({
+		"event": "moved",
+		"id": media.id,
+		"provider": media.provider.as_str(),
+		"title": media.title,
+		"from": from,
+		"to": to,
+	}));
+}
+
+/// Write that downloading `url` errored
+/// Errors reported by youtube-dl also have the "category", "id" and "exit_code" written
+pub fn write_error(url: &str, err: &crate::Error) {
+	let mut event = json!({
+		"event": "error",
+		"url": url,
+		"message": err.to_string(),
+	});
+
+	if let (Some(download_err), Value::Object(map)) = (err.as_download_error(), &mut event) {
+		map.insert("category".to_owned(), Value::String(download_err.category.to_string()));
+		map.insert("id".to_owned(), json!(download_err.media_id));
+		map.insert("exit_code".to_owned(), json!(download_err.exit_code));
+	}
+
+	write(event);
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_write_events() {
+		let tmp = tempfile::Builder::new()
+			.prefix("ytdlr-test-event_log-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let path = tmp.path().join("events.jsonl");
+
+		// not written, because the event log is not open yet
+		write_progress(&DownloadProgress::UrlStarting);
+
+		open(&path).expect("Expected the event log to open");
+		write_progress(&DownloadProgress::SingleStarting("id1".to_owned(), "Title".to_owned()));
+		write_moved(
+			&MediaInfo::new("id1", "youtube").with_title("Title"),
+			Path::new("/tmp/a.mkv"),
+			Path::new("/out/Title.mkv"),
+		);
+		write_error("https://example.com", &crate::Error::other("some error"));
+		write_error(
+			"https://example.com/id2",
+			&crate::Error::download_failed(libytdlr::error::DownloadError::from_line(
+				"ERROR: [youtube] id2: Video unavailable",
+			)),
+		);
+		close();
+
+		// not written, because the event log is closed
+		write_progress(&DownloadProgress::UrlFinished(1));
+
+		let lines: Vec<Value> = std::fs::read_to_string(&path)
+			.expect("Expected the event log to be readable")
+			.lines()
+			.map(|v| return serde_json::from_str(v).expect("Expected every line to be JSON"))
+			.collect();
+
+		assert_eq!(4, lines.len());
+		assert!(lines.iter().all(|v| return v["time"].is_string()));
+		assert_eq!("single_starting", lines[0]["event"]);
+		assert_eq!("id1", lines[0]["id"]);
+		assert_eq!("moved", lines[1]["event"]);
+		assert_eq!("youtube", lines[1]["provider"]);
+		assert_eq!("/out/Title.mkv", lines[1]["to"]);
+		assert_eq!("error", lines[2]["event"]);
+		assert_eq!("https://example.com", lines[2]["url"]);
+		assert!(lines[2]["category"].is_null());
+		assert_eq!("unavailable", lines[3]["category"]);
+		assert_eq!("id2", lines[3]["id"]);
+	}
+}
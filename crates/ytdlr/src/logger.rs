@@ -6,11 +6,28 @@ use colored::{
 };
 use flexi_logger::{
 	style,
+	writers::LogWriter,
 	DeferredNow,
 	Logger,
 	LoggerHandle,
 	Record,
 };
+use is_terminal::IsTerminal;
+use once_cell::sync::Lazy;
+use std::{
+	collections::VecDeque,
+	io::Write,
+	sync::Mutex,
+};
+
+use crate::utils;
+
+/// Maximal amount of lines to keep in [`LOG_TAIL`], "0" means the log tail is disabled
+static LOG_TAIL_SIZE: Mutex<usize> = Mutex::new(0);
+/// The last warn / error log lines, while the log tail is enabled
+static LOG_TAIL: Lazy<Mutex<VecDeque<String>>> = Lazy::new(|| {
+	return Mutex::new(VecDeque::new());
+});
 
 /// Function for setting up the logger
 /// This function is mainly to keep the code structured and sorted
@@ -18,8 +35,7 @@ use flexi_logger::{
 pub fn setup_logger() -> LoggerHandle {
 	let handle = Logger::try_with_env_or_str("warn")
 		.expect("Expected flexi_logger to be able to parse env or string")
-		.adaptive_format_for_stderr(flexi_logger::AdaptiveFormat::Custom(log_format, color_log_format))
-		.log_to_stderr()
+		.log_to_writer(Box::new(StderrTailWriter))
 		.start()
 		.expect("Expected flexi_logger to be able to start");
 
@@ -64,3 +80,95 @@ pub fn color_log_format(
 		&record.args() // dont apply any color to the input, so that the input can dynamically set the color
 	);
 }
+
+/// Log writer which writes to stderr (colored if stderr is a tty), except for warn / error lines while the log tail is enabled
+/// See [`enable_log_tail`]
+struct StderrTailWriter;
+
+impl LogWriter for StderrTailWriter {
+	fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+		if record.level() <= log::Level::Warn {
+			let size = *LOG_TAIL_SIZE
+				.lock()
+				.expect("Expected LOG_TAIL_SIZE lock to not be poisoned");
+
+			if size > 0 {
+				let mut line = Vec::new();
+				log_format(&mut line, now, record)?;
+
+				let mut tail = LOG_TAIL.lock().expect("Expected LOG_TAIL lock to not be poisoned");
+				tail.push_back(String::from_utf8_lossy(&line).into_owned());
+				while tail.len() > size {
+					tail.pop_front();
+				}
+
+				return Ok(());
+			}
+		}
+
+		let mut stderr = std::io::stderr().lock();
+
+		if stderr.is_terminal() {
+			color_log_format(&mut stderr, now, record)?;
+		} else {
+			log_format(&mut stderr, now, record)?;
+		}
+
+		return writeln!(stderr);
+	}
+
+	fn flush(&self) -> std::io::Result<()> {
+		return std::io::stderr().flush();
+	}
+}
+
+/// Enable the log tail, keeping the last `size` warn / error lines instead of writing them to stderr
+/// The lines can be displayed with [`write_log_tail`], like under a progress bar
+pub fn enable_log_tail(size: usize) {
+	*LOG_TAIL_SIZE
+		.lock()
+		.expect("Expected LOG_TAIL_SIZE lock to not be poisoned") = size;
+}
+
+/// Disable the log tail and write all lines still in it to stderr, so that they do not get lost
+pub fn disable_log_tail() {
+	*LOG_TAIL_SIZE
+		.lock()
+		.expect("Expected LOG_TAIL_SIZE lock to not be poisoned") = 0;
+
+	let mut stderr = std::io::stderr().lock();
+
+	for line in LOG_TAIL
+		.lock()
+		.expect("Expected LOG_TAIL lock to not be poisoned")
+		.drain(..)
+	{
+		// ignore errors, because there is nowhere else to log to
+		let _ = writeln!(stderr, "{line}");
+	}
+}
+
+/// Write the current log tail lines to `w`, separated by new-lines and truncated to the terminal width
+/// Meant to be used as a key for a [`indicatif::ProgressStyle`]
+pub fn write_log_tail(w: &mut dyn std::fmt::Write) {
+	let width = terminal_size::terminal_size().map(|(w, _h)| return w.0 as usize);
+
+	for (index, line) in LOG_TAIL
+		.lock()
+		.expect("Expected LOG_TAIL lock to not be poisoned")
+		.iter()
+		.enumerate()
+	{
+		if index > 0 {
+			// ignore errors, because a failed write only affects the display
+			let _ = w.write_char('\n');
+		}
+
+		let line = match width {
+			Some(width) => utils::truncate_message_display_pos(line, width, true),
+			None => line.into(),
+		};
+
+		let _ = w.write_str(&line);
+	}
+}
@@ -7,6 +7,7 @@ extern crate log;
 use flexi_logger::LogSpecification;
 use libytdlr::{
 	invoke_vscode_debugger,
+	main::download::CancellationToken,
 	Error,
 };
 use once_cell::sync::Lazy;
@@ -21,8 +22,12 @@ use clap_conf::{
 };
 
 mod commands;
+mod event_log;
 mod logger;
+mod messages;
+mod notification;
 mod state;
+mod trash;
 mod utils;
 
 /// Simple struct to keep all data for termination requests (ctrlc handler)
@@ -32,6 +37,8 @@ struct TerminateData {
 	enabled:             bool,
 	/// Stores whether termination has been requested
 	terminate_requested: bool,
+	/// Token cancelled once termination has been requested, to stop a running download
+	cancel:              CancellationToken,
 }
 
 impl Default for TerminateData {
@@ -39,6 +46,7 @@ impl Default for TerminateData {
 		return TerminateData {
 			enabled:             true,
 			terminate_requested: false,
+			cancel:              CancellationToken::new(),
 		};
 	}
 }
@@ -52,6 +60,12 @@ impl TerminateData {
 	/// Set that termination has been requested
 	pub fn set_terminate(&mut self) {
 		self.terminate_requested = true;
+		self.cancel.cancel();
+	}
+
+	/// Get a token which is cancelled once termination has been requested
+	pub fn cancellation_token(&self) -> CancellationToken {
+		return self.cancel.clone();
 	}
 
 	/// Set handler to be disabled until re-enabled
@@ -160,6 +174,15 @@ fn actual_main() -> Result<(), crate::Error> {
 
 	colored::control::set_override(cli_matches.enable_colors());
 
+	messages::set_lang(cli_matches.lang.unwrap_or_else(messages::Lang::from_env));
+
+	utils::set_assume_yes(cli_matches.assume_yes);
+
+	libytdlr::spawn::priority::set_process_priority(libytdlr::spawn::priority::ProcessPriority {
+		nice:   cli_matches.nice,
+		ionice: cli_matches.ionice,
+	});
+
 	// dont do anything if "-v" is not specified (use env / default instead)
 	if cli_matches.verbosity > 0 {
 		// apply cli "verbosity" argument to the log level
@@ -183,9 +206,15 @@ fn actual_main() -> Result<(), crate::Error> {
 		SubCommands::Download(v) => commands::download::command_download(&cli_matches, v),
 		SubCommands::Archive(v) => sub_archive(&cli_matches, v),
 		SubCommands::ReThumbnail(v) => commands::rethumbnail::command_rethumbnail(&cli_matches, v),
+		SubCommands::ReThumbnailBatch(v) => commands::rethumbnail::command_rethumbnail_batch(&cli_matches, v),
+		SubCommands::Diff(v) => commands::diff::command_diff(&cli_matches, v),
+		SubCommands::CheckArchive(v) => commands::check_archive::command_check_archive(&cli_matches, v),
+		SubCommands::Watch(v) => commands::watch::command_watch(&cli_matches, v),
+		SubCommands::SelfTest(v) => commands::selftest::command_selftest(&cli_matches, v),
 		SubCommands::Completions(v) => commands::completions::command_completions(&cli_matches, v),
-		#[cfg(debug_assertions)]
-		SubCommands::UnicodeTerminalTest(v) => commands::unicode_test::command_unicodeterminaltest(&cli_matches, v),
+		SubCommands::TermCheck(v) => commands::term_check::command_term_check(&cli_matches, v),
+		SubCommands::Restore(v) => commands::restore::command_restore(&cli_matches, v),
+		SubCommands::CheckDeps(v) => commands::check_deps::command_check_deps(&cli_matches, v),
 	};
 }
 
@@ -195,7 +224,17 @@ fn actual_main() -> Result<(), crate::Error> {
 fn sub_archive(main_args: &CliDerive, sub_args: &ArchiveDerive) -> Result<(), crate::Error> {
 	match &sub_args.subcommands {
 		ArchiveSubCommands::Import(v) => commands::import::command_import(main_args, v),
+		ArchiveSubCommands::Ingest(v) => commands::ingest::command_ingest(main_args, v),
+		ArchiveSubCommands::Remove(v) => commands::remove::command_remove(main_args, v),
 		ArchiveSubCommands::Search(v) => commands::search::command_search(main_args, v),
+		ArchiveSubCommands::Migrations(v) => commands::migrations::command_migrations(main_args, v),
+		ArchiveSubCommands::Tag(v) => commands::tag::command_tag(main_args, v),
+		ArchiveSubCommands::Browse(v) => commands::browse::command_browse(main_args, v),
+		ArchiveSubCommands::Maintenance(v) => commands::maintenance::command_maintenance(main_args, v),
+		ArchiveSubCommands::Stats(v) => commands::stats::command_stats(main_args, v),
+		ArchiveSubCommands::GenYtdlArchive(v) => commands::gen_ytdl_archive::command_gen_ytdl_archive(main_args, v),
+		ArchiveSubCommands::Has(v) => commands::has::command_has(main_args, v),
+		ArchiveSubCommands::Dupes(v) => commands::dupes::command_dupes(main_args, v),
 	}?;
 
 	return Ok(());
@@ -0,0 +1,395 @@
+//! Module for the catalog of user-facing messages, so that they can be translated
+//!
+//! Messages can contain numbered placeholders like `{0}`, which are replaced with [`trf`]
+
+use once_cell::sync::OnceCell;
+use std::fmt::{
+	Display,
+	Write,
+};
+
+/// Languages the messages are available in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Lang {
+	/// English, the default and fallback for all languages
+	#[default]
+	En,
+	/// German
+	De,
+}
+
+impl Lang {
+	/// Detect the language from the environment ("LC_ALL", "LC_MESSAGES", "LANG", in that order)
+	/// Falls back to [`Lang::En`] if no supported language is set
+	pub fn from_env() -> Self {
+		for var in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+			let Ok(value) = std::env::var(var) else {
+				continue;
+			};
+
+			// a empty variable does not override the next one
+			if value.is_empty() {
+				continue;
+			}
+
+			return Self::from_locale(&value).unwrap_or_default();
+		}
+
+		return Self::default();
+	}
+
+	/// Get the language from a locale string, like "de_DE.UTF-8" or "en"
+	/// Returns [`None`] if the language is not supported
+	pub fn from_locale(locale: &str) -> Option<Self> {
+		// strip country, encoding and modifier, like "de_DE.UTF-8@euro"
+		let lang = locale
+			.split(|c| return c == '_' || c == '-' || c == '.' || c == '@')
+			.next()
+			.unwrap_or_default()
+			.to_lowercase();
+
+		return match lang.as_str() {
+			"en" => Some(Self::En),
+			"de" => Some(Self::De),
+			_ => None,
+		};
+	}
+}
+
+/// The language in use, set once at startup
+static LANG: OnceCell<Lang> = OnceCell::new();
+
+/// Set the language to use for all messages
+/// Only the first call has a effect
+pub fn set_lang(lang: Lang) {
+	if LANG.set(lang).is_err() {
+		debug!("Language was already set, ignoring {lang:?}");
+	}
+}
+
+/// Get the language in use, defaults to [`Lang::En`] if not set
+pub fn lang() -> Lang {
+	return LANG.get().copied().unwrap_or_default();
+}
+
+/// All translatable user-facing messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+	/// Warning that only recovery will be done, because no URLs were given
+	OnlyRecovery,
+	/// Info that the edit loop is skipped, because there is no media
+	NoMediaToEdit,
+	/// A media did not have a filename, args: media id
+	MediaNoFilename,
+	/// A media did not exist anymore, args: media id
+	MediaNotExisting,
+	/// Ask whether to edit a media, args: media title, optional comment
+	EditMediaAsk,
+	/// Info that the video editor will be used
+	FiletypeVideo,
+	/// Info that the audio editor will be used
+	FiletypeAudio,
+	/// Ask which editor to use, because the filetype is unknown
+	UnknownEditorAsk,
+	/// Help for the "EditMediaAsk" choices
+	EditMediaHelp,
+	/// Info that going back is not possible anymore
+	CannotGoBack,
+	/// Info that the edit loop continues where a earlier run stopped, args: media position, media count
+	ResumeEditing,
+	/// Re-applying a thumbnail failed, args: media path, error
+	ReThumbnailFailed,
+	/// Title of the edit TUI, args: selected position, media count
+	TuiTitle,
+	/// Key bindings of the edit TUI
+	TuiKeys,
+	/// Status of the edit TUI after a media has been edited, args: media title
+	TuiEdited,
+	/// Status of the edit TUI when the editor cannot be chosen automatically
+	TuiUnknownFiletype,
+	/// Warning that the edit TUI could not be started, args: error
+	TuiFallback,
+	/// Info that there are no files to move or tag
+	NoFilesToFinish,
+	/// Ask what to do with the finished media
+	FinishMediaAsk,
+	/// Warning that there are still editable files that were not moved
+	EditableFilesRemaining,
+	/// A file already exists in the output directory, args: filename, new summary, existing summary
+	CollisionExists,
+	/// Ask what to do for a collision
+	CollisionAsk,
+	/// The input was empty, but has no default
+	InvalidInputEmpty,
+	/// The input was not a possible value, args: input
+	InvalidInput,
+}
+
+impl Msg {
+	/// All messages, used to check that all translations are consistent
+	#[cfg(test)]
+	const ALL: &'static [Msg] = &[
+		Msg::OnlyRecovery,
+		Msg::NoMediaToEdit,
+		Msg::MediaNoFilename,
+		Msg::MediaNotExisting,
+		Msg::EditMediaAsk,
+		Msg::FiletypeVideo,
+		Msg::FiletypeAudio,
+		Msg::UnknownEditorAsk,
+		Msg::EditMediaHelp,
+		Msg::CannotGoBack,
+		Msg::ResumeEditing,
+		Msg::ReThumbnailFailed,
+		Msg::TuiTitle,
+		Msg::TuiKeys,
+		Msg::TuiEdited,
+		Msg::TuiUnknownFiletype,
+		Msg::TuiFallback,
+		Msg::NoFilesToFinish,
+		Msg::FinishMediaAsk,
+		Msg::EditableFilesRemaining,
+		Msg::CollisionExists,
+		Msg::CollisionAsk,
+		Msg::InvalidInputEmpty,
+		Msg::InvalidInput,
+	];
+
+	/// Get the english message, which is the fallback for all other languages
+	fn en(self) -> &'static str {
+		return match self {
+			Msg::OnlyRecovery => {
+				"No URLs were provided, only checking recovery! To disable allowing 0 URLs, use \"--no-check-recovery\""
+			},
+			Msg::NoMediaToEdit => "Skipping asking for media, because there is no media to edit",
+			Msg::MediaNoFilename => "\"{0}\" did not have a filename, which is required beyond this point, skipping",
+			Msg::MediaNotExisting => {
+				"\"{0}\" did not exist anymore (moved via another invocation or editor rename?), skipping edit"
+			},
+			Msg::EditMediaAsk => "Edit Media \"{0}\"?{1}",
+			Msg::FiletypeVideo => "Found filetype to be of video",
+			Msg::FiletypeAudio => "Found filetype to be of audio",
+			Msg::UnknownEditorAsk => {
+				"Could not find suitable editor for extension, [a]udio editor, [v]ideo editor, a[b]ort, [n]ext."
+			},
+			Msg::EditMediaHelp => {
+				"Help:\n\
+				[h] print help (this)\n\
+				[n] skip element and move onto the next one\n\
+				[y] edit element, automatically choose editor\n\
+				[a] edit element with audio editor\n\
+				[v] edit element with video editor\n\
+				[p] start the element with a media player\n\
+				[b] go back a element"
+			},
+			Msg::CannotGoBack => "Cannot go back further",
+			Msg::ResumeEditing => "Resuming editing at media {0} of {1}",
+			Msg::ReThumbnailFailed => "Re-applying thumbnail for \"{0}\" failed: {1}",
+			Msg::TuiTitle => "Edit Media ({0} of {1})",
+			Msg::TuiKeys => {
+				"[y/Enter] edit  [a] audio editor  [v] video editor  [p] play  [n] next  [b] back  [↑/↓] select  [q] done"
+			},
+			Msg::TuiEdited => "Edited \"{0}\"",
+			Msg::TuiUnknownFiletype => "Could not find suitable editor for extension, use [a] or [v] to choose the editor",
+			Msg::TuiFallback => "Could not start the terminal UI, using the text prompts instead: {0}",
+			Msg::NoFilesToFinish => "No files to move or tag",
+			Msg::FinishMediaAsk => {
+				"[m]ove Media to Output Directory, [o]rganize Media into Artist/Album Directories, Open [p]icard or go [b]ack to editing?"
+			},
+			Msg::EditableFilesRemaining => {
+				"Found Editable file(s) that have not been moved.\nConsider running recovery mode if no other ytdlr is running (with 0 URLs)"
+			},
+			Msg::CollisionExists => "File \"{0}\" already exists in the output directory\n  new:      {1}\n  existing: {2}",
+			Msg::CollisionAsk => "[o]verwrite existing, append a [n]umber, [s]kip moving or show [d]iff?",
+			Msg::InvalidInputEmpty => "... Invalid Input: (Empty)",
+			Msg::InvalidInput => "... Invalid Input: \"{0}\"",
+		};
+	}
+
+	/// Get the german message, if translated
+	fn de(self) -> Option<&'static str> {
+		return Some(match self {
+			Msg::OnlyRecovery => {
+				"Keine URLs angegeben, es wird nur die Wiederherstellung geprüft! Um 0 URLs nicht zu erlauben, nutze \"--no-check-recovery\""
+			},
+			Msg::NoMediaToEdit => "Bearbeiten wird übersprungen, weil es keine Medien zum Bearbeiten gibt",
+			Msg::MediaNoFilename => "\"{0}\" hat keinen Dateinamen, welcher ab hier benötigt wird, überspringe",
+			Msg::MediaNotExisting => {
+				"\"{0}\" existiert nicht mehr (von einem anderen Aufruf verschoben oder im Editor umbenannt?), überspringe Bearbeiten"
+			},
+			Msg::EditMediaAsk => "Medium \"{0}\" bearbeiten?{1}",
+			Msg::FiletypeVideo => "Dateityp ist Video",
+			Msg::FiletypeAudio => "Dateityp ist Audio",
+			Msg::UnknownEditorAsk => {
+				"Kein passender Editor für die Dateiendung gefunden, [a]udio-Editor, [v]ideo-Editor, a[b]brechen, [n]ächstes."
+			},
+			Msg::EditMediaHelp => {
+				"Hilfe:\n\
+				[h] Hilfe anzeigen (diese)\n\
+				[n] Element überspringen und zum nächsten gehen\n\
+				[y] Element bearbeiten, Editor automatisch wählen\n\
+				[a] Element mit dem Audio-Editor bearbeiten\n\
+				[v] Element mit dem Video-Editor bearbeiten\n\
+				[p] Element mit einem Media-Player abspielen\n\
+				[b] ein Element zurück gehen"
+			},
+			Msg::CannotGoBack => "Weiter zurück ist nicht möglich",
+			Msg::ResumeEditing => "Bearbeitung wird bei Medium {0} von {1} fortgesetzt",
+			Msg::ReThumbnailFailed => "Erneutes Anwenden des Thumbnails für \"{0}\" fehlgeschlagen: {1}",
+			Msg::TuiTitle => "Medien bearbeiten ({0} von {1})",
+			Msg::TuiKeys => {
+				"[y/Enter] bearbeiten  [a] Audio-Editor  [v] Video-Editor  [p] abspielen  [n] nächstes  [b] zurück  [↑/↓] auswählen  [q] fertig"
+			},
+			Msg::TuiEdited => "\"{0}\" bearbeitet",
+			Msg::TuiUnknownFiletype => {
+				"Kein passender Editor für die Dateiendung gefunden, [a] oder [v] wählt den Editor"
+			},
+			Msg::TuiFallback => "Terminal-UI konnte nicht gestartet werden, nutze stattdessen die Text-Abfragen: {0}",
+			Msg::NoFilesToFinish => "Keine Dateien zum Verschieben oder Taggen",
+			Msg::FinishMediaAsk => {
+				"Medien in das Ausgabeverzeichnis [m] verschieben, in Künstler/Album-Verzeichnisse [o]rdnen, [p]icard öffnen oder zum Bearbeiten zurück [b]?"
+			},
+			Msg::EditableFilesRemaining => {
+				"Bearbeitbare Datei(en) gefunden, die nicht verschoben wurden.\nWiederherstellung ausführen, falls kein anderes ytdlr läuft (mit 0 URLs)"
+			},
+			Msg::CollisionExists => {
+				"Datei \"{0}\" existiert bereits im Ausgabeverzeichnis\n  neu:       {1}\n  vorhanden: {2}"
+			},
+			Msg::CollisionAsk => {
+				"Vorhandene [o] überschreiben, [n]ummer anhängen, Verschieben über[s]pringen oder Unterschie[d] anzeigen?"
+			},
+			Msg::InvalidInputEmpty => "... Ungültige Eingabe: (Leer)",
+			Msg::InvalidInput => "... Ungültige Eingabe: \"{0}\"",
+		});
+	}
+
+	/// Get the message in the given language, falling back to english if not translated
+	pub fn text(self, lang: Lang) -> &'static str {
+		return match lang {
+			Lang::En => None,
+			Lang::De => self.de(),
+		}
+		.unwrap_or_else(|| return self.en());
+	}
+}
+
+/// Get the message in the current language
+pub fn tr(msg: Msg) -> &'static str {
+	return msg.text(lang());
+}
+
+/// Get the message in the current language, with the numbered placeholders replaced by `args`
+pub fn trf(msg: Msg, args: &[&dyn Display]) -> String {
+	return format_placeholders(tr(msg), args);
+}
+
+/// Replace all numbered placeholders like `{0}` in `template` with the matching `args`
+/// Placeholders without a matching argument are kept as-is
+fn format_placeholders(template: &str, args: &[&dyn Display]) -> String {
+	let mut out = String::with_capacity(template.len());
+	let mut rest = template;
+
+	while let Some(start) = rest.find('{') {
+		out.push_str(&rest[..start]);
+		rest = &rest[start..];
+
+		let arg = rest
+			.find('}')
+			.and_then(|end| return rest[1..end].parse::<usize>().ok().map(|index| return (index, end)))
+			.and_then(|(index, end)| return args.get(index).map(|arg| return (arg, end)));
+
+		if let Some((arg, end)) = arg {
+			// writing to a string cannot fail
+			let _ = write!(out, "{arg}");
+			rest = &rest[end + 1..];
+		} else {
+			out.push('{');
+			rest = &rest[1..];
+		}
+	}
+
+	out.push_str(rest);
+
+	return out;
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	mod lang {
+		use super::*;
+
+		#[test]
+		fn test_from_locale() {
+			assert_eq!(Some(Lang::De), Lang::from_locale("de_DE.UTF-8"));
+			assert_eq!(Some(Lang::De), Lang::from_locale("de_AT@euro"));
+			assert_eq!(Some(Lang::De), Lang::from_locale("de"));
+			assert_eq!(Some(Lang::En), Lang::from_locale("en_US.UTF-8"));
+			assert_eq!(Some(Lang::En), Lang::from_locale("EN-gb"));
+			assert_eq!(None, Lang::from_locale("C"));
+			assert_eq!(None, Lang::from_locale("POSIX"));
+			assert_eq!(None, Lang::from_locale("fr_FR.UTF-8"));
+			assert_eq!(None, Lang::from_locale(""));
+		}
+	}
+
+	mod msg {
+		use super::*;
+
+		/// Get all placeholders of a message, sorted
+		fn placeholders(msg: &str) -> Vec<String> {
+			let mut found: Vec<String> = msg
+				.match_indices('{')
+				.filter_map(|(start, _)| {
+					let end = msg[start..].find('}')?;
+					return Some(msg[start..=start + end].to_owned());
+				})
+				.collect();
+			found.sort();
+
+			return found;
+		}
+
+		#[test]
+		fn test_translations_have_same_placeholders() {
+			for msg in Msg::ALL {
+				for lang in <Lang as clap::ValueEnum>::value_variants() {
+					assert_eq!(
+						placeholders(msg.en()),
+						placeholders(msg.text(*lang)),
+						"Expected {msg:?} in {lang:?} to have the same placeholders as english"
+					);
+				}
+			}
+		}
+
+		#[test]
+		fn test_text_fallback() {
+			assert_eq!(Msg::CannotGoBack.en(), Msg::CannotGoBack.text(Lang::En));
+			assert_eq!("Weiter zurück ist nicht möglich", Msg::CannotGoBack.text(Lang::De));
+		}
+	}
+
+	mod format_placeholders {
+		use super::*;
+
+		#[test]
+		fn test_basic() {
+			assert_eq!("a 1 b two", format_placeholders("a {0} b {1}", &[&1, &"two"]));
+			assert_eq!("two 1", format_placeholders("{1} {0}", &[&1, &"two"]));
+			assert_eq!("no placeholders", format_placeholders("no placeholders", &[&1]));
+		}
+
+		#[test]
+		fn test_no_recursive_replace() {
+			assert_eq!("{1} x", format_placeholders("{0} {1}", &[&"{1}", &"x"]));
+		}
+
+		#[test]
+		fn test_missing_or_invalid() {
+			assert_eq!("{0} {a} {", format_placeholders("{0} {a} {", &[]));
+			assert_eq!("x {2}", format_placeholders("{0} {2}", &[&"x"]));
+		}
+	}
+}
@@ -0,0 +1,251 @@
+//! Module for notifications once a media is finished (see "--notify-command" and "--notify-webhook")
+//! Every notification is a JSON object like `{"event":"downloaded","id":"...","provider":"...","title":"...","path":"..."}`
+//! where "event" is "downloaded" once youtube-dl finished a media and "moved" once it has been moved to its final path
+
+use std::{
+	collections::HashMap,
+	io::Write,
+	path::{
+		Path,
+		PathBuf,
+	},
+	process::Stdio,
+	sync::Mutex,
+};
+
+use libytdlr::{
+	data::cache::media_info::MediaInfo,
+	error::IOErrorToError,
+	main::download::DownloadProgress,
+	spawn::curl::curl_post_json,
+};
+use serde_json::{
+	json,
+	Value,
+};
+
+/// Where notifications are sent to, see [`open`]
+#[derive(Debug)]
+struct Notifier {
+	/// The command to run for every notification, run with "sh -c"
+	command:       Option<String>,
+	/// The URL to POST every notification to
+	webhook:       Option<String>,
+	/// The download path, because [`DownloadProgress::SingleFinished`] only contains the file name
+	download_path: PathBuf,
+	/// The titles of the started media, by id, because [`DownloadProgress::SingleFinished`] does not contain the title
+	titles:        HashMap<String, String>,
+}
+
+/// The currently open notifier, see [`open`]
+static NOTIFIER: Mutex<Option<Notifier>> = Mutex::new(None);
+
+/// Send notifications to `command` and / or `webhook` until [`close`] is called
+/// Does nothing if both are [`None`]
+pub fn open(command: Option<String>, webhook: Option<String>, download_path: &Path) {
+	if command.is_none() && webhook.is_none() {
+		return;
+	}
+
+	*NOTIFIER.lock().expect("Expected to acquire the notifier lock") = Some(Notifier {
+		command,
+		webhook,
+		download_path: download_path.to_path_buf(),
+		titles: HashMap::new(),
+	});
+}
+
+/// Close the notifier, following events do not send notifications anymore
+pub fn close() {
+	NOTIFIER.lock().expect("Expected to acquire the notifier lock").take();
+}
+
+/// Handle a download progress event, sending a "downloaded" notification for every [`DownloadProgress::SingleFinished`]
+pub fn on_progress(dpg: &DownloadProgress) {
+	let mut guard = NOTIFIER.lock().expect("Expected to acquire the notifier lock");
+	let Some(notifier) = guard.as_mut() else {
+		return;
+	};
+
+	match dpg {
+		DownloadProgress::SingleStarting(id, title) => {
+			notifier.titles.insert(id.clone(), title.clone());
+		},
+		DownloadProgress::SingleFinished(id, filename) => {
+			// the filename contains the provider, and is the fallback for the title
+			let from_filename = filename
+				.as_ref()
+				.and_then(|v| return v.to_str())
+				.and_then(|v| return MediaInfo::try_from_filename(&v));
+			let title = notifier
+				.titles
+				.remove(id)
+				.or_else(|| return from_filename.as_ref().and_then(|v| return v.title.clone()));
+
+			let payload = json!({
+				"event": "downloaded",
+				"id": id,
+				"provider": from_filename.as_ref().map(|v| return v.provider.as_str()),
+				"title": title,
+				"path": filename.as_ref().map(|v| return notifier.download_path.join(v)),
+			});
+
+			notifier.send(&payload);
+		},
+		DownloadProgress::UrlFinished(_) => {
+			// media that were started, but not finished (like skipped ones) are not needed anymore
+			notifier.titles.clear();
+		},
+		_ => (),
+	}
+}
+
+/// Send a "moved" notification, for `media` having been moved to its final path `to`
+pub fn notify_moved(media: &MediaInfo, to: &Path) {
+	let guard = NOTIFIER.lock().expect("Expected to acquire the notifier lock");
+	let Some(notifier) = guard.as_ref() else {
+		return;
+	};
+
+	notifier.send(&json!({
+		"event": "moved",
+		"id": media.id,
+		"provider": media.provider.as_str(),
+		"title": media.title,
+		"path": to,
+	}));
+}
+
+impl Notifier {
+	/// Send `payload` to the command and webhook
+	/// Errors are only logged, because a failed notification should not fail the download
+	fn send(&self, payload: &Value) {
+		let body = payload.to_string();
+
+		if let Some(command) = self.command.as_deref() {
+			if let Err(err) = run_command(command, payload, &body) {
+				warn!("Running the notify command failed: {}", err);
+			}
+		}
+
+		if let Some(webhook) = self.webhook.as_deref() {
+			if let Err(err) = curl_post_json(webhook, &body) {
+				warn!("Sending the notify webhook failed: {}", err);
+			}
+		}
+	}
+}
+
+/// Get the environment variables for the notify command from `payload`, like "YTDLR_ID" for "id"
+/// Values which are not set are not included
+fn command_env(payload: &Value) -> Vec<(String, String)> {
+	let Value::Object(map) = payload else {
+		return Vec::new();
+	};
+
+	return map
+		.iter()
+		.filter_map(|(key, value)| {
+			return value
+				.as_str()
+				.map(|v| return (format!("YTDLR_{}", key.to_uppercase()), v.to_owned()));
+		})
+		.collect();
+}
+
+/// Run `command` with "sh -c", with `payload` as environment variables (see [`command_env`]) and `body` on stdin
+fn run_command(command: &str, payload: &Value, body: &str) -> Result<(), crate::Error> {
+	let mut child = std::process::Command::new("sh")
+		.arg("-c")
+		.arg(command)
+		.envs(command_env(payload))
+		.stdin(Stdio::piped())
+		.stdout(Stdio::null())
+		.stderr(Stdio::inherit())
+		.spawn()
+		.attach_location_err("notify command spawn")?;
+
+	// take stdin, so that it is closed once written
+	if let Some(mut stdin) = child.stdin.take() {
+		// the command may not read stdin, which is not a error
+		let _ = stdin.write_all(body.as_bytes());
+	}
+
+	let status = child.wait().attach_location_err("notify command wait")?;
+
+	if !status.success() {
+		return Err(crate::Error::other(format!(
+			"Notify command did not successfully exit! Exit Status: {status}"
+		)));
+	}
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_command_env() {
+		let payload = json!({
+			"event": "moved",
+			"id": "id1",
+			"provider": "youtube",
+			"title": null,
+			"path": "/out/Title.mkv",
+		});
+
+		let mut env = command_env(&payload);
+		env.sort();
+
+		assert_eq!(
+			vec![
+				("YTDLR_EVENT".to_owned(), "moved".to_owned()),
+				("YTDLR_ID".to_owned(), "id1".to_owned()),
+				("YTDLR_PATH".to_owned(), "/out/Title.mkv".to_owned()),
+				("YTDLR_PROVIDER".to_owned(), "youtube".to_owned()),
+			],
+			env
+		);
+	}
+
+	#[test]
+	fn test_notify_command() {
+		let tmp = tempfile::Builder::new()
+			.prefix("ytdlr-test-notification-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let out = tmp.path().join("out.txt");
+
+		// not sent, because the notifier is not open yet
+		notify_moved(&MediaInfo::new("id0", "youtube"), Path::new("/out/a.mkv"));
+
+		open(
+			Some(format!(
+				"echo \"$YTDLR_EVENT $YTDLR_ID $YTDLR_PROVIDER $YTDLR_TITLE $YTDLR_PATH\" >> '{}'",
+				out.display()
+			)),
+			None,
+			Path::new("/tmp/dl"),
+		);
+		on_progress(&DownloadProgress::SingleStarting(
+			"id1".to_owned(),
+			"Some Title".to_owned(),
+		));
+		on_progress(&DownloadProgress::SingleFinished(
+			"id1".to_owned(),
+			Some(PathBuf::from("'youtube'-'id1'-Some Title.mkv")),
+		));
+		notify_moved(
+			&MediaInfo::new("id1", "youtube").with_title("Some Title"),
+			Path::new("/out/Some Title.mkv"),
+		);
+		close();
+
+		assert_eq!(
+			"downloaded id1 youtube Some Title /tmp/dl/'youtube'-'id1'-Some Title.mkv\nmoved id1 youtube Some Title /out/Some Title.mkv\n",
+			std::fs::read_to_string(&out).expect("Expected the notify command to have written")
+		);
+	}
+}
@@ -1,7 +1,10 @@
 //! Module for State Struct for all commands
 
 use std::{
-	ffi::OsString,
+	ffi::{
+		OsStr,
+		OsString,
+	},
 	path::PathBuf,
 };
 
@@ -9,16 +12,24 @@ use libytdlr::{
 	chrono,
 	diesel,
 	main::download::{
+		CookieSource,
 		DownloadOptions,
 		FormatArgument,
+		FormatConstraints,
+		PlaylistOrder,
 		MINIMAL_YTDL_VERSION,
 	},
-	spawn::ytdl::ytdl_parse_version_naivedate,
+	spawn::ytdl::{
+		ytdl_parse_version_naivedate,
+		YtdlCapabilities,
+	},
 };
 
 use crate::clap_conf::{
 	ArchiveMode,
 	CommandDownload,
+	DownloadProfile,
+	UrlOverrides,
 };
 
 /// Struct to keep configuration data for the [`DownloadOptions`] trait
@@ -41,26 +52,86 @@ pub struct DownloadState<'a> {
 	archive_mode: ArchiveMode,
 
 	/// Set the current URL to be downloaded
-	current_url: String,
+	current_url:     String,
 	/// Set which subtitle languages to download
-	sub_langs:   Option<&'a String>,
-
+	sub_langs:       Option<&'a String>,
+	/// Write subtitles as separate files instead of embedding them
+	subs_external:   bool,
+	/// Write the ".info.json" of every media
+	write_info_json: bool,
+
+	/// Stores the youtube-dl binary to spawn
+	ytdl_bin:          OsString,
 	/// Stores the youtube-dl version in use
-	ytdl_version: libytdlr::chrono::NaiveDate,
+	ytdl_version:      libytdlr::chrono::NaiveDate,
+	/// Stores the options supported by the youtube-dl in use, if they could be determined
+	ytdl_capabilities: Option<YtdlCapabilities>,
 
 	/// Set which audio container should be preferred
 	audio_format: &'a str,
 	/// Set which video container should be preferred
 	video_format: &'a str,
+
+	/// Error on unparsed youtube-dl lines
+	strict_parse: bool,
+
+	/// Set the maximum bytes that may be downloaded for the current URL
+	max_download_bytes: Option<u64>,
+
+	/// Set the time without output after which a media is considered stalled
+	stall_timeout: Option<std::time::Duration>,
+	/// Stop youtube-dl once a media is stalled
+	stall_kill:    bool,
+
+	/// The order playlist entries are downloaded in
+	playlist_order: PlaylistOrder,
+
+	/// Embed the thumbnail into the media
+	embed_thumbnail:      bool,
+	/// Merge and remux videos
+	remux_video:          bool,
+	/// Remux videos after the download instead of with youtube-dl, see "--remux-targets"
+	remux_after_download: bool,
+
+	/// Where youtube-dl should load cookies from
+	cookies: Option<CookieSource<'a>>,
+
+	/// Which entries of a playlist to download
+	playlist_items: Option<&'a str>,
+	/// The maximal count of media to download per URL
+	max_downloads:  Option<usize>,
+	/// The maximal download rate in bytes per second
+	limit_rate:     Option<u64>,
+
+	/// The constraints for the format to download
+	format_constraints:   FormatConstraints<'a>,
+	/// How many fragments are downloaded at the same time
+	concurrent_fragments: Option<usize>,
+	/// How often media that failed with a transient error are retried
+	retries:              usize,
+	/// Whether livestreams are downloaded from their start
+	live:                 bool,
+
+	/// The overrides for the current URL, see [`UrlOverrides`]
+	url_overrides: UrlOverrides,
+	/// The profile for the provider of the current URL, see [`DownloadProfile`]
+	profile:       DownloadProfile,
 }
 
 impl<'a> DownloadState<'a> {
 	/// Create a new instance of [`DownloadState`] with the required options
-	pub fn new(sub_args: &'a CommandDownload, download_path: PathBuf, ytdl_version: &str) -> Self {
+	pub fn new(
+		sub_args: &'a CommandDownload,
+		download_path: PathBuf,
+		ytdl_bin: OsString,
+		ytdl_version: &str,
+		ytdl_capabilities: Option<YtdlCapabilities>,
+	) -> Self {
 		// process extra arguments into separated arguments of key and value (split once)
 		let extra_cmd_args = sub_args
 			.extra_ytdl_args
 			.iter()
+			.filter(|v| return is_supported_extra_arg(v, ytdl_capabilities.as_ref()))
 			.flat_map(|v| {
 				if let Some((split1, split2)) = v.split_once(' ') {
 					return Vec::from([OsString::from(split1), OsString::from(split2)]);
@@ -82,14 +153,40 @@ impl<'a> DownloadState<'a> {
 			save_command_log: sub_args.save_youtubedl_log,
 			download_path,
 			sub_langs: sub_args.sub_langs.as_ref(),
+			subs_external: sub_args.subs_external,
+			write_info_json: sub_args.write_info_json,
 
 			archive_mode: sub_args.archive_mode,
 
 			current_url: String::default(),
+			ytdl_bin,
 			ytdl_version,
-
-			audio_format: &sub_args.audio_format,
-			video_format: &sub_args.video_format,
+			ytdl_capabilities,
+
+			audio_format: sub_args.audio_format.as_str(),
+			video_format: sub_args.video_format.as_str(),
+
+			strict_parse: sub_args.strict_parse,
+
+			max_download_bytes: None,
+
+			stall_timeout: sub_args.stall_timeout.map(std::time::Duration::from_secs),
+			stall_kill: sub_args.stall_kill,
+
+			playlist_order: sub_args.playlist_order(),
+			embed_thumbnail: !sub_args.no_embed_thumbnail,
+			remux_video: !sub_args.no_remux,
+			remux_after_download: !sub_args.remux_targets.is_empty(),
+			cookies: sub_args.cookie_source(),
+			playlist_items: sub_args.playlist_items.as_deref(),
+			max_downloads: sub_args.max_downloads,
+			limit_rate: sub_args.limit_rate,
+			format_constraints: sub_args.format_constraints(),
+			concurrent_fragments: sub_args.concurrent_fragments,
+			retries: sub_args.retries,
+			live: sub_args.live,
+			url_overrides: UrlOverrides::default(),
+			profile: DownloadProfile::default(),
 		};
 	}
 
@@ -98,19 +195,45 @@ impl<'a> DownloadState<'a> {
 		// replace the already allocated string with the "new_url" without creating a new string
 		self.current_url.replace_range(.., new_url.as_ref());
 	}
+
+	/// Set the overrides for the current url, resetting to no overrides if [`None`]
+	pub fn set_url_overrides(&mut self, overrides: Option<&UrlOverrides>) {
+		self.url_overrides = overrides.cloned().unwrap_or_default();
+	}
+
+	/// Set the profile for the current url, resetting to no profile if [`None`]
+	pub fn set_profile(&mut self, profile: Option<&DownloadProfile>) {
+		self.profile = profile.cloned().unwrap_or_default();
+	}
+
+	/// Set the maximum bytes that may be downloaded for the current url
+	pub fn set_max_download_bytes(&mut self, max_download_bytes: Option<u64>) {
+		self.max_download_bytes = max_download_bytes;
+	}
 }
 
 impl DownloadOptions for DownloadState<'_> {
 	fn audio_only(&self) -> bool {
-		return self.audio_only_enable;
+		return self
+			.url_overrides
+			.audio_only
+			.or(self.profile.audio_only)
+			.unwrap_or(self.audio_only_enable);
 	}
 
 	fn extra_ytdl_arguments(&self) -> Vec<&std::ffi::OsStr> {
-		return self
+		let mut args: Vec<&std::ffi::OsStr> = self
 			.extra_command_arguments
 			.iter()
 			.map(|v| return v.as_os_str())
 			.collect();
+
+		// "--continue" is the default of yt-dlp, but it is explicitly set to not be affected by config files
+		if self.url_overrides.resume {
+			args.push(std::ffi::OsStr::new("--continue"));
+		}
+
+		return args;
 	}
 
 	fn download_path(&self) -> &std::path::Path {
@@ -121,51 +244,11 @@ impl DownloadOptions for DownloadState<'_> {
 		&'a self,
 		connection: &'a mut diesel::SqliteConnection,
 	) -> Option<Box<dyn Iterator<Item = String> + 'a>> {
-		use diesel::prelude::*;
-		use libytdlr::data::{
-			sql_models::Media,
-			sql_schema::media_archive,
-		};
-
-		if self.archive_mode == ArchiveMode::None {
-			debug!("archive-mode is None, not outputting any ytdl archive");
-
-			return Some(Box::new([].into_iter()));
-		}
-
-		// function to use to format all output to a youtube-dl archive, consistent across all options
-		let fmtfn = |v: Result<libytdlr::data::sql_models::Media, diesel::result::Error>| {
-			let v = v.ok()?;
-			return Some(format!("{} {}\n", v.provider, v.media_id));
-		};
-
-		if self.archive_mode == ArchiveMode::All || self.archive_mode == ArchiveMode::Default {
-			debug!("Dumping full sqlite archive as youtube-dl archive");
-
-			let lines_iter = media_archive::dsl::media_archive
-				.order(media_archive::_id.asc())
-				// the following is some black-magic that rust-analyzer does not understand (no useful intellisense available)
-				.load_iter::<Media, diesel::connection::DefaultLoadingMode>(connection)
-				.ok()?
-				// the following has some explicit type-annotation for the argument, because otherwise rust-analyzer does not provide any types
-				.filter_map(fmtfn);
-
-			return Some(Box::new(lines_iter));
-		}
-
-		// ArchiveMode::ByDate1000
-
-		let lines_iter = media_archive::dsl::media_archive
-			// order by newest to oldest
-			.order(media_archive::inserted_at.desc())
-			// limit this case to the newest 1000 media
-			.limit(1000)
-			.load_iter::<Media, diesel::connection::DefaultLoadingMode>(connection)
-			.ok()?
-			// the following has some explicit type-annotation for the argument, because otherwise rust-analyzer does not provide any types
-			.filter_map(fmtfn);
+		return gen_archive_lines(connection, self.archive_mode);
+	}
 
-		return Some(Box::new(lines_iter));
+	fn gen_archive_count(&self, connection: &mut diesel::SqliteConnection) -> Option<usize> {
+		return gen_archive_lines_count(connection, self.archive_mode);
 	}
 
 	fn get_url(&self) -> &str {
@@ -187,25 +270,230 @@ impl DownloadOptions for DownloadState<'_> {
 	}
 
 	fn sub_langs(&self) -> Option<&str> {
-		return self.sub_langs.map(String::as_str);
+		return self
+			.url_overrides
+			.sub_langs
+			.as_deref()
+			.or(self.profile.sub_langs.as_deref())
+			.or(self.sub_langs.map(String::as_str));
+	}
+
+	fn subs_external(&self) -> bool {
+		return self.subs_external;
+	}
+
+	fn write_info_json(&self) -> bool {
+		return self.write_info_json;
 	}
 
 	fn ytdl_version(&self) -> chrono::NaiveDate {
 		return self.ytdl_version;
 	}
 
+	fn ytdl_bin(&self) -> &OsStr {
+		return &self.ytdl_bin;
+	}
+
 	fn get_audio_format(&self) -> FormatArgument {
-		return self.audio_format;
+		return self
+			.profile
+			.audio_format
+			.map_or(self.audio_format, |v| return v.as_str());
 	}
 
 	fn get_video_format(&self) -> FormatArgument {
-		return self.video_format;
+		return self
+			.profile
+			.video_format
+			.map_or(self.video_format, |v| return v.as_str());
+	}
+
+	fn strict_parse(&self) -> bool {
+		return self.strict_parse;
+	}
+
+	fn max_download_bytes(&self) -> Option<u64> {
+		return self.max_download_bytes;
+	}
+
+	fn stall_timeout(&self) -> Option<std::time::Duration> {
+		return self.stall_timeout;
+	}
+
+	fn stall_kill(&self) -> bool {
+		return self.stall_kill;
+	}
+
+	fn playlist_order(&self) -> PlaylistOrder {
+		return self.playlist_order;
+	}
+
+	fn embed_thumbnail(&self) -> bool {
+		return self.embed_thumbnail;
+	}
+
+	fn remux_video(&self) -> bool {
+		return self.remux_video;
+	}
+
+	fn remux_after_download(&self) -> bool {
+		return self.remux_after_download;
+	}
+
+	fn ytdl_capabilities(&self) -> Option<&YtdlCapabilities> {
+		return self.ytdl_capabilities.as_ref();
 	}
+
+	fn cookies(&self) -> Option<CookieSource<'_>> {
+		return self.cookies;
+	}
+
+	fn playlist_items(&self) -> Option<&str> {
+		return self.playlist_items;
+	}
+
+	fn max_downloads(&self) -> Option<usize> {
+		return self.max_downloads;
+	}
+
+	fn limit_rate(&self) -> Option<u64> {
+		return self.limit_rate;
+	}
+
+	fn format_constraints(&self) -> FormatConstraints<'_> {
+		return self.format_constraints;
+	}
+
+	fn concurrent_fragments(&self) -> Option<usize> {
+		return self.concurrent_fragments;
+	}
+
+	fn retries(&self) -> usize {
+		return self.retries;
+	}
+
+	fn live(&self) -> bool {
+		return self.live;
+	}
+}
+
+/// Generate the youtube-dl archive lines (like "youtube ID\n") from the SQLite archive for `archive_mode`
+/// Blacklisted media is included in every archive-mode
+/// Used for [`DownloadOptions::gen_archive`] and "archive gen-ytdl-archive"
+pub fn gen_archive_lines(
+	connection: &mut diesel::SqliteConnection,
+	archive_mode: ArchiveMode,
+) -> Option<Box<dyn Iterator<Item = String> + '_>> {
+	use diesel::prelude::*;
+	use libytdlr::data::{
+		sql_models::Media,
+		sql_schema::media_archive,
+	};
+
+	// blacklisted media is added in every archive-mode, so that youtube-dl always skips it
+	// the lines are loaded before the archive, because only one iterator can borrow the connection at a time
+	let blacklist_lines: Vec<String> = libytdlr::main::archive::remove::get_blacklist(connection)
+		.ok()?
+		.into_iter()
+		.map(|v| return format!("{} {}\n", v.provider, v.media_id))
+		.collect();
+
+	if archive_mode == ArchiveMode::None {
+		debug!("archive-mode is None, only outputting the blacklist as ytdl archive");
+
+		return Some(Box::new(blacklist_lines.into_iter()));
+	}
+
+	// function to use to format all output to a youtube-dl archive, consistent across all options
+	let fmtfn = |v: Result<libytdlr::data::sql_models::Media, diesel::result::Error>| {
+		let v = v.ok()?;
+		return Some(format!("{} {}\n", v.provider, v.media_id));
+	};
+
+	if archive_mode == ArchiveMode::All || archive_mode == ArchiveMode::Default {
+		debug!("Dumping full sqlite archive as youtube-dl archive");
+
+		let lines_iter = media_archive::dsl::media_archive
+			.order(media_archive::_id.asc())
+			// the following is some black-magic that rust-analyzer does not understand (no useful intellisense available)
+			.load_iter::<Media, diesel::connection::DefaultLoadingMode>(connection)
+			.ok()?
+			// the following has some explicit type-annotation for the argument, because otherwise rust-analyzer does not provide any types
+			.filter_map(fmtfn);
+
+		return Some(Box::new(blacklist_lines.into_iter().chain(lines_iter)));
+	}
+
+	// ArchiveMode::ByDate1000
+
+	let lines_iter = media_archive::dsl::media_archive
+		// order by newest to oldest
+		.order(media_archive::inserted_at.desc())
+		// limit this case to the newest 1000 media
+		.limit(1000)
+		.load_iter::<Media, diesel::connection::DefaultLoadingMode>(connection)
+		.ok()?
+		// the following has some explicit type-annotation for the argument, because otherwise rust-analyzer does not provide any types
+		.filter_map(fmtfn);
+
+	return Some(Box::new(blacklist_lines.into_iter().chain(lines_iter)));
+}
+
+/// Get the count of lines [`gen_archive_lines`] would generate for `archive_mode`
+pub fn gen_archive_lines_count(connection: &mut diesel::SqliteConnection, archive_mode: ArchiveMode) -> Option<usize> {
+	use diesel::prelude::*;
+	use libytdlr::data::sql_schema::{
+		blacklist,
+		media_archive,
+	};
+
+	// the blacklist is added in every archive-mode, see [`gen_archive_lines`]
+	let blacklist_count: i64 = blacklist::dsl::blacklist.count().get_result(connection).ok()?;
+	let blacklist_count = usize::try_from(blacklist_count).ok()?;
+
+	if archive_mode == ArchiveMode::None {
+		return Some(blacklist_count);
+	}
+
+	let count: i64 = media_archive::dsl::media_archive.count().get_result(connection).ok()?;
+	let count = usize::try_from(count).ok()?;
+
+	if archive_mode == ArchiveMode::ByDate1000 {
+		return Some(blacklist_count + count.min(1000));
+	}
+
+	return Some(blacklist_count + count);
+}
+
+/// Check if the extra youtube-dl argument `arg` (like "--impersonate chrome") is supported by `ytdl_capabilities`
+/// Warns about and returns `false` for unsupported options, everything that is not a long option is always supported
+fn is_supported_extra_arg(arg: &str, ytdl_capabilities: Option<&YtdlCapabilities>) -> bool {
+	let Some(ytdl_capabilities) = ytdl_capabilities else {
+		return true;
+	};
+
+	if !arg.starts_with("--") {
+		return true;
+	}
+
+	let option = arg.split_once(' ').map_or(arg, |(option, _)| return option);
+
+	if ytdl_capabilities.supports(option) {
+		return true;
+	}
+
+	warn!(
+		"Ignoring extra youtube-dl argument \"{}\", because \"{}\" is not supported by the installed youtube-dl",
+		arg, option
+	);
+
+	return false;
 }
 
 #[cfg(test)]
 mod test {
 	use super::*;
+	use crate::clap_conf::AudioFormat;
 
 	// test that all static dates compile without problem
 	#[test]
@@ -213,4 +501,62 @@ mod test {
 		// simple test to test that the versions compile without panic
 		let _ = *MINIMAL_YTDL_VERSION;
 	}
+
+	#[test]
+	fn test_is_supported_extra_arg() {
+		let capabilities = YtdlCapabilities::from_help("    --impersonate CLIENT[:OS]    Client to impersonate");
+
+		// without capabilities, everything is passed through
+		assert!(is_supported_extra_arg("--download-sections *0-10", None));
+
+		assert!(is_supported_extra_arg("--impersonate chrome", Some(&capabilities)));
+		assert!(is_supported_extra_arg("--impersonate=chrome", Some(&capabilities)));
+		assert!(!is_supported_extra_arg(
+			"--download-sections *0-10",
+			Some(&capabilities)
+		));
+		// non-long options cannot be checked
+		assert!(is_supported_extra_arg("-N 4", Some(&capabilities)));
+	}
+
+	#[test]
+	fn test_profile_precedence() {
+		let sub_args = CommandDownload {
+			sub_langs: Some("de".to_owned()),
+			..Default::default()
+		};
+		let mut state = DownloadState::new(
+			&sub_args,
+			PathBuf::from("/tmp"),
+			OsString::from("yt-dlp"),
+			"2023.03.04",
+			None,
+		);
+
+		assert!(!state.audio_only());
+		assert_eq!("best", state.get_audio_format());
+		assert_eq!(Some("de"), state.sub_langs());
+
+		state.set_profile(Some(&DownloadProfile {
+			audio_only:   Some(true),
+			audio_format: Some(AudioFormat::Mp3),
+			video_format: None,
+			sub_langs:    Some("en".to_owned()),
+		}));
+
+		assert!(state.audio_only());
+		assert_eq!("mp3", state.get_audio_format());
+		assert_eq!("mkv", state.get_video_format());
+		assert_eq!(Some("en"), state.sub_langs());
+
+		// per-URL overrides take priority over the profile
+		state.set_url_overrides(Some(&UrlOverrides {
+			audio_only: Some(false),
+			..Default::default()
+		}));
+		assert!(!state.audio_only());
+
+		state.set_profile(None);
+		assert_eq!("best", state.get_audio_format());
+	}
 }
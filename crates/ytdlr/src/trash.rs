@@ -0,0 +1,298 @@
+//! Module for the trash of "--use-trash", which keeps the original files of a "finish_with_move" so that it can be undone with "restore"
+//!
+//! Every move operation gets its own directory in the trash, with a manifest of which file was moved where and what has been trashed
+
+use std::path::{
+	Path,
+	PathBuf,
+};
+
+use libytdlr::error::IOErrorToError;
+use serde_json::{
+	json,
+	Value,
+};
+
+/// The directory name of the trash, inside the download directory
+pub const TRASH_DIR_NAME: &str = ".ytdlr-trash";
+/// The file name of the manifest in every operation directory
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// A single moved file (media or sidecar file) of a [`TrashOperation`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+	/// The path the file was moved from (in the download directory)
+	pub from:        PathBuf,
+	/// The path the file was moved to (in the output directory)
+	pub to:          PathBuf,
+	/// The path of the original file in the trash
+	pub original:    PathBuf,
+	/// The path in the trash of the file that was overwritten at "to", if any
+	pub overwritten: Option<PathBuf>,
+}
+
+/// A single move operation (like one "finish_with_move") in the trash
+#[derive(Debug)]
+pub struct TrashOperation {
+	/// The directory of this operation in the trash
+	dir:     PathBuf,
+	/// All files moved in this operation, in the order they have been moved
+	entries: Vec<TrashEntry>,
+}
+
+impl TrashOperation {
+	/// Create a new operation in the trash at `trash_path`, named after the current time
+	/// The directory is only created once the first file is trashed, so that operations without files do not leave empty directories
+	#[must_use]
+	pub fn new(trash_path: &Path) -> Self {
+		let dir = trash_path.join(
+			libytdlr::chrono::Local::now()
+				.format("%Y-%m-%dT%H-%M-%S%.3f")
+				.to_string(),
+		);
+
+		return Self {
+			dir,
+			entries: Vec::new(),
+		};
+	}
+
+	/// Load the latest operation in the trash at `trash_path`
+	/// Returns [`None`] if there is no operation in the trash
+	pub fn latest(trash_path: &Path) -> Result<Option<Self>, crate::Error> {
+		if !trash_path.exists() {
+			return Ok(None);
+		}
+
+		let mut dirs = Vec::new();
+		for entry in std::fs::read_dir(trash_path).attach_path_err(trash_path)? {
+			let path = entry.attach_path_err(trash_path)?.path();
+
+			if path.join(MANIFEST_NAME).is_file() {
+				dirs.push(path);
+			}
+		}
+
+		// the directory names are timestamps, so the latest sorts last
+		let Some(dir) = dirs.into_iter().max() else {
+			return Ok(None);
+		};
+
+		let manifest_path = dir.join(MANIFEST_NAME);
+		let manifest = std::fs::read_to_string(&manifest_path).attach_path_err(&manifest_path)?;
+		let entries = parse_manifest(&manifest).map_err(|msg| {
+			return crate::Error::other(format!("Invalid trash manifest \"{}\": {msg}", manifest_path.display()));
+		})?;
+
+		return Ok(Some(Self { dir, entries }));
+	}
+
+	/// Get the directory of this operation
+	#[must_use]
+	pub fn dir(&self) -> &Path {
+		return &self.dir;
+	}
+
+	/// Get all files moved in this operation
+	#[must_use]
+	pub fn entries(&self) -> &[TrashEntry] {
+		return &self.entries;
+	}
+
+	/// Move the original media at `path` into the operation directory and return the new path, see [`TrashEntry::original`]
+	pub fn trash_original(&self, path: &Path) -> Result<PathBuf, crate::Error> {
+		return self.trash_file(path, "original");
+	}
+
+	/// Move the file at `path`, which is about to be overwritten, into the operation directory and return the new path, see [`TrashEntry::overwritten`]
+	pub fn trash_overwritten(&self, path: &Path) -> Result<PathBuf, crate::Error> {
+		return self.trash_file(path, "overwritten");
+	}
+
+	/// Move `path` into the operation directory and return the new path
+	/// The file is prefixed with the current entry count and `kind`, so that files with the same name do not collide
+	fn trash_file(&self, path: &Path, kind: &str) -> Result<PathBuf, crate::Error> {
+		let file_name = path
+			.file_name()
+			.ok_or_else(|| return crate::Error::other(format!("Path \"{}\" has no file name", path.display())))?;
+		let mut trash_name = std::ffi::OsString::from(format!("{}-{kind}-", self.entries.len()));
+		trash_name.push(file_name);
+
+		std::fs::create_dir_all(&self.dir).attach_path_err(&self.dir)?;
+		let trash_path = self.dir.join(trash_name);
+		move_file(path, &trash_path)?;
+
+		return Ok(trash_path);
+	}
+
+	/// Add `entry` to the operation and write the manifest, so that it is always up-to-date
+	pub fn push(&mut self, entry: TrashEntry) -> Result<(), crate::Error> {
+		self.entries.push(entry);
+
+		let manifest_path = self.dir.join(MANIFEST_NAME);
+		std::fs::write(&manifest_path, format_manifest(&self.entries)?).attach_path_err(&manifest_path)?;
+
+		return Ok(());
+	}
+
+	/// Undo the operation: remove the moved media, put back overwritten files and move the originals back to the download directory
+	/// The operation is removed from the trash afterwards
+	/// Returns the count of restored files
+	pub fn restore(self) -> Result<usize, crate::Error> {
+		// undo in reverse, in case a later entry overwrote a earlier one
+		for entry in self.entries.iter().rev() {
+			if entry.to.exists() {
+				std::fs::remove_file(&entry.to).attach_path_err(&entry.to)?;
+			}
+
+			if let Some(overwritten) = &entry.overwritten {
+				move_file(overwritten, &entry.to)?;
+			}
+
+			move_file(&entry.original, &entry.from)?;
+		}
+
+		std::fs::remove_dir_all(&self.dir).attach_path_err(&self.dir)?;
+
+		return Ok(self.entries.len());
+	}
+}
+
+/// Move `from` to `to`, falling back to copy and remove if they are not on the same file-system
+pub fn move_file(from: &Path, to: &Path) -> Result<(), crate::Error> {
+	if std::fs::rename(from, to).is_ok() {
+		return Ok(());
+	}
+
+	std::fs::copy(from, to).attach_path_err(from)?;
+	std::fs::remove_file(from).attach_path_err(from)?;
+
+	return Ok(());
+}
+
+/// Convert `path` to a JSON value, paths which are not valid UTF-8 cannot be stored
+fn path_value(path: &Path) -> Result<Value, crate::Error> {
+	return path.to_str().map(|v| return json!(v)).ok_or_else(|| {
+		return crate::Error::other(format!(
+			"Path \"{}\" is not valid UTF-8 and cannot be trashed",
+			path.display()
+		));
+	});
+}
+
+/// Format `entries` as the manifest JSON
+fn format_manifest(entries: &[TrashEntry]) -> Result<String, crate::Error> {
+	let entries = entries
+		.iter()
+		.map(|v| {
+			return Ok(json!({
+				"from": path_value(&v.from)?,
+				"to": path_value(&v.to)?,
+				"original": path_value(&v.original)?,
+				"overwritten": v.overwritten.as_deref().map(path_value).transpose()?,
+			}));
+		})
+		.collect::<Result<Vec<Value>, crate::Error>>()?;
+
+	return Ok(json!({ "entries": entries }).to_string());
+}
+
+/// Parse the manifest JSON, returning the error message so it can be wrapped by the caller
+fn parse_manifest(input: &str) -> Result<Vec<TrashEntry>, String> {
+	let value: Value = serde_json::from_str(input).map_err(|err| return err.to_string())?;
+
+	let get_path = |entry: &Value, key: &str| {
+		return entry
+			.get(key)
+			.and_then(Value::as_str)
+			.map(PathBuf::from)
+			.ok_or_else(|| return format!("Missing \"{key}\""));
+	};
+
+	return value
+		.get("entries")
+		.and_then(Value::as_array)
+		.ok_or_else(|| return "Missing \"entries\"".to_owned())?
+		.iter()
+		.map(|entry| {
+			return Ok(TrashEntry {
+				from:        get_path(entry, "from")?,
+				to:          get_path(entry, "to")?,
+				original:    get_path(entry, "original")?,
+				overwritten: get_path(entry, "overwritten").ok(),
+			});
+		})
+		.collect();
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn test_manifest_roundtrip() {
+		let entries = vec![
+			TrashEntry {
+				from:        PathBuf::from("/tmp/dl/a.mkv"),
+				to:          PathBuf::from("/out/a.mkv"),
+				original:    PathBuf::from("/tmp/dl/.ytdlr-trash/op/0-original-a.mkv"),
+				overwritten: None,
+			},
+			TrashEntry {
+				from:        PathBuf::from("/tmp/dl/b.mp3"),
+				to:          PathBuf::from("/out/b.mp3"),
+				original:    PathBuf::from("/tmp/dl/.ytdlr-trash/op/1-original-b.mp3"),
+				overwritten: Some(PathBuf::from("/tmp/dl/.ytdlr-trash/op/1-overwritten-b.mp3")),
+			},
+		];
+
+		assert_eq!(Ok(entries.clone()), parse_manifest(&format_manifest(&entries).unwrap()));
+		assert!(parse_manifest("{}").is_err());
+		assert!(parse_manifest("{\"entries\":[{\"from\":\"a\"}]}").is_err());
+	}
+
+	#[test]
+	fn test_trash_and_restore() {
+		let tmp = tempfile::Builder::new()
+			.prefix("ytdl-test-trash-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let download_dir = tmp.path().join("download");
+		let out_dir = tmp.path().join("out");
+		std::fs::create_dir_all(&download_dir).unwrap();
+		std::fs::create_dir_all(&out_dir).unwrap();
+		let trash_path = download_dir.join(TRASH_DIR_NAME);
+
+		assert!(TrashOperation::latest(&trash_path).unwrap().is_none());
+
+		let from = download_dir.join("a.mkv");
+		let to = out_dir.join("a.mkv");
+		std::fs::write(&from, "new").unwrap();
+		std::fs::write(&to, "existing").unwrap();
+
+		// simulate "finish_with_move" with a overwrite
+		let mut operation = TrashOperation::new(&trash_path);
+		let overwritten = operation.trash_overwritten(&to).unwrap();
+		std::fs::copy(&from, &to).unwrap();
+		let original = operation.trash_original(&from).unwrap();
+		operation
+			.push(TrashEntry {
+				from: from.clone(),
+				to: to.clone(),
+				original,
+				overwritten: Some(overwritten),
+			})
+			.unwrap();
+
+		assert!(!from.exists());
+		assert_eq!("new", std::fs::read_to_string(&to).unwrap());
+
+		let latest = TrashOperation::latest(&trash_path).unwrap().unwrap();
+		assert_eq!(operation.entries(), latest.entries());
+		assert_eq!(1, latest.restore().unwrap());
+
+		assert_eq!("new", std::fs::read_to_string(&from).unwrap());
+		assert_eq!("existing", std::fs::read_to_string(&to).unwrap());
+		assert!(TrashOperation::latest(&trash_path).unwrap().is_none());
+	}
+}
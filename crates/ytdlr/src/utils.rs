@@ -1,7 +1,15 @@
 //! Utils for the `ytdlr` binary
 
 use crate::{
-	clap_conf::CliDerive,
+	clap_conf::{
+		CliDerive,
+		RecoverFilter,
+	},
+	messages::{
+		tr,
+		trf,
+		Msg,
+	},
 	TERMINATE,
 };
 use indicatif::{
@@ -19,6 +27,7 @@ use libytdlr::{
 };
 use std::{
 	borrow::Cow,
+	collections::HashMap,
 	ffi::{
 		OsStr,
 		OsString,
@@ -39,11 +48,30 @@ use std::{
 		PathBuf,
 	},
 	process::Stdio,
-	sync::mpsc,
+	sync::{
+		atomic::{
+			AtomicBool,
+			Ordering,
+		},
+		mpsc,
+	},
 };
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+/// Whether all prompts should take their default without asking, set once at startup
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Set whether all prompts should take their default without asking (from "--assume-yes")
+pub fn set_assume_yes(assume_yes: bool) {
+	ASSUME_YES.store(assume_yes, Ordering::Relaxed);
+}
+
+/// Get whether all prompts should take their default without asking
+pub fn assume_yes() -> bool {
+	return ASSUME_YES.load(Ordering::Relaxed);
+}
+
 /// Helper function to set the progressbar to a draw target based on if it is interactive
 pub fn set_progressbar(bar: &ProgressBar, main_args: &CliDerive) {
 	if main_args.is_interactive() {
@@ -88,8 +116,98 @@ pub fn handle_connect<'a>(
 	return Ok(res);
 }
 
+/// Get the host part of a URL, without scheme, port, path or query
+/// Returns the input as-is if no host can be determined
+pub fn url_host(url: &str) -> &str {
+	let without_scheme = url.split_once("://").map_or(url, |(_, rest)| return rest);
+
+	return without_scheme
+		.split(['/', '?', '#', ':'])
+		.next()
+		.unwrap_or(without_scheme);
+}
+
+/// Hosts whose provider is not the main part of the host, as "(host, provider)"
+const PROVIDER_HOST_ALIASES: &[(&str, &str)] = &[
+	("youtu.be", "youtube"),
+	("youtube-nocookie.com", "youtube"),
+	("dai.ly", "dailymotion"),
+	("nicovideo.jp", "niconico"),
+];
+
+/// Get the provider of a URL, like "youtube" for "https://youtu.be/-----------" or "soundcloud" for "https://soundcloud.com/some/track"
+/// This is a heuristic from the host, because the actual provider is only known once youtube-dl ran
+/// Returns [`None`] if the URL has no host with a domain (like "localhost")
+pub fn url_provider(url: &str) -> Option<String> {
+	let host = url_host(url.trim()).to_lowercase();
+
+	if let Some((_, provider)) = PROVIDER_HOST_ALIASES
+		.iter()
+		.find(|(alias, _)| return host == *alias || host.ends_with(&format!(".{alias}")))
+	{
+		return Some((*provider).to_owned());
+	}
+
+	// the part before the top-level domain, like "soundcloud" for "api-v2.soundcloud.com"
+	let mut parts = host.rsplit('.');
+	parts.next()?;
+
+	return parts.next().filter(|v| return !v.is_empty()).map(ToOwned::to_owned);
+}
+
+/// Read all URLs from a batch-file at `path`, one per line
+/// Empty lines and lines starting with "#" are ignored, like in youtube-dl batch-files
+pub fn read_url_file(path: &Path) -> Result<Vec<String>, crate::Error> {
+	let content = std::fs::read_to_string(path).attach_path_err(path)?;
+
+	return Ok(content
+		.lines()
+		.map(str::trim)
+		.filter(|v| return !v.is_empty() && !v.starts_with('#'))
+		.map(str::to_owned)
+		.collect());
+}
+
+/// Write all `urls` to a new batch-file "failed-<timestamp>.txt" in directory `dir`, one per line
+/// Returns the path of the written file
+pub fn write_failed_url_file<S: AsRef<str>>(dir: &Path, urls: &[S]) -> Result<PathBuf, crate::Error> {
+	std::fs::create_dir_all(dir).attach_path_err(dir)?;
+
+	let path = dir.join(format!(
+		"failed-{}.txt",
+		libytdlr::chrono::Local::now().format("%Y-%m-%dT%H-%M-%S")
+	));
+
+	let mut content = String::new();
+	for url in urls {
+		content.push_str(url.as_ref());
+		content.push('\n');
+	}
+
+	std::fs::write(&path, content).attach_path_err(&path)?;
+
+	return Ok(path);
+}
+
+/// Get the URL of a media from its provider and id
+/// Returns [`None`] if the URL for the provider is not known
+pub fn media_url(provider: &str, media_id: &str) -> Option<String> {
+	return match provider.to_lowercase().as_str() {
+		"youtube" => Some(format!("https://www.youtube.com/watch?v={media_id}")),
+		_ => None,
+	};
+}
+
 /// Find all files in the provided "path" that could be edited (like mkv, mp3)
 pub fn find_editable_files<P: AsRef<Path>>(path: P) -> Result<Vec<MediaInfo>, crate::Error> {
+	return find_editable_files_filtered(path, &[]);
+}
+
+/// Find all files in the provided "path" that could be edited (like mkv, mp3) and match all "filters"
+pub fn find_editable_files_filtered<P: AsRef<Path>>(
+	path: P,
+	filters: &[RecoverFilter],
+) -> Result<Vec<MediaInfo>, crate::Error> {
 	let path = path.as_ref();
 
 	// some basic checks that the path is actually valid
@@ -111,13 +229,30 @@ pub fn find_editable_files<P: AsRef<Path>>(path: P) -> Result<Vec<MediaInfo>, cr
 
 	// do a loop over each element in the directory, and filter out paths that are not valid / accessable
 	for entry in (std::fs::read_dir(path).attach_path_err(path)?).flatten() {
+		let entry_path = entry.path();
+
 		if let Ok(metadata) = entry.metadata() {
 			if !metadata.is_file() || metadata.size() == 0 {
 				continue;
 			}
+
+			// check the cheap filters before parsing the file
+			if !filters
+				.iter()
+				.all(|filter| return matches_recover_filter_file(filter, &entry_path, &metadata))
+			{
+				continue;
+			}
 		}
 
-		if let Some(mediainfo) = process_path_for_editable_files(&entry.path()) {
+		if let Some(mediainfo) = process_path_for_editable_files(&entry_path) {
+			if !filters
+				.iter()
+				.all(|filter| return matches_recover_filter_media(filter, &mediainfo))
+			{
+				continue;
+			}
+
 			mediainfo_vec.push(mediainfo);
 		}
 	}
@@ -125,6 +260,28 @@ pub fn find_editable_files<P: AsRef<Path>>(path: P) -> Result<Vec<MediaInfo>, cr
 	return Ok(mediainfo_vec);
 }
 
+/// Check the file based filters of [`RecoverFilter`], returns `true` for filters which are not file based
+fn matches_recover_filter_file(filter: &RecoverFilter, path: &Path, metadata: &std::fs::Metadata) -> bool {
+	return match filter {
+		RecoverFilter::MaxAge(max_age) => metadata
+			.modified()
+			.ok()
+			.and_then(|v| return v.elapsed().ok())
+			// files with a modified time in the future or not available are kept
+			.map_or(true, |age| return age <= *max_age),
+		RecoverFilter::Type(filetype) => &get_filetype(path) == filetype,
+		RecoverFilter::Provider(_) => true,
+	};
+}
+
+/// Check the media based filters of [`RecoverFilter`], returns `true` for filters which are not media based
+fn matches_recover_filter_media(filter: &RecoverFilter, media: &MediaInfo) -> bool {
+	return match filter {
+		RecoverFilter::Provider(provider) => media.provider.as_str().eq_ignore_ascii_case(provider),
+		RecoverFilter::MaxAge(_) | RecoverFilter::Type(_) => true,
+	};
+}
+
 /// Helper function to reduce nesting for [`find_editable_files`]
 /// for example, in a loop "?" cannot be used, but in a helper function
 #[inline]
@@ -137,15 +294,16 @@ fn process_path_for_editable_files(path: &Path) -> Option<MediaInfo> {
 	if !match_extension_for_editable_files(path.extension()?) {
 		return None;
 	}
-	// if the name contains "temp" as a extension at the end, ignore the file
-	// as it is not post-processed yet
-	// example: "'provider'-'id'-some name.with.dots.temp.opus"
-	if path
-		.file_stem()
-		.and_then(|v| return Path::new(v).extension())
-		.is_some_and(|v| return v == "temp")
-	{
-		return None;
+
+	// prefer the sidecar written at MOVE time, because it does not depend on the title being parsable from the filename
+	match MediaInfo::try_from_sidecar(path) {
+		Ok(Some(mediainfo)) => return Some(mediainfo),
+		Ok(None) => (),
+		Err(err) => warn!(
+			"Reading sidecar for \"{}\" failed, falling back to the filename: {}",
+			path.to_string_lossy(),
+			err
+		),
 	}
 
 	return MediaInfo::try_from_filename(&path.file_name()?.to_str()?);
@@ -155,6 +313,25 @@ fn process_path_for_editable_files(path: &Path) -> Option<MediaInfo> {
 const AUDIO_EXTENSION_LIST: &[&str] = &["mp3", "wav", "aac", "ogg", "opus", "m4a", "flac"];
 // Array of VIDEO extensions supported for matching in ytdlr
 const VIDEO_EXTENSION_LIST: &[&str] = &["mp4", "mkv", "webm"];
+// Array of SUBTITLE extensions supported for matching sidecar files in ytdlr
+const SUBTITLE_EXTENSION_LIST: &[&str] = &["srt", "vtt", "ass", "ttml"];
+// Array of THUMBNAIL extensions supported for matching sidecar files in ytdlr
+const THUMBNAIL_EXTENSION_LIST: &[&str] = &["jpg", "png", "webp"];
+// The suffix of youtube-dl info json files (like from "--write-info-json"), matched as sidecar files in ytdlr
+const INFO_JSON_SUFFIX: &str = ".info.json";
+
+/// Helper function to keep all sidecar file matching for [`find_sidecar_files`] together
+#[inline]
+fn match_sidecar_file(filename: &str) -> bool {
+	if filename.ends_with(INFO_JSON_SUFFIX) {
+		return true;
+	}
+
+	return Path::new(filename)
+		.extension()
+		.and_then(|v| return v.to_str())
+		.is_some_and(|ext| return SUBTITLE_EXTENSION_LIST.contains(&ext) || THUMBNAIL_EXTENSION_LIST.contains(&ext));
+}
 
 /// Helper function to keep all extension matching for [`find_editable_files`] sorted
 #[inline]
@@ -198,8 +375,94 @@ pub fn get_filetype<F: AsRef<Path>>(filename: F) -> FileType {
 	return FileType::Unknown;
 }
 
+/// Find the sidecar files of the media at `media_path`, which are named like it and are either next to it or in one of `extra_dirs`
+/// Sidecar files are subtitles (like from "--subs-external"), thumbnails and youtube-dl info json files
+/// Returns the path of every sidecar file together with its suffix after the media's file stem (like ".en.vtt"), sorted by path
+pub fn find_sidecar_files(media_path: &Path, extra_dirs: &[PathBuf]) -> Vec<(PathBuf, String)> {
+	let (Some(media_dir), Some(stem)) = (
+		media_path.parent(),
+		media_path.file_stem().and_then(|v| return v.to_str()),
+	) else {
+		return Vec::new();
+	};
+	let prefix = format!("{stem}.");
+
+	let mut sidecars: Vec<(PathBuf, String)> = std::iter::once(media_dir)
+		.chain(extra_dirs.iter().map(PathBuf::as_path))
+		// directories that do not exist (like the thumbnail directory without thumbnails) have no sidecars
+		.filter_map(|dir| return std::fs::read_dir(dir).ok())
+		.flatten()
+		.filter_map(|v| return v.ok())
+		.filter_map(|entry| {
+			let path = entry.path();
+			let filename = path.file_name()?.to_str()?;
+
+			if !filename.starts_with(&prefix) || !match_sidecar_file(filename) {
+				return None;
+			}
+
+			let suffix = filename[stem.len()..].to_owned();
+
+			return Some((path, suffix));
+		})
+		.collect();
+	sidecars.sort();
+
+	return sidecars;
+}
+
+/// Read a single line from STDIN (including the new-line), while still handling termination requests
+///
+/// Note:
+/// This function will not discard buffered stdin, see [`get_input`]
+pub fn read_line_terminable() -> Result<String, crate::Error> {
+	let input: String;
+
+	// the following has to be done because "read_line" is blocking, but the ctrlc handler should still be able to work
+	{
+		let (tx, rx) = mpsc::channel::<Result<String, ioError>>();
+		let read_thread = std::thread::Builder::new()
+			.name("input reader".to_owned())
+			.spawn(move || {
+				// input buffer for "read_line", 1 capacity, because of only expecting 1 character
+				let mut input = String::with_capacity(1);
+				let _ = tx.send(std::io::stdin().read_line(&mut input).map(|_| return input));
+			})
+			.attach_location_err("input reader thread spawn")?;
+
+		loop {
+			// handle terminate
+			if crate::TERMINATE
+				.read()
+				.map_err(|err| return crate::Error::other(format!("{err}")))?
+				.termination_requested()
+			{
+				return Err(crate::Error::other("Termination Requested"));
+			}
+
+			match rx.try_recv() {
+				Ok(v) => {
+					input = v.attach_location_err("input reader line")?;
+					break;
+				},
+				Err(mpsc::TryRecvError::Empty) => (),
+				Err(mpsc::TryRecvError::Disconnected) => {
+					return Err(crate::Error::other("Channel unexpectedly disconnected"))
+				},
+			}
+
+			std::thread::sleep(std::time::Duration::from_millis(50)); // sleep 50ms to not immediately try again, but still be responding
+		}
+
+		read_thread.join_err()?;
+	}
+
+	return Ok(input);
+}
+
 /// Get input from STDIN with "possible" or "default"
 /// if using "default", remember to set a character in "possible" to upper-case
+/// If [`assume_yes`] is set, the "default" is returned without reading, or a error if there is no "default"
 ///
 /// Note:
 /// This function will not discard buffered stdin, because in native rust there is no good way to clear a sync-read, and for async-read the whole library would be needed to be converted to async
@@ -218,50 +481,25 @@ pub fn get_input(msg: &str, possible: &[&'static str], default: &'static str) ->
 		.collect::<Vec<String>>();
 	// dont use "possible_converted" for "possible_converted_string", because otherwise the default will not be shown anymore
 	let possible_converted_string = possible.join("/");
+
+	if assume_yes() {
+		if default.is_empty() {
+			return Err(crate::Error::other(format!(
+				"Prompt \"{msg}\" has no default, but \"--assume-yes\" is set"
+			)));
+		}
+
+		// still print the prompt, so that it is visible in the output which prompts were answered
+		println!("{msg} [{possible_converted_string}]: {default}");
+
+		return Ok(default.to_owned());
+	}
+
 	loop {
 		print!("{msg} [{possible_converted_string}]: ");
 		// ensure the message is printed before reading
 		std::io::stdout().flush().attach_location_err("stdout flush")?;
-		let input: String;
-
-		// the following has to be done because "read_line" is blocking, but the ctrlc handler should still be able to work
-		{
-			let (tx, rx) = mpsc::channel::<Result<String, ioError>>();
-			let read_thread = std::thread::Builder::new()
-				.name("input reader".to_owned())
-				.spawn(move || {
-					// input buffer for "read_line", 1 capacity, because of only expecting 1 character
-					let mut input = String::with_capacity(1);
-					let _ = tx.send(std::io::stdin().read_line(&mut input).map(|_| return input));
-				})
-				.attach_location_err("input reader thread spawn")?;
-
-			loop {
-				// handle terminate
-				if crate::TERMINATE
-					.read()
-					.map_err(|err| return crate::Error::other(format!("{err}")))?
-					.termination_requested()
-				{
-					return Err(crate::Error::other("Termination Requested"));
-				}
-
-				match rx.try_recv() {
-					Ok(v) => {
-						input = v.attach_location_err("input reader line")?;
-						break;
-					},
-					Err(mpsc::TryRecvError::Empty) => (),
-					Err(mpsc::TryRecvError::Disconnected) => {
-						return Err(crate::Error::other("Channel unexpectedly disconnected"))
-					},
-				}
-
-				std::thread::sleep(std::time::Duration::from_millis(50)); // sleep 50ms to not immediately try again, but still be responding
-			}
-
-			read_thread.join_err()?;
-		}
+		let input = read_line_terminable()?;
 
 		let input = input.trim().to_lowercase();
 
@@ -269,7 +507,7 @@ pub fn get_input(msg: &str, possible: &[&'static str], default: &'static str) ->
 		if input.is_empty() {
 			if default.is_empty() {
 				// special case when empty, to more emphasize that its empty
-				println!("... Invalid Input: (Empty)");
+				println!("{}", tr(Msg::InvalidInputEmpty));
 				continue;
 			} else {
 				return Ok(default.to_owned());
@@ -280,7 +518,7 @@ pub fn get_input(msg: &str, possible: &[&'static str], default: &'static str) ->
 			return Ok(input);
 		}
 
-		println!("... Invalid Input: \"{input}\"");
+		println!("{}", trf(Msg::InvalidInput, &[&input]));
 	}
 }
 
@@ -347,6 +585,10 @@ fn get_editor_base(maybe_editor: &Option<PathBuf>) -> Result<PathBuf, crate::Err
 	}
 
 	// path where "maybe_editor" is "none" or user selected to "set new path" because not existing
+	if assume_yes() {
+		return Err(crate::Error::other("No Editor base is set and \"--assume-yes\" is set"));
+	}
+
 	'ask_for_editor: loop {
 		print!("Enter new Editor base: ");
 		// ensure the message is printed before reading
@@ -384,6 +626,13 @@ fn test_editor_base(path: &Path) -> Result<Option<PathBuf>, crate::Error> {
 
 		println!("Editor base is not available, Error: {err}");
 
+		// the default of the following prompt would retry forever
+		if assume_yes() {
+			return Err(crate::Error::other(
+				"Editor base is not available and \"--assume-yes\" is set",
+			));
+		}
+
 		let input = get_input("[R]etry, [a]bort or [s]et new path?", &["R", "a", "s"], "r")?;
 
 		match input.as_str() {
@@ -419,28 +668,77 @@ pub fn convert_mediainfo_to_filename(media: &MediaInfo) -> Option<(&PathBuf, Pat
 	let media_title = media.title.as_ref()?;
 	let extension = media_filename.extension()?;
 
-	// replace all "/" with a similar looking character, so to not create multiple segments
-	let media_title_conv = media_title.replace('/', "⧸");
+	return Some((
+		media_filename,
+		PathBuf::from(&filename_with_extension(media_title, extension)),
+	));
+}
 
-	let extension_length = extension.as_bytes().len() + 1;
+/// Convert `name` into a single path segment (replacing "/"), truncated so that it is below 255 bytes with `reserved_bytes` added
+fn path_segment(name: &str, reserved_bytes: usize) -> Cow<str> {
+	// replace all "/" with a similar looking character, so to not create multiple segments
+	let name_conv = name.replace('/', "⧸");
 
-	// the title to use in the end
 	// using 254 instead of 255 just to be safe
-	let title_use = if media_title_conv.len() + extension_length > 254 {
-		let truncate_to_max = 254 - extension_length;
-		truncate_to_size_bytes(&media_title_conv, truncate_to_max, true)
-	} else {
-		media_title_conv[..].into()
-	};
+	if name_conv.len() + reserved_bytes > 254 {
+		let truncate_to_max = 254 - reserved_bytes;
+
+		return Cow::Owned(truncate_to_size_bytes(&name_conv, truncate_to_max, true).into_owned());
+	}
+
+	return Cow::Owned(name_conv);
+}
+
+/// Convert `title` into a filename with `extension`, see [`path_segment`]
+fn filename_with_extension(title: &str, extension: &OsStr) -> OsString {
+	let extension_length = extension.as_bytes().len() + 1;
 
 	// convert converted title into OsString and add the extension
 	// this needs to be done so that titles containing "." do not accidentally get overwritten by "set_extension"
-	let mut final_name_osstr: OsString = title_use.as_ref().into();
+	let mut final_name_osstr: OsString = path_segment(title, extension_length).as_ref().into();
 
 	final_name_osstr.push(".");
 	final_name_osstr.push(extension); // the extension can be easily added here, because we can safely assume the title does not have a extension
 
-	return Some((media_filename, PathBuf::from(&final_name_osstr)));
+	return final_name_osstr;
+}
+
+/// Directory name used by [`convert_mediainfo_to_organized`] if no artist is known
+pub const ORGANIZE_UNKNOWN_ARTIST: &str = "Unknown Artist";
+/// Directory name used by [`convert_mediainfo_to_organized`] if no album is known
+pub const ORGANIZE_UNKNOWN_ALBUM: &str = "Unknown Album";
+
+/// Convert a "MediaInfo" instance and the tags of its file (see [`libytdlr::spawn::ffmpeg::parse_metadata`]) to a organized path
+/// Every segment gets truncated to be below 255 bytes
+/// Returns [`Some`] the organized path (Path Format: "artist/album/title.extension") (filename, organized_path)
+/// Returns [`None`] when `media.filename` or `media.filename.extension` are [`None`], or no title is known
+///
+/// Missing tags fall back to the uploader and the title of the media, or [`ORGANIZE_UNKNOWN_ARTIST`] and [`ORGANIZE_UNKNOWN_ALBUM`]
+pub fn convert_mediainfo_to_organized<'a>(
+	media: &'a MediaInfo,
+	metadata: &HashMap<String, String>,
+) -> Option<(&'a PathBuf, PathBuf)> {
+	let media_filename = media.filename.as_ref()?;
+	let extension = media_filename.extension()?;
+
+	// values that are empty or would be a special path segment are not usable
+	let usable = |value: &str| return !value.is_empty() && value != "." && value != "..";
+	let tag = |key: &str| {
+		return metadata.get(key).map(|v| return v.trim()).filter(|v| return usable(v));
+	};
+
+	let artist = tag("artist")
+		.or_else(|| return tag("album_artist"))
+		.or_else(|| return media.uploader.as_deref().map(str::trim).filter(|v| return usable(v)))
+		.unwrap_or(ORGANIZE_UNKNOWN_ARTIST);
+	let album = tag("album").unwrap_or(ORGANIZE_UNKNOWN_ALBUM);
+	let title = tag("title").or_else(|| return media.title.as_deref().map(str::trim).filter(|v| return usable(v)))?;
+
+	let mut organized = PathBuf::from(path_segment(artist, 0).as_ref());
+	organized.push(path_segment(album, 0).as_ref());
+	organized.push(filename_with_extension(title, extension));
+
+	return Some((media_filename, organized));
 }
 
 /// Apply all required processing to paths that need extra processing
@@ -467,10 +765,58 @@ pub struct CharInfo<'a> {
 	pub full_char:        &'a str,
 }
 
+/// Parse a byte size (or rate) with a optional 1024-based suffix "K", "M" or "G" (case-insensitive), like "500K" or "1.5M"
+/// The same format as yt-dlp "--limit-rate", but validated before running yt-dlp
+pub fn parse_byte_size(input: &str) -> Result<u64, crate::Error> {
+	let input = input.trim();
+	let (number, multiplier) = match input.chars().last().map(|v| return v.to_ascii_uppercase()) {
+		Some('K') => (&input[..input.len() - 1], 1024_f64),
+		Some('M') => (&input[..input.len() - 1], 1024_f64.powi(2)),
+		Some('G') => (&input[..input.len() - 1], 1024_f64.powi(3)),
+		_ => (input, 1_f64),
+	};
+
+	let number: f64 = number.parse().map_err(|_| {
+		return crate::Error::other(format!(
+			"Invalid byte size \"{input}\", expected a number like \"500K\" or \"2M\""
+		));
+	})?;
+
+	if !number.is_finite() || number <= 0_f64 {
+		return Err(crate::Error::other(format!(
+			"Invalid byte size \"{input}\", has to be more than 0"
+		)));
+	}
+
+	// truncation is wanted, there are no partial bytes
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+	let bytes = (number * multiplier) as u64;
+
+	if bytes == 0 {
+		return Err(crate::Error::other(format!(
+			"Invalid byte size \"{input}\", has to be at least 1 byte"
+		)));
+	}
+
+	return Ok(bytes);
+}
+
+/// Format a duration in seconds like a media player would, like "3:05" or "1:02:03"
+#[must_use]
+pub fn format_duration_secs(secs: u64) -> String {
+	let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+
+	if hours > 0 {
+		return format!("{hours}:{minutes:02}:{seconds:02}");
+	}
+
+	return format!("{minutes}:{seconds:02}");
+}
+
 /// Convert a given string into a array of [CharInfo] to index at the correct positions
 pub fn msg_to_cluster<M>(msg: &M) -> Vec<CharInfo>
 where
-	M: AsRef<str>,
+	M: AsRef<str> + ?Sized,
 {
 	let msg = msg.as_ref();
 
@@ -614,6 +960,245 @@ where
 mod test {
 	use super::*;
 
+	#[test]
+	fn test_convert_mediainfo_to_organized() {
+		let media = MediaInfo::new("someid", "youtube")
+			.with_filename("someid.mp3")
+			.with_title("Media Title");
+		let mut metadata = HashMap::new();
+
+		// no tags
+		assert_eq!(
+			Some((
+				&PathBuf::from("someid.mp3"),
+				PathBuf::from("Unknown Artist/Unknown Album/Media Title.mp3")
+			)),
+			convert_mediainfo_to_organized(&media, &metadata)
+		);
+
+		metadata.insert("artist".to_owned(), "AC/DC".to_owned());
+		metadata.insert("album".to_owned(), " .. ".to_owned());
+		metadata.insert("title".to_owned(), "Tag Title".to_owned());
+
+		assert_eq!(
+			Some((
+				&PathBuf::from("someid.mp3"),
+				PathBuf::from("AC⧸DC/Unknown Album/Tag Title.mp3")
+			)),
+			convert_mediainfo_to_organized(&media, &metadata)
+		);
+
+		// no title to use
+		let media = MediaInfo::new("someid", "youtube").with_filename("someid.mp3");
+		assert_eq!(None, convert_mediainfo_to_organized(&media, &HashMap::new()));
+	}
+
+	#[test]
+	fn test_media_url() {
+		assert_eq!(
+			Some("https://www.youtube.com/watch?v=-----------".to_owned()),
+			media_url("youtube", "-----------")
+		);
+		assert_eq!(
+			Some("https://www.youtube.com/watch?v=-----------".to_owned()),
+			media_url("Youtube", "-----------")
+		);
+		assert_eq!(None, media_url("soundcloud", "123"));
+	}
+
+	#[test]
+	fn test_url_file_roundtrip() {
+		let testdir = tempfile::Builder::new()
+			.prefix("ytdlr-test-url-file-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+
+		let path = write_failed_url_file(testdir.as_ref(), &["https://a.com/1", "https://b.com/2"])
+			.expect("Expected url file to be written");
+		assert!(path
+			.file_name()
+			.expect("Expected a filename")
+			.to_string_lossy()
+			.starts_with("failed-"));
+		assert_eq!(
+			vec!["https://a.com/1".to_owned(), "https://b.com/2".to_owned()],
+			read_url_file(&path).expect("Expected url file to be read")
+		);
+
+		let manual_path = testdir.as_ref().join("manual.txt");
+		std::fs::write(&manual_path, "# comment\n\n  https://c.com/3  \n").expect("Expected file to be written");
+		assert_eq!(
+			vec!["https://c.com/3".to_owned()],
+			read_url_file(&manual_path).expect("Expected url file to be read")
+		);
+	}
+
+	#[test]
+	fn test_find_sidecar_files() {
+		let testdir = tempfile::Builder::new()
+			.prefix("ytdlr-test-sidecars-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+		let thumbnail_dir = testdir.as_ref().join("thumbnail");
+		std::fs::create_dir_all(&thumbnail_dir).expect("Expected the thumbnail dir to be created");
+
+		for name in [
+			"'youtube'-'id1'-Title.mkv",
+			"'youtube'-'id1'-Title.en.vtt",
+			"'youtube'-'id1'-Title.de.srt",
+			"'youtube'-'id1'-Title.info.json",
+			"'youtube'-'id1'-Title.mkv.ytdlr.json",
+			"'youtube'-'id2'-Other.en.vtt",
+			"thumbnail/'youtube'-'id1'-Title.jpg",
+			"thumbnail/'youtube'-'id2'-Other.jpg",
+		] {
+			std::fs::write(testdir.as_ref().join(name), "").expect("Expected file to be written");
+		}
+
+		assert_eq!(
+			vec![
+				(
+					testdir.as_ref().join("'youtube'-'id1'-Title.de.srt"),
+					".de.srt".to_owned()
+				),
+				(
+					testdir.as_ref().join("'youtube'-'id1'-Title.en.vtt"),
+					".en.vtt".to_owned()
+				),
+				(
+					testdir.as_ref().join("'youtube'-'id1'-Title.info.json"),
+					".info.json".to_owned()
+				),
+				(thumbnail_dir.join("'youtube'-'id1'-Title.jpg"), ".jpg".to_owned()),
+			],
+			find_sidecar_files(
+				&testdir.as_ref().join("'youtube'-'id1'-Title.mkv"),
+				&[thumbnail_dir.clone(), testdir.as_ref().join("missing")]
+			)
+		);
+		assert!(find_sidecar_files(&testdir.as_ref().join("'youtube'-'id3'-None.mkv"), &[]).is_empty());
+	}
+
+	#[test]
+	fn test_find_editable_files_filtered() {
+		let testdir = tempfile::Builder::new()
+			.prefix("ytdlr-test-editable-")
+			.tempdir()
+			.expect("Expected a temp dir to be created");
+
+		for name in [
+			"'youtube'-'id1'-Some Title.mkv",
+			"'youtube'-'id2'-Some Title.mp3",
+			"'soundcloud'-'id3'-Some Title.mp3",
+			"not a media.txt",
+		] {
+			std::fs::write(testdir.as_ref().join(name), "content").expect("Expected file to be written");
+		}
+
+		// make one file older than the others
+		std::fs::File::options()
+			.write(true)
+			.open(testdir.as_ref().join("'soundcloud'-'id3'-Some Title.mp3"))
+			.expect("Expected file to be opened")
+			.set_modified(std::time::SystemTime::now() - std::time::Duration::from_secs(2 * 60 * 60))
+			.expect("Expected modified time to be set");
+
+		let find_ids = |filters: &[RecoverFilter]| {
+			let mut ids: Vec<String> = find_editable_files_filtered(testdir.as_ref(), filters)
+				.expect("Expected find_editable_files_filtered to be ok")
+				.into_iter()
+				.map(|v| return v.id)
+				.collect();
+			ids.sort();
+
+			return ids;
+		};
+
+		assert_eq!(vec!["id1", "id2", "id3"], find_ids(&[]));
+		assert_eq!(
+			vec!["id1", "id2"],
+			find_ids(&[RecoverFilter::Provider("youtube".to_owned())])
+		);
+		assert_eq!(
+			vec!["id2"],
+			find_ids(&[
+				RecoverFilter::Provider("youtube".to_owned()),
+				RecoverFilter::Type(FileType::Audio)
+			])
+		);
+		assert_eq!(
+			vec!["id1", "id2"],
+			find_ids(&[RecoverFilter::MaxAge(std::time::Duration::from_secs(60 * 60))])
+		);
+	}
+
+	#[test]
+	fn test_url_host() {
+		assert_eq!(
+			"www.youtube.com",
+			url_host("https://www.youtube.com/watch?v=-----------")
+		);
+		assert_eq!("soundcloud.com", url_host("soundcloud.com/some/track"));
+		assert_eq!("localhost", url_host("http://localhost:8080/playlist"));
+		assert_eq!("youtu.be", url_host("https://youtu.be?si=something"));
+	}
+
+	#[test]
+	fn test_url_provider() {
+		assert_eq!(
+			Some("youtube".to_owned()),
+			url_provider("https://www.youtube.com/watch?v=-----------")
+		);
+		assert_eq!(Some("youtube".to_owned()), url_provider("https://youtu.be/-----------"));
+		assert_eq!(
+			Some("youtube".to_owned()),
+			url_provider("https://www.youtube.com/playlist?list=someplaylist")
+		);
+		assert_eq!(Some("soundcloud".to_owned()), url_provider("soundcloud.com/some/track"));
+		assert_eq!(
+			Some("dailymotion".to_owned()),
+			url_provider("https://www.dai.ly/someplaylist")
+		);
+		assert_eq!(None, url_provider("http://localhost:8080/playlist"));
+	}
+
+	#[test]
+	fn test_get_input_assume_yes() {
+		set_assume_yes(true);
+		// does not read from stdin when "assume_yes" is set
+		let with_default = get_input("Test prompt?", &["Y", "n"], "y");
+		let without_default = get_input("Test prompt?", &["y", "n"], "");
+		set_assume_yes(false);
+
+		assert_eq!("y", with_default.unwrap());
+		assert!(without_default.is_err());
+	}
+
+	#[test]
+	fn test_format_duration_secs() {
+		assert_eq!("0:00", format_duration_secs(0));
+		assert_eq!("3:05", format_duration_secs(185));
+		assert_eq!("59:59", format_duration_secs(3599));
+		assert_eq!("1:02:03", format_duration_secs(3723));
+		assert_eq!("26:00:00", format_duration_secs(93600));
+	}
+
+	#[test]
+	fn test_parse_byte_size() {
+		assert_eq!(1000, parse_byte_size("1000").unwrap());
+		assert_eq!(500 * 1024, parse_byte_size("500K").unwrap());
+		assert_eq!(2 * 1024 * 1024, parse_byte_size("2m").unwrap());
+		assert_eq!(1536 * 1024, parse_byte_size("1.5M").unwrap());
+		assert_eq!(1024 * 1024 * 1024, parse_byte_size(" 1G ").unwrap());
+
+		assert!(parse_byte_size("").is_err());
+		assert!(parse_byte_size("M").is_err());
+		assert!(parse_byte_size("0").is_err());
+		assert!(parse_byte_size("-1K").is_err());
+		assert!(parse_byte_size("2X").is_err());
+		assert!(parse_byte_size("0.1").is_err());
+	}
+
 	mod truncate_to_size_bytes {
 		use super::*;
 
@@ -0,0 +1,227 @@
+//! Integration tests running the whole "download" pipeline with a mock youtube-dl (see "tests/mock/yt-dlp")
+//! This does not require network access or a real youtube-dl
+// the mock is a shell script
+#![cfg(unix)]
+
+use std::{
+	path::{
+		Path,
+		PathBuf,
+	},
+	process::{
+		Command,
+		Output,
+	},
+};
+
+use libytdlr::{
+	data::{
+		sql_models::Media,
+		sql_schema::media_archive,
+	},
+	diesel::prelude::*,
+	main::sql_utils::sqlite_connect,
+};
+use tempfile::{
+	Builder as TempBuilder,
+	TempDir,
+};
+
+/// Get the path of the mock youtube-dl
+fn mock_ytdl_path() -> PathBuf {
+	return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/mock/yt-dlp");
+}
+
+/// Test helper to create a clean testing dir
+fn create_testdir() -> TempDir {
+	return TempBuilder::new()
+		.prefix("ytdlr-test-mock_ytdl-")
+		.tempdir()
+		.expect("Expected a temp dir to be created");
+}
+
+/// Run ytdlr non-interactively with the mock youtube-dl and a archive, tmp directory and home inside `testdir`
+/// The environment is cleared, so that no user config or "YTDL_*" variable is used
+fn run_ytdlr_unchecked(testdir: &Path, args: &[&str]) -> Output {
+	return Command::new(env!("CARGO_BIN_EXE_ytdlr"))
+		.env_clear()
+		.env("PATH", std::env::var_os("PATH").unwrap_or_default())
+		.env("HOME", testdir)
+		.env("XDG_CONFIG_HOME", testdir.join("config"))
+		.arg("--ytdl-bin")
+		.arg(mock_ytdl_path())
+		.arg("--interactive=false")
+		.arg("--archive")
+		.arg(testdir.join("archive.db"))
+		.arg("--tmp")
+		.arg(testdir.join("tmp"))
+		.args(args)
+		.output()
+		.expect("Expected ytdlr to run");
+}
+
+/// Run ytdlr like [`run_ytdlr_unchecked`] and assert that it succeeded
+fn run_ytdlr(testdir: &Path, args: &[&str]) -> Output {
+	let output = run_ytdlr_unchecked(testdir, args);
+
+	assert!(
+		output.status.success(),
+		"Expected ytdlr to succeed, stdout:\n{}\nstderr:\n{}",
+		String::from_utf8_lossy(&output.stdout),
+		String::from_utf8_lossy(&output.stderr)
+	);
+
+	return output;
+}
+
+/// Get the sorted file names in `dir`, or a empty list if it does not exist
+fn dir_file_names(dir: &Path) -> Vec<String> {
+	let Ok(entries) = std::fs::read_dir(dir) else {
+		return Vec::new();
+	};
+
+	let mut names: Vec<String> = entries
+		.map(|v| {
+			return v
+				.expect("Expected a dir entry")
+				.file_name()
+				.to_string_lossy()
+				.to_string();
+		})
+		.collect();
+	names.sort();
+
+	return names;
+}
+
+#[test]
+fn test_download_roundtrip() {
+	let testdir = create_testdir();
+	let out_dir = testdir.path().join("out");
+	let out_arg = out_dir.to_string_lossy().to_string();
+	let event_log_path = testdir.path().join("events.jsonl");
+	let event_log_arg = event_log_path.to_string_lossy().to_string();
+
+	let download_args = [
+		"download",
+		"--no-remux",
+		"--no-embed-thumbnail",
+		"--no-resume-partial",
+		"-o",
+		&out_arg,
+		"--event-log",
+		&event_log_arg,
+		"https://example.com/media/id1",
+		"https://example.com/media/id2",
+	];
+
+	run_ytdlr(testdir.path(), &download_args);
+
+	// the media got moved to the output directory
+	assert_eq!(
+		vec!["Mock Title id1.mkv".to_owned(), "Mock Title id2.mkv".to_owned()],
+		dir_file_names(&out_dir)
+	);
+
+	// the lifecycle of every media was written to the event log
+	let events: Vec<String> = std::fs::read_to_string(&event_log_path)
+		.expect("Expected the event log to exist")
+		.lines()
+		.map(|v| {
+			let event: serde_json::Value = serde_json::from_str(v).expect("Expected every event to be JSON");
+
+			return format!(
+				"{} {}",
+				event["event"].as_str().unwrap_or_default(),
+				event["id"].as_str().unwrap_or_default()
+			);
+		})
+		.filter(|v| return !v.starts_with("single_progress") && !v.starts_with("single_downloaded"))
+		.collect();
+	assert_eq!(
+		vec![
+			"url_starting ",
+			"single_starting id1",
+			"single_finished id1",
+			"url_finished ",
+			"url_starting ",
+			"single_starting id2",
+			"single_finished id2",
+			"url_finished ",
+			"moved id1",
+			"moved id2",
+		],
+		events
+	);
+
+	// no recovery was left behind
+	assert!(!dir_file_names(&testdir.path().join("tmp").join("ytdl_rust_tmp"))
+		.iter()
+		.any(|v| return v.starts_with("recovery_")));
+
+	// the media got inserted into the archive
+	let mut connection = sqlite_connect(testdir.path().join("archive.db")).expect("Expected the archive to open");
+	let archived = media_archive::table
+		.order(media_archive::media_id.asc())
+		.load::<Media>(&mut connection)
+		.expect("Expected the archive to be readable");
+	assert_eq!(
+		vec![
+			("mock".to_owned(), "id1".to_owned(), "Mock Title id1".to_owned()),
+			("mock".to_owned(), "id2".to_owned(), "Mock Title id2".to_owned()),
+		],
+		archived
+			.into_iter()
+			.map(|v| return (v.provider, v.media_id, v.title))
+			.collect::<Vec<_>>()
+	);
+
+	// downloading again skips the media, because it is already in the archive
+	std::fs::remove_dir_all(&out_dir).expect("Expected the output directory to be removed");
+	run_ytdlr(testdir.path(), &download_args);
+	assert!(dir_file_names(&out_dir).is_empty());
+}
+
+#[test]
+fn test_download_failed_url_continues() {
+	let testdir = create_testdir();
+	let out_dir = testdir.path().join("out");
+	let out_arg = out_dir.to_string_lossy().to_string();
+
+	let output = run_ytdlr_unchecked(
+		testdir.path(),
+		&[
+			"download",
+			"--no-remux",
+			"--no-embed-thumbnail",
+			"--no-resume-partial",
+			"-o",
+			&out_arg,
+			"https://example.com/media/fail1",
+			"https://example.com/media/id2",
+		],
+	);
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	let stderr = String::from_utf8_lossy(&output.stderr);
+
+	// a failed url fails the whole run, but only after all other urls have been downloaded
+	assert!(
+		!output.status.success(),
+		"Expected ytdlr to fail, stdout:\n{stdout}\nstderr:\n{stderr}"
+	);
+	assert!(
+		stderr.contains("1 of 2 URLs failed to download"),
+		"Expected the failed count to be reported, stderr:\n{stderr}"
+	);
+	assert_eq!(vec!["Mock Title id2.mkv".to_owned()], dir_file_names(&out_dir));
+
+	// the summary has a row for every url
+	let summary_rows: Vec<&str> = stdout
+		.lines()
+		.filter(|v| return v.contains("https://example.com/media/"))
+		.filter(|v| return v.trim_end().ends_with("failed") || v.trim_end().ends_with("ok"))
+		.collect();
+	assert_eq!(2, summary_rows.len(), "Expected 2 summary rows, stdout:\n{stdout}");
+	assert!(summary_rows[0].contains("fail1") && summary_rows[0].trim_end().ends_with("failed"));
+	assert!(summary_rows[1].contains("id2") && summary_rows[1].trim_end().ends_with("ok"));
+}